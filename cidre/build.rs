@@ -230,6 +230,11 @@ fn parse_deployment_targets() -> DeploymentTargets {
 }
 
 fn main() {
+    // frameworks and pomace are Apple only, pure Rust modules build anywhere
+    if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() != Ok("apple") {
+        return;
+    }
+
     let versions = parse_deployment_targets();
 
     let sdk = match env::var("TARGET").unwrap().as_ref() {
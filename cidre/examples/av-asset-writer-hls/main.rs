@@ -1,10 +1,10 @@
 #[cfg(target_os = "macos")]
 mod macos {
-    use std::{fs, io::Write, path::PathBuf};
+    use std::{fs, path::PathBuf};
 
     use cidre::{
-        arc, av, av::AssetWriterDelegate, cm, define_obj_type, dispatch, ns, objc, objc::Obj, sc,
-        sc::StreamOutput, ut,
+        arc, av, av::AssetWriterDelegate, cm, define_obj_type, dispatch, media::m3u8, ns, objc,
+        objc::Obj, sc, sc::StreamOutput, ut,
     };
 
     #[repr(C)]
@@ -53,7 +53,7 @@ mod macos {
         n: u32,
         dir: PathBuf,
         base_name: String,
-        target_dur: u32,
+        live: m3u8::LiveSegmenter,
     }
 
     impl SegmentWriter {
@@ -73,41 +73,28 @@ mod macos {
             format!("{}{}.m4s", self.base_name, self.n)
         }
 
+        fn write_playlist(&self) {
+            fs::write(self.playlist_path(), self.live.playlist().to_string()).unwrap();
+        }
+
         fn write_init(&mut self, data: &[u8]) {
             fs::write(self.init_path(), data).unwrap();
-
-            let target_dur = self.target_dur;
-            let base_name = &self.base_name;
-            fs::write(
-                self.playlist_path(),
-                format!(
-                    "#EXTM3U
-#EXT-X-VERSION:7
-#EXT-X-TARGETDURATION:{target_dur}
-#EXT-X-MEDIA-SEQUENCE:0
-#EXT-X-MAP:URI=\"{base_name}.mp4\"
-"
-                ),
-            )
-            .unwrap();
+            self.live.set_map(format!("{}.mp4", self.base_name));
+            self.write_playlist();
         }
 
         fn write_segment(&mut self, data: &[u8], duration: f64) {
             fs::write(self.segment_path(), data).unwrap();
-            let mut file = fs::File::options()
-                .append(true)
-                .open(self.playlist_path())
-                .unwrap();
             let segment_name = self.segment_name();
-            writeln!(file, "\n#EXTINF:{duration},\n{segment_name}").unwrap();
+            for removed in self.live.push_segment(segment_name, duration).unwrap() {
+                fs::remove_file(self.dir.join(removed.uri)).unwrap();
+            }
+            self.write_playlist();
         }
 
         fn write_end(&mut self) {
-            let mut file = fs::File::options()
-                .append(true)
-                .open(self.playlist_path())
-                .unwrap();
-            writeln!(file, "\n#EXT-X-ENDLIST").unwrap();
+            self.live.finish();
+            self.write_playlist();
         }
     }
 
@@ -181,7 +168,12 @@ mod macos {
             n: 0,
             dir: "/tmp/".into(),
             base_name: "hls".into(),
-            target_dur: TARGET_DUR,
+            live: m3u8::LiveSegmenter::new(m3u8::LiveConfig {
+                target_duration: TARGET_DUR,
+                // keep all segments
+                window: 0,
+                ..Default::default()
+            }),
         });
 
         let mut input = av::AssetWriterInput::with_media_type_and_output_settings(
@@ -1,7 +1,6 @@
 use std::{
     ffi::{c_char, c_void, CStr},
     marker::PhantomData,
-    str::FromStr,
     sync::atomic::{fence, AtomicUsize, Ordering},
 };
 
@@ -89,53 +88,54 @@ impl<T> DlSym<T> {
 
 unsafe impl<T> Sync for DlSym<T> {}
 
-use crate::ns;
+#[cfg(feature = "ns")]
+use {crate::ns, std::str::FromStr};
 
 #[inline]
 pub fn macos_available(_ver: &str) -> bool {
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(not(all(target_os = "macos", feature = "ns")))]
     return false;
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
 #[inline]
 pub fn ios_available(_ver: &str) -> bool {
-    #[cfg(not(target_os = "ios"))]
+    #[cfg(not(all(target_os = "ios", feature = "ns")))]
     return false;
-    #[cfg(target_os = "ios")]
+    #[cfg(all(target_os = "ios", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
 #[inline]
 pub fn tvos_available(_ver: &str) -> bool {
-    #[cfg(not(target_os = "tvos"))]
+    #[cfg(not(all(target_os = "tvos", feature = "ns")))]
     return false;
-    #[cfg(target_os = "tvos")]
+    #[cfg(all(target_os = "tvos", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
 #[inline]
 pub fn watchos_available(_ver: &str) -> bool {
-    #[cfg(not(target_os = "watchos"))]
+    #[cfg(not(all(target_os = "watchos", feature = "ns")))]
     return false;
-    #[cfg(target_os = "watchos")]
+    #[cfg(all(target_os = "watchos", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
 #[inline]
 pub fn visionos_available(_ver: &str) -> bool {
-    #[cfg(not(target_os = "visionos"))]
+    #[cfg(not(all(target_os = "visionos", feature = "ns")))]
     return false;
-    #[cfg(target_os = "visionos")]
+    #[cfg(all(target_os = "visionos", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
 #[inline]
 pub fn maccatalyst_available(_ver: &str) -> bool {
-    #[cfg(not(all(target_os = "ios", target_abi = "macabi")))]
+    #[cfg(not(all(target_os = "ios", target_abi = "macabi", feature = "ns")))]
     return false;
-    #[cfg(all(target_os = "ios", target_abi = "macabi"))]
+    #[cfg(all(target_os = "ios", target_abi = "macabi", feature = "ns"))]
     ns::OsVersion::from_str(_ver).unwrap().at_least()
 }
 
@@ -215,11 +215,13 @@ macro_rules! version {
         $( || $crate::api::version!(visionos = $visionos_ver))?
     };
 }
+#[cfg(feature = "objc")]
 pub use cidre_macros::api_available as available;
+#[cfg(feature = "objc")]
 pub use cidre_macros::api_weak as weak;
 pub use version;
 
-#[cfg(test)]
+#[cfg(all(test, target_vendor = "apple", feature = "ns"))]
 mod tests {
     use crate::{api, ns};
 
@@ -143,6 +143,7 @@ macro_rules! return_ar {
 }
 
 /// ```
+/// # #[cfg(feature = "cf")] {
 /// use cidre::cf;
 ///
 /// let n = cf::Number::from_i8(10);
@@ -152,6 +153,7 @@ macro_rules! return_ar {
 /// };
 ///
 /// assert!(f.equal(&n));
+/// # }
 /// ```
 impl<T: Retain> Clone for Retained<T> {
     #[inline]
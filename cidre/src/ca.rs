@@ -16,8 +16,7 @@ mod media_timing_function;
 pub use media_timing_function::MediaTimingFn;
 pub use media_timing_function::Name as MediaTimingFnName;
 
-mod transform3d;
-pub use transform3d::Transform3d;
+pub use crate::gfx::ca::Transform3d;
 
 mod layer;
 pub use layer::AutoresizingMask;
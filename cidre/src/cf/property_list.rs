@@ -254,6 +254,18 @@ impl From<arc::R<cf::Boolean>> for arc::R<cf::Plist> {
     }
 }
 
+impl From<&cf::Date> for &cf::Plist {
+    fn from(value: &cf::Date) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
+impl From<arc::R<cf::Date>> for arc::R<cf::Plist> {
+    fn from(value: arc::R<cf::Date>) -> Self {
+        unsafe { std::mem::transmute(value) }
+    }
+}
+
 impl<'a, T: arc::Retain> From<&'a cf::DictionaryOf<cf::String, T>> for &'a cf::Plist
 where
     &'a T: Into<&'a cf::Plist>,
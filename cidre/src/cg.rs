@@ -1,9 +1,9 @@
 mod geometry;
-pub use geometry::Float;
-pub use geometry::Point;
-pub use geometry::Rect;
-pub use geometry::Size;
-pub use geometry::Vector;
+pub use crate::gfx::cg::Float;
+pub use crate::gfx::cg::Point;
+pub use crate::gfx::cg::Rect;
+pub use crate::gfx::cg::Size;
+pub use crate::gfx::cg::Vector;
 
 pub mod color_space;
 pub use color_space::ColorRenderingIntent;
@@ -13,6 +13,8 @@ pub use color_space::ColorSpaceModel;
 mod color;
 pub use color::Color;
 
+pub use crate::gfx::cg::color_math;
+
 mod window;
 pub use window::Id as WindowId;
 
@@ -24,9 +26,9 @@ pub mod direct_display;
 pub use direct_display::main_display_id;
 pub use direct_display::Id as DirectDisplayId;
 
-pub mod affine_transform;
-pub use affine_transform::AffineTransform;
-pub use affine_transform::Components as AffineTransformComponents;
+pub use crate::gfx::cg::affine_transform;
+pub use crate::gfx::cg::AffineTransform;
+pub use crate::gfx::cg::AffineTransformComponents;
 
 mod error;
 pub use error::Callback as ErrorCallback;
@@ -74,6 +76,12 @@ pub use path::Path;
 pub use path::PathApplierFn;
 pub use path::PathMut;
 
+pub mod path_data;
+pub use path_data::FillRule as PathFillRule;
+pub use path_data::PathData;
+pub use path_data::Polyline as PathPolyline;
+pub use path_data::Segment as PathDataSegment;
+
 #[cfg(target_os = "macos")]
 mod display_stream;
 #[cfg(target_os = "macos")]
@@ -30,6 +30,21 @@ impl Color {
         unsafe { CGColorCreateGenericRGB(red, green, blue, alpha) }
     }
 
+    /// Components are in `space` followed by alpha.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let space = cg::ColorSpace::device_rgb().unwrap();
+    /// let c = cg::Color::with_space(&space, &[1.0, 0.5, 0.0, 0.25]).unwrap();
+    ///
+    /// assert_eq!(c.alpha(), 0.25);
+    /// ```
+    #[inline]
+    pub fn with_space(space: &cg::ColorSpace, components: &[cg::Float]) -> Option<arc::R<Color>> {
+        unsafe { CGColorCreate(space, components.as_ptr()) }
+    }
+
     #[inline]
     pub fn alpha(&self) -> cg::Float {
         unsafe { CGColorGetAlpha(self) }
@@ -45,6 +60,8 @@ extern "C" {
         blue: cg::Float,
         alpha: cg::Float,
     ) -> arc::R<Color>;
+    fn CGColorCreate(space: &cg::ColorSpace, components: *const cg::Float)
+        -> Option<arc::R<Color>>;
 
     fn CGColorGetAlpha(color: &Color) -> cg::Float;
 }
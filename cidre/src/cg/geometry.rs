@@ -1,23 +1,6 @@
-use crate::{arc, cf};
-
-// #[cfg(target_os = "watchos")]
-// pub type Float = f32;
-
-// #[cfg(not(target_os = "watchos"))]
-pub type Float = f64;
-
-#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
-#[repr(C)]
-pub struct Point {
-    pub x: Float,
-    pub y: Float,
-}
-
-impl Point {
-    pub fn zero() -> Self {
-        Default::default()
-    }
+use crate::{arc, cf, cg};
 
+impl cg::Point {
     /// ```
     /// use cidre::cg;
     ///
@@ -27,25 +10,9 @@ impl Point {
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGPointCreateDictionaryRepresentation(*self) }
     }
-
-    #[inline]
-    pub fn new(x: Float, y: Float) -> Self {
-        Self { x, y }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
-#[repr(C)]
-pub struct Size {
-    pub width: Float,
-    pub height: Float,
 }
 
-impl Size {
-    pub fn zero() -> Self {
-        Default::default()
-    }
-
+impl cg::Size {
     /// ```
     /// use cidre::cg;
     ///
@@ -55,26 +22,9 @@ impl Size {
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGSizeCreateDictionaryRepresentation(*self) }
     }
-
-    #[inline]
-    pub fn new(width: Float, height: Float) -> Self {
-        Self { width, height }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
-#[repr(C)]
-pub struct Rect {
-    pub origin: Point,
-    pub size: Size,
 }
 
-impl Rect {
-    #[inline]
-    pub fn zero() -> Self {
-        Default::default()
-    }
-
+impl cg::Rect {
     /// ```
     /// use cidre::cg;
     ///
@@ -84,33 +34,10 @@ impl Rect {
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGRectCreateDictionaryRepresentation(*self) }
     }
-
-    #[inline]
-    pub fn new(x: Float, y: Float, width: Float, height: Float) -> Self {
-        Self {
-            origin: Point { x, y },
-            size: Size { width, height },
-        }
-    }
-
-    #[inline]
-    pub fn with_size(width: Float, height: Float) -> Self {
-        Self {
-            origin: Point::zero(),
-            size: Size { width, height },
-        }
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
-#[repr(C)]
-pub struct Vector {
-    pub dx: Float,
-    pub dy: Float,
 }
 
 extern "C" {
-    fn CGPointCreateDictionaryRepresentation(point: Point) -> arc::R<cf::Dictionary>;
-    fn CGSizeCreateDictionaryRepresentation(size: Size) -> arc::R<cf::Dictionary>;
-    fn CGRectCreateDictionaryRepresentation(rect: Rect) -> arc::R<cf::Dictionary>;
+    fn CGPointCreateDictionaryRepresentation(point: cg::Point) -> arc::R<cf::Dictionary>;
+    fn CGSizeCreateDictionaryRepresentation(size: cg::Size) -> arc::R<cf::Dictionary>;
+    fn CGRectCreateDictionaryRepresentation(rect: cg::Rect) -> arc::R<cf::Dictionary>;
 }
@@ -0,0 +1,1057 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{arc, cg};
+
+/// Default flattening tolerance used for hit testing.
+pub const DEFAULT_TOLERANCE: cg::Float = 0.01;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+/// SVG style elliptical arc from the current point to `to`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Arc {
+    pub radii: cg::Size,
+    /// Rotation of the ellipse x axis in degrees.
+    pub x_axis_rotation: cg::Float,
+    pub large_arc: bool,
+    pub sweep: bool,
+    pub to: cg::Point,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Segment {
+    Move(cg::Point),
+    Line(cg::Point),
+    Quad(cg::Point, cg::Point),
+    Curve(cg::Point, cg::Point, cg::Point),
+    Arc(Arc),
+    Close,
+}
+
+/// Flattened subpath.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polyline {
+    pub points: Vec<cg::Point>,
+    pub closed: bool,
+}
+
+impl Polyline {
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Iterates over edges, including the closing one for closed polylines.
+    pub fn edges(&self) -> impl Iterator<Item = (cg::Point, cg::Point)> + '_ {
+        let closing = match (self.closed, self.points.first(), self.points.last()) {
+            (true, Some(first), Some(last)) if first != last => Some((*last, *first)),
+            _ => None,
+        };
+        self.points.windows(2).map(|w| (w[0], w[1])).chain(closing)
+    }
+
+    pub fn length(&self) -> cg::Float {
+        self.edges().map(|(a, b)| dist(a, b)).sum()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SvgErrorKind {
+    UnexpectedChar(char),
+    ExpectedNumber,
+    ExpectedFlag,
+    MissingMoveTo,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SvgError {
+    /// Byte offset in the source string.
+    pub pos: usize,
+    pub kind: SvgErrorKind,
+}
+
+impl std::fmt::Display for SvgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            SvgErrorKind::UnexpectedChar(c) => write!(f, "unexpected {c:?} at {}", self.pos),
+            SvgErrorKind::ExpectedNumber => write!(f, "expected number at {}", self.pos),
+            SvgErrorKind::ExpectedFlag => write!(f, "expected arc flag at {}", self.pos),
+            SvgErrorKind::MissingMoveTo => write!(f, "path must start with moveto at {}", self.pos),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+/// Path elements recorded in Rust.
+///
+/// Converts to and from `cg::Path` without loss, except for arcs: `cg::Path` has no
+/// elliptical arc element, so arcs come back from [`PathData::to_cg_path`] as cubic curves.
+///
+/// ```
+/// use cidre::cg;
+///
+/// let path = cg::PathData::with_svg("M0 0 H10 V10 H0 Z").unwrap();
+/// assert_eq!(path.bounding_box(), cg::Rect::new(0.0, 0.0, 10.0, 10.0));
+/// assert!(path.contains_point(cg::Point::new(5.0, 5.0), cg::PathFillRule::NonZero));
+/// assert_eq!(path.length(0.1), 40.0);
+/// assert_eq!(path.to_svg(), "M0 0 L10 0 L10 10 L0 10 Z");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PathData {
+    segments: Vec<Segment>,
+    start: cg::Point,
+    current: cg::Point,
+}
+
+impl PathData {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    #[inline]
+    pub fn current_point(&self) -> cg::Point {
+        self.current
+    }
+
+    pub fn move_to(&mut self, to: cg::Point) {
+        self.segments.push(Segment::Move(to));
+        self.start = to;
+        self.current = to;
+    }
+
+    pub fn line_to(&mut self, to: cg::Point) {
+        self.ensure_subpath();
+        self.segments.push(Segment::Line(to));
+        self.current = to;
+    }
+
+    pub fn quad_to(&mut self, ctrl: cg::Point, to: cg::Point) {
+        self.ensure_subpath();
+        self.segments.push(Segment::Quad(ctrl, to));
+        self.current = to;
+    }
+
+    pub fn curve_to(&mut self, ctrl1: cg::Point, ctrl2: cg::Point, to: cg::Point) {
+        self.ensure_subpath();
+        self.segments.push(Segment::Curve(ctrl1, ctrl2, to));
+        self.current = to;
+    }
+
+    pub fn arc_to(&mut self, arc: Arc) {
+        self.ensure_subpath();
+        self.segments.push(Segment::Arc(arc));
+        self.current = arc.to;
+    }
+
+    pub fn close_subpath(&mut self) {
+        if self.segments.is_empty() {
+            return;
+        }
+        self.segments.push(Segment::Close);
+        self.current = self.start;
+    }
+
+    fn ensure_subpath(&mut self) {
+        if self.segments.is_empty() {
+            self.move_to(self.current);
+        } else if let Some(Segment::Close) = self.segments.last() {
+            // after close the next subpath starts at the previous subpath start
+            self.move_to(self.start);
+        }
+    }
+
+    fn pieces(&self) -> Pieces<'_> {
+        Pieces {
+            segments: self.segments.iter(),
+            start: cg::Point::zero(),
+            current: cg::Point::zero(),
+            pending: None,
+        }
+    }
+
+    /// Tight bounding box of the path, control points are not included.
+    pub fn bounding_box(&self) -> cg::Rect {
+        let mut bounds = Bounds::default();
+        for piece in self.pieces() {
+            match piece {
+                Piece::Move(p) => bounds.add(p),
+                Piece::Line(b) => bounds.add(b),
+                Piece::Quad(a, c, b) => {
+                    bounds.add(b);
+                    for t in quad_extrema(a.x, c.x, b.x)
+                        .into_iter()
+                        .chain(quad_extrema(a.y, c.y, b.y))
+                        .flatten()
+                    {
+                        bounds.add(quad_at(a, c, b, t));
+                    }
+                }
+                Piece::Curve(a, c1, c2, b) => {
+                    bounds.add(b);
+                    for t in cubic_extrema(a.x, c1.x, c2.x, b.x)
+                        .into_iter()
+                        .chain(cubic_extrema(a.y, c1.y, c2.y, b.y))
+                        .flatten()
+                    {
+                        bounds.add(cubic_at(a, c1, c2, b, t));
+                    }
+                }
+                Piece::Arc(e) => {
+                    bounds.add(e.end);
+                    let (sin, cos) = e.phi.sin_cos();
+                    let tx = (-e.ry * sin).atan2(e.rx * cos);
+                    let ty = (e.ry * cos).atan2(e.rx * sin);
+                    for theta in [tx, tx + PI, ty, ty + PI] {
+                        if e.contains_angle(theta) {
+                            bounds.add(e.at(theta));
+                        }
+                    }
+                }
+                Piece::Close => {}
+            }
+        }
+        bounds.rect()
+    }
+
+    /// Approximates the path with polylines, no point of a curve is further
+    /// than `tolerance` from the result.
+    pub fn flatten(&self, tolerance: cg::Float) -> Vec<Polyline> {
+        let tolerance = tolerance.abs().max(1e-9);
+        let mut res = Vec::new();
+        let mut poly = Polyline::default();
+        for piece in self.pieces() {
+            match piece {
+                Piece::Move(p) => {
+                    if poly.len() > 1 {
+                        res.push(std::mem::take(&mut poly));
+                    }
+                    poly.points.clear();
+                    poly.points.push(p);
+                }
+                Piece::Line(b) => poly.points.push(b),
+                Piece::Quad(a, c, b) => {
+                    let dd = dist(sub(add(a, b), scale(c, 2.0)), cg::Point::zero());
+                    let n = steps(0.25 * dd / tolerance);
+                    for i in 1..n {
+                        poly.points.push(quad_at(a, c, b, i as f64 / n as f64));
+                    }
+                    poly.points.push(b);
+                }
+                Piece::Curve(a, c1, c2, b) => {
+                    let dd0 = dist(sub(add(a, c2), scale(c1, 2.0)), cg::Point::zero());
+                    let dd1 = dist(sub(add(c1, b), scale(c2, 2.0)), cg::Point::zero());
+                    let n = steps(0.75 * dd0.max(dd1) / tolerance);
+                    for i in 1..n {
+                        poly.points
+                            .push(cubic_at(a, c1, c2, b, i as f64 / n as f64));
+                    }
+                    poly.points.push(b);
+                }
+                Piece::Arc(e) => {
+                    let r = e.rx.max(e.ry);
+                    let step = if tolerance >= r {
+                        FRAC_PI_2
+                    } else {
+                        2.0 * (1.0 - tolerance / r).acos()
+                    };
+                    let n = (e.delta.abs() / step).ceil().max(1.0) as usize;
+                    for i in 1..n {
+                        poly.points
+                            .push(e.at(e.theta + e.delta * i as f64 / n as f64));
+                    }
+                    poly.points.push(e.end);
+                }
+                Piece::Close => {
+                    poly.closed = true;
+                    let start = poly.points.first().copied();
+                    res.push(std::mem::take(&mut poly));
+                    if let Some(start) = start {
+                        poly.points.push(start);
+                    }
+                }
+            }
+        }
+        if poly.len() > 1 {
+            res.push(poly);
+        }
+        res
+    }
+
+    /// Length of the path flattened with `tolerance`.
+    pub fn length(&self, tolerance: cg::Float) -> cg::Float {
+        self.flatten(tolerance).iter().map(Polyline::length).sum()
+    }
+
+    /// Point at `distance` along the path flattened with `tolerance`.
+    /// Returns `None` for negative distances and distances past the end of the path.
+    pub fn point_at_length(&self, distance: cg::Float, tolerance: cg::Float) -> Option<cg::Point> {
+        if distance < 0.0 {
+            return None;
+        }
+        let mut left = distance;
+        for poly in self.flatten(tolerance) {
+            for (a, b) in poly.edges() {
+                let len = dist(a, b);
+                if left <= len {
+                    if len == 0.0 {
+                        return Some(a);
+                    }
+                    return Some(lerp(a, b, left / len));
+                }
+                left -= len;
+            }
+        }
+        None
+    }
+
+    /// Hit test `point` against the filled path. Open subpaths are implicitly closed.
+    pub fn contains_point(&self, point: cg::Point, rule: FillRule) -> bool {
+        self.contains_point_with_tolerance(point, rule, DEFAULT_TOLERANCE)
+    }
+
+    pub fn contains_point_with_tolerance(
+        &self,
+        point: cg::Point,
+        rule: FillRule,
+        tolerance: cg::Float,
+    ) -> bool {
+        let mut winding = 0i32;
+        for mut poly in self.flatten(tolerance) {
+            poly.closed = true;
+            for (a, b) in poly.edges() {
+                if a.y <= point.y {
+                    if b.y > point.y && is_left(a, b, point) > 0.0 {
+                        winding += 1;
+                    }
+                } else if b.y <= point.y && is_left(a, b, point) < 0.0 {
+                    winding -= 1;
+                }
+            }
+        }
+        match rule {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    /// Parses SVG path data (the `d` attribute).
+    pub fn with_svg(d: &str) -> Result<Self, SvgError> {
+        SvgParser {
+            text: d,
+            src: d.as_bytes(),
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// Serializes to SVG path data. Only absolute commands are emitted.
+    pub fn to_svg(&self) -> String {
+        use std::fmt::Write;
+
+        let mut res = String::new();
+        for s in &self.segments {
+            if !res.is_empty() {
+                res.push(' ');
+            }
+            let _ = match s {
+                Segment::Move(p) => write!(res, "M{} {}", p.x, p.y),
+                Segment::Line(p) => write!(res, "L{} {}", p.x, p.y),
+                Segment::Quad(c, p) => write!(res, "Q{} {} {} {}", c.x, c.y, p.x, p.y),
+                Segment::Curve(c1, c2, p) => {
+                    write!(res, "C{} {} {} {} {} {}", c1.x, c1.y, c2.x, c2.y, p.x, p.y)
+                }
+                Segment::Arc(a) => write!(
+                    res,
+                    "A{} {} {} {} {} {} {}",
+                    a.radii.width,
+                    a.radii.height,
+                    a.x_axis_rotation,
+                    a.large_arc as u8,
+                    a.sweep as u8,
+                    a.to.x,
+                    a.to.y
+                ),
+                Segment::Close => write!(res, "Z"),
+            };
+        }
+        res
+    }
+
+    /// Builds `cg::PathMut` with the same elements. Arcs are converted to cubic curves,
+    /// so [`PathData::with_cg_path`] returns them as curves.
+    pub fn to_cg_path(&self) -> arc::R<cg::PathMut> {
+        let mut path = cg::PathMut::new();
+        let mut current = cg::Point::zero();
+        for s in &self.segments {
+            match *s {
+                Segment::Move(p) => path.move_to(p.x, p.y),
+                Segment::Line(p) => path.line_to(p.x, p.y),
+                Segment::Quad(c, p) => path.quad_to(c.x, c.y, p.x, p.y),
+                Segment::Curve(c1, c2, p) => path.curve_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y),
+                Segment::Arc(a) => match EllipseArc::new(current, &a) {
+                    Some(e) => {
+                        for (c1, c2, p) in e.to_curves() {
+                            path.curve_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                        }
+                    }
+                    None if current != a.to => path.line_to(a.to.x, a.to.y),
+                    None => {}
+                },
+                Segment::Close => path.close_subpath(),
+            }
+            current = match *s {
+                Segment::Move(p) | Segment::Line(p) | Segment::Quad(_, p) => p,
+                Segment::Curve(_, _, p) => p,
+                Segment::Arc(a) => a.to,
+                Segment::Close => path.current_point(),
+            };
+        }
+        path
+    }
+
+    /// Records elements of `cg::Path`.
+    pub fn with_cg_path(path: &cg::Path) -> Self {
+        extern "C" fn applier(info: *mut PathData, element: *mut cg::PathElement) {
+            let (data, element) = unsafe { (&mut *info, &*element) };
+            let pts = element.points();
+            match element.type_ {
+                cg::PathElementType::MoveToPoint => data.move_to(pts[0]),
+                cg::PathElementType::AddLineToPoint => data.line_to(pts[0]),
+                cg::PathElementType::AddQuadCurveToPoint => data.quad_to(pts[0], pts[1]),
+                cg::PathElementType::AddCurveToPoint => data.curve_to(pts[0], pts[1], pts[2]),
+                cg::PathElementType::CloseSubpath => data.close_subpath(),
+            }
+        }
+        let mut res = Self::new();
+        path.apply(&mut res, applier);
+        res
+    }
+}
+
+impl std::str::FromStr for PathData {
+    type Err = SvgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::with_svg(s)
+    }
+}
+
+impl std::fmt::Display for PathData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_svg())
+    }
+}
+
+#[inline]
+fn add(a: cg::Point, b: cg::Point) -> cg::Point {
+    cg::Point::new(a.x + b.x, a.y + b.y)
+}
+
+#[inline]
+fn sub(a: cg::Point, b: cg::Point) -> cg::Point {
+    cg::Point::new(a.x - b.x, a.y - b.y)
+}
+
+#[inline]
+fn scale(a: cg::Point, s: f64) -> cg::Point {
+    cg::Point::new(a.x * s, a.y * s)
+}
+
+#[inline]
+fn lerp(a: cg::Point, b: cg::Point, t: f64) -> cg::Point {
+    add(a, scale(sub(b, a), t))
+}
+
+#[inline]
+fn dist(a: cg::Point, b: cg::Point) -> f64 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+#[inline]
+fn is_left(a: cg::Point, b: cg::Point, p: cg::Point) -> f64 {
+    (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)
+}
+
+#[inline]
+fn steps(n2: f64) -> usize {
+    (n2.sqrt().ceil() as usize).clamp(1, 1 << 16)
+}
+
+fn quad_at(a: cg::Point, c: cg::Point, b: cg::Point, t: f64) -> cg::Point {
+    let mt = 1.0 - t;
+    cg::Point::new(
+        mt * mt * a.x + 2.0 * mt * t * c.x + t * t * b.x,
+        mt * mt * a.y + 2.0 * mt * t * c.y + t * t * b.y,
+    )
+}
+
+fn cubic_at(a: cg::Point, c1: cg::Point, c2: cg::Point, b: cg::Point, t: f64) -> cg::Point {
+    let mt = 1.0 - t;
+    let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+    cg::Point::new(
+        w0 * a.x + w1 * c1.x + w2 * c2.x + w3 * b.x,
+        w0 * a.y + w1 * c1.y + w2 * c2.y + w3 * b.y,
+    )
+}
+
+fn quad_extrema(a: f64, c: f64, b: f64) -> [Option<f64>; 1] {
+    let den = a - 2.0 * c + b;
+    if den == 0.0 {
+        return [None];
+    }
+    let t = (a - c) / den;
+    [(t > 0.0 && t < 1.0).then_some(t)]
+}
+
+fn cubic_extrema(a: f64, c1: f64, c2: f64, b: f64) -> [Option<f64>; 2] {
+    // derivative / 3 = qa t^2 + qb t + qc
+    let qa = -a + 3.0 * c1 - 3.0 * c2 + b;
+    let qb = 2.0 * (a - 2.0 * c1 + c2);
+    let qc = c1 - a;
+    let valid = |t: f64| (t > 0.0 && t < 1.0).then_some(t);
+    if qa.abs() < 1e-12 {
+        if qb == 0.0 {
+            return [None, None];
+        }
+        return [valid(-qc / qb), None];
+    }
+    let disc = qb * qb - 4.0 * qa * qc;
+    if disc < 0.0 {
+        return [None, None];
+    }
+    let sq = disc.sqrt();
+    [
+        valid((-qb + sq) / (2.0 * qa)),
+        valid((-qb - sq) / (2.0 * qa)),
+    ]
+}
+
+#[derive(Default)]
+struct Bounds {
+    min: Option<(cg::Point, cg::Point)>,
+}
+
+impl Bounds {
+    fn add(&mut self, p: cg::Point) {
+        match &mut self.min {
+            None => self.min = Some((p, p)),
+            Some((min, max)) => {
+                min.x = min.x.min(p.x);
+                min.y = min.y.min(p.y);
+                max.x = max.x.max(p.x);
+                max.y = max.y.max(p.y);
+            }
+        }
+    }
+
+    fn rect(&self) -> cg::Rect {
+        match self.min {
+            None => cg::Rect::zero(),
+            Some((min, max)) => cg::Rect::new(min.x, min.y, max.x - min.x, max.y - min.y),
+        }
+    }
+}
+
+/// Center parameterization of an SVG arc.
+#[derive(Debug, Copy, Clone)]
+struct EllipseArc {
+    center: cg::Point,
+    rx: f64,
+    ry: f64,
+    phi: f64,
+    theta: f64,
+    delta: f64,
+    end: cg::Point,
+}
+
+impl EllipseArc {
+    /// See SVG 1.1 implementation notes F.6.5. Returns `None` if the arc
+    /// degenerates to a line or to nothing.
+    fn new(from: cg::Point, arc: &Arc) -> Option<Self> {
+        let to = arc.to;
+        let mut rx = arc.radii.width.abs();
+        let mut ry = arc.radii.height.abs();
+        if from == to || rx == 0.0 || ry == 0.0 {
+            return None;
+        }
+        let phi = arc.x_axis_rotation.to_radians();
+        let (sin, cos) = phi.sin_cos();
+        let dx = (from.x - to.x) / 2.0;
+        let dy = (from.y - to.y) / 2.0;
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            let s = lambda.sqrt();
+            rx *= s;
+            ry *= s;
+        }
+
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = (num / den).max(0.0).sqrt();
+        if arc.large_arc == arc.sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+
+        let center = cg::Point::new(
+            cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0,
+            sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0,
+        );
+
+        let theta = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+        let theta2 = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+        let mut delta = (theta2 - theta) % TAU;
+        if !arc.sweep && delta > 0.0 {
+            delta -= TAU;
+        } else if arc.sweep && delta < 0.0 {
+            delta += TAU;
+        }
+
+        Some(Self {
+            center,
+            rx,
+            ry,
+            phi,
+            theta,
+            delta,
+            end: to,
+        })
+    }
+
+    fn at(&self, theta: f64) -> cg::Point {
+        let (sp, cp) = self.phi.sin_cos();
+        let (st, ct) = theta.sin_cos();
+        cg::Point::new(
+            self.center.x + self.rx * cp * ct - self.ry * sp * st,
+            self.center.y + self.rx * sp * ct + self.ry * cp * st,
+        )
+    }
+
+    fn derivative(&self, theta: f64) -> cg::Point {
+        let (sp, cp) = self.phi.sin_cos();
+        let (st, ct) = theta.sin_cos();
+        cg::Point::new(
+            -self.rx * cp * st - self.ry * sp * ct,
+            -self.rx * sp * st + self.ry * cp * ct,
+        )
+    }
+
+    fn contains_angle(&self, theta: f64) -> bool {
+        let (lo, hi) = if self.delta >= 0.0 {
+            (self.theta, self.theta + self.delta)
+        } else {
+            (self.theta + self.delta, self.theta)
+        };
+        let t = lo + (theta - lo).rem_euclid(TAU);
+        t <= hi
+    }
+
+    /// Approximates the arc with cubic curves each spanning at most a quarter turn.
+    fn to_curves(self) -> Vec<(cg::Point, cg::Point, cg::Point)> {
+        let n = (self.delta.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = self.delta / n as f64;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        let mut res = Vec::with_capacity(n);
+        let mut p0 = self.at(self.theta);
+        for i in 0..n {
+            let t0 = self.theta + step * i as f64;
+            let t1 = t0 + step;
+            let p1 = if i + 1 == n { self.end } else { self.at(t1) };
+            let c1 = add(p0, scale(self.derivative(t0), k));
+            let c2 = sub(p1, scale(self.derivative(t1), k));
+            res.push((c1, c2, p1));
+            p0 = p1;
+        }
+        res
+    }
+}
+
+/// Segments resolved to absolute geometry.
+enum Piece {
+    Move(cg::Point),
+    Line(cg::Point),
+    Quad(cg::Point, cg::Point, cg::Point),
+    Curve(cg::Point, cg::Point, cg::Point, cg::Point),
+    Arc(EllipseArc),
+    Close,
+}
+
+struct Pieces<'a> {
+    segments: std::slice::Iter<'a, Segment>,
+    start: cg::Point,
+    current: cg::Point,
+    pending: Option<Piece>,
+}
+
+impl Iterator for Pieces<'_> {
+    type Item = Piece;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(p) = self.pending.take() {
+            return Some(p);
+        }
+        let from = self.current;
+        let piece = match *self.segments.next()? {
+            Segment::Move(p) => {
+                self.start = p;
+                Piece::Move(p)
+            }
+            Segment::Line(p) => Piece::Line(p),
+            Segment::Quad(c, p) => Piece::Quad(from, c, p),
+            Segment::Curve(c1, c2, p) => Piece::Curve(from, c1, c2, p),
+            Segment::Arc(a) => match EllipseArc::new(from, &a) {
+                Some(e) => Piece::Arc(e),
+                None => Piece::Line(a.to),
+            },
+            Segment::Close => {
+                self.current = self.start;
+                if from != self.start {
+                    self.pending = Some(Piece::Close);
+                    return Some(Piece::Line(self.start));
+                }
+                return Some(Piece::Close);
+            }
+        };
+        self.current = match piece {
+            Piece::Move(p) | Piece::Line(p) | Piece::Quad(_, _, p) => p,
+            Piece::Curve(_, _, _, p) => p,
+            Piece::Arc(e) => e.end,
+            Piece::Close => self.start,
+        };
+        Some(piece)
+    }
+}
+
+struct SvgParser<'a> {
+    text: &'a str,
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl SvgParser<'_> {
+    fn err<T>(&self, kind: SvgErrorKind) -> Result<T, SvgError> {
+        Err(SvgError {
+            pos: self.pos,
+            kind,
+        })
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0C') = self.src.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_ws_comma(&mut self) {
+        self.skip_ws();
+        if self.src.get(self.pos) == Some(&b',') {
+            self.pos += 1;
+            self.skip_ws();
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_ws();
+        matches!(
+            self.src.get(self.pos),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.')
+        )
+    }
+
+    fn number(&mut self) -> Result<f64, SvgError> {
+        self.skip_ws();
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let s = p.pos;
+            while let Some(b'0'..=b'9') = p.src.get(p.pos) {
+                p.pos += 1;
+            }
+            p.pos > s
+        };
+        if let Some(b'-' | b'+') = self.src.get(self.pos) {
+            self.pos += 1;
+        }
+        let int = digits(self);
+        let mut frac = false;
+        if self.src.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            frac = digits(self);
+        }
+        if !int && !frac {
+            self.pos = start;
+            return self.err(SvgErrorKind::ExpectedNumber);
+        }
+        if let Some(b'e' | b'E') = self.src.get(self.pos) {
+            let mark = self.pos;
+            self.pos += 1;
+            if let Some(b'-' | b'+') = self.src.get(self.pos) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mark;
+            }
+        }
+        let val = self.text[start..self.pos].parse::<f64>().or_else(|_| {
+            self.pos = start;
+            self.err(SvgErrorKind::ExpectedNumber)
+        })?;
+        self.skip_ws_comma();
+        Ok(val)
+    }
+
+    fn flag(&mut self) -> Result<bool, SvgError> {
+        self.skip_ws();
+        let val = match self.src.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return self.err(SvgErrorKind::ExpectedFlag),
+        };
+        self.pos += 1;
+        self.skip_ws_comma();
+        Ok(val)
+    }
+
+    fn point(&mut self, rel: bool, current: cg::Point) -> Result<cg::Point, SvgError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(if rel {
+            cg::Point::new(current.x + x, current.y + y)
+        } else {
+            cg::Point::new(x, y)
+        })
+    }
+
+    fn parse(mut self) -> Result<PathData, SvgError> {
+        let mut path = PathData::new();
+        // reflected control point for S/s and T/t
+        let mut last_ctrl: Option<(u8, cg::Point)> = None;
+        let mut cmd: Option<u8> = None;
+
+        loop {
+            self.skip_ws();
+            let Some(&c) = self.src.get(self.pos) else {
+                break;
+            };
+            if c.is_ascii_alphabetic() {
+                if !b"MmZzLlHhVvCcSsQqTtAa".contains(&c) {
+                    return self.err(SvgErrorKind::UnexpectedChar(c as char));
+                }
+                if cmd.is_none() && !matches!(c, b'M' | b'm') {
+                    return self.err(SvgErrorKind::MissingMoveTo);
+                }
+                self.pos += 1;
+                cmd = Some(c);
+            } else if cmd.is_none() {
+                return self.err(SvgErrorKind::MissingMoveTo);
+            } else if !self.at_number() || matches!(cmd, Some(b'Z' | b'z')) {
+                let ch = self.text[self.pos..].chars().next().unwrap_or_default();
+                return self.err(SvgErrorKind::UnexpectedChar(ch));
+            }
+
+            let c = cmd.unwrap();
+            let rel = c.is_ascii_lowercase();
+            let cur = path.current_point();
+            let mut ctrl = None;
+            match c.to_ascii_uppercase() {
+                b'M' => {
+                    let p = self.point(rel, cur)?;
+                    path.move_to(p);
+                    // subsequent pairs are implicit lineto
+                    cmd = Some(if rel { b'l' } else { b'L' });
+                }
+                b'Z' => {
+                    path.close_subpath();
+                }
+                b'L' => {
+                    let p = self.point(rel, cur)?;
+                    path.line_to(p);
+                }
+                b'H' => {
+                    let x = self.number()?;
+                    let x = if rel { cur.x + x } else { x };
+                    path.line_to(cg::Point::new(x, cur.y));
+                }
+                b'V' => {
+                    let y = self.number()?;
+                    let y = if rel { cur.y + y } else { y };
+                    path.line_to(cg::Point::new(cur.x, y));
+                }
+                b'C' => {
+                    let c1 = self.point(rel, cur)?;
+                    let c2 = self.point(rel, cur)?;
+                    let p = self.point(rel, cur)?;
+                    path.curve_to(c1, c2, p);
+                    ctrl = Some((b'C', c2));
+                }
+                b'S' => {
+                    let c1 = match last_ctrl {
+                        Some((b'C', c)) => sub(scale(cur, 2.0), c),
+                        _ => cur,
+                    };
+                    let c2 = self.point(rel, cur)?;
+                    let p = self.point(rel, cur)?;
+                    path.curve_to(c1, c2, p);
+                    ctrl = Some((b'C', c2));
+                }
+                b'Q' => {
+                    let c1 = self.point(rel, cur)?;
+                    let p = self.point(rel, cur)?;
+                    path.quad_to(c1, p);
+                    ctrl = Some((b'Q', c1));
+                }
+                b'T' => {
+                    let c1 = match last_ctrl {
+                        Some((b'Q', c)) => sub(scale(cur, 2.0), c),
+                        _ => cur,
+                    };
+                    let p = self.point(rel, cur)?;
+                    path.quad_to(c1, p);
+                    ctrl = Some((b'Q', c1));
+                }
+                b'A' => {
+                    let rx = self.number()?;
+                    let ry = self.number()?;
+                    let x_axis_rotation = self.number()?;
+                    let large_arc = self.flag()?;
+                    let sweep = self.flag()?;
+                    let to = self.point(rel, cur)?;
+                    path.arc_to(Arc {
+                        radii: cg::Size::new(rx, ry),
+                        x_axis_rotation,
+                        large_arc,
+                        sweep,
+                        to,
+                    });
+                }
+                _ => unreachable!(),
+            }
+            last_ctrl = ctrl;
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cg;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn svg_roundtrip() {
+        let src = "M10,20l5-5h10v10 c1 1 2 2 3 3s4 4 5 5q1 1 2 2t3 3a5 5 30 1 0 10 0zm1 1";
+        let path = cg::PathData::with_svg(src).unwrap();
+        assert_eq!(path.segments().len(), 11);
+        let svg = path.to_svg();
+        assert_eq!(svg.parse::<cg::PathData>().unwrap(), path);
+
+        let compact = cg::PathData::with_svg("M0 0a1 1 0 00.5.5").unwrap();
+        let arc = match compact.segments()[1] {
+            cg::PathDataSegment::Arc(a) => a,
+            _ => panic!("expected arc"),
+        };
+        assert!(!arc.large_arc && !arc.sweep);
+        assert_eq!(arc.to, cg::Point::new(0.5, 0.5));
+
+        let err = cg::PathData::with_svg("L 1 1").unwrap_err();
+        assert_eq!(err.kind, cg::path_data::SvgErrorKind::MissingMoveTo);
+        let err = cg::PathData::with_svg("M 1 1 L 2").unwrap_err();
+        assert_eq!(err.kind, cg::path_data::SvgErrorKind::ExpectedNumber);
+        let err = cg::PathData::with_svg("M 1 1 X").unwrap_err();
+        assert_eq!(err.pos, 6);
+    }
+
+    #[test]
+    fn bounds() {
+        let path = cg::PathData::with_svg("M0 0 C0 10 10 10 10 0").unwrap();
+        let b = path.bounding_box();
+        assert!(close(b.size.height, 7.5));
+        assert!(close(b.size.width, 10.0));
+
+        let circle = cg::PathData::with_svg("M-5 0 A5 5 0 1 1 5 0 A5 5 0 1 1 -5 0 Z").unwrap();
+        let b = circle.bounding_box();
+        assert!(close(b.origin.x, -5.0) && close(b.origin.y, -5.0));
+        assert!(close(b.size.width, 10.0) && close(b.size.height, 10.0));
+        assert!((circle.length(1e-5) - std::f64::consts::TAU * 5.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hit_test() {
+        // two nested squares with the same orientation
+        let path = cg::PathData::with_svg("M0 0H10V10H0Z M2 2H8V8H2Z").unwrap();
+        let p = cg::Point::new(5.0, 5.0);
+        assert!(path.contains_point(p, cg::PathFillRule::NonZero));
+        assert!(!path.contains_point(p, cg::PathFillRule::EvenOdd));
+        assert!(path.contains_point(cg::Point::new(1.0, 1.0), cg::PathFillRule::EvenOdd));
+        assert!(!path.contains_point(cg::Point::new(11.0, 1.0), cg::PathFillRule::NonZero));
+    }
+
+    #[test]
+    fn measure() {
+        let path = cg::PathData::with_svg("M0 0 L10 0 L10 10").unwrap();
+        assert_eq!(path.length(0.1), 20.0);
+        assert_eq!(
+            path.point_at_length(15.0, 0.1),
+            Some(cg::Point::new(10.0, 5.0))
+        );
+        assert_eq!(path.point_at_length(25.0, 0.1), None);
+
+        let quad = cg::PathData::with_svg("M0 0 Q5 10 10 0").unwrap();
+        let flat = quad.flatten(0.01);
+        assert_eq!(flat.len(), 1);
+        assert!(flat[0].len() > 10);
+        let top = quad.point_at_length(quad.length(0.01) / 2.0, 0.01).unwrap();
+        assert!((top.x - 5.0).abs() < 0.01 && (top.y - 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn cg_path() {
+        let path = cg::PathData::with_svg("M0 0 L10 0 Q15 5 10 10 C5 15 0 15 0 10 Z").unwrap();
+        let cg_path = path.to_cg_path();
+        assert_eq!(cg::PathData::with_cg_path(&cg_path), path);
+
+        // paths without arcs round trip, relative and shorthand commands are absolute
+        let paths = [
+            "M1 2 l3 4 h5 v-6 z m10 10 L20 20 20 30 Z",
+            "M0 0 q5 5 10 0 t10 0 s5 5 10 0 c1 2 3 4 5 6",
+            "M-1.5 2.25 C0 0 4 4 5 0 M7 7 L8 9 Z",
+        ];
+        for svg in paths {
+            let path = cg::PathData::with_svg(svg).unwrap();
+            let back = cg::PathData::with_cg_path(&path.to_cg_path());
+            assert_eq!(back, path, "{svg}");
+            assert_eq!(back.to_svg(), path.to_svg());
+        }
+
+        let arc = cg::PathData::with_svg("M0 0 A5 5 0 0 1 10 0").unwrap();
+        let curves = cg::PathData::with_cg_path(&arc.to_cg_path());
+        assert_eq!(curves.segments().len(), 3);
+        assert_eq!(curves.current_point(), cg::Point::new(10.0, 0.0));
+    }
+}
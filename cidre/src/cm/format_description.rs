@@ -166,7 +166,8 @@ impl VideoCodec {
     #[doc(alias = "kCMVideoCodecType_AV1")]
     pub const AV1: Self = Self::from_be_bytes(b"av01");
 
-    const fn from_be_bytes(bytes: &[u8; 4]) -> Self {
+    #[inline]
+    pub const fn from_be_bytes(bytes: &[u8; 4]) -> Self {
         Self(FourCharCode::from_be_bytes(*bytes))
     }
 }
@@ -219,7 +220,8 @@ impl FormatDesc {
         }
     }
 
-    fn video_cfg(&self, key: &cf::String) -> Option<Vec<u8>> {
+    /// Copy of the sample description extension atom payload, e.g. `avcC` or `dOps`
+    pub fn ext_atom(&self, key: &cf::String) -> Option<Vec<u8>> {
         let Some(dict) = self.ext_atoms() else {
             return None;
         };
@@ -237,11 +239,11 @@ impl FormatDesc {
     }
 
     pub fn avcc(&self) -> Option<Vec<u8>> {
-        self.video_cfg(cf::str!(c"avcC"))
+        self.ext_atom(cf::str!(c"avcC"))
     }
 
     pub fn hvcc(&self) -> Option<Vec<u8>> {
-        self.video_cfg(cf::str!(c"hvcC"))
+        self.ext_atom(cf::str!(c"hvcC"))
     }
 
     pub fn verbatim_sample_desc(&self) -> Option<&cf::Data> {
@@ -604,6 +606,20 @@ impl AudioFormatDesc {
     pub fn stream_basic_desc(&self) -> Option<&cat::audio::StreamBasicDesc> {
         unsafe { CMAudioFormatDescriptionGetStreamBasicDescription(self) }
     }
+
+    /// Codec specific data, `ES_Descriptor` for AAC
+    #[doc(alias = "CMAudioFormatDescriptionGetMagicCookie")]
+    pub fn magic_cookie(&self) -> Option<&[u8]> {
+        let mut size = 0;
+        unsafe {
+            let ptr = CMAudioFormatDescriptionGetMagicCookie(self, &mut size);
+            if ptr.is_null() || size == 0 {
+                None
+            } else {
+                Some(std::slice::from_raw_parts(ptr, size))
+            }
+        }
+    }
 }
 
 define_cf_type!(
@@ -988,6 +1004,12 @@ extern "C-unwind" {
         desc: &AudioFormatDesc,
     ) -> Option<&cat::audio::StreamBasicDesc>;
 
+    #[cfg(feature = "cat")]
+    fn CMAudioFormatDescriptionGetMagicCookie(
+        desc: &AudioFormatDesc,
+        size_out: *mut usize,
+    ) -> *const u8;
+
     fn CMFormatDescriptionCreate(
         allocator: Option<&cf::Allocator>,
         media_type: MediaType,
@@ -18,9 +18,9 @@ pub use image_buffer::attachment as image_buf_attachment;
 pub use image_buffer::ImageBuf;
 
 pub mod pixel_buffer;
+pub use crate::gfx::cv::PixelFormat;
 pub use pixel_buffer::keys as pixel_buffer_keys;
 pub use pixel_buffer::PixelBuf;
-pub use pixel_buffer::PixelFormat;
 
 pub mod pixel_buffer_pool;
 pub use pixel_buffer_pool::FlushFlags as PixelBufPoolFlushFlags;
@@ -30,6 +30,45 @@ pub mod pixel_format_description;
 pub use pixel_format_description::all_pixel_formats as pixel_format_desc_array_with_all_pixel_formats;
 pub use pixel_format_description::create as pixel_format_desc_create;
 
+pub use crate::gfx::cv::pixel_format_info;
+pub use crate::gfx::cv::ChromaSubsampling;
+pub use crate::gfx::cv::PixelFormatColorModel;
+pub use crate::gfx::cv::PixelFormatCompression;
+pub use crate::gfx::cv::PixelFormatInfo;
+pub use crate::gfx::cv::PixelFormatPlane;
+pub use crate::gfx::cv::PixelFormatRange;
+
+pub use crate::gfx::cv::pixel_convert;
+pub use crate::gfx::cv::ChromaSiting;
+pub use crate::gfx::cv::PixelConvertError;
+pub use crate::gfx::cv::PixelConvertFilter;
+pub use crate::gfx::cv::PixelConvertImageMut;
+pub use crate::gfx::cv::PixelConvertImageRef;
+pub use crate::gfx::cv::PixelConvertOpts;
+pub use crate::gfx::cv::PixelConvertPlaneMut;
+pub use crate::gfx::cv::PixelConvertPlaneRef;
+pub use crate::gfx::cv::YCbCrMatrix;
+
+pub use crate::gfx::cv::pixel_image;
+pub use crate::gfx::cv::PixelBufExtendedPixels;
+pub use crate::gfx::cv::PixelImage;
+pub use crate::gfx::cv::PixelImageError;
+pub use crate::gfx::cv::PixelImageLayout;
+pub use crate::gfx::cv::PixelImagePlaneView;
+pub use crate::gfx::cv::PixelImagePlaneViewMut;
+pub use crate::gfx::cv::PixelImageView;
+pub use crate::gfx::cv::PixelImageViewMut;
+pub use crate::gfx::cv::PixelSample;
+
+pub use crate::gfx::cv::y4m;
+pub use crate::gfx::cv::RawYuvReader;
+pub use crate::gfx::cv::RawYuvWriter;
+pub use crate::gfx::cv::Y4mError;
+pub use crate::gfx::cv::Y4mHeader;
+pub use crate::gfx::cv::Y4mInterlace;
+pub use crate::gfx::cv::Y4mReader;
+pub use crate::gfx::cv::Y4mWriter;
+
 #[cfg(feature = "mtl")]
 pub mod metal;
 #[cfg(feature = "mtl")]
@@ -0,0 +1,17 @@
+//! Framework independent value types, transform, color, pixel and texture layout
+//! math of Core Graphics, Core Animation, Core Video and Metal.
+//!
+//! Nothing here links Apple frameworks, so it builds on every target.
+//! Framework modules re-export these items, `cg::Point` is `gfx::cg::Point`.
+//!
+//! ```
+//! use cidre::gfx::cg;
+//!
+//! let t = cg::AffineTransform::new_scale(2.0, 3.0);
+//! assert_eq!(cg::Point::new(1.0, 1.0) * t, cg::Point::new(2.0, 3.0));
+//! ```
+
+pub mod ca;
+pub mod cg;
+pub mod cv;
+pub mod mtl;
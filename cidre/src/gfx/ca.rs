@@ -0,0 +1,2 @@
+mod transform3d;
+pub use transform3d::Transform3d;
@@ -0,0 +1,400 @@
+use crate::gfx::cg;
+
+#[cfg(feature = "simd")]
+use crate::simd;
+
+/// ```
+/// use cidre::gfx::ca;
+///
+/// let t = ca::Transform3d::identity();
+///
+/// assert!(t.is_identity());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct Transform3d {
+    pub m11: f64,
+    pub m12: f64,
+    pub m13: f64,
+    pub m14: f64,
+    pub m21: f64,
+    pub m22: f64,
+    pub m23: f64,
+    pub m24: f64,
+    pub m31: f64,
+    pub m32: f64,
+    pub m33: f64,
+    pub m34: f64,
+    pub m41: f64,
+    pub m42: f64,
+    pub m43: f64,
+    pub m44: f64,
+}
+
+impl Transform3d {
+    /// The identity transform: [1 0 0 0; 0 1 0 0; 0 0 1 0; 0 0 0 1].
+    #[inline]
+    pub const fn identity() -> Self {
+        Self::with_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Builds a transform from rows `[m11 m12 m13 m14]` ... `[m41 m42 m43 m44]`.
+    #[inline]
+    pub const fn with_rows(m: [[f64; 4]; 4]) -> Self {
+        Self {
+            m11: m[0][0],
+            m12: m[0][1],
+            m13: m[0][2],
+            m14: m[0][3],
+            m21: m[1][0],
+            m22: m[1][1],
+            m23: m[1][2],
+            m24: m[1][3],
+            m31: m[2][0],
+            m32: m[2][1],
+            m33: m[2][2],
+            m34: m[2][3],
+            m41: m[3][0],
+            m42: m[3][1],
+            m43: m[3][2],
+            m44: m[3][3],
+        }
+    }
+
+    #[inline]
+    pub const fn rows(&self) -> [[f64; 4]; 4] {
+        [
+            [self.m11, self.m12, self.m13, self.m14],
+            [self.m21, self.m22, self.m23, self.m24],
+            [self.m31, self.m32, self.m33, self.m34],
+            [self.m41, self.m42, self.m43, self.m44],
+        ]
+    }
+
+    /// Returns a transform that translates by '(tx, ty, tz)':
+    /// self =  [1 0 0 0; 0 1 0 0; 0 0 1 0; tx ty tz 1].
+    #[inline]
+    pub const fn new_translation(tx: f64, ty: f64, tz: f64) -> Self {
+        Self::with_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [tx, ty, tz, 1.0],
+        ])
+    }
+
+    /// Returns a transform that scales by `(sx, sy, sz)':
+    /// self = [sx 0 0 0; 0 sy 0 0; 0 0 sz 0; 0 0 0 1].
+    #[inline]
+    pub const fn new_scale(sx: f64, sy: f64, sz: f64) -> Self {
+        Self::with_rows([
+            [sx, 0.0, 0.0, 0.0],
+            [0.0, sy, 0.0, 0.0],
+            [0.0, 0.0, sz, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Returns a transform that rotates by 'angle' radians about the vector
+    /// '(x, y, z)'. If the vector has length zero the identity transform is
+    /// returned.
+    ///
+    /// ```
+    /// use cidre::gfx::{ca, cg};
+    ///
+    /// let angle = 0.3;
+    /// let t = ca::Transform3d::new_rotation(angle, 0.0, 0.0, 2.0);
+    /// assert!(t.is_affine());
+    /// assert_eq!(t.to_affine_transform(), cg::AffineTransform::new_rotation(angle));
+    /// assert!(ca::Transform3d::new_rotation(angle, 0.0, 0.0, 0.0).is_identity());
+    /// ```
+    #[inline]
+    pub fn new_rotation(angle: f64, x: f64, y: f64, z: f64) -> Self {
+        let len = (x * x + y * y + z * z).sqrt();
+        if len == 0.0 || !len.is_finite() {
+            return Self::identity();
+        }
+        let (x, y, z) = (x / len, y / len, z / len);
+        let (s, c) = angle.sin_cos();
+        let t = 1.0 - c;
+        Self::with_rows([
+            [t * x * x + c, t * x * y + z * s, t * x * z - y * s, 0.0],
+            [t * x * y - z * s, t * y * y + c, t * y * z + x * s, 0.0],
+            [t * x * z + y * s, t * y * z - x * s, t * z * z + c, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    #[inline]
+    pub const fn from_cg_affine_transform(m: cg::AffineTransform) -> Self {
+        Self::with_rows([
+            [m.a, m.b, 0.0, 0.0],
+            [m.c, m.d, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [m.tx, m.ty, 0.0, 1.0],
+        ])
+    }
+
+    /// Returns true if 'self' is the identity transform.
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Translate 'self' by '(tx, ty, tz)': self' = translate(tx, ty, tz) * self.
+    #[inline]
+    pub fn translate(&self, tx: f64, ty: f64, tz: f64) -> Self {
+        Self {
+            m41: tx * self.m11 + ty * self.m21 + tz * self.m31 + self.m41,
+            m42: tx * self.m12 + ty * self.m22 + tz * self.m32 + self.m42,
+            m43: tx * self.m13 + ty * self.m23 + tz * self.m33 + self.m43,
+            m44: tx * self.m14 + ty * self.m24 + tz * self.m34 + self.m44,
+            ..*self
+        }
+    }
+
+    /// Scale 'self' by '(sx, sy, sz)': self' = scale(sx, sy, sz) * self.
+    #[inline]
+    pub fn scale(&self, sx: f64, sy: f64, sz: f64) -> Self {
+        Self {
+            m11: self.m11 * sx,
+            m12: self.m12 * sx,
+            m13: self.m13 * sx,
+            m14: self.m14 * sx,
+            m21: self.m21 * sy,
+            m22: self.m22 * sy,
+            m23: self.m23 * sy,
+            m24: self.m24 * sy,
+            m31: self.m31 * sz,
+            m32: self.m32 * sz,
+            m33: self.m33 * sz,
+            m34: self.m34 * sz,
+            ..*self
+        }
+    }
+
+    /// Rotate 'self' by 'angle' radians about the vector '(x, y, z)':
+    /// self' = rotation(angle, x, y, z) * self.
+    /// If the vector has zero length the behavior is undefined.
+    #[inline]
+    pub fn rotate(&self, angle: f64, x: f64, y: f64, z: f64) -> Self {
+        Self::new_rotation(angle, x, y, z).concat(self)
+    }
+
+    /// Concatenate 'other' to 'self': self' = self * other.
+    #[inline]
+    pub fn concat(&self, other: &Transform3d) -> Self {
+        let a = self.rows();
+        let b = other.rows();
+        let mut m = [[0.0f64; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j] + a[i][3] * b[3][j];
+            }
+        }
+        Self::with_rows(m)
+    }
+
+    /// Invert 'self'. If 'self' has no inverse it is returned unchanged.
+    #[inline]
+    pub fn invert(&self) -> Self {
+        self.try_invert().unwrap_or(*self)
+    }
+
+    /// Returns `None` if 'self' is singular.
+    pub fn try_invert(&self) -> Option<Self> {
+        let mut m = self.rows();
+        let mut inv = Self::identity().rows();
+
+        // Gauss-Jordan elimination with partial pivoting
+        for col in 0..4 {
+            let mut pivot = col;
+            for row in col + 1..4 {
+                if m[row][col].abs() > m[pivot][col].abs() {
+                    pivot = row;
+                }
+            }
+            let p = m[pivot][col];
+            if p == 0.0 || !p.is_finite() {
+                return None;
+            }
+            m.swap(col, pivot);
+            inv.swap(col, pivot);
+            for j in 0..4 {
+                m[col][j] /= p;
+                inv[col][j] /= p;
+            }
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let f = m[row][col];
+                if f == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    m[row][j] -= f * m[col][j];
+                    inv[row][j] -= f * inv[col][j];
+                }
+            }
+        }
+        Some(Self::with_rows(inv))
+    }
+
+    /// Returns true if 'self' can be exactly represented by an affine transform.
+    #[inline]
+    pub fn is_affine(&self) -> bool {
+        self.m13 == 0.0
+            && self.m14 == 0.0
+            && self.m23 == 0.0
+            && self.m24 == 0.0
+            && self.m31 == 0.0
+            && self.m32 == 0.0
+            && self.m33 == 1.0
+            && self.m34 == 0.0
+            && self.m43 == 0.0
+            && self.m44 == 1.0
+    }
+
+    /// Returns the affine transform represented by 'self'. If 'self' can not be
+    /// exactly represented as an affine transform the returned value is
+    /// undefined.
+    #[inline]
+    pub const fn to_affine_transform(&self) -> cg::AffineTransform {
+        cg::AffineTransform {
+            a: self.m11,
+            b: self.m12,
+            c: self.m21,
+            d: self.m22,
+            tx: self.m41,
+            ty: self.m42,
+        }
+    }
+
+    /// Column-vector 4x4 matrix, that is the transpose of 'self'.
+    /// Translation ends up in the last lane of the first three rows.
+    /// Values are narrowed to `f32`.
+    #[cfg(feature = "simd")]
+    #[inline]
+    pub fn to_simd(&self) -> simd::f32x4x4 {
+        let m = self.rows();
+        let row = |j: usize| {
+            simd::f32x4::with_xyzw_f32(
+                m[0][j] as f32,
+                m[1][j] as f32,
+                m[2][j] as f32,
+                m[3][j] as f32,
+            )
+        };
+        simd::f32x4x4([row(0), row(1), row(2), row(3)])
+    }
+
+    /// Inverse of [`Self::to_simd`].
+    #[cfg(feature = "simd")]
+    #[inline]
+    pub fn with_simd(m: &simd::f32x4x4) -> Self {
+        let mut rows = [[0.0f64; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, v) in row.iter_mut().enumerate() {
+                *v = m[j][i] as f64;
+            }
+        }
+        Self::with_rows(rows)
+    }
+}
+
+impl Default for Transform3d {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl std::ops::Mul for Transform3d {
+    type Output = Self;
+
+    /// Same as [`Transform3d::concat`]
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.concat(&rhs)
+    }
+}
+
+impl std::ops::MulAssign for Transform3d {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.concat(&rhs)
+    }
+}
+
+impl From<cg::AffineTransform> for Transform3d {
+    #[inline]
+    fn from(value: cg::AffineTransform) -> Self {
+        Self::from_cg_affine_transform(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::{ca, cg};
+
+    fn assert_close(a: &ca::Transform3d, b: &ca::Transform3d) {
+        for (a, b) in a.rows().iter().flatten().zip(b.rows().iter().flatten()) {
+            assert!((a - b).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let t = ca::Transform3d::identity()
+            .translate(1.0, 2.0, 3.0)
+            .scale(2.0, 2.0, 2.0);
+        assert_close(
+            &t,
+            &(ca::Transform3d::new_scale(2.0, 2.0, 2.0)
+                * ca::Transform3d::new_translation(1.0, 2.0, 3.0)),
+        );
+        assert_eq!(t.m41, 1.0);
+
+        let r = ca::Transform3d::identity().rotate(1.0, 1.0, 2.0, 3.0);
+        assert_close(&r, &ca::Transform3d::new_rotation(1.0, 1.0, 2.0, 3.0));
+        assert!(!r.is_affine());
+
+        let mut m = r * t;
+        m *= (r * t).invert();
+        assert_close(&m, &ca::Transform3d::identity());
+        assert!(ca::Transform3d::new_scale(1.0, 0.0, 1.0)
+            .try_invert()
+            .is_none());
+    }
+
+    #[test]
+    fn affine() {
+        let a = cg::AffineTransform::new_rotation(0.7).translate(3.0, 4.0);
+        let t = ca::Transform3d::from(a);
+        assert!(t.is_affine());
+        assert_eq!(t.to_affine_transform(), a);
+
+        let b = cg::AffineTransform::new_scale(2.0, 3.0);
+        let c = (t * ca::Transform3d::from(b)).to_affine_transform();
+        let expected = a * b;
+        assert!((c.tx - expected.tx).abs() < 1e-9);
+        assert!((c.d - expected.d).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd() {
+        let t = ca::Transform3d::new_translation(1.0, 2.0, 3.0);
+        let m = t.to_simd();
+        assert_eq!(m.tx(), 1.0);
+        assert_eq!(m.ty(), 2.0);
+        assert_eq!(m.tz(), 3.0);
+        assert_eq!(ca::Transform3d::with_simd(&m), t);
+    }
+}
@@ -0,0 +1,12 @@
+mod geometry;
+pub use geometry::Float;
+pub use geometry::Point;
+pub use geometry::Rect;
+pub use geometry::Size;
+pub use geometry::Vector;
+
+pub mod affine_transform;
+pub use affine_transform::AffineTransform;
+pub use affine_transform::Components as AffineTransformComponents;
+
+pub mod color_math;
@@ -0,0 +1,435 @@
+use crate::gfx::cg;
+
+#[cfg(feature = "simd")]
+use crate::simd;
+
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct AffineTransform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub tx: f64,
+    pub ty: f64,
+}
+
+/*                      |--------------------------- Components ------------------------|
+ *
+ *      | a  b  0 |     | sx  0  0 |   |  1  0  0 |   | cos(t)  sin(t)  0 |   | 1  0  0 |
+ *      | c  d  0 |  =  |  0 sy  0 | * | sh  1  0 | * |-sin(t)  cos(t)  0 | * | 0  1  0 |
+ *      | tx ty 1 |     |  0  0  1 |   |  0  0  1 |   |   0       0     1 |   | tx ty 1 |
+ *    AffineTransform      scale           shear            rotation          translation
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[repr(C)]
+pub struct Components {
+    /// Initial scaling in X and Y dimensions. {sx,sy}
+    /// Negative values indicate the image has been flipped in this dimension.
+    pub scale: cg::Size,
+
+    /// shear distortion (sh). Turns rectangles to parallelograms. 0 for no shear. Typically 0.
+    pub horizontal_shear: cg::Float,
+
+    /// Rotation angle in radians about the origin. (t) Sign convention for clockwise rotation
+    /// may differ between various Apple frameworks based on origin placement. Please see discussion.
+    pub rotation: cg::Float,
+
+    /// Displacement from the origin (ty, ty)
+    pub translation: cg::Vector,
+}
+
+impl AffineTransform {
+    #[inline]
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, tx: f64, ty: f64) -> Self {
+        Self { a, b, c, d, tx, ty }
+    }
+
+    /// ```
+    /// use cidre::gfx::cg;
+    ///
+    /// let c = cg::AffineTransformComponents {
+    ///     scale: cg::Size::new(2.0, -3.0),
+    ///     horizontal_shear: 0.5,
+    ///     rotation: 0.25,
+    ///     translation: cg::Vector { dx: 10.0, dy: 20.0 },
+    /// };
+    /// let t = cg::AffineTransform::with_components(c);
+    /// let d = t.decompose();
+    /// assert!((d.scale.width - 2.0).abs() < 1e-12);
+    /// assert!((d.scale.height + 3.0).abs() < 1e-12);
+    /// assert!((d.horizontal_shear - 0.5).abs() < 1e-12);
+    /// assert!((d.rotation - 0.25).abs() < 1e-12);
+    /// assert_eq!(d.translation, c.translation);
+    /// ```
+    #[inline]
+    pub fn with_components(components: Components) -> Self {
+        let (sin, cos) = components.rotation.sin_cos();
+        let sx = components.scale.width;
+        let sy = components.scale.height;
+        let sh = components.horizontal_shear;
+        Self {
+            a: sx * cos,
+            b: sx * sin,
+            c: sy * (sh * cos - sin),
+            d: sy * (sh * sin + cos),
+            tx: components.translation.dx,
+            ty: components.translation.dy,
+        }
+    }
+
+    #[inline]
+    pub const fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    #[inline]
+    pub const fn new_translation(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    #[inline]
+    pub const fn new_scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    #[inline]
+    pub fn new_rotation(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Translate `self` by `(tx, ty)`: `self' = translate(tx, ty) * self`.
+    #[inline]
+    pub fn translate(&self, tx: f64, ty: f64) -> Self {
+        Self {
+            tx: self.tx + tx * self.a + ty * self.c,
+            ty: self.ty + tx * self.b + ty * self.d,
+            ..*self
+        }
+    }
+
+    /// Scale `self` by `(sx, sy)`: `self' = scale(sx, sy) * self`.
+    #[inline]
+    pub fn scale(&self, sx: f64, sy: f64) -> Self {
+        Self {
+            a: self.a * sx,
+            b: self.b * sx,
+            c: self.c * sy,
+            d: self.d * sy,
+            ..*self
+        }
+    }
+
+    /// Rotate `self` by `angle` radians: `self' = rotation(angle) * self`.
+    #[inline]
+    pub fn rotate(&self, angle: f64) -> Self {
+        Self::new_rotation(angle).concat(self)
+    }
+
+    #[inline]
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Invert `self`. If `self` can't be inverted, it is returned unchanged.
+    ///
+    /// ```
+    /// use cidre::gfx::cg;
+    ///
+    /// let t = cg::AffineTransform::new_scale(2.0, 4.0).translate(10.0, 20.0);
+    /// assert!((t * t.invert()).is_identity());
+    ///
+    /// let singular = cg::AffineTransform::new_scale(0.0, 1.0);
+    /// assert_eq!(singular.invert(), singular);
+    /// ```
+    #[inline]
+    pub fn invert(&self) -> Self {
+        self.try_invert().unwrap_or(*self)
+    }
+
+    /// Returns `None` if `self` is singular.
+    #[inline]
+    pub fn try_invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let a = self.d / det;
+        let b = -self.b / det;
+        let c = -self.c / det;
+        let d = self.a / det;
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            tx: -(self.tx * a + self.ty * c),
+            ty: -(self.tx * b + self.ty * d),
+        })
+    }
+
+    /// Concatenate `self` and `other`: `self' = self * other`.
+    /// The result applies `self` first and then `other`.
+    #[inline]
+    pub fn concat(&self, other: &Self) -> Self {
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.tx * other.a + self.ty * other.c + other.tx,
+            ty: self.tx * other.b + self.ty * other.d + other.ty,
+        }
+    }
+
+    #[inline]
+    pub fn equal_to(&self, other: &Self) -> bool {
+        self.a == other.a
+            && self.b == other.b
+            && self.c == other.c
+            && self.d == other.d
+            && self.tx == other.tx
+            && self.ty == other.ty
+    }
+
+    /// Decompose `self` into scale, shear, rotation and translation.
+    /// Horizontal scale is always non-negative, flips are reported in vertical scale.
+    #[inline]
+    pub fn decompose(&self) -> Components {
+        let sx = self.a.hypot(self.b);
+        let rotation = if sx == 0.0 { 0.0 } else { self.b.atan2(self.a) };
+        let (sin, cos) = rotation.sin_cos();
+        let sy = self.d * cos - self.c * sin;
+        let horizontal_shear = if sy == 0.0 {
+            0.0
+        } else {
+            (self.c * cos + self.d * sin) / sy
+        };
+        Components {
+            scale: cg::Size::new(sx, sy),
+            horizontal_shear,
+            rotation,
+            translation: cg::Vector {
+                dx: self.tx,
+                dy: self.ty,
+            },
+        }
+    }
+
+    /// Column-vector 3x3 matrix, rows are `[a c tx]`, `[b d ty]`, `[0 0 1]`.
+    /// Values are narrowed to `f32`.
+    #[cfg(feature = "simd")]
+    #[inline]
+    pub fn to_simd(&self) -> simd::f32x3x3 {
+        simd::f32x3x3([
+            simd::f32x3::with_xyz_f32(self.a as f32, self.c as f32, self.tx as f32),
+            simd::f32x3::with_xyz_f32(self.b as f32, self.d as f32, self.ty as f32),
+            simd::f32x3::with_xyz_f32(0.0, 0.0, 1.0),
+        ])
+    }
+
+    /// Inverse of [`Self::to_simd`]. The projective row is ignored.
+    #[cfg(feature = "simd")]
+    #[inline]
+    pub fn with_simd(m: &simd::f32x3x3) -> Self {
+        let [r0, r1, _] = m.0;
+        Self {
+            a: r0.x() as f64,
+            b: r1.x() as f64,
+            c: r0.y() as f64,
+            d: r1.y() as f64,
+            tx: r0.z() as f64,
+            ty: r1.z() as f64,
+        }
+    }
+}
+
+impl Default for AffineTransform {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl PartialEq for AffineTransform {
+    /// ```
+    /// use cidre::gfx::cg::AffineTransform;
+    ///
+    /// let a = AffineTransform::identity();
+    /// assert!(a.is_identity());
+    /// assert_eq!(a, AffineTransform::identity());
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.equal_to(other)
+    }
+}
+
+impl std::ops::Mul for AffineTransform {
+    type Output = Self;
+
+    /// Same as [`AffineTransform::concat`]
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.concat(&rhs)
+    }
+}
+
+impl std::ops::MulAssign for AffineTransform {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.concat(&rhs)
+    }
+}
+
+impl std::ops::Mul<AffineTransform> for cg::Point {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: AffineTransform) -> Self::Output {
+        self.apply_affine_transform(&rhs)
+    }
+}
+
+impl std::ops::Mul<AffineTransform> for cg::Size {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: AffineTransform) -> Self::Output {
+        self.apply_affine_transform(&rhs)
+    }
+}
+
+impl std::ops::Mul<AffineTransform> for cg::Rect {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: AffineTransform) -> Self::Output {
+        self.apply_affine_transform(&rhs)
+    }
+}
+
+impl cg::Point {
+    #[inline]
+    pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        cg::Point {
+            x: t.a * self.x + t.c * self.y + t.tx,
+            y: t.b * self.x + t.d * self.y + t.ty,
+        }
+    }
+}
+
+impl cg::Size {
+    #[inline]
+    pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        cg::Size {
+            width: t.a * self.width + t.c * self.height,
+            height: t.b * self.width + t.d * self.height,
+        }
+    }
+}
+
+impl cg::Rect {
+    /// Returns the smallest rectangle which contains all four transformed corners of `self`.
+    ///
+    /// ```
+    /// use cidre::gfx::cg;
+    ///
+    /// let r = cg::Rect::new(0.0, 0.0, 10.0, 20.0);
+    /// let t = cg::AffineTransform::new_rotation(std::f64::consts::FRAC_PI_2);
+    /// let b = r.apply_affine_transform(&t);
+    /// assert!((b.origin.x + 20.0).abs() < 1e-9);
+    /// assert!(b.origin.y.abs() < 1e-9);
+    /// assert!((b.size.width - 20.0).abs() < 1e-9);
+    /// assert!((b.size.height - 10.0).abs() < 1e-9);
+    /// ```
+    #[inline]
+    pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        let x0 = self.origin.x;
+        let y0 = self.origin.y;
+        let x1 = x0 + self.size.width;
+        let y1 = y0 + self.size.height;
+        let corners = [
+            cg::Point::new(x0, y0).apply_affine_transform(t),
+            cg::Point::new(x1, y0).apply_affine_transform(t),
+            cg::Point::new(x0, y1).apply_affine_transform(t),
+            cg::Point::new(x1, y1).apply_affine_transform(t),
+        ];
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for p in &corners[1..] {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+        cg::Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::cg;
+
+    fn assert_close(a: &cg::AffineTransform, b: &cg::AffineTransform) {
+        let a = [a.a, a.b, a.c, a.d, a.tx, a.ty];
+        let b = [b.a, b.b, b.c, b.d, b.tx, b.ty];
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let t = cg::AffineTransform::identity()
+            .translate(10.0, 20.0)
+            .scale(2.0, 3.0);
+        let p = cg::Point::new(1.0, 1.0) * t;
+        assert_eq!(p, cg::Point::new(12.0, 23.0));
+
+        let expected = cg::AffineTransform::new_scale(2.0, 3.0)
+            * cg::AffineTransform::new_translation(10.0, 20.0);
+        assert_close(&t, &expected);
+
+        let r = cg::AffineTransform::identity().rotate(0.5);
+        assert_close(&r, &cg::AffineTransform::new_rotation(0.5));
+        assert_close(&(r * r.invert()), &cg::AffineTransform::identity());
+
+        let mut m = t;
+        m *= t.invert();
+        assert!(m.try_invert().is_some());
+        assert_close(&m, &cg::AffineTransform::identity());
+        assert!(cg::AffineTransform::new_scale(0.0, 0.0)
+            .try_invert()
+            .is_none());
+    }
+
+    #[test]
+    fn components() {
+        for t in [
+            cg::AffineTransform::identity(),
+            cg::AffineTransform::new_rotation(-2.0).scale(1.5, -0.5),
+            cg::AffineTransform::new(1.0, 0.2, 0.7, 1.1, -4.0, 5.0),
+        ] {
+            let c = t.decompose();
+            assert_close(&cg::AffineTransform::with_components(c), &t);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd() {
+        let t = cg::AffineTransform::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        let m = t.to_simd();
+        assert_eq!(m.0[0].z(), 5.0);
+        assert_eq!(m.0[1].z(), 6.0);
+        assert_eq!(cg::AffineTransform::with_simd(&m), t);
+    }
+}
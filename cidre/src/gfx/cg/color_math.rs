@@ -0,0 +1,800 @@
+//! Pure Rust color conversions.
+//!
+//! RGB values are encoded with the transfer function of their [`RgbSpace`],
+//! XYZ values are relative to the D65 white with Y = 1 for diffuse white
+//! unless stated otherwise.
+
+use crate::gfx::cg;
+
+type Mat3 = [[f64; 3]; 3];
+
+/// Luminance of PQ code value 1.0 in cd/m².
+pub const PQ_MAX_NITS: f64 = 10_000.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Rgb {
+    pub r: cg::Float,
+    pub g: cg::Float,
+    pub b: cg::Float,
+}
+
+impl Rgb {
+    #[inline]
+    pub const fn new(r: cg::Float, g: cg::Float, b: cg::Float) -> Self {
+        Self { r, g, b }
+    }
+
+    #[inline]
+    const fn to_array(self) -> [f64; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    #[inline]
+    const fn with_array(v: [f64; 3]) -> Self {
+        Self::new(v[0], v[1], v[2])
+    }
+
+    #[inline]
+    fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b))
+    }
+
+    /// Clamps components to `[0, 1]`.
+    #[inline]
+    pub fn clip(self) -> Self {
+        self.map(|v| v.clamp(0.0, 1.0))
+    }
+
+    #[inline]
+    pub fn is_in_unit_range(&self, epsilon: f64) -> bool {
+        self.to_array()
+            .iter()
+            .all(|v| *v >= -epsilon && *v <= 1.0 + epsilon)
+    }
+
+    pub fn to_hsb(&self) -> Hsb {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        Hsb {
+            hue: hue(self, max, delta),
+            saturation: if max == 0.0 { 0.0 } else { delta / max },
+            brightness: max,
+        }
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        Hsl {
+            hue: hue(self, max, delta),
+            saturation,
+            lightness,
+        }
+    }
+}
+
+/// Hue in `[0, 1)`.
+fn hue(c: &Rgb, max: f64, delta: f64) -> f64 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+    let h = if max == c.r {
+        ((c.g - c.b) / delta).rem_euclid(6.0)
+    } else if max == c.g {
+        (c.b - c.r) / delta + 2.0
+    } else {
+        (c.r - c.g) / delta + 4.0
+    };
+    h / 6.0
+}
+
+fn rgb_with_hue(hue: f64, chroma: f64, m: f64) -> Rgb {
+    let h = hue.rem_euclid(1.0) * 6.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    Rgb::new(r + m, g + m, b + m)
+}
+
+/// Hue, saturation and brightness (value), all in `[0, 1]`.
+/// Same model as `ns::Color` hue/saturation/brightness components.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Hsb {
+    pub hue: cg::Float,
+    pub saturation: cg::Float,
+    pub brightness: cg::Float,
+}
+
+impl Hsb {
+    pub fn to_rgb(&self) -> Rgb {
+        let chroma = self.brightness * self.saturation;
+        rgb_with_hue(self.hue, chroma, self.brightness - chroma)
+    }
+}
+
+/// Hue, saturation and lightness, all in `[0, 1]`.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Hsl {
+    pub hue: cg::Float,
+    pub saturation: cg::Float,
+    pub lightness: cg::Float,
+}
+
+impl Hsl {
+    pub fn to_rgb(&self) -> Rgb {
+        let chroma = (1.0 - (2.0 * self.lightness - 1.0).abs()) * self.saturation;
+        rgb_with_hue(self.hue, chroma, self.lightness - chroma / 2.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Xyz {
+    pub x: cg::Float,
+    pub y: cg::Float,
+    pub z: cg::Float,
+}
+
+impl Xyz {
+    #[inline]
+    pub const fn new(x: cg::Float, y: cg::Float, z: cg::Float) -> Self {
+        Self { x, y, z }
+    }
+
+    #[inline]
+    const fn to_array(self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    #[inline]
+    const fn with_array(v: [f64; 3]) -> Self {
+        Self::new(v[0], v[1], v[2])
+    }
+
+    /// Chromaticity coordinates `(x, y)`.
+    pub fn chromaticity(&self) -> (f64, f64) {
+        let sum = self.x + self.y + self.z;
+        if sum == 0.0 {
+            return (0.0, 0.0);
+        }
+        (self.x / sum, self.y / sum)
+    }
+
+    /// Bradford chromatic adaptation from `src` to `dst` white.
+    pub fn adapt(&self, src: WhitePoint, dst: WhitePoint) -> Self {
+        if src == dst {
+            return *self;
+        }
+        Self::with_array(mul_vec(&bradford(src, dst), self.to_array()))
+    }
+}
+
+/// CIE L*a*b*, L in `[0, 100]`.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Lab {
+    pub l: cg::Float,
+    pub a: cg::Float,
+    pub b: cg::Float,
+}
+
+const LAB_E: f64 = 216.0 / 24389.0;
+const LAB_K: f64 = 24389.0 / 27.0;
+
+impl Lab {
+    /// `xyz` must be relative to `white`.
+    pub fn with_xyz(xyz: Xyz, white: WhitePoint) -> Self {
+        let w = white.xyz();
+        let f = |v: f64| {
+            if v > LAB_E {
+                v.cbrt()
+            } else {
+                (LAB_K * v + 16.0) / 116.0
+            }
+        };
+        let fx = f(xyz.x / w.x);
+        let fy = f(xyz.y / w.y);
+        let fz = f(xyz.z / w.z);
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    /// Result is relative to `white`.
+    pub fn to_xyz(&self, white: WhitePoint) -> Xyz {
+        let w = white.xyz();
+        let fy = (self.l + 16.0) / 116.0;
+        let fx = fy + self.a / 500.0;
+        let fz = fy - self.b / 200.0;
+        let inv = |f: f64| {
+            let f3 = f * f * f;
+            if f3 > LAB_E {
+                f3
+            } else {
+                (116.0 * f - 16.0) / LAB_K
+            }
+        };
+        let y = if self.l > LAB_K * LAB_E {
+            fy * fy * fy
+        } else {
+            self.l / LAB_K
+        };
+        Xyz::new(inv(fx) * w.x, y * w.y, inv(fz) * w.z)
+    }
+
+    /// Chroma and hue angle in radians.
+    pub fn to_lch(&self) -> (f64, f64) {
+        (self.a.hypot(self.b), self.b.atan2(self.a))
+    }
+
+    pub fn with_lch(l: f64, chroma: f64, hue: f64) -> Self {
+        let (sin, cos) = hue.sin_cos();
+        Self {
+            l,
+            a: chroma * cos,
+            b: chroma * sin,
+        }
+    }
+
+    /// CIE76 color difference.
+    pub fn delta_e(&self, other: &Self) -> f64 {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2))
+            .sqrt()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WhitePoint {
+    D65,
+    D50,
+}
+
+impl WhitePoint {
+    /// CIE 1931 chromaticity coordinates.
+    pub const fn xy(self) -> (f64, f64) {
+        match self {
+            Self::D65 => (0.3127, 0.3290),
+            Self::D50 => (0.3457, 0.3585),
+        }
+    }
+
+    /// Tristimulus values with Y = 1.
+    pub fn xyz(self) -> Xyz {
+        let (x, y) = self.xy();
+        Xyz::new(x / y, 1.0, (1.0 - x - y) / y)
+    }
+}
+
+const BRADFORD: Mat3 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+fn bradford(src: WhitePoint, dst: WhitePoint) -> Mat3 {
+    let s = mul_vec(&BRADFORD, src.xyz().to_array());
+    let d = mul_vec(&BRADFORD, dst.xyz().to_array());
+    let scale = [
+        [d[0] / s[0], 0.0, 0.0],
+        [0.0, d[1] / s[1], 0.0],
+        [0.0, 0.0, d[2] / s[2]],
+    ];
+    mul(&invert(&BRADFORD), &mul(&scale, &BRADFORD))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Primaries {
+    /// ITU-R BT.709, shared by sRGB.
+    Bt709,
+    /// DCI-P3 primaries as used by Display P3.
+    P3,
+    /// ITU-R BT.2020 and BT.2100.
+    Bt2020,
+}
+
+impl Primaries {
+    /// Red, green and blue CIE 1931 chromaticity coordinates.
+    pub const fn xy(self) -> [(f64, f64); 3] {
+        match self {
+            Self::Bt709 => [(0.640, 0.330), (0.300, 0.600), (0.150, 0.060)],
+            Self::P3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+            Self::Bt2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+        }
+    }
+
+    pub const fn white_point(self) -> WhitePoint {
+        WhitePoint::D65
+    }
+
+    /// Linear RGB to XYZ matrix.
+    pub fn to_xyz_matrix(self) -> [[f64; 3]; 3] {
+        let xyz = |(x, y): (f64, f64)| [x / y, 1.0, (1.0 - x - y) / y];
+        let [r, g, b] = self.xy().map(xyz);
+        let m = [[r[0], g[0], b[0]], [r[1], g[1], b[1]], [r[2], g[2], b[2]]];
+        let s = mul_vec(&invert(&m), self.white_point().xyz().to_array());
+        [
+            [m[0][0] * s[0], m[0][1] * s[1], m[0][2] * s[2]],
+            [m[1][0] * s[0], m[1][1] * s[1], m[1][2] * s[2]],
+            [m[2][0] * s[0], m[2][1] * s[1], m[2][2] * s[2]],
+        ]
+    }
+
+    /// XYZ to linear RGB matrix.
+    pub fn from_xyz_matrix(self) -> [[f64; 3]; 3] {
+        invert(&self.to_xyz_matrix())
+    }
+}
+
+/// Transfer functions. `to_linear` decodes (EOTF), `from_linear` encodes (inverse EOTF or OETF).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TransferFn {
+    Linear,
+    /// IEC 61966-2-1
+    Srgb,
+    Gamma22,
+    /// ITU-R BT.709 OETF
+    Bt709,
+    /// SMPTE ST 2084, linear 1.0 is [`PQ_MAX_NITS`].
+    Pq,
+    /// ITU-R BT.2100 HLG OETF, linear values are scene light in `[0, 1]`.
+    Hlg,
+}
+
+const PQ_M1: f64 = 2610.0 / 16384.0;
+const PQ_M2: f64 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f64 = 3424.0 / 4096.0;
+const PQ_C2: f64 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f64 = 2392.0 / 4096.0 * 32.0;
+
+const HLG_A: f64 = 0.17883277;
+const HLG_B: f64 = 0.28466892;
+const HLG_C: f64 = 0.55991073;
+
+impl TransferFn {
+    /// Decodes a single component. Negative values are mirrored for
+    /// extended range curves.
+    pub fn to_linear(self, v: f64) -> f64 {
+        let sign = v.signum();
+        let a = v.abs();
+        match self {
+            Self::Linear => v,
+            Self::Srgb => {
+                sign * if a <= 0.04045 {
+                    a / 12.92
+                } else {
+                    ((a + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            Self::Gamma22 => sign * a.powf(2.2),
+            Self::Bt709 => {
+                sign * if a < 0.081 {
+                    a / 4.5
+                } else {
+                    ((a + 0.099) / 1.099).powf(1.0 / 0.45)
+                }
+            }
+            Self::Pq => {
+                let p = v.max(0.0).powf(1.0 / PQ_M2);
+                ((p - PQ_C1).max(0.0) / (PQ_C2 - PQ_C3 * p)).powf(1.0 / PQ_M1)
+            }
+            Self::Hlg => {
+                let v = v.max(0.0);
+                if v <= 0.5 {
+                    v * v / 3.0
+                } else {
+                    (((v - HLG_C) / HLG_A).exp() + HLG_B) / 12.0
+                }
+            }
+        }
+    }
+
+    /// Encodes a single component.
+    pub fn from_linear(self, v: f64) -> f64 {
+        let sign = v.signum();
+        let a = v.abs();
+        match self {
+            Self::Linear => v,
+            Self::Srgb => {
+                sign * if a <= 0.0031308 {
+                    a * 12.92
+                } else {
+                    1.055 * a.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            Self::Gamma22 => sign * a.powf(1.0 / 2.2),
+            Self::Bt709 => {
+                sign * if a < 0.018 {
+                    a * 4.5
+                } else {
+                    1.099 * a.powf(0.45) - 0.099
+                }
+            }
+            Self::Pq => {
+                let p = v.max(0.0).powf(PQ_M1);
+                ((PQ_C1 + PQ_C2 * p) / (1.0 + PQ_C3 * p)).powf(PQ_M2)
+            }
+            Self::Hlg => {
+                let v = v.max(0.0);
+                if v <= 1.0 / 12.0 {
+                    (3.0 * v).sqrt()
+                } else {
+                    HLG_A * (12.0 * v - HLG_B).ln() + HLG_C
+                }
+            }
+        }
+    }
+
+    /// ```
+    /// use cidre::gfx::cg::color_math::TransferFn;
+    ///
+    /// let v = TransferFn::nits_to_pq(100.0);
+    /// assert!((v - 0.5081).abs() < 1e-4);
+    /// assert!((TransferFn::pq_to_nits(v) - 100.0).abs() < 1e-9);
+    /// ```
+    pub fn nits_to_pq(nits: f64) -> f64 {
+        Self::Pq.from_linear(nits / PQ_MAX_NITS)
+    }
+
+    pub fn pq_to_nits(v: f64) -> f64 {
+        Self::Pq.to_linear(v) * PQ_MAX_NITS
+    }
+}
+
+/// RGB color space described by primaries and transfer function.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RgbSpace {
+    pub primaries: Primaries,
+    pub transfer: TransferFn,
+    /// Values outside of `[0, 1]` are valid.
+    pub extended: bool,
+}
+
+impl RgbSpace {
+    pub const SRGB: Self = Self::new(Primaries::Bt709, TransferFn::Srgb, false);
+    pub const LINEAR_SRGB: Self = Self::new(Primaries::Bt709, TransferFn::Linear, false);
+    pub const EXTENDED_SRGB: Self = Self::new(Primaries::Bt709, TransferFn::Srgb, true);
+    pub const EXTENDED_LINEAR_SRGB: Self = Self::new(Primaries::Bt709, TransferFn::Linear, true);
+    pub const DISPLAY_P3: Self = Self::new(Primaries::P3, TransferFn::Srgb, false);
+    pub const LINEAR_DISPLAY_P3: Self = Self::new(Primaries::P3, TransferFn::Linear, false);
+    pub const EXTENDED_DISPLAY_P3: Self = Self::new(Primaries::P3, TransferFn::Srgb, true);
+    pub const ITUR_709: Self = Self::new(Primaries::Bt709, TransferFn::Bt709, false);
+    pub const ITUR_2020: Self = Self::new(Primaries::Bt2020, TransferFn::Bt709, false);
+    pub const LINEAR_ITUR_2020: Self = Self::new(Primaries::Bt2020, TransferFn::Linear, false);
+    pub const ITUR_2100_PQ: Self = Self::new(Primaries::Bt2020, TransferFn::Pq, false);
+    pub const ITUR_2100_HLG: Self = Self::new(Primaries::Bt2020, TransferFn::Hlg, false);
+
+    #[inline]
+    pub const fn new(primaries: Primaries, transfer: TransferFn, extended: bool) -> Self {
+        Self {
+            primaries,
+            transfer,
+            extended,
+        }
+    }
+
+    /// The `cg::color_space::names` entry describing the same space.
+    #[cfg(feature = "cg")]
+    pub fn cg_name(&self) -> Option<&'static crate::cf::String> {
+        use crate::cg::color_space::names;
+        use Primaries as P;
+        use TransferFn as T;
+        Some(match (self.primaries, self.transfer, self.extended) {
+            (P::Bt709, T::Srgb, false) => names::srgb(),
+            (P::Bt709, T::Srgb, true) => names::extended_srgb(),
+            (P::Bt709, T::Linear, false) => names::linear_srgb(),
+            (P::Bt709, T::Linear, true) => names::extended_linear_srgb(),
+            (P::Bt709, T::Bt709, false) => names::itur_709(),
+            (P::Bt709, T::Pq, false) => names::itur_709_pq(),
+            (P::P3, T::Srgb, false) => names::display_p3(),
+            (P::P3, T::Srgb, true) => names::extended_display_p3(),
+            (P::P3, T::Linear, false) => names::linear_display_p3(),
+            (P::P3, T::Linear, true) => names::extended_linear_display_p3(),
+            (P::P3, T::Pq, false) => names::display_p3_pq(),
+            (P::P3, T::Hlg, false) => names::display_p3_hlg(),
+            (P::Bt2020, T::Bt709, false) => names::itur_2020(),
+            (P::Bt2020, T::Srgb, false) => names::itur_2020_srgb_gamma(),
+            (P::Bt2020, T::Linear, false) => names::linerar_itur_2020(),
+            (P::Bt2020, T::Linear, true) => names::extended_linear_itur_2020(),
+            (P::Bt2020, T::Pq, false) => names::itur_2100_pq(),
+            (P::Bt2020, T::Hlg, false) => names::itur_2100_hlg(),
+            _ => return None,
+        })
+    }
+
+    /// `cg::Color` of encoded `rgb` in this space.
+    ///
+    /// ```
+    /// # #[cfg(feature = "cg")] {
+    /// use cidre::gfx::cg::color_math::{Rgb, RgbSpace};
+    ///
+    /// let c = RgbSpace::DISPLAY_P3.cg_color(Rgb::new(1.0, 0.5, 0.0), 0.5).unwrap();
+    ///
+    /// assert_eq!(c.alpha(), 0.5);
+    /// # }
+    /// ```
+    #[cfg(feature = "cg")]
+    pub fn cg_color(&self, rgb: Rgb, alpha: cg::Float) -> Option<crate::arc::R<crate::cg::Color>> {
+        let space = crate::cg::ColorSpace::with_name(self.cg_name()?)?;
+        crate::cg::Color::with_space(&space, &[rgb.r, rgb.g, rgb.b, alpha])
+    }
+
+    /// `ns::Color` of encoded `rgb` in this space.
+    #[cfg(all(feature = "app", feature = "cg", target_os = "macos"))]
+    pub fn ns_color(&self, rgb: Rgb, alpha: cg::Float) -> Option<crate::arc::R<crate::ns::Color>> {
+        let cg_color = self.cg_color(rgb, alpha)?;
+        crate::ns::Color::with_cg_color(&cg_color)
+    }
+
+    #[inline]
+    pub fn to_linear(&self, rgb: Rgb) -> Rgb {
+        rgb.map(|v| self.transfer.to_linear(v))
+    }
+
+    #[inline]
+    pub fn from_linear(&self, rgb: Rgb) -> Rgb {
+        rgb.map(|v| self.transfer.from_linear(v))
+    }
+
+    /// Encoded `rgb` to D65 XYZ.
+    pub fn to_xyz(&self, rgb: Rgb) -> Xyz {
+        let m = self.primaries.to_xyz_matrix();
+        Xyz::with_array(mul_vec(&m, self.to_linear(rgb).to_array()))
+    }
+
+    /// D65 XYZ to encoded rgb. Out of gamut values are kept for extended spaces
+    /// and clipped otherwise.
+    pub fn from_xyz(&self, xyz: Xyz) -> Rgb {
+        let m = self.primaries.from_xyz_matrix();
+        let rgb = self.from_linear(Rgb::with_array(mul_vec(&m, xyz.to_array())));
+        if self.extended {
+            rgb
+        } else {
+            rgb.clip()
+        }
+    }
+
+    /// Relative luminance (Y) of encoded `rgb`.
+    pub fn luminance(&self, rgb: Rgb) -> f64 {
+        self.to_xyz(rgb).y
+    }
+
+    pub fn to_lab(&self, rgb: Rgb, white: WhitePoint) -> Lab {
+        let xyz = self.to_xyz(rgb).adapt(WhitePoint::D65, white);
+        Lab::with_xyz(xyz, white)
+    }
+
+    pub fn from_lab(&self, lab: Lab, white: WhitePoint) -> Rgb {
+        self.from_xyz(lab.to_xyz(white).adapt(white, WhitePoint::D65))
+    }
+
+    /// Converts encoded `rgb` to `dst` without any gamut handling.
+    pub fn convert_unclipped(&self, rgb: Rgb, dst: &Self) -> Rgb {
+        if self.primaries == dst.primaries {
+            return dst.from_linear(self.to_linear(rgb));
+        }
+        let m = mul(
+            &dst.primaries.from_xyz_matrix(),
+            &self.primaries.to_xyz_matrix(),
+        );
+        let linear = mul_vec(&m, self.to_linear(rgb).to_array());
+        dst.from_linear(Rgb::with_array(linear))
+    }
+
+    /// ```
+    /// use cidre::gfx::cg::color_math::{GamutMapping, Rgb, RgbSpace};
+    ///
+    /// let red = Rgb::new(1.0, 0.0, 0.0);
+    /// let p3 = RgbSpace::SRGB.convert(red, &RgbSpace::DISPLAY_P3, GamutMapping::Clip);
+    /// assert!((p3.r - 0.9175).abs() < 1e-3);
+    /// assert!((p3.g - 0.2003).abs() < 1e-3);
+    ///
+    /// let back = RgbSpace::DISPLAY_P3.convert(p3, &RgbSpace::SRGB, GamutMapping::Clip);
+    /// assert!((back.r - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn convert(&self, rgb: Rgb, dst: &Self, mapping: GamutMapping) -> Rgb {
+        let res = self.convert_unclipped(rgb, dst);
+        if dst.extended || dst.is_in_gamut(res) {
+            return res;
+        }
+        match mapping {
+            GamutMapping::Clip => res.clip(),
+            GamutMapping::PreserveLightness => {
+                let lab = self.to_lab(rgb, WhitePoint::D50);
+                dst.map_lab_to_gamut(lab)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_in_gamut(&self, rgb: Rgb) -> bool {
+        self.extended || rgb.is_in_unit_range(1e-9)
+    }
+
+    /// Reduces chroma of `lab` (D50) keeping lightness and hue until it fits into `self`.
+    pub fn map_lab_to_gamut(&self, lab: Lab) -> Rgb {
+        let to_rgb = |lab: Lab| {
+            let xyz = lab
+                .to_xyz(WhitePoint::D50)
+                .adapt(WhitePoint::D50, WhitePoint::D65);
+            let linear = mul_vec(&self.primaries.from_xyz_matrix(), xyz.to_array());
+            self.from_linear(Rgb::with_array(linear))
+        };
+        let l = lab.l.clamp(0.0, 100.0);
+        let (chroma, hue) = lab.to_lch();
+        let candidate = to_rgb(Lab::with_lch(l, chroma, hue));
+        if self.is_in_gamut(candidate) {
+            return candidate;
+        }
+        let (mut lo, mut hi) = (0.0, chroma);
+        for _ in 0..32 {
+            let mid = (lo + hi) / 2.0;
+            if self.is_in_gamut(to_rgb(Lab::with_lch(l, mid, hue))) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        to_rgb(Lab::with_lch(l, lo, hue)).clip()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum GamutMapping {
+    /// Clamp each component.
+    #[default]
+    Clip,
+    /// Reduce CIE LCh chroma, keeping lightness and hue.
+    PreserveLightness,
+}
+
+fn mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut res = [[0.0; 3]; 3];
+    for (i, row) in res.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+        }
+    }
+    res
+}
+
+fn mul_vec(m: &Mat3, v: [f64; 3]) -> [f64; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+fn invert(m: &Mat3) -> Mat3 {
+    let [[a, b, c], [d, e, f], [g, h, i]] = *m;
+    let co = [
+        e * i - f * h,
+        -(d * i - f * g),
+        d * h - e * g,
+        -(b * i - c * h),
+        a * i - c * g,
+        -(a * h - b * g),
+        b * f - c * e,
+        -(a * f - c * d),
+        a * e - b * d,
+    ];
+    let det = a * co[0] + b * co[1] + c * co[2];
+    [
+        [co[0] / det, co[3] / det, co[6] / det],
+        [co[1] / det, co[4] / det, co[7] / det],
+        [co[2] / det, co[5] / det, co[8] / det],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::cg::color_math::*;
+
+    fn close(a: f64, b: f64, eps: f64) -> bool {
+        (a - b).abs() < eps
+    }
+
+    #[test]
+    fn xyz() {
+        let white = RgbSpace::SRGB.to_xyz(Rgb::new(1.0, 1.0, 1.0));
+        let d65 = WhitePoint::D65.xyz();
+        assert!(close(white.x, d65.x, 1e-12));
+        assert!(close(white.z, d65.z, 1e-12));
+        assert!(close(
+            RgbSpace::SRGB.luminance(Rgb::new(0.0, 1.0, 0.0)),
+            0.7152,
+            1e-4
+        ));
+
+        let m = Primaries::Bt709.to_xyz_matrix();
+        assert!(close(m[0][0], 0.4124, 1e-4));
+        assert!(close(m[1][1], 0.7152, 1e-4));
+
+        let d50 = white.adapt(WhitePoint::D65, WhitePoint::D50);
+        let (x, y) = d50.chromaticity();
+        assert!(close(x, 0.3457, 1e-4) && close(y, 0.3585, 1e-4));
+    }
+
+    #[test]
+    fn lab() {
+        let lab = RgbSpace::SRGB.to_lab(Rgb::new(1.0, 1.0, 1.0), WhitePoint::D50);
+        assert!(close(lab.l, 100.0, 1e-9) && close(lab.a, 0.0, 1e-9));
+
+        let c = Rgb::new(0.2, 0.4, 0.6);
+        let lab = RgbSpace::SRGB.to_lab(c, WhitePoint::D50);
+        let back = RgbSpace::SRGB.from_lab(lab, WhitePoint::D50);
+        assert!(close(back.r, c.r, 1e-9) && close(back.b, c.b, 1e-9));
+    }
+
+    #[test]
+    fn transfer() {
+        for f in [
+            TransferFn::Linear,
+            TransferFn::Srgb,
+            TransferFn::Gamma22,
+            TransferFn::Bt709,
+            TransferFn::Pq,
+            TransferFn::Hlg,
+        ] {
+            for v in [0.0, 0.01, 0.2, 0.5, 0.9, 1.0] {
+                assert!(close(f.to_linear(f.from_linear(v)), v, 1e-9), "{f:?} {v}");
+            }
+        }
+        assert!(close(TransferFn::Hlg.from_linear(1.0), 1.0, 1e-6));
+        assert!(close(TransferFn::Pq.from_linear(1.0), 1.0, 1e-12));
+        // extended sRGB mirrors negative values
+        assert_eq!(
+            TransferFn::Srgb.to_linear(-0.5),
+            -TransferFn::Srgb.to_linear(0.5)
+        );
+    }
+
+    #[test]
+    fn gamut() {
+        let green = Rgb::new(0.0, 1.0, 0.0);
+        let unclipped = RgbSpace::ITUR_2020.convert_unclipped(green, &RgbSpace::SRGB);
+        assert!(!RgbSpace::SRGB.is_in_gamut(unclipped));
+        assert!(RgbSpace::EXTENDED_SRGB.is_in_gamut(unclipped));
+
+        let clipped = RgbSpace::ITUR_2020.convert(green, &RgbSpace::SRGB, GamutMapping::Clip);
+        assert!(clipped.is_in_unit_range(0.0));
+        let mapped =
+            RgbSpace::ITUR_2020.convert(green, &RgbSpace::SRGB, GamutMapping::PreserveLightness);
+        assert!(mapped.is_in_unit_range(0.0));
+        let l = RgbSpace::ITUR_2020.to_lab(green, WhitePoint::D50).l;
+        assert!(close(
+            RgbSpace::SRGB.to_lab(mapped, WhitePoint::D50).l,
+            l,
+            0.5
+        ));
+    }
+
+    #[test]
+    fn hsb_hsl() {
+        let c = Rgb::new(0.2, 0.4, 0.6);
+        let hsb = c.to_hsb();
+        assert!(close(hsb.hue, 210.0 / 360.0, 1e-9));
+        let back = hsb.to_rgb();
+        assert!(close(back.r, c.r, 1e-9) && close(back.g, c.g, 1e-9));
+
+        let hsl = c.to_hsl();
+        assert!(close(hsl.lightness, 0.4, 1e-9));
+        assert!(close(hsl.saturation, 0.5, 1e-9));
+        let back = hsl.to_rgb();
+        assert!(close(back.b, c.b, 1e-9));
+    }
+}
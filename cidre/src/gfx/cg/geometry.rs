@@ -0,0 +1,78 @@
+// #[cfg(target_os = "watchos")]
+// pub type Float = f32;
+
+// #[cfg(not(target_os = "watchos"))]
+pub type Float = f64;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+#[repr(C)]
+pub struct Point {
+    pub x: Float,
+    pub y: Float,
+}
+
+impl Point {
+    pub fn zero() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn new(x: Float, y: Float) -> Self {
+        Self { x, y }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+#[repr(C)]
+pub struct Size {
+    pub width: Float,
+    pub height: Float,
+}
+
+impl Size {
+    pub fn zero() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn new(width: Float, height: Float) -> Self {
+        Self { width, height }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+#[repr(C)]
+pub struct Rect {
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl Rect {
+    #[inline]
+    pub fn zero() -> Self {
+        Default::default()
+    }
+
+    #[inline]
+    pub fn new(x: Float, y: Float, width: Float, height: Float) -> Self {
+        Self {
+            origin: Point { x, y },
+            size: Size { width, height },
+        }
+    }
+
+    #[inline]
+    pub fn with_size(width: Float, height: Float) -> Self {
+        Self {
+            origin: Point::zero(),
+            size: Size { width, height },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
+#[repr(C)]
+pub struct Vector {
+    pub dx: Float,
+    pub dy: Float,
+}
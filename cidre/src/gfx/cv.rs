@@ -0,0 +1,41 @@
+mod pixel_format;
+pub use pixel_format::PixelFormat;
+
+pub mod pixel_format_info;
+pub use pixel_format_info::ChromaSubsampling;
+pub use pixel_format_info::ColorModel as PixelFormatColorModel;
+pub use pixel_format_info::Compression as PixelFormatCompression;
+pub use pixel_format_info::Info as PixelFormatInfo;
+pub use pixel_format_info::Plane as PixelFormatPlane;
+pub use pixel_format_info::Range as PixelFormatRange;
+
+pub mod pixel_convert;
+pub use pixel_convert::ChromaSiting;
+pub use pixel_convert::Error as PixelConvertError;
+pub use pixel_convert::Filter as PixelConvertFilter;
+pub use pixel_convert::ImageMut as PixelConvertImageMut;
+pub use pixel_convert::ImageRef as PixelConvertImageRef;
+pub use pixel_convert::Matrix as YCbCrMatrix;
+pub use pixel_convert::Opts as PixelConvertOpts;
+pub use pixel_convert::PlaneMut as PixelConvertPlaneMut;
+pub use pixel_convert::PlaneRef as PixelConvertPlaneRef;
+
+pub mod pixel_image;
+pub use pixel_image::Error as PixelImageError;
+pub use pixel_image::ExtendedPixels as PixelBufExtendedPixels;
+pub use pixel_image::ImageView as PixelImageView;
+pub use pixel_image::ImageViewMut as PixelImageViewMut;
+pub use pixel_image::Layout as PixelImageLayout;
+pub use pixel_image::PixelImage;
+pub use pixel_image::PlaneView as PixelImagePlaneView;
+pub use pixel_image::PlaneViewMut as PixelImagePlaneViewMut;
+pub use pixel_image::Sample as PixelSample;
+
+pub mod y4m;
+pub use y4m::Error as Y4mError;
+pub use y4m::Header as Y4mHeader;
+pub use y4m::Interlace as Y4mInterlace;
+pub use y4m::RawReader as RawYuvReader;
+pub use y4m::RawWriter as RawYuvWriter;
+pub use y4m::Reader as Y4mReader;
+pub use y4m::Writer as Y4mWriter;
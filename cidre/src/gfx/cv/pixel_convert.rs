@@ -0,0 +1,1020 @@
+//! CPU conversion between YCbCr and RGB pixel formats.
+//!
+//! Works on raw plane memory (for example of a locked `cv::PixelBuf`) and
+//! produces the same output on every platform, which makes it suitable for
+//! golden-image tests. Use `vt::PixelTransferSession` when speed matters more.
+//!
+//! Supported formats:
+//! - bi- and tri-planar YCbCr with 8-bit or 16-bit samples (`420v`, `420f`,
+//!   `x420`, `xf22`, `y420`, `v0a8` ...). 10-bit samples are MSB aligned (p010 style).
+//! - packed 8-bit RGB (`BGRA`, `ARGB`, `RGBA`, `ABGR`, `24RGB`, `24BG`).
+
+use crate::gfx::{cg, cv};
+
+pub const MAX_PLANES: usize = 3;
+
+/// YCbCr to RGB matrix
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Matrix {
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_601_4")]
+    Bt601,
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_709_2")]
+    Bt709,
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_2020")]
+    Bt2020,
+}
+
+impl Matrix {
+    /// Kr and Kb luma coefficients
+    #[inline]
+    pub const fn coefficients(&self) -> (f32, f32) {
+        match self {
+            Self::Bt601 => (0.299, 0.114),
+            Self::Bt709 => (0.2126, 0.0722),
+            Self::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+
+    /// Normalized RGB to Y in \[0, 1\] and Cb, Cr in \[-0.5, 0.5\]
+    pub fn rgb_to_y_cb_cr(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let (kr, kb) = self.coefficients();
+        let kg = 1.0 - kr - kb;
+        let [r, g, b] = rgb;
+        let y = kr * r + kg * g + kb * b;
+        [
+            y,
+            (b - y) / (2.0 * (1.0 - kb)),
+            (r - y) / (2.0 * (1.0 - kr)),
+        ]
+    }
+
+    /// Y in \[0, 1\] and Cb, Cr in \[-0.5, 0.5\] to normalized RGB
+    pub fn y_cb_cr_to_rgb(&self, y_cb_cr: [f32; 3]) -> [f32; 3] {
+        let (kr, kb) = self.coefficients();
+        let kg = 1.0 - kr - kb;
+        let [y, cb, cr] = y_cb_cr;
+        let r = y + 2.0 * (1.0 - kr) * cr;
+        let b = y + 2.0 * (1.0 - kb) * cb;
+        [r, (y - kr * r - kb * b) / kg, b]
+    }
+}
+
+/// Position of chroma samples relative to luma samples
+#[doc(alias = "kCVImageBufferChromaLocationTopFieldKey")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ChromaSiting {
+    /// Co-sited horizontally, centered vertically (MPEG-2, H.264, HEVC default)
+    #[doc(alias = "kCVImageBufferChromaLocation_Left")]
+    Left,
+    /// Centered in both directions (JPEG, MPEG-1)
+    #[doc(alias = "kCVImageBufferChromaLocation_Center")]
+    Center,
+    /// Co-sited in both directions (BT.2020 4:2:0)
+    #[doc(alias = "kCVImageBufferChromaLocation_TopLeft")]
+    TopLeft,
+    #[doc(alias = "kCVImageBufferChromaLocation_Top")]
+    Top,
+    #[doc(alias = "kCVImageBufferChromaLocation_BottomLeft")]
+    BottomLeft,
+    #[doc(alias = "kCVImageBufferChromaLocation_Bottom")]
+    Bottom,
+}
+
+impl ChromaSiting {
+    /// Offset of chroma sample in subsampling intervals (0.0 - co-sited, 0.5 - centered, 1.0 - next)
+    #[inline]
+    pub const fn offsets(&self) -> (f32, f32) {
+        match self {
+            Self::Left => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::TopLeft => (0.0, 0.0),
+            Self::Top => (0.5, 0.0),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::Bottom => (0.5, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Opts {
+    pub matrix: Matrix,
+    pub chroma_siting: ChromaSiting,
+
+    /// Filter used for scaling and chroma resampling
+    pub filter: Filter,
+
+    /// Source rect in pixels, whole source if `None`. Scaled to destination size.
+    pub crop: Option<cg::Rect>,
+}
+
+impl Default for Opts {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix::Bt709,
+            chroma_siting: ChromaSiting::Left,
+            filter: Filter::Bilinear,
+            crop: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    UnsupportedFormat(cv::PixelFormat),
+    /// Plane at index is missing, its rows are shorter than plane width or data is too short
+    InvalidPlane(usize),
+    InvalidCrop,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedFormat(format) => write!(f, "unsupported pixel format {format}"),
+            Self::InvalidPlane(index) => write!(f, "invalid plane {index}"),
+            Self::InvalidCrop => f.write_str("crop rect is empty or out of source bounds"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PlaneRef<'a> {
+    pub data: &'a [u8],
+    pub bytes_per_row: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct PlaneMut<'a> {
+    pub data: &'a mut [u8],
+    pub bytes_per_row: usize,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ImageRef<'a> {
+    pub format: cv::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub planes: [PlaneRef<'a>; MAX_PLANES],
+}
+
+impl<'a> ImageRef<'a> {
+    pub fn new(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: impl IntoIterator<Item = PlaneRef<'a>>,
+    ) -> Self {
+        let mut res = Self {
+            format,
+            width,
+            height,
+            planes: Default::default(),
+        };
+        for (dst, src) in res.planes.iter_mut().zip(planes) {
+            *dst = src;
+        }
+        res
+    }
+}
+
+#[derive(Debug)]
+pub struct ImageMut<'a> {
+    pub format: cv::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub planes: [PlaneMut<'a>; MAX_PLANES],
+}
+
+impl<'a> ImageMut<'a> {
+    pub fn new(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: impl IntoIterator<Item = PlaneMut<'a>>,
+    ) -> Self {
+        let mut res = Self {
+            format,
+            width,
+            height,
+            planes: Default::default(),
+        };
+        for (dst, src) in res.planes.iter_mut().zip(planes) {
+            *dst = src;
+        }
+        res
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Layout {
+    YCbCr {
+        /// 1 or 2 bytes per sample
+        sample_bytes: usize,
+        depth: u32,
+        shift: u32,
+        video_range: bool,
+        subsampling: (usize, usize),
+        /// Cb and Cr in separate planes
+        tri_planar: bool,
+        alpha_plane: Option<usize>,
+    },
+    Rgb {
+        bytes_per_pixel: usize,
+        /// Byte offsets of R, G, B
+        offsets: [usize; 3],
+        alpha: Option<usize>,
+    },
+}
+
+impl Layout {
+    fn with_format(format: cv::PixelFormat) -> Result<Self, Error> {
+        let unsupported = Error::UnsupportedFormat(format);
+        let info = format.info().ok_or(unsupported)?;
+        if info.is_compressed() {
+            return Err(unsupported);
+        }
+        let planes = info.planes;
+        match info.model {
+            cv::PixelFormatColorModel::YCbCr => {
+                if planes.len() < 2 || planes[0].components != "Y" {
+                    return Err(unsupported);
+                }
+                let tri_planar = match planes[1].components {
+                    "CbCr" => false,
+                    "Cb" if planes.get(2).map(|p| p.components) == Some("Cr") => true,
+                    _ => return Err(unsupported),
+                };
+                let sample_bytes = planes[0].bits_per_block / 8;
+                let depth = info.bit_depth as u32;
+                if !(sample_bytes == 1 || sample_bytes == 2) || depth > sample_bytes as u32 * 8 {
+                    return Err(unsupported);
+                }
+                let video_range = match info.range {
+                    cv::PixelFormatRange::Video => true,
+                    cv::PixelFormatRange::Full => false,
+                    cv::PixelFormatRange::Extended => return Err(unsupported),
+                };
+                Ok(Self::YCbCr {
+                    sample_bytes,
+                    depth,
+                    shift: sample_bytes as u32 * 8 - depth,
+                    video_range,
+                    subsampling: (
+                        planes[1].horizontal_subsampling,
+                        planes[1].vertical_subsampling,
+                    ),
+                    tri_planar,
+                    alpha_plane: planes.iter().position(|p| p.components == "A"),
+                })
+            }
+            cv::PixelFormatColorModel::Rgb => {
+                let plane = &planes[0];
+                let comps = plane.components.as_bytes();
+                if planes.len() != 1
+                    || info.bit_depth != 8
+                    || comps.len() * 8 != plane.bits_per_block
+                {
+                    return Err(unsupported);
+                }
+                let pos = |c| comps.iter().position(|&x| x == c);
+                Ok(Self::Rgb {
+                    bytes_per_pixel: comps.len(),
+                    offsets: [
+                        pos(b'R').ok_or(unsupported)?,
+                        pos(b'G').ok_or(unsupported)?,
+                        pos(b'B').ok_or(unsupported)?,
+                    ],
+                    alpha: pos(b'A'),
+                })
+            }
+            _ => Err(unsupported),
+        }
+    }
+
+    /// (width, height, bytes per row) of planes
+    fn plane_dims(&self, width: usize, height: usize) -> [(usize, usize, usize); MAX_PLANES] {
+        let mut res = [(0, 0, 0); MAX_PLANES];
+        match *self {
+            Self::YCbCr {
+                sample_bytes,
+                subsampling: (h, v),
+                tri_planar,
+                alpha_plane,
+                ..
+            } => {
+                let (cw, ch) = (width.div_ceil(h), height.div_ceil(v));
+                res[0] = (width, height, width * sample_bytes);
+                if tri_planar {
+                    res[1] = (cw, ch, cw * sample_bytes);
+                    res[2] = (cw, ch, cw * sample_bytes);
+                } else {
+                    res[1] = (cw, ch, cw * 2 * sample_bytes);
+                }
+                if let Some(a) = alpha_plane {
+                    res[a] = (width, height, width);
+                }
+            }
+            Self::Rgb {
+                bytes_per_pixel, ..
+            } => res[0] = (width, height, width * bytes_per_pixel),
+        }
+        res
+    }
+}
+
+fn check_plane(
+    index: usize,
+    len: usize,
+    bytes_per_row: usize,
+    dims: (usize, usize, usize),
+) -> Result<(), Error> {
+    let (_, rows, row_bytes) = dims;
+    if rows == 0 {
+        return Ok(());
+    }
+    if bytes_per_row < row_bytes || len < (rows - 1) * bytes_per_row + row_bytes {
+        return Err(Error::InvalidPlane(index));
+    }
+    Ok(())
+}
+
+/// Neighbour indices and weight of the second one for sampling at `x`
+#[inline]
+fn taps(x: f32, len: usize, filter: Filter) -> (usize, usize, f32) {
+    let x = x.clamp(0.0, (len - 1) as f32);
+    match filter {
+        Filter::Nearest => {
+            let i = x.round() as usize;
+            (i, i, 0.0)
+        }
+        Filter::Bilinear => {
+            let i = x.floor() as usize;
+            (i, (i + 1).min(len - 1), x - i as f32)
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Sampler<'a> {
+    plane: PlaneRef<'a>,
+    width: usize,
+    height: usize,
+    /// Bytes between horizontally adjacent samples
+    stride: usize,
+    offset: usize,
+    sample_bytes: usize,
+    shift: u32,
+}
+
+impl<'a> Sampler<'a> {
+    #[inline]
+    fn read(&self, x: usize, y: usize) -> f32 {
+        let i = y * self.plane.bytes_per_row + x * self.stride + self.offset;
+        let d = self.plane.data;
+        if self.sample_bytes == 1 {
+            d[i] as f32
+        } else {
+            (u16::from_le_bytes([d[i], d[i + 1]]) >> self.shift) as f32
+        }
+    }
+
+    #[inline]
+    fn sample(&self, x: f32, y: f32, filter: Filter) -> f32 {
+        let (x0, x1, wx) = taps(x, self.width, filter);
+        let (y0, y1, wy) = taps(y, self.height, filter);
+        if wx == 0.0 && wy == 0.0 {
+            return self.read(x0, y0);
+        }
+        let top = self.read(x0, y0) * (1.0 - wx) + self.read(x1, y0) * wx;
+        let bottom = self.read(x0, y1) * (1.0 - wx) + self.read(x1, y1) * wx;
+        top * (1.0 - wy) + bottom * wy
+    }
+}
+
+/// Code value scales for normalized YCbCr values
+#[derive(Copy, Clone)]
+struct Quant {
+    y_off: f32,
+    y_scale: f32,
+    c_off: f32,
+    c_scale: f32,
+    max: f32,
+}
+
+impl Quant {
+    fn new(depth: u32, video_range: bool) -> Self {
+        let max = ((1u32 << depth) - 1) as f32;
+        let c_off = (1u32 << (depth - 1)) as f32;
+        if video_range {
+            let k = (1u32 << (depth - 8)) as f32;
+            Self {
+                y_off: 16.0 * k,
+                y_scale: 219.0 * k,
+                c_off,
+                c_scale: 224.0 * k,
+                max,
+            }
+        } else {
+            Self {
+                y_off: 0.0,
+                y_scale: max,
+                c_off,
+                c_scale: max,
+                max,
+            }
+        }
+    }
+}
+
+/// Reads source pixels at luma coordinates in the source native model
+struct Reader<'a> {
+    y_cb_cr: bool,
+    channels: [Sampler<'a>; 3],
+    alpha: Option<Sampler<'a>>,
+    alpha_max: f32,
+    quant: Quant,
+    chroma_pos: (f32, f32, f32, f32),
+    filter: Filter,
+}
+
+impl<'a> Reader<'a> {
+    fn new(layout: Layout, src: &ImageRef<'a>, opts: &Opts) -> Self {
+        let sampler = |plane: usize, width, height, stride, offset, sample_bytes, shift| Sampler {
+            plane: src.planes[plane],
+            width,
+            height,
+            stride,
+            offset,
+            sample_bytes,
+            shift,
+        };
+        let (w, h) = (src.width, src.height);
+        match layout {
+            Layout::YCbCr {
+                sample_bytes: sb,
+                depth,
+                shift,
+                video_range,
+                subsampling: (hs, vs),
+                tri_planar,
+                alpha_plane,
+            } => {
+                let (cw, ch) = (w.div_ceil(hs), h.div_ceil(vs));
+                let (cb, cr) = if tri_planar {
+                    (
+                        sampler(1, cw, ch, sb, 0, sb, shift),
+                        sampler(2, cw, ch, sb, 0, sb, shift),
+                    )
+                } else {
+                    (
+                        sampler(1, cw, ch, 2 * sb, 0, sb, shift),
+                        sampler(1, cw, ch, 2 * sb, sb, sb, shift),
+                    )
+                };
+                let (fx, fy) = opts.chroma_siting.offsets();
+                Self {
+                    y_cb_cr: true,
+                    channels: [sampler(0, w, h, sb, 0, sb, shift), cb, cr],
+                    alpha: alpha_plane.map(|a| sampler(a, w, h, 1, 0, 1, 0)),
+                    alpha_max: 255.0,
+                    quant: Quant::new(depth, video_range),
+                    chroma_pos: (
+                        fx * (hs - 1) as f32,
+                        hs as f32,
+                        fy * (vs - 1) as f32,
+                        vs as f32,
+                    ),
+                    filter: opts.filter,
+                }
+            }
+            Layout::Rgb {
+                bytes_per_pixel: bpp,
+                offsets: [r, g, b],
+                alpha,
+            } => Self {
+                y_cb_cr: false,
+                channels: [
+                    sampler(0, w, h, bpp, r, 1, 0),
+                    sampler(0, w, h, bpp, g, 1, 0),
+                    sampler(0, w, h, bpp, b, 1, 0),
+                ],
+                alpha: alpha.map(|a| sampler(0, w, h, bpp, a, 1, 0)),
+                alpha_max: 255.0,
+                quant: Quant::new(8, false),
+                chroma_pos: (0.0, 1.0, 0.0, 1.0),
+                filter: opts.filter,
+            },
+        }
+    }
+
+    /// Normalized luma (or RGB for RGB sources)
+    #[inline]
+    fn luma(&self, x: f32, y: f32) -> f32 {
+        let q = &self.quant;
+        (self.channels[0].sample(x, y, self.filter) - q.y_off) / q.y_scale
+    }
+
+    /// Normalized Y, Cb, Cr or R, G, B
+    #[inline]
+    fn color(&self, x: f32, y: f32) -> [f32; 3] {
+        let q = &self.quant;
+        if self.y_cb_cr {
+            let (ox, sx, oy, sy) = self.chroma_pos;
+            let (cx, cy) = ((x - ox) / sx, (y - oy) / sy);
+            [
+                self.luma(x, y),
+                (self.channels[1].sample(cx, cy, self.filter) - q.c_off) / q.c_scale,
+                (self.channels[2].sample(cx, cy, self.filter) - q.c_off) / q.c_scale,
+            ]
+        } else {
+            self.channels.map(|c| c.sample(x, y, self.filter) / q.max)
+        }
+    }
+
+    #[inline]
+    fn alpha(&self, x: f32, y: f32) -> f32 {
+        match &self.alpha {
+            Some(a) => a.sample(x, y, self.filter) / self.alpha_max,
+            None => 1.0,
+        }
+    }
+}
+
+#[inline]
+fn quantize(v: f32, max: f32) -> u32 {
+    v.round().clamp(0.0, max) as u32
+}
+
+#[inline]
+fn write(data: &mut [u8], i: usize, sample_bytes: usize, shift: u32, v: u32) {
+    if sample_bytes == 1 {
+        data[i] = v as u8;
+    } else {
+        data[i..i + 2].copy_from_slice(&((v << shift) as u16).to_le_bytes());
+    }
+}
+
+/// Converts `src` image to `dst` format and size.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let bgra = [255u8, 0, 0, 255].repeat(4); // 2x2 blue
+/// let src = cv::PixelConvertImageRef::new(
+///     cv::PixelFormat::_32_BGRA,
+///     2,
+///     2,
+///     [cv::PixelConvertPlaneRef { data: &bgra, bytes_per_row: 8 }],
+/// );
+///
+/// let mut y = [0u8; 4];
+/// let mut cb_cr = [0u8; 2];
+/// let mut dst = cv::PixelConvertImageMut::new(
+///     cv::PixelFormat::_420V,
+///     2,
+///     2,
+///     [
+///         cv::PixelConvertPlaneMut { data: &mut y, bytes_per_row: 2 },
+///         cv::PixelConvertPlaneMut { data: &mut cb_cr, bytes_per_row: 2 },
+///     ],
+/// );
+/// cv::pixel_convert::convert(&src, &mut dst, &Default::default()).unwrap();
+/// assert_eq!(y, [32; 4]);
+/// assert_eq!(cb_cr, [240, 118]);
+/// ```
+pub fn convert(src: &ImageRef, dst: &mut ImageMut, opts: &Opts) -> Result<(), Error> {
+    let src_layout = Layout::with_format(src.format)?;
+    let dst_layout = Layout::with_format(dst.format)?;
+
+    for (i, dims) in src_layout
+        .plane_dims(src.width, src.height)
+        .iter()
+        .enumerate()
+    {
+        check_plane(
+            i,
+            src.planes[i].data.len(),
+            src.planes[i].bytes_per_row,
+            *dims,
+        )?;
+    }
+    let dst_dims = dst_layout.plane_dims(dst.width, dst.height);
+    for (i, dims) in dst_dims.iter().enumerate() {
+        check_plane(
+            i,
+            dst.planes[i].data.len(),
+            dst.planes[i].bytes_per_row,
+            *dims,
+        )?;
+    }
+
+    let full = cg::Rect::new(0.0, 0.0, src.width as _, src.height as _);
+    let crop = opts.crop.unwrap_or(full);
+    if crop.size.width <= 0.0
+        || crop.size.height <= 0.0
+        || crop.origin.x < 0.0
+        || crop.origin.y < 0.0
+        || crop.origin.x + crop.size.width > full.size.width
+        || crop.origin.y + crop.size.height > full.size.height
+    {
+        return Err(Error::InvalidCrop);
+    }
+    if dst.width == 0 || dst.height == 0 {
+        return Ok(());
+    }
+
+    let sx = (crop.size.width / dst.width as f64) as f32;
+    let sy = (crop.size.height / dst.height as f64) as f32;
+    let (ox, oy) = (crop.origin.x as f32, crop.origin.y as f32);
+    // destination luma position to source luma position
+    let map_x = |x: f32| ox + (x + 0.5) * sx - 0.5;
+    let map_y = |y: f32| oy + (y + 0.5) * sy - 0.5;
+
+    let reader = Reader::new(src_layout, src, opts);
+    let matrix = opts.matrix;
+    let to_y_cb_cr = |c: [f32; 3]| {
+        if reader.y_cb_cr {
+            c
+        } else {
+            matrix.rgb_to_y_cb_cr(c)
+        }
+    };
+
+    match dst_layout {
+        Layout::Rgb {
+            bytes_per_pixel,
+            offsets,
+            alpha,
+        } => {
+            let plane = &mut dst.planes[0];
+            for dy in 0..dst.height {
+                let y = map_y(dy as f32);
+                for dx in 0..dst.width {
+                    let x = map_x(dx as f32);
+                    let mut rgb = reader.color(x, y);
+                    if reader.y_cb_cr {
+                        rgb = matrix.y_cb_cr_to_rgb(rgb);
+                    }
+                    let i = dy * plane.bytes_per_row + dx * bytes_per_pixel;
+                    for (c, off) in rgb.iter().zip(offsets) {
+                        plane.data[i + off] = quantize(c * 255.0, 255.0) as u8;
+                    }
+                    if let Some(a) = alpha {
+                        plane.data[i + a] = quantize(reader.alpha(x, y) * 255.0, 255.0) as u8;
+                    }
+                }
+            }
+        }
+        Layout::YCbCr {
+            sample_bytes: sb,
+            depth,
+            shift,
+            video_range,
+            subsampling: (hs, vs),
+            tri_planar,
+            alpha_plane,
+        } => {
+            let q = Quant::new(depth, video_range);
+            let [luma, chroma @ ..] = &mut dst.planes;
+
+            for dy in 0..dst.height {
+                let y = map_y(dy as f32);
+                for dx in 0..dst.width {
+                    let x = map_x(dx as f32);
+                    let v = if reader.y_cb_cr {
+                        reader.luma(x, y)
+                    } else {
+                        to_y_cb_cr(reader.color(x, y))[0]
+                    };
+                    let i = dy * luma.bytes_per_row + dx * sb;
+                    write(
+                        luma.data,
+                        i,
+                        sb,
+                        shift,
+                        quantize(v * q.y_scale + q.y_off, q.max),
+                    );
+                }
+            }
+
+            let (fx, fy) = opts.chroma_siting.offsets();
+            let (fx, fy) = (fx * (hs - 1) as f32, fy * (vs - 1) as f32);
+            let (cw, ch, _) = dst_dims[1];
+            for cy in 0..ch {
+                let y = map_y(cy as f32 * vs as f32 + fy);
+                for cx in 0..cw {
+                    let x = map_x(cx as f32 * hs as f32 + fx);
+                    let [_, cb, cr] = to_y_cb_cr(reader.color(x, y));
+                    let cb = quantize(cb * q.c_scale + q.c_off, q.max);
+                    let cr = quantize(cr * q.c_scale + q.c_off, q.max);
+                    if tri_planar {
+                        let [cb_plane, cr_plane] = &mut *chroma;
+                        write(
+                            cb_plane.data,
+                            cy * cb_plane.bytes_per_row + cx * sb,
+                            sb,
+                            shift,
+                            cb,
+                        );
+                        write(
+                            cr_plane.data,
+                            cy * cr_plane.bytes_per_row + cx * sb,
+                            sb,
+                            shift,
+                            cr,
+                        );
+                    } else {
+                        let plane = &mut chroma[0];
+                        let i = cy * plane.bytes_per_row + cx * 2 * sb;
+                        write(plane.data, i, sb, shift, cb);
+                        write(plane.data, i + sb, sb, shift, cr);
+                    }
+                }
+            }
+
+            if let Some(a) = alpha_plane {
+                let plane = &mut dst.planes[a];
+                for dy in 0..dst.height {
+                    let y = map_y(dy as f32);
+                    for dx in 0..dst.width {
+                        let x = map_x(dx as f32);
+                        plane.data[dy * plane.bytes_per_row + dx] =
+                            quantize(reader.alpha(x, y) * 255.0, 255.0) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::{cg, cv};
+
+    use super::{convert, ChromaSiting, Error, Filter, ImageMut, ImageRef, Matrix, Opts};
+    use super::{PlaneMut, PlaneRef};
+
+    fn to_nv12(
+        format: cv::PixelFormat,
+        bgra: &[u8],
+        w: usize,
+        h: usize,
+        opts: &Opts,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let sb = if format == cv::PixelFormat::_420V || format == cv::PixelFormat::_420F {
+            1
+        } else {
+            2
+        };
+        let mut y = vec![0u8; w * h * sb];
+        let mut c = vec![0u8; w.div_ceil(2) * 2 * h.div_ceil(2) * sb];
+        let src = ImageRef::new(
+            cv::PixelFormat::_32_BGRA,
+            w,
+            h,
+            [PlaneRef {
+                data: bgra,
+                bytes_per_row: w * 4,
+            }],
+        );
+        let mut dst = ImageMut::new(
+            format,
+            w,
+            h,
+            [
+                PlaneMut {
+                    data: &mut y,
+                    bytes_per_row: w * sb,
+                },
+                PlaneMut {
+                    data: &mut c,
+                    bytes_per_row: w.div_ceil(2) * 2 * sb,
+                },
+            ],
+        );
+        convert(&src, &mut dst, opts).unwrap();
+        (y, c)
+    }
+
+    #[test]
+    fn matrix() {
+        for m in [Matrix::Bt601, Matrix::Bt709, Matrix::Bt2020] {
+            let rgb = [0.25, 0.5, 0.75];
+            let back = m.y_cb_cr_to_rgb(m.rgb_to_y_cb_cr(rgb));
+            for (a, b) in rgb.iter().zip(back) {
+                assert!((a - b).abs() < 1e-6);
+            }
+        }
+        let [y, cb, cr] = Matrix::Bt601.rgb_to_y_cb_cr([1.0, 0.0, 0.0]);
+        assert_eq!((y * 219.0 + 16.0).round(), 81.0);
+        assert_eq!((cb * 224.0 + 128.0).round(), 90.0);
+        assert_eq!((cr * 224.0 + 128.0).round(), 240.0);
+    }
+
+    #[test]
+    fn ranges() {
+        let white = [255u8; 16];
+        let opts = Opts::default();
+        let (y, c) = to_nv12(cv::PixelFormat::_420V, &white, 2, 2, &opts);
+        assert_eq!(y, [235; 4]);
+        assert_eq!(c, [128; 2]);
+
+        let (y, c) = to_nv12(cv::PixelFormat::_420F, &white, 2, 2, &opts);
+        assert_eq!(y, [255; 4]);
+        assert_eq!(c, [128; 2]);
+
+        let (y, c) = to_nv12(
+            cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            &white,
+            2,
+            2,
+            &opts,
+        );
+        assert_eq!(u16::from_le_bytes([y[0], y[1]]), 940 << 6);
+        assert_eq!(u16::from_le_bytes([c[0], c[1]]), 512 << 6);
+    }
+
+    #[test]
+    fn round_trip() {
+        let (w, h) = (6, 4);
+        let mut bgra = Vec::new();
+        for i in 0..w * h {
+            let v = (i * 10) as u8;
+            bgra.extend_from_slice(&[v, v, v, 255]);
+        }
+        let opts = Opts {
+            matrix: Matrix::Bt2020,
+            chroma_siting: ChromaSiting::TopLeft,
+            ..Default::default()
+        };
+        let (y, c) = to_nv12(cv::PixelFormat::_420F, &bgra, w, h, &opts);
+
+        let src = ImageRef::new(
+            cv::PixelFormat::_420F,
+            w,
+            h,
+            [
+                PlaneRef {
+                    data: &y,
+                    bytes_per_row: w,
+                },
+                PlaneRef {
+                    data: &c,
+                    bytes_per_row: w,
+                },
+            ],
+        );
+        let mut argb = vec![0u8; w * h * 4];
+        let mut dst = ImageMut::new(
+            cv::PixelFormat::_32_ARGB,
+            w,
+            h,
+            [PlaneMut {
+                data: &mut argb,
+                bytes_per_row: w * 4,
+            }],
+        );
+        convert(&src, &mut dst, &opts).unwrap();
+        for (i, px) in argb.chunks(4).enumerate() {
+            let v = (i * 10) as u8;
+            assert_eq!(px, [255, v, v, v]);
+        }
+    }
+
+    #[test]
+    fn scale_crop() {
+        // 4x2, left half black, right half white
+        let mut bgra = Vec::new();
+        for _ in 0..2 {
+            bgra.extend_from_slice(&[[0u8; 4], [0; 4], [255; 4], [255; 4]].concat());
+        }
+        let src = ImageRef::new(
+            cv::PixelFormat::_32_BGRA,
+            4,
+            2,
+            [PlaneRef {
+                data: &bgra,
+                bytes_per_row: 16,
+            }],
+        );
+
+        let mut out = [0u8; 8];
+        let mut dst = ImageMut::new(
+            cv::PixelFormat::_32_RGBA,
+            2,
+            1,
+            [PlaneMut {
+                data: &mut out,
+                bytes_per_row: 8,
+            }],
+        );
+        convert(&src, &mut dst, &Default::default()).unwrap();
+        assert_eq!(out, [0, 0, 0, 0, 255, 255, 255, 255]);
+
+        let rgb = |crop: cg::Rect, filter: Filter| {
+            let mut out = [0u8; 3];
+            let mut dst = ImageMut::new(
+                cv::PixelFormat::_24_RGB,
+                1,
+                1,
+                [PlaneMut {
+                    data: &mut out,
+                    bytes_per_row: 3,
+                }],
+            );
+            let opts = Opts {
+                crop: Some(crop),
+                filter,
+                ..Default::default()
+            };
+            convert(&src, &mut dst, &opts).map(|_| out)
+        };
+        let crop = cg::Rect::new(1.0, 0.0, 2.0, 2.0);
+        assert_eq!(rgb(crop, Filter::Bilinear), Ok([128; 3]));
+        let crop = cg::Rect::new(1.0, 0.0, 1.0, 2.0);
+        assert_eq!(rgb(crop, Filter::Nearest), Ok([0; 3]));
+        let crop = cg::Rect::new(3.0, 0.0, 2.0, 2.0);
+        assert_eq!(rgb(crop, Filter::Bilinear), Err(Error::InvalidCrop));
+    }
+
+    #[test]
+    fn tri_planar() {
+        let bgra = [0u8, 0, 255, 255].repeat(4);
+        let src = ImageRef::new(
+            cv::PixelFormat::_32_BGRA,
+            2,
+            2,
+            [PlaneRef {
+                data: &bgra,
+                bytes_per_row: 8,
+            }],
+        );
+        let (mut y, mut cb, mut cr) = ([0u8; 4], [0u8; 1], [0u8; 1]);
+        let mut dst = ImageMut::new(
+            cv::PixelFormat::_420_YP_CB_CR_8_PLANAR,
+            2,
+            2,
+            [
+                PlaneMut {
+                    data: &mut y,
+                    bytes_per_row: 2,
+                },
+                PlaneMut {
+                    data: &mut cb,
+                    bytes_per_row: 1,
+                },
+                PlaneMut {
+                    data: &mut cr,
+                    bytes_per_row: 1,
+                },
+            ],
+        );
+        let opts = Opts {
+            matrix: Matrix::Bt601,
+            ..Default::default()
+        };
+        convert(&src, &mut dst, &opts).unwrap();
+        assert_eq!((y, cb, cr), ([81; 4], [90], [240]));
+    }
+
+    #[test]
+    fn errors() {
+        let data = [0u8; 4];
+        let src = ImageRef::new(
+            cv::PixelFormat::_32_BGRA,
+            2,
+            1,
+            [PlaneRef {
+                data: &data,
+                bytes_per_row: 8,
+            }],
+        );
+        let mut out = [0u8; 8];
+        let mut dst = ImageMut::new(
+            cv::PixelFormat::_32_BGRA,
+            2,
+            1,
+            [PlaneMut {
+                data: &mut out,
+                bytes_per_row: 8,
+            }],
+        );
+        let opts = Opts::default();
+        assert_eq!(convert(&src, &mut dst, &opts), Err(Error::InvalidPlane(0)));
+
+        dst.format = cv::PixelFormat::LOSSY_32_BGRA;
+        assert_eq!(
+            convert(&src, &mut dst, &opts),
+            Err(Error::UnsupportedFormat(cv::PixelFormat::LOSSY_32_BGRA))
+        );
+    }
+}
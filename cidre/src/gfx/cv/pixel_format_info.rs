@@ -0,0 +1,971 @@
+//! Static layout descriptors for [`cv::PixelFormat`].
+//!
+//! Unlike `cv::pixel_format_description`, which asks CoreVideo at runtime,
+//! this table is plain data. It is used to compute plane geometry and buffer
+//! sizes without allocating a `cv::PixelBuf`.
+
+use crate::gfx::cv;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ColorModel {
+    Rgb,
+    YCbCr,
+    Gray,
+    Indexed,
+    /// Generic one or two component formats (`L008`, `2C0f` ...)
+    Components,
+    Depth,
+    Disparity,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Range {
+    Full,
+    /// luma=\[16,235\] chroma=\[16,240\] for 8-bit, scaled for higher bit depths
+    Video,
+    /// Wide-gamut (XR) encoding, 10-bit values in 384-895 map to 0.0-1.0
+    Extended,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ChromaSubsampling {
+    _444,
+    _422,
+    _420,
+}
+
+impl ChromaSubsampling {
+    /// Horizontal and vertical subsampling factors
+    #[inline]
+    pub const fn factors(&self) -> (usize, usize) {
+        match self {
+            Self::_444 => (1, 1),
+            Self::_422 => (2, 1),
+            Self::_420 => (2, 2),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Compression {
+    None,
+    Lossless,
+    Lossy,
+}
+
+/// Memory layout of a single plane.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Plane {
+    /// Component order in memory, e.g. `"BGRA"`, `"CbYCrY"` or `"CbCr"`
+    pub components: &'static str,
+
+    /// Bits occupied by one block of `block_width` x `block_height` pixels
+    pub bits_per_block: usize,
+    pub block_width: usize,
+    pub block_height: usize,
+
+    /// Plane subsampling relative to the image size
+    pub horizontal_subsampling: usize,
+    pub vertical_subsampling: usize,
+}
+
+impl Plane {
+    const fn new(components: &'static str, bits_per_block: usize) -> Self {
+        Self {
+            components,
+            bits_per_block,
+            block_width: 1,
+            block_height: 1,
+            horizontal_subsampling: 1,
+            vertical_subsampling: 1,
+        }
+    }
+
+    const fn block(mut self, width: usize, height: usize) -> Self {
+        self.block_width = width;
+        self.block_height = height;
+        self
+    }
+
+    const fn subsampled(mut self, horizontal: usize, vertical: usize) -> Self {
+        self.horizontal_subsampling = horizontal;
+        self.vertical_subsampling = vertical;
+        self
+    }
+
+    /// Bits per pixel if a block splits into whole-bit pixels (`None` for `v210`)
+    #[inline]
+    pub const fn bits_per_pixel(&self) -> Option<usize> {
+        let pixels = self.block_width * self.block_height;
+        let bits = self.bits_per_block / pixels;
+        if bits * pixels == self.bits_per_block {
+            Some(bits)
+        } else {
+            None
+        }
+    }
+
+    /// Width of the plane in pixels for an image of `width`
+    #[inline]
+    pub const fn width(&self, width: usize) -> usize {
+        width.div_ceil(self.horizontal_subsampling)
+    }
+
+    /// Height of the plane in pixels for an image of `height`
+    #[inline]
+    pub const fn height(&self, height: usize) -> usize {
+        height.div_ceil(self.vertical_subsampling)
+    }
+
+    /// Bytes per row for an image of `width`, rounded up to `alignment` (0 or 1 means no alignment)
+    pub const fn bytes_per_row(&self, width: usize, alignment: usize) -> usize {
+        let blocks = self.width(width).div_ceil(self.block_width);
+        let bytes = (blocks * self.bits_per_block).div_ceil(8);
+        if alignment > 1 {
+            bytes.next_multiple_of(alignment)
+        } else {
+            bytes
+        }
+    }
+
+    /// Number of block rows for an image of `height`
+    #[inline]
+    pub const fn rows(&self, height: usize) -> usize {
+        self.height(height).div_ceil(self.block_height)
+    }
+}
+
+/// Static description of a [`cv::PixelFormat`].
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let info = cv::PixelFormat::_420V.info().unwrap();
+/// assert_eq!(info.name, "420YpCbCr8BiPlanarVideoRange");
+/// assert_eq!(info.plane_count(), 2);
+/// assert_eq!(info.bytes_per_row(1, 1920, 64), Some(1920));
+/// assert_eq!(info.buffer_size(1920, 1080, 64), Some(1920 * 1080 * 3 / 2));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Info {
+    pub format: cv::PixelFormat,
+
+    /// Name without `kCVPixelFormatType_` prefix
+    pub name: &'static str,
+
+    pub model: ColorModel,
+    pub range: Range,
+
+    /// Significant bits per component (the widest one for packed 16-bit RGB formats)
+    pub bit_depth: u8,
+    pub has_alpha: bool,
+    pub is_float: bool,
+
+    /// `None` for non YCbCr formats
+    pub chroma_subsampling: Option<ChromaSubsampling>,
+    pub compression: Compression,
+    pub planes: &'static [Plane],
+}
+
+impl Info {
+    const fn new(
+        format: cv::PixelFormat,
+        name: &'static str,
+        model: ColorModel,
+        bit_depth: u8,
+        planes: &'static [Plane],
+    ) -> Self {
+        Self {
+            format,
+            name,
+            model,
+            range: Range::Full,
+            bit_depth,
+            has_alpha: false,
+            is_float: false,
+            chroma_subsampling: None,
+            compression: Compression::None,
+            planes,
+        }
+    }
+
+    const fn range(mut self, range: Range) -> Self {
+        self.range = range;
+        self
+    }
+
+    const fn alpha(mut self) -> Self {
+        self.has_alpha = true;
+        self
+    }
+
+    const fn float(mut self) -> Self {
+        self.is_float = true;
+        self
+    }
+
+    const fn chroma(mut self, chroma: ChromaSubsampling) -> Self {
+        self.chroma_subsampling = Some(chroma);
+        self
+    }
+
+    const fn compressed(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// All known pixel formats
+    #[inline]
+    pub fn all() -> &'static [Info] {
+        TABLE
+    }
+
+    pub fn with_format(format: cv::PixelFormat) -> Option<&'static Info> {
+        TABLE.iter().find(|i| i.format == format)
+    }
+
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.planes.len()
+    }
+
+    #[inline]
+    pub fn is_planar(&self) -> bool {
+        self.planes.len() > 1
+    }
+
+    /// Single plane with interleaved components
+    #[inline]
+    pub fn is_packed(&self) -> bool {
+        self.planes.len() == 1
+    }
+
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        self.compression != Compression::None
+    }
+
+    #[inline]
+    pub fn is_y_cb_cr(&self) -> bool {
+        self.model == ColorModel::YCbCr
+    }
+
+    #[inline]
+    pub fn plane(&self, plane_index: usize) -> Option<&'static Plane> {
+        self.planes.get(plane_index)
+    }
+
+    #[inline]
+    pub fn plane_width(&self, plane_index: usize, width: usize) -> Option<usize> {
+        Some(self.plane(plane_index)?.width(width))
+    }
+
+    #[inline]
+    pub fn plane_height(&self, plane_index: usize, height: usize) -> Option<usize> {
+        Some(self.plane(plane_index)?.height(height))
+    }
+
+    /// Bytes per row of a plane rounded up to `alignment`.
+    ///
+    /// Returns `None` for compressed formats, their layout is device specific.
+    pub fn bytes_per_row(
+        &self,
+        plane_index: usize,
+        width: usize,
+        alignment: usize,
+    ) -> Option<usize> {
+        if self.is_compressed() {
+            return None;
+        }
+        Some(self.plane(plane_index)?.bytes_per_row(width, alignment))
+    }
+
+    pub fn plane_size(
+        &self,
+        plane_index: usize,
+        width: usize,
+        height: usize,
+        alignment: usize,
+    ) -> Option<usize> {
+        let bpr = self.bytes_per_row(plane_index, width, alignment)?;
+        Some(bpr * self.planes[plane_index].rows(height))
+    }
+
+    /// Sum of all plane sizes, without padding between planes
+    pub fn buffer_size(&self, width: usize, height: usize, alignment: usize) -> Option<usize> {
+        let mut size = 0;
+        for i in 0..self.planes.len() {
+            size += self.plane_size(i, width, height, alignment)?;
+        }
+        Some(size)
+    }
+}
+
+use ChromaSubsampling as C;
+use ColorModel as M;
+use Compression::{Lossless, Lossy};
+use Range::{Extended, Video};
+
+type F = cv::PixelFormat;
+
+const fn p(components: &'static str, bits_per_block: usize) -> Plane {
+    Plane::new(components, bits_per_block)
+}
+
+const fn i(
+    format: cv::PixelFormat,
+    name: &'static str,
+    model: ColorModel,
+    bit_depth: u8,
+    planes: &'static [Plane],
+) -> Info {
+    Info::new(format, name, model, bit_depth, planes)
+}
+
+const Y8_CB_CR8_420: &[Plane] = &[p("Y", 8), p("CbCr", 16).subsampled(2, 2)];
+const Y8_CB_CR8_422: &[Plane] = &[p("Y", 8), p("CbCr", 16).subsampled(2, 1)];
+const Y8_CB_CR8_444: &[Plane] = &[p("Y", 8), p("CbCr", 16)];
+const Y10_CB_CR10_420: &[Plane] = &[p("Y", 16), p("CbCr", 32).subsampled(2, 2)];
+const Y10_CB_CR10_422: &[Plane] = &[p("Y", 16), p("CbCr", 32).subsampled(2, 1)];
+const Y10_CB_CR10_444: &[Plane] = &[p("Y", 16), p("CbCr", 32)];
+const Y_CB_CR8_420_PLANAR: &[Plane] = &[
+    p("Y", 8),
+    p("Cb", 8).subsampled(2, 2),
+    p("Cr", 8).subsampled(2, 2),
+];
+const PACKED_Y10_CB_CR10_420: &[Plane] = &[p("Y", 10), p("CbCr", 20).subsampled(2, 2)];
+const PACKED_Y10_CB_CR10_422: &[Plane] = &[p("Y", 10), p("CbCr", 20).subsampled(2, 1)];
+
+static TABLE: &[Info] = &[
+    i(F::_1_MONOCHROME, "1Monochrome", M::Gray, 1, &[p("W", 1)]),
+    i(F::_2_INDEXED, "2Indexed", M::Indexed, 2, &[p("I", 2)]),
+    i(F::_4_INDEXED, "4Indexed", M::Indexed, 4, &[p("I", 4)]),
+    i(F::_8_INDEXED, "8Indexed", M::Indexed, 8, &[p("I", 8)]),
+    i(
+        F::_1_INDEXED_GREY_WHITE_IS_ZERO,
+        "1IndexedGray_WhiteIsZero",
+        M::Gray,
+        1,
+        &[p("W", 1)],
+    ),
+    i(
+        F::_2_INDEXED_GREY_WHITE_IS_ZERO,
+        "2IndexedGray_WhiteIsZero",
+        M::Gray,
+        2,
+        &[p("W", 2)],
+    ),
+    i(
+        F::_4_INDEXED_GREY_WHITE_IS_ZERO,
+        "4IndexedGray_WhiteIsZero",
+        M::Gray,
+        4,
+        &[p("W", 4)],
+    ),
+    i(
+        F::_8_INDEXED_GREY_WHITE_IS_ZERO,
+        "8IndexedGray_WhiteIsZero",
+        M::Gray,
+        8,
+        &[p("W", 8)],
+    ),
+    i(F::_16_BE_555, "16BE555", M::Rgb, 5, &[p("XRGB", 16)]),
+    i(F::_16_LE_555, "16LE555", M::Rgb, 5, &[p("XRGB", 16)]),
+    i(F::_16_LE_5551, "16LE5551", M::Rgb, 5, &[p("RGBA", 16)]).alpha(),
+    i(F::_16_BE_565, "16BE565", M::Rgb, 6, &[p("RGB", 16)]),
+    i(F::_16_LE_565, "16LE565", M::Rgb, 6, &[p("RGB", 16)]),
+    i(F::_24_RGB, "24RGB", M::Rgb, 8, &[p("RGB", 24)]),
+    i(F::_24_BGR, "24BGR", M::Rgb, 8, &[p("BGR", 24)]),
+    i(F::_32_ARGB, "32ARGB", M::Rgb, 8, &[p("ARGB", 32)]).alpha(),
+    i(F::_32_BGRA, "32BGRA", M::Rgb, 8, &[p("BGRA", 32)]).alpha(),
+    i(F::_32_ABGR, "32ABGR", M::Rgb, 8, &[p("ABGR", 32)]).alpha(),
+    i(F::_32_RGBA, "32RGBA", M::Rgb, 8, &[p("RGBA", 32)]).alpha(),
+    i(F::_16_GRAY, "16Gray", M::Gray, 16, &[p("W", 16)]),
+    i(
+        F::_30_RGB_LE_PACKED_WIDE_GAMUT,
+        "30RGBLEPackedWideGamut",
+        M::Rgb,
+        10,
+        &[p("BGRX", 32)],
+    )
+    .range(Extended),
+    i(
+        F::_40_ARGB_LE_WIDE_GAMUT,
+        "40ARGBLEWideGamut",
+        M::Rgb,
+        10,
+        &[p("BGRA", 64)],
+    )
+    .range(Extended)
+    .alpha(),
+    i(
+        F::_40_ARGB_LE_WIDE_GAMUT_PREMULTIPLIED,
+        "40ARGBLEWideGamutPremultiplied",
+        M::Rgb,
+        10,
+        &[p("BGRA", 64)],
+    )
+    .range(Extended)
+    .alpha(),
+    i(F::_64_ARGB, "64ARGB", M::Rgb, 16, &[p("ARGB", 64)]).alpha(),
+    i(F::_64_RGBALE, "64RGBALE", M::Rgb, 16, &[p("RGBA", 64)]).alpha(),
+    i(F::_30_RGB, "30RGB", M::Rgb, 10, &[p("RGBX", 32)]),
+    i(F::_30_RGB_R210, "30RGB_r210", M::Rgb, 10, &[p("XRGB", 32)]),
+    i(
+        F::_422_YP_CB_CR_8,
+        "422YpCbCr8",
+        M::YCbCr,
+        8,
+        &[p("CbYCrY", 32).block(2, 1)],
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_4444_YP_CB_CR_A_8,
+        "4444YpCbCrA8",
+        M::YCbCr,
+        8,
+        &[p("CbYCrA", 32)],
+    )
+    .range(Video)
+    .chroma(C::_444)
+    .alpha(),
+    i(
+        F::_4444_YP_CB_CR_A_8_R,
+        "4444YpCbCrA8R",
+        M::YCbCr,
+        8,
+        &[p("AYCbCr", 32)],
+    )
+    .chroma(C::_444)
+    .alpha(),
+    i(
+        F::_4444_A_YP_CB_CR_8,
+        "4444AYpCbCr8",
+        M::YCbCr,
+        8,
+        &[p("AYCbCr", 32)],
+    )
+    .range(Video)
+    .chroma(C::_444)
+    .alpha(),
+    i(
+        F::_4444_A_YP_CB_CR_16,
+        "4444AYpCbCr16",
+        M::YCbCr,
+        16,
+        &[p("AYCbCr", 64)],
+    )
+    .range(Video)
+    .chroma(C::_444)
+    .alpha(),
+    i(
+        F::_4444_A_YP_CB_CR_FLOAT,
+        "4444AYpCbCrFloat",
+        M::YCbCr,
+        32,
+        &[p("AYCbCr", 128)],
+    )
+    .chroma(C::_444)
+    .alpha()
+    .float(),
+    i(
+        F::_444_YP_CB_CR_8,
+        "444YpCbCr8",
+        M::YCbCr,
+        8,
+        &[p("CrYCb", 24)],
+    )
+    .range(Video)
+    .chroma(C::_444),
+    i(
+        F::_422_YP_CB_CR_16,
+        "422YpCbCr16",
+        M::YCbCr,
+        16,
+        &[p("CbYCrY", 64).block(2, 1)],
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_422_YP_CB_CR_10,
+        "422YpCbCr10",
+        M::YCbCr,
+        10,
+        &[p("CbYCrY", 128).block(6, 1)],
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_444_YP_CB_CR_10,
+        "444YpCbCr10",
+        M::YCbCr,
+        10,
+        &[p("CbYCr", 32)],
+    )
+    .range(Video)
+    .chroma(C::_444),
+    i(
+        F::_420_YP_CB_CR_8_PLANAR,
+        "420YpCbCr8Planar",
+        M::YCbCr,
+        8,
+        Y_CB_CR8_420_PLANAR,
+    )
+    .range(Video)
+    .chroma(C::_420),
+    i(
+        F::_420_YP_CB_CR_8_PLANAR_FULL_RANGE,
+        "420YpCbCr8PlanarFullRange",
+        M::YCbCr,
+        8,
+        Y_CB_CR8_420_PLANAR,
+    )
+    .chroma(C::_420),
+    i(
+        F::_422_YP_CB_CR_4_A_8_BI_PLANAR,
+        "422YpCbCr_4A_8BiPlanar",
+        M::YCbCr,
+        8,
+        &[p("CbYCrY", 32).block(2, 1), p("A", 8)],
+    )
+    .range(Video)
+    .chroma(C::_422)
+    .alpha(),
+    i(
+        F::_422_YP_CB_CR_8_YUVS,
+        "422YpCbCr8_yuvs",
+        M::YCbCr,
+        8,
+        &[p("YCbYCr", 32).block(2, 1)],
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_422_YP_CB_CR_8_FULL_RANGE,
+        "422YpCbCr8FullRange",
+        M::YCbCr,
+        8,
+        &[p("YCbYCr", 32).block(2, 1)],
+    )
+    .chroma(C::_422),
+    i(
+        F::_420_YP_CB_CR_8_VIDEO_RANGE_8A_TRI_PLANAR,
+        "420YpCbCr8VideoRange_8A_TriPlanar",
+        M::YCbCr,
+        8,
+        &[p("Y", 8), p("CbCr", 16).subsampled(2, 2), p("A", 8)],
+    )
+    .range(Video)
+    .chroma(C::_420)
+    .alpha(),
+    i(
+        F::_420V,
+        "420YpCbCr8BiPlanarVideoRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .range(Video)
+    .chroma(C::_420),
+    i(
+        F::_420F,
+        "420YpCbCr8BiPlanarFullRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .chroma(C::_420),
+    i(
+        F::_422_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE,
+        "422YpCbCr8BiPlanarVideoRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_422,
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_422_YP_CB_CR_8_BI_PLANAR_FULL_RANGE,
+        "422YpCbCr8BiPlanarFullRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_422,
+    )
+    .chroma(C::_422),
+    i(
+        F::_444_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE,
+        "444YpCbCr8BiPlanarVideoRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_444,
+    )
+    .range(Video)
+    .chroma(C::_444),
+    i(
+        F::_444_YP_CB_CR_8_BI_PLANAR_FULL_RANGE,
+        "444YpCbCr8BiPlanarFullRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_444,
+    )
+    .chroma(C::_444),
+    i(
+        F::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        "420YpCbCr10BiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_420,
+    )
+    .range(Video)
+    .chroma(C::_420),
+    i(
+        F::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        "422YpCbCr10BiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_422,
+    )
+    .range(Video)
+    .chroma(C::_422),
+    i(
+        F::_444_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        "444YpCbCr10BiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_444,
+    )
+    .range(Video)
+    .chroma(C::_444),
+    i(
+        F::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        "420YpCbCr10BiPlanarFullRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_420,
+    )
+    .chroma(C::_420),
+    i(
+        F::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        "422YpCbCr10BiPlanarFullRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_422,
+    )
+    .chroma(C::_422),
+    i(
+        F::_444_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        "444YpCbCr10BiPlanarFullRange",
+        M::YCbCr,
+        10,
+        Y10_CB_CR10_444,
+    )
+    .chroma(C::_444),
+    i(
+        F::ARGB_2101010_LE_PACKED,
+        "ARGB2101010LEPacked",
+        M::Rgb,
+        10,
+        &[p("BGRA", 32)],
+    )
+    .alpha(),
+    i(
+        F::ONE_COMPONENT_8,
+        "OneComponent8",
+        M::Components,
+        8,
+        &[p("R", 8)],
+    ),
+    i(
+        F::ONE_COMPONENT_10,
+        "OneComponent10",
+        M::Components,
+        10,
+        &[p("R", 16)],
+    ),
+    i(
+        F::ONE_COMPONENT_12,
+        "OneComponent12",
+        M::Components,
+        12,
+        &[p("R", 16)],
+    ),
+    i(
+        F::ONE_COMPONENT_16,
+        "OneComponent16",
+        M::Components,
+        16,
+        &[p("R", 16)],
+    ),
+    i(
+        F::ONE_COMPONENT_16_HALF,
+        "OneComponent16Half",
+        M::Components,
+        16,
+        &[p("R", 16)],
+    )
+    .float(),
+    i(
+        F::ONE_COMPONENT_32_FLOAT,
+        "OneComponent32Float",
+        M::Components,
+        32,
+        &[p("R", 32)],
+    )
+    .float(),
+    i(
+        F::TWO_COMPONENT_8,
+        "TwoComponent8",
+        M::Components,
+        8,
+        &[p("RG", 16)],
+    ),
+    i(
+        F::TWO_COMPONENT_16,
+        "TwoComponent16",
+        M::Components,
+        16,
+        &[p("RG", 32)],
+    ),
+    i(
+        F::TWO_COMPONENT_16_HALF,
+        "TwoComponent16Half",
+        M::Components,
+        16,
+        &[p("RG", 32)],
+    )
+    .float(),
+    i(
+        F::TWO_COMPONENT_32_FLOAT,
+        "TwoComponent32Float",
+        M::Components,
+        32,
+        &[p("RG", 64)],
+    )
+    .float(),
+    i(F::_64_RGBA_HALF, "64RGBAHalf", M::Rgb, 16, &[p("RGBA", 64)])
+        .alpha()
+        .float(),
+    i(
+        F::_128_RGBA_FLOAT,
+        "128RGBAFloat",
+        M::Rgb,
+        32,
+        &[p("RGBA", 128)],
+    )
+    .alpha()
+    .float(),
+    i(
+        F::DISPARITY_FLOAT_16,
+        "DisparityFloat16",
+        M::Disparity,
+        16,
+        &[p("D", 16)],
+    )
+    .float(),
+    i(
+        F::DISPARITY_FLOAT_32,
+        "DisparityFloat32",
+        M::Disparity,
+        32,
+        &[p("D", 32)],
+    )
+    .float(),
+    i(
+        F::DEPTH_FLOAT_16,
+        "DepthFloat16",
+        M::Depth,
+        16,
+        &[p("D", 16)],
+    )
+    .float(),
+    i(
+        F::DEPTH_FLOAT_32,
+        "DepthFloat32",
+        M::Depth,
+        32,
+        &[p("D", 32)],
+    )
+    .float(),
+    i(
+        F::LOSSLESS_32_BGRA,
+        "Lossless_32BGRA",
+        M::Rgb,
+        8,
+        &[p("BGRA", 32)],
+    )
+    .alpha()
+    .compressed(Lossless),
+    i(
+        F::LOSSLESS_64_RGBA_HALF,
+        "Lossless_64RGBAHalf",
+        M::Rgb,
+        16,
+        &[p("RGBA", 64)],
+    )
+    .alpha()
+    .float()
+    .compressed(Lossless),
+    i(
+        F::LOSSLESS_420V,
+        "Lossless_420YpCbCr8BiPlanarVideoRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .range(Video)
+    .chroma(C::_420)
+    .compressed(Lossless),
+    i(
+        F::LOSSLESS_420F,
+        "Lossless_420YpCbCr8BiPlanarFullRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .chroma(C::_420)
+    .compressed(Lossless),
+    i(
+        F::LOSSLESS_420_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE,
+        "Lossless_420YpCbCr10PackedBiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        PACKED_Y10_CB_CR10_420,
+    )
+    .range(Video)
+    .chroma(C::_420)
+    .compressed(Lossless),
+    i(
+        F::LOSSLESS_422_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE,
+        "Lossless_422YpCbCr10PackedBiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        PACKED_Y10_CB_CR10_422,
+    )
+    .range(Video)
+    .chroma(C::_422)
+    .compressed(Lossless),
+    i(
+        F::LOSSY_32_BGRA,
+        "Lossy_32BGRA",
+        M::Rgb,
+        8,
+        &[p("BGRA", 32)],
+    )
+    .alpha()
+    .compressed(Lossy),
+    i(
+        F::LOSSY_420V,
+        "Lossy_420YpCbCr8BiPlanarVideoRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .range(Video)
+    .chroma(C::_420)
+    .compressed(Lossy),
+    i(
+        F::LOSSY_420F,
+        "Lossy_420YpCbCr8BiPlanarFullRange",
+        M::YCbCr,
+        8,
+        Y8_CB_CR8_420,
+    )
+    .chroma(C::_420)
+    .compressed(Lossy),
+    i(
+        F::LOSSY_420_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE,
+        "Lossy_420YpCbCr10PackedBiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        PACKED_Y10_CB_CR10_420,
+    )
+    .range(Video)
+    .chroma(C::_420)
+    .compressed(Lossy),
+    i(
+        F::LOSSY_422_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE,
+        "Lossy_422YpCbCr10PackedBiPlanarVideoRange",
+        M::YCbCr,
+        10,
+        PACKED_Y10_CB_CR10_422,
+    )
+    .range(Video)
+    .chroma(C::_422)
+    .compressed(Lossy),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::cv;
+
+    use super::{ChromaSubsampling, ColorModel, Info, Range};
+
+    #[test]
+    fn unique() {
+        let all = Info::all();
+        for (n, a) in all.iter().enumerate() {
+            assert!(!a.planes.is_empty(), "{}", a.name);
+            for b in &all[n + 1..] {
+                assert_ne!(a.format, b.format, "{} {}", a.name, b.name);
+            }
+        }
+    }
+
+    #[test]
+    fn bi_planar() {
+        let info = cv::PixelFormat::_420F.info().unwrap();
+        assert_eq!(info.model, ColorModel::YCbCr);
+        assert_eq!(info.range, Range::Full);
+        assert_eq!(info.chroma_subsampling, Some(ChromaSubsampling::_420));
+        assert!(info.is_planar());
+        assert_eq!(info.plane_width(1, 1921), Some(961));
+        assert_eq!(info.plane_height(1, 1081), Some(541));
+        assert_eq!(info.bytes_per_row(0, 1921, 16), Some(1936));
+        assert_eq!(info.bytes_per_row(1, 1921, 16), Some(1936));
+        assert_eq!(info.bytes_per_row(2, 1921, 16), None);
+        assert_eq!(info.plane_size(1, 1920, 1080, 0), Some(1920 * 540));
+
+        let info = cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE
+            .info()
+            .unwrap();
+        assert_eq!(info.bit_depth, 10);
+        assert_eq!(info.buffer_size(1920, 1080, 0), Some(1920 * 1080 * 3));
+    }
+
+    #[test]
+    fn packed() {
+        let info = cv::PixelFormat::_32_BGRA.info().unwrap();
+        assert!(info.is_packed());
+        assert!(info.has_alpha);
+        assert_eq!(info.planes[0].bits_per_pixel(), Some(32));
+        assert_eq!(info.bytes_per_row(0, 10, 64), Some(64));
+
+        let info = cv::PixelFormat::_2VUY.info().unwrap();
+        assert_eq!(info.planes[0].bits_per_pixel(), Some(16));
+        assert_eq!(info.bytes_per_row(0, 3, 0), Some(8));
+
+        let info = cv::PixelFormat::_422_YP_CB_CR_10.info().unwrap();
+        assert_eq!(info.planes[0].bits_per_pixel(), None);
+        assert_eq!(info.bytes_per_row(0, 1920, 128), Some(5120));
+
+        let info = cv::PixelFormat::_1_MONOCHROME.info().unwrap();
+        assert_eq!(info.bytes_per_row(0, 9, 0), Some(2));
+    }
+
+    #[test]
+    fn compressed() {
+        let info = cv::PixelFormat::LOSSY_420V.info().unwrap();
+        assert!(info.is_compressed());
+        assert_eq!(info.plane_count(), 2);
+        assert_eq!(info.bytes_per_row(0, 1920, 0), None);
+        assert_eq!(info.buffer_size(1920, 1080, 0), None);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(cv::PixelFormat::_420V.to_string(), "420v");
+        assert_eq!(cv::PixelFormat::_32_ARGB.to_string(), "0x00000020");
+        assert!(cv::PixelFormat(0).info().is_none());
+    }
+}
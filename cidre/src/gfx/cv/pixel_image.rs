@@ -0,0 +1,988 @@
+//! Owned and borrowed CPU images laid out like `cv::PixelBuf` planes.
+//!
+//! [`PixelImage`] owns its memory, [`ImageView`] and [`ImageViewMut`] borrow it
+//! either from a [`PixelImage`], from any byte slices or, without copying, from a
+//! locked `cv::PixelBuf` (see `cv::pixel_buffer::BaseAddrLockGuard::view` and
+//! `cv::pixel_buffer::BaseAddrLockGuardMut::view_mut`).
+
+use crate::gfx::cv;
+
+pub const MAX_PLANES: usize = 3;
+
+/// `bytes_per_row` alignment used by [`Layout::new`]
+pub const DEFAULT_ALIGNMENT: usize = 64;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Unknown, compressed or packed into less than a byte per block format
+    UnsupportedFormat(cv::PixelFormat),
+
+    /// Plane at index is missing, too short or its rows are shorter than plane width
+    InvalidPlane(usize),
+
+    /// Formats or sizes of images differ
+    Mismatch,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedFormat(format) => write!(f, "unsupported pixel format {format}"),
+            Self::InvalidPlane(index) => write!(f, "invalid plane {index}"),
+            Self::Mismatch => f.write_str("image formats or sizes differ"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Plain data types which can be read from plane memory.
+///
+/// # Safety
+///
+/// Any bit pattern must be a valid value and type must have no padding.
+pub unsafe trait Sample: Copy + 'static {}
+
+unsafe impl Sample for u8 {}
+unsafe impl Sample for i8 {}
+unsafe impl Sample for u16 {}
+unsafe impl Sample for i16 {}
+unsafe impl Sample for u32 {}
+unsafe impl Sample for i32 {}
+unsafe impl Sample for u64 {}
+unsafe impl Sample for f32 {}
+unsafe impl Sample for f64 {}
+unsafe impl<T: Sample, const N: usize> Sample for [T; N] {}
+
+/// Extra pixels around the image, see `cv::pixel_buffer_keys::extended_pixels_left` and others.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ExtendedPixels {
+    pub left: usize,
+    pub right: usize,
+    pub top: usize,
+    pub bottom: usize,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PlaneLayout {
+    /// Width in pixels without extended pixels
+    pub width: usize,
+
+    /// Height in pixels without extended pixels
+    pub height: usize,
+
+    pub bytes_per_row: usize,
+
+    /// Offset of the first image byte from the start of the buffer, extended pixels are before it.
+    pub offset: usize,
+
+    /// Offset of the plane from the start of the buffer
+    pub start: usize,
+
+    /// Size in bytes including extended pixels
+    pub size: usize,
+}
+
+/// Memory layout of a [`PixelImage`].
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let layout = cv::PixelImageLayout::new(cv::PixelFormat::_420V, 1918, 1080).unwrap();
+/// assert_eq!(layout.plane_count(), 2);
+/// assert_eq!(layout.plane_width(1), 959);
+/// assert_eq!(layout.bytes_per_row(0), 1920);
+/// assert_eq!(layout.size(), 1920 * 1080 + 1920 * 540);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Layout {
+    pub format: cv::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub extended_pixels: ExtendedPixels,
+    planes: [PlaneLayout; MAX_PLANES],
+    plane_count: usize,
+    size: usize,
+}
+
+fn format_info(format: cv::PixelFormat) -> Result<&'static cv::PixelFormatInfo, Error> {
+    match format.info() {
+        Some(info)
+            if !info.is_compressed()
+                && info.planes.len() <= MAX_PLANES
+                && info.planes.iter().all(|p| p.bits_per_block % 8 == 0) =>
+        {
+            Ok(info)
+        }
+        _ => Err(Error::UnsupportedFormat(format)),
+    }
+}
+
+impl Layout {
+    #[inline]
+    pub fn new(format: cv::PixelFormat, width: usize, height: usize) -> Result<Self, Error> {
+        Self::with_opts(format, width, height, DEFAULT_ALIGNMENT, Default::default())
+    }
+
+    /// Layout with `bytes_per_row` and plane starts aligned to `alignment`.
+    pub fn with_opts(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        alignment: usize,
+        extended_pixels: ExtendedPixels,
+    ) -> Result<Self, Error> {
+        let info = format_info(format)?;
+        let ext = extended_pixels;
+        let mut planes = [PlaneLayout::default(); MAX_PLANES];
+        let mut start = 0;
+        for (plane, info) in planes.iter_mut().zip(info.planes) {
+            let bytes_per_row = info.bytes_per_row(ext.left + width + ext.right, alignment);
+            let rows = info.rows(ext.top) + info.rows(height) + info.rows(ext.bottom);
+            let offset =
+                start + info.rows(ext.top) * bytes_per_row + info.bytes_per_row(ext.left, 0);
+            *plane = PlaneLayout {
+                width: info.width(width),
+                height: info.height(height),
+                bytes_per_row,
+                offset,
+                start,
+                size: rows * bytes_per_row,
+            };
+            start += plane.size;
+            if alignment > 1 {
+                start = start.next_multiple_of(alignment);
+            }
+        }
+        let plane_count = info.planes.len();
+        Ok(Self {
+            format,
+            width,
+            height,
+            extended_pixels,
+            planes,
+            plane_count,
+            size: planes[plane_count - 1].start + planes[plane_count - 1].size,
+        })
+    }
+
+    /// Number of planes, 1 for packed formats (`cv::PixelBuf::plane_count` returns 0 for them).
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.plane_count
+    }
+
+    #[inline]
+    pub fn planes(&self) -> &[PlaneLayout] {
+        &self.planes[..self.plane_count]
+    }
+
+    #[inline]
+    pub fn plane_width(&self, plane_index: usize) -> usize {
+        self.planes()[plane_index].width
+    }
+
+    #[inline]
+    pub fn plane_height(&self, plane_index: usize) -> usize {
+        self.planes()[plane_index].height
+    }
+
+    #[inline]
+    pub fn bytes_per_row(&self, plane_index: usize) -> usize {
+        self.planes()[plane_index].bytes_per_row
+    }
+
+    /// Total size in bytes
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// Borrowed plane.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PlaneView<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+    bytes_per_row: usize,
+    bits_per_block: usize,
+    block_width: usize,
+    block_height: usize,
+}
+
+/// Mutably borrowed plane.
+#[derive(Debug, Default)]
+pub struct PlaneViewMut<'a> {
+    data: &'a mut [u8],
+    width: usize,
+    height: usize,
+    bytes_per_row: usize,
+    bits_per_block: usize,
+    block_width: usize,
+    block_height: usize,
+}
+
+/// Shared geometry of plane views
+macro_rules! plane_geometry {
+    () => {
+        #[inline]
+        pub fn width(&self) -> usize {
+            self.width
+        }
+
+        #[inline]
+        pub fn height(&self) -> usize {
+            self.height
+        }
+
+        #[inline]
+        pub fn bytes_per_row(&self) -> usize {
+            self.bytes_per_row
+        }
+
+        /// Bytes of pixel data in a row, without padding
+        #[inline]
+        pub fn row_len(&self) -> usize {
+            (self.width.div_ceil(self.block_width) * self.bits_per_block).div_ceil(8)
+        }
+
+        /// Number of rows of blocks
+        #[inline]
+        pub fn rows_count(&self) -> usize {
+            self.height.div_ceil(self.block_height)
+        }
+
+        #[inline]
+        fn row_range(&self, y: usize) -> std::ops::Range<usize> {
+            assert!(y < self.rows_count(), "row {y} out of bounds");
+            let start = y * self.bytes_per_row;
+            start..start + self.row_len()
+        }
+
+        #[inline]
+        fn pixel_offset<T: Sample>(&self, x: usize, y: usize) -> usize {
+            assert!(x < self.width, "column {x} out of bounds");
+            let size = std::mem::size_of::<T>();
+            debug_assert!(self.block_width > 1 || size * 8 <= self.bits_per_block);
+            let offset = self.row_range(y).start + x * size;
+            assert!(offset + size <= self.data.len());
+            offset
+        }
+
+        /// Sub-rectangle in plane pixels, `x` must be aligned to block width.
+        fn sub_range(
+            &self,
+            x: usize,
+            y: usize,
+            width: usize,
+            height: usize,
+        ) -> Option<std::ops::RangeFrom<usize>> {
+            if x % self.block_width != 0
+                || y % self.block_height != 0
+                || x + width > self.width
+                || y + height > self.height
+            {
+                return None;
+            }
+            let x_bytes = x / self.block_width * self.bits_per_block / 8;
+            Some(y / self.block_height * self.bytes_per_row + x_bytes..)
+        }
+    };
+}
+
+fn check_plane(
+    index: usize,
+    len: usize,
+    bytes_per_row: usize,
+    row_len: usize,
+    rows: usize,
+) -> Result<(), Error> {
+    if rows > 0 && (bytes_per_row < row_len || len < (rows - 1) * bytes_per_row + row_len) {
+        return Err(Error::InvalidPlane(index));
+    }
+    Ok(())
+}
+
+impl<'a> PlaneView<'a> {
+    plane_geometry!();
+
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Pixel data of row `y` without padding
+    #[inline]
+    pub fn row(&self, y: usize) -> &'a [u8] {
+        &self.data[self.row_range(y)]
+    }
+
+    #[inline]
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        (0..self.rows_count()).map(|y| self.row(y))
+    }
+
+    /// Row `y` as samples, `None` if the row is not aligned for `T`
+    pub fn row_as<T: Sample>(&self, y: usize) -> Option<&'a [T]> {
+        let row = self.row(y);
+        let (prefix, samples, _) = unsafe { row.align_to::<T>() };
+        if prefix.is_empty() {
+            Some(samples)
+        } else {
+            None
+        }
+    }
+
+    /// Pixel at `x`, `y` where `T` is the whole pixel, like `[u8; 4]` for `BGRA` or
+    /// `[u16; 2]` for the CbCr plane of `x420`.
+    #[inline]
+    pub fn pixel<T: Sample>(&self, x: usize, y: usize) -> T {
+        let offset = self.pixel_offset::<T>(x, y);
+        unsafe { self.data.as_ptr().add(offset).cast::<T>().read_unaligned() }
+    }
+
+    pub fn sub_view(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        let range = self.sub_range(x, y, width, height)?;
+        Some(Self {
+            data: self.data.get(range).unwrap_or_default(),
+            width,
+            height,
+            ..*self
+        })
+    }
+}
+
+impl<'a> PlaneViewMut<'a> {
+    plane_geometry!();
+
+    #[inline]
+    pub fn as_view(&self) -> PlaneView<'_> {
+        PlaneView {
+            data: self.data,
+            width: self.width,
+            height: self.height,
+            bytes_per_row: self.bytes_per_row,
+            bits_per_block: self.bits_per_block,
+            block_width: self.block_width,
+            block_height: self.block_height,
+        }
+    }
+
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    #[inline]
+    pub fn row(&self, y: usize) -> &[u8] {
+        &self.data[self.row_range(y)]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, y: usize) -> &mut [u8] {
+        let range = self.row_range(y);
+        &mut self.data[range]
+    }
+
+    pub fn row_as_mut<T: Sample>(&mut self, y: usize) -> Option<&mut [T]> {
+        let row = self.row_mut(y);
+        let (prefix, samples, _) = unsafe { row.align_to_mut::<T>() };
+        if prefix.is_empty() {
+            Some(samples)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn pixel<T: Sample>(&self, x: usize, y: usize) -> T {
+        self.as_view().pixel(x, y)
+    }
+
+    #[inline]
+    pub fn set_pixel<T: Sample>(&mut self, x: usize, y: usize, value: T) {
+        let offset = self.pixel_offset::<T>(x, y);
+        unsafe {
+            self.data
+                .as_mut_ptr()
+                .add(offset)
+                .cast::<T>()
+                .write_unaligned(value)
+        }
+    }
+
+    /// Sets every pixel to `value`
+    pub fn fill<T: Sample>(&mut self, value: T) {
+        for y in 0..self.rows_count() {
+            for x in 0..self.width {
+                self.set_pixel(x, y, value);
+            }
+        }
+    }
+
+    /// Copies rows from plane with the same size, strides may differ.
+    pub fn copy_from(&mut self, src: &PlaneView) -> Result<(), Error> {
+        if src.width != self.width
+            || src.height != self.height
+            || src.bits_per_block != self.bits_per_block
+            || src.block_width != self.block_width
+        {
+            return Err(Error::Mismatch);
+        }
+        for y in 0..self.rows_count() {
+            self.row_mut(y).copy_from_slice(src.row(y));
+        }
+        Ok(())
+    }
+
+    pub fn sub_view_mut(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<PlaneViewMut<'_>> {
+        let range = self.sub_range(x, y, width, height)?;
+        Some(PlaneViewMut {
+            data: self.data.get_mut(range).unwrap_or_default(),
+            width,
+            height,
+            bytes_per_row: self.bytes_per_row,
+            bits_per_block: self.bits_per_block,
+            block_width: self.block_width,
+            block_height: self.block_height,
+        })
+    }
+}
+
+/// Borrowed image.
+#[derive(Debug, Copy, Clone)]
+pub struct ImageView<'a> {
+    format: cv::PixelFormat,
+    width: usize,
+    height: usize,
+    planes: [PlaneView<'a>; MAX_PLANES],
+    plane_count: usize,
+}
+
+/// Mutably borrowed image.
+#[derive(Debug)]
+pub struct ImageViewMut<'a> {
+    format: cv::PixelFormat,
+    width: usize,
+    height: usize,
+    planes: [PlaneViewMut<'a>; MAX_PLANES],
+    plane_count: usize,
+}
+
+/// Plane geometry without data
+fn plane_view(
+    info: &cv::PixelFormatPlane,
+    width: usize,
+    height: usize,
+    bpr: usize,
+) -> PlaneView<'static> {
+    PlaneView {
+        data: &[],
+        width: info.width(width),
+        height: info.height(height),
+        bytes_per_row: bpr,
+        bits_per_block: info.bits_per_block,
+        block_width: info.block_width,
+        block_height: info.block_height,
+    }
+}
+
+/// Subsampling of a plane for sub views
+fn sub_rect(
+    info: &cv::PixelFormatPlane,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let (h, v) = (info.horizontal_subsampling, info.vertical_subsampling);
+    if x % h != 0 || y % v != 0 {
+        return None;
+    }
+    Some((x / h, y / v, info.width(width), info.height(height)))
+}
+
+impl<'a> ImageView<'a> {
+    /// View of `planes` given as data and bytes per row.
+    ///
+    /// ```
+    /// use cidre::gfx::cv;
+    ///
+    /// let bgra = [0u8, 0, 255, 255].repeat(6);
+    /// let view = cv::PixelImageView::new(cv::PixelFormat::_32_BGRA, 2, 3, [(&bgra[..], 8)]).unwrap();
+    /// let plane = view.plane(0).unwrap();
+    /// assert_eq!(plane.pixel::<[u8; 4]>(1, 2), [0, 0, 255, 255]);
+    /// ```
+    pub fn new(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: impl IntoIterator<Item = (&'a [u8], usize)>,
+    ) -> Result<Self, Error> {
+        let info = format_info(format)?;
+        let mut res = Self {
+            format,
+            width,
+            height,
+            planes: Default::default(),
+            plane_count: info.planes.len(),
+        };
+        let mut planes = planes.into_iter();
+        for (i, plane_info) in info.planes.iter().enumerate() {
+            let (data, bpr) = planes.next().ok_or(Error::InvalidPlane(i))?;
+            let plane = PlaneView {
+                data,
+                ..plane_view(plane_info, width, height, bpr)
+            };
+            check_plane(i, data.len(), bpr, plane.row_len(), plane.rows_count())?;
+            res.planes[i] = plane;
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn format(&self) -> cv::PixelFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.plane_count
+    }
+
+    #[inline]
+    pub fn planes(&self) -> &[PlaneView<'a>] {
+        &self.planes[..self.plane_count]
+    }
+
+    #[inline]
+    pub fn plane(&self, plane_index: usize) -> Option<PlaneView<'a>> {
+        self.planes().get(plane_index).copied()
+    }
+
+    /// Sub-image in image pixels.
+    ///
+    /// `x` and `y` must be aligned to chroma subsampling and block width.
+    pub fn sub_view(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        let info = format_info(self.format).ok()?;
+        let mut res = *self;
+        res.width = width;
+        res.height = height;
+        for (plane, info) in res.planes.iter_mut().zip(info.planes) {
+            let (x, y, w, h) = sub_rect(info, x, y, width, height)?;
+            *plane = plane.sub_view(x, y, w, h)?;
+        }
+        Some(res)
+    }
+}
+
+impl<'a> ImageViewMut<'a> {
+    pub fn new(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: impl IntoIterator<Item = (&'a mut [u8], usize)>,
+    ) -> Result<Self, Error> {
+        let info = format_info(format)?;
+        let mut res = Self {
+            format,
+            width,
+            height,
+            planes: Default::default(),
+            plane_count: info.planes.len(),
+        };
+        let mut planes = planes.into_iter();
+        for (i, plane_info) in info.planes.iter().enumerate() {
+            let (data, bpr) = planes.next().ok_or(Error::InvalidPlane(i))?;
+            let geometry = plane_view(plane_info, width, height, bpr);
+            check_plane(
+                i,
+                data.len(),
+                bpr,
+                geometry.row_len(),
+                geometry.rows_count(),
+            )?;
+            res.planes[i] = PlaneViewMut {
+                data,
+                width: geometry.width,
+                height: geometry.height,
+                bytes_per_row: bpr,
+                bits_per_block: geometry.bits_per_block,
+                block_width: geometry.block_width,
+                block_height: geometry.block_height,
+            };
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn format(&self) -> cv::PixelFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.plane_count
+    }
+
+    #[inline]
+    pub fn planes_mut(&mut self) -> &mut [PlaneViewMut<'a>] {
+        &mut self.planes[..self.plane_count]
+    }
+
+    #[inline]
+    pub fn plane_mut(&mut self, plane_index: usize) -> Option<&mut PlaneViewMut<'a>> {
+        self.planes_mut().get_mut(plane_index)
+    }
+
+    pub fn as_view(&self) -> ImageView<'_> {
+        let mut planes: [PlaneView; MAX_PLANES] = Default::default();
+        for (dst, src) in planes.iter_mut().zip(&self.planes) {
+            *dst = src.as_view();
+        }
+        ImageView {
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            planes,
+            plane_count: self.plane_count,
+        }
+    }
+
+    /// Copies pixels from image of the same format and size, strides may differ.
+    pub fn copy_from(&mut self, src: &ImageView) -> Result<(), Error> {
+        if src.format != self.format || src.width != self.width || src.height != self.height {
+            return Err(Error::Mismatch);
+        }
+        for (dst, src) in self.planes_mut().iter_mut().zip(src.planes()) {
+            dst.copy_from(src)?;
+        }
+        Ok(())
+    }
+
+    pub fn sub_view_mut(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<ImageViewMut<'_>> {
+        let info = format_info(self.format).ok()?;
+        let mut planes: [PlaneViewMut; MAX_PLANES] = Default::default();
+        for ((dst, src), info) in planes.iter_mut().zip(&mut self.planes).zip(info.planes) {
+            let (x, y, w, h) = sub_rect(info, x, y, width, height)?;
+            *dst = src.sub_view_mut(x, y, w, h)?;
+        }
+        Some(ImageViewMut {
+            format: self.format,
+            width,
+            height,
+            planes,
+            plane_count: self.plane_count,
+        })
+    }
+}
+
+impl<'a> From<ImageView<'a>> for cv::PixelConvertImageRef<'a> {
+    fn from(value: ImageView<'a>) -> Self {
+        Self::new(
+            value.format,
+            value.width,
+            value.height,
+            value.planes().iter().map(|p| cv::PixelConvertPlaneRef {
+                data: p.data,
+                bytes_per_row: p.bytes_per_row,
+            }),
+        )
+    }
+}
+
+impl<'a> From<ImageViewMut<'a>> for cv::PixelConvertImageMut<'a> {
+    fn from(value: ImageViewMut<'a>) -> Self {
+        let count = value.plane_count;
+        Self::new(
+            value.format,
+            value.width,
+            value.height,
+            value
+                .planes
+                .into_iter()
+                .take(count)
+                .map(|p| cv::PixelConvertPlaneMut {
+                    data: p.data,
+                    bytes_per_row: p.bytes_per_row,
+                }),
+        )
+    }
+}
+
+/// Owned CPU image with `cv::PixelBuf` compatible plane layout.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let mut image = cv::PixelImage::new(cv::PixelFormat::_420V, 4, 4).unwrap();
+/// image.plane_mut(0).unwrap().fill(16u8);
+/// image.plane_mut(1).unwrap().fill([128u8, 128]);
+///
+/// let view = image.view();
+/// let sub = view.sub_view(2, 2, 2, 2).unwrap();
+/// assert_eq!(sub.plane(0).unwrap().row(1), [16, 16]);
+/// assert_eq!(sub.plane(1).unwrap().pixel::<[u8; 2]>(0, 0), [128, 128]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PixelImage {
+    layout: Layout,
+    /// u64 storage keeps planes aligned for typed access
+    storage: Vec<u64>,
+}
+
+impl PixelImage {
+    /// Zeroed image with [`DEFAULT_ALIGNMENT`]
+    #[inline]
+    pub fn new(format: cv::PixelFormat, width: usize, height: usize) -> Result<Self, Error> {
+        Ok(Self::with_layout(Layout::new(format, width, height)?))
+    }
+
+    pub fn with_layout(layout: Layout) -> Self {
+        Self {
+            layout,
+            storage: vec![0; layout.size().div_ceil(8)],
+        }
+    }
+
+    /// Copy of `view` with [`DEFAULT_ALIGNMENT`]
+    pub fn with_view(view: &ImageView) -> Result<Self, Error> {
+        let mut res = Self::new(view.format, view.width, view.height)?;
+        res.view_mut().copy_from(view)?;
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    #[inline]
+    pub fn format(&self) -> cv::PixelFormat {
+        self.layout.format
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.layout.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.layout.height
+    }
+
+    #[inline]
+    pub fn plane_count(&self) -> usize {
+        self.layout.plane_count
+    }
+
+    /// Whole buffer including extended pixels and padding
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.storage.as_ptr().cast(), self.layout.size) }
+    }
+
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(self.storage.as_mut_ptr().cast(), self.layout.size)
+        }
+    }
+
+    pub fn view(&self) -> ImageView<'_> {
+        let bytes = self.as_bytes();
+        let planes = self.layout.planes().iter().map(|p| {
+            let end = p.start + p.size;
+            (&bytes[p.offset..end], p.bytes_per_row)
+        });
+        ImageView::new(self.format(), self.width(), self.height(), planes)
+            .expect("layout matches format")
+    }
+
+    pub fn view_mut(&mut self) -> ImageViewMut<'_> {
+        let layout = self.layout;
+        let mut rest = self.as_bytes_mut();
+        let mut consumed = 0;
+        let mut planes = Vec::with_capacity(layout.plane_count);
+        for p in layout.planes() {
+            let (plane, tail) = std::mem::take(&mut rest).split_at_mut(p.start + p.size - consumed);
+            planes.push((&mut plane[p.offset - consumed..], p.bytes_per_row));
+            consumed = p.start + p.size;
+            rest = tail;
+        }
+        ImageViewMut::new(layout.format, layout.width, layout.height, planes)
+            .expect("layout matches format")
+    }
+
+    #[inline]
+    pub fn plane(&self, plane_index: usize) -> Option<PlaneView<'_>> {
+        self.view().plane(plane_index)
+    }
+
+    pub fn plane_mut(&mut self, plane_index: usize) -> Option<PlaneViewMut<'_>> {
+        if plane_index >= self.plane_count() {
+            return None;
+        }
+        let view = self.view_mut();
+        view.planes.into_iter().nth(plane_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::cv;
+
+    use super::{Error, ExtendedPixels, ImageView, Layout, PixelImage};
+
+    #[test]
+    fn layout() {
+        let ext = ExtendedPixels {
+            left: 16,
+            right: 16,
+            top: 2,
+            bottom: 2,
+        };
+        let layout = Layout::with_opts(cv::PixelFormat::_420F, 100, 50, 64, ext).unwrap();
+        let y = layout.planes()[0];
+        assert_eq!(y.bytes_per_row, 192);
+        assert_eq!(y.offset, 2 * 192 + 16);
+        assert_eq!(y.size, 54 * 192);
+        let c = layout.planes()[1];
+        assert_eq!(c.width, 50);
+        assert_eq!(c.start, 54 * 192);
+        assert_eq!(c.offset, c.start + 192 + 16);
+        assert_eq!(layout.size(), c.start + 27 * 192);
+
+        let layout = Layout::new(cv::PixelFormat::_422_YP_CB_CR_10, 1280, 720).unwrap();
+        assert_eq!(layout.bytes_per_row(0), 3456);
+
+        assert_eq!(
+            Layout::new(cv::PixelFormat::LOSSY_420V, 16, 16),
+            Err(Error::UnsupportedFormat(cv::PixelFormat::LOSSY_420V))
+        );
+        assert!(Layout::new(cv::PixelFormat::_1_MONOCHROME, 16, 16).is_err());
+    }
+
+    #[test]
+    fn typed_access() {
+        let mut image =
+            PixelImage::new(cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE, 4, 2).unwrap();
+        {
+            let mut luma = image.plane_mut(0).unwrap();
+            luma.row_as_mut::<u16>(1).unwrap()[3] = 1023 << 6;
+            luma.set_pixel(0, 0, 64u16 << 6);
+        }
+        let luma = image.plane(0).unwrap();
+        assert_eq!(luma.row_len(), 8);
+        assert_eq!(luma.pixel::<u16>(3, 1), 1023 << 6);
+        assert_eq!(luma.row_as::<u16>(0).unwrap(), [64 << 6, 0, 0, 0]);
+
+        let chroma = image.plane(1).unwrap();
+        assert_eq!((chroma.width(), chroma.height()), (2, 1));
+        assert_eq!(chroma.pixel::<[u16; 2]>(1, 0), [0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        let image = PixelImage::new(cv::PixelFormat::_32_BGRA, 4, 2).unwrap();
+        image.plane(0).unwrap().pixel::<u32>(4, 0);
+    }
+
+    #[test]
+    fn copy() {
+        let bgra: Vec<u8> = (0..4 * 3 * 10).map(|i| i as u8).collect();
+        let src = ImageView::new(cv::PixelFormat::_32_BGRA, 3, 4, [(&bgra[..], 30)]).unwrap();
+        let image = PixelImage::with_view(&src).unwrap();
+        assert_eq!(image.layout().bytes_per_row(0), 64);
+        let view = image.view();
+        for y in 0..4 {
+            assert_eq!(view.plane(0).unwrap().row(y), &bgra[y * 30..y * 30 + 12]);
+        }
+
+        let mut dst = PixelImage::new(cv::PixelFormat::_32_BGRA, 2, 2).unwrap();
+        let sub = src.sub_view(1, 2, 2, 2).unwrap();
+        dst.view_mut().copy_from(&sub).unwrap();
+        assert_eq!(
+            dst.plane(0).unwrap().pixel::<[u8; 4]>(0, 1),
+            [94, 95, 96, 97]
+        );
+
+        let other = PixelImage::new(cv::PixelFormat::_32_RGBA, 2, 2).unwrap();
+        assert_eq!(
+            dst.view_mut().copy_from(&other.view()),
+            Err(Error::Mismatch)
+        );
+
+        assert_eq!(
+            ImageView::new(cv::PixelFormat::_32_BGRA, 3, 4, [(&bgra[..100], 30)]).err(),
+            Some(Error::InvalidPlane(0))
+        );
+    }
+
+    #[test]
+    fn sub_view() {
+        let mut image = PixelImage::new(cv::PixelFormat::_420V, 8, 8).unwrap();
+        {
+            let mut view = image.view_mut();
+            let mut sub = view.sub_view_mut(4, 2, 4, 4).unwrap();
+            sub.plane_mut(0).unwrap().fill(200u8);
+            sub.plane_mut(1).unwrap().fill([1u8, 2]);
+            assert!(view.sub_view_mut(3, 2, 4, 4).is_none());
+        }
+        let luma = image.plane(0).unwrap();
+        assert_eq!(luma.row(1), [0; 8]);
+        assert_eq!(luma.row(2), [0, 0, 0, 0, 200, 200, 200, 200]);
+        let chroma = image.plane(1).unwrap();
+        assert_eq!(chroma.row(1), [0, 0, 0, 0, 1, 2, 1, 2]);
+        assert_eq!(chroma.row(3), [0; 8]);
+    }
+
+    #[test]
+    fn convert() {
+        let mut src = PixelImage::new(cv::PixelFormat::_32_BGRA, 2, 2).unwrap();
+        src.plane_mut(0).unwrap().fill([255u8; 4]);
+        let mut dst = PixelImage::new(cv::PixelFormat::_420V, 2, 2).unwrap();
+        cv::pixel_convert::convert(
+            &src.view().into(),
+            &mut dst.view_mut().into(),
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(dst.plane(0).unwrap().row(0), [235, 235]);
+    }
+}
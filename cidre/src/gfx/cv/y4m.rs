@@ -0,0 +1,963 @@
+//! YUV4MPEG2 (`.y4m`) and raw planar YUV (`.yuv`) files.
+//!
+//! Files store Y, Cb and Cr in separate planes (`yuv420p`, `yuv422p10le` and so on).
+//! Frames are read into and written from bi-planar [`cv::PixelFormat`]s, so they can
+//! be copied into `cv::PixelBuf` as is:
+//!
+//! | file              | pixel format                                      |
+//! |-------------------|---------------------------------------------------|
+//! | `420`, `420jpeg`… | `420v`, `420f`                                    |
+//! | `422`, `444`      | `422v`, `422f`, `444v`, `444f`                    |
+//! | `420p10`…         | `x420`, `x422`, `x444`, `xf20`, `xf22`, `xf44`    |
+//!
+//! Range comes from the `XCOLORRANGE` extension, video range is assumed without it.
+
+use std::io::{Read, Write};
+
+use crate::gfx::cv;
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+const SIGNATURE: &[u8] = b"YUV4MPEG2";
+const FRAME: &[u8] = b"FRAME";
+const MAX_LINE_LEN: usize = 4096;
+
+/// Largest width or height, keeps frame size arithmetic in range
+const MAX_SIZE: usize = 1 << 15;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+
+    /// Malformed stream or frame header
+    InvalidHeader(String),
+
+    /// `C` parameter value which has no pixel format
+    UnsupportedColorspace(String),
+
+    UnsupportedFormat(cv::PixelFormat),
+
+    /// Image passed to read or write doesn't match stream format or size
+    Image(cv::PixelImageError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => err.fmt(f),
+            Self::InvalidHeader(msg) => write!(f, "invalid y4m header: {msg}"),
+            Self::UnsupportedColorspace(c) => write!(f, "unsupported y4m colorspace {c}"),
+            Self::UnsupportedFormat(format) => write!(f, "unsupported pixel format {format}"),
+            Self::Image(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Image(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    #[inline]
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<cv::PixelImageError> for Error {
+    #[inline]
+    fn from(value: cv::PixelImageError) -> Self {
+        Self::Image(value)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum Interlace {
+    #[default]
+    Progressive,
+    TopFieldFirst,
+    BottomFieldFirst,
+    Mixed,
+}
+
+impl Interlace {
+    const fn tag(&self) -> char {
+        match self {
+            Self::Progressive => 'p',
+            Self::TopFieldFirst => 't',
+            Self::BottomFieldFirst => 'b',
+            Self::Mixed => 'm',
+        }
+    }
+}
+
+/// Planar file layout of a pixel format
+#[derive(Debug, Copy, Clone)]
+struct Planar {
+    subsampling: cv::ChromaSubsampling,
+    /// 1 for 8 bit, 2 for 10 bit little-endian samples
+    bytes_per_sample: usize,
+}
+
+impl Planar {
+    fn with_format(format: cv::PixelFormat) -> Result<Self, Error> {
+        let err = || Error::UnsupportedFormat(format);
+        let info = format.info().ok_or_else(err)?;
+        if info.model != cv::PixelFormatColorModel::YCbCr
+            || info.is_compressed()
+            || info.has_alpha
+            || info.planes.len() != 2
+        {
+            return Err(err());
+        }
+        let subsampling = info.chroma_subsampling.ok_or_else(err)?;
+        let bytes_per_sample = match (info.bit_depth, info.planes[0].bits_per_block) {
+            (8, 8) => 1,
+            (10, 16) => 2,
+            _ => return Err(err()),
+        };
+        Ok(Self {
+            subsampling,
+            bytes_per_sample,
+        })
+    }
+
+    fn format(
+        subsampling: cv::ChromaSubsampling,
+        bit_depth: u8,
+        range: cv::PixelFormatRange,
+    ) -> Option<cv::PixelFormat> {
+        use cv::ChromaSubsampling as C;
+        use cv::PixelFormat as F;
+        use cv::PixelFormatRange::{Full, Video};
+
+        Some(match (bit_depth, subsampling, range) {
+            (8, C::_420, Video) => F::_420V,
+            (8, C::_420, Full) => F::_420F,
+            (8, C::_422, Video) => F::_422_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE,
+            (8, C::_422, Full) => F::_422_YP_CB_CR_8_BI_PLANAR_FULL_RANGE,
+            (8, C::_444, Video) => F::_444_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE,
+            (8, C::_444, Full) => F::_444_YP_CB_CR_8_BI_PLANAR_FULL_RANGE,
+            (10, C::_420, Video) => F::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            (10, C::_420, Full) => F::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+            (10, C::_422, Video) => F::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            (10, C::_422, Full) => F::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+            (10, C::_444, Video) => F::_444_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            (10, C::_444, Full) => F::_444_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+            _ => return None,
+        })
+    }
+
+    fn chroma_size(&self, width: usize, height: usize) -> (usize, usize) {
+        let (h, v) = self.subsampling.factors();
+        (width.div_ceil(h), height.div_ceil(v))
+    }
+
+    fn frame_size(&self, width: usize, height: usize) -> usize {
+        let (cw, ch) = self.chroma_size(width, height);
+        (width * height + 2 * cw * ch) * self.bytes_per_sample
+    }
+
+    /// Planar file samples to bi-planar image
+    fn unpack(&self, frame: &[u8], dst: &mut cv::PixelImageViewMut) {
+        let (w, h) = (dst.width(), dst.height());
+        let (cw, ch) = self.chroma_size(w, h);
+        let bps = self.bytes_per_sample;
+        let (luma, chroma) = frame.split_at(w * h * bps);
+        let (cb, cr) = chroma.split_at(cw * ch * bps);
+
+        let y_plane = dst.plane_mut(0).unwrap();
+        for (y, src) in luma.chunks_exact(w * bps).enumerate() {
+            let row = y_plane.row_mut(y);
+            if bps == 1 {
+                row.copy_from_slice(src);
+            } else {
+                for (d, s) in row.chunks_exact_mut(2).zip(src.chunks_exact(2)) {
+                    let v = u16::from_le_bytes([s[0], s[1]]) << 6;
+                    d.copy_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+
+        let c_plane = dst.plane_mut(1).unwrap();
+        let rows = cb.chunks_exact(cw * bps).zip(cr.chunks_exact(cw * bps));
+        for (y, (cb, cr)) in rows.enumerate() {
+            let row = c_plane.row_mut(y);
+            let pairs = cb.chunks_exact(bps).zip(cr.chunks_exact(bps));
+            for (d, (cb, cr)) in row.chunks_exact_mut(2 * bps).zip(pairs) {
+                if bps == 1 {
+                    d[0] = cb[0];
+                    d[1] = cr[0];
+                } else {
+                    let cb = u16::from_le_bytes([cb[0], cb[1]]) << 6;
+                    let cr = u16::from_le_bytes([cr[0], cr[1]]) << 6;
+                    d[..2].copy_from_slice(&cb.to_le_bytes());
+                    d[2..].copy_from_slice(&cr.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    /// Bi-planar image to planar file samples
+    fn pack(&self, src: &cv::PixelImageView, frame: &mut Vec<u8>) {
+        let (w, h) = (src.width(), src.height());
+        let (cw, ch) = self.chroma_size(w, h);
+        let bps = self.bytes_per_sample;
+        frame.clear();
+        frame.reserve(self.frame_size(w, h));
+
+        let y_plane = src.plane(0).unwrap();
+        for row in y_plane.rows() {
+            if bps == 1 {
+                frame.extend_from_slice(row);
+            } else {
+                for s in row.chunks_exact(2) {
+                    let v = u16::from_le_bytes([s[0], s[1]]) >> 6;
+                    frame.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+
+        let c_plane = src.plane(1).unwrap();
+        for component in 0..2 {
+            for y in 0..ch {
+                let row = c_plane.row(y);
+                for pair in row.chunks_exact(2 * bps).take(cw) {
+                    let s = &pair[component * bps..(component + 1) * bps];
+                    if bps == 1 {
+                        frame.push(s[0]);
+                    } else {
+                        let v = u16::from_le_bytes([s[0], s[1]]) >> 6;
+                        frame.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Stream header.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let header: cv::Y4mHeader = "YUV4MPEG2 W1920 H1080 F30000:1001 Ip A1:1 C420mpeg2 XYSCSS=420MPEG2"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(header.format, cv::PixelFormat::_420V);
+/// assert_eq!(header.chroma_siting, cv::ChromaSiting::Left);
+/// assert_eq!(header.frame_size(), 1920 * 1080 * 3 / 2);
+/// assert_eq!(header.extensions, ["YSCSS=420MPEG2"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub width: usize,
+    pub height: usize,
+
+    /// Frames per second as numerator and denominator, e.g. `(30000, 1001)`
+    pub frame_rate: (u32, u32),
+
+    /// Pixel aspect ratio, `(0, 0)` if unknown
+    pub pixel_aspect: (u32, u32),
+    pub interlace: Interlace,
+
+    /// Bi-planar format of frames
+    pub format: cv::PixelFormat,
+
+    /// Chroma siting of 4:2:0 8-bit streams
+    pub chroma_siting: cv::ChromaSiting,
+
+    /// `X` parameters without the leading `X`, `XCOLORRANGE` is parsed into `format`
+    pub extensions: Vec<String>,
+}
+
+impl Header {
+    pub fn new(
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        frame_rate: (u32, u32),
+    ) -> Result<Self, Error> {
+        Planar::with_format(format)?;
+        check_size(width, height)?;
+        Ok(Self {
+            width,
+            height,
+            frame_rate,
+            pixel_aspect: (1, 1),
+            interlace: Interlace::Progressive,
+            format,
+            chroma_siting: cv::ChromaSiting::Left,
+            extensions: Vec::new(),
+        })
+    }
+
+    /// Checks stream signature
+    #[inline]
+    pub fn is_y4m(data: &[u8]) -> bool {
+        data.starts_with(SIGNATURE)
+    }
+
+    /// Size of frame data in bytes without `FRAME` header
+    #[inline]
+    pub fn frame_size(&self) -> usize {
+        Planar::with_format(self.format)
+            .map(|p| p.frame_size(self.width, self.height))
+            .unwrap_or(0)
+    }
+
+    /// Presentation time of frame at `index` with frame rate numerator as timescale.
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn frame_pts(&self, index: u64) -> cm::Time {
+        let (num, den) = self.frame_rate;
+        cm::Time::new(index as i64 * den as i64, num as i32)
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn frame_duration(&self) -> cm::Time {
+        let (num, den) = self.frame_rate;
+        cm::Time::new(den as i64, num as i32)
+    }
+
+    fn colorspace(&self) -> Result<&'static str, Error> {
+        let planar = Planar::with_format(self.format)?;
+        use cv::ChromaSubsampling as C;
+        Ok(match (planar.bytes_per_sample, planar.subsampling) {
+            (1, C::_420) => match self.chroma_siting {
+                cv::ChromaSiting::Left => "420mpeg2",
+                cv::ChromaSiting::TopLeft => "420paldv",
+                _ => "420jpeg",
+            },
+            (1, C::_422) => "422",
+            (1, C::_444) => "444",
+            (_, C::_420) => "420p10",
+            (_, C::_422) => "422p10",
+            (_, C::_444) => "444p10",
+        })
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> Result<(), Error> {
+        let (fn_, fd) = self.frame_rate;
+        write!(
+            w,
+            "YUV4MPEG2 W{} H{} F{fn_}:{fd} I{}",
+            self.width,
+            self.height,
+            self.interlace.tag()
+        )?;
+        let (an, ad) = self.pixel_aspect;
+        write!(w, " A{an}:{ad} C{}", self.colorspace()?)?;
+        let full = self
+            .format
+            .info()
+            .is_some_and(|i| i.range == cv::PixelFormatRange::Full);
+        let range = if full { "FULL" } else { "LIMITED" };
+        write!(w, " XCOLORRANGE={range}")?;
+        for ext in &self.extensions {
+            write!(w, " X{ext}")?;
+        }
+        w.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+fn check_size(width: usize, height: usize) -> Result<(), Error> {
+    if width == 0 || height == 0 || width > MAX_SIZE || height > MAX_SIZE {
+        return Err(Error::InvalidHeader(format!(
+            "invalid size {width}x{height}"
+        )));
+    }
+    Ok(())
+}
+
+fn parse_ratio(value: &str) -> Result<(u32, u32), Error> {
+    value
+        .split_once(':')
+        .and_then(|(n, d)| Some((n.parse().ok()?, d.parse().ok()?)))
+        .ok_or_else(|| Error::InvalidHeader(format!("invalid ratio {value}")))
+}
+
+impl std::str::FromStr for Header {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = s.trim_end_matches('\n').split(' ');
+        if params.next() != Some("YUV4MPEG2") {
+            return Err(Error::InvalidHeader("missing YUV4MPEG2 signature".into()));
+        }
+        let (mut width, mut height, mut frame_rate) = (None, None, None);
+        let mut header = Self {
+            width: 0,
+            height: 0,
+            frame_rate: (0, 0),
+            pixel_aspect: (0, 0),
+            interlace: Interlace::Progressive,
+            format: cv::PixelFormat::_420V,
+            chroma_siting: cv::ChromaSiting::Center,
+            extensions: Vec::new(),
+        };
+        let mut subsampling = cv::ChromaSubsampling::_420;
+        let mut bit_depth = 8;
+        let mut range = cv::PixelFormatRange::Video;
+        for param in params.filter(|p| !p.is_empty()) {
+            let mut chars = param.chars();
+            let tag = chars.next();
+            let value = chars.as_str();
+            match tag {
+                Some('W') => width = value.parse().ok(),
+                Some('H') => height = value.parse().ok(),
+                Some('F') => frame_rate = Some(parse_ratio(value)?),
+                Some('A') => header.pixel_aspect = parse_ratio(value)?,
+                Some('I') => {
+                    header.interlace = match value {
+                        "p" | "?" => Interlace::Progressive,
+                        "t" => Interlace::TopFieldFirst,
+                        "b" => Interlace::BottomFieldFirst,
+                        "m" => Interlace::Mixed,
+                        _ => return Err(Error::InvalidHeader(format!("invalid I{value}"))),
+                    }
+                }
+                Some('C') => {
+                    use cv::ChromaSubsampling as C;
+                    (subsampling, bit_depth, header.chroma_siting) = match value {
+                        "420" | "420jpeg" => (C::_420, 8, cv::ChromaSiting::Center),
+                        "420mpeg2" => (C::_420, 8, cv::ChromaSiting::Left),
+                        "420paldv" => (C::_420, 8, cv::ChromaSiting::TopLeft),
+                        "422" => (C::_422, 8, cv::ChromaSiting::Left),
+                        "444" => (C::_444, 8, cv::ChromaSiting::Center),
+                        "420p10" => (C::_420, 10, cv::ChromaSiting::Left),
+                        "422p10" => (C::_422, 10, cv::ChromaSiting::Left),
+                        "444p10" => (C::_444, 10, cv::ChromaSiting::Center),
+                        _ => return Err(Error::UnsupportedColorspace(value.to_string())),
+                    }
+                }
+                Some('X') => match value {
+                    "COLORRANGE=FULL" => range = cv::PixelFormatRange::Full,
+                    "COLORRANGE=LIMITED" => range = cv::PixelFormatRange::Video,
+                    _ => header.extensions.push(value.to_string()),
+                },
+                _ => return Err(Error::InvalidHeader(format!("unknown parameter {param}"))),
+            }
+        }
+        match (width, height, frame_rate) {
+            (Some(w), Some(h), Some(f)) if f.0 > 0 && f.1 > 0 => {
+                check_size(w, h)?;
+                header.width = w;
+                header.height = h;
+                header.frame_rate = f;
+            }
+            _ => return Err(Error::InvalidHeader("missing W, H or F".into())),
+        }
+        header.format = Planar::format(subsampling, bit_depth, range)
+            .ok_or_else(|| Error::InvalidHeader("no pixel format for C parameter".into()))?;
+        Ok(header)
+    }
+}
+
+/// Reads line without `\n`, `Ok(None)` on end of stream before first byte
+fn read_line<R: Read>(r: &mut R) -> Result<Option<String>, Error> {
+    let mut line = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        if r.read(&mut byte)? == 0 {
+            if line.is_empty() {
+                return Ok(None);
+            }
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+        if line.len() > MAX_LINE_LEN {
+            return Err(Error::InvalidHeader("header line too long".into()));
+        }
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| Error::InvalidHeader("header is not utf-8".into()))
+}
+
+/// Reads frames of image size into `dst`, `Ok(false)` on end of stream
+fn read_frame_data<R: Read>(r: &mut R, frame: &mut Vec<u8>, size: usize) -> Result<bool, Error> {
+    frame.resize(size, 0);
+    let mut filled = 0;
+    while filled < size {
+        match r.read(&mut frame[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(true)
+}
+
+fn check_image(
+    format: cv::PixelFormat,
+    width: usize,
+    height: usize,
+    image_format: cv::PixelFormat,
+    image_width: usize,
+    image_height: usize,
+) -> Result<(), Error> {
+    if format != image_format || width != image_width || height != image_height {
+        return Err(cv::PixelImageError::Mismatch.into());
+    }
+    Ok(())
+}
+
+/// YUV4MPEG2 stream reader.
+///
+/// Wrap files into `std::io::BufReader`, headers are read byte by byte.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let mut data = b"YUV4MPEG2 W2 H2 F25:1 C420jpeg\nFRAME\n".to_vec();
+/// data.extend_from_slice(&[16, 16, 16, 16, 128, 128]);
+///
+/// let mut reader = cv::Y4mReader::new(&data[..]).unwrap();
+/// assert_eq!(reader.header().frame_rate, (25, 1));
+///
+/// let frame = reader.read_frame().unwrap().unwrap();
+/// assert_eq!(frame.format(), cv::PixelFormat::_420V);
+/// assert_eq!(frame.plane(1).unwrap().row(0), [128, 128]);
+/// assert!(reader.read_frame().unwrap().is_none());
+/// ```
+pub struct Reader<R> {
+    inner: R,
+    header: Header,
+    planar: Planar,
+    frame: Vec<u8>,
+    frame_index: u64,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(mut inner: R) -> Result<Self, Error> {
+        let line = read_line(&mut inner)?
+            .ok_or_else(|| Error::InvalidHeader("missing YUV4MPEG2 signature".into()))?;
+        let header: Header = line.parse()?;
+        let planar = Planar::with_format(header.format)?;
+        Ok(Self {
+            inner,
+            header,
+            planar,
+            frame: Vec::new(),
+            frame_index: 0,
+        })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Number of frames read so far
+    #[inline]
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Presentation time of the next frame
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn next_pts(&self) -> cm::Time {
+        self.header.frame_pts(self.frame_index)
+    }
+
+    /// Reads next frame into `dst` of header format and size, `Ok(false)` on end of stream
+    pub fn read_frame_into(&mut self, dst: &mut cv::PixelImageViewMut) -> Result<bool, Error> {
+        let h = &self.header;
+        check_image(
+            h.format,
+            h.width,
+            h.height,
+            dst.format(),
+            dst.width(),
+            dst.height(),
+        )?;
+        let Some(line) = read_line(&mut self.inner)? else {
+            return Ok(false);
+        };
+        if !line.as_bytes().starts_with(FRAME) {
+            return Err(Error::InvalidHeader(format!("invalid frame header {line}")));
+        }
+        let size = self.planar.frame_size(h.width, h.height);
+        if !read_frame_data(&mut self.inner, &mut self.frame, size)? {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        self.planar.unpack(&self.frame, dst);
+        self.frame_index += 1;
+        Ok(true)
+    }
+
+    /// Reads next frame, `Ok(None)` on end of stream
+    pub fn read_frame(&mut self) -> Result<Option<cv::PixelImage>, Error> {
+        let h = &self.header;
+        let mut image = cv::PixelImage::new(h.format, h.width, h.height)?;
+        if self.read_frame_into(&mut image.view_mut())? {
+            Ok(Some(image))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// YUV4MPEG2 stream writer.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let header = cv::Y4mHeader::new(cv::PixelFormat::_420F, 2, 2, (30, 1)).unwrap();
+/// let mut writer = cv::Y4mWriter::new(Vec::new(), header).unwrap();
+/// let frame = cv::PixelImage::new(cv::PixelFormat::_420F, 2, 2).unwrap();
+/// writer.write_frame(&frame.view()).unwrap();
+///
+/// let data = writer.into_inner();
+/// assert!(data.starts_with(b"YUV4MPEG2 W2 H2 F30:1 Ip A1:1 C420mpeg2 XCOLORRANGE=FULL\nFRAME\n"));
+/// ```
+pub struct Writer<W> {
+    inner: W,
+    header: Header,
+    planar: Planar,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Writes stream header
+    pub fn new(mut inner: W, header: Header) -> Result<Self, Error> {
+        let planar = Planar::with_format(header.format)?;
+        header.write_to(&mut inner)?;
+        Ok(Self {
+            inner,
+            header,
+            planar,
+            frame: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn write_frame(&mut self, src: &cv::PixelImageView) -> Result<(), Error> {
+        let h = &self.header;
+        check_image(
+            h.format,
+            h.width,
+            h.height,
+            src.format(),
+            src.width(),
+            src.height(),
+        )?;
+        self.planar.pack(src, &mut self.frame);
+        self.inner.write_all(FRAME)?;
+        self.inner.write_all(b"\n")?;
+        self.inner.write_all(&self.frame)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.inner.flush()?)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Headerless planar YUV reader with explicit format and size.
+///
+/// `format` selects the file layout: `420v` reads `yuv420p`, `xf22` reads `yuv422p10le`.
+///
+/// ```
+/// use cidre::gfx::cv;
+///
+/// let data = [0u8; 4 * 2 * 3];
+/// let format = cv::PixelFormat::_444_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE;
+/// let mut reader = cv::RawYuvReader::new(&data[..], format, 4, 2).unwrap();
+/// assert_eq!(reader.frame_size(), 24);
+/// assert!(reader.read_frame().unwrap().is_some());
+/// assert!(reader.read_frame().unwrap().is_none());
+/// ```
+pub struct RawReader<R> {
+    inner: R,
+    format: cv::PixelFormat,
+    width: usize,
+    height: usize,
+    planar: Planar,
+    frame: Vec<u8>,
+    frame_index: u64,
+}
+
+impl<R: Read> RawReader<R> {
+    pub fn new(
+        inner: R,
+        format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, Error> {
+        let planar = Planar::with_format(format)?;
+        check_size(width, height)?;
+        Ok(Self {
+            inner,
+            format,
+            width,
+            height,
+            planar,
+            frame: Vec::new(),
+            frame_index: 0,
+        })
+    }
+
+    #[inline]
+    pub fn format(&self) -> cv::PixelFormat {
+        self.format
+    }
+
+    /// Size of frame in bytes
+    #[inline]
+    pub fn frame_size(&self) -> usize {
+        self.planar.frame_size(self.width, self.height)
+    }
+
+    #[inline]
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    pub fn read_frame_into(&mut self, dst: &mut cv::PixelImageViewMut) -> Result<bool, Error> {
+        let (format, w, h) = (self.format, self.width, self.height);
+        check_image(format, w, h, dst.format(), dst.width(), dst.height())?;
+        let size = self.frame_size();
+        if !read_frame_data(&mut self.inner, &mut self.frame, size)? {
+            return Ok(false);
+        }
+        self.planar.unpack(&self.frame, dst);
+        self.frame_index += 1;
+        Ok(true)
+    }
+
+    pub fn read_frame(&mut self) -> Result<Option<cv::PixelImage>, Error> {
+        let mut image = cv::PixelImage::new(self.format, self.width, self.height)?;
+        if self.read_frame_into(&mut image.view_mut())? {
+            Ok(Some(image))
+        } else {
+            Ok(None)
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Headerless planar YUV writer, file layout follows the image format.
+pub struct RawWriter<W> {
+    inner: W,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> RawWriter<W> {
+    #[inline]
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            frame: Vec::new(),
+        }
+    }
+
+    pub fn write_frame(&mut self, src: &cv::PixelImageView) -> Result<(), Error> {
+        let planar = Planar::with_format(src.format())?;
+        planar.pack(src, &mut self.frame);
+        self.inner.write_all(&self.frame)?;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.inner.flush()?)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::cv;
+
+    use super::{Error, Header, Interlace, RawReader, RawWriter, Reader, Writer};
+
+    fn gradient(format: cv::PixelFormat, width: usize, height: usize) -> cv::PixelImage {
+        let mut image = cv::PixelImage::new(format, width, height).unwrap();
+        let wide = format.info().unwrap().bit_depth > 8;
+        for p in 0..2 {
+            let mut plane = image.plane_mut(p).unwrap();
+            for y in 0..plane.height() {
+                for x in 0..plane.width() {
+                    let v = (x * 7 + y * 13 + p * 5) as u16;
+                    if wide {
+                        let v = (v % 1024) << 6;
+                        if p == 0 {
+                            plane.set_pixel(x, y, v);
+                        } else {
+                            plane.set_pixel(x, y, [v, v ^ (1 << 15)]);
+                        }
+                    } else if p == 0 {
+                        plane.set_pixel(x, y, v as u8);
+                    } else {
+                        plane.set_pixel(x, y, [v as u8, !v as u8]);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    fn assert_same(a: &cv::PixelImage, b: &cv::PixelImage) {
+        let (a, b) = (a.view(), b.view());
+        for (a, b) in a.planes().iter().zip(b.planes()) {
+            assert!(a.rows().eq(b.rows()));
+        }
+    }
+
+    #[test]
+    fn header() {
+        let h: Header = "YUV4MPEG2 W720 H576 F25:1 It A16:15 C420paldv XCOLORRANGE=FULL"
+            .parse()
+            .unwrap();
+        assert_eq!(h.format, cv::PixelFormat::_420F);
+        assert_eq!(h.interlace, Interlace::TopFieldFirst);
+        assert_eq!(h.pixel_aspect, (16, 15));
+        assert_eq!(h.chroma_siting, cv::ChromaSiting::TopLeft);
+        assert!(h.extensions.is_empty());
+
+        let h: Header = "YUV4MPEG2 W4 H4 F1:1 C422p10".parse().unwrap();
+        assert_eq!(
+            h.format,
+            cv::PixelFormat::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE
+        );
+        assert_eq!(h.frame_size(), (16 + 16) * 2);
+
+        assert!(matches!(
+            "YUV4MPEG2 W4 H4 F1:1 Cmono".parse::<Header>(),
+            Err(Error::UnsupportedColorspace(c)) if c == "mono"
+        ));
+        assert!(matches!(
+            "YUV4MPEG2 W4 F1:1".parse::<Header>(),
+            Err(Error::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            "YUV4MPEG2 \u{e9}W2".parse::<Header>(),
+            Err(Error::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            "YUV4MPEG2 W4294967296 H4294967296 F1:1".parse::<Header>(),
+            Err(Error::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            Header::new(cv::PixelFormat::_32_BGRA, 4, 4, (1, 1)),
+            Err(Error::UnsupportedFormat(_))
+        ));
+        assert!(Header::is_y4m(b"YUV4MPEG2 W4"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let formats = [
+            cv::PixelFormat::_420V,
+            cv::PixelFormat::_422_YP_CB_CR_8_BI_PLANAR_FULL_RANGE,
+            cv::PixelFormat::_444_YP_CB_CR_8_BI_PLANAR_VIDEO_RANGE,
+            cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+            cv::PixelFormat::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            cv::PixelFormat::_444_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        ];
+        for format in formats {
+            let frames = [gradient(format, 6, 5), gradient(format, 6, 5)];
+            let mut header = Header::new(format, 6, 5, (30000, 1001)).unwrap();
+            header.extensions.push("VENDOR=cidre".into());
+            let mut writer = Writer::new(Vec::new(), header.clone()).unwrap();
+            for frame in &frames {
+                writer.write_frame(&frame.view()).unwrap();
+            }
+            let data = writer.into_inner();
+            let header_len = data.iter().position(|&b| b == b'\n').unwrap() + 1;
+            assert_eq!(data.len(), header_len + 2 * (6 + header.frame_size()));
+
+            let mut reader = Reader::new(&data[..]).unwrap();
+            assert_eq!(reader.header().format, format);
+            assert_eq!(reader.header().extensions, ["VENDOR=cidre"]);
+            for frame in &frames {
+                assert_same(&reader.read_frame().unwrap().unwrap(), frame);
+            }
+            assert_eq!(reader.frame_index(), 2);
+            assert!(reader.read_frame().unwrap().is_none());
+
+            let mut raw = RawWriter::new(Vec::new());
+            raw.write_frame(&frames[0].view()).unwrap();
+            let data = raw.into_inner();
+            let mut reader = RawReader::new(&data[..], format, 6, 5).unwrap();
+            assert_eq!(data.len(), reader.frame_size());
+            assert_same(&reader.read_frame().unwrap().unwrap(), &frames[0]);
+        }
+    }
+
+    #[test]
+    fn ten_bit_samples() {
+        let mut data = b"YUV4MPEG2 W2 H2 F25:1 C420p10\nFRAME\n".to_vec();
+        for v in [64u16, 940, 64, 940, 512, 960] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        let frame = Reader::new(&data[..])
+            .unwrap()
+            .read_frame()
+            .unwrap()
+            .unwrap();
+        let y = frame.plane(0).unwrap();
+        assert_eq!(y.pixel::<u16>(1, 0), 940 << 6);
+        let c = frame.plane(1).unwrap();
+        assert_eq!(c.pixel::<[u16; 2]>(0, 0), [512 << 6, 960 << 6]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut data = b"YUV4MPEG2 W2 H2 F25:1\nFRAME\n".to_vec();
+        data.extend_from_slice(&[16, 16, 16]);
+        let mut reader = Reader::new(&data[..]).unwrap();
+        assert!(matches!(reader.read_frame(), Err(Error::Io(_))));
+
+        let data = b"YUV4MPEG2 W2 H2 F25:1\nFRAMX\n";
+        let mut reader = Reader::new(&data[..]).unwrap();
+        assert!(matches!(reader.read_frame(), Err(Error::InvalidHeader(_))));
+
+        let mut reader = Reader::new(&b"YUV4MPEG2 W2 H2 F25:1\n"[..]).unwrap();
+        let mut other = cv::PixelImage::new(cv::PixelFormat::_420F, 2, 2).unwrap();
+        assert!(matches!(
+            reader.read_frame_into(&mut other.view_mut()),
+            Err(Error::Image(cv::PixelImageError::Mismatch))
+        ));
+    }
+}
@@ -0,0 +1,20 @@
+mod types;
+pub use types::Coordinate2d;
+pub use types::Origin;
+pub use types::Region;
+pub use types::ResId;
+pub use types::SamplePos;
+pub use types::Size;
+pub use types::Tiles as RegionTiles;
+
+mod pixel_format;
+pub use pixel_format::ComponentKind as PixelFormatComponentKind;
+pub use pixel_format::PixelFormat;
+pub use pixel_format::ViewClass as PixelFormatViewClass;
+
+mod texture;
+pub use texture::Type as TextureType;
+
+mod texture_layout;
+pub use texture_layout::Layout as TextureLayout;
+pub use texture_layout::LevelCopy as TextureLevelCopy;
@@ -0,0 +1,1182 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+#[repr(usize)]
+pub enum PixelFormat {
+    /// The default value of the pixel format for the mtl::RenderPipelineState.
+    /// You cannot create a texture with this value.
+    #[doc(alias = "MTLPixelFormatInvalid")]
+    Invalid = 0,
+
+    /* Normal 8 bit formats */
+    /// Ordinary format with one 8-bit normalized unsigned integer component
+    #[doc(alias = "MTLPixelFormatA8Unorm")]
+    A8UNorm = 1,
+
+    /// Ordinary format with one 8-bit normalized unsigned integer component
+    #[doc(alias = "MTLPixelFormatR8Unorm")]
+    R8UNorm = 10,
+
+    /// Ordinary format with one 8-bit normalized unsigned integer component with
+    /// conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatR8Unorm_sRGB")]
+    R8UNormSrgb = 11,
+
+    /// Ordinary format with one 8-bit normalized signed integer component.
+    #[doc(alias = "MTLPixelFormatR8Snorm")]
+    R8SNorm = 12,
+
+    /// Ordinary format with one 8-bit unsigned integer component.
+    #[doc(alias = "MTLPixelFormatR8Uint")]
+    R8UInt = 13,
+
+    /// Ordinary format with one 8-bit signed integer component.
+    #[doc(alias = "MTLPixelFormatR8Sint")]
+    R8SInt = 14,
+
+    /* Normal 16 bit formats */
+    /// Ordinary format with one 16-bit normalized unsigned integer component.
+    #[doc(alias = "MTLPixelFormatR16Unorm")]
+    R16UNorm = 20,
+
+    /// Ordinary format with one 16-bit normalized signed integer component.
+    #[doc(alias = "MTLPixelFormatR16Snorm")]
+    R16SNorm = 22,
+
+    /// Ordinary format with one 16-bit unsigned integer component.
+    #[doc(alias = "MTLPixelFormatR16Uint")]
+    R16UInt = 23,
+
+    /// Ordinary format with one 16-bit signed integer component.
+    #[doc(alias = "MTLPixelFormatR16Sint")]
+    R16SInt = 24,
+
+    /// Ordinary format with one 16-bit floating-point component.
+    #[doc(alias = "MTLPixelFormatR16Float")]
+    R16Float = 25,
+
+    /// Ordinary format with two 8-bit normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatRG8Unorm")]
+    Rg8UNorm = 30,
+
+    /// Ordinary format with two 8-bit normalized unsigned integer components
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatRG8Unorm_sRGB")]
+    Rg8UNormSrgb = 31,
+
+    /// Ordinary format with two 8-bit normalized signed integer components.
+    #[doc(alias = "MTLPixelFormatRG8Snorm")]
+    Rg8SNorm = 32,
+
+    /// Ordinary format with two 8-bit unsigned integer components.
+    #[doc(alias = "MTLPixelFormatRG8Uint")]
+    Rg8UInt = 33,
+
+    /// Ordinary format with two 8-bit signed integer components.
+    #[doc(alias = "MTLPixelFormatRG8Sint")]
+    Rg8SInt = 34,
+
+    /* Packed 16 bit formats */
+    /// Packed 16-bit format with normalized unsigned integer color components:
+    /// 5 bits for blue, 6 bits for green, 5 bits for red, packed into 16 bits.
+    #[doc(alias = "MTLPixelFormatB5G6R5Unorm")]
+    B5G6R5UNorm = 40,
+
+    /// Packed 16-bit format with normalized unsigned integer color components:
+    /// 5 bits each for BGR and 1 for alpha, packed into 16 bits.
+    #[doc(alias = "MTLPixelFormatA1BGR5Unorm")]
+    A1Bgr5UNorm = 41,
+
+    /// Packed 16-bit format with normalized unsigned integer color components:
+    /// 4 bits each for ABGR, packed into 16 bits.
+    #[doc(alias = "MTLPixelFormatABGR4Unorm")]
+    Abgr4UNorm = 42,
+
+    /// Packed 16-bit format with normalized unsigned integer color components:
+    /// 5 bits each for BGR and 1 for alpha, packed into 16 bits.
+    #[doc(alias = "MTLPixelFormatBGR5A1Unorm")]
+    Bgr5A1UNorm = 43,
+
+    /* Normal 32 bit formats */
+    /// Ordinary format with one 32-bit unsigned integer component.
+    #[doc(alias = "MTLPixelFormatR32Uint")]
+    R32UInt = 53,
+
+    /// Ordinary format with one 32-bit signed integer component.
+    #[doc(alias = "MTLPixelFormatR32Sint")]
+    R32SInt = 54,
+
+    /// Ordinary format with one 32-bit floating-point component.
+    #[doc(alias = "MTLPixelFormatR32Float")]
+    R32Float = 55,
+
+    /// Ordinary format with two 16-bit normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatRG16Unorm")]
+    Rg16UNorm = 60,
+
+    /// Ordinary format with two 16-bit normalized signed integer components.
+    #[doc(alias = "MTLPixelFormatRG16Snorm")]
+    Rg16SNorm = 62,
+
+    /// Ordinary format with two 16-bit unsigned integer components.
+    #[doc(alias = "MTLPixelFormatRG16Uint")]
+    Rg16UInt = 63,
+
+    /// Ordinary format with two 16-bit signed integer components.
+    #[doc(alias = "MTLPixelFormatRG16Sint")]
+    Rg16SInt = 64,
+
+    /// Ordinary format with two 16-bit floating-point components.
+    #[doc(alias = "MTLPixelFormatRG16Float")]
+    Rg16Float = 65,
+
+    /// Ordinary format with four 8-bit normalized unsigned integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA8Unorm")]
+    Rgba8UNorm = 70,
+
+    /// Ordinary format with four 8-bit normalized unsigned integer components in RGBA order with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatRGBA8Unorm_sRGB")]
+    Rgba8UNormSrgb = 71,
+
+    /// Ordinary format with four 8-bit normalized signed integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA8Snorm")]
+    Rgba8SNorm = 72,
+
+    /// Ordinary format with four 8-bit unsigned integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA8Sint")]
+    Rgba8UInt = 73,
+
+    /// Ordinary format with four 8-bit signed integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA8Sint")]
+    Rgba8SInt = 74,
+
+    /// Ordinary format with four 8-bit normalized unsigned integer components in BGRA order.
+    #[doc(alias = "MTLPixelFormatBGRA8Unorm")]
+    Bgra8UNorm = 80,
+
+    /// Ordinary format with four 8-bit normalized unsigned integer components in BGRA order
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatBGRA8Unorm_sRGB")]
+    Bgra8UNormSrgb = 81,
+
+    /* Packed 32 bit formats */
+    /// A 32-bit packed pixel format with four normalized unsigned integer components:
+    /// 10-bit red, 10-bit green, 10-bit blue, and 2-bit alpha.
+    #[doc(alias = "MTLPixelFormatRGB10A2Unorm")]
+    Rgb10A2UNorm = 90,
+
+    /// A 32-bit packed pixel format with four unsigned integer components: 10-bit red, 10-bit green, 10-bit blue, and 2-bit alpha.
+    ///
+    /// Pixel data is stored in red, green, blue, and alpha order, from least significant bit to most significant bit.
+    #[doc(alias = "MTLPixelFormatRGB10A2Uint")]
+    Rgb10A2UInt = 91,
+
+    /// 32-bit format with floating-point color components, 11 bits each for red and green and 10 bits for blue.
+    #[doc(alias = "MTLPixelFormatRG11B10Float")]
+    Rg11B10Float = 92,
+
+    /// Packed 32-bit format with floating-point color components: 9 bits each for RGB and 5 bits for an exponent
+    /// shared by RGB, packed into 32 bits.
+    #[doc(alias = "MTLPixelFormatRGB9E5Float")]
+    Rgb9E5Float = 93,
+
+    /// A 32-bit packed pixel format with four normalized unsigned integer components:
+    /// 10-bit blue, 10-bit green, 10-bit red, and 2-bit alpha.
+    #[doc(alias = "MTLPixelFormatBGR10A2Unorm")]
+    Bgr10A2UNorm = 94,
+
+    /// A 32-bit extended range pixel format with three fixed-point components:
+    /// 10-bit blue, 10-bit green, and 10-bit red.
+    ///
+    /// Pixel data is stored in blue, green, and red order, from least significant bit
+    /// to most significant bit. Bits 30 and 31 are used as padding, and their value is 0.
+    ///
+    /// The blue, green, and red components are linearly encoded, and their values range
+    /// from -0.752941 to 1.25098.
+    ///
+    /// To display wide color values on devices with wide color displays, you can set this
+    /// pixel format on the colorPixelFormat property of an 'mtk::View' or the pixel_format
+    /// property of a [`crate::ca::MetalLayer`].
+    #[doc(alias = "MTLPixelFormatBGR10_XR")]
+    Bgr10Xr = 554,
+
+    /// A 32-bit extended range pixel format with sRGB conversion and three fixed-point components:
+    /// 10-bit blue, 10-bit green, and 10-bit red.
+    ///
+    /// Pixel data is stored in blue, green, and red order, from least significant bit to most
+    /// significant bit. Bits 30 and 31 are used as padding, and their value is 0.
+    ///
+    /// The blue, green, and red components are gamma encoded, and their values range
+    /// from -0.5271 to 1.66894, before gamma expansion.
+    ///
+    /// To display wide color values on devices with wide color displays, you can set this pixel
+    /// format on the colorPixelFormat property of an 'mtk::View' or the pixel_format property of
+    /// a [`crate::ca::MetalLayer`]. You must also use specify an extended sRGB color space.
+    #[doc(alias = "MTLPixelFormatBGR10_XR_sRGB")]
+    Bgr10XrSrgb = 555,
+
+    /* Normal 64 bit formats */
+    /// Ordinary format with two 32-bit unsigned integer components.
+    #[doc(alias = "MTLPixelFormatRG32Uint")]
+    Rg32UInt = 103,
+
+    /// Ordinary format with two 32-bit signed integer components.
+    #[doc(alias = "MTLPixelFormatRG32Sint")]
+    Rg32SInt = 104,
+
+    /// Ordinary format with two 32-bit floating-point components.
+    #[doc(alias = "MTLPixelFormatRG32Float")]
+    Rg32Float = 105,
+
+    /// Ordinary format with four 16-bit normalized unsigned integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA16Unorm")]
+    Rgba16UNorm = 110,
+
+    /// Ordinary format with four 16-bit normalized signed integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA16Snorm")]
+    Rgba16SNorm = 112,
+
+    /// Ordinary format with four 16-bit unsigned integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA16Uint")]
+    Rgba16UInt = 113,
+
+    /// Ordinary format with four 16-bit signed integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA16Sint")]
+    Rgba16SInt = 114,
+
+    /// Ordinary format with four 16-bit floating-point components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA16Float")]
+    Rgba16Float = 115,
+
+    /// A 64-bit extended range pixel format with four fixed-point components:
+    /// 10-bit blue, 10-bit green, 10-bit red, and 10-bit alpha.
+    ///
+    /// Pixel data is stored in blue, green, red, and alpha order, from least significant
+    /// bit to most significant bit. Each component is stored in a 16-bit chunk that's arranged
+    /// as follows:
+    ///
+    /// - The 10 left-most bits store the component's data.
+    /// - The 6 right-most bits are used as padding, and their value is 0.
+    ///
+    /// The blue, green, and red components are linearly encoded, and their values range
+    /// from -0.752941 to 1.25098. The alpha component is always clamped to a \[0.0, 1.0\] range
+    /// in sampling, rendering, and writing operations, despite supporting values outside this range.
+    ///
+    /// To display wide color values on devices with wide color displays, you can set this pixel
+    /// format on the colorPixelFormat property of an 'mtk::View' or the pixelFormat property of
+    /// a [`crate::ca::MetalLayer`].
+    #[doc(alias = "MTLPixelFormatBGRA10_XR")]
+    Bgra10Xr = 552,
+
+    /// A 64-bit extended range pixel format with sRGB conversion and four fixed-point components:
+    /// 10-bit blue, 10-bit green, 10-bit red, and 10-bit alpha.
+    ///
+    /// Pixel data is stored in blue, green, red, and alpha order, from least significant bit to most
+    /// significant bit. Each component is stored in a 16-bit chunk that's arranged as follows:
+    ///
+    /// - The 10 left-most bits store the component's data.
+    /// - The 6 right-most bits are used as padding, and their value is 0.
+    ///
+    /// The blue, green, and red components are gamma encoded, and their values range from
+    /// -0.5271 to 1.66894, before gamma expansion. The alpha component is always clamped
+    /// to a \[0.0, 1.0\] range in sampling, rendering, and writing operations, despite supporting
+    /// values outside this range.
+    ///
+    /// To display wide color values on devices with wide color displays, you can set this
+    /// pixel format on the colorPixelFormat property of an 'mtk::View' or the pixelFormat property
+    /// of a [`crate::ca::MetalLayer`]. You must also use specify an extended sRGB color space.
+    #[doc(alias = "MTLPixelFormatBGRA10_XR_sRGB")]
+    Bgra10XrSrgb = 553,
+
+    /* Normal 128 bit formats */
+    /// Ordinary format with four 32-bit unsigned integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA32Uint")]
+    Rgba32UInt = 123,
+
+    /// Ordinary format with four 32-bit signed integer components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA32Sint")]
+    Rgba32SInt = 124,
+
+    /// Ordinary format with four 32-bit floating-point components in RGBA order.
+    #[doc(alias = "MTLPixelFormatRGBA32Float")]
+    Rgba32Float = 125,
+
+    /* Compressed formats. */
+    /// Compressed format with two 16-bit color components and one 32-bit descriptor component.
+    #[doc(alias = "MTLPixelFormatBC1_RGBA")]
+    Bc1Rgba = 130,
+
+    /// Compressed format with two 16-bit color components and one 32-bit descriptor component,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatBC1_RGBA_sRGB")]
+    Bc1RgbaSrgb = 131,
+
+    /// Compressed format with two 64-bit chunks.
+    ///
+    /// The first chunk contains two 8-bit alpha components and one 48-bit descriptor component.
+    /// The second chunk contains two 16-bit color components and one 32-bit descriptor component.
+    #[doc(alias = "MTLPixelFormatBC2_RGBA")]
+    Bc2Rgba = 132,
+
+    /// Compressed format with two 64-bit chunks, with conversion between sRGB and linear space.
+    ///
+    /// The first chunk contains two 8-bit alpha components and one 48-bit descriptor component.
+    /// The second chunk contains two 16-bit color components and one 32-bit descriptor component.
+    #[doc(alias = "MTLPixelFormatBC2_RGBA_sRGB")]
+    Bc2RgbaSrgb = 133,
+
+    /// Compressed format with two 64-bit chunks.
+    ///
+    /// The first chunk contains two 8-bit alpha components and one 48-bit descriptor component.
+    /// The second chunk contains two 16-bit color components and one 32-bit descriptor component.
+    #[doc(alias = "MTLPixelFormatBC3_RGBA")]
+    Bc3Rgba = 134,
+
+    /// Compressed format with two 64-bit chunks, with conversion between sRGB and linear space.
+    ///
+    /// The first chunk contains two 8-bit alpha components and one 48-bit descriptor component.
+    /// The second chunk contains two 16-bit color components and one 32-bit descriptor component.
+    #[doc(alias = "MTLPixelFormatBC3_RGBA_sRGB")]
+    Bc3RgbaSrgb = 135,
+
+    /// Compressed format with one normalized unsigned integer component.
+    #[doc(alias = "MTLPixelFormatBC4_RUnorm")]
+    Bc4RUNorm = 140,
+
+    /// Compressed format with one normalized signed integer component.
+    #[doc(alias = "MTLPixelFormatBC4_RSnorm")]
+    Bc4RSNorm = 141,
+
+    /// Compressed format with two normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatBC5_RGUnorm")]
+    Bc5RgUNorm = 142,
+
+    /// Compressed format with two normalized signed integer components.
+    #[doc(alias = "MTLPixelFormatBC5_RGSnorm")]
+    Bc5RgSNorm = 143,
+
+    /// Compressed format with four floating-point components.
+    #[doc(alias = "MTLPixelFormatBC6H_RGBFloat")]
+    Bc6HRgbFloat = 150,
+
+    /// Compressed format with four unsigned floating-point components.
+    #[doc(alias = "MTLPixelFormatBC6H_RGBUfloat")]
+    Bc6HRgbUFloat = 151,
+
+    /// Compressed format with four normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatBC7_RGBAUnorm")]
+    Bc7RgbaUNorm = 152,
+
+    /// Compressed format with four normalized unsigned integer components,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatBC7_RGBAUnorm_sRGB")]
+    Bc7RgbaUNormSrgb = 153,
+
+    /// PVRTC-compressed format with three color components, 2 bits per pixel.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_2BPP")]
+    PvrtcRgb2Bpp = 160,
+
+    /// PVRTC-compressed format with three color components, 2 bits per pixel,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_2BPP_sRGB")]
+    PvrtcRgb2BppSrgb = 161,
+
+    /// PVRTC-compressed format with three color components, 4 bits per pixel.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_4BPP")]
+    PvrtcRgb4Bpp = 162,
+
+    /// PVRTC-compressed format with three color components, 4 bits per pixel,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_4BPP_sRGB")]
+    PvrtcRgb4BppSrgb = 163,
+
+    /// PVRTC-compressed format with four components, 2 bits per pixel.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_2BPP")]
+    PvrtcRgba2Bpp = 164,
+
+    /// PVRTC-compressed format with four components, 2 bits per pixel,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_2BPP_sRGB")]
+    PvrtcRgba2BppSrgb = 165,
+
+    /// PVRTC-compressed format with four components, 4 bits per pixel.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_4BPP")]
+    PvrtcRgba4Bpp = 166,
+
+    /// PVRTC-compressed format with four components, 4 bits per pixel,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_4BPP_sRGB")]
+    PvrtcRgba4BppSrgb = 167,
+
+    /// EAC-compressed format with one normalized unsigned integer component.
+    #[doc(alias = "MTLPixelFormatEAC_R11Unorm")]
+    EacR11UNorm = 170,
+
+    /// EAC-compressed format with one normalized signed integer component.
+    #[doc(alias = "MTLPixelFormatEAC_R11Snorm")]
+    EacR11SNorm = 172,
+
+    /// EAC-compressed format with two normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatEAC_RG11Unorm")]
+    EacRg11UNorm = 174,
+
+    /// EAC-compressed format with two normalized signed integer components.
+    #[doc(alias = "MTLPixelFormatEAC_RG11Snorm")]
+    EacRg11SNorm = 176,
+
+    /// EAC-compressed format with four normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatEAC_RGBA8")]
+    EacRgba8 = 178,
+
+    /// EAC-compressed format with four normalized unsigned integer components,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatEAC_RGBA8_sRGB")]
+    EacRgba8Srgb = 179,
+
+    /// ETC2-compressed format with three normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatETC2_RGB8")]
+    Etc2Rgb8 = 180,
+
+    /// ETC2-compressed format with three normalized unsigned integer components,
+    /// with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatETC2_RGB8_sRGB")]
+    Etc2Rgb8Srgb = 181,
+
+    /// ETC2-compressed format with four normalized unsigned integer components
+    /// and 1-bit alpha.
+    #[doc(alias = "MTLPixelFormatETC2_RGB8A1")]
+    Etc2Rgb8A1 = 182,
+
+    /// ETC2-compressed format with four normalized unsigned integer components
+    /// and 1-bit alpha, with conversion between sRGB and linear space.
+    #[doc(alias = "MTLPixelFormatETC2_RGB8A1_sRGB")]
+    Etc2Rgb8A1Srgb = 183,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 4, and a block height of 4.
+    #[doc(alias = "MTLPixelFormatASTC_4x4_sRGB")]
+    Astc4x4Srgb = 186,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 5, and a block height of 4.
+    #[doc(alias = "MTLPixelFormatASTC_5x4_sRGB")]
+    Astc5x4Srgb = 187,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 5, and a block height of 5.
+    #[doc(alias = "MTLPixelFormatASTC_5x5_sRGB")]
+    Astc5x5Srgb = 188,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 6, and a block height of 5.
+    #[doc(alias = "MTLPixelFormatASTC_6x5_sRGB")]
+    Astc6x5Srgb = 189,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 6, and a block height of 6.
+    #[doc(alias = "MTLPixelFormatASTC_6x6_sRGB")]
+    Astc6x6Srgb = 190,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 8, and a block height of 5.
+    #[doc(alias = "MTLPixelFormatASTC_8x5_sRGB")]
+    Astc8x5Srgb = 192,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 8, and a block height of 6.
+    #[doc(alias = "MTLPixelFormatASTC_8x6_sRGB")]
+    Astc8x6Srgb = 193,
+
+    /// ASTC-compressed format with low-dynamic-range content,
+    /// conversion between sRGB and linear space, a block width of 8, and a block height of 8.
+    #[doc(alias = "MTLPixelFormatASTC_8x8_sRGB")]
+    Astc8x8Srgb = 194,
+    Astc10x5Srgb = 195,
+    Astc10x6Srgb = 196,
+    Astc10x8Srgb = 197,
+    Astc10x10Srgb = 198,
+    Astc12x10Srgb = 199,
+    Astc12x12Srgb = 200,
+
+    Astc4x4Ldr = 204,
+    Astc5x4Ldr = 205,
+    Astc5x5Ldr = 206,
+    Astc6x5Ldr = 207,
+    Astc6x6Ldr = 208,
+    Astc8x5Ldr = 210,
+    Astc8x6Ldr = 211,
+    Astc8x8Ldr = 212,
+    Astc10x5Ldr = 213,
+    Astc10x6Ldr = 214,
+    Astc10x8Ldr = 215,
+    Astc10x10Ldr = 216,
+    Astc12x10Ldr = 217,
+    Astc12x12Ldr = 218,
+
+    Astc4x4Hdr = 222,
+    Astc5x4Hdr = 223,
+    Astc5x5Hdr = 224,
+    Astc6x5Hdr = 225,
+    Astc6x6Hdr = 226,
+    Astc8x5Hdr = 228,
+    Astc8x6Hdr = 229,
+    Astc8x8Hdr = 230,
+    Astc10x5Hdr = 231,
+    Astc10x6Hdr = 232,
+    Astc10x8Hdr = 233,
+    Astc10x10Hdr = 234,
+    Astc12x10Hdr = 235,
+    Asrc12x12Hdr = 236,
+
+    /// A pixel format where the red and green channels are subsampled horizontally.
+    /// Two pixels are stored in 32 bits, with shared red and blue values, and unique green values.
+    ///
+    /// This format is equivalent to YUY2, YUYV, yuvs, or GL_RGB_422_APPLE/GL_UNSIGNED_SHORT_8_8_REV_APPLE.
+    /// The component order, from lowest addressed byte to highest, is Y0, Cb, Y1, Cr.
+    /// There is no implicit colorspace conversion from YUV to RGB, the shader will receive (Cr, Y, Cb, 1).
+    /// 422 textures must have a width that is a multiple of 2, and can only be used for 2D non-mipmap textures.
+    /// When sampling, ClampToEdge is the only usable wrap mode.
+    ///
+    /// This format is a compressed format with a block size of 2x1 in a 32-bit block.
+    #[doc(alias = "MTLPixelFormatGBGR422")]
+    Gbgr422 = 240,
+
+    /// A pixel format where the red and green channels are subsampled horizontally.
+    /// Two pixels are stored in 32 bits, with shared red and blue values, and unique green values.
+    ///
+    /// This format is equivalent to UYVY, 2vuy, or GL_RGB_422_APPLE/GL_UNSIGNED_SHORT_8_8_APPLE.
+    /// The component order, from lowest addressed byte to highest, is Cb, Y0, Cr, Y1.
+    /// There is no implicit colorspace conversion from YUV to RGB, the shader will receive (Cr, Y, Cb, 1).
+    /// 422 textures must have a width that is a multiple of 2, and can only be used for 2D non-mipmap textures.
+    /// When sampling, ClampToEdge is the only usable wrap mode.
+    ///
+    /// This format is a compressed format with a block size of 2x1 in a 32-bit block.
+    #[doc(alias = "MTLPixelFormatBGRG422")]
+    Bgrg422 = 241,
+
+    /// A pixel format with a 16-bit normalized unsigned integer component, used for a depth render target.
+    #[doc(alias = "MTLPixelFormatDepth16Unorm")]
+    Depth16Unorm = 250,
+
+    /// A pixel format with one 32-bit floating-point component, used for a depth render target.
+    #[doc(alias = "MTLPixelFormatDepth32Float")]
+    Depth32Float = 252,
+
+    /// A pixel format with an 8-bit unsigned integer component, used for a stencil render target.
+    #[doc(alias = "MTLPixelFormatStencil8")]
+    Stencil8 = 253,
+
+    /// A 32-bit combined depth and stencil pixel format with a 24-bit normalized unsigned integer for depth and an 8-bit unsigned integer for stencil.
+    #[doc(alias = "MTLPixelFormatDepth24Unorm_Stencil8")]
+    Depth24UnormStencil8 = 255,
+
+    /// A 40-bit combined depth and stencil pixel format with a 32-bit floating-point value for depth and an 8-bit unsigned integer for stencil.
+    ///
+    /// When using this format, some Metal device objects allocate 64-bits per pixel.
+    #[doc(alias = "MTLPixelFormatDepth32Float_Stencil8")]
+    Depth32FloatStencil8 = 260,
+
+    /// A stencil pixel format used to read the stencil value from a texture with a combined 32-bit
+    /// depth and 8-bit stencil value.
+    ///
+    /// You can't directly read the stencil value of a texture with the `mtl::PixelFormat::Depth32FloatStencil8`
+    /// format. To read stencil values from a texture with the `mtl::PixelFormat::Depth32FloatStencil8` format,
+    /// create a texture view of that texture using the `mtl::PixelFormat::X32Stencil8` format, and sample the
+    /// texture view instead.
+    #[doc(alias = "MTLPixelFormatX32_Stencil8")]
+    X32Stencil8 = 261,
+
+    /// A stencil pixel format used to read the stencil value from a texture with a combined 24-bit
+    /// depth and 8-bit stencil value.
+    ///
+    /// You can't directly read the stencil value of a texture with the `mtl::PixelFormat::Depth24UnormStencil8`
+    /// format. To read stencil values from a texture with the `mtl::PixelFormat::Depth24UnormStencil8` format,
+    /// create a texture view of that texture using the `mtl::PixelFormat::X24Stencil8` format,
+    /// and sample the texture view instead.
+    #[doc(alias = "MTLPixelFormatX24_Stencil8")]
+    X24Stencil8 = 262,
+}
+
+/// How component values are stored and read in shaders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentKind {
+    UNorm,
+    SNorm,
+    UInt,
+    SInt,
+    Float,
+}
+
+/// Texture view compatibility class.
+///
+/// A texture created with `mtl::TextureUsage::PIXEL_FORMAT_VIEW` can be viewed
+/// with any pixel format of the same class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ViewClass {
+    /// Ordinary and packed formats with the same bits per pixel are freely reinterpretable.
+    Ordinary { bits: usize },
+
+    /// Compressed and subsampled formats can only be viewed as their sRGB or linear
+    /// counterpart. Holds the linear format.
+    Block(PixelFormat),
+
+    /// Combined depth/stencil formats can be viewed with the matching `X*Stencil8`
+    /// format. Holds the combined format.
+    DepthStencil(PixelFormat),
+}
+
+#[derive(Clone, Copy)]
+struct Layout {
+    bytes_per_block: usize,
+    block_width: usize,
+    block_height: usize,
+    components: usize,
+    kind: ComponentKind,
+}
+
+const fn l(bytes_per_block: usize, components: usize, kind: ComponentKind) -> Layout {
+    Layout {
+        bytes_per_block,
+        block_width: 1,
+        block_height: 1,
+        components,
+        kind,
+    }
+}
+
+const fn b(
+    bytes_per_block: usize,
+    block_width: usize,
+    block_height: usize,
+    components: usize,
+    kind: ComponentKind,
+) -> Layout {
+    Layout {
+        bytes_per_block,
+        block_width,
+        block_height,
+        components,
+        kind,
+    }
+}
+
+impl PixelFormat {
+    fn layout(&self) -> Option<Layout> {
+        use ComponentKind::*;
+        use PixelFormat::*;
+
+        Some(match self {
+            Invalid => return None,
+            A8UNorm | R8UNorm | R8UNormSrgb => l(1, 1, UNorm),
+            R8SNorm => l(1, 1, SNorm),
+            R8UInt | Stencil8 => l(1, 1, UInt),
+            R8SInt => l(1, 1, SInt),
+
+            R16UNorm | Depth16Unorm => l(2, 1, UNorm),
+            R16SNorm => l(2, 1, SNorm),
+            R16UInt => l(2, 1, UInt),
+            R16SInt => l(2, 1, SInt),
+            R16Float => l(2, 1, Float),
+            Rg8UNorm | Rg8UNormSrgb => l(2, 2, UNorm),
+            Rg8SNorm => l(2, 2, SNorm),
+            Rg8UInt => l(2, 2, UInt),
+            Rg8SInt => l(2, 2, SInt),
+            B5G6R5UNorm => l(2, 3, UNorm),
+            A1Bgr5UNorm | Abgr4UNorm | Bgr5A1UNorm => l(2, 4, UNorm),
+
+            R32UInt => l(4, 1, UInt),
+            R32SInt => l(4, 1, SInt),
+            R32Float | Depth32Float => l(4, 1, Float),
+            Rg16UNorm => l(4, 2, UNorm),
+            Rg16SNorm => l(4, 2, SNorm),
+            Rg16UInt => l(4, 2, UInt),
+            Rg16SInt => l(4, 2, SInt),
+            Rg16Float => l(4, 2, Float),
+            Rgba8UNorm | Rgba8UNormSrgb | Bgra8UNorm | Bgra8UNormSrgb => l(4, 4, UNorm),
+            Rgba8SNorm => l(4, 4, SNorm),
+            Rgba8UInt => l(4, 4, UInt),
+            Rgba8SInt => l(4, 4, SInt),
+            Rgb10A2UNorm | Bgr10A2UNorm => l(4, 4, UNorm),
+            Rgb10A2UInt => l(4, 4, UInt),
+            Rg11B10Float | Rgb9E5Float => l(4, 3, Float),
+            Bgr10Xr | Bgr10XrSrgb => l(4, 3, UNorm),
+            Depth24UnormStencil8 => l(4, 2, UNorm),
+            X24Stencil8 => l(4, 1, UInt),
+
+            Rg32UInt => l(8, 2, UInt),
+            Rg32SInt => l(8, 2, SInt),
+            Rg32Float => l(8, 2, Float),
+            Rgba16UNorm => l(8, 4, UNorm),
+            Rgba16SNorm => l(8, 4, SNorm),
+            Rgba16UInt => l(8, 4, UInt),
+            Rgba16SInt => l(8, 4, SInt),
+            Rgba16Float => l(8, 4, Float),
+            Bgra10Xr | Bgra10XrSrgb => l(8, 4, UNorm),
+            // 40 bits, but allocated as 64 bits per pixel
+            Depth32FloatStencil8 => l(8, 2, Float),
+            X32Stencil8 => l(8, 1, UInt),
+
+            Rgba32UInt => l(16, 4, UInt),
+            Rgba32SInt => l(16, 4, SInt),
+            Rgba32Float => l(16, 4, Float),
+
+            Bc1Rgba | Bc1RgbaSrgb => b(8, 4, 4, 4, UNorm),
+            Bc2Rgba | Bc2RgbaSrgb | Bc3Rgba | Bc3RgbaSrgb => b(16, 4, 4, 4, UNorm),
+            Bc4RUNorm => b(8, 4, 4, 1, UNorm),
+            Bc4RSNorm => b(8, 4, 4, 1, SNorm),
+            Bc5RgUNorm => b(16, 4, 4, 2, UNorm),
+            Bc5RgSNorm => b(16, 4, 4, 2, SNorm),
+            Bc6HRgbFloat | Bc6HRgbUFloat => b(16, 4, 4, 3, Float),
+            Bc7RgbaUNorm | Bc7RgbaUNormSrgb => b(16, 4, 4, 4, UNorm),
+
+            PvrtcRgb2Bpp | PvrtcRgb2BppSrgb => b(8, 8, 4, 3, UNorm),
+            PvrtcRgb4Bpp | PvrtcRgb4BppSrgb => b(8, 4, 4, 3, UNorm),
+            PvrtcRgba2Bpp | PvrtcRgba2BppSrgb => b(8, 8, 4, 4, UNorm),
+            PvrtcRgba4Bpp | PvrtcRgba4BppSrgb => b(8, 4, 4, 4, UNorm),
+
+            EacR11UNorm => b(8, 4, 4, 1, UNorm),
+            EacR11SNorm => b(8, 4, 4, 1, SNorm),
+            EacRg11UNorm => b(16, 4, 4, 2, UNorm),
+            EacRg11SNorm => b(16, 4, 4, 2, SNorm),
+            EacRgba8 | EacRgba8Srgb => b(16, 4, 4, 4, UNorm),
+            Etc2Rgb8 | Etc2Rgb8Srgb => b(8, 4, 4, 3, UNorm),
+            Etc2Rgb8A1 | Etc2Rgb8A1Srgb => b(8, 4, 4, 4, UNorm),
+
+            Astc4x4Srgb | Astc4x4Ldr => b(16, 4, 4, 4, UNorm),
+            Astc5x4Srgb | Astc5x4Ldr => b(16, 5, 4, 4, UNorm),
+            Astc5x5Srgb | Astc5x5Ldr => b(16, 5, 5, 4, UNorm),
+            Astc6x5Srgb | Astc6x5Ldr => b(16, 6, 5, 4, UNorm),
+            Astc6x6Srgb | Astc6x6Ldr => b(16, 6, 6, 4, UNorm),
+            Astc8x5Srgb | Astc8x5Ldr => b(16, 8, 5, 4, UNorm),
+            Astc8x6Srgb | Astc8x6Ldr => b(16, 8, 6, 4, UNorm),
+            Astc8x8Srgb | Astc8x8Ldr => b(16, 8, 8, 4, UNorm),
+            Astc10x5Srgb | Astc10x5Ldr => b(16, 10, 5, 4, UNorm),
+            Astc10x6Srgb | Astc10x6Ldr => b(16, 10, 6, 4, UNorm),
+            Astc10x8Srgb | Astc10x8Ldr => b(16, 10, 8, 4, UNorm),
+            Astc10x10Srgb | Astc10x10Ldr => b(16, 10, 10, 4, UNorm),
+            Astc12x10Srgb | Astc12x10Ldr => b(16, 12, 10, 4, UNorm),
+            Astc12x12Srgb | Astc12x12Ldr => b(16, 12, 12, 4, UNorm),
+            Astc4x4Hdr => b(16, 4, 4, 4, Float),
+            Astc5x4Hdr => b(16, 5, 4, 4, Float),
+            Astc5x5Hdr => b(16, 5, 5, 4, Float),
+            Astc6x5Hdr => b(16, 6, 5, 4, Float),
+            Astc6x6Hdr => b(16, 6, 6, 4, Float),
+            Astc8x5Hdr => b(16, 8, 5, 4, Float),
+            Astc8x6Hdr => b(16, 8, 6, 4, Float),
+            Astc8x8Hdr => b(16, 8, 8, 4, Float),
+            Astc10x5Hdr => b(16, 10, 5, 4, Float),
+            Astc10x6Hdr => b(16, 10, 6, 4, Float),
+            Astc10x8Hdr => b(16, 10, 8, 4, Float),
+            Astc10x10Hdr => b(16, 10, 10, 4, Float),
+            Astc12x10Hdr => b(16, 12, 10, 4, Float),
+            Asrc12x12Hdr => b(16, 12, 12, 4, Float),
+
+            Gbgr422 | Bgrg422 => b(4, 2, 1, 3, UNorm),
+        })
+    }
+
+    /// Bytes per block, or per pixel for ordinary formats. 0 for `Invalid`.
+    #[inline]
+    pub fn bytes_per_block(&self) -> usize {
+        self.layout().map_or(0, |l| l.bytes_per_block)
+    }
+
+    /// Bytes per pixel, `None` for block-compressed, subsampled and invalid formats.
+    #[inline]
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        let l = self.layout()?;
+        if l.block_width == 1 && l.block_height == 1 {
+            Some(l.bytes_per_block)
+        } else {
+            None
+        }
+    }
+
+    /// Block width and height in pixels, `(1, 1)` for ordinary formats.
+    #[inline]
+    pub fn block_size(&self) -> (usize, usize) {
+        self.layout()
+            .map_or((1, 1), |l| (l.block_width, l.block_height))
+    }
+
+    /// Block-compressed (BC, ASTC, ETC2, EAC, PVRTC) or subsampled (422) format.
+    #[inline]
+    pub fn is_compressed(&self) -> bool {
+        self.block_size() != (1, 1)
+    }
+
+    #[inline]
+    pub fn is_pvrtc(&self) -> bool {
+        matches!(
+            self,
+            Self::PvrtcRgb2Bpp
+                | Self::PvrtcRgb2BppSrgb
+                | Self::PvrtcRgb4Bpp
+                | Self::PvrtcRgb4BppSrgb
+                | Self::PvrtcRgba2Bpp
+                | Self::PvrtcRgba2BppSrgb
+                | Self::PvrtcRgba4Bpp
+                | Self::PvrtcRgba4BppSrgb
+        )
+    }
+
+    /// Tightly packed bytes per row for `width` pixels, to be used with
+    /// `mtl::Texture::replace_region` and blit copies from `mtl::Buf`.
+    ///
+    /// Metal expects 0 bytes per row for PVRTC formats.
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// assert_eq!(mtl::PixelFormat::Bgra8UNorm.bytes_per_row(100), 400);
+    /// assert_eq!(mtl::PixelFormat::Bc1Rgba.bytes_per_row(100), 200);
+    /// assert_eq!(mtl::PixelFormat::Astc6x6Ldr.bytes_per_row(100), 272);
+    /// ```
+    #[inline]
+    pub fn bytes_per_row(&self, width: usize) -> usize {
+        let Some(l) = self.layout() else {
+            return 0;
+        };
+        width.div_ceil(l.block_width) * l.bytes_per_block
+    }
+
+    /// Tightly packed bytes per 2D image of `width` x `height` pixels.
+    ///
+    /// PVRTC images are at least 2x2 blocks.
+    pub fn bytes_per_image(&self, width: usize, height: usize) -> usize {
+        let Some(l) = self.layout() else {
+            return 0;
+        };
+        let mut cols = width.div_ceil(l.block_width);
+        let mut rows = height.div_ceil(l.block_height);
+        if self.is_pvrtc() {
+            cols = cols.max(2);
+            rows = rows.max(2);
+        }
+        cols * rows * l.bytes_per_block
+    }
+
+    /// Number of components: 1 for `R8UNorm`, 3 for `Bc6HRgbFloat`,
+    /// 2 for combined depth/stencil formats.
+    #[inline]
+    pub fn component_count(&self) -> usize {
+        self.layout().map_or(0, |l| l.components)
+    }
+
+    /// Component type of the format, depth for combined depth/stencil formats.
+    #[inline]
+    pub fn component_kind(&self) -> Option<ComponentKind> {
+        Some(self.layout()?.kind)
+    }
+
+    #[inline]
+    pub fn is_normalized(&self) -> bool {
+        matches!(
+            self.component_kind(),
+            Some(ComponentKind::UNorm | ComponentKind::SNorm)
+        )
+    }
+
+    #[inline]
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.component_kind(),
+            Some(ComponentKind::UInt | ComponentKind::SInt)
+        )
+    }
+
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        self.component_kind() == Some(ComponentKind::Float)
+    }
+
+    /// sRGB and linear pair of the format
+    fn srgb_pair(&self) -> Option<(Self, Self)> {
+        use PixelFormat::*;
+        Some(match self {
+            R8UNorm | R8UNormSrgb => (R8UNorm, R8UNormSrgb),
+            Rg8UNorm | Rg8UNormSrgb => (Rg8UNorm, Rg8UNormSrgb),
+            Rgba8UNorm | Rgba8UNormSrgb => (Rgba8UNorm, Rgba8UNormSrgb),
+            Bgra8UNorm | Bgra8UNormSrgb => (Bgra8UNorm, Bgra8UNormSrgb),
+            Bgr10Xr | Bgr10XrSrgb => (Bgr10Xr, Bgr10XrSrgb),
+            Bgra10Xr | Bgra10XrSrgb => (Bgra10Xr, Bgra10XrSrgb),
+            Bc1Rgba | Bc1RgbaSrgb => (Bc1Rgba, Bc1RgbaSrgb),
+            Bc2Rgba | Bc2RgbaSrgb => (Bc2Rgba, Bc2RgbaSrgb),
+            Bc3Rgba | Bc3RgbaSrgb => (Bc3Rgba, Bc3RgbaSrgb),
+            Bc7RgbaUNorm | Bc7RgbaUNormSrgb => (Bc7RgbaUNorm, Bc7RgbaUNormSrgb),
+            PvrtcRgb2Bpp | PvrtcRgb2BppSrgb => (PvrtcRgb2Bpp, PvrtcRgb2BppSrgb),
+            PvrtcRgb4Bpp | PvrtcRgb4BppSrgb => (PvrtcRgb4Bpp, PvrtcRgb4BppSrgb),
+            PvrtcRgba2Bpp | PvrtcRgba2BppSrgb => (PvrtcRgba2Bpp, PvrtcRgba2BppSrgb),
+            PvrtcRgba4Bpp | PvrtcRgba4BppSrgb => (PvrtcRgba4Bpp, PvrtcRgba4BppSrgb),
+            EacRgba8 | EacRgba8Srgb => (EacRgba8, EacRgba8Srgb),
+            Etc2Rgb8 | Etc2Rgb8Srgb => (Etc2Rgb8, Etc2Rgb8Srgb),
+            Etc2Rgb8A1 | Etc2Rgb8A1Srgb => (Etc2Rgb8A1, Etc2Rgb8A1Srgb),
+            Astc4x4Ldr | Astc4x4Srgb => (Astc4x4Ldr, Astc4x4Srgb),
+            Astc5x4Ldr | Astc5x4Srgb => (Astc5x4Ldr, Astc5x4Srgb),
+            Astc5x5Ldr | Astc5x5Srgb => (Astc5x5Ldr, Astc5x5Srgb),
+            Astc6x5Ldr | Astc6x5Srgb => (Astc6x5Ldr, Astc6x5Srgb),
+            Astc6x6Ldr | Astc6x6Srgb => (Astc6x6Ldr, Astc6x6Srgb),
+            Astc8x5Ldr | Astc8x5Srgb => (Astc8x5Ldr, Astc8x5Srgb),
+            Astc8x6Ldr | Astc8x6Srgb => (Astc8x6Ldr, Astc8x6Srgb),
+            Astc8x8Ldr | Astc8x8Srgb => (Astc8x8Ldr, Astc8x8Srgb),
+            Astc10x5Ldr | Astc10x5Srgb => (Astc10x5Ldr, Astc10x5Srgb),
+            Astc10x6Ldr | Astc10x6Srgb => (Astc10x6Ldr, Astc10x6Srgb),
+            Astc10x8Ldr | Astc10x8Srgb => (Astc10x8Ldr, Astc10x8Srgb),
+            Astc10x10Ldr | Astc10x10Srgb => (Astc10x10Ldr, Astc10x10Srgb),
+            Astc12x10Ldr | Astc12x10Srgb => (Astc12x10Ldr, Astc12x10Srgb),
+            Astc12x12Ldr | Astc12x12Srgb => (Astc12x12Ldr, Astc12x12Srgb),
+            _ => return None,
+        })
+    }
+
+    /// Format with conversion between sRGB and linear space on sampling and writing.
+    #[inline]
+    pub fn is_srgb(&self) -> bool {
+        self.srgb_pair().is_some_and(|p| p.1 == *self)
+    }
+
+    /// sRGB counterpart of the format, `None` if there is no sRGB variant.
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// let f = mtl::PixelFormat::Bgra8UNorm;
+    /// assert_eq!(f.to_srgb(), Some(mtl::PixelFormat::Bgra8UNormSrgb));
+    /// assert_eq!(f.to_srgb().unwrap().to_linear(), f);
+    /// ```
+    #[inline]
+    pub fn to_srgb(&self) -> Option<Self> {
+        self.srgb_pair().map(|p| p.1)
+    }
+
+    /// Linear counterpart of sRGB format or self.
+    #[inline]
+    pub fn to_linear(&self) -> Self {
+        self.srgb_pair().map_or(*self, |p| p.0)
+    }
+
+    #[inline]
+    pub fn has_depth(&self) -> bool {
+        matches!(
+            self,
+            Self::Depth16Unorm
+                | Self::Depth32Float
+                | Self::Depth24UnormStencil8
+                | Self::Depth32FloatStencil8
+        )
+    }
+
+    #[inline]
+    pub fn has_stencil(&self) -> bool {
+        matches!(
+            self,
+            Self::Stencil8
+                | Self::Depth24UnormStencil8
+                | Self::Depth32FloatStencil8
+                | Self::X24Stencil8
+                | Self::X32Stencil8
+        )
+    }
+
+    /// Format usable as depth or stencil attachment
+    #[inline]
+    pub fn is_depth_or_stencil(&self) -> bool {
+        self.has_depth() || self.has_stencil()
+    }
+
+    pub fn view_class(&self) -> Option<ViewClass> {
+        use PixelFormat::*;
+        let l = self.layout()?;
+        Some(match self {
+            Depth24UnormStencil8 | X24Stencil8 => ViewClass::DepthStencil(Depth24UnormStencil8),
+            Depth32FloatStencil8 | X32Stencil8 => ViewClass::DepthStencil(Depth32FloatStencil8),
+            f if f.is_depth_or_stencil() => ViewClass::DepthStencil(*f),
+            f if f.is_compressed() => ViewClass::Block(f.to_linear()),
+            _ => ViewClass::Ordinary {
+                bits: l.bytes_per_block * 8,
+            },
+        })
+    }
+
+    /// Can a texture of this format be viewed with `other` format.
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// let f = mtl::PixelFormat::Rgba8UNorm;
+    /// assert!(f.is_view_compatible(mtl::PixelFormat::R32Float));
+    /// assert!(!f.is_view_compatible(mtl::PixelFormat::Rg32Float));
+    /// ```
+    #[inline]
+    pub fn is_view_compatible(&self, other: Self) -> bool {
+        let class = self.view_class();
+        class.is_some() && class == other.view_class()
+    }
+}
+
+use crate::gfx::cv;
+
+impl PixelFormat {
+    /// Metal pixel format for a packed `cv::PixelFormat`, as used by `cv::MetalTextureCache`.
+    ///
+    /// Use [`Self::from_cv_plane`] for planar formats.
+    #[inline]
+    pub fn from_cv(format: cv::PixelFormat) -> Option<Self> {
+        if Self::from_cv_plane(format, 1).is_some() {
+            return None;
+        }
+        Self::from_cv_plane(format, 0)
+    }
+
+    /// Metal pixel format of a plane of `cv::PixelFormat`.
+    ///
+    /// ```
+    /// use cidre::gfx::{cv, mtl};
+    ///
+    /// let f = cv::PixelFormat::_420V;
+    /// assert_eq!(mtl::PixelFormat::from_cv_plane(f, 0), Some(mtl::PixelFormat::R8UNorm));
+    /// assert_eq!(mtl::PixelFormat::from_cv_plane(f, 1), Some(mtl::PixelFormat::Rg8UNorm));
+    /// assert_eq!(mtl::PixelFormat::from_cv(f), None);
+    /// ```
+    pub fn from_cv_plane(format: cv::PixelFormat, plane_index: usize) -> Option<Self> {
+        use cv::PixelFormat as F;
+        use PixelFormat::*;
+
+        let planes: &[Self] = match format {
+            F::_32_BGRA | F::LOSSLESS_32_BGRA | F::LOSSY_32_BGRA => &[Bgra8UNorm],
+            F::_32_RGBA => &[Rgba8UNorm],
+            F::_64_RGBALE => &[Rgba16UNorm],
+            F::_64_RGBA_HALF | F::LOSSLESS_64_RGBA_HALF => &[Rgba16Float],
+            F::_128_RGBA_FLOAT => &[Rgba32Float],
+            F::ARGB_2101010_LE_PACKED => &[Bgr10A2UNorm],
+            F::_30_RGB_LE_PACKED_WIDE_GAMUT => &[Bgr10Xr],
+            F::_40_ARGB_LE_WIDE_GAMUT | F::_40_ARGB_LE_WIDE_GAMUT_PREMULTIPLIED => &[Bgra10Xr],
+            F::_2VUY => &[Bgrg422],
+            F::_422_YP_CB_CR_8_YUVS | F::_422_YP_CB_CR_8_FULL_RANGE => &[Gbgr422],
+            F::ONE_COMPONENT_8 => &[R8UNorm],
+            F::ONE_COMPONENT_10 | F::ONE_COMPONENT_12 | F::ONE_COMPONENT_16 => &[R16UNorm],
+            F::ONE_COMPONENT_16_HALF | F::DISPARITY_FLOAT_16 | F::DEPTH_FLOAT_16 => &[R16Float],
+            F::ONE_COMPONENT_32_FLOAT | F::DISPARITY_FLOAT_32 | F::DEPTH_FLOAT_32 => &[R32Float],
+            F::TWO_COMPONENT_8 => &[Rg8UNorm],
+            F::TWO_COMPONENT_16 => &[Rg16UNorm],
+            F::TWO_COMPONENT_16_HALF => &[Rg16Float],
+            F::TWO_COMPONENT_32_FLOAT => &[Rg32Float],
+            F::_420V
+            | F::_420F
+            | F::LOSSLESS_420V
+            | F::LOSSLESS_420F
+            | F::LOSSY_420V
+            | F::LOSSY_420F => &[R8UNorm, Rg8UNorm],
+            F::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE
+            | F::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE
+            | F::_444_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE
+            | F::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE
+            | F::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE
+            | F::_444_YP_CB_CR_10_BI_PLANAR_FULL_RANGE => &[R16UNorm, Rg16UNorm],
+            F::_420_YP_CB_CR_8_PLANAR | F::_420_YP_CB_CR_8_PLANAR_FULL_RANGE => {
+                &[R8UNorm, R8UNorm, R8UNorm]
+            }
+            F::_420_YP_CB_CR_8_VIDEO_RANGE_8A_TRI_PLANAR => &[R8UNorm, Rg8UNorm, R8UNorm],
+            F::_422_YP_CB_CR_4_A_8_BI_PLANAR => &[Bgrg422, R8UNorm],
+            _ => &[],
+        };
+        planes.get(plane_index).copied()
+    }
+
+    /// Packed `cv::PixelFormat` with the same memory layout.
+    ///
+    /// ```
+    /// use cidre::gfx::{cv, mtl};
+    ///
+    /// let f = mtl::PixelFormat::Bgra8UNormSrgb;
+    /// assert_eq!(f.to_cv(), Some(cv::PixelFormat::_32_BGRA));
+    /// ```
+    pub fn to_cv(&self) -> Option<cv::PixelFormat> {
+        use cv::PixelFormat as F;
+        use PixelFormat::*;
+
+        Some(match self.to_linear() {
+            Bgra8UNorm => F::_32_BGRA,
+            Rgba8UNorm => F::_32_RGBA,
+            Rgba16UNorm => F::_64_RGBALE,
+            Rgba16Float => F::_64_RGBA_HALF,
+            Rgba32Float => F::_128_RGBA_FLOAT,
+            Bgr10A2UNorm => F::ARGB_2101010_LE_PACKED,
+            Bgr10Xr => F::_30_RGB_LE_PACKED_WIDE_GAMUT,
+            Bgra10Xr => F::_40_ARGB_LE_WIDE_GAMUT,
+            Bgrg422 => F::_2VUY,
+            Gbgr422 => F::_422_YP_CB_CR_8_YUVS,
+            R8UNorm => F::ONE_COMPONENT_8,
+            R16UNorm => F::ONE_COMPONENT_16,
+            R16Float => F::ONE_COMPONENT_16_HALF,
+            R32Float => F::ONE_COMPONENT_32_FLOAT,
+            Rg8UNorm => F::TWO_COMPONENT_8,
+            Rg16UNorm => F::TWO_COMPONENT_16,
+            Rg16Float => F::TWO_COMPONENT_16_HALF,
+            Rg32Float => F::TWO_COMPONENT_32_FLOAT,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::mtl;
+
+    #[test]
+    fn layout() {
+        let f = mtl::PixelFormat::Rgba16Float;
+        assert_eq!(f.bytes_per_pixel(), Some(8));
+        assert_eq!(f.component_count(), 4);
+        assert!(f.is_float());
+        assert!(!f.is_compressed());
+
+        let f = mtl::PixelFormat::Astc10x8Hdr;
+        assert_eq!(f.bytes_per_pixel(), None);
+        assert_eq!(f.block_size(), (10, 8));
+        assert_eq!(f.bytes_per_image(21, 9), 3 * 2 * 16);
+        assert!(f.is_float());
+
+        let f = mtl::PixelFormat::PvrtcRgba2Bpp;
+        assert_eq!(f.block_size(), (8, 4));
+        assert_eq!(f.bytes_per_image(1, 1), 32);
+
+        let f = mtl::PixelFormat::Gbgr422;
+        assert_eq!(f.bytes_per_row(1920), 3840);
+
+        let f = mtl::PixelFormat::Invalid;
+        assert_eq!(f.bytes_per_row(10), 0);
+        assert_eq!(f.component_kind(), None);
+        assert_eq!(f.view_class(), None);
+        assert!(!f.is_view_compatible(f));
+    }
+
+    #[test]
+    fn srgb() {
+        let f = mtl::PixelFormat::Astc4x4Srgb;
+        assert!(f.is_srgb());
+        assert_eq!(f.to_linear(), mtl::PixelFormat::Astc4x4Ldr);
+        assert_eq!(f.to_srgb(), Some(f));
+        assert_eq!(mtl::PixelFormat::Rgba16Float.to_srgb(), None);
+        assert_eq!(
+            mtl::PixelFormat::Rgba16Float.to_linear(),
+            mtl::PixelFormat::Rgba16Float
+        );
+    }
+
+    #[test]
+    fn depth_stencil() {
+        let f = mtl::PixelFormat::Depth32FloatStencil8;
+        assert!(f.has_depth() && f.has_stencil());
+        assert!(f.is_view_compatible(mtl::PixelFormat::X32Stencil8));
+        assert!(!f.is_view_compatible(mtl::PixelFormat::Rg32Float));
+        assert!(!mtl::PixelFormat::Depth32Float.is_view_compatible(mtl::PixelFormat::R32Float));
+        assert!(!mtl::PixelFormat::R8UNorm.is_depth_or_stencil());
+    }
+
+    #[test]
+    fn view_class() {
+        let f = mtl::PixelFormat::Bc7RgbaUNorm;
+        assert!(f.is_view_compatible(mtl::PixelFormat::Bc7RgbaUNormSrgb));
+        assert!(!f.is_view_compatible(mtl::PixelFormat::Bc3Rgba));
+        assert!(mtl::PixelFormat::Rgb10A2UNorm.is_view_compatible(mtl::PixelFormat::Bgra8UNorm));
+    }
+}
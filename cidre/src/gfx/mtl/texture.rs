@@ -0,0 +1,79 @@
+/// Describes the dimensionality of each image, and if multiple images are arranged into an array or cube.
+#[doc(alias = "MTLTextureType")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+#[repr(usize)]
+pub enum Type {
+    #[doc(alias = "MTLTextureType1D")]
+    _1d = 0,
+
+    #[doc(alias = "MTLTextureType1DArray")]
+    _1dArray = 1,
+
+    #[doc(alias = "MTLTextureType2D")]
+    _2d = 2,
+
+    #[doc(alias = "MTLTextureType2DArray")]
+    _2dArray = 3,
+
+    #[doc(alias = "MTLTextureType2DMultisample")]
+    _2dMultisample = 4,
+
+    #[doc(alias = "MTLTextureTypeCube")]
+    Cube = 5,
+
+    #[doc(alias = "MTLTextureTypeCubeArray")]
+    CubeArray = 6,
+
+    #[doc(alias = "MTLTextureType3D")]
+    _3d = 7,
+
+    #[doc(alias = "MTLTextureType2DMultisampleArray")]
+    _2dMultisampleArray = 8,
+
+    #[doc(alias = "MTLTextureTypeTextureBuffer")]
+    TextureBuffer = 9,
+}
+
+impl Type {
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            Self::_1dArray | Self::_2dArray | Self::CubeArray | Self::_2dMultisampleArray
+        )
+    }
+
+    #[inline]
+    pub fn is_cube(&self) -> bool {
+        matches!(self, Self::Cube | Self::CubeArray)
+    }
+
+    #[inline]
+    pub fn is_multisample(&self) -> bool {
+        matches!(self, Self::_2dMultisample | Self::_2dMultisampleArray)
+    }
+
+    /// Number of dimensions reduced by mipmapping: 1, 2 or 3
+    #[inline]
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Self::_1d | Self::_1dArray | Self::TextureBuffer => 1,
+            Self::_3d => 3,
+            _ => 2,
+        }
+    }
+
+    /// Multisample textures and texture buffers have single mip level
+    #[inline]
+    pub fn supports_mipmaps(&self) -> bool {
+        !self.is_multisample() && *self != Self::TextureBuffer
+    }
+
+    /// Number of 2D slices for `array_len`: 6 faces per cube
+    #[inline]
+    pub fn slice_count(&self, array_len: usize) -> usize {
+        let faces = if self.is_cube() { 6 } else { 1 };
+        let layers = if self.is_array() { array_len } else { 1 };
+        faces * layers
+    }
+}
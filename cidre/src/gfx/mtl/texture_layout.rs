@@ -0,0 +1,298 @@
+use crate::gfx::mtl;
+
+/// Tightly packed linear layout of texture contents in CPU memory or `mtl::Buf`.
+///
+/// Slices (array layers and cube faces) go one after another, each holds all of its mip levels.
+/// Sizes are what uploads and readbacks need; GPU allocations are larger
+/// (see `mtl::Device::heap_texture_size_and_align`).
+///
+/// ```
+/// use cidre::gfx::mtl;
+///
+/// let layout = mtl::TextureLayout::new(
+///     mtl::TextureType::Cube,
+///     mtl::PixelFormat::Rgba8UNorm,
+///     mtl::Size::_2d(256, 256),
+/// )
+/// .with_full_mip_chain();
+///
+/// assert_eq!(layout.mipmap_level_count, 9);
+/// assert_eq!(layout.slice_count(), 6);
+/// assert_eq!(layout.level_size(2), mtl::Size::_2d(64, 64));
+/// assert_eq!(layout.offset(1, 0), layout.slice_bytes());
+///
+/// let copy = layout.copies().nth(1).unwrap();
+/// assert_eq!((copy.slice, copy.level), (0, 1));
+/// assert_eq!(copy.offset, 256 * 256 * 4);
+/// assert_eq!(copy.bytes_per_row, 128 * 4);
+/// assert_eq!(copy.bytes_per_image, 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub texture_type: mtl::TextureType,
+    pub pixel_format: mtl::PixelFormat,
+
+    /// Size of the base level
+    pub size: mtl::Size,
+    pub mipmap_level_count: usize,
+    pub array_len: usize,
+    pub sample_count: usize,
+}
+
+/// Arguments of `mtl::Texture::replace_region` or blit copy between texture level and buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelCopy {
+    pub slice: usize,
+    pub level: usize,
+    pub region: mtl::Region,
+
+    /// Offset in buffer
+    pub offset: usize,
+
+    /// 0 for PVRTC formats
+    pub bytes_per_row: usize,
+
+    /// Stride between depth images of 3D textures, 0 for other types and PVRTC formats
+    pub bytes_per_image: usize,
+}
+
+impl Layout {
+    /// Layout with single mip level, array layer and sample
+    #[inline]
+    pub fn new(
+        texture_type: mtl::TextureType,
+        pixel_format: mtl::PixelFormat,
+        size: mtl::Size,
+    ) -> Self {
+        Self {
+            texture_type,
+            pixel_format,
+            size,
+            mipmap_level_count: 1,
+            array_len: 1,
+            sample_count: 1,
+        }
+    }
+
+    #[inline]
+    pub fn with_full_mip_chain(mut self) -> Self {
+        self.mipmap_level_count = self.max_mipmap_level_count();
+        self
+    }
+
+    /// Longest mip chain for texture type and size
+    pub fn max_mipmap_level_count(&self) -> usize {
+        if !self.texture_type.supports_mipmaps() {
+            return 1;
+        }
+        self.base_size().mip_level_count().max(1)
+    }
+
+    /// Size with dimensions which are not used by texture type set to 1
+    fn base_size(&self) -> mtl::Size {
+        let s = self.size;
+        match self.texture_type.dimensions() {
+            1 => mtl::Size::_1d(s.width),
+            2 => mtl::Size::_2d(s.width, s.height),
+            _ => s,
+        }
+    }
+
+    #[inline]
+    pub fn slice_count(&self) -> usize {
+        self.texture_type.slice_count(self.array_len)
+    }
+
+    /// Size of mip `level`
+    #[inline]
+    pub fn level_size(&self, level: usize) -> mtl::Size {
+        self.base_size().mip_level(level)
+    }
+
+    #[inline]
+    pub fn level_bytes_per_row(&self, level: usize) -> usize {
+        self.level_size(level).bytes_per_row(self.pixel_format)
+    }
+
+    /// Bytes of one 2D image of mip `level`
+    #[inline]
+    pub fn level_bytes_per_image(&self, level: usize) -> usize {
+        self.level_size(level).bytes_per_image(self.pixel_format)
+    }
+
+    /// Bytes of mip `level` in single slice including all depth images and samples
+    #[inline]
+    pub fn level_bytes(&self, level: usize) -> usize {
+        self.level_size(level).bytes(self.pixel_format) * self.sample_count.max(1)
+    }
+
+    /// Bytes of all mip levels of single slice
+    #[inline]
+    pub fn slice_bytes(&self) -> usize {
+        (0..self.mipmap_level_count)
+            .map(|l| self.level_bytes(l))
+            .sum()
+    }
+
+    /// Total bytes of all slices and mip levels
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.slice_bytes() * self.slice_count()
+    }
+
+    /// Offset of mip `level` of `slice`
+    pub fn offset(&self, slice: usize, level: usize) -> usize {
+        slice * self.slice_bytes() + (0..level).map(|l| self.level_bytes(l)).sum::<usize>()
+    }
+
+    /// Copy of whole mip `level` of `slice`
+    pub fn level_copy(&self, slice: usize, level: usize) -> LevelCopy {
+        let size = self.level_size(level);
+        let pvrtc = self.pixel_format.is_pvrtc();
+        let bytes_per_row = if pvrtc {
+            0
+        } else {
+            size.bytes_per_row(self.pixel_format)
+        };
+        let bytes_per_image = if pvrtc || self.texture_type != mtl::TextureType::_3d {
+            0
+        } else {
+            size.bytes_per_image(self.pixel_format)
+        };
+        LevelCopy {
+            slice,
+            level,
+            region: mtl::Region::with_size(size),
+            offset: self.offset(slice, level),
+            bytes_per_row,
+            bytes_per_image,
+        }
+    }
+
+    /// Copies of every level of every slice in buffer order.
+    /// Empty for multisample textures which can't be copied from CPU.
+    pub fn copies(&self) -> impl Iterator<Item = LevelCopy> + '_ {
+        let slices = if self.texture_type.is_multisample() {
+            0
+        } else {
+            self.slice_count()
+        };
+        (0..slices).flat_map(move |slice| {
+            (0..self.mipmap_level_count).map(move |level| self.level_copy(slice, level))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gfx::mtl;
+
+    use super::Layout;
+
+    #[test]
+    fn mip_chains() {
+        let layout = Layout::new(
+            mtl::TextureType::_1dArray,
+            mtl::PixelFormat::R8UNorm,
+            mtl::Size::_3d(100, 50, 7),
+        )
+        .with_full_mip_chain();
+        assert_eq!(layout.mipmap_level_count, 7);
+        assert_eq!(layout.level_size(1), mtl::Size::_1d(50));
+
+        let mut layout = Layout::new(
+            mtl::TextureType::_3d,
+            mtl::PixelFormat::Rgba16Float,
+            mtl::Size::_3d(16, 8, 64),
+        )
+        .with_full_mip_chain();
+        assert_eq!(layout.mipmap_level_count, 7);
+        assert_eq!(layout.level_size(4), mtl::Size::_3d(1, 1, 4));
+        assert_eq!(layout.level_bytes(0), 16 * 8 * 64 * 8);
+        layout.array_len = 4;
+        assert_eq!(layout.slice_count(), 1);
+
+        let layout = Layout::new(
+            mtl::TextureType::_2dMultisample,
+            mtl::PixelFormat::Bgra8UNorm,
+            mtl::Size::_2d(64, 64),
+        )
+        .with_full_mip_chain();
+        assert_eq!(layout.mipmap_level_count, 1);
+        assert_eq!(layout.copies().count(), 0);
+    }
+
+    #[test]
+    fn sizes() {
+        let mut layout = Layout::new(
+            mtl::TextureType::CubeArray,
+            mtl::PixelFormat::Bc1Rgba,
+            mtl::Size::_2d(64, 64),
+        )
+        .with_full_mip_chain();
+        layout.array_len = 2;
+        assert_eq!(layout.slice_count(), 12);
+        // 64, 32, 16, 8, 4 => 16x16, 8x8, 4x4, 2x2, 1x1 blocks, then 2x2 and 1x1 are 1 block
+        let blocks = 256 + 64 + 16 + 4 + 1 + 1 + 1;
+        assert_eq!(layout.slice_bytes(), blocks * 8);
+        assert_eq!(layout.bytes(), blocks * 8 * 12);
+        assert_eq!(layout.offset(3, 2), 3 * blocks * 8 + (256 + 64) * 8);
+
+        let copies: Vec<_> = layout.copies().collect();
+        assert_eq!(copies.len(), 12 * 7);
+        assert_eq!(copies[6].region, mtl::Region::new_2d(0, 0, 1, 1));
+        assert_eq!(copies[6].bytes_per_row, 8);
+        for pair in copies.windows(2) {
+            let a = &pair[0];
+            assert_eq!(
+                a.offset + layout.level_bytes(a.level),
+                pair[1].offset,
+                "copies are contiguous"
+            );
+        }
+
+        let layout = Layout::new(
+            mtl::TextureType::_2d,
+            mtl::PixelFormat::PvrtcRgba4Bpp,
+            mtl::Size::_2d(8, 8),
+        );
+        let copy = layout.level_copy(0, 0);
+        assert_eq!((copy.bytes_per_row, copy.bytes_per_image), (0, 0));
+        assert_eq!(layout.bytes(), 2 * 2 * 8);
+    }
+
+    #[test]
+    fn regions() {
+        let level = mtl::Size::_2d(30, 30);
+        let region = mtl::Region::new_2d(20, 20, 40, 40).clamped(level).unwrap();
+        assert_eq!(region, mtl::Region::new_2d(20, 20, 10, 10));
+        assert!(region.is_block_aligned(mtl::PixelFormat::Astc5x5Ldr, level));
+        assert!(region.is_block_aligned(mtl::PixelFormat::Bc7RgbaUNorm, level));
+        assert!(!region.is_block_aligned(mtl::PixelFormat::Astc6x6Ldr, level));
+        assert!(mtl::Region::with_size(level).contains(&region));
+        assert_eq!(mtl::Region::new_2d(40, 0, 1, 1).clamped(level), None);
+
+        let region = mtl::Region::new_3d(0, 0, 2, 8, 8, 3);
+        let tiles: Vec<_> = region
+            .tiles(mtl::Size::_3d(8, 8, 2), mtl::PixelFormat::R8UNorm)
+            .collect();
+        assert_eq!(
+            tiles,
+            [
+                mtl::Region::new_3d(0, 0, 2, 8, 8, 2),
+                mtl::Region::new_3d(0, 0, 4, 8, 8, 1)
+            ]
+        );
+        assert_eq!(region.slice_offset(mtl::PixelFormat::R8UNorm, 2), 128);
+        assert_eq!(
+            region.offset(mtl::PixelFormat::Bc4RUNorm, 4, 4, 1),
+            32 + 16 + 8
+        );
+        assert_eq!(
+            mtl::Region::new_2d(0, 0, 0, 4)
+                .tiles(mtl::Size::_2d(1, 1), mtl::PixelFormat::R8UNorm)
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::gfx::mtl;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Size {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+}
+
+impl Size {
+    #[inline]
+    pub fn _1d(width: usize) -> Self {
+        Self {
+            width,
+            height: 1,
+            depth: 1,
+        }
+    }
+
+    #[inline]
+    pub fn _2d(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth: 1,
+        }
+    }
+
+    #[inline]
+    pub fn _3d(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0 || self.depth == 0
+    }
+
+    /// Length of the full mip chain if all three dimensions are halved
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// assert_eq!(mtl::Size::_2d(1920, 1080).mip_level_count(), 11);
+    /// assert_eq!(mtl::Size::_3d(4, 4, 64).mip_level_count(), 7);
+    /// assert_eq!(mtl::Size::_2d(0, 0).mip_level_count(), 0);
+    /// ```
+    #[inline]
+    pub fn mip_level_count(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        self.width.max(self.height).max(self.depth).ilog2() as usize + 1
+    }
+
+    /// Size of mip `level`, each dimension is halved and clamped to 1
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// assert_eq!(mtl::Size::_2d(1920, 1080).mip_level(3), mtl::Size::_2d(240, 135));
+    /// assert_eq!(mtl::Size::_2d(1920, 1080).mip_level(10), mtl::Size::_2d(1, 1));
+    /// ```
+    #[inline]
+    pub fn mip_level(&self, level: usize) -> Self {
+        let f = |v: usize| v.checked_shr(level as u32).unwrap_or(0).max(1);
+        Self {
+            width: f(self.width),
+            height: f(self.height),
+            depth: f(self.depth),
+        }
+    }
+
+    /// Rounds width and height up to multiples of `pixel_format` block size
+    #[inline]
+    pub fn block_aligned(&self, pixel_format: mtl::PixelFormat) -> Self {
+        let (bw, bh) = pixel_format.block_size();
+        Self {
+            width: self.width.next_multiple_of(bw),
+            height: self.height.next_multiple_of(bh),
+            depth: self.depth,
+        }
+    }
+
+    /// Tightly packed `bytes_per_row` for copies of this size
+    #[inline]
+    pub fn bytes_per_row(&self, pixel_format: mtl::PixelFormat) -> usize {
+        pixel_format.bytes_per_row(self.width)
+    }
+
+    /// Tightly packed `bytes_per_image` for copies of this size
+    #[inline]
+    pub fn bytes_per_image(&self, pixel_format: mtl::PixelFormat) -> usize {
+        pixel_format.bytes_per_image(self.width, self.height)
+    }
+
+    /// Tightly packed bytes of all `depth` images
+    #[inline]
+    pub fn bytes(&self, pixel_format: mtl::PixelFormat) -> usize {
+        self.bytes_per_image(pixel_format) * self.depth
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Origin {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+}
+
+impl Origin {
+    #[inline]
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn new(x: usize, y: usize, z: usize) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Handle of the GPU resource suitable for storing in an Argument Buffer
+#[doc(alias = "MTLResourceID")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(C)]
+pub struct ResId {
+    _impl: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Region {
+    pub origin: Origin,
+    pub size: Size,
+}
+
+impl Region {
+    #[doc(alias = "MTLRegionMake1D")]
+    #[inline]
+    pub fn new_1d(x: usize, width: usize) -> Self {
+        Self {
+            origin: Origin {
+                x,
+                ..Default::default()
+            },
+            size: Size::_1d(width),
+        }
+    }
+
+    #[doc(alias = "MTLRegionMake2D")]
+    #[inline]
+    pub fn new_2d(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            origin: Origin { x, y, z: 0 },
+            size: Size::_2d(width, height),
+        }
+    }
+
+    #[inline]
+    pub fn new_3d(x: usize, y: usize, z: usize, width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            origin: Origin { x, y, z },
+            size: Size {
+                width,
+                height,
+                depth,
+            },
+        }
+    }
+
+    /// Region covering whole `size`
+    #[inline]
+    pub fn with_size(size: Size) -> Self {
+        Self {
+            origin: Origin::zero(),
+            size,
+        }
+    }
+
+    /// Exclusive end corner
+    #[inline]
+    pub fn end(&self) -> Origin {
+        Origin {
+            x: self.origin.x + self.size.width,
+            y: self.origin.y + self.size.height,
+            z: self.origin.z + self.size.depth,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.size.is_empty()
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        let (end, other_end) = (self.end(), other.end());
+        other.origin.x >= self.origin.x
+            && other.origin.y >= self.origin.y
+            && other.origin.z >= self.origin.z
+            && other_end.x <= end.x
+            && other_end.y <= end.y
+            && other_end.z <= end.z
+    }
+
+    /// Common part of two regions, `None` if they don't overlap
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// let a = mtl::Region::new_2d(0, 0, 100, 100);
+    /// let b = mtl::Region::new_2d(50, 80, 100, 100);
+    /// assert_eq!(a.intersection(&b), Some(mtl::Region::new_2d(50, 80, 50, 20)));
+    /// assert_eq!(a.intersection(&mtl::Region::new_2d(100, 0, 1, 1)), None);
+    /// ```
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (end, other_end) = (self.end(), other.end());
+        let origin = Origin {
+            x: self.origin.x.max(other.origin.x),
+            y: self.origin.y.max(other.origin.y),
+            z: self.origin.z.max(other.origin.z),
+        };
+        let res = Self {
+            origin,
+            size: Size {
+                width: end.x.min(other_end.x).saturating_sub(origin.x),
+                height: end.y.min(other_end.y).saturating_sub(origin.y),
+                depth: end.z.min(other_end.z).saturating_sub(origin.z),
+            },
+        };
+        if res.is_empty() {
+            None
+        } else {
+            Some(res)
+        }
+    }
+
+    /// Part of region inside texture (or mip level) of `size`
+    #[inline]
+    pub fn clamped(&self, size: Size) -> Option<Self> {
+        self.intersection(&Self::with_size(size))
+    }
+
+    /// Checks that region can be copied into texture level of `level_size`:
+    /// origin is on block boundary and size is multiple of block or ends at the level edge.
+    pub fn is_block_aligned(&self, pixel_format: mtl::PixelFormat, level_size: Size) -> bool {
+        let (bw, bh) = pixel_format.block_size();
+        let end = self.end();
+        self.origin.x % bw == 0
+            && self.origin.y % bh == 0
+            && (self.size.width % bw == 0 || end.x == level_size.width)
+            && (self.size.height % bh == 0 || end.y == level_size.height)
+    }
+
+    /// Splits region into tiles of at most `tile_size`, rounded up to `pixel_format` blocks.
+    ///
+    /// Tiles go in x, then y, then z order. Edge tiles are smaller.
+    ///
+    /// ```
+    /// use cidre::gfx::mtl;
+    ///
+    /// let region = mtl::Region::new_2d(0, 0, 100, 50);
+    /// let tiles: Vec<_> = region.tiles(mtl::Size::_2d(30, 30), mtl::PixelFormat::Bc1Rgba).collect();
+    /// assert_eq!(tiles.len(), 8);
+    /// assert_eq!(tiles[0], mtl::Region::new_2d(0, 0, 32, 32));
+    /// assert_eq!(tiles[3], mtl::Region::new_2d(96, 0, 4, 32));
+    /// assert_eq!(tiles[7], mtl::Region::new_2d(96, 32, 4, 18));
+    /// ```
+    pub fn tiles(&self, tile_size: Size, pixel_format: mtl::PixelFormat) -> Tiles {
+        let tile = Size {
+            width: tile_size.width.max(1),
+            height: tile_size.height.max(1),
+            depth: tile_size.depth.max(1),
+        }
+        .block_aligned(pixel_format);
+        Tiles {
+            region: *self,
+            tile,
+            next: if self.is_empty() {
+                None
+            } else {
+                Some(self.origin)
+            },
+        }
+    }
+
+    /// Byte offset of `(x, y, z)` relative to region origin in tightly packed copy of the region
+    pub fn offset(&self, pixel_format: mtl::PixelFormat, x: usize, y: usize, z: usize) -> usize {
+        let (bw, bh) = pixel_format.block_size();
+        z * self.size.bytes_per_image(pixel_format)
+            + y / bh * self.size.bytes_per_row(pixel_format)
+            + x / bw * pixel_format.bytes_per_block()
+    }
+
+    /// Byte offset of depth slice or array layer `z` in tightly packed copy of the region
+    #[inline]
+    pub fn slice_offset(&self, pixel_format: mtl::PixelFormat, z: usize) -> usize {
+        z * self.size.bytes_per_image(pixel_format)
+    }
+}
+
+/// Iterator over block-aligned tiles of a region, see [`Region::tiles`]
+#[derive(Debug, Clone)]
+pub struct Tiles {
+    region: Region,
+    tile: Size,
+    next: Option<Origin>,
+}
+
+impl Iterator for Tiles {
+    type Item = Region;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let origin = self.next?;
+        let end = self.region.end();
+        let size = Size {
+            width: self.tile.width.min(end.x - origin.x),
+            height: self.tile.height.min(end.y - origin.y),
+            depth: self.tile.depth.min(end.z - origin.z),
+        };
+        let start = self.region.origin;
+        self.next = if origin.x + size.width < end.x {
+            Some(Origin::new(origin.x + size.width, origin.y, origin.z))
+        } else if origin.y + size.height < end.y {
+            Some(Origin::new(start.x, origin.y + size.height, origin.z))
+        } else if origin.z + size.depth < end.z {
+            Some(Origin::new(start.x, start.y, origin.z + size.depth))
+        } else {
+            None
+        };
+        Some(Region { origin, size })
+    }
+}
+
+/// Identify a sample within a pixel. Origin is top-left with a range [0,1) for both x and y.
+#[doc(alias = "MTLSamplePosition")]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct SamplePos {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A floating point coordinate in an abstract 2D space.
+///
+/// Refer to location of use for concrete information on the space in which the coordinate exists.
+pub type Coordinate2d = SamplePos;
@@ -83,6 +83,14 @@ pub mod mach;
 #[cfg(feature = "mc")]
 pub mod mc;
 
+/// Framework independent graphics types and math
+#[cfg(feature = "gfx")]
+pub mod gfx;
+
+/// Media bitstreams and containers
+#[cfg(feature = "media")]
+pub mod media;
+
 /// Metal
 #[cfg(feature = "mtl")]
 pub mod mtl;
@@ -121,6 +129,10 @@ pub mod gc;
 pub mod os;
 pub mod sys;
 
+/// Property lists
+#[cfg(feature = "plist")]
+pub mod plist;
+
 /// Security
 #[cfg(feature = "sec")]
 pub mod sec;
@@ -260,7 +272,7 @@ impl std::fmt::Debug for dyn Dyn {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_vendor = "apple", feature = "cf"))]
 mod tests {
     use crate::cf;
 
@@ -33,7 +33,7 @@ extern "C" {
     ) -> mach::KernReturn;
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_vendor = "apple"))]
 mod tests {
     use crate::mach;
 
@@ -9,6 +9,7 @@ pub struct TimeBaseInfo {
 
 impl TimeBaseInfo {
     /// ```
+    /// # #[cfg(target_vendor = "apple")] {
     /// use cidre::mach;
     ///
     /// let mut tbi = mach::TimeBaseInfo::default();
@@ -19,6 +20,7 @@ impl TimeBaseInfo {
     ///
     /// assert!(tbi.numer > 0);
     /// assert!(tbi.denom > 0);
+    /// # }
     /// ```
     #[inline]
     pub fn fill(&mut self) -> KernReturn {
@@ -0,0 +1,42 @@
+//! Pure-Rust bitstream and container helpers for encoded media.
+//!
+//! Nothing here calls into Apple frameworks, so streams can be validated and packaged
+//! on any platform. Conversions to `cm` types are available with `cm` feature.
+
+pub mod bits;
+pub use bits::Reader as BitReader;
+pub use bits::Writer as BitWriter;
+
+pub mod nal;
+
+pub mod vui;
+pub use vui::ColorDescription;
+pub use vui::VideoSignal;
+
+pub mod h264;
+pub mod hevc;
+
+pub mod av1;
+pub mod vp9;
+
+pub mod aac;
+
+#[cfg(all(feature = "cm", feature = "cv"))]
+mod ext;
+
+pub mod hdr;
+pub mod sei;
+
+pub mod reorder;
+
+pub mod ladder;
+
+pub mod m3u8;
+pub mod mkv;
+pub mod mp4;
+pub mod rtp;
+pub mod ts;
+
+pub mod amf;
+pub mod flv;
+pub mod rtmp;
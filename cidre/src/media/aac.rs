@@ -0,0 +1,690 @@
+//! AAC `AudioSpecificConfig` (ISO/IEC 14496-3) and ADTS and LOAS/LATM framing of raw AAC frames.
+//!
+//! MP4 samples and `cm::SampleBuf`s carry raw frames with `AudioSpecificConfig` in
+//! `esds` or magic cookie, while transport streams need self-describing frames.
+
+use crate::media::bits;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// ADTS or LOAS sync word not found
+    InvalidSync,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// Valid syntax which isn't supported, e.g. multiple LATM layers
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated AAC data"),
+            Self::InvalidSync => f.write_str("invalid AAC sync word"),
+            Self::InvalidValue(name) => write!(f, "invalid AAC {name}"),
+            Self::Unsupported(name) => write!(f, "unsupported AAC {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Eof => Self::Truncated,
+            bits::Error::InvalidExpGolomb => Self::InvalidValue("exp-golomb code"),
+        }
+    }
+}
+
+/// Sample rates of `samplingFrequencyIndex`
+pub const SAMPLE_RATES: [u32; 13] = [
+    96_000, 88_200, 64_000, 48_000, 44_100, 32_000, 24_000, 22_050, 16_000, 12_000, 11_025, 8_000,
+    7_350,
+];
+
+/// `audioObjectType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct ObjectType(pub u8);
+
+impl ObjectType {
+    pub const MAIN: Self = Self(1);
+    pub const LC: Self = Self(2);
+    pub const SSR: Self = Self(3);
+    pub const LTP: Self = Self(4);
+    pub const SBR: Self = Self(5);
+    pub const PS: Self = Self(29);
+    pub const ER_AAC_LD: Self = Self(23);
+    pub const ER_AAC_ELD: Self = Self(39);
+
+    /// Types with `GASpecificConfig` which fit into ADTS header
+    #[inline]
+    pub const fn is_general_audio(&self) -> bool {
+        matches!(self.0, 1..=4 | 6 | 7)
+    }
+}
+
+#[inline]
+fn sample_rate_index(rate: u32) -> Option<u8> {
+    SAMPLE_RATES
+        .iter()
+        .position(|&r| r == rate)
+        .map(|i| i as u8)
+}
+
+fn read_object_type(r: &mut bits::Reader) -> Result<ObjectType, Error> {
+    let aot = r.read_u8(5)?;
+    if aot == 31 {
+        Ok(ObjectType(32 + r.read_u8(6)?))
+    } else {
+        Ok(ObjectType(aot))
+    }
+}
+
+fn write_object_type(w: &mut bits::Writer, aot: ObjectType) {
+    if aot.0 >= 31 {
+        w.write_bits(31, 5);
+        w.write_bits(aot.0 as u64 - 32, 6);
+    } else {
+        w.write_bits(aot.0 as u64, 5);
+    }
+}
+
+fn read_sample_rate(r: &mut bits::Reader) -> Result<u32, Error> {
+    match r.read_u8(4)? {
+        15 => Ok(r.read_u32(24)?),
+        i => SAMPLE_RATES
+            .get(i as usize)
+            .copied()
+            .ok_or(Error::InvalidValue("samplingFrequencyIndex")),
+    }
+}
+
+fn write_sample_rate(w: &mut bits::Writer, rate: u32) {
+    match sample_rate_index(rate) {
+        Some(i) => w.write_bits(i as u64, 4),
+        None => {
+            w.write_bits(15, 4);
+            w.write_bits(rate as u64, 24);
+        }
+    }
+}
+
+/// `AudioSpecificConfig`, contents of `DecoderSpecificInfo` of `esds`
+/// and LATM `StreamMuxConfig`.
+///
+/// ```
+/// use cidre::media::aac;
+///
+/// let asc = aac::AudioSpecificConfig::parse(&[0x12, 0x10]).unwrap();
+/// assert_eq!(asc.object_type, aac::ObjectType::LC);
+/// assert_eq!(asc.sample_rate, 44_100);
+/// assert_eq!(asc.channel_config, 2);
+/// assert_eq!(asc.samples_per_frame(), 1024);
+/// assert_eq!(asc.to_bytes(), [0x12, 0x10]);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AudioSpecificConfig {
+    /// Object type of the core decoder, `LC` for HE-AAC
+    pub object_type: ObjectType,
+
+    /// Sample rate of the core decoder
+    pub sample_rate: u32,
+
+    /// 1..=6 for mono to 5.1, 7 for 7.1, 0 for program config element
+    pub channel_config: u8,
+
+    /// Frames of 960 samples instead of 1024
+    pub frame_length_960: bool,
+
+    /// Output sample rate of explicitly signalled SBR (HE-AAC)
+    pub sbr_sample_rate: Option<u32>,
+
+    /// Explicitly signalled parametric stereo (HE-AAC v2)
+    pub ps: bool,
+}
+
+impl AudioSpecificConfig {
+    /// AAC LC config
+    #[inline]
+    pub const fn lc(sample_rate: u32, channel_config: u8) -> Self {
+        Self {
+            object_type: ObjectType::LC,
+            sample_rate,
+            channel_config,
+            frame_length_960: false,
+            sbr_sample_rate: None,
+            ps: false,
+        }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        Self::read(&mut bits::Reader::new(data))
+    }
+
+    fn read(r: &mut bits::Reader) -> Result<Self, Error> {
+        let mut object_type = read_object_type(r)?;
+        let sample_rate = read_sample_rate(r)?;
+        let channel_config = r.read_u8(4)?;
+        let mut sbr_sample_rate = None;
+        let mut ps = false;
+        if object_type == ObjectType::SBR || object_type == ObjectType::PS {
+            ps = object_type == ObjectType::PS;
+            sbr_sample_rate = Some(read_sample_rate(r)?);
+            object_type = read_object_type(r)?;
+            if object_type.0 == 22 {
+                return Err(Error::Unsupported("ER BSAC extension"));
+            }
+        }
+        let mut frame_length_960 = false;
+        if object_type.is_general_audio() {
+            frame_length_960 = r.read_bool()?;
+            if r.read_bool()? {
+                // dependsOnCoreCoder
+                r.skip(14)?;
+            }
+            // extensionFlag is reserved for non-ER types
+            r.skip(1)?;
+        }
+        Ok(Self {
+            object_type,
+            sample_rate,
+            channel_config,
+            frame_length_960,
+            sbr_sample_rate,
+            ps,
+        })
+    }
+
+    fn write(&self, w: &mut bits::Writer) {
+        match self.sbr_sample_rate {
+            Some(rate) => {
+                write_object_type(
+                    w,
+                    if self.ps {
+                        ObjectType::PS
+                    } else {
+                        ObjectType::SBR
+                    },
+                );
+                write_sample_rate(w, self.sample_rate);
+                w.write_bits(self.channel_config as u64, 4);
+                write_sample_rate(w, rate);
+                write_object_type(w, self.object_type);
+            }
+            None => {
+                write_object_type(w, self.object_type);
+                write_sample_rate(w, self.sample_rate);
+                w.write_bits(self.channel_config as u64, 4);
+            }
+        }
+        if self.object_type.is_general_audio() {
+            w.write_bool(self.frame_length_960);
+            w.write_bool(false);
+            w.write_bool(false);
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        self.write(&mut w);
+        w.byte_align();
+        w.into_vec()
+    }
+
+    /// Decoded samples per frame at output sample rate
+    #[inline]
+    pub fn samples_per_frame(&self) -> u32 {
+        let core = if self.frame_length_960 { 960 } else { 1024 };
+        if self.sbr_sample_rate.is_some() {
+            core * 2
+        } else {
+            core
+        }
+    }
+
+    /// Sample rate of decoded audio
+    #[inline]
+    pub fn output_sample_rate(&self) -> u32 {
+        self.sbr_sample_rate.unwrap_or(self.sample_rate)
+    }
+
+    /// RFC 6381 codec string, e.g. `mp4a.40.2`
+    #[inline]
+    pub fn codec_string(&self) -> String {
+        let aot = match self.sbr_sample_rate {
+            Some(_) if self.ps => ObjectType::PS,
+            Some(_) => ObjectType::SBR,
+            None => self.object_type,
+        };
+        format!("mp4a.40.{}", aot.0)
+    }
+}
+
+/// ADTS frame header (ISO/IEC 13818-7), 7 bytes or 9 with CRC.
+///
+/// ```
+/// use cidre::media::aac;
+///
+/// let asc = aac::AudioSpecificConfig::lc(48_000, 2);
+/// let frame = aac::Adts::with_asc(&asc, 100).unwrap().to_bytes();
+/// let adts = aac::Adts::parse(&frame).unwrap();
+/// assert_eq!(adts.frame_len, 107);
+/// assert_eq!(adts.to_asc(), asc);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Adts {
+    /// `ID` is MPEG-2 instead of MPEG-4
+    pub mpeg2: bool,
+    pub object_type: ObjectType,
+    pub sample_rate: u32,
+    pub channel_config: u8,
+
+    /// Length of the frame including header
+    pub frame_len: usize,
+
+    /// 0x7ff for variable bitrate
+    pub buffer_fullness: u16,
+
+    /// `number_of_raw_data_blocks_in_frame` + 1
+    pub raw_blocks: u8,
+    pub has_crc: bool,
+}
+
+impl Adts {
+    pub const SYNC_WORD: u16 = 0xfff;
+    pub const HEADER_LEN: usize = 7;
+
+    /// Header of a single raw frame of `payload_len` bytes
+    pub fn with_asc(asc: &AudioSpecificConfig, payload_len: usize) -> Result<Self, Error> {
+        if !matches!(asc.object_type.0, 1..=4) {
+            return Err(Error::Unsupported("ADTS object type"));
+        }
+        if sample_rate_index(asc.sample_rate).is_none() {
+            return Err(Error::Unsupported("ADTS sample rate"));
+        }
+        if asc.channel_config > 7 {
+            return Err(Error::InvalidValue("channelConfiguration"));
+        }
+        let frame_len = payload_len + Self::HEADER_LEN;
+        if frame_len > 0x1fff {
+            return Err(Error::InvalidValue("frame_length"));
+        }
+        Ok(Self {
+            mpeg2: false,
+            object_type: asc.object_type,
+            sample_rate: asc.sample_rate,
+            channel_config: asc.channel_config,
+            frame_len,
+            buffer_fullness: 0x7ff,
+            raw_blocks: 1,
+            has_crc: false,
+        })
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut r = bits::Reader::new(data);
+        if r.read_u16(12)? != Self::SYNC_WORD {
+            return Err(Error::InvalidSync);
+        }
+        let mpeg2 = r.read_bool()?;
+        r.skip(2)?; // layer
+        let has_crc = !r.read_bool()?;
+        let object_type = ObjectType(r.read_u8(2)? + 1);
+        let sample_rate = *SAMPLE_RATES
+            .get(r.read_u8(4)? as usize)
+            .ok_or(Error::InvalidValue("sampling_frequency_index"))?;
+        r.skip(1)?; // private_bit
+        let channel_config = r.read_u8(3)?;
+        r.skip(4)?; // original_copy, home, copyright_id_bit and start
+        let frame_len = r.read_u16(13)? as usize;
+        let buffer_fullness = r.read_u16(11)?;
+        let raw_blocks = r.read_u8(2)? + 1;
+        let res = Self {
+            mpeg2,
+            object_type,
+            sample_rate,
+            channel_config,
+            frame_len,
+            buffer_fullness,
+            raw_blocks,
+            has_crc,
+        };
+        if frame_len < res.header_len() {
+            return Err(Error::InvalidValue("frame_length"));
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        if self.has_crc {
+            Self::HEADER_LEN + 2
+        } else {
+            Self::HEADER_LEN
+        }
+    }
+
+    /// Header without CRC, `has_crc` is ignored
+    pub fn to_bytes(&self) -> [u8; 7] {
+        let mut w = bits::Writer::new();
+        w.write_bits(Self::SYNC_WORD as u64, 12);
+        w.write_bool(self.mpeg2);
+        w.write_bits(0, 2);
+        w.write_bool(true); // protection_absent
+        w.write_bits(self.object_type.0 as u64 - 1, 2);
+        w.write_bits(sample_rate_index(self.sample_rate).unwrap_or(15) as u64, 4);
+        w.write_bool(false);
+        w.write_bits(self.channel_config as u64, 3);
+        w.write_bits(0, 4);
+        w.write_bits(self.frame_len as u64, 13);
+        w.write_bits(self.buffer_fullness as u64, 11);
+        w.write_bits(self.raw_blocks as u64 - 1, 2);
+        let mut res = [0; 7];
+        res.copy_from_slice(&w.into_vec());
+        res
+    }
+
+    #[inline]
+    pub fn to_asc(&self) -> AudioSpecificConfig {
+        AudioSpecificConfig {
+            object_type: self.object_type,
+            ..AudioSpecificConfig::lc(self.sample_rate, self.channel_config)
+        }
+    }
+}
+
+/// Prepends ADTS header to raw AAC frame
+pub fn push_adts(out: &mut Vec<u8>, asc: &AudioSpecificConfig, frame: &[u8]) -> Result<(), Error> {
+    out.extend_from_slice(&Adts::with_asc(asc, frame.len())?.to_bytes());
+    out.extend_from_slice(frame);
+    Ok(())
+}
+
+/// Iterator over frames of ADTS stream, see [`adts_frames`]
+#[derive(Debug, Clone)]
+pub struct AdtsFrames<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for AdtsFrames<'a> {
+    /// Header and raw frame
+    type Item = Result<(Adts, &'a [u8]), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let res = Adts::parse(self.rest).and_then(|adts| {
+            let frame = self.rest.get(..adts.frame_len).ok_or(Error::Truncated)?;
+            self.rest = &self.rest[adts.frame_len..];
+            Ok((adts, &frame[adts.header_len()..]))
+        });
+        if res.is_err() {
+            self.rest = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Frames of ADTS stream, stops after the first error.
+///
+/// ```
+/// use cidre::media::aac;
+///
+/// let asc = aac::AudioSpecificConfig::lc(44_100, 1);
+/// let mut stream = Vec::new();
+/// aac::push_adts(&mut stream, &asc, &[1, 2, 3]).unwrap();
+/// aac::push_adts(&mut stream, &asc, &[4]).unwrap();
+///
+/// let frames: Vec<_> = aac::adts_frames(&stream).map(|f| f.unwrap().1).collect();
+/// assert_eq!(frames, [&[1, 2, 3][..], &[4]]);
+/// ```
+#[inline]
+pub fn adts_frames(data: &[u8]) -> AdtsFrames<'_> {
+    AdtsFrames { rest: data }
+}
+
+const LOAS_SYNC_WORD: u16 = 0x2b7;
+
+/// Appends LOAS `AudioSyncStream` frame with LATM `AudioMuxElement`
+/// carrying `StreamMuxConfig` and a single raw frame
+pub fn push_loas(out: &mut Vec<u8>, asc: &AudioSpecificConfig, frame: &[u8]) -> Result<(), Error> {
+    let mut w = bits::Writer::new();
+    w.write_bool(false); // useSameStreamMux
+
+    // StreamMuxConfig
+    w.write_bool(false); // audioMuxVersion
+    w.write_bool(true); // allStreamsSameTimeFraming
+    w.write_bits(0, 6); // numSubFrames
+    w.write_bits(0, 4); // numProgram
+    w.write_bits(0, 3); // numLayer
+    asc.write(&mut w);
+    w.write_bits(0, 3); // frameLengthType
+    w.write_bits(0xff, 8); // latmBufferFullness
+    w.write_bool(false); // otherDataPresent
+    w.write_bool(false); // crcCheckPresent
+
+    // PayloadLengthInfo and PayloadMux
+    let mut len = frame.len();
+    while len >= 255 {
+        w.write_bits(255, 8);
+        len -= 255;
+    }
+    w.write_bits(len as u64, 8);
+    for &b in frame {
+        w.write_bits(b as u64, 8);
+    }
+    w.byte_align();
+    let element = w.into_vec();
+    if element.len() > 0x1fff {
+        return Err(Error::InvalidValue("audioMuxLengthBytes"));
+    }
+    let header = (LOAS_SYNC_WORD as u32) << 13 | element.len() as u32;
+    out.extend_from_slice(&header.to_be_bytes()[1..]);
+    out.extend_from_slice(&element);
+    Ok(())
+}
+
+/// Extracts raw frames from LOAS stream, remembering `StreamMuxConfig`
+/// for elements which reuse it.
+///
+/// ```
+/// use cidre::media::aac;
+///
+/// let asc = aac::AudioSpecificConfig::lc(48_000, 2);
+/// let mut stream = Vec::new();
+/// aac::push_loas(&mut stream, &asc, &[0xde, 0xad]).unwrap();
+///
+/// let mut reader = aac::LoasReader::new();
+/// assert_eq!(reader.read(&stream).unwrap(), [vec![0xde, 0xad]]);
+/// assert_eq!(reader.config(), Some(&asc));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct LoasReader {
+    config: Option<AudioSpecificConfig>,
+}
+
+impl LoasReader {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Config of the last `StreamMuxConfig`
+    #[inline]
+    pub fn config(&self) -> Option<&AudioSpecificConfig> {
+        self.config.as_ref()
+    }
+
+    /// Raw frames of complete LOAS frames in `data`
+    pub fn read(&mut self, mut data: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+        let mut frames = Vec::new();
+        while !data.is_empty() {
+            let header = data.get(..3).ok_or(Error::Truncated)?;
+            let header = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+            if header >> 13 != LOAS_SYNC_WORD as u32 {
+                return Err(Error::InvalidSync);
+            }
+            let len = (header & 0x1fff) as usize;
+            let element = data.get(3..3 + len).ok_or(Error::Truncated)?;
+            self.read_element(element, &mut frames)?;
+            data = &data[3 + len..];
+        }
+        Ok(frames)
+    }
+
+    fn read_element(&mut self, element: &[u8], frames: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        let mut r = bits::Reader::new(element);
+        if !r.read_bool()? {
+            self.read_stream_mux_config(&mut r)?;
+        }
+        if self.config.is_none() {
+            return Err(Error::InvalidValue(
+                "useSameStreamMux without StreamMuxConfig",
+            ));
+        }
+        let mut len = 0;
+        loop {
+            let tmp = r.read_u8(8)?;
+            len += tmp as usize;
+            if tmp != 255 {
+                break;
+            }
+        }
+        if r.bits_left() < len * 8 {
+            return Err(Error::Truncated);
+        }
+        let mut frame = Vec::with_capacity(len);
+        for _ in 0..len {
+            frame.push(r.read_u8(8)?);
+        }
+        // other data following the payload is ignored
+        frames.push(frame);
+        Ok(())
+    }
+
+    fn read_stream_mux_config(&mut self, r: &mut bits::Reader) -> Result<(), Error> {
+        if r.read_bool()? {
+            return Err(Error::Unsupported("audioMuxVersion"));
+        }
+        if !r.read_bool()? {
+            return Err(Error::Unsupported("allStreamsSameTimeFraming"));
+        }
+        if r.read_u8(6)? != 0 {
+            return Err(Error::Unsupported("numSubFrames"));
+        }
+        if r.read_u8(4)? != 0 || r.read_u8(3)? != 0 {
+            return Err(Error::Unsupported("multiple programs or layers"));
+        }
+        let asc = AudioSpecificConfig::read(r)?;
+        if r.read_u8(3)? != 0 {
+            return Err(Error::Unsupported("frameLengthType"));
+        }
+        r.skip(8)?; // latmBufferFullness
+        if r.read_bool()? {
+            // otherDataLenBits
+            loop {
+                let esc = r.read_bool()?;
+                r.skip(8)?;
+                if !esc {
+                    break;
+                }
+            }
+        }
+        if r.read_bool()? {
+            r.skip(8)?; // crcCheckSum
+        }
+        self.config = Some(asc);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        adts_frames, push_adts, push_loas, Adts, AudioSpecificConfig, Error, LoasReader, ObjectType,
+    };
+
+    #[test]
+    fn asc() {
+        // HE-AAC v2 with explicit signalling: 24 kHz core, 48 kHz SBR
+        let asc = AudioSpecificConfig {
+            sbr_sample_rate: Some(48_000),
+            ps: true,
+            ..AudioSpecificConfig::lc(24_000, 1)
+        };
+        let bytes = asc.to_bytes();
+        assert_eq!(bytes[0] >> 3, 29);
+        assert_eq!(AudioSpecificConfig::parse(&bytes).unwrap(), asc);
+        assert_eq!(asc.samples_per_frame(), 2048);
+        assert_eq!(asc.output_sample_rate(), 48_000);
+        assert_eq!(asc.codec_string(), "mp4a.40.29");
+
+        // explicit sample rate and escaped object type
+        let asc = AudioSpecificConfig {
+            object_type: ObjectType(42),
+            ..AudioSpecificConfig::lc(12_345, 2)
+        };
+        let parsed = AudioSpecificConfig::parse(&asc.to_bytes()).unwrap();
+        assert_eq!(parsed, asc);
+
+        assert_eq!(AudioSpecificConfig::parse(&[0x12]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn adts() {
+        let asc = AudioSpecificConfig::lc(44_100, 2);
+        let header = Adts::with_asc(&asc, 371).unwrap().to_bytes();
+        assert_eq!(header, [0xff, 0xf1, 0x50, 0x80, 0x2f, 0x5f, 0xfc]);
+
+        let mut stream = Vec::new();
+        push_adts(&mut stream, &asc, &[7; 10]).unwrap();
+        stream.extend_from_slice(&[0xff, 0xf1, 0x50]);
+        let mut frames = adts_frames(&stream);
+        let (adts, frame) = frames.next().unwrap().unwrap();
+        assert_eq!(adts.frame_len, 17);
+        assert_eq!(frame, [7; 10]);
+        assert_eq!(frames.next(), Some(Err(Error::Truncated)));
+        assert_eq!(frames.next(), None);
+
+        let asc = AudioSpecificConfig {
+            object_type: ObjectType::ER_AAC_ELD,
+            ..asc
+        };
+        assert!(Adts::with_asc(&asc, 1).is_err());
+    }
+
+    #[test]
+    fn loas() {
+        let asc = AudioSpecificConfig::lc(48_000, 2);
+        let big: Vec<u8> = (0..600).map(|i| i as u8).collect();
+        let mut stream = Vec::new();
+        push_loas(&mut stream, &asc, &big).unwrap();
+        push_loas(&mut stream, &asc, &[1]).unwrap();
+        assert_eq!(stream[0], 0x56);
+        assert_eq!(stream[1] >> 5, 0x7);
+
+        let mut reader = LoasReader::new();
+        let frames = reader.read(&stream).unwrap();
+        assert_eq!(frames, [big, vec![1]]);
+
+        // element reusing previous config
+        let reuse = [0x56, 0xe0, 0x03, 0x80, 0xd5, 0x80];
+        assert_eq!(reader.read(&reuse).unwrap(), [vec![0xab]]);
+        assert_eq!(
+            LoasReader::new().read(&reuse),
+            Err(Error::InvalidValue(
+                "useSameStreamMux without StreamMuxConfig"
+            ))
+        );
+        assert_eq!(reader.read(&[0x56, 0xe0]), Err(Error::Truncated));
+    }
+}
@@ -0,0 +1,221 @@
+//! Action Message Format values of RTMP commands and FLV script data.
+//!
+//! [`amf0`] is used by `onMetaData` and RTMP commands, [`amf3`] by `avmplus` values
+//! embedded in AMF0 and by AMF3 command messages. Both share [`Value`].
+//!
+//! ```
+//! use cidre::media::amf::{self, Value};
+//!
+//! let value = Value::object([("width", 1920.0.into()), ("codec", "avc1".into())]);
+//!
+//! let mut out = Vec::new();
+//! amf::amf0::write(&mut out, &value);
+//! assert_eq!(amf::amf0::read_all(&out).unwrap(), [value.clone()]);
+//!
+//! let mut out = Vec::new();
+//! amf::amf3::Writer::new().write(&mut out, &value);
+//! assert_eq!(amf::amf3::Reader::new(&out).read().unwrap(), value);
+//! assert_eq!(value.get("width").and_then(Value::as_f64), Some(1920.0));
+//! ```
+
+pub mod amf0;
+pub mod amf3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the value
+    Truncated,
+
+    /// Unknown or reserved type marker
+    InvalidMarker(u8),
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// String is not UTF-8
+    Utf8,
+
+    /// Valid type which isn't supported, e.g. AMF3 vectors and dictionaries
+    Unsupported(u8),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated AMF data"),
+            Self::InvalidMarker(marker) => write!(f, "invalid AMF type marker {marker:#x}"),
+            Self::InvalidValue(name) => write!(f, "invalid AMF {name}"),
+            Self::Utf8 => f.write_str("AMF string is not UTF-8"),
+            Self::Unsupported(marker) => write!(f, "unsupported AMF type marker {marker:#x}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::string::FromUtf8Error> for Error {
+    #[inline]
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        Self::Utf8
+    }
+}
+
+/// Object with its class name and properties in order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Object {
+    /// `None` for anonymous objects
+    pub class_name: Option<String>,
+    pub properties: Vec<(String, Value)>,
+}
+
+/// AMF0 or AMF3 value.
+///
+/// Values are decoded without sharing, references to earlier values are resolved into copies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Value {
+    Undefined,
+
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+
+    /// AMF3 29-bit integer, AMF0 writes it as number
+    Integer(i32),
+    String(String),
+    Object(Object),
+
+    /// Associative array, AMF3 arrays with dense part use keys of element indices
+    EcmaArray(Vec<(String, Value)>),
+
+    /// AMF0 strict array or dense AMF3 array
+    Array(Vec<Value>),
+
+    /// Milliseconds since Unix epoch in UTC
+    Date(f64),
+    XmlDocument(String),
+
+    /// AMF3 byte array, AMF0 writes it as AMF3 value
+    ByteArray(Vec<u8>),
+}
+
+impl Value {
+    /// Anonymous object of properties
+    pub fn object<'a>(properties: impl IntoIterator<Item = (&'a str, Value)>) -> Self {
+        Self::Object(Object {
+            class_name: None,
+            properties: properties
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        })
+    }
+
+    /// ECMA array of properties, e.g. of `onMetaData`
+    pub fn ecma_array<'a>(properties: impl IntoIterator<Item = (&'a str, Value)>) -> Self {
+        Self::EcmaArray(
+            properties
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    /// Properties of object or ECMA array
+    pub fn properties(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Self::Object(object) => Some(&object.properties),
+            Self::EcmaArray(properties) => Some(properties),
+            _ => None,
+        }
+    }
+
+    /// Property of object or ECMA array
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.properties()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Number(n) => Some(n),
+            Self::Integer(i) => Some(i as f64),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl From<f64> for Value {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+/// Numbers of `onMetaData` and commands are AMF0 numbers, so integers become numbers too
+impl From<u32> for Value {
+    #[inline]
+    fn from(value: u32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Object> for Value {
+    #[inline]
+    fn from(value: Object) -> Self {
+        Self::Object(value)
+    }
+}
+
+/// Splits `len` bytes off the front of `data`
+#[inline]
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    let (head, tail) = data.split_at_checked(len).ok_or(Error::Truncated)?;
+    *data = tail;
+    Ok(head)
+}
+
+#[inline]
+fn take_array<const N: usize>(data: &mut &[u8]) -> Result<[u8; N], Error> {
+    Ok(take(data, N)?.try_into().unwrap())
+}
@@ -0,0 +1,347 @@
+//! AMF0 encoding.
+
+use super::{amf3, take, take_array, Error, Object, Value};
+
+pub const NUMBER: u8 = 0x00;
+pub const BOOLEAN: u8 = 0x01;
+pub const STRING: u8 = 0x02;
+pub const OBJECT: u8 = 0x03;
+pub const NULL: u8 = 0x05;
+pub const UNDEFINED: u8 = 0x06;
+pub const REFERENCE: u8 = 0x07;
+pub const ECMA_ARRAY: u8 = 0x08;
+pub const OBJECT_END: u8 = 0x09;
+pub const STRICT_ARRAY: u8 = 0x0a;
+pub const DATE: u8 = 0x0b;
+pub const LONG_STRING: u8 = 0x0c;
+pub const UNSUPPORTED: u8 = 0x0d;
+pub const XML_DOCUMENT: u8 = 0x0f;
+pub const TYPED_OBJECT: u8 = 0x10;
+
+/// Switch to AMF3 for the next value
+pub const AVMPLUS_OBJECT: u8 = 0x11;
+
+/// Limit of nested objects and arrays
+const MAX_DEPTH: usize = 64;
+
+/// Reader of consecutive AMF0 values sharing reference table
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+
+    /// Objects and arrays in order of their start, `None` while being read
+    refs: Vec<Option<Value>>,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            refs: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Data after values read so far
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    #[inline]
+    pub fn read(&mut self) -> Result<Value, Error> {
+        self.read_value(0)
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidValue("nesting depth"));
+        }
+        let [marker] = take_array(&mut self.data)?;
+        Ok(match marker {
+            NUMBER => Value::Number(f64::from_be_bytes(take_array(&mut self.data)?)),
+            BOOLEAN => Value::Bool(take_array::<1>(&mut self.data)?[0] != 0),
+            STRING => Value::String(self.read_string()?),
+            OBJECT | TYPED_OBJECT => {
+                let class_name = if marker == TYPED_OBJECT {
+                    Some(self.read_string()?)
+                } else {
+                    None
+                };
+                let index = self.reserve();
+                let properties = self.read_properties(depth)?;
+                self.complete(
+                    index,
+                    Object {
+                        class_name,
+                        properties,
+                    }
+                    .into(),
+                )
+            }
+            NULL => Value::Null,
+            UNDEFINED | UNSUPPORTED => Value::Undefined,
+            REFERENCE => {
+                let index = u16::from_be_bytes(take_array(&mut self.data)?) as usize;
+                match self.refs.get(index) {
+                    Some(Some(value)) => value.clone(),
+                    Some(None) => return Err(Error::InvalidValue("cyclic reference")),
+                    None => return Err(Error::InvalidValue("reference")),
+                }
+            }
+            ECMA_ARRAY => {
+                // associative count is a hint only, the array ends with object end marker
+                take(&mut self.data, 4)?;
+                let index = self.reserve();
+                let properties = self.read_properties(depth)?;
+                self.complete(index, Value::EcmaArray(properties))
+            }
+            STRICT_ARRAY => {
+                let count = u32::from_be_bytes(take_array(&mut self.data)?) as usize;
+                let index = self.reserve();
+                // each value takes at least a byte
+                let mut values = Vec::with_capacity(count.min(self.data.len()));
+                for _ in 0..count {
+                    values.push(self.read_value(depth + 1)?);
+                }
+                self.complete(index, Value::Array(values))
+            }
+            DATE => {
+                let millis = f64::from_be_bytes(take_array(&mut self.data)?);
+                // time zone is reserved and should be 0
+                take(&mut self.data, 2)?;
+                Value::Date(millis)
+            }
+            LONG_STRING => Value::String(self.read_long_string()?),
+            XML_DOCUMENT => Value::XmlDocument(self.read_long_string()?),
+            AVMPLUS_OBJECT => {
+                let mut reader = amf3::Reader::new(self.data);
+                let value = reader.read()?;
+                self.data = reader.remaining();
+                value
+            }
+            marker => return Err(Error::InvalidMarker(marker)),
+        })
+    }
+
+    fn read_string(&mut self) -> Result<String, Error> {
+        let len = u16::from_be_bytes(take_array(&mut self.data)?) as usize;
+        Ok(String::from_utf8(take(&mut self.data, len)?.to_vec())?)
+    }
+
+    fn read_long_string(&mut self) -> Result<String, Error> {
+        let len = u32::from_be_bytes(take_array(&mut self.data)?) as usize;
+        Ok(String::from_utf8(take(&mut self.data, len)?.to_vec())?)
+    }
+
+    fn read_properties(&mut self, depth: usize) -> Result<Vec<(String, Value)>, Error> {
+        let mut properties = Vec::new();
+        loop {
+            let key = self.read_string()?;
+            if key.is_empty() && self.data.first() == Some(&OBJECT_END) {
+                self.data = &self.data[1..];
+                return Ok(properties);
+            }
+            let value = self.read_value(depth + 1)?;
+            properties.push((key, value));
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self) -> usize {
+        self.refs.push(None);
+        self.refs.len() - 1
+    }
+
+    #[inline]
+    fn complete(&mut self, index: usize, value: Value) -> Value {
+        self.refs[index] = Some(value.clone());
+        value
+    }
+}
+
+/// All values of data, e.g. of RTMP command or FLV script tag
+pub fn read_all(data: &[u8]) -> Result<Vec<Value>, Error> {
+    let mut reader = Reader::new(data);
+    let mut values = Vec::new();
+    while !reader.is_empty() {
+        values.push(reader.read()?);
+    }
+    Ok(values)
+}
+
+/// Appends value without references.
+///
+/// Property keys and class names must be shorter than 64 KiB.
+pub fn write(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Undefined => out.push(UNDEFINED),
+        Value::Null => out.push(NULL),
+        Value::Bool(b) => out.extend_from_slice(&[BOOLEAN, *b as u8]),
+        Value::Number(n) => write_number(out, *n),
+        Value::Integer(i) => write_number(out, *i as f64),
+        Value::String(s) if s.len() <= u16::MAX as usize => {
+            out.push(STRING);
+            write_string(out, s);
+        }
+        Value::String(s) => {
+            out.push(LONG_STRING);
+            write_long_string(out, s);
+        }
+        Value::Object(object) => {
+            match &object.class_name {
+                Some(name) => {
+                    out.push(TYPED_OBJECT);
+                    write_string(out, name);
+                }
+                None => out.push(OBJECT),
+            }
+            write_properties(out, &object.properties);
+        }
+        Value::EcmaArray(properties) => {
+            out.push(ECMA_ARRAY);
+            out.extend_from_slice(&(properties.len() as u32).to_be_bytes());
+            write_properties(out, properties);
+        }
+        Value::Array(values) => {
+            out.push(STRICT_ARRAY);
+            out.extend_from_slice(&(values.len() as u32).to_be_bytes());
+            for v in values {
+                write(out, v);
+            }
+        }
+        Value::Date(millis) => {
+            out.push(DATE);
+            out.extend_from_slice(&millis.to_be_bytes());
+            out.extend_from_slice(&[0, 0]);
+        }
+        Value::XmlDocument(s) => {
+            out.push(XML_DOCUMENT);
+            write_long_string(out, s);
+        }
+        Value::ByteArray(_) => {
+            out.push(AVMPLUS_OBJECT);
+            amf3::Writer::new().write(out, value);
+        }
+    }
+}
+
+#[inline]
+fn write_number(out: &mut Vec<u8>, n: f64) {
+    out.push(NUMBER);
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+#[inline]
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    debug_assert!(s.len() <= u16::MAX as usize);
+    out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+#[inline]
+fn write_long_string(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_properties(out: &mut Vec<u8>, properties: &[(String, Value)]) {
+    for (key, value) in properties {
+        write_string(out, key);
+        write(out, value);
+    }
+    out.extend_from_slice(&[0, 0, OBJECT_END]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_all, write, Reader};
+    use crate::media::amf::{Error, Object, Value};
+
+    #[test]
+    fn connect() {
+        // "connect", 1, {app: "live", tcUrl: "rtmp://localhost/live"}
+        let mut data = vec![0x02, 0, 7];
+        data.extend_from_slice(b"connect");
+        data.extend_from_slice(&[0x00, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0x03, 0, 3]);
+        data.extend_from_slice(b"app");
+        data.extend_from_slice(&[0x02, 0, 4]);
+        data.extend_from_slice(b"live");
+        data.extend_from_slice(&[0, 5]);
+        data.extend_from_slice(b"tcUrl");
+        data.extend_from_slice(&[0x02, 0, 21]);
+        data.extend_from_slice(b"rtmp://localhost/live");
+        data.extend_from_slice(&[0, 0, 0x09]);
+
+        let values = read_all(&data).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0].as_str(), Some("connect"));
+        assert_eq!(values[1], Value::Number(1.0));
+        assert_eq!(values[2].get("app").and_then(Value::as_str), Some("live"));
+
+        let mut out = Vec::new();
+        for v in &values {
+            write(&mut out, v);
+        }
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn round_trip() {
+        let values = [
+            Value::Undefined,
+            Value::Null,
+            Value::Bool(true),
+            Value::String("x".repeat(70_000)),
+            Value::ecma_array([("duration", 0.0.into()), ("stereo", true.into())]),
+            Value::Array(vec![1.0.into(), "a".into()]),
+            Value::Date(1.5e12),
+            Value::XmlDocument("<a/>".into()),
+            Object {
+                class_name: Some("Point".into()),
+                properties: vec![("x".into(), 1.0.into())],
+            }
+            .into(),
+            Value::ByteArray(vec![1, 2, 3]),
+        ];
+        let mut out = Vec::new();
+        for v in &values {
+            write(&mut out, v);
+        }
+        assert_eq!(read_all(&out).unwrap(), values);
+
+        // integers become numbers
+        let mut out = Vec::new();
+        write(&mut out, &Value::Integer(3));
+        assert_eq!(read_all(&out).unwrap(), [Value::Number(3.0)]);
+    }
+
+    #[test]
+    fn references() {
+        // [{a: null}, ref 1, ref 0]
+        let data = [
+            0x0a, 0, 0, 0, 3, 0x03, 0, 1, b'a', 0x05, 0, 0, 0x09, 0x07, 0, 1, 0x07, 0, 0,
+        ];
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read(), Err(Error::InvalidValue("cyclic reference")));
+
+        let object = Value::object([("a", Value::Null)]);
+        let data = [
+            0x0a, 0, 0, 0, 2, 0x03, 0, 1, b'a', 0x05, 0, 0, 0x09, 0x07, 0, 1,
+        ];
+        assert_eq!(
+            read_all(&data).unwrap(),
+            [Value::Array(vec![object.clone(), object])]
+        );
+
+        assert_eq!(read_all(&[0x04]), Err(Error::InvalidMarker(0x04)));
+        assert_eq!(read_all(&[0x02, 0, 2, b'a']), Err(Error::Truncated));
+        let nested = [0x0a, 0, 0, 0, 1].repeat(100);
+        assert_eq!(read_all(&nested), Err(Error::InvalidValue("nesting depth")));
+    }
+}
@@ -0,0 +1,473 @@
+//! AMF3 encoding.
+
+use std::collections::HashMap;
+
+use super::{take, take_array, Error, Object, Value};
+
+pub const UNDEFINED: u8 = 0x00;
+pub const NULL: u8 = 0x01;
+pub const FALSE: u8 = 0x02;
+pub const TRUE: u8 = 0x03;
+pub const INTEGER: u8 = 0x04;
+pub const DOUBLE: u8 = 0x05;
+pub const STRING: u8 = 0x06;
+pub const XML_DOC: u8 = 0x07;
+pub const DATE: u8 = 0x08;
+pub const ARRAY: u8 = 0x09;
+pub const OBJECT: u8 = 0x0a;
+pub const XML: u8 = 0x0b;
+pub const BYTE_ARRAY: u8 = 0x0c;
+
+/// Range of `U29` integers
+pub const INTEGER_MIN: i32 = -(1 << 28);
+pub const INTEGER_MAX: i32 = (1 << 28) - 1;
+
+/// Limit of nested objects and arrays
+const MAX_DEPTH: usize = 64;
+
+/// Reads variable length 29-bit unsigned integer
+fn read_u29(data: &mut &[u8]) -> Result<u32, Error> {
+    let mut value = 0;
+    for _ in 0..3 {
+        let [b] = take_array(data)?;
+        value = value << 7 | (b & 0x7f) as u32;
+        if b & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    let [b] = take_array(data)?;
+    Ok(value << 8 | b as u32)
+}
+
+/// Appends variable length 29-bit unsigned integer, higher bits are ignored
+pub fn write_u29(out: &mut Vec<u8>, value: u32) {
+    let value = value & 0x1fff_ffff;
+    if value < 0x80 {
+        out.push(value as u8);
+    } else if value < 0x4000 {
+        out.extend_from_slice(&[(value >> 7) as u8 | 0x80, value as u8 & 0x7f]);
+    } else if value < 0x20_0000 {
+        out.extend_from_slice(&[
+            (value >> 14) as u8 | 0x80,
+            (value >> 7) as u8 | 0x80,
+            value as u8 & 0x7f,
+        ]);
+    } else {
+        out.extend_from_slice(&[
+            (value >> 22) as u8 | 0x80,
+            (value >> 15) as u8 | 0x80,
+            (value >> 8) as u8 | 0x80,
+            value as u8,
+        ]);
+    }
+}
+
+/// Sealed member names of class
+#[derive(Debug, Clone)]
+struct Traits {
+    class_name: Option<String>,
+    dynamic: bool,
+    members: Vec<String>,
+}
+
+/// Reader of consecutive AMF3 values sharing reference tables
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+    strings: Vec<String>,
+
+    /// Complex values in order of their start, `None` while being read
+    objects: Vec<Option<Value>>,
+    traits: Vec<Traits>,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            strings: Vec::new(),
+            objects: Vec::new(),
+            traits: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Data after values read so far
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    #[inline]
+    pub fn read(&mut self) -> Result<Value, Error> {
+        self.read_value(0)
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidValue("nesting depth"));
+        }
+        let [marker] = take_array(&mut self.data)?;
+        Ok(match marker {
+            UNDEFINED => Value::Undefined,
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            INTEGER => {
+                // sign extend 29 bits
+                let value = read_u29(&mut self.data)?;
+                Value::Integer(((value << 3) as i32) >> 3)
+            }
+            DOUBLE => Value::Number(f64::from_be_bytes(take_array(&mut self.data)?)),
+            STRING => Value::String(self.read_string()?),
+            XML_DOC | XML | DATE | BYTE_ARRAY => {
+                let header = read_u29(&mut self.data)?;
+                if header & 1 == 0 {
+                    return self.reference(header >> 1);
+                }
+                let len = (header >> 1) as usize;
+                let value = match marker {
+                    DATE => Value::Date(f64::from_be_bytes(take_array(&mut self.data)?)),
+                    BYTE_ARRAY => Value::ByteArray(take(&mut self.data, len)?.to_vec()),
+                    _ => {
+                        Value::XmlDocument(String::from_utf8(take(&mut self.data, len)?.to_vec())?)
+                    }
+                };
+                self.objects.push(Some(value.clone()));
+                value
+            }
+            ARRAY => {
+                let header = read_u29(&mut self.data)?;
+                if header & 1 == 0 {
+                    return self.reference(header >> 1);
+                }
+                let count = (header >> 1) as usize;
+                let index = self.reserve();
+                let mut properties = Vec::new();
+                loop {
+                    let key = self.read_string()?;
+                    if key.is_empty() {
+                        break;
+                    }
+                    properties.push((key, self.read_value(depth + 1)?));
+                }
+                // each value takes at least a byte
+                let mut values = Vec::with_capacity(count.min(self.data.len()));
+                for _ in 0..count {
+                    values.push(self.read_value(depth + 1)?);
+                }
+                let value = if properties.is_empty() {
+                    Value::Array(values)
+                } else {
+                    let dense = values.into_iter().enumerate();
+                    properties.extend(dense.map(|(i, v)| (i.to_string(), v)));
+                    Value::EcmaArray(properties)
+                };
+                self.complete(index, value)
+            }
+            OBJECT => {
+                let header = read_u29(&mut self.data)?;
+                if header & 1 == 0 {
+                    return self.reference(header >> 1);
+                }
+                let traits = self.read_traits(header)?;
+                let index = self.reserve();
+                let mut properties = Vec::with_capacity(traits.members.len());
+                for name in &traits.members {
+                    properties.push((name.clone(), self.read_value(depth + 1)?));
+                }
+                if traits.dynamic {
+                    loop {
+                        let key = self.read_string()?;
+                        if key.is_empty() {
+                            break;
+                        }
+                        properties.push((key, self.read_value(depth + 1)?));
+                    }
+                }
+                let object = Object {
+                    class_name: traits.class_name,
+                    properties,
+                };
+                self.complete(index, object.into())
+            }
+            0x0d..=0x11 => return Err(Error::Unsupported(marker)),
+            marker => return Err(Error::InvalidMarker(marker)),
+        })
+    }
+
+    fn read_string(&mut self) -> Result<String, Error> {
+        let header = read_u29(&mut self.data)?;
+        if header & 1 == 0 {
+            return self
+                .strings
+                .get((header >> 1) as usize)
+                .cloned()
+                .ok_or(Error::InvalidValue("string reference"));
+        }
+        let bytes = take(&mut self.data, (header >> 1) as usize)?;
+        let s = String::from_utf8(bytes.to_vec())?;
+        // empty string is never sent by reference
+        if !s.is_empty() {
+            self.strings.push(s.clone());
+        }
+        Ok(s)
+    }
+
+    fn read_traits(&mut self, header: u32) -> Result<Traits, Error> {
+        if header & 2 == 0 {
+            return self
+                .traits
+                .get((header >> 2) as usize)
+                .cloned()
+                .ok_or(Error::InvalidValue("traits reference"));
+        }
+        if header & 4 != 0 {
+            // externalizable traits need class specific decoding
+            return Err(Error::Unsupported(OBJECT));
+        }
+        let class_name = Some(self.read_string()?).filter(|s| !s.is_empty());
+        let count = (header >> 4) as usize;
+        let mut members = Vec::with_capacity(count.min(self.data.len()));
+        for _ in 0..count {
+            members.push(self.read_string()?);
+        }
+        let traits = Traits {
+            class_name,
+            dynamic: header & 8 != 0,
+            members,
+        };
+        self.traits.push(traits.clone());
+        Ok(traits)
+    }
+
+    fn reference(&self, index: u32) -> Result<Value, Error> {
+        match self.objects.get(index as usize) {
+            Some(Some(value)) => Ok(value.clone()),
+            Some(None) => Err(Error::InvalidValue("cyclic reference")),
+            None => Err(Error::InvalidValue("object reference")),
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self) -> usize {
+        self.objects.push(None);
+        self.objects.len() - 1
+    }
+
+    #[inline]
+    fn complete(&mut self, index: usize, value: Value) -> Value {
+        self.objects[index] = Some(value.clone());
+        value
+    }
+}
+
+/// Writer of consecutive AMF3 values sharing string reference table.
+///
+/// Anonymous objects are written as dynamic, typed ones with sealed members.
+/// Complex values are always written inline.
+#[derive(Debug, Clone, Default)]
+pub struct Writer {
+    strings: HashMap<String, u32>,
+}
+
+impl Writer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, out: &mut Vec<u8>, value: &Value) {
+        match value {
+            Value::Undefined => out.push(UNDEFINED),
+            Value::Null => out.push(NULL),
+            Value::Bool(false) => out.push(FALSE),
+            Value::Bool(true) => out.push(TRUE),
+            Value::Integer(i) if (INTEGER_MIN..=INTEGER_MAX).contains(i) => {
+                out.push(INTEGER);
+                write_u29(out, *i as u32);
+            }
+            Value::Integer(i) => self.write(out, &Value::Number(*i as f64)),
+            Value::Number(n) => {
+                out.push(DOUBLE);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::String(s) => {
+                out.push(STRING);
+                self.write_string(out, s);
+            }
+            Value::Object(object) => {
+                out.push(OBJECT);
+                match &object.class_name {
+                    Some(name) => {
+                        // inline sealed traits
+                        write_u29(out, (object.properties.len() as u32) << 4 | 0b0011);
+                        self.write_string(out, name);
+                        for (key, _) in &object.properties {
+                            self.write_string(out, key);
+                        }
+                        for (_, value) in &object.properties {
+                            self.write(out, value);
+                        }
+                    }
+                    None => {
+                        // inline dynamic traits without sealed members
+                        write_u29(out, 0b1011);
+                        self.write_string(out, "");
+                        self.write_properties(out, &object.properties);
+                    }
+                }
+            }
+            Value::EcmaArray(properties) => {
+                out.push(ARRAY);
+                write_u29(out, 1);
+                self.write_properties(out, properties);
+            }
+            Value::Array(values) => {
+                out.push(ARRAY);
+                write_u29(out, (values.len() as u32) << 1 | 1);
+                self.write_string(out, "");
+                for v in values {
+                    self.write(out, v);
+                }
+            }
+            Value::Date(millis) => {
+                out.push(DATE);
+                write_u29(out, 1);
+                out.extend_from_slice(&millis.to_be_bytes());
+            }
+            Value::XmlDocument(s) => {
+                out.push(XML_DOC);
+                write_u29(out, (s.len() as u32) << 1 | 1);
+                out.extend_from_slice(s.as_bytes());
+            }
+            Value::ByteArray(bytes) => {
+                out.push(BYTE_ARRAY);
+                write_u29(out, (bytes.len() as u32) << 1 | 1);
+                out.extend_from_slice(bytes);
+            }
+        }
+    }
+
+    fn write_string(&mut self, out: &mut Vec<u8>, s: &str) {
+        if let Some(&index) = self.strings.get(s) {
+            write_u29(out, index << 1);
+            return;
+        }
+        write_u29(out, (s.len() as u32) << 1 | 1);
+        out.extend_from_slice(s.as_bytes());
+        if !s.is_empty() {
+            let index = self.strings.len() as u32;
+            self.strings.insert(s.to_string(), index);
+        }
+    }
+
+    fn write_properties(&mut self, out: &mut Vec<u8>, properties: &[(String, Value)]) {
+        for (key, value) in properties {
+            self.write_string(out, key);
+            self.write(out, value);
+        }
+        self.write_string(out, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_u29, write_u29, Reader, Writer};
+    use crate::media::amf::{Error, Object, Value};
+
+    #[test]
+    fn u29() {
+        for (value, bytes) in [
+            (0x7f, &[0x7f][..]),
+            (0x80, &[0x81, 0x00]),
+            (0x3fff, &[0xff, 0x7f]),
+            (0x4000, &[0x81, 0x80, 0x00]),
+            (0x1f_ffff, &[0xff, 0xff, 0x7f]),
+            (0x20_0000, &[0x80, 0xc0, 0x80, 0x00]),
+            (0x1fff_ffff, &[0xff, 0xff, 0xff, 0xff]),
+        ] {
+            let mut out = Vec::new();
+            write_u29(&mut out, value);
+            assert_eq!(out, bytes);
+            assert_eq!(read_u29(&mut &out[..]).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let values = [
+            Value::Undefined,
+            Value::Bool(false),
+            Value::Integer(-1),
+            Value::Integer(1 << 28),
+            Value::Number(0.5),
+            Value::object([("name", "a".into()), ("other", "name".into())]),
+            Object {
+                class_name: Some("Point".into()),
+                properties: vec![("x".into(), Value::Integer(1)), ("y".into(), 2.0.into())],
+            }
+            .into(),
+            Value::ecma_array([("name", Value::Null)]),
+            Value::Array(vec![Value::Integer(1), "name".into()]),
+            Value::Date(1.5e12),
+            Value::XmlDocument("<a/>".into()),
+            Value::ByteArray(vec![1, 2, 3]),
+        ];
+        let mut writer = Writer::new();
+        let mut out = Vec::new();
+        for v in &values {
+            writer.write(&mut out, v);
+        }
+        let mut reader = Reader::new(&out);
+        for v in &values {
+            let expected = match v {
+                // out of U29 range
+                Value::Integer(i) if *i == 1 << 28 => Value::Number(*i as f64),
+                v => v.clone(),
+            };
+            assert_eq!(reader.read().unwrap(), expected);
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn references() {
+        // [{a: "x"} with traits, object ref 1, object with traits ref 0, string ref 0 of "a"]
+        let data = [
+            0x09, 0x09, 0x01, // dense array of 4
+            0x0a, 0x0b, 0x01, 0x03, b'a', 0x06, 0x03, b'x', 0x01, // {a: "x"}
+            0x0a, 0x02, // reference to object 1
+            0x0a, 0x01, 0x00, 0x06, 0x00, 0x01, // dynamic traits ref 0, {a: "a"}
+            0x06, 0x00, // string "a"
+        ];
+        let object = Value::object([("a", "x".into())]);
+        assert_eq!(
+            Reader::new(&data).read().unwrap(),
+            Value::Array(vec![
+                object.clone(),
+                object,
+                Value::object([("a", "a".into())]),
+                "a".into()
+            ])
+        );
+
+        // mixed array
+        let data = [0x09, 0x03, 0x03, b'k', 0x01, 0x01, 0x04, 0x05];
+        assert_eq!(
+            Reader::new(&data).read().unwrap(),
+            Value::ecma_array([("k", Value::Null), ("0", Value::Integer(5))])
+        );
+
+        assert_eq!(
+            Reader::new(&[0x09, 0x00]).read(),
+            Err(Error::InvalidValue("object reference"))
+        );
+        assert_eq!(Reader::new(&[0x11]).read(), Err(Error::Unsupported(0x11)));
+    }
+}
@@ -0,0 +1,813 @@
+//! AV1 OBUs, sequence headers and `av1C` records.
+//!
+//! Samples in ISO BMFF and `cm::SampleBuf`s are temporal units of OBUs in low overhead
+//! bitstream format (each with `obu_size`) without temporal delimiters.
+
+use crate::media::{bits, vui};
+
+#[cfg(all(feature = "cm", feature = "cv"))]
+use crate::{arc, cf, cm, media::ext, os};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    UnexpectedObuType(ObuType),
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated AV1 data"),
+            Self::UnexpectedObuType(t) => write!(f, "unexpected AV1 OBU type {}", t.0),
+            Self::InvalidValue(name) => write!(f, "invalid AV1 {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Eof => Self::Truncated,
+            bits::Error::InvalidExpGolomb => Self::InvalidValue("uvlc"),
+        }
+    }
+}
+
+/// `obu_type`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct ObuType(pub u8);
+
+impl ObuType {
+    pub const SEQUENCE_HEADER: Self = Self(1);
+    pub const TEMPORAL_DELIMITER: Self = Self(2);
+    pub const FRAME_HEADER: Self = Self(3);
+    pub const TILE_GROUP: Self = Self(4);
+    pub const METADATA: Self = Self(5);
+    pub const FRAME: Self = Self(6);
+    pub const REDUNDANT_FRAME_HEADER: Self = Self(7);
+    pub const TILE_LIST: Self = Self(8);
+    pub const PADDING: Self = Self(15);
+}
+
+/// Reads `leb128()` value, returns value and its length in bytes
+pub fn read_leb128(data: &[u8]) -> Result<(u64, usize), Error> {
+    let mut value = 0u64;
+    for (i, &b) in data.iter().take(8).enumerate() {
+        value |= ((b & 0x7f) as u64) << (i * 7);
+        if b & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    if data.len() < 8 {
+        Err(Error::Truncated)
+    } else {
+        Err(Error::InvalidValue("leb128"))
+    }
+}
+
+pub fn write_leb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(b);
+            return;
+        }
+        out.push(b | 0x80);
+    }
+}
+
+/// Open bitstream unit
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Obu<'a> {
+    /// Whole OBU with header and size
+    pub data: &'a [u8],
+    pub obu_type: ObuType,
+    pub temporal_id: u8,
+    pub spatial_id: u8,
+    pub payload: &'a [u8],
+}
+
+/// Iterator over OBUs, see [`obus`]
+#[derive(Debug, Clone)]
+pub struct Obus<'a> {
+    rest: &'a [u8],
+}
+
+/// OBUs of temporal unit or `configOBUs`.
+///
+/// ```
+/// use cidre::media::av1;
+///
+/// let tu = [0x12, 0x00, 0x32, 0x02, 0xaa, 0xbb];
+/// let obus: Vec<_> = av1::obus(&tu).map(|o| o.unwrap().obu_type).collect();
+/// assert_eq!(obus, [av1::ObuType::TEMPORAL_DELIMITER, av1::ObuType::FRAME]);
+/// ```
+#[inline]
+pub fn obus(data: &[u8]) -> Obus<'_> {
+    Obus { rest: data }
+}
+
+impl<'a> Obus<'a> {
+    fn read(&mut self) -> Result<Obu<'a>, Error> {
+        let data = self.rest;
+        let header = *data.first().ok_or(Error::Truncated)?;
+        if header & 0x80 != 0 {
+            return Err(Error::InvalidValue("obu_forbidden_bit"));
+        }
+        let obu_type = ObuType((header >> 3) & 0xf);
+        let has_extension = header & 4 != 0;
+        let has_size = header & 2 != 0;
+        let mut pos = 1;
+        let (mut temporal_id, mut spatial_id) = (0, 0);
+        if has_extension {
+            let ext = *data.get(1).ok_or(Error::Truncated)?;
+            temporal_id = ext >> 5;
+            spatial_id = (ext >> 3) & 3;
+            pos += 1;
+        }
+        let size = if has_size {
+            let (size, len) = read_leb128(&data[pos..])?;
+            pos += len;
+            usize::try_from(size).map_err(|_| Error::Truncated)?
+        } else {
+            data.len() - pos
+        };
+        let end = pos.checked_add(size).ok_or(Error::Truncated)?;
+        if end > data.len() {
+            return Err(Error::Truncated);
+        }
+        self.rest = &data[end..];
+        Ok(Obu {
+            data: &data[..end],
+            obu_type,
+            temporal_id,
+            spatial_id,
+            payload: &data[pos..end],
+        })
+    }
+}
+
+impl<'a> Iterator for Obus<'a> {
+    type Item = Result<Obu<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let res = self.read();
+        if res.is_err() {
+            self.rest = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Appends OBU without extension and with `obu_size`
+pub fn push_obu(out: &mut Vec<u8>, obu_type: ObuType, payload: &[u8]) {
+    out.push((obu_type.0 << 3) | 2);
+    write_leb128(out, payload.len() as u64);
+    out.extend_from_slice(payload);
+}
+
+/// Temporal unit without temporal delimiters and padding for ISO BMFF samples
+pub fn to_sample(temporal_unit: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(temporal_unit.len());
+    for obu in obus(temporal_unit) {
+        let obu = obu?;
+        match obu.obu_type {
+            ObuType::TEMPORAL_DELIMITER | ObuType::PADDING | ObuType::TILE_LIST => {}
+            _ if obu.data[0] & 2 != 0 => out.extend_from_slice(obu.data),
+            // last OBU may omit size, samples need it on every OBU
+            _ => {
+                let header_len = obu.data.len() - obu.payload.len();
+                out.push(obu.data[0] | 2);
+                out.extend_from_slice(&obu.data[1..header_len]);
+                write_leb128(&mut out, obu.payload.len() as u64);
+                out.extend_from_slice(obu.payload);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Timing {
+    pub num_units_in_display_tick: u32,
+    pub time_scale: u32,
+    pub num_ticks_per_picture: Option<u32>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DecoderModel {
+    pub buffer_delay_length: u8,
+    pub num_units_in_decoding_tick: u32,
+    pub buffer_removal_time_length: u8,
+    pub frame_presentation_time_length: u8,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct OperatingPoint {
+    /// Bitmask of temporal layers in low 8 bits and spatial layers in next 4 bits
+    pub idc: u16,
+
+    /// `seq_level_idx`, level is `2 + (idx >> 2)`.`idx & 3`, 31 is unconstrained
+    pub level_idx: u8,
+
+    /// High tier if `true`
+    pub tier: bool,
+    pub initial_display_delay: Option<u8>,
+}
+
+/// `color_config()`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ColorConfig {
+    pub bit_depth: u8,
+    pub mono_chrome: bool,
+    pub color: vui::ColorDescription,
+    pub full_range: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+
+    /// 0 unknown, 1 vertical, 2 colocated
+    pub chroma_sample_position: u8,
+    pub separate_uv_delta_q: bool,
+}
+
+impl ColorConfig {
+    fn read(r: &mut bits::Reader, profile: u8) -> Result<Self, Error> {
+        let high_bitdepth = r.read_bool()?;
+        let bit_depth = if profile == 2 && high_bitdepth {
+            if r.read_bool()? {
+                12
+            } else {
+                10
+            }
+        } else if high_bitdepth {
+            10
+        } else {
+            8
+        };
+        let mono_chrome = profile != 1 && r.read_bool()?;
+        let color = if r.read_bool()? {
+            vui::ColorDescription::read(r)?
+        } else {
+            vui::ColorDescription {
+                primaries: 2,
+                transfer: 2,
+                matrix: 2,
+            }
+        };
+        let mut cfg = Self {
+            bit_depth,
+            mono_chrome,
+            color,
+            full_range: false,
+            subsampling_x: true,
+            subsampling_y: true,
+            chroma_sample_position: 0,
+            separate_uv_delta_q: false,
+        };
+        if mono_chrome {
+            cfg.full_range = r.read_bool()?;
+            return Ok(cfg);
+        }
+        if color == vui::ColorDescription::SRGB_IDENTITY {
+            cfg.full_range = true;
+            cfg.subsampling_x = false;
+            cfg.subsampling_y = false;
+        } else {
+            cfg.full_range = r.read_bool()?;
+            match profile {
+                0 => {}
+                1 => (cfg.subsampling_x, cfg.subsampling_y) = (false, false),
+                _ if bit_depth == 12 => {
+                    cfg.subsampling_x = r.read_bool()?;
+                    cfg.subsampling_y = cfg.subsampling_x && r.read_bool()?;
+                }
+                _ => cfg.subsampling_y = false,
+            }
+            if cfg.subsampling_x && cfg.subsampling_y {
+                cfg.chroma_sample_position = r.read_u8(2)?;
+            }
+        }
+        cfg.separate_uv_delta_q = r.read_bool()?;
+        Ok(cfg)
+    }
+
+    #[inline]
+    pub fn high_bitdepth(&self) -> bool {
+        self.bit_depth > 8
+    }
+
+    #[inline]
+    pub fn twelve_bit(&self) -> bool {
+        self.bit_depth == 12
+    }
+}
+
+/// Sequence header OBU.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SequenceHeader {
+    /// 0 Main, 1 High, 2 Professional
+    pub profile: u8,
+    pub still_picture: bool,
+    pub reduced_still_picture_header: bool,
+    pub timing: Option<Timing>,
+    pub decoder_model: Option<DecoderModel>,
+    pub operating_points: Vec<OperatingPoint>,
+    pub max_frame_width: u32,
+    pub max_frame_height: u32,
+    pub frame_id_numbers_present: bool,
+    pub use_128x128_superblock: bool,
+    pub enable_order_hint: bool,
+    pub order_hint_bits: u8,
+    pub enable_superres: bool,
+    pub enable_cdef: bool,
+    pub enable_restoration: bool,
+    pub color: ColorConfig,
+    pub film_grain_params_present: bool,
+}
+
+impl SequenceHeader {
+    /// Parses sequence header OBU with header, see [`SequenceHeader::parse_payload`]
+    pub fn parse(obu: &[u8]) -> Result<Self, Error> {
+        let obu = obus(obu).next().ok_or(Error::Truncated)??;
+        if obu.obu_type != ObuType::SEQUENCE_HEADER {
+            return Err(Error::UnexpectedObuType(obu.obu_type));
+        }
+        Self::parse_payload(obu.payload)
+    }
+
+    pub fn parse_payload(data: &[u8]) -> Result<Self, Error> {
+        let r = &mut bits::Reader::new(data);
+        let profile = r.read_u8(3)?;
+        if profile > 2 {
+            return Err(Error::InvalidValue("seq_profile"));
+        }
+        let still_picture = r.read_bool()?;
+        let reduced_still_picture_header = r.read_bool()?;
+        let mut timing = None;
+        let mut decoder_model = None;
+        let mut operating_points = Vec::with_capacity(1);
+        if reduced_still_picture_header {
+            operating_points.push(OperatingPoint {
+                idc: 0,
+                level_idx: r.read_u8(5)?,
+                tier: false,
+                initial_display_delay: None,
+            });
+        } else {
+            if r.read_bool()? {
+                let num_units_in_display_tick = r.read_u32(32)?;
+                let time_scale = r.read_u32(32)?;
+                let num_ticks_per_picture = if r.read_bool()? {
+                    Some(read_uvlc(r)?.saturating_add(1))
+                } else {
+                    None
+                };
+                timing = Some(Timing {
+                    num_units_in_display_tick,
+                    time_scale,
+                    num_ticks_per_picture,
+                });
+                if r.read_bool()? {
+                    decoder_model = Some(DecoderModel {
+                        buffer_delay_length: r.read_u8(5)? + 1,
+                        num_units_in_decoding_tick: r.read_u32(32)?,
+                        buffer_removal_time_length: r.read_u8(5)? + 1,
+                        frame_presentation_time_length: r.read_u8(5)? + 1,
+                    });
+                }
+            }
+            let initial_display_delay_present = r.read_bool()?;
+            let count = r.read_u8(5)? + 1;
+            for _ in 0..count {
+                let idc = r.read_u16(12)?;
+                let level_idx = r.read_u8(5)?;
+                let tier = level_idx > 7 && r.read_bool()?;
+                if let Some(model) = decoder_model {
+                    if r.read_bool()? {
+                        let n = model.buffer_delay_length as usize;
+                        r.skip(2 * n + 1)?;
+                    }
+                }
+                let initial_display_delay = if initial_display_delay_present && r.read_bool()? {
+                    Some(r.read_u8(4)? + 1)
+                } else {
+                    None
+                };
+                operating_points.push(OperatingPoint {
+                    idc,
+                    level_idx,
+                    tier,
+                    initial_display_delay,
+                });
+            }
+        }
+        let width_bits = r.read_u32(4)? + 1;
+        let height_bits = r.read_u32(4)? + 1;
+        let max_frame_width = r.read_u32(width_bits)? + 1;
+        let max_frame_height = r.read_u32(height_bits)? + 1;
+        let frame_id_numbers_present = !reduced_still_picture_header && r.read_bool()?;
+        if frame_id_numbers_present {
+            r.skip(7)?;
+        }
+        let use_128x128_superblock = r.read_bool()?;
+        r.skip(2)?; // enable_filter_intra, enable_intra_edge_filter
+        let mut enable_order_hint = false;
+        let mut order_hint_bits = 0;
+        if !reduced_still_picture_header {
+            r.skip(4)?; // interintra, masked compound, warped motion, dual filter
+            enable_order_hint = r.read_bool()?;
+            if enable_order_hint {
+                r.skip(2)?; // jnt_comp, ref_frame_mvs
+            }
+            let force_screen_content_tools = if r.read_bool()? { 2 } else { r.read_u8(1)? };
+            if force_screen_content_tools > 0 && !r.read_bool()? {
+                r.skip(1)?; // seq_force_integer_mv
+            }
+            if enable_order_hint {
+                order_hint_bits = r.read_u8(3)? + 1;
+            }
+        }
+        let enable_superres = r.read_bool()?;
+        let enable_cdef = r.read_bool()?;
+        let enable_restoration = r.read_bool()?;
+        let color = ColorConfig::read(r, profile)?;
+        let film_grain_params_present = r.read_bool()?;
+        Ok(Self {
+            profile,
+            still_picture,
+            reduced_still_picture_header,
+            timing,
+            decoder_model,
+            operating_points,
+            max_frame_width,
+            max_frame_height,
+            frame_id_numbers_present,
+            use_128x128_superblock,
+            enable_order_hint,
+            order_hint_bits,
+            enable_superres,
+            enable_cdef,
+            enable_restoration,
+            color,
+            film_grain_params_present,
+        })
+    }
+
+    /// First operating point, which is decoded by default
+    #[inline]
+    pub fn operating_point(&self) -> &OperatingPoint {
+        &self.operating_points[0]
+    }
+
+    /// Frames per second from timing info with equal picture interval
+    #[inline]
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        let t = self.timing?;
+        let ticks = t.num_ticks_per_picture? as u64;
+        vui::frame_rate(t.time_scale, t.num_units_in_display_tick as u64 * ticks)
+    }
+
+    /// RFC 6381 codec string, e.g. `av01.0.08M.08`. Color fields are included only
+    /// if they differ from defaults.
+    pub fn codec_string(&self) -> String {
+        let op = self.operating_point();
+        let c = &self.color;
+        let mut res = format!(
+            "av01.{}.{:02}{}.{:02}",
+            self.profile,
+            op.level_idx,
+            if op.tier { 'H' } else { 'M' },
+            c.bit_depth
+        );
+        let default = !c.mono_chrome
+            && c.subsampling_x
+            && c.subsampling_y
+            && c.chroma_sample_position == 0
+            && c.color == vui::ColorDescription::BT709
+            && !c.full_range;
+        if !default {
+            res.push_str(&format!(
+                ".{}.{}{}{}.{:02}.{:02}.{:02}.{}",
+                c.mono_chrome as u8,
+                c.subsampling_x as u8,
+                c.subsampling_y as u8,
+                c.chroma_sample_position,
+                c.color.primaries,
+                c.color.transfer,
+                c.color.matrix,
+                c.full_range as u8
+            ));
+        }
+        res
+    }
+}
+
+/// `uvlc()`
+fn read_uvlc(r: &mut bits::Reader) -> Result<u32, Error> {
+    let mut zeros = 0;
+    while !r.read_bool()? {
+        zeros += 1;
+        if zeros >= 32 {
+            return Ok(u32::MAX);
+        }
+    }
+    Ok(((1u64 << zeros) - 1 + r.read_bits(zeros)?) as u32)
+}
+
+/// Checks if temporal unit contains shown key frame
+pub fn is_sync_sample(temporal_unit: &[u8], seq: &SequenceHeader) -> Result<bool, Error> {
+    if seq.reduced_still_picture_header {
+        return Ok(true);
+    }
+    for obu in obus(temporal_unit) {
+        let obu = obu?;
+        if !matches!(obu.obu_type, ObuType::FRAME | ObuType::FRAME_HEADER) {
+            continue;
+        }
+        let r = &mut bits::Reader::new(obu.payload);
+        if r.read_bool()? {
+            // show_existing_frame
+            return Ok(false);
+        }
+        let key_frame = r.read_u8(2)? == 0;
+        return Ok(key_frame && r.read_bool()?);
+    }
+    Ok(false)
+}
+
+/// `AV1CodecConfigurationRecord`, contents of `av1C` box.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Av1c {
+    pub profile: u8,
+    pub level_idx: u8,
+    pub tier: bool,
+    pub high_bitdepth: bool,
+    pub twelve_bit: bool,
+    pub mono_chrome: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+    pub chroma_sample_position: u8,
+    pub initial_presentation_delay: Option<u8>,
+
+    /// Sequence header OBU and optional metadata OBUs
+    pub config_obus: Vec<u8>,
+}
+
+impl Av1c {
+    /// Record from the first sequence header OBU of temporal unit.
+    ///
+    /// ```
+    /// use cidre::media::av1;
+    ///
+    /// let seq = [
+    ///     0x0a, 0x0e, 0x00, 0x00, 0x00, 0x42, 0xab, 0xbf, 0xc3, 0x73, 0xff, 0xe6, 0x40, 0x40, 0x40,
+    ///     0x41,
+    /// ];
+    /// let av1c = av1::Av1c::with_obus(&seq).unwrap();
+    /// assert_eq!(av1c.to_bytes()[..4], [0x81, 0x08, 0x0c, 0x00]);
+    ///
+    /// let seq = av1c.parse_sequence_header().unwrap();
+    /// assert_eq!((seq.max_frame_width, seq.max_frame_height), (1920, 1080));
+    /// assert_eq!(seq.codec_string(), "av01.0.08M.08");
+    /// ```
+    pub fn with_obus(data: &[u8]) -> Result<Self, Error> {
+        for obu in obus(data) {
+            let obu = obu?;
+            if obu.obu_type == ObuType::SEQUENCE_HEADER {
+                let seq = SequenceHeader::parse_payload(obu.payload)?;
+                let mut config_obus = Vec::with_capacity(obu.data.len() + 2);
+                push_obu(&mut config_obus, ObuType::SEQUENCE_HEADER, obu.payload);
+                return Ok(Self::with_sequence_header(&seq, config_obus));
+            }
+        }
+        Err(Error::InvalidValue("missing sequence header"))
+    }
+
+    pub fn with_sequence_header(seq: &SequenceHeader, config_obus: Vec<u8>) -> Self {
+        let op = seq.operating_point();
+        let c = &seq.color;
+        Self {
+            profile: seq.profile,
+            level_idx: op.level_idx,
+            tier: op.tier,
+            high_bitdepth: c.high_bitdepth(),
+            twelve_bit: c.twelve_bit(),
+            mono_chrome: c.mono_chrome,
+            subsampling_x: c.subsampling_x,
+            subsampling_y: c.subsampling_y,
+            chroma_sample_position: c.chroma_sample_position,
+            initial_presentation_delay: op.initial_display_delay,
+            config_obus,
+        }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (h, rest) = data.split_at_checked(4).ok_or(Error::Truncated)?;
+        if h[0] != 0x81 {
+            return Err(Error::InvalidValue("marker or version"));
+        }
+        Ok(Self {
+            profile: h[1] >> 5,
+            level_idx: h[1] & 0x1f,
+            tier: h[2] & 0x80 != 0,
+            high_bitdepth: h[2] & 0x40 != 0,
+            twelve_bit: h[2] & 0x20 != 0,
+            mono_chrome: h[2] & 0x10 != 0,
+            subsampling_x: h[2] & 0x08 != 0,
+            subsampling_y: h[2] & 0x04 != 0,
+            chroma_sample_position: h[2] & 3,
+            initial_presentation_delay: (h[3] & 0x10 != 0).then_some((h[3] & 0xf) + 1),
+            config_obus: rest.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(4 + self.config_obus.len());
+        out.push(0x81);
+        out.push((self.profile << 5) | self.level_idx);
+        out.push(
+            ((self.tier as u8) << 7)
+                | ((self.high_bitdepth as u8) << 6)
+                | ((self.twelve_bit as u8) << 5)
+                | ((self.mono_chrome as u8) << 4)
+                | ((self.subsampling_x as u8) << 3)
+                | ((self.subsampling_y as u8) << 2)
+                | self.chroma_sample_position,
+        );
+        out.push(match self.initial_presentation_delay {
+            Some(delay) => 0x10 | (delay - 1),
+            None => 0,
+        });
+        out.extend_from_slice(&self.config_obus);
+        out
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u8 {
+        match (self.high_bitdepth, self.twelve_bit) {
+            (false, _) => 8,
+            (true, false) => 10,
+            (true, true) => 12,
+        }
+    }
+
+    /// Sequence header from `configOBUs`
+    pub fn parse_sequence_header(&self) -> Result<SequenceHeader, Error> {
+        for obu in obus(&self.config_obus) {
+            let obu = obu?;
+            if obu.obu_type == ObuType::SEQUENCE_HEADER {
+                return SequenceHeader::parse_payload(obu.payload);
+            }
+        }
+        Err(Error::InvalidValue("missing sequence header"))
+    }
+
+    /// Format description extensions with `av1C` atom and color tags
+    #[cfg(all(feature = "cm", feature = "cv"))]
+    pub fn to_format_desc_exts(&self) -> Result<arc::R<ext::Exts>, Error> {
+        let seq = self.parse_sequence_header()?;
+        let color = Some(seq.color.color);
+        ext::video_exts(
+            cf::str!(c"av1C"),
+            &self.to_bytes(),
+            color,
+            seq.color.full_range,
+        )
+        .ok_or(Error::InvalidValue("av1C"))
+    }
+
+    #[cfg(all(feature = "cm", feature = "cv"))]
+    pub fn to_format_desc(&self) -> os::Result<arc::R<cm::VideoFormatDesc>> {
+        let (Ok(seq), Ok(exts)) = (self.parse_sequence_header(), self.to_format_desc_exts()) else {
+            return Err(cm::format_desc_bridge_err::INVALID_PARAMETER);
+        };
+        cm::VideoFormatDesc::video(
+            cm::VideoCodec::AV1,
+            seq.max_frame_width as i32,
+            seq.max_frame_height as i32,
+            Some(&exts),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{bits, vui};
+
+    use super::{obus, push_obu, read_leb128, write_leb128, Av1c, Error, ObuType, SequenceHeader};
+
+    /// Main 10-bit 3840x2160 BT.2100 PQ full range at 24000/1001 with two operating points
+    fn seq_payload() -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(0, 3);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(true); // timing
+        w.write_bits(1001, 32);
+        w.write_bits(24000, 32);
+        w.write_bool(true);
+        w.write_bits(1, 1); // uvlc 0
+        w.write_bool(false); // decoder model
+        w.write_bool(true); // initial display delay
+        w.write_bits(1, 5);
+        for (idc, level) in [(0x103, 13u64), (0x101, 12)] {
+            w.write_bits(idc, 12);
+            w.write_bits(level, 5);
+            w.write_bool(level == 13); // tier
+            w.write_bool(true);
+            w.write_bits(3, 4);
+        }
+        w.write_bits(15, 4);
+        w.write_bits(15, 4);
+        w.write_bits(3839, 16);
+        w.write_bits(2159, 16);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bits(0b11, 2);
+        w.write_bits(0, 4);
+        w.write_bool(true); // order hint
+        w.write_bits(0b11, 2);
+        w.write_bool(true); // choose screen content tools
+        w.write_bool(true); // choose integer mv
+        w.write_bits(6, 3);
+        w.write_bits(0b011, 3);
+        w.write_bool(true); // high bitdepth
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bits(9, 8);
+        w.write_bits(16, 8);
+        w.write_bits(9, 8);
+        w.write_bool(true); // full range
+        w.write_bits(2, 2);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_trailing_bits();
+        w.into_vec()
+    }
+
+    #[test]
+    fn leb128() {
+        for v in [0, 1, 127, 128, 300, u32::MAX as u64] {
+            let mut out = Vec::new();
+            write_leb128(&mut out, v);
+            assert_eq!(read_leb128(&out), Ok((v, out.len())));
+        }
+        assert_eq!(read_leb128(&[0x80]), Err(Error::Truncated));
+        assert_eq!(read_leb128(&[0x80; 9]), Err(Error::InvalidValue("leb128")));
+    }
+
+    #[test]
+    fn sequence_header() {
+        let seq = SequenceHeader::parse_payload(&seq_payload()).unwrap();
+        assert_eq!((seq.max_frame_width, seq.max_frame_height), (3840, 2160));
+        assert_eq!(seq.operating_points.len(), 2);
+        assert!(seq.operating_point().tier);
+        assert_eq!(seq.operating_point().initial_display_delay, Some(4));
+        assert_eq!(seq.frame_rate(), Some((24000, 1001)));
+        assert_eq!(seq.order_hint_bits, 7);
+        assert_eq!(seq.color.bit_depth, 10);
+        assert_eq!(seq.color.color, vui::ColorDescription::BT2100_PQ);
+        assert_eq!(seq.color.chroma_sample_position, 2);
+        assert_eq!(seq.codec_string(), "av01.0.13H.10.0.112.09.16.09.1");
+
+        let mut tu = Vec::new();
+        push_obu(&mut tu, ObuType::TEMPORAL_DELIMITER, &[]);
+        push_obu(&mut tu, ObuType::SEQUENCE_HEADER, &seq_payload());
+        // key frame, shown
+        tu.extend_from_slice(&[ObuType::FRAME.0 << 3, 0x10, 0xaa]);
+
+        let sample = super::to_sample(&tu).unwrap();
+        let types: Vec<_> = obus(&sample).map(|o| o.unwrap().obu_type).collect();
+        assert_eq!(types, [ObuType::SEQUENCE_HEADER, ObuType::FRAME]);
+        assert_eq!(&sample[sample.len() - 4..], [0x32, 0x02, 0x10, 0xaa]);
+        assert!(super::is_sync_sample(&sample, &seq).unwrap());
+        assert!(!super::is_sync_sample(&[0x32, 0x01, 0x30], &seq).unwrap());
+
+        let av1c = Av1c::with_obus(&tu).unwrap();
+        assert_eq!(av1c.bit_depth(), 10);
+        assert_eq!(av1c.initial_presentation_delay, Some(4));
+        let bytes = av1c.to_bytes();
+        assert_eq!(bytes[..4], [0x81, 0x0d, 0xce, 0x13]);
+        assert_eq!(Av1c::parse(&bytes).unwrap(), av1c);
+        assert_eq!(av1c.parse_sequence_header().unwrap(), seq);
+
+        assert!(Av1c::with_obus(&[0x12, 0x00]).is_err());
+        assert_eq!(
+            SequenceHeader::parse(&[0x12, 0x00]),
+            Err(Error::UnexpectedObuType(ObuType::TEMPORAL_DELIMITER))
+        );
+    }
+}
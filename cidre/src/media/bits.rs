@@ -0,0 +1,273 @@
+//! MSB-first bit reader and writer for RBSP payloads with Exp-Golomb codes.
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Not enough bits left
+    Eof,
+
+    /// Exp-Golomb code doesn't fit into 32 bits
+    InvalidExpGolomb,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eof => f.write_str("unexpected end of bitstream"),
+            Self::InvalidExpGolomb => f.write_str("invalid exp-golomb code"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Position in bits
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    #[inline]
+    pub fn is_byte_aligned(&self) -> bool {
+        self.pos % 8 == 0
+    }
+
+    #[inline]
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        let byte = *self.data.get(self.pos / 8).ok_or(Error::Eof)?;
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        Ok(bit == 1)
+    }
+
+    /// Reads `n` bits, `n` must be 64 or less
+    pub fn read_bits(&mut self, n: u32) -> Result<u64, Error> {
+        debug_assert!(n <= 64);
+        if (n as usize) > self.bits_left() {
+            return Err(Error::Eof);
+        }
+        let mut res = 0u64;
+        let mut n = n as usize;
+        while n > 0 {
+            let byte = self.data[self.pos / 8];
+            let offset = self.pos % 8;
+            let take = n.min(8 - offset);
+            let bits = (byte >> (8 - offset - take)) & (0xff >> (8 - take));
+            res = (res << take) | bits as u64;
+            self.pos += take;
+            n -= take;
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn read_u8(&mut self, n: u32) -> Result<u8, Error> {
+        debug_assert!(n <= 8);
+        Ok(self.read_bits(n)? as u8)
+    }
+
+    #[inline]
+    pub fn read_u16(&mut self, n: u32) -> Result<u16, Error> {
+        debug_assert!(n <= 16);
+        Ok(self.read_bits(n)? as u16)
+    }
+
+    #[inline]
+    pub fn read_u32(&mut self, n: u32) -> Result<u32, Error> {
+        debug_assert!(n <= 32);
+        Ok(self.read_bits(n)? as u32)
+    }
+
+    /// Unsigned Exp-Golomb code, `ue(v)`
+    pub fn read_ue(&mut self) -> Result<u32, Error> {
+        let mut zeros = 0;
+        while !self.read_bool()? {
+            zeros += 1;
+            if zeros > 31 {
+                return Err(Error::InvalidExpGolomb);
+            }
+        }
+        let rest = self.read_bits(zeros)?;
+        Ok(((1u64 << zeros) - 1 + rest) as u32)
+    }
+
+    /// Signed Exp-Golomb code, `se(v)`
+    pub fn read_se(&mut self) -> Result<i32, Error> {
+        let k = self.read_ue()? as i64;
+        let v = if k % 2 == 1 { (k + 1) / 2 } else { -(k / 2) };
+        Ok(v as i32)
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result<(), Error> {
+        if n > self.bits_left() {
+            return Err(Error::Eof);
+        }
+        self.pos += n;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn byte_align(&mut self) {
+        self.pos = self.pos.next_multiple_of(8).min(self.data.len() * 8);
+    }
+
+    /// Bytes from the next byte boundary
+    #[inline]
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        let start = self.pos.div_ceil(8).min(self.data.len());
+        &self.data[start..]
+    }
+
+    /// `more_rbsp_data()`: there are bits before `rbsp_trailing_bits`
+    pub fn more_rbsp_data(&self) -> bool {
+        let Some(last) = self.data.iter().rposition(|&b| b != 0) else {
+            return false;
+        };
+        let stop_bit = last * 8 + 7 - self.data[last].trailing_zeros() as usize;
+        self.pos < stop_bit
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Writer {
+    data: Vec<u8>,
+    bit_len: usize,
+}
+
+impl Writer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Length in bits
+    #[inline]
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+
+    #[inline]
+    pub fn is_byte_aligned(&self) -> bool {
+        self.bit_len % 8 == 0
+    }
+
+    #[inline]
+    pub fn write_bool(&mut self, value: bool) {
+        if self.is_byte_aligned() {
+            self.data.push(0);
+        }
+        if value {
+            *self.data.last_mut().unwrap() |= 0x80 >> (self.bit_len % 8);
+        }
+        self.bit_len += 1;
+    }
+
+    /// Writes low `n` bits of `value`, `n` must be 64 or less
+    pub fn write_bits(&mut self, value: u64, n: u32) {
+        debug_assert!(n <= 64);
+        for i in (0..n).rev() {
+            self.write_bool((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn write_ue(&mut self, value: u32) {
+        let v = value as u64 + 1;
+        let len = 64 - v.leading_zeros();
+        self.write_bits(0, len - 1);
+        self.write_bits(v, len);
+    }
+
+    pub fn write_se(&mut self, value: i32) {
+        let v = value as i64;
+        let k = if v > 0 { 2 * v - 1 } else { -2 * v };
+        self.write_ue(k as u32);
+    }
+
+    /// Writes bytes, writer must be byte aligned
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        assert!(self.is_byte_aligned());
+        self.data.extend_from_slice(bytes);
+        self.bit_len += bytes.len() * 8;
+    }
+
+    /// Pads with zero bits up to the byte boundary
+    #[inline]
+    pub fn byte_align(&mut self) {
+        self.bit_len = self.bit_len.next_multiple_of(8);
+    }
+
+    /// `rbsp_trailing_bits()`: stop bit and zero bits up to the byte boundary
+    #[inline]
+    pub fn write_trailing_bits(&mut self) {
+        self.write_bool(true);
+        self.byte_align();
+    }
+
+    #[inline]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Reader, Writer};
+
+    #[test]
+    fn exp_golomb() {
+        let mut w = Writer::new();
+        for v in [0, 1, 2, 3, 7, 255, u32::MAX - 1] {
+            w.write_ue(v);
+        }
+        for v in [0, 1, -1, 2, -2, i32::MAX, -i32::MAX] {
+            w.write_se(v);
+        }
+        w.write_bits(0b101, 3);
+        w.write_trailing_bits();
+        let data = w.into_vec();
+        assert_eq!(data[0], 0b1010_0110);
+
+        let mut r = Reader::new(&data);
+        for v in [0, 1, 2, 3, 7, 255, u32::MAX - 1] {
+            assert_eq!(r.read_ue().unwrap(), v);
+        }
+        for v in [0, 1, -1, 2, -2, i32::MAX, -i32::MAX] {
+            assert_eq!(r.read_se().unwrap(), v);
+        }
+        assert!(r.more_rbsp_data());
+        assert_eq!(r.read_u8(3).unwrap(), 0b101);
+        assert!(!r.more_rbsp_data());
+
+        let mut r = Reader::new(&[0, 0, 0, 0, 0x80]);
+        assert_eq!(r.read_ue(), Err(Error::InvalidExpGolomb));
+    }
+
+    #[test]
+    fn bits() {
+        let mut r = Reader::new(&[0xab, 0xcd, 0xef]);
+        assert_eq!(r.read_bits(4).unwrap(), 0xa);
+        assert_eq!(r.read_bits(12).unwrap(), 0xbcd);
+        assert!(r.is_byte_aligned());
+        r.skip(1).unwrap();
+        r.byte_align();
+        assert_eq!(r.bits_left(), 0);
+        assert_eq!(r.read_bool(), Err(Error::Eof));
+        assert!(r.remaining_bytes().is_empty());
+    }
+}
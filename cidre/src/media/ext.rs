@@ -0,0 +1,37 @@
+//! `cm::FormatDescExtKey` dictionaries for codec configuration records.
+
+use crate::{arc, cf, cm, media::vui};
+
+pub(crate) type Exts = cf::DictionaryOf<cm::FormatDescExtKey, cf::Type>;
+
+/// Extensions with configuration `record` in sample description extension atoms
+/// and color tags
+pub(crate) fn video_exts(
+    atom: &cf::String,
+    record: &[u8],
+    color: Option<vui::ColorDescription>,
+    full_range: bool,
+) -> Option<arc::R<Exts>> {
+    let data = cf::Data::from_slice(record)?;
+    let atoms = cf::DictionaryOf::with_keys_values(&[atom], &[&*data]);
+    let mut exts = cf::DictionaryMut::with_capacity(5);
+    exts.insert(cm::FormatDescExtKey::sample_desc_ext_atoms(), &atoms);
+    if let Some(color) = color {
+        if let Some(val) = color.cv_primaries() {
+            exts.insert(cm::FormatDescExtKey::color_primaries(), val);
+        }
+        if let Some(val) = color.cv_transfer_fn() {
+            exts.insert(cm::FormatDescExtKey::transfer_fn(), val);
+        }
+        if let Some(val) = color.cv_ycbcr_matrix() {
+            exts.insert(cm::FormatDescExtKey::ycbcr_matrix(), val);
+        }
+    }
+    if full_range {
+        exts.insert(
+            cm::FormatDescExtKey::full_range_video(),
+            cf::Boolean::value_true(),
+        );
+    }
+    Some(unsafe { std::mem::transmute::<arc::R<cf::DictionaryMut>, arc::R<Exts>>(exts) })
+}
@@ -0,0 +1,771 @@
+//! FLV tags of AVC, AAC and Enhanced RTMP HEVC, AV1 and VP9 video.
+//!
+//! Tag bodies built here are also the payloads of RTMP audio, video and data messages.
+//! AVC uses legacy `CodecID` 7, other codecs Enhanced RTMP `ExVideoTagHeader` with FourCC.
+//!
+//! ```
+//! use cidre::media::{aac, flv};
+//!
+//! let mut out = Vec::new();
+//! flv::Header { has_audio: true, has_video: true }.write(&mut out);
+//!
+//! let asc = aac::AudioSpecificConfig::lc(48_000, 2);
+//! let config = flv::aac_sequence_header(&asc);
+//! flv::Tag::new(flv::TagType::AUDIO, 0, &config).write(&mut out);
+//!
+//! let frame = flv::VideoTag::coded_frames(flv::VideoFourCc::HEVC, &[0, 0, 0, 1, 0x02], true, 33);
+//! flv::Tag::new(flv::TagType::VIDEO, 0, &frame.to_bytes()).write(&mut out);
+//!
+//! let (header, rest) = flv::Header::parse(&out).unwrap();
+//! assert!(header.has_video);
+//! let tags: Vec<_> = flv::tags(rest).map(|t| t.unwrap()).collect();
+//! assert!(flv::AudioTag::parse(tags[0].data).unwrap().is_config);
+//! let video = flv::VideoTag::parse(tags[1].data).unwrap();
+//! assert_eq!((video.codec, video.composition_time), (flv::VideoFourCc::HEVC, 33));
+//! ```
+
+use crate::media::{aac, amf, av1, h264, hevc};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+pub const HEADER_LEN: usize = 9;
+pub const TAG_HEADER_LEN: usize = 11;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// File doesn't start with `FLV` signature
+    InvalidSignature,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    Amf(amf::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated FLV data"),
+            Self::InvalidSignature => f.write_str("invalid FLV signature"),
+            Self::InvalidValue(name) => write!(f, "invalid FLV {name}"),
+            Self::Amf(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<amf::Error> for Error {
+    #[inline]
+    fn from(value: amf::Error) -> Self {
+        Self::Amf(value)
+    }
+}
+
+/// FLV file header
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    pub has_audio: bool,
+    pub has_video: bool,
+}
+
+impl Header {
+    /// Header and data after `PreviousTagSize0`
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), Error> {
+        if data.len() < 3 {
+            return Err(Error::Truncated);
+        }
+        if &data[..3] != b"FLV" {
+            return Err(Error::InvalidSignature);
+        }
+        if data.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if data[3] != 1 {
+            return Err(Error::InvalidValue("version"));
+        }
+        let offset = u32::from_be_bytes([data[5], data[6], data[7], data[8]]) as usize;
+        let rest = data
+            .get(offset.max(HEADER_LEN) + 4..)
+            .ok_or(Error::Truncated)?;
+        let header = Self {
+            has_audio: data[4] & 4 != 0,
+            has_video: data[4] & 1 != 0,
+        };
+        Ok((header, rest))
+    }
+
+    /// Appends header with `PreviousTagSize0`
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let flags = (self.has_audio as u8) << 2 | self.has_video as u8;
+        out.extend_from_slice(b"FLV\x01");
+        out.push(flags);
+        out.extend_from_slice(&(HEADER_LEN as u32).to_be_bytes());
+        out.extend_from_slice(&[0; 4]);
+    }
+}
+
+/// `TagType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct TagType(pub u8);
+
+impl TagType {
+    pub const AUDIO: Self = Self(8);
+    pub const VIDEO: Self = Self(9);
+    pub const SCRIPT_DATA: Self = Self(18);
+}
+
+/// Tag with body of [`AudioTag`], [`VideoTag`] or AMF0 script data
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tag<'a> {
+    pub tag_type: TagType,
+
+    /// Decoding time in milliseconds
+    pub timestamp: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> Tag<'a> {
+    #[inline]
+    pub const fn new(tag_type: TagType, timestamp: u32, data: &'a [u8]) -> Self {
+        Self {
+            tag_type,
+            timestamp,
+            data,
+        }
+    }
+
+    /// Tag and data after its `PreviousTagSize`
+    pub fn parse(data: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        let header = data.get(..TAG_HEADER_LEN).ok_or(Error::Truncated)?;
+        if header[0] & 0x20 != 0 {
+            return Err(Error::InvalidValue("encrypted tag"));
+        }
+        let size = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        let timestamp = u32::from_be_bytes([header[7], header[4], header[5], header[6]]);
+        let end = TAG_HEADER_LEN + size;
+        let body = data.get(TAG_HEADER_LEN..end).ok_or(Error::Truncated)?;
+        let rest = data.get(end + 4..).ok_or(Error::Truncated)?;
+        let tag = Self {
+            tag_type: TagType(header[0] & 0x1f),
+            timestamp,
+            data: body,
+        };
+        Ok((tag, rest))
+    }
+
+    /// Appends tag with its `PreviousTagSize`
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let size = (self.data.len() as u32).to_be_bytes();
+        let ts = self.timestamp.to_be_bytes();
+        out.push(self.tag_type.0);
+        out.extend_from_slice(&size[1..]);
+        out.extend_from_slice(&[ts[1], ts[2], ts[3], ts[0], 0, 0, 0]);
+        out.extend_from_slice(self.data);
+        out.extend_from_slice(&((TAG_HEADER_LEN + self.data.len()) as u32).to_be_bytes());
+    }
+}
+
+/// Iterator over tags, see [`tags`]
+#[derive(Debug, Clone)]
+pub struct Tags<'a> {
+    rest: &'a [u8],
+}
+
+/// Tags of data after [`Header`]
+#[inline]
+pub fn tags(data: &[u8]) -> Tags<'_> {
+    Tags { rest: data }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = Result<Tag<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match Tag::parse(self.rest) {
+            Ok((tag, rest)) => {
+                self.rest = rest;
+                Some(Ok(tag))
+            }
+            Err(err) => {
+                self.rest = &[];
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// `SoundFormat` of audio tag
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct SoundFormat(pub u8);
+
+impl SoundFormat {
+    pub const LINEAR_PCM: Self = Self(0);
+    pub const ADPCM: Self = Self(1);
+    pub const MP3: Self = Self(2);
+    pub const LINEAR_PCM_LE: Self = Self(3);
+    pub const G711_A_LAW: Self = Self(7);
+    pub const G711_MU_LAW: Self = Self(8);
+    pub const AAC: Self = Self(10);
+    pub const SPEEX: Self = Self(11);
+}
+
+/// Audio tag body
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AudioTag<'a> {
+    pub format: SoundFormat,
+
+    /// `SoundRate`, `SoundSize` and `SoundType` bits, always `0xf` for AAC
+    pub flags: u8,
+
+    /// AAC sequence header with `AudioSpecificConfig`
+    pub is_config: bool,
+    pub data: &'a [u8],
+}
+
+impl<'a> AudioTag<'a> {
+    /// Raw AAC frame
+    #[inline]
+    pub const fn aac(data: &'a [u8]) -> Self {
+        Self {
+            format: SoundFormat::AAC,
+            flags: 0xf,
+            is_config: false,
+            data,
+        }
+    }
+
+    /// AAC sequence header of `AudioSpecificConfig` bytes
+    #[inline]
+    pub const fn aac_config(asc: &'a [u8]) -> Self {
+        Self {
+            format: SoundFormat::AAC,
+            flags: 0xf,
+            is_config: true,
+            data: asc,
+        }
+    }
+
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (&header, mut rest) = data.split_first().ok_or(Error::Truncated)?;
+        let format = SoundFormat(header >> 4);
+        let mut is_config = false;
+        if format == SoundFormat::AAC {
+            let (&packet_type, tail) = rest.split_first().ok_or(Error::Truncated)?;
+            is_config = packet_type == 0;
+            rest = tail;
+        }
+        Ok(Self {
+            format,
+            flags: header & 0xf,
+            is_config,
+            data: rest,
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.format.0 << 4 | self.flags & 0xf);
+        if self.format == SoundFormat::AAC {
+            out.push(!self.is_config as u8);
+        }
+        out.extend_from_slice(self.data);
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(2 + self.data.len());
+        self.write(&mut out);
+        out
+    }
+}
+
+/// Enhanced RTMP `VideoFourCc`
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct VideoFourCc(pub [u8; 4]);
+
+impl VideoFourCc {
+    /// Written with legacy `CodecID` 7
+    pub const AVC: Self = Self(*b"avc1");
+    pub const HEVC: Self = Self(*b"hvc1");
+    pub const AV1: Self = Self(*b"av01");
+    pub const VP9: Self = Self(*b"vp09");
+
+    /// FourCC of codec, HEVC variants map to `hvc1`
+    #[cfg(feature = "cm")]
+    pub fn with_video_codec(codec: cm::VideoCodec) -> Option<Self> {
+        match codec {
+            cm::VideoCodec::H264 => Some(Self::AVC),
+            cm::VideoCodec::HEVC
+            | cm::VideoCodec::HEVC_WITH_ALPHA
+            | cm::VideoCodec::DOLBY_VISION_HEVC => Some(Self::HEVC),
+            cm::VideoCodec::AV1 => Some(Self::AV1),
+            cm::VideoCodec::VP9 => Some(Self::VP9),
+            _ => None,
+        }
+    }
+
+    /// `videocodecid` of `onMetaData`, FourCC as number for enhanced codecs
+    #[inline]
+    pub fn metadata_codec_id(&self) -> f64 {
+        if *self == Self::AVC {
+            7.0
+        } else {
+            u32::from_be_bytes(self.0) as f64
+        }
+    }
+
+    /// Codecs with composition time in coded frames
+    #[inline]
+    fn has_composition_time(&self) -> bool {
+        *self == Self::AVC || *self == Self::HEVC
+    }
+}
+
+impl std::fmt::Debug for VideoFourCc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}'", crate::four_cc_to_string(self.0))
+    }
+}
+
+/// `FrameType` of video tag
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct FrameType(pub u8);
+
+impl FrameType {
+    pub const KEY: Self = Self(1);
+    pub const INTER: Self = Self(2);
+    pub const DISPOSABLE_INTER: Self = Self(3);
+    pub const GENERATED_KEY: Self = Self(4);
+    pub const COMMAND: Self = Self(5);
+
+    #[inline]
+    pub fn is_key(&self) -> bool {
+        *self == Self::KEY || *self == Self::GENERATED_KEY
+    }
+}
+
+/// `AVCPacketType` or Enhanced RTMP `VideoPacketType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct VideoPacketType(pub u8);
+
+impl VideoPacketType {
+    /// Decoder configuration record
+    pub const SEQUENCE_START: Self = Self(0);
+    pub const CODED_FRAMES: Self = Self(1);
+    pub const SEQUENCE_END: Self = Self(2);
+
+    /// Coded frames without composition time
+    pub const CODED_FRAMES_X: Self = Self(3);
+    pub const METADATA: Self = Self(4);
+}
+
+/// Video tag body.
+///
+/// Parsed `CODED_FRAMES_X` packets are reported as `CODED_FRAMES` with zero composition time
+/// and chosen again when written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VideoTag<'a> {
+    pub codec: VideoFourCc,
+    pub frame_type: FrameType,
+    pub packet_type: VideoPacketType,
+
+    /// PTS minus DTS in milliseconds, only carried for AVC and HEVC
+    pub composition_time: i32,
+
+    /// Configuration record or length prefixed sample
+    pub data: &'a [u8],
+}
+
+impl<'a> VideoTag<'a> {
+    /// Sequence start of `avcC`, `hvcC`, `av1C` or `vpcC` bytes
+    #[inline]
+    pub const fn sequence_start(codec: VideoFourCc, config: &'a [u8]) -> Self {
+        Self {
+            codec,
+            frame_type: FrameType::KEY,
+            packet_type: VideoPacketType::SEQUENCE_START,
+            composition_time: 0,
+            data: config,
+        }
+    }
+
+    /// Frames of length prefixed sample as in MP4, with 4-byte lengths for AVC and HEVC
+    #[inline]
+    pub const fn coded_frames(
+        codec: VideoFourCc,
+        sample: &'a [u8],
+        is_key: bool,
+        composition_time: i32,
+    ) -> Self {
+        Self {
+            codec,
+            frame_type: if is_key {
+                FrameType::KEY
+            } else {
+                FrameType::INTER
+            },
+            packet_type: VideoPacketType::CODED_FRAMES,
+            composition_time,
+            data: sample,
+        }
+    }
+
+    #[inline]
+    pub const fn sequence_end(codec: VideoFourCc) -> Self {
+        Self {
+            codec,
+            frame_type: FrameType::KEY,
+            packet_type: VideoPacketType::SEQUENCE_END,
+            composition_time: 0,
+            data: &[],
+        }
+    }
+
+    #[inline]
+    pub fn is_key(&self) -> bool {
+        self.frame_type.is_key()
+    }
+
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (&header, rest) = data.split_first().ok_or(Error::Truncated)?;
+        if header & 0x80 == 0 {
+            let codec = match header & 0xf {
+                7 => VideoFourCc::AVC,
+                // non-standard HEVC extension of legacy tags
+                12 => VideoFourCc::HEVC,
+                _ => return Err(Error::InvalidValue("video CodecID")),
+            };
+            let (head, data) = rest.split_at_checked(4).ok_or(Error::Truncated)?;
+            return Ok(Self {
+                codec,
+                frame_type: FrameType(header >> 4),
+                packet_type: VideoPacketType(head[0]),
+                composition_time: read_si24(&head[1..]),
+                data,
+            });
+        }
+        let (fourcc, mut data) = rest.split_at_checked(4).ok_or(Error::Truncated)?;
+        let codec = VideoFourCc(fourcc.try_into().unwrap());
+        let mut packet_type = VideoPacketType(header & 0xf);
+        let mut composition_time = 0;
+        match packet_type {
+            VideoPacketType::CODED_FRAMES if codec.has_composition_time() => {
+                let (cts, tail) = data.split_at_checked(3).ok_or(Error::Truncated)?;
+                composition_time = read_si24(cts);
+                data = tail;
+            }
+            VideoPacketType::CODED_FRAMES_X => packet_type = VideoPacketType::CODED_FRAMES,
+            VideoPacketType(0..=4) => {}
+            _ => return Err(Error::InvalidValue("VideoPacketType")),
+        }
+        Ok(Self {
+            codec,
+            frame_type: FrameType(header >> 4 & 7),
+            packet_type,
+            composition_time,
+            data,
+        })
+    }
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let cts = self.composition_time.to_be_bytes();
+        if self.codec == VideoFourCc::AVC {
+            out.push(self.frame_type.0 << 4 | 7);
+            out.extend_from_slice(&[self.packet_type.0, cts[1], cts[2], cts[3]]);
+        } else {
+            let coded = self.packet_type == VideoPacketType::CODED_FRAMES;
+            let with_cts = coded && self.codec.has_composition_time() && self.composition_time != 0;
+            let packet_type = if coded && self.codec.has_composition_time() && !with_cts {
+                VideoPacketType::CODED_FRAMES_X
+            } else {
+                self.packet_type
+            };
+            out.push(0x80 | (self.frame_type.0 & 7) << 4 | packet_type.0);
+            out.extend_from_slice(&self.codec.0);
+            if with_cts {
+                out.extend_from_slice(&cts[1..]);
+            }
+        }
+        out.extend_from_slice(self.data);
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.data.len());
+        self.write(&mut out);
+        out
+    }
+}
+
+#[inline]
+fn read_si24(data: &[u8]) -> i32 {
+    i32::from_be_bytes([data[0], data[1], data[2], 0]) >> 8
+}
+
+/// AVC sequence header tag body
+#[inline]
+pub fn avc_sequence_header(avcc: &h264::Avcc) -> Vec<u8> {
+    VideoTag::sequence_start(VideoFourCc::AVC, &avcc.to_bytes()).to_bytes()
+}
+
+/// Enhanced RTMP HEVC sequence start tag body
+#[inline]
+pub fn hevc_sequence_header(hvcc: &hevc::Hvcc) -> Vec<u8> {
+    VideoTag::sequence_start(VideoFourCc::HEVC, &hvcc.to_bytes()).to_bytes()
+}
+
+/// Enhanced RTMP AV1 sequence start tag body
+#[inline]
+pub fn av1_sequence_header(av1c: &av1::Av1c) -> Vec<u8> {
+    VideoTag::sequence_start(VideoFourCc::AV1, &av1c.to_bytes()).to_bytes()
+}
+
+/// AAC sequence header tag body
+#[inline]
+pub fn aac_sequence_header(asc: &aac::AudioSpecificConfig) -> Vec<u8> {
+    AudioTag::aac_config(&asc.to_bytes()).to_bytes()
+}
+
+/// Stream properties of `onMetaData`, absent ones are omitted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// Seconds, zero for live streams
+    pub duration: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub video_codec: Option<VideoFourCc>,
+
+    /// Kilobits per second
+    pub video_data_rate: Option<f64>,
+    pub audio_codec: Option<SoundFormat>,
+    pub audio_sample_rate: Option<u32>,
+    pub audio_channels: Option<u32>,
+
+    /// Kilobits per second
+    pub audio_data_rate: Option<f64>,
+    pub encoder: Option<String>,
+}
+
+impl Metadata {
+    /// ECMA array of properties
+    pub fn to_value(&self) -> amf::Value {
+        let mut properties: Vec<(&str, amf::Value)> = Vec::new();
+        let mut push = |key, value: Option<amf::Value>| {
+            if let Some(value) = value {
+                properties.push((key, value));
+            }
+        };
+        push("duration", self.duration.map(Into::into));
+        push("width", self.width.map(Into::into));
+        push("height", self.height.map(Into::into));
+        push("framerate", self.frame_rate.map(Into::into));
+        let video_codec = self.video_codec.map(|c| c.metadata_codec_id().into());
+        push("videocodecid", video_codec);
+        push("videodatarate", self.video_data_rate.map(Into::into));
+        let audio_codec = self.audio_codec.map(|c| (c.0 as u32).into());
+        push("audiocodecid", audio_codec);
+        push("audiosamplerate", self.audio_sample_rate.map(Into::into));
+        push("audiochannels", self.audio_channels.map(Into::into));
+        push("stereo", self.audio_channels.map(|c| (c == 2).into()));
+        push("audiodatarate", self.audio_data_rate.map(Into::into));
+        push("encoder", self.encoder.as_deref().map(Into::into));
+        amf::Value::ecma_array(properties)
+    }
+
+    /// Properties of ECMA array or object, unknown ones are ignored
+    pub fn with_value(value: &amf::Value) -> Result<Self, Error> {
+        let properties = value
+            .properties()
+            .ok_or(Error::InvalidValue("onMetaData"))?;
+        let mut metadata = Self::default();
+        for (key, value) in properties {
+            let number = value.as_f64();
+            match key.as_str() {
+                "duration" => metadata.duration = number,
+                "width" => metadata.width = number.map(|n| n as u32),
+                "height" => metadata.height = number.map(|n| n as u32),
+                "framerate" => metadata.frame_rate = number,
+                "videocodecid" => {
+                    metadata.video_codec = match number.map(|n| n as u32) {
+                        Some(7) => Some(VideoFourCc::AVC),
+                        Some(n) if n > 0xff => Some(VideoFourCc(n.to_be_bytes())),
+                        _ => None,
+                    }
+                }
+                "videodatarate" => metadata.video_data_rate = number,
+                "audiocodecid" => metadata.audio_codec = number.map(|n| SoundFormat(n as u8)),
+                "audiosamplerate" => metadata.audio_sample_rate = number.map(|n| n as u32),
+                "audiochannels" => metadata.audio_channels = number.map(|n| n as u32),
+                "audiodatarate" => metadata.audio_data_rate = number,
+                "encoder" => metadata.encoder = value.as_str().map(str::to_string),
+                _ => {}
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// Script data tag body, `onMetaData` followed by ECMA array
+    pub fn to_script_data(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        amf::amf0::write(&mut out, &"onMetaData".into());
+        amf::amf0::write(&mut out, &self.to_value());
+        out
+    }
+
+    /// Metadata of script data tag body, RTMP `@setDataFrame` prefix is skipped
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let values = amf::amf0::read_all(data)?;
+        let mut values = values.iter();
+        let mut name = values.next();
+        if name.and_then(amf::Value::as_str) == Some("@setDataFrame") {
+            name = values.next();
+        }
+        if name.and_then(amf::Value::as_str) != Some("onMetaData") {
+            return Err(Error::InvalidValue("onMetaData"));
+        }
+        Self::with_value(values.next().ok_or(Error::Truncated)?)
+    }
+}
+
+/// Milliseconds of `cm::Time`, e.g. tag timestamp or composition time of PTS minus DTS
+#[cfg(feature = "cm")]
+#[inline]
+pub fn timestamp(time: cm::Time) -> i64 {
+    time.convert_scale(1000, cm::TimeRoundingMethod::default())
+        .value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        tags, AudioTag, Error, FrameType, Header, Metadata, SoundFormat, Tag, TagType, VideoFourCc,
+        VideoPacketType, VideoTag,
+    };
+    use crate::media::{aac, h264};
+
+    #[test]
+    fn tags_round_trip() {
+        let mut out = Vec::new();
+        Header {
+            has_audio: false,
+            has_video: true,
+        }
+        .write(&mut out);
+        assert_eq!(out, b"FLV\x01\x01\0\0\0\x09\0\0\0\0");
+        Tag::new(TagType::VIDEO, 0x1234_5678, &[1, 2]).write(&mut out);
+        assert_eq!(
+            out[13..],
+            [9, 0, 0, 2, 0x34, 0x56, 0x78, 0x12, 0, 0, 0, 1, 2, 0, 0, 0, 13]
+        );
+        Tag::new(TagType::SCRIPT_DATA, 0, &[]).write(&mut out);
+
+        let (header, rest) = Header::parse(&out).unwrap();
+        assert!(!header.has_audio && header.has_video);
+        let tags: Vec<_> = tags(rest).collect::<Result<_, _>>().unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].timestamp, 0x1234_5678);
+        assert_eq!(tags[1].tag_type, TagType::SCRIPT_DATA);
+
+        assert_eq!(Header::parse(b"FLX"), Err(Error::InvalidSignature));
+        assert_eq!(Tag::parse(&out[13..20]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn avc() {
+        let avcc = h264::Avcc::with_param_sets(
+            &[0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4],
+            &[0x68, 0xce, 0x3c, 0x80],
+            4,
+        )
+        .unwrap();
+        let header = super::avc_sequence_header(&avcc);
+        assert_eq!(header[..5], [0x17, 0, 0, 0, 0]);
+        assert_eq!(header[5..], avcc.to_bytes());
+
+        let sample = [0, 0, 0, 2, 0x41, 0x9a];
+        let tag = VideoTag::coded_frames(VideoFourCc::AVC, &sample, false, -40);
+        let bytes = tag.to_bytes();
+        assert_eq!(bytes[..5], [0x27, 1, 0xff, 0xff, 0xd8]);
+        assert_eq!(VideoTag::parse(&bytes).unwrap(), tag);
+
+        let end = VideoTag::sequence_end(VideoFourCc::AVC).to_bytes();
+        assert_eq!(end, [0x17, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn enhanced() {
+        let sample = [0, 0, 0, 1, 0x02];
+        let tag = VideoTag::coded_frames(VideoFourCc::HEVC, &sample, true, 0);
+        let bytes = tag.to_bytes();
+        // CodedFramesX without composition time
+        assert_eq!(bytes[..5], [0x93, b'h', b'v', b'c', b'1']);
+        assert_eq!(bytes[5..], sample);
+        assert_eq!(VideoTag::parse(&bytes).unwrap(), tag);
+
+        let tag = VideoTag::coded_frames(VideoFourCc::HEVC, &sample, false, 80);
+        let bytes = tag.to_bytes();
+        assert_eq!(bytes[..8], [0xa1, b'h', b'v', b'c', b'1', 0, 0, 80]);
+        assert_eq!(VideoTag::parse(&bytes).unwrap(), tag);
+
+        let obus = [0x32, 0x00];
+        let tag = VideoTag::coded_frames(VideoFourCc::AV1, &obus, true, 0);
+        let bytes = tag.to_bytes();
+        assert_eq!(bytes, [0x91, b'a', b'v', b'0', b'1', 0x32, 0x00]);
+        let parsed = VideoTag::parse(&bytes).unwrap();
+        assert_eq!(parsed.packet_type, VideoPacketType::CODED_FRAMES);
+        assert_eq!(parsed.frame_type, FrameType::KEY);
+
+        let start = VideoTag::sequence_start(VideoFourCc::AV1, &[0x81]).to_bytes();
+        assert_eq!(start, [0x90, b'a', b'v', b'0', b'1', 0x81]);
+        assert_eq!(
+            VideoTag::parse(&[0x96, b'a', b'v', b'0', b'1']),
+            Err(Error::InvalidValue("VideoPacketType"))
+        );
+    }
+
+    #[test]
+    fn audio_and_metadata() {
+        let asc = aac::AudioSpecificConfig::lc(44_100, 2);
+        let header = super::aac_sequence_header(&asc);
+        assert_eq!(header, [0xaf, 0, 0x12, 0x10]);
+        let frame = AudioTag::aac(&[1, 2]).to_bytes();
+        assert_eq!(frame, [0xaf, 1, 1, 2]);
+        let parsed = AudioTag::parse(&frame).unwrap();
+        assert_eq!(parsed.format, SoundFormat::AAC);
+        assert!(!parsed.is_config);
+
+        let metadata = Metadata {
+            duration: Some(0.0),
+            width: Some(1280),
+            height: Some(720),
+            frame_rate: Some(30.0),
+            video_codec: Some(VideoFourCc::HEVC),
+            audio_codec: Some(SoundFormat::AAC),
+            audio_sample_rate: Some(44_100),
+            audio_channels: Some(2),
+            encoder: Some("cidre".into()),
+            ..Default::default()
+        };
+        let data = metadata.to_script_data();
+        assert_eq!(Metadata::parse(&data).unwrap(), metadata);
+        let value = metadata.to_value();
+        assert_eq!(
+            value.get("videocodecid").and_then(|v| v.as_f64()),
+            Some(u32::from_be_bytes(*b"hvc1") as f64)
+        );
+        assert_eq!(value.get("stereo").and_then(|v| v.as_bool()), Some(true));
+    }
+}
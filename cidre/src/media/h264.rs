@@ -0,0 +1,1293 @@
+//! H.264 (ITU-T H.264 / ISO/IEC 14496-10) NAL units, parameter sets and `avcC` records.
+
+use crate::media::{bits, nal, vui};
+
+#[cfg(feature = "cm")]
+use crate::{arc, cm, os};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    UnexpectedNalType(NalUnitType),
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    Nal(nal::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated H.264 data"),
+            Self::UnexpectedNalType(t) => write!(f, "unexpected H.264 NAL unit type {}", t.0),
+            Self::InvalidValue(name) => write!(f, "invalid H.264 {name}"),
+            Self::Nal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Eof => Self::Truncated,
+            bits::Error::InvalidExpGolomb => Self::InvalidValue("exp-golomb code"),
+        }
+    }
+}
+
+impl From<nal::Error> for Error {
+    #[inline]
+    fn from(value: nal::Error) -> Self {
+        Self::Nal(value)
+    }
+}
+
+/// `nal_unit_type`
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct NalUnitType(pub u8);
+
+impl NalUnitType {
+    pub const UNSPECIFIED: Self = Self(0);
+    pub const SLICE: Self = Self(1);
+    pub const SLICE_DATA_A: Self = Self(2);
+    pub const SLICE_DATA_B: Self = Self(3);
+    pub const SLICE_DATA_C: Self = Self(4);
+    pub const IDR_SLICE: Self = Self(5);
+    pub const SEI: Self = Self(6);
+    pub const SPS: Self = Self(7);
+    pub const PPS: Self = Self(8);
+    pub const AUD: Self = Self(9);
+    pub const END_OF_SEQ: Self = Self(10);
+    pub const END_OF_STREAM: Self = Self(11);
+    pub const FILLER: Self = Self(12);
+    pub const SPS_EXT: Self = Self(13);
+    pub const PREFIX: Self = Self(14);
+    pub const SUBSET_SPS: Self = Self(15);
+    pub const DPS: Self = Self(16);
+    pub const AUX_SLICE: Self = Self(19);
+    pub const SLICE_EXT: Self = Self(20);
+    pub const SLICE_EXT_DEPTH: Self = Self(21);
+
+    /// Coded slice (video coding layer) NAL unit
+    #[inline]
+    pub fn is_vcl(&self) -> bool {
+        (1..=5).contains(&self.0)
+    }
+
+    #[inline]
+    pub fn is_param_set(&self) -> bool {
+        matches!(
+            *self,
+            Self::SPS | Self::PPS | Self::SPS_EXT | Self::SUBSET_SPS
+        )
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            1 => "SLICE",
+            2 => "SLICE_DATA_A",
+            3 => "SLICE_DATA_B",
+            4 => "SLICE_DATA_C",
+            5 => "IDR_SLICE",
+            6 => "SEI",
+            7 => "SPS",
+            8 => "PPS",
+            9 => "AUD",
+            10 => "END_OF_SEQ",
+            11 => "END_OF_STREAM",
+            12 => "FILLER",
+            13 => "SPS_EXT",
+            14 => "PREFIX",
+            15 => "SUBSET_SPS",
+            16 => "DPS",
+            19 => "AUX_SLICE",
+            20 => "SLICE_EXT",
+            21 => "SLICE_EXT_DEPTH",
+            _ => "UNSPECIFIED",
+        }
+    }
+}
+
+impl std::fmt::Debug for NalUnitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.name(), self.0)
+    }
+}
+
+/// NAL unit with one byte header.
+///
+/// ```
+/// use cidre::media::h264;
+///
+/// let nal = h264::Nal::new(&[0x65, 0x88, 0x84]).unwrap();
+/// assert_eq!(nal.unit_type(), h264::NalUnitType::IDR_SLICE);
+/// assert_eq!(nal.ref_idc(), 3);
+/// assert!(nal.is_keyframe());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Nal<'a>(&'a [u8]);
+
+impl<'a> Nal<'a> {
+    /// `None` if empty or `forbidden_zero_bit` is set
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        match data.first() {
+            Some(b) if b & 0x80 == 0 => Some(Self(data)),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.0
+    }
+
+    #[inline]
+    pub fn ref_idc(&self) -> u8 {
+        (self.0[0] >> 5) & 3
+    }
+
+    #[inline]
+    pub fn unit_type(&self) -> NalUnitType {
+        NalUnitType(self.0[0] & 0x1f)
+    }
+
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.unit_type() == NalUnitType::IDR_SLICE
+    }
+
+    /// Payload without header and emulation prevention bytes
+    #[inline]
+    pub fn rbsp(&self) -> std::borrow::Cow<'a, [u8]> {
+        nal::to_rbsp(&self.0[1..])
+    }
+}
+
+/// Checks if length-prefixed sample contains IDR slice
+pub fn is_sync_sample(sample: &[u8], nal_length_size: usize) -> Result<bool, Error> {
+    for data in nal::length_prefixed(sample, nal_length_size)? {
+        if Nal::new(data?).is_some_and(|n| n.is_keyframe()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// `frame_cropping` offsets in crop units
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Crop {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Cpb {
+    /// Bits per second
+    pub bit_rate: u64,
+
+    /// Bits
+    pub size: u64,
+    pub cbr: bool,
+}
+
+/// `hrd_parameters()`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Hrd {
+    pub cpbs: Vec<Cpb>,
+    pub initial_cpb_removal_delay_length: u8,
+    pub cpb_removal_delay_length: u8,
+    pub dpb_output_delay_length: u8,
+    pub time_offset_length: u8,
+}
+
+impl Hrd {
+    fn read(r: &mut bits::Reader) -> Result<Self, Error> {
+        let cpb_cnt = r.read_ue()? as usize + 1;
+        if cpb_cnt > 32 {
+            return Err(Error::InvalidValue("cpb_cnt_minus1"));
+        }
+        let bit_rate_scale = r.read_u8(4)?;
+        let cpb_size_scale = r.read_u8(4)?;
+        let mut cpbs = Vec::with_capacity(cpb_cnt);
+        for _ in 0..cpb_cnt {
+            let bit_rate = r.read_ue()? as u64 + 1;
+            let size = r.read_ue()? as u64 + 1;
+            cpbs.push(Cpb {
+                bit_rate: bit_rate << (6 + bit_rate_scale),
+                size: size << (4 + cpb_size_scale),
+                cbr: r.read_bool()?,
+            });
+        }
+        Ok(Self {
+            cpbs,
+            initial_cpb_removal_delay_length: r.read_u8(5)? + 1,
+            cpb_removal_delay_length: r.read_u8(5)? + 1,
+            dpb_output_delay_length: r.read_u8(5)? + 1,
+            time_offset_length: r.read_u8(5)?,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Timing {
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+    pub fixed_frame_rate: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BitstreamRestriction {
+    pub motion_vectors_over_pic_boundaries: bool,
+    pub max_bytes_per_pic_denom: u32,
+    pub max_bits_per_mb_denom: u32,
+    pub log2_max_mv_length_horizontal: u32,
+    pub log2_max_mv_length_vertical: u32,
+    pub max_num_reorder_frames: u32,
+    pub max_dec_frame_buffering: u32,
+}
+
+/// `vui_parameters()`
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Vui {
+    /// Sample aspect ratio, `(0, 0)` if unspecified
+    pub sample_aspect_ratio: Option<(u16, u16)>,
+    pub overscan_appropriate: Option<bool>,
+    pub video_signal: Option<vui::VideoSignal>,
+    pub chroma_location: Option<vui::ChromaLocation>,
+    pub timing: Option<Timing>,
+    pub nal_hrd: Option<Hrd>,
+    pub vcl_hrd: Option<Hrd>,
+    pub low_delay_hrd: bool,
+    pub pic_struct_present: bool,
+    pub bitstream_restriction: Option<BitstreamRestriction>,
+}
+
+impl Vui {
+    fn read(r: &mut bits::Reader) -> Result<Self, Error> {
+        let mut vui = Self::default();
+        if r.read_bool()? {
+            vui.sample_aspect_ratio = Some(vui::read_aspect_ratio(r)?);
+        }
+        if r.read_bool()? {
+            vui.overscan_appropriate = Some(r.read_bool()?);
+        }
+        if r.read_bool()? {
+            vui.video_signal = Some(vui::VideoSignal::read(r)?);
+        }
+        if r.read_bool()? {
+            vui.chroma_location = Some(vui::ChromaLocation {
+                top_field: r.read_ue()?,
+                bottom_field: r.read_ue()?,
+            });
+        }
+        if r.read_bool()? {
+            vui.timing = Some(Timing {
+                num_units_in_tick: r.read_u32(32)?,
+                time_scale: r.read_u32(32)?,
+                fixed_frame_rate: r.read_bool()?,
+            });
+        }
+        if r.read_bool()? {
+            vui.nal_hrd = Some(Hrd::read(r)?);
+        }
+        if r.read_bool()? {
+            vui.vcl_hrd = Some(Hrd::read(r)?);
+        }
+        if vui.nal_hrd.is_some() || vui.vcl_hrd.is_some() {
+            vui.low_delay_hrd = r.read_bool()?;
+        }
+        vui.pic_struct_present = r.read_bool()?;
+        if r.read_bool()? {
+            vui.bitstream_restriction = Some(BitstreamRestriction {
+                motion_vectors_over_pic_boundaries: r.read_bool()?,
+                max_bytes_per_pic_denom: r.read_ue()?,
+                max_bits_per_mb_denom: r.read_ue()?,
+                log2_max_mv_length_horizontal: r.read_ue()?,
+                log2_max_mv_length_vertical: r.read_ue()?,
+                max_num_reorder_frames: r.read_ue()?,
+                max_dec_frame_buffering: r.read_ue()?,
+            });
+        }
+        Ok(vui)
+    }
+
+    /// Frames per second from timing info, field rate divided by two
+    #[inline]
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        let t = self.timing?;
+        vui::frame_rate(t.time_scale, t.num_units_in_tick as u64 * 2)
+    }
+}
+
+/// `pic_order_cnt_type` specific fields
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PicOrderCnt {
+    Type0 {
+        log2_max_pic_order_cnt_lsb: u8,
+    },
+    Type1 {
+        delta_pic_order_always_zero: bool,
+        offset_for_non_ref_pic: i32,
+        offset_for_top_to_bottom_field: i32,
+        offsets_for_ref_frame: Vec<i32>,
+    },
+    Type2,
+}
+
+/// Profiles with `chroma_format_idc` and bit depth in SPS and extension in `avcC`
+#[inline]
+fn is_high_profile(profile_idc: u8) -> bool {
+    matches!(
+        profile_idc,
+        100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+    )
+}
+
+fn skip_scaling_list(r: &mut bits::Reader, size: usize) -> Result<(), Error> {
+    let (mut last, mut next) = (8i32, 8i32);
+    for _ in 0..size {
+        if next != 0 {
+            let delta = r.read_se()?;
+            if !(-128..=127).contains(&delta) {
+                return Err(Error::InvalidValue("delta_scale"));
+            }
+            next = (last + delta + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+    Ok(())
+}
+
+/// `QP - 26` of PPS, within bounds of 14 bit samples
+fn read_qp(r: &mut bits::Reader, name: &'static str) -> Result<i32, Error> {
+    let qp_minus26 = r.read_se()?;
+    if !(-62..=25).contains(&qp_minus26) {
+        return Err(Error::InvalidValue(name));
+    }
+    Ok(qp_minus26 + 26)
+}
+
+fn read_nal<'a>(data: &'a [u8], expected: NalUnitType) -> Result<Nal<'a>, Error> {
+    let nal = Nal::new(data).ok_or(Error::Truncated)?;
+    if nal.unit_type() != expected {
+        return Err(Error::UnexpectedNalType(nal.unit_type()));
+    }
+    Ok(nal)
+}
+
+/// Sequence parameter set.
+///
+/// ```
+/// use cidre::media::h264;
+///
+/// let sps = [
+///     0x67, 0x64, 0x00, 0x28, 0xac, 0xd9, 0x40, 0x78, 0x02, 0x27, 0xe5, 0x84, 0x00, 0x00,
+///     0x03, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xf0, 0x3c, 0x60, 0xc6, 0x58,
+/// ];
+/// let sps = h264::Sps::parse(&sps).unwrap();
+/// assert_eq!(sps.profile_idc, h264::Sps::PROFILE_HIGH);
+/// assert_eq!(sps.level_idc, 40);
+/// assert_eq!((sps.width(), sps.height()), (1920, 1080));
+/// assert_eq!(sps.frame_rate(), Some((30, 1)));
+/// assert_eq!(sps.codec_string(), "avc1.640028");
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Sps {
+    pub profile_idc: u8,
+
+    /// `constraint_set0_flag` in MSB through `constraint_set5_flag` and 2 reserved bits
+    pub constraint_flags: u8,
+    pub level_idc: u8,
+    pub id: u32,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub qpprime_y_zero_transform_bypass: bool,
+    pub scaling_matrix_present: bool,
+    pub log2_max_frame_num: u8,
+    pub pic_order_cnt: PicOrderCnt,
+    pub max_num_ref_frames: u32,
+    pub gaps_in_frame_num_allowed: bool,
+    pub pic_width_in_mbs: u32,
+    pub pic_height_in_map_units: u32,
+    pub frame_mbs_only: bool,
+    pub mb_adaptive_frame_field: bool,
+    pub direct_8x8_inference: bool,
+    pub crop: Option<Crop>,
+    pub vui: Option<Vui>,
+}
+
+impl Sps {
+    pub const PROFILE_BASELINE: u8 = 66;
+    pub const PROFILE_MAIN: u8 = 77;
+    pub const PROFILE_EXTENDED: u8 = 88;
+    pub const PROFILE_HIGH: u8 = 100;
+    pub const PROFILE_HIGH_10: u8 = 110;
+    pub const PROFILE_HIGH_422: u8 = 122;
+    pub const PROFILE_HIGH_444: u8 = 244;
+
+    /// Frame width or height in macroblocks at level 6.2, `sqrt(MaxFS * 8)`
+    pub const MAX_SIZE_IN_MBS: u32 = 1055;
+
+    /// Parses SPS NAL unit with header
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let rbsp = read_nal(nal, NalUnitType::SPS)?.rbsp();
+        let r = &mut bits::Reader::new(&rbsp);
+        let profile_idc = r.read_u8(8)?;
+        let constraint_flags = r.read_u8(8)?;
+        let level_idc = r.read_u8(8)?;
+        let id = r.read_ue()?;
+        if id > 31 {
+            return Err(Error::InvalidValue("seq_parameter_set_id"));
+        }
+        let mut sps = Self {
+            profile_idc,
+            constraint_flags,
+            level_idc,
+            id,
+            chroma_format_idc: 1,
+            separate_colour_plane: false,
+            bit_depth_luma: 8,
+            bit_depth_chroma: 8,
+            qpprime_y_zero_transform_bypass: false,
+            scaling_matrix_present: false,
+            log2_max_frame_num: 4,
+            pic_order_cnt: PicOrderCnt::Type2,
+            max_num_ref_frames: 0,
+            gaps_in_frame_num_allowed: false,
+            pic_width_in_mbs: 0,
+            pic_height_in_map_units: 0,
+            frame_mbs_only: true,
+            mb_adaptive_frame_field: false,
+            direct_8x8_inference: false,
+            crop: None,
+            vui: None,
+        };
+        if is_high_profile(profile_idc) {
+            let chroma_format_idc = r.read_ue()?;
+            if chroma_format_idc > 3 {
+                return Err(Error::InvalidValue("chroma_format_idc"));
+            }
+            sps.chroma_format_idc = chroma_format_idc as u8;
+            if chroma_format_idc == 3 {
+                sps.separate_colour_plane = r.read_bool()?;
+            }
+            let luma = r.read_ue()?;
+            let chroma = r.read_ue()?;
+            if luma > 6 || chroma > 6 {
+                return Err(Error::InvalidValue("bit_depth_minus8"));
+            }
+            sps.bit_depth_luma = luma as u8 + 8;
+            sps.bit_depth_chroma = chroma as u8 + 8;
+            sps.qpprime_y_zero_transform_bypass = r.read_bool()?;
+            sps.scaling_matrix_present = r.read_bool()?;
+            if sps.scaling_matrix_present {
+                let count = if chroma_format_idc == 3 { 12 } else { 8 };
+                for i in 0..count {
+                    if r.read_bool()? {
+                        skip_scaling_list(r, if i < 6 { 16 } else { 64 })?;
+                    }
+                }
+            }
+        }
+        let log2_max_frame_num_minus4 = r.read_ue()?;
+        if log2_max_frame_num_minus4 > 12 {
+            return Err(Error::InvalidValue("log2_max_frame_num_minus4"));
+        }
+        sps.log2_max_frame_num = log2_max_frame_num_minus4 as u8 + 4;
+        sps.pic_order_cnt = match r.read_ue()? {
+            0 => {
+                let lsb_minus4 = r.read_ue()?;
+                if lsb_minus4 > 12 {
+                    return Err(Error::InvalidValue("log2_max_pic_order_cnt_lsb_minus4"));
+                }
+                PicOrderCnt::Type0 {
+                    log2_max_pic_order_cnt_lsb: lsb_minus4 as u8 + 4,
+                }
+            }
+            1 => {
+                let delta_pic_order_always_zero = r.read_bool()?;
+                let offset_for_non_ref_pic = r.read_se()?;
+                let offset_for_top_to_bottom_field = r.read_se()?;
+                let count = r.read_ue()?;
+                if count > 255 {
+                    return Err(Error::InvalidValue("num_ref_frames_in_pic_order_cnt_cycle"));
+                }
+                let offsets_for_ref_frame =
+                    (0..count).map(|_| r.read_se()).collect::<Result<_, _>>()?;
+                PicOrderCnt::Type1 {
+                    delta_pic_order_always_zero,
+                    offset_for_non_ref_pic,
+                    offset_for_top_to_bottom_field,
+                    offsets_for_ref_frame,
+                }
+            }
+            2 => PicOrderCnt::Type2,
+            _ => return Err(Error::InvalidValue("pic_order_cnt_type")),
+        };
+        sps.max_num_ref_frames = r.read_ue()?;
+        sps.gaps_in_frame_num_allowed = r.read_bool()?;
+        sps.pic_width_in_mbs = r.read_ue()? + 1;
+        if sps.pic_width_in_mbs > Self::MAX_SIZE_IN_MBS {
+            return Err(Error::InvalidValue("pic_width_in_mbs_minus1"));
+        }
+        sps.pic_height_in_map_units = r.read_ue()? + 1;
+        if sps.pic_height_in_map_units > Self::MAX_SIZE_IN_MBS {
+            return Err(Error::InvalidValue("pic_height_in_map_units_minus1"));
+        }
+        sps.frame_mbs_only = r.read_bool()?;
+        if !sps.frame_mbs_only {
+            sps.mb_adaptive_frame_field = r.read_bool()?;
+        }
+        sps.direct_8x8_inference = r.read_bool()?;
+        if r.read_bool()? {
+            let crop = Crop {
+                left: r.read_ue()?,
+                right: r.read_ue()?,
+                top: r.read_ue()?,
+                bottom: r.read_ue()?,
+            };
+            // crop must leave at least one sample in each direction
+            let (x, y) = sps.crop_units();
+            let (w, h) = (sps.coded_width() / x, sps.coded_height() / y);
+            if crop.left as u64 + crop.right as u64 >= w as u64
+                || crop.top as u64 + crop.bottom as u64 >= h as u64
+            {
+                return Err(Error::InvalidValue("frame_crop_offset"));
+            }
+            sps.crop = Some(crop);
+        }
+        if r.read_bool()? {
+            sps.vui = Some(Vui::read(r)?);
+        }
+        Ok(sps)
+    }
+
+    /// `ChromaArrayType`
+    #[inline]
+    pub fn chroma_array_type(&self) -> u8 {
+        if self.separate_colour_plane {
+            0
+        } else {
+            self.chroma_format_idc
+        }
+    }
+
+    /// Width in pixels before cropping
+    #[inline]
+    pub fn coded_width(&self) -> u32 {
+        self.pic_width_in_mbs * 16
+    }
+
+    /// Height in pixels before cropping
+    #[inline]
+    pub fn coded_height(&self) -> u32 {
+        (2 - self.frame_mbs_only as u32) * self.pic_height_in_map_units * 16
+    }
+
+    /// Crop units in luma samples
+    fn crop_units(&self) -> (u32, u32) {
+        let (x, y) = match self.chroma_array_type() {
+            0 | 3 => (1, 1),
+            1 => (2, 2),
+            _ => (2, 1),
+        };
+        (x, y * (2 - self.frame_mbs_only as u32))
+    }
+
+    /// Crop in luma samples
+    pub fn crop_rect(&self) -> Crop {
+        let Some(c) = self.crop else {
+            return Crop::default();
+        };
+        let (x, y) = self.crop_units();
+        Crop {
+            left: c.left * x,
+            right: c.right * x,
+            top: c.top * y,
+            bottom: c.bottom * y,
+        }
+    }
+
+    /// Display width in pixels
+    #[inline]
+    pub fn width(&self) -> u32 {
+        let c = self.crop_rect();
+        self.coded_width().saturating_sub(c.left + c.right)
+    }
+
+    /// Display height in pixels
+    #[inline]
+    pub fn height(&self) -> u32 {
+        let c = self.crop_rect();
+        self.coded_height().saturating_sub(c.top + c.bottom)
+    }
+
+    #[inline]
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        self.vui.as_ref()?.frame_rate()
+    }
+
+    #[inline]
+    pub fn video_signal(&self) -> Option<&vui::VideoSignal> {
+        self.vui.as_ref()?.video_signal.as_ref()
+    }
+
+    #[inline]
+    pub fn color(&self) -> Option<vui::ColorDescription> {
+        self.video_signal()?.color
+    }
+
+    /// `max_num_reorder_frames` from VUI bitstream restriction
+    #[inline]
+    pub fn max_num_reorder_frames(&self) -> Option<u32> {
+        Some(
+            self.vui
+                .as_ref()?
+                .bitstream_restriction?
+                .max_num_reorder_frames,
+        )
+    }
+
+    /// RFC 6381 codec string, e.g. `avc1.64001f`
+    #[inline]
+    pub fn codec_string(&self) -> String {
+        format!(
+            "avc1.{:02x}{:02x}{:02x}",
+            self.profile_idc, self.constraint_flags, self.level_idc
+        )
+    }
+}
+
+/// Limits of level (Table A-1), level 1b has `level_idc` 9.
+///
+/// ```
+/// use cidre::media::h264;
+///
+/// let limits = h264::LevelLimits::with_level_idc(40).unwrap();
+/// // 1080p is 8160 macroblocks
+/// assert_eq!(limits.max_dpb_frames(8160), 4);
+/// assert_eq!(limits.max_bit_rate(h264::Sps::PROFILE_HIGH), 25_000_000);
+/// assert_eq!(h264::LevelLimits::min_level_idc(1920, 1080, 60.0, 0, 100), Some(42));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct LevelLimits {
+    pub level_idc: u8,
+
+    /// Macroblocks per second
+    pub max_mbps: u32,
+
+    /// Macroblocks per frame
+    pub max_fs: u32,
+
+    /// Macroblocks of decoded picture buffer
+    pub max_dpb_mbs: u32,
+
+    /// VCL bit rate of Baseline, Main and Extended profiles in 1000 bits/s
+    pub max_br: u32,
+}
+
+impl LevelLimits {
+    #[rustfmt::skip]
+    pub const ALL: [Self; 20] = [
+        Self::new(10, 1485, 99, 396, 64),
+        Self::new(9, 1485, 99, 396, 128),
+        Self::new(11, 3000, 396, 900, 192),
+        Self::new(12, 6000, 396, 2376, 384),
+        Self::new(13, 11880, 396, 2376, 768),
+        Self::new(20, 11880, 396, 2376, 2000),
+        Self::new(21, 19800, 792, 4752, 4000),
+        Self::new(22, 20250, 1620, 8100, 4000),
+        Self::new(30, 40500, 1620, 8100, 10000),
+        Self::new(31, 108000, 3600, 18000, 14000),
+        Self::new(32, 216000, 5120, 20480, 20000),
+        Self::new(40, 245760, 8192, 32768, 20000),
+        Self::new(41, 245760, 8192, 32768, 50000),
+        Self::new(42, 522240, 8704, 34816, 50000),
+        Self::new(50, 589824, 22080, 110400, 135000),
+        Self::new(51, 983040, 36864, 184320, 240000),
+        Self::new(52, 2073600, 36864, 184320, 240000),
+        Self::new(60, 4177920, 139264, 696320, 240000),
+        Self::new(61, 8355840, 139264, 696320, 480000),
+        Self::new(62, 16711680, 139264, 696320, 800000),
+    ];
+
+    const fn new(level_idc: u8, max_mbps: u32, max_fs: u32, max_dpb_mbs: u32, max_br: u32) -> Self {
+        Self {
+            level_idc,
+            max_mbps,
+            max_fs,
+            max_dpb_mbs,
+            max_br,
+        }
+    }
+
+    #[inline]
+    pub fn with_level_idc(level_idc: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.level_idc == level_idc)
+    }
+
+    /// Max VCL bit rate in bits/s, scaled by `cpbBrVclFactor` of profile
+    pub fn max_bit_rate(&self, profile_idc: u8) -> u64 {
+        let factor = match profile_idc {
+            Sps::PROFILE_HIGH => 1250,
+            Sps::PROFILE_HIGH_10 => 3000,
+            Sps::PROFILE_HIGH_422 | Sps::PROFILE_HIGH_444 | 44 => 4000,
+            _ => 1000,
+        };
+        self.max_br as u64 * factor
+    }
+
+    /// `MaxDpbFrames` of frame size in macroblocks
+    #[inline]
+    pub fn max_dpb_frames(&self, frame_size_in_mbs: u32) -> u32 {
+        (self.max_dpb_mbs / frame_size_in_mbs.max(1)).min(16)
+    }
+
+    /// Checks frame size, macroblock rate and bit rate, 0 means unknown
+    pub fn fits(
+        &self,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bit_rate: u64,
+        profile_idc: u8,
+    ) -> bool {
+        let frame_size = width.div_ceil(16) as u64 * height.div_ceil(16) as u64;
+        // frame dimensions are limited to sqrt(8 * MaxFS) macroblocks too
+        let max_dim = ((self.max_fs as f64 * 8.0).sqrt()) as u32;
+        frame_size <= self.max_fs as u64
+            && width.div_ceil(16) <= max_dim
+            && height.div_ceil(16) <= max_dim
+            && frame_size as f64 * frame_rate <= self.max_mbps as f64
+            && bit_rate <= self.max_bit_rate(profile_idc)
+    }
+
+    /// The lowest level fitting stream
+    pub fn min_level_idc(
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bit_rate: u64,
+        profile_idc: u8,
+    ) -> Option<u8> {
+        Self::ALL
+            .into_iter()
+            // level 1b is signalled differently in Baseline and Main profiles
+            .filter(|l| l.level_idc != 9)
+            .find(|l| l.fits(width, height, frame_rate, bit_rate, profile_idc))
+            .map(|l| l.level_idc)
+    }
+}
+
+/// Picture parameter set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Pps {
+    pub id: u32,
+    pub sps_id: u32,
+
+    /// CABAC if `true`, CAVLC otherwise
+    pub entropy_coding_mode: bool,
+    pub bottom_field_pic_order_in_frame_present: bool,
+    pub num_slice_groups: u32,
+    pub num_ref_idx_l0_default_active: u32,
+    pub num_ref_idx_l1_default_active: u32,
+    pub weighted_pred: bool,
+    pub weighted_bipred_idc: u8,
+    pub pic_init_qp: i32,
+    pub pic_init_qs: i32,
+    pub chroma_qp_index_offset: i32,
+    pub deblocking_filter_control_present: bool,
+    pub constrained_intra_pred: bool,
+    pub redundant_pic_cnt_present: bool,
+    pub transform_8x8_mode: bool,
+    pub scaling_matrix_present: bool,
+    pub second_chroma_qp_index_offset: i32,
+}
+
+impl Pps {
+    /// Parses PPS NAL unit of 4:2:0 or 4:2:2 stream
+    #[inline]
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        Self::parse_with_chroma_format(nal, 1)
+    }
+
+    /// Parses PPS NAL unit, `sps` is needed for scaling lists of 4:4:4 streams
+    #[inline]
+    pub fn parse_with_sps(nal: &[u8], sps: &Sps) -> Result<Self, Error> {
+        Self::parse_with_chroma_format(nal, sps.chroma_format_idc)
+    }
+
+    fn parse_with_chroma_format(nal: &[u8], chroma_format_idc: u8) -> Result<Self, Error> {
+        let rbsp = read_nal(nal, NalUnitType::PPS)?.rbsp();
+        let r = &mut bits::Reader::new(&rbsp);
+        let id = r.read_ue()?;
+        if id > 255 {
+            return Err(Error::InvalidValue("pic_parameter_set_id"));
+        }
+        let sps_id = r.read_ue()?;
+        if sps_id > 31 {
+            return Err(Error::InvalidValue("seq_parameter_set_id"));
+        }
+        let entropy_coding_mode = r.read_bool()?;
+        let bottom_field_pic_order_in_frame_present = r.read_bool()?;
+        let num_slice_groups = r.read_ue()? + 1;
+        if num_slice_groups > 8 {
+            return Err(Error::InvalidValue("num_slice_groups_minus1"));
+        }
+        if num_slice_groups > 1 {
+            match r.read_ue()? {
+                0 => {
+                    for _ in 0..num_slice_groups {
+                        r.read_ue()?;
+                    }
+                }
+                2 => {
+                    for _ in 0..num_slice_groups - 1 {
+                        r.read_ue()?;
+                        r.read_ue()?;
+                    }
+                }
+                3..=5 => {
+                    r.read_bool()?;
+                    r.read_ue()?;
+                }
+                6 => {
+                    let count = r.read_ue()? as usize + 1;
+                    let bits = 32 - (num_slice_groups - 1).leading_zeros();
+                    r.skip(count * bits as usize)?;
+                }
+                _ => {}
+            }
+        }
+        let mut pps = Self {
+            id,
+            sps_id,
+            entropy_coding_mode,
+            bottom_field_pic_order_in_frame_present,
+            num_slice_groups,
+            num_ref_idx_l0_default_active: r.read_ue()? + 1,
+            num_ref_idx_l1_default_active: r.read_ue()? + 1,
+            weighted_pred: r.read_bool()?,
+            weighted_bipred_idc: r.read_u8(2)?,
+            pic_init_qp: read_qp(r, "pic_init_qp_minus26")?,
+            pic_init_qs: read_qp(r, "pic_init_qs_minus26")?,
+            chroma_qp_index_offset: r.read_se()?,
+            deblocking_filter_control_present: r.read_bool()?,
+            constrained_intra_pred: r.read_bool()?,
+            redundant_pic_cnt_present: r.read_bool()?,
+            transform_8x8_mode: false,
+            scaling_matrix_present: false,
+            second_chroma_qp_index_offset: 0,
+        };
+        pps.second_chroma_qp_index_offset = pps.chroma_qp_index_offset;
+        if r.more_rbsp_data() {
+            pps.transform_8x8_mode = r.read_bool()?;
+            pps.scaling_matrix_present = r.read_bool()?;
+            if pps.scaling_matrix_present {
+                let extra = if chroma_format_idc == 3 { 6 } else { 2 };
+                let count = 6 + extra * pps.transform_8x8_mode as usize;
+                for i in 0..count {
+                    if r.read_bool()? {
+                        skip_scaling_list(r, if i < 6 { 16 } else { 64 })?;
+                    }
+                }
+            }
+            pps.second_chroma_qp_index_offset = r.read_se()?;
+        }
+        Ok(pps)
+    }
+}
+
+/// `avcC` extension of high profiles
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AvccExt {
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub sps_ext: Vec<Vec<u8>>,
+}
+
+/// `AVCDecoderConfigurationRecord` (ISO/IEC 14496-15), contents of `avcC` box and
+/// `cm::VideoFormatDesc::avcc`.
+///
+/// ```
+/// use cidre::media::h264;
+///
+/// let sps = [0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4];
+/// let pps = [0x68, 0xce, 0x3c, 0x80];
+/// let avcc = h264::Avcc::with_param_sets(&sps, &pps, 4).unwrap();
+/// assert_eq!(avcc.profile, 66);
+///
+/// let bytes = avcc.to_bytes();
+/// assert_eq!(bytes[..6], [1, 0x42, 0xc0, 0x1e, 0xff, 0xe1]);
+/// assert_eq!(h264::Avcc::parse(&bytes).unwrap(), avcc);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Avcc {
+    pub profile: u8,
+    pub compatibility: u8,
+    pub level: u8,
+
+    /// Size of NAL unit length prefix in samples: 1, 2 or 4
+    pub nal_length_size: u8,
+    pub sps: Vec<Vec<u8>>,
+    pub pps: Vec<Vec<u8>>,
+    pub ext: Option<AvccExt>,
+}
+
+fn read_param_sets(data: &mut &[u8], count: usize) -> Result<Vec<Vec<u8>>, Error> {
+    let mut res = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (len, rest) = data.split_at_checked(2).ok_or(Error::Truncated)?;
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        let (ps, rest) = rest.split_at_checked(len).ok_or(Error::Truncated)?;
+        res.push(ps.to_vec());
+        *data = rest;
+    }
+    Ok(res)
+}
+
+fn write_param_sets(out: &mut Vec<u8>, sets: &[Vec<u8>]) {
+    for ps in sets {
+        out.extend_from_slice(&(ps.len() as u16).to_be_bytes());
+        out.extend_from_slice(ps);
+    }
+}
+
+impl Avcc {
+    /// Record for single SPS and PPS, profile and level are taken from SPS
+    pub fn with_param_sets(sps: &[u8], pps: &[u8], nal_length_size: u8) -> Result<Self, Error> {
+        Self::with_param_set_lists(vec![sps.to_vec()], vec![pps.to_vec()], nal_length_size)
+    }
+
+    pub fn with_param_set_lists(
+        sps: Vec<Vec<u8>>,
+        pps: Vec<Vec<u8>>,
+        nal_length_size: u8,
+    ) -> Result<Self, Error> {
+        if !matches!(nal_length_size, 1 | 2 | 4) {
+            return Err(Error::InvalidValue("nal_length_size"));
+        }
+        if sps.is_empty() || sps.len() > 31 || pps.len() > 255 {
+            return Err(Error::InvalidValue("number of parameter sets"));
+        }
+        let first = Sps::parse(&sps[0])?;
+        for p in &pps {
+            Pps::parse_with_sps(p, &first)?;
+        }
+        let ext = is_high_profile(first.profile_idc).then(|| AvccExt {
+            chroma_format_idc: first.chroma_format_idc,
+            bit_depth_luma: first.bit_depth_luma,
+            bit_depth_chroma: first.bit_depth_chroma,
+            sps_ext: Vec::new(),
+        });
+        Ok(Self {
+            profile: first.profile_idc,
+            compatibility: first.constraint_flags,
+            level: first.level_idc,
+            nal_length_size,
+            sps,
+            pps,
+            ext,
+        })
+    }
+
+    /// Collects SPS and PPS NAL units from Annex B stream
+    pub fn with_annex_b(data: &[u8], nal_length_size: u8) -> Result<Self, Error> {
+        let (mut sps, mut pps) = (Vec::new(), Vec::new());
+        for data in nal::annex_b(data) {
+            match Nal::new(data).map(|n| n.unit_type()) {
+                Some(NalUnitType::SPS) if !sps.iter().any(|s| s == data) => sps.push(data.to_vec()),
+                Some(NalUnitType::PPS) if !pps.iter().any(|p| p == data) => pps.push(data.to_vec()),
+                _ => {}
+            }
+        }
+        Self::with_param_set_lists(sps, pps, nal_length_size)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (header, mut rest) = data.split_at_checked(6).ok_or(Error::Truncated)?;
+        if header[0] != 1 {
+            return Err(Error::InvalidValue("configurationVersion"));
+        }
+        let nal_length_size = (header[4] & 3) + 1;
+        if nal_length_size == 3 {
+            return Err(Error::InvalidValue("lengthSizeMinusOne"));
+        }
+        let sps = read_param_sets(&mut rest, (header[5] & 0x1f) as usize)?;
+        let (&pps_count, tail) = rest.split_first().ok_or(Error::Truncated)?;
+        rest = tail;
+        let pps = read_param_sets(&mut rest, pps_count as usize)?;
+
+        // extension is optional even for high profiles
+        let ext = if is_high_profile(header[1]) && !rest.is_empty() {
+            if rest.len() < 4 {
+                return Err(Error::Truncated);
+            }
+            let chroma_format_idc = rest[0] & 3;
+            let bit_depth_luma = (rest[1] & 7) + 8;
+            let bit_depth_chroma = (rest[2] & 7) + 8;
+            let count = rest[3] as usize;
+            rest = &rest[4..];
+            Some(AvccExt {
+                chroma_format_idc,
+                bit_depth_luma,
+                bit_depth_chroma,
+                sps_ext: read_param_sets(&mut rest, count)?,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            profile: header[1],
+            compatibility: header[2],
+            level: header[3],
+            nal_length_size,
+            sps,
+            pps,
+            ext,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![
+            1,
+            self.profile,
+            self.compatibility,
+            self.level,
+            0xfc | (self.nal_length_size - 1),
+            0xe0 | self.sps.len() as u8,
+        ];
+        write_param_sets(&mut out, &self.sps);
+        out.push(self.pps.len() as u8);
+        write_param_sets(&mut out, &self.pps);
+        if let Some(ext) = &self.ext {
+            out.push(0xfc | ext.chroma_format_idc);
+            out.push(0xf8 | (ext.bit_depth_luma - 8));
+            out.push(0xf8 | (ext.bit_depth_chroma - 8));
+            out.push(ext.sps_ext.len() as u8);
+            write_param_sets(&mut out, &ext.sps_ext);
+        }
+        out
+    }
+
+    /// First SPS
+    #[inline]
+    pub fn parse_sps(&self) -> Result<Sps, Error> {
+        Sps::parse(self.sps.first().ok_or(Error::Truncated)?)
+    }
+
+    /// Parameter sets in Annex B format to prepend to keyframes
+    pub fn to_annex_b(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for ps in self.sps.iter().chain(&self.pps) {
+            out.extend_from_slice(&[0, 0, 0, 1]);
+            out.extend_from_slice(ps);
+        }
+        out
+    }
+
+    /// RFC 6381 codec string
+    #[inline]
+    pub fn codec_string(&self) -> String {
+        format!(
+            "avc1.{:02x}{:02x}{:02x}",
+            self.profile, self.compatibility, self.level
+        )
+    }
+
+    #[cfg(feature = "cm")]
+    pub fn with_format_desc(desc: &cm::VideoFormatDesc) -> Option<Self> {
+        Self::parse(&desc.avcc()?).ok()
+    }
+
+    /// Format description with the first SPS and PPS
+    #[cfg(feature = "cm")]
+    pub fn to_format_desc(&self) -> os::Result<arc::R<cm::VideoFormatDesc>> {
+        let (Some(sps), Some(pps)) = (self.sps.first(), self.pps.first()) else {
+            return Err(cm::format_desc_bridge_err::INVALID_PARAMETER);
+        };
+        cm::VideoFormatDesc::with_h264_param_sets(
+            &[sps.as_ptr(), pps.as_ptr()],
+            &[sps.len(), pps.len()],
+            self.nal_length_size as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{bits, nal};
+
+    use super::{Avcc, Error, NalUnitType, PicOrderCnt, Pps, Sps};
+
+    /// 1280x720 High 4:2:2 10-bit with cropping, VUI color and timing
+    fn high_422_sps() -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(122, 8);
+        w.write_bits(0, 8);
+        w.write_bits(31, 8);
+        w.write_ue(0); // sps id
+        w.write_ue(2); // chroma_format_idc
+        w.write_ue(2); // bit_depth_luma_minus8
+        w.write_ue(2);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_ue(0); // log2_max_frame_num_minus4
+        w.write_ue(0); // poc type
+        w.write_ue(2);
+        w.write_ue(4); // max_num_ref_frames
+        w.write_bool(false);
+        w.write_ue(79); // 80 mbs
+        w.write_ue(45); // 46 mbs
+        w.write_bool(true); // frame_mbs_only
+        w.write_bool(true);
+        w.write_bool(true); // cropping
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_ue(16); // 16 rows for 4:2:2
+        w.write_bool(true); // vui
+        w.write_bool(true);
+        w.write_bits(1, 8); // 1:1
+        w.write_bool(false);
+        w.write_bool(true); // video signal
+        w.write_bits(5, 3);
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_bits(9, 8);
+        w.write_bits(16, 8);
+        w.write_bits(9, 8);
+        w.write_bool(false);
+        w.write_bool(true); // timing
+        w.write_bits(1001, 32);
+        w.write_bits(60000, 32);
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(true); // bitstream restriction
+        w.write_bool(true);
+        for v in [2, 1, 16, 16, 2, 4] {
+            w.write_ue(v);
+        }
+        w.write_trailing_bits();
+        let mut nal = vec![0x67];
+        nal.extend(nal::to_ebsp(&w.into_vec()));
+        nal
+    }
+
+    #[test]
+    fn sps() {
+        let sps = Sps::parse(&high_422_sps()).unwrap();
+        assert_eq!((sps.bit_depth_luma, sps.chroma_format_idc), (10, 2));
+        assert_eq!((sps.coded_width(), sps.coded_height()), (1280, 736));
+        assert_eq!((sps.width(), sps.height()), (1280, 720));
+        assert_eq!(sps.frame_rate(), Some((30000, 1001)));
+        let signal = sps.video_signal().unwrap();
+        assert!(signal.full_range);
+        assert!(sps.color().unwrap().is_hdr());
+        assert_eq!(sps.max_num_reorder_frames(), Some(2));
+        assert_eq!(sps.vui.as_ref().unwrap().sample_aspect_ratio, Some((1, 1)));
+        assert_eq!(
+            sps.pic_order_cnt,
+            PicOrderCnt::Type0 {
+                log2_max_pic_order_cnt_lsb: 6
+            }
+        );
+
+        let baseline = Sps::parse(&[0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4]);
+        let baseline = baseline.unwrap();
+        assert_eq!((baseline.width(), baseline.height()), (320, 240));
+        assert_eq!(baseline.codec_string(), "avc1.42c01e");
+
+        assert_eq!(
+            Sps::parse(&[0x68, 0xce]),
+            Err(Error::UnexpectedNalType(NalUnitType::PPS))
+        );
+        assert_eq!(Sps::parse(&[0x67, 0x64, 0x00]), Err(Error::Truncated));
+        assert_eq!(
+            Sps::parse(&[0x67, 0x42, 0xc0, 0x1e, 0x80, 0, 0, 0, 0xff, 0xff, 0xff, 0xfe, 0xfe]),
+            Err(Error::InvalidValue("log2_max_frame_num_minus4"))
+        );
+
+        // width in samples would overflow
+        let mut w = bits::Writer::new();
+        w.write_bits(0x6742_c01e, 32);
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_ue(2); // poc type
+        w.write_ue(0);
+        w.write_bool(false);
+        w.write_ue(u32::MAX - 1);
+        w.write_trailing_bits();
+        assert_eq!(
+            Sps::parse(&w.into_vec()),
+            Err(Error::InvalidValue("pic_width_in_mbs_minus1"))
+        );
+    }
+
+    #[test]
+    fn pps() {
+        let pps = Pps::parse(&[0x68, 0xce, 0x3c, 0x80]).unwrap();
+        assert_eq!((pps.id, pps.sps_id), (0, 0));
+        assert!(!pps.entropy_coding_mode);
+        assert_eq!(pps.pic_init_qp, 26);
+
+        // x264 high profile PPS with transform_8x8_mode
+        let pps = Pps::parse(&[0x68, 0xeb, 0xe3, 0xcb, 0x22, 0xc0]).unwrap();
+        assert!(pps.entropy_coding_mode);
+        assert!(pps.transform_8x8_mode);
+        assert_eq!(pps.weighted_bipred_idc, 2);
+        assert_eq!(pps.chroma_qp_index_offset, -2);
+    }
+
+    #[test]
+    fn avcc() {
+        let sps = high_422_sps();
+        let pps = [0x68, 0xeb, 0xe3, 0xcb, 0x22, 0xc0];
+        let mut stream = vec![0, 0, 0, 1, 0x09, 0xf0, 0, 0, 1];
+        stream.extend_from_slice(&sps);
+        stream.extend_from_slice(&[0, 0, 1]);
+        stream.extend_from_slice(&pps);
+        stream.extend_from_slice(&[0, 0, 1, 0x65, 0x88]);
+
+        let avcc = Avcc::with_annex_b(&stream, 2).unwrap();
+        assert_eq!((avcc.sps.len(), avcc.pps.len()), (1, 1));
+        let ext = avcc.ext.as_ref().unwrap();
+        assert_eq!((ext.chroma_format_idc, ext.bit_depth_luma), (2, 10));
+        assert_eq!(avcc.codec_string(), "avc1.7a001f");
+
+        let bytes = avcc.to_bytes();
+        assert_eq!(bytes[4], 0xfd);
+        assert_eq!(Avcc::parse(&bytes).unwrap(), avcc);
+        assert_eq!(
+            Avcc::parse(&bytes[..bytes.len() - 1]),
+            Err(Error::Truncated)
+        );
+        assert_eq!(avcc.parse_sps().unwrap().width(), 1280);
+        assert!(avcc.to_annex_b().starts_with(&[0, 0, 0, 1, 0x67, 122]));
+
+        let sample = nal::annex_b_to_length_prefixed(&stream, 2).unwrap();
+        assert!(super::is_sync_sample(&sample, 2).unwrap());
+        assert!(!super::is_sync_sample(&sample[..4], 2).unwrap());
+
+        assert!(Avcc::with_param_sets(&sps, &pps, 3).is_err());
+        assert!(Avcc::with_annex_b(&[0, 0, 1, 0x65], 4).is_err());
+    }
+}
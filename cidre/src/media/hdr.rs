@@ -0,0 +1,606 @@
+//! HDR static metadata in the big-endian layouts shared by H.264/HEVC SEI payloads,
+//! `cm::FormatDescExtKey` values and ISO BMFF boxes.
+
+use crate::media::vui;
+
+#[cfg(any(feature = "cm", feature = "cv"))]
+use crate::cf;
+
+#[cfg(feature = "cm")]
+use crate::{arc, cm};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Box type or size does not match
+    InvalidValue(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated HDR metadata"),
+            Self::InvalidValue(name) => write!(f, "invalid HDR metadata {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[inline]
+fn u16_at(data: &[u8], i: usize) -> u16 {
+    u16::from_be_bytes([data[i], data[i + 1]])
+}
+
+#[inline]
+fn u32_at(data: &[u8], i: usize) -> u32 {
+    u32::from_be_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]])
+}
+
+fn to_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + payload.len());
+    out.extend_from_slice(&(8 + payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(box_type);
+    out.extend_from_slice(payload);
+    out
+}
+
+fn box_payload<'a>(data: &'a [u8], box_type: &[u8; 4]) -> Result<&'a [u8], Error> {
+    let (header, _) = data.split_at_checked(8).ok_or(Error::Truncated)?;
+    if &header[4..] != box_type {
+        return Err(Error::InvalidValue("box type"));
+    }
+    let (start, end) = match u32_at(header, 0) {
+        0 => (8, data.len()),
+        1 => {
+            let size = data.get(8..16).ok_or(Error::Truncated)?;
+            let size = u64::from_be_bytes(size.try_into().unwrap());
+            (16, usize::try_from(size).map_err(|_| Error::Truncated)?)
+        }
+        size => (8, size as usize),
+    };
+    if end < start {
+        return Err(Error::InvalidValue("box size"));
+    }
+    data.get(start..end).ok_or(Error::Truncated)
+}
+
+/// SMPTE ST 2086 mastering display color volume.
+///
+/// Chromaticity coordinates are in units of 0.00002 and luminance in units of
+/// 0.0001 cd/m². Primaries are stored in green, blue, red order as recommended
+/// by H.265 D.3.28 and expected by Core Media.
+///
+/// ```
+/// use cidre::media::hdr;
+///
+/// let mdcv = hdr::MasteringDisplay::P3_D65_1000;
+/// assert_eq!(mdcv.red(), (0.68, 0.32));
+/// assert_eq!(mdcv.max_nits(), 1000.0);
+///
+/// let bytes = mdcv.to_bytes();
+/// assert_eq!(bytes[..4], [0x33, 0xc2, 0x86, 0xc4]);
+/// assert_eq!(hdr::MasteringDisplay::parse(&bytes).unwrap(), mdcv);
+/// assert_eq!(hdr::MasteringDisplay::parse_box(&mdcv.to_box()).unwrap(), mdcv);
+/// ```
+#[doc(alias = "mdcv")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MasteringDisplay {
+    pub red: [u16; 2],
+    pub green: [u16; 2],
+    pub blue: [u16; 2],
+    pub white_point: [u16; 2],
+    pub max_luminance: u32,
+    pub min_luminance: u32,
+}
+
+impl MasteringDisplay {
+    pub const LEN: usize = 24;
+
+    /// `mastering_display_colour_volume` SEI payloadType
+    pub const SEI_PAYLOAD_TYPE: u32 = 137;
+    pub const BOX_TYPE: [u8; 4] = *b"mdcv";
+
+    /// Display P3 D65 mastering display with 1000 and 0.0001 cd/m²
+    pub const P3_D65_1000: Self = Self {
+        red: [34000, 16000],
+        green: [13250, 34500],
+        blue: [7500, 3000],
+        white_point: [15635, 16450],
+        max_luminance: 10_000_000,
+        min_luminance: 1,
+    };
+
+    /// BT.2020 mastering display with 1000 and 0.0001 cd/m²
+    pub const BT2020_1000: Self = Self {
+        red: [35400, 14600],
+        green: [8500, 39850],
+        blue: [6550, 2300],
+        white_point: [15635, 16450],
+        max_luminance: 10_000_000,
+        min_luminance: 1,
+    };
+
+    /// Mastering display from CIE 1931 xy coordinates and luminance in cd/m²
+    pub fn with_chromaticities(
+        red: (f64, f64),
+        green: (f64, f64),
+        blue: (f64, f64),
+        white_point: (f64, f64),
+        max_nits: f64,
+        min_nits: f64,
+    ) -> Self {
+        let xy = |(x, y): (f64, f64)| [(x * 50000.0).round() as u16, (y * 50000.0).round() as u16];
+        Self {
+            red: xy(red),
+            green: xy(green),
+            blue: xy(blue),
+            white_point: xy(white_point),
+            max_luminance: (max_nits * 10000.0).round() as u32,
+            min_luminance: (min_nits * 10000.0).round() as u32,
+        }
+    }
+
+    #[inline]
+    fn xy(v: [u16; 2]) -> (f64, f64) {
+        (v[0] as f64 / 50000.0, v[1] as f64 / 50000.0)
+    }
+
+    #[inline]
+    pub fn red(&self) -> (f64, f64) {
+        Self::xy(self.red)
+    }
+
+    #[inline]
+    pub fn green(&self) -> (f64, f64) {
+        Self::xy(self.green)
+    }
+
+    #[inline]
+    pub fn blue(&self) -> (f64, f64) {
+        Self::xy(self.blue)
+    }
+
+    #[inline]
+    pub fn white_point_xy(&self) -> (f64, f64) {
+        Self::xy(self.white_point)
+    }
+
+    #[inline]
+    pub fn max_nits(&self) -> f64 {
+        self.max_luminance as f64 / 10000.0
+    }
+
+    #[inline]
+    pub fn min_nits(&self) -> f64 {
+        self.min_luminance as f64 / 10000.0
+    }
+
+    /// Parses SEI payload, `mdcv` box payload or Core Media extension data
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let d = data.get(..Self::LEN).ok_or(Error::Truncated)?;
+        let xy = |i| [u16_at(d, i), u16_at(d, i + 2)];
+        Ok(Self {
+            green: xy(0),
+            blue: xy(4),
+            red: xy(8),
+            white_point: xy(12),
+            max_luminance: u32_at(d, 16),
+            min_luminance: u32_at(d, 20),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        for (i, v) in [self.green, self.blue, self.red, self.white_point]
+            .iter()
+            .flatten()
+            .enumerate()
+        {
+            out[i * 2..i * 2 + 2].copy_from_slice(&v.to_be_bytes());
+        }
+        out[16..20].copy_from_slice(&self.max_luminance.to_be_bytes());
+        out[20..].copy_from_slice(&self.min_luminance.to_be_bytes());
+        out
+    }
+
+    /// Parses whole `mdcv` box with header
+    pub fn parse_box(data: &[u8]) -> Result<Self, Error> {
+        Self::parse(box_payload(data, &Self::BOX_TYPE)?)
+    }
+
+    /// `mdcv` box with header
+    pub fn to_box(&self) -> Vec<u8> {
+        to_box(&Self::BOX_TYPE, &self.to_bytes())
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn format_desc_ext_key() -> &'static cm::FormatDescExtKey {
+        cm::FormatDescExtKey::mastering_display_color_volume()
+    }
+
+    /// Value for [`Self::format_desc_ext_key`]
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn to_cf_data(&self) -> Option<arc::R<cf::Data>> {
+        cf::Data::from_slice(&self.to_bytes())
+    }
+}
+
+/// Content light level information, MaxCLL and MaxFALL in cd/m².
+///
+/// ```
+/// use cidre::media::hdr;
+///
+/// let clli = hdr::ContentLightLevel { max_cll: 1000, max_fall: 400 };
+/// assert_eq!(clli.to_bytes(), [0x03, 0xe8, 0x01, 0x90]);
+/// assert_eq!(hdr::ContentLightLevel::parse(&clli.to_bytes()).unwrap(), clli);
+/// ```
+#[doc(alias = "clli")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContentLightLevel {
+    pub max_cll: u16,
+    pub max_fall: u16,
+}
+
+impl ContentLightLevel {
+    pub const LEN: usize = 4;
+
+    /// `content_light_level_info` SEI payloadType
+    pub const SEI_PAYLOAD_TYPE: u32 = 144;
+    pub const BOX_TYPE: [u8; 4] = *b"clli";
+
+    /// Parses SEI payload, `clli` box payload or Core Media extension data
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let d = data.get(..Self::LEN).ok_or(Error::Truncated)?;
+        Ok(Self {
+            max_cll: u16_at(d, 0),
+            max_fall: u16_at(d, 2),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let [a, b] = self.max_cll.to_be_bytes();
+        let [c, d] = self.max_fall.to_be_bytes();
+        [a, b, c, d]
+    }
+
+    /// Parses whole `clli` box with header
+    pub fn parse_box(data: &[u8]) -> Result<Self, Error> {
+        Self::parse(box_payload(data, &Self::BOX_TYPE)?)
+    }
+
+    /// `clli` box with header
+    pub fn to_box(&self) -> Vec<u8> {
+        to_box(&Self::BOX_TYPE, &self.to_bytes())
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn format_desc_ext_key() -> &'static cm::FormatDescExtKey {
+        cm::FormatDescExtKey::content_light_level_info()
+    }
+
+    /// Value for [`Self::format_desc_ext_key`]
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn to_cf_data(&self) -> Option<arc::R<cf::Data>> {
+        cf::Data::from_slice(&self.to_bytes())
+    }
+}
+
+/// Ambient viewing environment.
+///
+/// Illuminance is in units of 0.0001 lux and light chromaticity in units of 0.00002.
+///
+/// ```
+/// use cidre::media::hdr;
+///
+/// let amve = hdr::AmbientViewing::REFERENCE;
+/// assert_eq!(amve.illuminance_lux(), 5.0);
+/// assert_eq!(hdr::AmbientViewing::parse_box(&amve.to_box()).unwrap(), amve);
+/// ```
+#[doc(alias = "amve")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AmbientViewing {
+    pub illuminance: u32,
+    pub light_x: u16,
+    pub light_y: u16,
+}
+
+impl AmbientViewing {
+    pub const LEN: usize = 8;
+
+    /// `ambient_viewing_environment` SEI payloadType
+    pub const SEI_PAYLOAD_TYPE: u32 = 148;
+    pub const BOX_TYPE: [u8; 4] = *b"amve";
+
+    /// 5 lux D65 surround of ITU-R BT.2100 reference viewing environment
+    pub const REFERENCE: Self = Self {
+        illuminance: 50_000,
+        light_x: 15635,
+        light_y: 16450,
+    };
+
+    #[inline]
+    pub fn illuminance_lux(&self) -> f64 {
+        self.illuminance as f64 / 10000.0
+    }
+
+    /// Parses SEI payload or `amve` box payload
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let d = data.get(..Self::LEN).ok_or(Error::Truncated)?;
+        Ok(Self {
+            illuminance: u32_at(d, 0),
+            light_x: u16_at(d, 4),
+            light_y: u16_at(d, 6),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0u8; Self::LEN];
+        out[..4].copy_from_slice(&self.illuminance.to_be_bytes());
+        out[4..6].copy_from_slice(&self.light_x.to_be_bytes());
+        out[6..].copy_from_slice(&self.light_y.to_be_bytes());
+        out
+    }
+
+    /// Parses whole `amve` box with header
+    pub fn parse_box(data: &[u8]) -> Result<Self, Error> {
+        Self::parse(box_payload(data, &Self::BOX_TYPE)?)
+    }
+
+    /// `amve` box with header
+    pub fn to_box(&self) -> Vec<u8> {
+        to_box(&Self::BOX_TYPE, &self.to_bytes())
+    }
+}
+
+/// Content color volume (H.265 D.2.40) without cancel flag.
+///
+/// Primaries are signed in units of 0.00002 and luminance in units of 0.0000001 cd/m².
+#[doc(alias = "ccv")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ContentColorVolume {
+    pub persistence: bool,
+
+    /// Primaries in `[x, y]` pairs
+    pub primaries: Option<[[i32; 2]; 3]>,
+    pub min_luminance: Option<u32>,
+    pub max_luminance: Option<u32>,
+    pub avg_luminance: Option<u32>,
+}
+
+impl ContentColorVolume {
+    /// `content_colour_volume` SEI payloadType
+    pub const SEI_PAYLOAD_TYPE: u32 = 149;
+
+    /// Parses SEI payload or Core Media extension data, `None` if it cancels previous one
+    pub fn parse(data: &[u8]) -> Result<Option<Self>, Error> {
+        let (&flags, mut rest) = data.split_first().ok_or(Error::Truncated)?;
+        if flags & 0x80 != 0 {
+            return Ok(None);
+        }
+        let mut next = || -> Result<u32, Error> {
+            let (v, tail) = rest.split_at_checked(4).ok_or(Error::Truncated)?;
+            rest = tail;
+            Ok(u32_at(v, 0))
+        };
+        let primaries = if flags & 0x20 != 0 {
+            let mut p = [[0i32; 2]; 3];
+            for v in p.iter_mut().flatten() {
+                *v = next()? as i32;
+            }
+            Some(p)
+        } else {
+            None
+        };
+        let mut optional = |mask: u8| -> Result<Option<u32>, Error> {
+            if flags & mask != 0 {
+                next().map(Some)
+            } else {
+                Ok(None)
+            }
+        };
+        Ok(Some(Self {
+            persistence: flags & 0x40 != 0,
+            primaries,
+            min_luminance: optional(0x10)?,
+            max_luminance: optional(0x08)?,
+            avg_luminance: optional(0x04)?,
+        }))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 24 + 12);
+        out.push(
+            (self.persistence as u8) << 6
+                | (self.primaries.is_some() as u8) << 5
+                | (self.min_luminance.is_some() as u8) << 4
+                | (self.max_luminance.is_some() as u8) << 3
+                | (self.avg_luminance.is_some() as u8) << 2,
+        );
+        for v in self.primaries.iter().flatten().flatten() {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        for v in [self.min_luminance, self.max_luminance, self.avg_luminance]
+            .into_iter()
+            .flatten()
+        {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+        out
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn format_desc_ext_key() -> &'static cm::FormatDescExtKey {
+        cm::FormatDescExtKey::content_color_volume()
+    }
+
+    /// Value for [`Self::format_desc_ext_key`]
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn to_cf_data(&self) -> Option<arc::R<cf::Data>> {
+        cf::Data::from_slice(&self.to_bytes())
+    }
+}
+
+/// Alternative transfer characteristics, usually HLG signaled over BT.2020 transfer
+/// for backward compatible streams.
+///
+/// ```
+/// use cidre::media::{hdr, vui};
+///
+/// let atc = hdr::AlternativeTransfer::HLG;
+/// assert_eq!(atc.to_bytes(), [vui::ColorDescription::TRANSFER_HLG]);
+/// ```
+#[doc(alias = "atc")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AlternativeTransfer {
+    pub preferred_transfer: u8,
+}
+
+impl AlternativeTransfer {
+    /// `alternative_transfer_characteristics` SEI payloadType
+    pub const SEI_PAYLOAD_TYPE: u32 = 147;
+
+    pub const HLG: Self = Self {
+        preferred_transfer: vui::ColorDescription::TRANSFER_HLG,
+    };
+
+    /// Parses SEI payload
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let &preferred_transfer = data.first().ok_or(Error::Truncated)?;
+        Ok(Self { preferred_transfer })
+    }
+
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; 1] {
+        [self.preferred_transfer]
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn format_desc_ext_key() -> &'static cm::FormatDescExtKey {
+        cm::FormatDescExtKey::alternate_transfer_characterisitcs()
+    }
+
+    /// Value for [`Self::format_desc_ext_key`], `None` if there is no matching transfer function
+    #[cfg(feature = "cv")]
+    pub fn cv_transfer_fn(&self) -> Option<&'static cf::String> {
+        vui::ColorDescription {
+            primaries: vui::ColorDescription::PRIMARIES_UNSPECIFIED,
+            transfer: self.preferred_transfer,
+            matrix: vui::ColorDescription::MATRIX_UNSPECIFIED,
+        }
+        .cv_transfer_fn()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AmbientViewing, ContentColorVolume, ContentLightLevel, Error, MasteringDisplay};
+
+    #[test]
+    fn mastering_display() {
+        let mdcv = MasteringDisplay::with_chromaticities(
+            (0.708, 0.292),
+            (0.170, 0.797),
+            (0.131, 0.046),
+            (0.3127, 0.3290),
+            1000.0,
+            0.0001,
+        );
+        assert_eq!(mdcv, MasteringDisplay::BT2020_1000);
+        assert_eq!(mdcv.white_point_xy(), (0.3127, 0.329));
+        assert_eq!(mdcv.min_nits(), 0.0001);
+
+        let bytes = mdcv.to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                0x21, 0x34, 0x9b, 0xaa, 0x19, 0x96, 0x08, 0xfc, 0x8a, 0x48, 0x39, 0x08, 0x3d, 0x13,
+                0x40, 0x42, 0x00, 0x98, 0x96, 0x80, 0x00, 0x00, 0x00, 0x01
+            ]
+        );
+        assert_eq!(MasteringDisplay::parse(&bytes), Ok(mdcv));
+        assert_eq!(MasteringDisplay::parse(&bytes[..23]), Err(Error::Truncated));
+
+        let unknown = MasteringDisplay::parse(&[0; 24]).unwrap();
+        assert_eq!(unknown.to_bytes(), [0; 24]);
+
+        let mut mdcv_box = mdcv.to_box();
+        assert_eq!(mdcv_box[..8], [0, 0, 0, 32, b'm', b'd', b'c', b'v']);
+        assert_eq!(MasteringDisplay::parse_box(&mdcv_box), Ok(mdcv));
+        mdcv_box[3] = 40;
+        assert_eq!(
+            MasteringDisplay::parse_box(&mdcv_box),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            MasteringDisplay::parse_box(&[0, 0, 0, 12, b'c', b'l', b'l', b'i', 0, 0, 0, 0]),
+            Err(Error::InvalidValue("box type"))
+        );
+
+        let mut large = vec![0, 0, 0, 1, b'm', b'd', b'c', b'v'];
+        large.extend_from_slice(&40u64.to_be_bytes());
+        large.extend_from_slice(&bytes);
+        assert_eq!(MasteringDisplay::parse_box(&large), Ok(mdcv));
+    }
+
+    #[test]
+    fn light_level_and_ambient() {
+        let clli =
+            ContentLightLevel::parse_box(&[0, 0, 0, 12, b'c', b'l', b'l', b'i', 4, 0, 0, 200])
+                .unwrap();
+        assert_eq!((clli.max_cll, clli.max_fall), (1024, 200));
+        assert_eq!(ContentLightLevel::parse(&[0, 1, 2]), Err(Error::Truncated));
+
+        let amve = AmbientViewing::parse(&[0, 0, 0x4e, 0x20, 0x3d, 0x13, 0x40, 0x42]).unwrap();
+        assert_eq!(amve.illuminance_lux(), 2.0);
+        assert_eq!((amve.light_x, amve.light_y), (15635, 16450));
+        assert_eq!(AmbientViewing::parse(&[0; 7]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn content_color_volume() {
+        assert_eq!(ContentColorVolume::parse(&[0x80]), Ok(None));
+        assert_eq!(ContentColorVolume::parse(&[]), Err(Error::Truncated));
+
+        let ccv = ContentColorVolume {
+            persistence: true,
+            primaries: Some([[35400, 14600], [8500, 39850], [6550, -2300]]),
+            min_luminance: None,
+            max_luminance: Some(4_000_000_000),
+            avg_luminance: Some(1_000_000_000),
+        };
+        let bytes = ccv.to_bytes();
+        assert_eq!(bytes.len(), 1 + 24 + 8);
+        assert_eq!(bytes[0], 0x6c);
+        assert_eq!(bytes[21..25], (-2300i32).to_be_bytes());
+        assert_eq!(ContentColorVolume::parse(&bytes), Ok(Some(ccv)));
+        assert_eq!(
+            ContentColorVolume::parse(&bytes[..bytes.len() - 1]),
+            Err(Error::Truncated)
+        );
+
+        let only_avg = ContentColorVolume {
+            persistence: false,
+            primaries: None,
+            min_luminance: None,
+            max_luminance: None,
+            avg_luminance: Some(7),
+        };
+        assert_eq!(only_avg.to_bytes(), [0x04, 0, 0, 0, 7]);
+        assert_eq!(
+            ContentColorVolume::parse(&[0x04, 0, 0, 0, 7]),
+            Ok(Some(only_avg))
+        );
+    }
+}
@@ -0,0 +1,2412 @@
+//! HEVC (ITU-T H.265 / ISO/IEC 23008-2) NAL units, parameter sets and `hvcC`/`lhvC` records.
+//!
+//! Multi-layer (MV-HEVC) streams, like spatial video recorded by iPhone and Vision Pro,
+//! carry base layer parameter sets in `hvcC` and the second view ones in `lhvC`.
+
+use crate::media::{bits, nal, vui};
+
+#[cfg(feature = "cm")]
+use crate::{arc, cm, os};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    UnexpectedNalType(NalUnitType),
+
+    /// Syntax element is out of range or inconsistent
+    InvalidValue(&'static str),
+
+    Nal(nal::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated HEVC data"),
+            Self::UnexpectedNalType(t) => write!(f, "unexpected HEVC NAL unit type {}", t.0),
+            Self::InvalidValue(name) => write!(f, "invalid HEVC {name}"),
+            Self::Nal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Eof => Self::Truncated,
+            bits::Error::InvalidExpGolomb => Self::InvalidValue("exp-golomb code"),
+        }
+    }
+}
+
+impl From<nal::Error> for Error {
+    #[inline]
+    fn from(value: nal::Error) -> Self {
+        Self::Nal(value)
+    }
+}
+
+/// `nal_unit_type`
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct NalUnitType(pub u8);
+
+impl NalUnitType {
+    pub const TRAIL_N: Self = Self(0);
+    pub const TRAIL_R: Self = Self(1);
+    pub const TSA_N: Self = Self(2);
+    pub const TSA_R: Self = Self(3);
+    pub const STSA_N: Self = Self(4);
+    pub const STSA_R: Self = Self(5);
+    pub const RADL_N: Self = Self(6);
+    pub const RADL_R: Self = Self(7);
+    pub const RASL_N: Self = Self(8);
+    pub const RASL_R: Self = Self(9);
+    pub const BLA_W_LP: Self = Self(16);
+    pub const BLA_W_RADL: Self = Self(17);
+    pub const BLA_N_LP: Self = Self(18);
+    pub const IDR_W_RADL: Self = Self(19);
+    pub const IDR_N_LP: Self = Self(20);
+    pub const CRA: Self = Self(21);
+    pub const VPS: Self = Self(32);
+    pub const SPS: Self = Self(33);
+    pub const PPS: Self = Self(34);
+    pub const AUD: Self = Self(35);
+    pub const EOS: Self = Self(36);
+    pub const EOB: Self = Self(37);
+    pub const FD: Self = Self(38);
+    pub const PREFIX_SEI: Self = Self(39);
+    pub const SUFFIX_SEI: Self = Self(40);
+
+    /// Coded slice segment (video coding layer) NAL unit
+    #[inline]
+    pub fn is_vcl(&self) -> bool {
+        self.0 < 32
+    }
+
+    /// Intra random access point: BLA, IDR or CRA
+    #[inline]
+    pub fn is_irap(&self) -> bool {
+        (16..=23).contains(&self.0)
+    }
+
+    #[inline]
+    pub fn is_idr(&self) -> bool {
+        matches!(*self, Self::IDR_W_RADL | Self::IDR_N_LP)
+    }
+
+    #[inline]
+    pub fn is_bla(&self) -> bool {
+        (16..=18).contains(&self.0)
+    }
+
+    #[inline]
+    pub fn is_cra(&self) -> bool {
+        *self == Self::CRA
+    }
+
+    /// Random access skipped leading picture, not decodable after random access
+    #[inline]
+    pub fn is_rasl(&self) -> bool {
+        matches!(*self, Self::RASL_N | Self::RASL_R)
+    }
+
+    /// Random access decodable leading picture
+    #[inline]
+    pub fn is_radl(&self) -> bool {
+        matches!(*self, Self::RADL_N | Self::RADL_R)
+    }
+
+    #[inline]
+    pub fn is_leading(&self) -> bool {
+        (6..=9).contains(&self.0)
+    }
+
+    /// Picture not used for reference by pictures of the same sub-layer
+    #[inline]
+    pub fn is_sub_layer_non_ref(&self) -> bool {
+        self.0 <= 14 && self.0 % 2 == 0
+    }
+
+    #[inline]
+    pub fn is_param_set(&self) -> bool {
+        matches!(*self, Self::VPS | Self::SPS | Self::PPS)
+    }
+
+    #[inline]
+    pub fn is_sei(&self) -> bool {
+        matches!(*self, Self::PREFIX_SEI | Self::SUFFIX_SEI)
+    }
+
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            0 => "TRAIL_N",
+            1 => "TRAIL_R",
+            2 => "TSA_N",
+            3 => "TSA_R",
+            4 => "STSA_N",
+            5 => "STSA_R",
+            6 => "RADL_N",
+            7 => "RADL_R",
+            8 => "RASL_N",
+            9 => "RASL_R",
+            16 => "BLA_W_LP",
+            17 => "BLA_W_RADL",
+            18 => "BLA_N_LP",
+            19 => "IDR_W_RADL",
+            20 => "IDR_N_LP",
+            21 => "CRA",
+            32 => "VPS",
+            33 => "SPS",
+            34 => "PPS",
+            35 => "AUD",
+            36 => "EOS",
+            37 => "EOB",
+            38 => "FD",
+            39 => "PREFIX_SEI",
+            40 => "SUFFIX_SEI",
+            _ => "RESERVED",
+        }
+    }
+}
+
+impl std::fmt::Debug for NalUnitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.name(), self.0)
+    }
+}
+
+/// NAL unit with two byte header.
+///
+/// ```
+/// use cidre::media::hevc;
+///
+/// let nal = hevc::Nal::new(&[0x2a, 0x01, 0xaf]).unwrap();
+/// assert_eq!(nal.unit_type(), hevc::NalUnitType::CRA);
+/// assert!(nal.unit_type().is_irap());
+/// assert_eq!((nal.layer_id(), nal.temporal_id()), (0, 0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Nal<'a>(&'a [u8]);
+
+impl<'a> Nal<'a> {
+    /// `None` if shorter than header or `forbidden_zero_bit` is set
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        if data.len() < 2 || data[0] & 0x80 != 0 {
+            return None;
+        }
+        Some(Self(data))
+    }
+
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.0
+    }
+
+    #[inline]
+    pub fn unit_type(&self) -> NalUnitType {
+        NalUnitType((self.0[0] >> 1) & 0x3f)
+    }
+
+    /// `nuh_layer_id`, view or scalable layer, 0 for base layer
+    #[inline]
+    pub fn layer_id(&self) -> u8 {
+        ((self.0[0] & 1) << 5) | (self.0[1] >> 3)
+    }
+
+    /// `TemporalId`
+    #[inline]
+    pub fn temporal_id(&self) -> u8 {
+        (self.0[1] & 7).saturating_sub(1)
+    }
+
+    /// Payload without header and emulation prevention bytes
+    #[inline]
+    pub fn rbsp(&self) -> std::borrow::Cow<'a, [u8]> {
+        nal::to_rbsp(&self.0[2..])
+    }
+}
+
+/// Checks if length-prefixed sample contains IRAP picture of the base layer
+pub fn is_sync_sample(sample: &[u8], nal_length_size: usize) -> Result<bool, Error> {
+    for data in nal::length_prefixed(sample, nal_length_size)? {
+        if Nal::new(data?).is_some_and(|n| n.unit_type().is_irap() && n.layer_id() == 0) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// General `profile_tier_level()`, sub-layer levels are skipped
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ProfileTierLevel {
+    pub profile_space: u8,
+
+    /// High tier if `true`
+    pub tier: bool,
+    pub profile_idc: u8,
+
+    /// `general_profile_compatibility_flag[0]` in MSB
+    pub compatibility_flags: u32,
+
+    /// 48 bits starting with `general_progressive_source_flag`
+    pub constraint_flags: u64,
+
+    /// 30 times level number, e.g. 153 for 5.1
+    pub level_idc: u8,
+}
+
+impl ProfileTierLevel {
+    pub const PROFILE_MAIN: u8 = 1;
+    pub const PROFILE_MAIN_10: u8 = 2;
+    pub const PROFILE_MAIN_STILL: u8 = 3;
+    pub const PROFILE_REXT: u8 = 4;
+    pub const PROFILE_MULTIVIEW_MAIN: u8 = 6;
+
+    fn read(
+        r: &mut bits::Reader,
+        profile_present: bool,
+        max_sub_layers_minus1: u8,
+    ) -> Result<Self, Error> {
+        let mut ptl = Self::default();
+        if profile_present {
+            ptl.profile_space = r.read_u8(2)?;
+            ptl.tier = r.read_bool()?;
+            ptl.profile_idc = r.read_u8(5)?;
+            ptl.compatibility_flags = r.read_u32(32)?;
+            ptl.constraint_flags = r.read_bits(48)?;
+        }
+        ptl.level_idc = r.read_u8(8)?;
+        let mut sub_layers = [(false, false); 7];
+        for sl in sub_layers.iter_mut().take(max_sub_layers_minus1 as usize) {
+            *sl = (r.read_bool()?, r.read_bool()?);
+        }
+        if max_sub_layers_minus1 > 0 {
+            r.skip(2 * (8 - max_sub_layers_minus1 as usize))?;
+        }
+        for &(profile, level) in sub_layers.iter().take(max_sub_layers_minus1 as usize) {
+            r.skip(if profile { 88 } else { 0 } + if level { 8 } else { 0 })?;
+        }
+        Ok(ptl)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push((self.profile_space << 6) | ((self.tier as u8) << 5) | self.profile_idc);
+        out.extend_from_slice(&self.compatibility_flags.to_be_bytes());
+        out.extend_from_slice(&self.constraint_flags.to_be_bytes()[2..]);
+        out.push(self.level_idc);
+    }
+
+    fn parse_bytes(data: &[u8]) -> Self {
+        let mut constraints = [0u8; 8];
+        constraints[2..].copy_from_slice(&data[5..11]);
+        Self {
+            profile_space: data[0] >> 6,
+            tier: data[0] & 0x20 != 0,
+            profile_idc: data[0] & 0x1f,
+            compatibility_flags: u32::from_be_bytes([data[1], data[2], data[3], data[4]]),
+            constraint_flags: u64::from_be_bytes(constraints),
+            level_idc: data[11],
+        }
+    }
+
+    #[inline]
+    pub fn is_progressive_source(&self) -> bool {
+        self.constraint_flags & (1 << 47) != 0
+    }
+
+    #[inline]
+    pub fn is_interlaced_source(&self) -> bool {
+        self.constraint_flags & (1 << 46) != 0
+    }
+
+    /// RFC 6381 codec string with `hvc1` or `hev1` sample entry, e.g. `hvc1.2.4.L153.90`
+    pub fn codec_string(&self, sample_entry: &str) -> String {
+        let space = match self.profile_space {
+            1 => "A",
+            2 => "B",
+            3 => "C",
+            _ => "",
+        };
+        let mut res = format!(
+            "{sample_entry}.{space}{}.{:x}.{}{}",
+            self.profile_idc,
+            self.compatibility_flags.reverse_bits(),
+            if self.tier { 'H' } else { 'L' },
+            self.level_idc
+        );
+        let bytes = self.constraint_flags.to_be_bytes();
+        let len = bytes[2..]
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |p| p + 1);
+        for b in &bytes[2..2 + len] {
+            res.push_str(&format!(".{b:X}"));
+        }
+        res
+    }
+}
+
+/// General tier and level limits (Table A.8), `level_idc` is 30 times level number.
+///
+/// ```
+/// use cidre::media::hevc;
+///
+/// let limits = hevc::LevelLimits::with_level_idc(120).unwrap();
+/// assert_eq!(limits.max_bit_rate(false), 12_000_000);
+/// assert_eq!(limits.max_bit_rate(true), 30_000_000);
+/// assert_eq!(hevc::LevelLimits::min_level_idc(1920, 1080, 60.0, 0, false), Some(123));
+/// assert_eq!(hevc::LevelLimits::min_level_idc(3840, 2160, 60.0, 0, false), Some(153));
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct LevelLimits {
+    pub level_idc: u8,
+
+    /// Luma samples per picture
+    pub max_luma_ps: u32,
+
+    /// Luma samples per second
+    pub max_luma_sr: u64,
+
+    /// Main tier bit rate of Main and Main 10 profiles in 1000 bits/s
+    pub max_br: u32,
+
+    /// High tier bit rate in 1000 bits/s, the same as Main tier below level 4
+    pub max_br_high: u32,
+}
+
+impl LevelLimits {
+    #[rustfmt::skip]
+    pub const ALL: [Self; 13] = [
+        Self::new(30, 36864, 552960, 128, 128),
+        Self::new(60, 122880, 3686400, 1500, 1500),
+        Self::new(63, 245760, 7372800, 3000, 3000),
+        Self::new(90, 552960, 16588800, 6000, 6000),
+        Self::new(93, 983040, 33177600, 10000, 10000),
+        Self::new(120, 2228224, 66846720, 12000, 30000),
+        Self::new(123, 2228224, 133693440, 20000, 50000),
+        Self::new(150, 8912896, 267386880, 25000, 100000),
+        Self::new(153, 8912896, 534773760, 40000, 160000),
+        Self::new(156, 8912896, 1069547520, 60000, 240000),
+        Self::new(180, 35651584, 1069547520, 60000, 240000),
+        Self::new(183, 35651584, 2139095040, 120000, 480000),
+        Self::new(186, 35651584, 4278190080, 240000, 800000),
+    ];
+
+    const fn new(
+        level_idc: u8,
+        max_luma_ps: u32,
+        max_luma_sr: u64,
+        max_br: u32,
+        max_br_high: u32,
+    ) -> Self {
+        Self {
+            level_idc,
+            max_luma_ps,
+            max_luma_sr,
+            max_br,
+            max_br_high,
+        }
+    }
+
+    #[inline]
+    pub fn with_level_idc(level_idc: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.level_idc == level_idc)
+    }
+
+    /// Max VCL bit rate in bits/s of Main or High tier
+    #[inline]
+    pub fn max_bit_rate(&self, high_tier: bool) -> u64 {
+        let br = if high_tier {
+            self.max_br_high
+        } else {
+            self.max_br
+        };
+        br as u64 * 1000
+    }
+
+    /// Checks picture size, sample rate and bit rate, 0 means unknown
+    pub fn fits(
+        &self,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bit_rate: u64,
+        high_tier: bool,
+    ) -> bool {
+        let luma_ps = width as u64 * height as u64;
+        // picture dimensions are limited to sqrt(8 * MaxLumaPs)
+        let max_dim = (self.max_luma_ps as f64 * 8.0).sqrt() as u32;
+        luma_ps <= self.max_luma_ps as u64
+            && width <= max_dim
+            && height <= max_dim
+            && luma_ps as f64 * frame_rate <= self.max_luma_sr as f64
+            && bit_rate <= self.max_bit_rate(high_tier)
+    }
+
+    /// The lowest level fitting stream
+    pub fn min_level_idc(
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bit_rate: u64,
+        high_tier: bool,
+    ) -> Option<u8> {
+        Self::ALL
+            .into_iter()
+            .find(|l| l.fits(width, height, frame_rate, bit_rate, high_tier))
+            .map(|l| l.level_idc)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Cpb {
+    /// Bits per second
+    pub bit_rate: u64,
+
+    /// Bits
+    pub size: u64,
+    pub cbr: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SubPicHrd {
+    pub tick_divisor: u16,
+    pub du_cpb_removal_delay_increment_length: u8,
+    pub sub_pic_cpb_params_in_pic_timing_sei: bool,
+    pub dpb_output_delay_du_length: u8,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SubLayerHrd {
+    pub fixed_pic_rate_within_cvs: bool,
+    pub elemental_duration_in_tc: Option<u32>,
+    pub low_delay: bool,
+    pub nal_cpbs: Vec<Cpb>,
+    pub vcl_cpbs: Vec<Cpb>,
+}
+
+/// `hrd_parameters()`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Hrd {
+    pub nal_hrd: bool,
+    pub vcl_hrd: bool,
+    pub sub_pic: Option<SubPicHrd>,
+    bit_rate_scale: u8,
+    cpb_size_scale: u8,
+    pub initial_cpb_removal_delay_length: u8,
+    pub au_cpb_removal_delay_length: u8,
+    pub dpb_output_delay_length: u8,
+    pub sub_layers: Vec<SubLayerHrd>,
+}
+
+impl Hrd {
+    /// Reads `hrd_parameters()`, common info is taken from `common` when it is not present
+    fn read(
+        r: &mut bits::Reader,
+        common: Option<&Hrd>,
+        max_sub_layers_minus1: u8,
+    ) -> Result<Self, Error> {
+        let mut hrd = match common {
+            Some(c) => Self {
+                sub_layers: Vec::new(),
+                ..c.clone()
+            },
+            None => {
+                let mut hrd = Self {
+                    nal_hrd: r.read_bool()?,
+                    vcl_hrd: r.read_bool()?,
+                    sub_pic: None,
+                    bit_rate_scale: 0,
+                    cpb_size_scale: 0,
+                    initial_cpb_removal_delay_length: 24,
+                    au_cpb_removal_delay_length: 24,
+                    dpb_output_delay_length: 24,
+                    sub_layers: Vec::new(),
+                };
+                if hrd.nal_hrd || hrd.vcl_hrd {
+                    if r.read_bool()? {
+                        hrd.sub_pic = Some(SubPicHrd {
+                            tick_divisor: r.read_u16(8)? + 2,
+                            du_cpb_removal_delay_increment_length: r.read_u8(5)? + 1,
+                            sub_pic_cpb_params_in_pic_timing_sei: r.read_bool()?,
+                            dpb_output_delay_du_length: r.read_u8(5)? + 1,
+                        });
+                    }
+                    hrd.bit_rate_scale = r.read_u8(4)?;
+                    hrd.cpb_size_scale = r.read_u8(4)?;
+                    if hrd.sub_pic.is_some() {
+                        r.skip(4)?;
+                    }
+                    hrd.initial_cpb_removal_delay_length = r.read_u8(5)? + 1;
+                    hrd.au_cpb_removal_delay_length = r.read_u8(5)? + 1;
+                    hrd.dpb_output_delay_length = r.read_u8(5)? + 1;
+                }
+                hrd
+            }
+        };
+        for _ in 0..=max_sub_layers_minus1 {
+            let fixed_pic_rate_general = r.read_bool()?;
+            let fixed_pic_rate_within_cvs = fixed_pic_rate_general || r.read_bool()?;
+            let mut low_delay = false;
+            let elemental_duration_in_tc = if fixed_pic_rate_within_cvs {
+                Some(r.read_ue()? + 1)
+            } else {
+                low_delay = r.read_bool()?;
+                None
+            };
+            let cpb_cnt = if low_delay {
+                1
+            } else {
+                r.read_ue()? as usize + 1
+            };
+            if cpb_cnt > 32 {
+                return Err(Error::InvalidValue("cpb_cnt_minus1"));
+            }
+            let nal_cpbs = if hrd.nal_hrd {
+                hrd.read_cpbs(r, cpb_cnt)?
+            } else {
+                Vec::new()
+            };
+            let vcl_cpbs = if hrd.vcl_hrd {
+                hrd.read_cpbs(r, cpb_cnt)?
+            } else {
+                Vec::new()
+            };
+            hrd.sub_layers.push(SubLayerHrd {
+                fixed_pic_rate_within_cvs,
+                elemental_duration_in_tc,
+                low_delay,
+                nal_cpbs,
+                vcl_cpbs,
+            });
+        }
+        Ok(hrd)
+    }
+
+    /// `sub_layer_hrd_parameters()`
+    fn read_cpbs(&self, r: &mut bits::Reader, count: usize) -> Result<Vec<Cpb>, Error> {
+        let mut cpbs = Vec::with_capacity(count);
+        for _ in 0..count {
+            let bit_rate = r.read_ue()? as u64 + 1;
+            let size = r.read_ue()? as u64 + 1;
+            if self.sub_pic.is_some() {
+                r.read_ue()?;
+                r.read_ue()?;
+            }
+            cpbs.push(Cpb {
+                bit_rate: bit_rate << (6 + self.bit_rate_scale),
+                size: size << (4 + self.cpb_size_scale),
+                cbr: r.read_bool()?,
+            });
+        }
+        Ok(cpbs)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Timing {
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+    pub num_ticks_poc_diff_one: Option<u32>,
+}
+
+impl Timing {
+    fn read(r: &mut bits::Reader) -> Result<Self, Error> {
+        let num_units_in_tick = r.read_u32(32)?;
+        let time_scale = r.read_u32(32)?;
+        let num_ticks_poc_diff_one = if r.read_bool()? {
+            Some(r.read_ue()? + 1)
+        } else {
+            None
+        };
+        Ok(Self {
+            num_units_in_tick,
+            time_scale,
+            num_ticks_poc_diff_one,
+        })
+    }
+
+    /// Pictures per second as reduced fraction
+    #[inline]
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        vui::frame_rate(self.time_scale, self.num_units_in_tick as u64)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BitstreamRestriction {
+    pub tiles_fixed_structure: bool,
+    pub motion_vectors_over_pic_boundaries: bool,
+    pub restricted_ref_pic_lists: bool,
+    pub min_spatial_segmentation_idc: u16,
+    pub max_bytes_per_pic_denom: u32,
+    pub max_bits_per_min_cu_denom: u32,
+    pub log2_max_mv_length_horizontal: u32,
+    pub log2_max_mv_length_vertical: u32,
+}
+
+/// Conformance or display window offsets in chroma sample units
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Window {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl Window {
+    fn read(r: &mut bits::Reader) -> Result<Self, Error> {
+        Ok(Self {
+            left: r.read_ue()?,
+            right: r.read_ue()?,
+            top: r.read_ue()?,
+            bottom: r.read_ue()?,
+        })
+    }
+}
+
+/// `vui_parameters()`
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Vui {
+    pub sample_aspect_ratio: Option<(u16, u16)>,
+    pub overscan_appropriate: Option<bool>,
+    pub video_signal: Option<vui::VideoSignal>,
+    pub chroma_location: Option<vui::ChromaLocation>,
+    pub neutral_chroma_indication: bool,
+    pub field_seq: bool,
+    pub frame_field_info_present: bool,
+    pub default_display_window: Option<Window>,
+    pub timing: Option<Timing>,
+    pub hrd: Option<Hrd>,
+    pub bitstream_restriction: Option<BitstreamRestriction>,
+}
+
+impl Vui {
+    fn read(r: &mut bits::Reader, max_sub_layers_minus1: u8) -> Result<Self, Error> {
+        let mut vui = Self::default();
+        if r.read_bool()? {
+            vui.sample_aspect_ratio = Some(vui::read_aspect_ratio(r)?);
+        }
+        if r.read_bool()? {
+            vui.overscan_appropriate = Some(r.read_bool()?);
+        }
+        if r.read_bool()? {
+            vui.video_signal = Some(vui::VideoSignal::read(r)?);
+        }
+        if r.read_bool()? {
+            vui.chroma_location = Some(vui::ChromaLocation {
+                top_field: r.read_ue()?,
+                bottom_field: r.read_ue()?,
+            });
+        }
+        vui.neutral_chroma_indication = r.read_bool()?;
+        vui.field_seq = r.read_bool()?;
+        vui.frame_field_info_present = r.read_bool()?;
+        if r.read_bool()? {
+            vui.default_display_window = Some(Window::read(r)?);
+        }
+        if r.read_bool()? {
+            vui.timing = Some(Timing::read(r)?);
+            if r.read_bool()? {
+                vui.hrd = Some(Hrd::read(r, None, max_sub_layers_minus1)?);
+            }
+        }
+        if r.read_bool()? {
+            let tiles_fixed_structure = r.read_bool()?;
+            let motion_vectors_over_pic_boundaries = r.read_bool()?;
+            let restricted_ref_pic_lists = r.read_bool()?;
+            let min_spatial_segmentation_idc = r.read_ue()?;
+            if min_spatial_segmentation_idc > 4095 {
+                return Err(Error::InvalidValue("min_spatial_segmentation_idc"));
+            }
+            vui.bitstream_restriction = Some(BitstreamRestriction {
+                tiles_fixed_structure,
+                motion_vectors_over_pic_boundaries,
+                restricted_ref_pic_lists,
+                min_spatial_segmentation_idc: min_spatial_segmentation_idc as u16,
+                max_bytes_per_pic_denom: r.read_ue()?,
+                max_bits_per_min_cu_denom: r.read_ue()?,
+                log2_max_mv_length_horizontal: r.read_ue()?,
+                log2_max_mv_length_vertical: r.read_ue()?,
+            });
+        }
+        Ok(vui)
+    }
+}
+
+/// Layer of multi-layer stream described by VPS extension
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Layer {
+    /// `nuh_layer_id` of NAL units of the layer
+    pub id: u8,
+    pub view_order_idx: u8,
+    pub view_id: Option<u16>,
+
+    /// Layer ids this layer directly references
+    pub direct_dependencies: Vec<u8>,
+}
+
+/// Beginning of `vps_extension()` up to layer dependencies
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VpsExt {
+    /// `scalability_mask_flag` with index 0 in MSB
+    pub scalability_mask: u16,
+    pub layers: Vec<Layer>,
+}
+
+impl VpsExt {
+    pub const SCALABILITY_DEPTH: u16 = 0x8000;
+    pub const SCALABILITY_MULTIVIEW: u16 = 0x4000;
+    pub const SCALABILITY_SPATIAL: u16 = 0x2000;
+    pub const SCALABILITY_AUXILIARY: u16 = 0x1000;
+
+    fn read(
+        r: &mut bits::Reader,
+        max_layers_minus1: u8,
+        max_sub_layers_minus1: u8,
+        base_layer_internal: bool,
+    ) -> Result<Self, Error> {
+        if max_layers_minus1 > 0 && base_layer_internal {
+            ProfileTierLevel::read(r, false, max_sub_layers_minus1)?;
+        }
+        let splitting = r.read_bool()?;
+        let scalability_mask = r.read_u16(16)?;
+        let num_types = scalability_mask.count_ones() as usize;
+        let mut dim_lens = Vec::with_capacity(num_types);
+        for _ in 0..num_types.saturating_sub(splitting as usize) {
+            dim_lens.push(r.read_u8(3)? + 1);
+        }
+        if splitting && num_types > 0 {
+            let used: u8 = dim_lens.iter().sum();
+            dim_lens.push(
+                6u8.checked_sub(used)
+                    .ok_or(Error::InvalidValue("dimension_id_len"))?,
+            );
+        }
+        let nuh_layer_id_present = r.read_bool()?;
+
+        // index of multiview dimension among present scalability types
+        let multiview_dim = (scalability_mask & Self::SCALABILITY_MULTIVIEW != 0)
+            .then_some((scalability_mask >> 15) as usize);
+
+        let mut layers = vec![Layer {
+            id: 0,
+            view_order_idx: 0,
+            view_id: None,
+            direct_dependencies: Vec::new(),
+        }];
+        for i in 1..=max_layers_minus1 {
+            let id = if nuh_layer_id_present {
+                r.read_u8(6)?
+            } else {
+                i
+            };
+            let mut dims = Vec::with_capacity(num_types);
+            let mut offset = 0;
+            for &len in &dim_lens {
+                if splitting {
+                    dims.push((id >> offset) & ((1 << len) - 1));
+                    offset += len;
+                } else {
+                    dims.push(r.read_u8(len as u32)?);
+                }
+            }
+            layers.push(Layer {
+                id,
+                view_order_idx: multiview_dim.map_or(0, |d| dims[d]),
+                view_id: None,
+                direct_dependencies: Vec::new(),
+            });
+        }
+
+        let mut views: Vec<u8> = Vec::new();
+        for l in &layers {
+            if !views.contains(&l.view_order_idx) {
+                views.push(l.view_order_idx);
+            }
+        }
+        let view_id_len = r.read_u8(4)?;
+        if view_id_len > 0 {
+            let mut view_ids = Vec::with_capacity(views.len());
+            for _ in 0..views.len() {
+                view_ids.push(r.read_u16(view_id_len as u32)?);
+            }
+            for l in &mut layers {
+                let idx = views.iter().position(|&v| v == l.view_order_idx).unwrap();
+                l.view_id = Some(view_ids[idx]);
+            }
+        }
+        for i in 1..layers.len() {
+            for j in 0..i {
+                if r.read_bool()? {
+                    let id = layers[j].id;
+                    layers[i].direct_dependencies.push(id);
+                }
+            }
+        }
+        Ok(Self {
+            scalability_mask,
+            layers,
+        })
+    }
+
+    #[inline]
+    pub fn is_multiview(&self) -> bool {
+        self.scalability_mask & Self::SCALABILITY_MULTIVIEW != 0
+    }
+}
+
+fn read_nal<'a>(data: &'a [u8], expected: NalUnitType) -> Result<Nal<'a>, Error> {
+    let nal = Nal::new(data).ok_or(Error::Truncated)?;
+    if nal.unit_type() != expected {
+        return Err(Error::UnexpectedNalType(nal.unit_type()));
+    }
+    Ok(nal)
+}
+
+/// Video parameter set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Vps {
+    pub id: u8,
+    pub base_layer_internal: bool,
+    pub base_layer_available: bool,
+    pub max_layers: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+    pub ptl: ProfileTierLevel,
+    pub max_dec_pic_buffering: u32,
+    pub max_num_reorder_pics: u32,
+    pub max_layer_id: u8,
+    pub num_layer_sets: u32,
+    pub timing: Option<Timing>,
+    pub hrd: Vec<Hrd>,
+    pub ext: Option<VpsExt>,
+}
+
+impl Vps {
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let rbsp = read_nal(nal, NalUnitType::VPS)?.rbsp();
+        let r = &mut bits::Reader::new(&rbsp);
+        let id = r.read_u8(4)?;
+        let base_layer_internal = r.read_bool()?;
+        let base_layer_available = r.read_bool()?;
+        let max_layers_minus1 = r.read_u8(6)?;
+        let max_sub_layers_minus1 = r.read_u8(3)?;
+        if max_sub_layers_minus1 > 6 {
+            return Err(Error::InvalidValue("vps_max_sub_layers_minus1"));
+        }
+        let temporal_id_nesting = r.read_bool()?;
+        r.skip(16)?;
+        let ptl = ProfileTierLevel::read(r, true, max_sub_layers_minus1)?;
+        let (max_dec_pic_buffering, max_num_reorder_pics) =
+            read_sub_layer_ordering(r, max_sub_layers_minus1)?;
+        let max_layer_id = r.read_u8(6)?;
+        let num_layer_sets = r.read_ue()? + 1;
+        if num_layer_sets > 1024 {
+            return Err(Error::InvalidValue("vps_num_layer_sets_minus1"));
+        }
+        r.skip((num_layer_sets as usize - 1) * (max_layer_id as usize + 1))?;
+        let mut timing = None;
+        let mut hrd: Vec<Hrd> = Vec::new();
+        if r.read_bool()? {
+            timing = Some(Timing::read(r)?);
+            let count = r.read_ue()?;
+            if count > num_layer_sets {
+                return Err(Error::InvalidValue("vps_num_hrd_parameters"));
+            }
+            for i in 0..count {
+                r.read_ue()?;
+                let common_present = i == 0 || r.read_bool()?;
+                let common = if common_present { None } else { hrd.last() };
+                let h = Hrd::read(r, common, max_sub_layers_minus1)?;
+                hrd.push(h);
+            }
+        }
+        let ext = if r.read_bool()? {
+            while !r.is_byte_aligned() {
+                r.read_bool()?;
+            }
+            Some(VpsExt::read(
+                r,
+                max_layers_minus1,
+                max_sub_layers_minus1,
+                base_layer_internal,
+            )?)
+        } else {
+            None
+        };
+        Ok(Self {
+            id,
+            base_layer_internal,
+            base_layer_available,
+            max_layers: max_layers_minus1 + 1,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            max_dec_pic_buffering,
+            max_num_reorder_pics,
+            max_layer_id,
+            num_layer_sets,
+            timing,
+            hrd,
+            ext,
+        })
+    }
+
+    /// Two or more views, as in MV-HEVC spatial video
+    #[inline]
+    pub fn is_multiview(&self) -> bool {
+        self.ext.as_ref().is_some_and(|e| e.is_multiview())
+    }
+}
+
+/// Returns `max_dec_pic_buffering` and `max_num_reorder_pics` of the highest sub-layer
+fn read_sub_layer_ordering(
+    r: &mut bits::Reader,
+    max_sub_layers_minus1: u8,
+) -> Result<(u32, u32), Error> {
+    let all = r.read_bool()?;
+    let mut res = (0, 0);
+    for _ in (if all { 0 } else { max_sub_layers_minus1 })..=max_sub_layers_minus1 {
+        res = (r.read_ue()? + 1, r.read_ue()?);
+        r.read_ue()?;
+    }
+    Ok(res)
+}
+
+fn skip_scaling_list_data(r: &mut bits::Reader) -> Result<(), Error> {
+    for size_id in 0..4 {
+        let step = if size_id == 3 { 3 } else { 1 };
+        for _ in (0..6).step_by(step) {
+            if !r.read_bool()? {
+                r.read_ue()?;
+            } else {
+                let coef_num = 64.min(1 << (4 + (size_id << 1)));
+                if size_id > 1 {
+                    r.read_se()?;
+                }
+                for _ in 0..coef_num {
+                    r.read_se()?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Delta POC coded as `minus1` value of up to 2^15 - 1
+fn read_delta_poc(r: &mut bits::Reader, name: &'static str) -> Result<i32, Error> {
+    let minus1 = r.read_ue()?;
+    if minus1 > 0x7fff {
+        return Err(Error::InvalidValue(name));
+    }
+    Ok(minus1 as i32 + 1)
+}
+
+/// Delta POCs of `st_ref_pic_set()`
+#[derive(Debug, Clone, Default)]
+struct ShortTermRps {
+    negative: Vec<i32>,
+    positive: Vec<i32>,
+}
+
+impl ShortTermRps {
+    fn read(r: &mut bits::Reader, idx: usize, sets: &[ShortTermRps]) -> Result<Self, Error> {
+        if idx != 0 && r.read_bool()? {
+            let sign = r.read_bool()?;
+            let abs = read_delta_poc(r, "abs_delta_rps_minus1")?;
+            let delta_rps = if sign { -abs } else { abs };
+            let rf = &sets[idx - 1];
+            let count = rf.negative.len() + rf.positive.len();
+            let mut use_delta = Vec::with_capacity(count + 1);
+            for _ in 0..=count {
+                let used = r.read_bool()?;
+                use_delta.push(used || r.read_bool()?);
+            }
+            let n = rf.negative.len();
+            let mut rps = Self::default();
+            for (j, d) in rf.positive.iter().enumerate().rev() {
+                let poc = d + delta_rps;
+                if poc < 0 && use_delta[n + j] {
+                    rps.negative.push(poc);
+                }
+            }
+            if delta_rps < 0 && use_delta[count] {
+                rps.negative.push(delta_rps);
+            }
+            for (j, d) in rf.negative.iter().enumerate() {
+                let poc = d + delta_rps;
+                if poc < 0 && use_delta[j] {
+                    rps.negative.push(poc);
+                }
+            }
+            for (j, d) in rf.negative.iter().enumerate().rev() {
+                let poc = d + delta_rps;
+                if poc > 0 && use_delta[j] {
+                    rps.positive.push(poc);
+                }
+            }
+            if delta_rps > 0 && use_delta[count] {
+                rps.positive.push(delta_rps);
+            }
+            for (j, d) in rf.positive.iter().enumerate() {
+                let poc = d + delta_rps;
+                if poc > 0 && use_delta[n + j] {
+                    rps.positive.push(poc);
+                }
+            }
+            return Ok(rps);
+        }
+        let num_negative = r.read_ue()?;
+        let num_positive = r.read_ue()?;
+        if num_negative > 16 || num_positive > 16 {
+            return Err(Error::InvalidValue("st_ref_pic_set"));
+        }
+        let mut rps = Self::default();
+        let mut poc = 0;
+        for _ in 0..num_negative {
+            poc -= read_delta_poc(r, "delta_poc_s0_minus1")?;
+            r.read_bool()?;
+            rps.negative.push(poc);
+        }
+        poc = 0;
+        for _ in 0..num_positive {
+            poc += read_delta_poc(r, "delta_poc_s1_minus1")?;
+            r.read_bool()?;
+            rps.positive.push(poc);
+        }
+        Ok(rps)
+    }
+}
+
+/// Picture format part of SPS
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RepFormat {
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub pic_width: u32,
+    pub pic_height: u32,
+    pub conformance_window: Option<Window>,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+}
+
+impl RepFormat {
+    /// Width or height in luma samples at level 6.2, `sqrt(MaxLumaPs * 8)`
+    pub const MAX_SIZE: u32 = 16888;
+
+    /// `SubWidthC` and `SubHeightC`
+    #[inline]
+    pub fn chroma_subsampling(&self) -> (u32, u32) {
+        match (self.chroma_format_idc, self.separate_colour_plane) {
+            (1, false) => (2, 2),
+            (2, false) => (2, 1),
+            _ => (1, 1),
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        let w = self.conformance_window.unwrap_or_default();
+        let (x, _) = self.chroma_subsampling();
+        self.pic_width.saturating_sub(x * (w.left + w.right))
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        let w = self.conformance_window.unwrap_or_default();
+        let (_, y) = self.chroma_subsampling();
+        self.pic_height.saturating_sub(y * (w.top + w.bottom))
+    }
+}
+
+/// Sequence parameter set.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Sps {
+    pub vps_id: u8,
+
+    /// `nuh_layer_id` of NAL unit
+    pub layer_id: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+
+    /// `None` for multi-layer extension SPS
+    pub ptl: Option<ProfileTierLevel>,
+    pub id: u32,
+
+    /// `sps_rep_format_idx` of multi-layer extension SPS
+    pub rep_format_idx: Option<u8>,
+
+    /// `None` for multi-layer extension SPS parsed without base SPS
+    pub rep_format: Option<RepFormat>,
+    pub log2_max_pic_order_cnt_lsb: u8,
+    pub max_dec_pic_buffering: u32,
+    pub max_num_reorder_pics: u32,
+    pub log2_min_luma_coding_block_size: u8,
+    pub log2_diff_max_min_luma_coding_block_size: u8,
+    pub scaling_list_enabled: bool,
+    pub amp_enabled: bool,
+    pub sample_adaptive_offset_enabled: bool,
+    pub pcm_enabled: bool,
+    pub num_short_term_ref_pic_sets: u32,
+    pub long_term_ref_pics_present: bool,
+    pub num_long_term_ref_pics: u32,
+    pub temporal_mvp_enabled: bool,
+    pub strong_intra_smoothing_enabled: bool,
+    pub vui: Option<Vui>,
+}
+
+impl Sps {
+    /// Parses SPS NAL unit with header.
+    ///
+    /// Multi-layer extension SPS of non-base layers doesn't carry picture format,
+    /// use [`Sps::parse_with_base`] for them.
+    #[inline]
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        Self::parse_inner(nal, None)
+    }
+
+    /// Parses SPS taking format and sub-layer info omitted by multi-layer extension SPS
+    /// from `base`. Views of MV-HEVC spatial video share the picture format.
+    #[inline]
+    pub fn parse_with_base(nal: &[u8], base: &Sps) -> Result<Self, Error> {
+        Self::parse_inner(nal, Some(base))
+    }
+
+    fn parse_inner(data: &[u8], base: Option<&Sps>) -> Result<Self, Error> {
+        let nal = read_nal(data, NalUnitType::SPS)?;
+        let layer_id = nal.layer_id();
+        let rbsp = nal.rbsp();
+        let r = &mut bits::Reader::new(&rbsp);
+        let vps_id = r.read_u8(4)?;
+        let max_sub_layers_minus1 = r.read_u8(3)?;
+        let multi_layer_ext = layer_id != 0 && max_sub_layers_minus1 == 7;
+        let (max_sub_layers_minus1, temporal_id_nesting, ptl) = if multi_layer_ext {
+            let max = base.map_or(0, |b| b.max_sub_layers - 1);
+            (max, base.is_some_and(|b| b.temporal_id_nesting), None)
+        } else {
+            if max_sub_layers_minus1 > 6 {
+                return Err(Error::InvalidValue("sps_max_sub_layers_minus1"));
+            }
+            let nesting = r.read_bool()?;
+            let ptl = ProfileTierLevel::read(r, true, max_sub_layers_minus1)?;
+            (max_sub_layers_minus1, nesting, Some(ptl))
+        };
+        let id = r.read_ue()?;
+        if id > 15 {
+            return Err(Error::InvalidValue("sps_seq_parameter_set_id"));
+        }
+        let mut rep_format_idx = None;
+        let rep_format = if multi_layer_ext {
+            if r.read_bool()? {
+                rep_format_idx = Some(r.read_u8(8)?);
+            }
+            base.and_then(|b| b.rep_format)
+        } else {
+            let chroma_format_idc = r.read_ue()?;
+            if chroma_format_idc > 3 {
+                return Err(Error::InvalidValue("chroma_format_idc"));
+            }
+            let separate_colour_plane = chroma_format_idc == 3 && r.read_bool()?;
+            let pic_width = r.read_ue()?;
+            let pic_height = r.read_ue()?;
+            if pic_width > RepFormat::MAX_SIZE || pic_height > RepFormat::MAX_SIZE {
+                return Err(Error::InvalidValue("pic_width_in_luma_samples"));
+            }
+            let conformance_window = if r.read_bool()? {
+                Some(Window::read(r)?)
+            } else {
+                None
+            };
+            let luma = r.read_ue()?;
+            let chroma = r.read_ue()?;
+            if luma > 8 || chroma > 8 {
+                return Err(Error::InvalidValue("bit_depth_minus8"));
+            }
+            let format = RepFormat {
+                chroma_format_idc: chroma_format_idc as u8,
+                separate_colour_plane,
+                pic_width,
+                pic_height,
+                conformance_window,
+                bit_depth_luma: luma as u8 + 8,
+                bit_depth_chroma: chroma as u8 + 8,
+            };
+            if let Some(w) = conformance_window {
+                // window must leave at least one sample in each direction
+                let (x, y) = format.chroma_subsampling();
+                if (w.left as u64 + w.right as u64) * x as u64 >= pic_width as u64
+                    || (w.top as u64 + w.bottom as u64) * y as u64 >= pic_height as u64
+                {
+                    return Err(Error::InvalidValue("conf_win_offset"));
+                }
+            }
+            Some(format)
+        };
+        let log2_max_pic_order_cnt_lsb_minus4 = r.read_ue()?;
+        if log2_max_pic_order_cnt_lsb_minus4 > 12 {
+            return Err(Error::InvalidValue("log2_max_pic_order_cnt_lsb_minus4"));
+        }
+        let log2_max_pic_order_cnt_lsb = log2_max_pic_order_cnt_lsb_minus4 + 4;
+        let (max_dec_pic_buffering, max_num_reorder_pics) = if multi_layer_ext {
+            base.map_or((0, 0), |b| {
+                (b.max_dec_pic_buffering, b.max_num_reorder_pics)
+            })
+        } else {
+            read_sub_layer_ordering(r, max_sub_layers_minus1)?
+        };
+        let log2_min_luma_coding_block_size_minus3 = r.read_ue()?;
+        if log2_min_luma_coding_block_size_minus3 > 3 {
+            return Err(Error::InvalidValue(
+                "log2_min_luma_coding_block_size_minus3",
+            ));
+        }
+        let log2_min_luma_coding_block_size = log2_min_luma_coding_block_size_minus3 + 3;
+        let log2_diff_max_min_luma_coding_block_size = r.read_ue()?;
+        if log2_diff_max_min_luma_coding_block_size > 6 - log2_min_luma_coding_block_size {
+            return Err(Error::InvalidValue(
+                "log2_diff_max_min_luma_coding_block_size",
+            ));
+        }
+        for _ in 0..4 {
+            // transform block sizes and hierarchy depths
+            r.read_ue()?;
+        }
+        let scaling_list_enabled = r.read_bool()?;
+        if scaling_list_enabled {
+            let infer = multi_layer_ext && r.read_bool()?;
+            if infer {
+                r.skip(6)?;
+            } else if r.read_bool()? {
+                skip_scaling_list_data(r)?;
+            }
+        }
+        let amp_enabled = r.read_bool()?;
+        let sample_adaptive_offset_enabled = r.read_bool()?;
+        let pcm_enabled = r.read_bool()?;
+        if pcm_enabled {
+            r.skip(8)?;
+            r.read_ue()?;
+            r.read_ue()?;
+            r.skip(1)?;
+        }
+        let num_short_term_ref_pic_sets = r.read_ue()?;
+        if num_short_term_ref_pic_sets > 64 {
+            return Err(Error::InvalidValue("num_short_term_ref_pic_sets"));
+        }
+        let mut sets = Vec::with_capacity(num_short_term_ref_pic_sets as usize);
+        for i in 0..num_short_term_ref_pic_sets as usize {
+            let rps = ShortTermRps::read(r, i, &sets)?;
+            sets.push(rps);
+        }
+        let long_term_ref_pics_present = r.read_bool()?;
+        let mut num_long_term_ref_pics = 0;
+        if long_term_ref_pics_present {
+            num_long_term_ref_pics = r.read_ue()?;
+            if num_long_term_ref_pics > 32 {
+                return Err(Error::InvalidValue("num_long_term_ref_pics_sps"));
+            }
+            r.skip(num_long_term_ref_pics as usize * (log2_max_pic_order_cnt_lsb as usize + 1))?;
+        }
+        let temporal_mvp_enabled = r.read_bool()?;
+        let strong_intra_smoothing_enabled = r.read_bool()?;
+        let vui = if r.read_bool()? {
+            Some(Vui::read(r, max_sub_layers_minus1)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            vps_id,
+            layer_id,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            id,
+            rep_format_idx,
+            rep_format,
+            log2_max_pic_order_cnt_lsb: log2_max_pic_order_cnt_lsb as u8,
+            max_dec_pic_buffering,
+            max_num_reorder_pics,
+            log2_min_luma_coding_block_size: log2_min_luma_coding_block_size as u8,
+            log2_diff_max_min_luma_coding_block_size: log2_diff_max_min_luma_coding_block_size
+                as u8,
+            scaling_list_enabled,
+            amp_enabled,
+            sample_adaptive_offset_enabled,
+            pcm_enabled,
+            num_short_term_ref_pic_sets,
+            long_term_ref_pics_present,
+            num_long_term_ref_pics,
+            temporal_mvp_enabled,
+            strong_intra_smoothing_enabled,
+            vui,
+        })
+    }
+
+    /// Display width in pixels, 0 if picture format is unknown
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.rep_format.map_or(0, |f| f.width())
+    }
+
+    /// Display height in pixels, 0 if picture format is unknown
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.rep_format.map_or(0, |f| f.height())
+    }
+
+    #[inline]
+    pub fn chroma_format_idc(&self) -> Option<u8> {
+        Some(self.rep_format?.chroma_format_idc)
+    }
+
+    #[inline]
+    pub fn bit_depth_luma(&self) -> Option<u8> {
+        Some(self.rep_format?.bit_depth_luma)
+    }
+
+    #[inline]
+    pub fn bit_depth_chroma(&self) -> Option<u8> {
+        Some(self.rep_format?.bit_depth_chroma)
+    }
+
+    #[inline]
+    pub fn frame_rate(&self) -> Option<(u32, u32)> {
+        self.vui.as_ref()?.timing?.frame_rate()
+    }
+
+    #[inline]
+    pub fn video_signal(&self) -> Option<&vui::VideoSignal> {
+        self.vui.as_ref()?.video_signal.as_ref()
+    }
+
+    #[inline]
+    pub fn color(&self) -> Option<vui::ColorDescription> {
+        self.video_signal()?.color
+    }
+
+    /// PQ or HLG transfer signalled in VUI
+    #[inline]
+    pub fn is_hdr(&self) -> bool {
+        self.color().is_some_and(|c| c.is_hdr())
+    }
+
+    /// RFC 6381 codec string of `hvc1` sample entry
+    #[inline]
+    pub fn codec_string(&self) -> Option<String> {
+        Some(self.ptl?.codec_string("hvc1"))
+    }
+}
+
+/// `pps_tiles` of PPS
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Tiles {
+    pub columns: u32,
+    pub rows: u32,
+    pub uniform_spacing: bool,
+    pub loop_filter_across_tiles: bool,
+}
+
+/// Picture parameter set up to deblocking control.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Pps {
+    pub id: u32,
+    pub sps_id: u32,
+    pub dependent_slice_segments_enabled: bool,
+    pub output_flag_present: bool,
+    pub num_extra_slice_header_bits: u8,
+    pub sign_data_hiding_enabled: bool,
+    pub cabac_init_present: bool,
+    pub num_ref_idx_l0_default_active: u32,
+    pub num_ref_idx_l1_default_active: u32,
+    pub init_qp: i32,
+    pub constrained_intra_pred: bool,
+    pub transform_skip_enabled: bool,
+    pub diff_cu_qp_delta_depth: Option<u32>,
+    pub cb_qp_offset: i32,
+    pub cr_qp_offset: i32,
+    pub slice_chroma_qp_offsets_present: bool,
+    pub weighted_pred: bool,
+    pub weighted_bipred: bool,
+    pub transquant_bypass_enabled: bool,
+    pub tiles: Option<Tiles>,
+    pub entropy_coding_sync_enabled: bool,
+    pub loop_filter_across_slices_enabled: bool,
+    pub deblocking_filter_control_present: bool,
+}
+
+impl Pps {
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let rbsp = read_nal(nal, NalUnitType::PPS)?.rbsp();
+        let r = &mut bits::Reader::new(&rbsp);
+        let id = r.read_ue()?;
+        if id > 63 {
+            return Err(Error::InvalidValue("pps_pic_parameter_set_id"));
+        }
+        let sps_id = r.read_ue()?;
+        if sps_id > 15 {
+            return Err(Error::InvalidValue("pps_seq_parameter_set_id"));
+        }
+        let dependent_slice_segments_enabled = r.read_bool()?;
+        let output_flag_present = r.read_bool()?;
+        let num_extra_slice_header_bits = r.read_u8(3)?;
+        let sign_data_hiding_enabled = r.read_bool()?;
+        let cabac_init_present = r.read_bool()?;
+        let num_ref_idx_l0_default_active = r.read_ue()? + 1;
+        let num_ref_idx_l1_default_active = r.read_ue()? + 1;
+        let init_qp_minus26 = r.read_se()?;
+        // QpBdOffsetY of 16 bit samples
+        if !(-74..=25).contains(&init_qp_minus26) {
+            return Err(Error::InvalidValue("init_qp_minus26"));
+        }
+        let init_qp = init_qp_minus26 + 26;
+        let constrained_intra_pred = r.read_bool()?;
+        let transform_skip_enabled = r.read_bool()?;
+        let diff_cu_qp_delta_depth = if r.read_bool()? {
+            Some(r.read_ue()?)
+        } else {
+            None
+        };
+        let cb_qp_offset = r.read_se()?;
+        let cr_qp_offset = r.read_se()?;
+        let slice_chroma_qp_offsets_present = r.read_bool()?;
+        let weighted_pred = r.read_bool()?;
+        let weighted_bipred = r.read_bool()?;
+        let transquant_bypass_enabled = r.read_bool()?;
+        let tiles_enabled = r.read_bool()?;
+        let entropy_coding_sync_enabled = r.read_bool()?;
+        let tiles = if tiles_enabled {
+            let columns = r.read_ue()? + 1;
+            let rows = r.read_ue()? + 1;
+            if columns > 20 || rows > 22 {
+                return Err(Error::InvalidValue("num_tile_columns_minus1"));
+            }
+            let uniform_spacing = r.read_bool()?;
+            if !uniform_spacing {
+                for _ in 0..(columns - 1) + (rows - 1) {
+                    r.read_ue()?;
+                }
+            }
+            Some(Tiles {
+                columns,
+                rows,
+                uniform_spacing,
+                loop_filter_across_tiles: r.read_bool()?,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            id,
+            sps_id,
+            dependent_slice_segments_enabled,
+            output_flag_present,
+            num_extra_slice_header_bits,
+            sign_data_hiding_enabled,
+            cabac_init_present,
+            num_ref_idx_l0_default_active,
+            num_ref_idx_l1_default_active,
+            init_qp,
+            constrained_intra_pred,
+            transform_skip_enabled,
+            diff_cu_qp_delta_depth,
+            cb_qp_offset,
+            cr_qp_offset,
+            slice_chroma_qp_offsets_present,
+            weighted_pred,
+            weighted_bipred,
+            transquant_bypass_enabled,
+            tiles,
+            entropy_coding_sync_enabled,
+            loop_filter_across_slices_enabled: r.read_bool()?,
+            deblocking_filter_control_present: r.read_bool()?,
+        })
+    }
+
+    /// `parallelismType` of `hvcC`: 1 slices, 2 tiles, 3 wavefront, 0 mixed
+    #[inline]
+    pub fn parallelism_type(&self) -> u8 {
+        match (self.tiles.is_some(), self.entropy_coding_sync_enabled) {
+            (true, true) => 0,
+            (false, true) => 3,
+            (true, false) => 2,
+            (false, false) => 1,
+        }
+    }
+}
+
+/// NAL units of one type in `hvcC` or `lhvC`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct NalArray {
+    /// All NAL units of the type are in the array and none are in the stream
+    pub completeness: bool,
+    pub unit_type: NalUnitType,
+    pub nals: Vec<Vec<u8>>,
+}
+
+fn read_arrays(mut data: &[u8]) -> Result<Vec<NalArray>, Error> {
+    let (&count, rest) = data.split_first().ok_or(Error::Truncated)?;
+    data = rest;
+    let mut arrays = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let (header, rest) = data.split_at_checked(3).ok_or(Error::Truncated)?;
+        let mut nals = Vec::new();
+        data = rest;
+        for _ in 0..u16::from_be_bytes([header[1], header[2]]) {
+            let (len, rest) = data.split_at_checked(2).ok_or(Error::Truncated)?;
+            let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+            let (nal, rest) = rest.split_at_checked(len).ok_or(Error::Truncated)?;
+            nals.push(nal.to_vec());
+            data = rest;
+        }
+        arrays.push(NalArray {
+            completeness: header[0] & 0x80 != 0,
+            unit_type: NalUnitType(header[0] & 0x3f),
+            nals,
+        });
+    }
+    Ok(arrays)
+}
+
+fn write_arrays(out: &mut Vec<u8>, arrays: &[NalArray]) {
+    out.push(arrays.len() as u8);
+    for a in arrays {
+        out.push(((a.completeness as u8) << 7) | a.unit_type.0);
+        out.extend_from_slice(&(a.nals.len() as u16).to_be_bytes());
+        for nal in &a.nals {
+            out.extend_from_slice(&(nal.len() as u16).to_be_bytes());
+            out.extend_from_slice(nal);
+        }
+    }
+}
+
+/// Groups parameter sets and SEI by type in VPS, SPS, PPS, SEI order
+fn collect_arrays<'a>(
+    nals: impl IntoIterator<Item = &'a [u8]>,
+    layer: impl Fn(u8) -> bool,
+) -> Vec<NalArray> {
+    let order = [
+        NalUnitType::VPS,
+        NalUnitType::SPS,
+        NalUnitType::PPS,
+        NalUnitType::PREFIX_SEI,
+        NalUnitType::SUFFIX_SEI,
+    ];
+    let mut arrays: Vec<NalArray> = Vec::new();
+    for data in nals {
+        let Some(nal) = Nal::new(data) else {
+            continue;
+        };
+        let unit_type = nal.unit_type();
+        if !order.contains(&unit_type) || !layer(nal.layer_id()) {
+            continue;
+        }
+        match arrays.iter_mut().find(|a| a.unit_type == unit_type) {
+            Some(a) if !a.nals.iter().any(|n| n == data) => a.nals.push(data.to_vec()),
+            Some(_) => {}
+            None => arrays.push(NalArray {
+                completeness: true,
+                unit_type,
+                nals: vec![data.to_vec()],
+            }),
+        }
+    }
+    arrays.sort_by_key(|a| order.iter().position(|&t| t == a.unit_type));
+    arrays
+}
+
+fn check_nal_length_size(size: u8) -> Result<(), Error> {
+    if matches!(size, 1 | 2 | 4) {
+        Ok(())
+    } else {
+        Err(Error::InvalidValue("nal_length_size"))
+    }
+}
+
+#[derive(Default)]
+struct ParamSets {
+    vps: Vec<Vps>,
+    sps: Vec<Sps>,
+    pps: Vec<Pps>,
+}
+
+/// Checks NAL headers of arrays and returns parsed parameter sets
+fn validate_arrays(arrays: &[NalArray], base: Option<&Sps>) -> Result<ParamSets, Error> {
+    let ParamSets {
+        mut vps,
+        mut sps,
+        mut pps,
+    } = ParamSets::default();
+    for a in arrays {
+        for data in &a.nals {
+            let nal = Nal::new(data).ok_or(Error::Truncated)?;
+            if nal.unit_type() != a.unit_type {
+                return Err(Error::UnexpectedNalType(nal.unit_type()));
+            }
+            match a.unit_type {
+                NalUnitType::VPS => vps.push(Vps::parse(data)?),
+                NalUnitType::SPS => sps.push(match base {
+                    Some(b) => Sps::parse_with_base(data, b)?,
+                    None => Sps::parse(data)?,
+                }),
+                NalUnitType::PPS => pps.push(Pps::parse(data)?),
+                _ => {}
+            }
+        }
+    }
+    for p in &pps {
+        if !sps.iter().any(|s| s.id == p.sps_id) {
+            return Err(Error::InvalidValue("pps_seq_parameter_set_id"));
+        }
+    }
+    Ok(ParamSets { vps, sps, pps })
+}
+
+/// `HEVCDecoderConfigurationRecord` (ISO/IEC 14496-15), contents of `hvcC` box and
+/// `cm::VideoFormatDesc::hvcc`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Hvcc {
+    pub general: ProfileTierLevel,
+    pub min_spatial_segmentation_idc: u16,
+    pub parallelism_type: u8,
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+
+    /// Frames per 256 seconds, 0 if unspecified
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+
+    /// Size of NAL unit length prefix in samples: 1, 2 or 4
+    pub nal_length_size: u8,
+    pub arrays: Vec<NalArray>,
+}
+
+impl Hvcc {
+    const HEADER_LEN: usize = 22;
+
+    /// Record from base layer VPS, SPS, PPS and SEI NAL units, other NAL units are skipped.
+    ///
+    /// ```
+    /// use cidre::media::{hevc, nal};
+    ///
+    /// let stream = [
+    ///     0, 0, 0, 1, 0x40, 0x01, 0x0c, 0x01, 0xff, 0xff, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00,
+    ///     0x90, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x5d, 0x95, 0x98, 0x09,
+    ///     0, 0, 0, 1, 0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0x90, 0x00, 0x00,
+    ///     0x03, 0x00, 0x00, 0x03, 0x00, 0x5d, 0xa0, 0x02, 0x80, 0x80, 0x2d, 0x16, 0x59, 0x59,
+    ///     0xa4, 0x93, 0x2b, 0xc0, 0x5a, 0x70, 0x80, 0x00, 0x01, 0xf4, 0x80, 0x00, 0x3a, 0x98,
+    ///     0x04,
+    ///     0, 0, 0, 1, 0x44, 0x01, 0xc1, 0x72, 0xb4, 0x62, 0x40,
+    /// ];
+    /// let hvcc = hevc::Hvcc::with_annex_b(&stream, 4).unwrap();
+    /// assert_eq!(hvcc.codec_string(), "hvc1.1.6.L93.90");
+    ///
+    /// let sps = hvcc.parse_sps().unwrap();
+    /// assert_eq!((sps.width(), sps.height()), (1280, 720));
+    ///
+    /// let bytes = hvcc.to_bytes();
+    /// assert_eq!(hevc::Hvcc::parse(&bytes).unwrap(), hvcc);
+    /// ```
+    pub fn with_nals<'a>(
+        nals: impl IntoIterator<Item = &'a [u8]>,
+        nal_length_size: u8,
+    ) -> Result<Self, Error> {
+        check_nal_length_size(nal_length_size)?;
+        let arrays = collect_arrays(nals, |layer| layer == 0);
+        let ParamSets { vps, sps, pps } = validate_arrays(&arrays, None)?;
+        let (Some(vps), Some(sps)) = (vps.first(), sps.first()) else {
+            return Err(Error::InvalidValue("missing VPS or SPS"));
+        };
+        if pps.is_empty() {
+            return Err(Error::InvalidValue("missing PPS"));
+        }
+        let format = sps.rep_format.ok_or(Error::InvalidValue("rep_format"))?;
+        let min_spatial_segmentation_idc = sps
+            .vui
+            .as_ref()
+            .and_then(|v| v.bitstream_restriction)
+            .map_or(0, |b| b.min_spatial_segmentation_idc);
+        let mut parallelism_type = pps[0].parallelism_type();
+        if min_spatial_segmentation_idc == 0
+            || pps.iter().any(|p| p.parallelism_type() != parallelism_type)
+        {
+            parallelism_type = 0;
+        }
+        let avg_frame_rate = sps
+            .frame_rate()
+            .and_then(|(num, den)| u16::try_from(num as u64 * 256 / den as u64).ok())
+            .unwrap_or(0);
+        Ok(Self {
+            general: sps.ptl.unwrap_or(vps.ptl),
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            chroma_format_idc: format.chroma_format_idc,
+            bit_depth_luma: format.bit_depth_luma,
+            bit_depth_chroma: format.bit_depth_chroma,
+            avg_frame_rate,
+            constant_frame_rate: 0,
+            num_temporal_layers: sps.max_sub_layers.max(vps.max_sub_layers),
+            temporal_id_nested: sps.temporal_id_nesting,
+            nal_length_size,
+            arrays,
+        })
+    }
+
+    /// Collects base layer parameter sets from Annex B stream
+    #[inline]
+    pub fn with_annex_b(data: &[u8], nal_length_size: u8) -> Result<Self, Error> {
+        Self::with_nals(nal::annex_b(data), nal_length_size)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (h, rest) = data
+            .split_at_checked(Self::HEADER_LEN)
+            .ok_or(Error::Truncated)?;
+        if h[0] != 1 {
+            return Err(Error::InvalidValue("configurationVersion"));
+        }
+        let nal_length_size = (h[21] & 3) + 1;
+        if nal_length_size == 3 {
+            return Err(Error::InvalidValue("lengthSizeMinusOne"));
+        }
+        Ok(Self {
+            general: ProfileTierLevel::parse_bytes(&h[1..13]),
+            min_spatial_segmentation_idc: u16::from_be_bytes([h[13], h[14]]) & 0x0fff,
+            parallelism_type: h[15] & 3,
+            chroma_format_idc: h[16] & 3,
+            bit_depth_luma: (h[17] & 7) + 8,
+            bit_depth_chroma: (h[18] & 7) + 8,
+            avg_frame_rate: u16::from_be_bytes([h[19], h[20]]),
+            constant_frame_rate: h[21] >> 6,
+            num_temporal_layers: (h[21] >> 3) & 7,
+            temporal_id_nested: h[21] & 4 != 0,
+            nal_length_size,
+            arrays: read_arrays(rest)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::HEADER_LEN + 128);
+        out.push(1);
+        self.general.write(&mut out);
+        out.extend_from_slice(&(0xf000 | self.min_spatial_segmentation_idc).to_be_bytes());
+        out.push(0xfc | self.parallelism_type);
+        out.push(0xfc | self.chroma_format_idc);
+        out.push(0xf8 | (self.bit_depth_luma - 8));
+        out.push(0xf8 | (self.bit_depth_chroma - 8));
+        out.extend_from_slice(&self.avg_frame_rate.to_be_bytes());
+        out.push(
+            (self.constant_frame_rate << 6)
+                | (self.num_temporal_layers << 3)
+                | ((self.temporal_id_nested as u8) << 2)
+                | (self.nal_length_size - 1),
+        );
+        write_arrays(&mut out, &self.arrays);
+        out
+    }
+
+    /// Checks that record has VPS, SPS and PPS of the base layer which match header fields
+    pub fn validate(&self) -> Result<(), Error> {
+        check_nal_length_size(self.nal_length_size)?;
+        let ParamSets { vps, sps, pps } = validate_arrays(&self.arrays, None)?;
+        if vps.is_empty() || sps.is_empty() || pps.is_empty() {
+            return Err(Error::InvalidValue("missing parameter sets"));
+        }
+        for s in &sps {
+            if s.layer_id != 0 {
+                continue;
+            }
+            let (Some(ptl), Some(format)) = (s.ptl, s.rep_format) else {
+                return Err(Error::InvalidValue("SPS"));
+            };
+            if ptl.profile_idc != self.general.profile_idc {
+                return Err(Error::InvalidValue("general_profile_idc"));
+            }
+            if format.chroma_format_idc != self.chroma_format_idc {
+                return Err(Error::InvalidValue("chromaFormat"));
+            }
+            if format.bit_depth_luma != self.bit_depth_luma
+                || format.bit_depth_chroma != self.bit_depth_chroma
+            {
+                return Err(Error::InvalidValue("bitDepth"));
+            }
+        }
+        Ok(())
+    }
+
+    /// NAL units of the type from all arrays
+    pub fn nals(&self, unit_type: NalUnitType) -> impl Iterator<Item = &[u8]> {
+        self.arrays
+            .iter()
+            .filter(move |a| a.unit_type == unit_type)
+            .flat_map(|a| a.nals.iter().map(|n| n.as_slice()))
+    }
+
+    /// First VPS
+    #[inline]
+    pub fn parse_vps(&self) -> Result<Vps, Error> {
+        let vps = self.nals(NalUnitType::VPS).next();
+        Vps::parse(vps.ok_or(Error::InvalidValue("missing VPS"))?)
+    }
+
+    /// First base layer SPS
+    #[inline]
+    pub fn parse_sps(&self) -> Result<Sps, Error> {
+        let sps = self
+            .nals(NalUnitType::SPS)
+            .find(|n| Nal::new(n).is_some_and(|n| n.layer_id() == 0));
+        Sps::parse(sps.ok_or(Error::InvalidValue("missing SPS"))?)
+    }
+
+    /// Parameter sets in Annex B format to prepend to keyframes
+    pub fn to_annex_b(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for a in self.arrays.iter().filter(|a| a.unit_type.is_param_set()) {
+            for nal in &a.nals {
+                out.extend_from_slice(&[0, 0, 0, 1]);
+                out.extend_from_slice(nal);
+            }
+        }
+        out
+    }
+
+    /// RFC 6381 codec string of `hvc1` sample entry
+    #[inline]
+    pub fn codec_string(&self) -> String {
+        self.general.codec_string("hvc1")
+    }
+
+    #[cfg(feature = "cm")]
+    pub fn with_format_desc(desc: &cm::VideoFormatDesc) -> Option<Self> {
+        Self::parse(&desc.hvcc()?).ok()
+    }
+
+    /// Format description with all parameter sets of the record
+    #[cfg(feature = "cm")]
+    pub fn to_format_desc(&self) -> os::Result<arc::R<cm::VideoFormatDesc>> {
+        let sets: Vec<&[u8]> = self
+            .arrays
+            .iter()
+            .filter(|a| a.unit_type.is_param_set())
+            .flat_map(|a| a.nals.iter().map(|n| n.as_slice()))
+            .collect();
+        let pointers: Vec<_> = sets.iter().map(|s| s.as_ptr()).collect();
+        let sizes: Vec<_> = sets.iter().map(|s| s.len()).collect();
+        cm::VideoFormatDesc::with_hevc_param_sets(
+            sets.len(),
+            &pointers,
+            &sizes,
+            self.nal_length_size as i32,
+            None,
+        )
+    }
+}
+
+/// `LHEVCDecoderConfigurationRecord` (ISO/IEC 14496-15), contents of `lhvC` box
+/// with parameter sets of non-base layers.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Lhvc {
+    pub min_spatial_segmentation_idc: u16,
+    pub parallelism_type: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+    pub nal_length_size: u8,
+    pub arrays: Vec<NalArray>,
+}
+
+impl Lhvc {
+    const HEADER_LEN: usize = 5;
+
+    /// Record from parameter sets of non-base layers, `hvcc` provides stream-wide fields
+    pub fn with_nals<'a>(
+        nals: impl IntoIterator<Item = &'a [u8]>,
+        hvcc: &Hvcc,
+    ) -> Result<Self, Error> {
+        let arrays = collect_arrays(nals, |layer| layer != 0);
+        let res = Self {
+            min_spatial_segmentation_idc: hvcc.min_spatial_segmentation_idc,
+            parallelism_type: hvcc.parallelism_type,
+            num_temporal_layers: hvcc.num_temporal_layers,
+            temporal_id_nested: hvcc.temporal_id_nested,
+            nal_length_size: hvcc.nal_length_size,
+            arrays,
+        };
+        res.validate(hvcc)?;
+        Ok(res)
+    }
+
+    /// Collects non-base layer parameter sets from Annex B stream
+    #[inline]
+    pub fn with_annex_b(data: &[u8], hvcc: &Hvcc) -> Result<Self, Error> {
+        Self::with_nals(nal::annex_b(data), hvcc)
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (h, rest) = data
+            .split_at_checked(Self::HEADER_LEN)
+            .ok_or(Error::Truncated)?;
+        if h[0] != 1 {
+            return Err(Error::InvalidValue("configurationVersion"));
+        }
+        let nal_length_size = (h[4] & 3) + 1;
+        if nal_length_size == 3 {
+            return Err(Error::InvalidValue("lengthSizeMinusOne"));
+        }
+        Ok(Self {
+            min_spatial_segmentation_idc: u16::from_be_bytes([h[1], h[2]]) & 0x0fff,
+            parallelism_type: h[3] & 3,
+            num_temporal_layers: (h[4] >> 3) & 7,
+            temporal_id_nested: h[4] & 4 != 0,
+            nal_length_size,
+            arrays: read_arrays(rest)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(Self::HEADER_LEN + 64);
+        out.push(1);
+        out.extend_from_slice(&(0xf000 | self.min_spatial_segmentation_idc).to_be_bytes());
+        out.push(0xfc | self.parallelism_type);
+        out.push(
+            0xc0 | (self.num_temporal_layers << 3)
+                | ((self.temporal_id_nested as u8) << 2)
+                | (self.nal_length_size - 1),
+        );
+        write_arrays(&mut out, &self.arrays);
+        out
+    }
+
+    /// Checks that record has SPS and PPS of non-base layers only
+    pub fn validate(&self, hvcc: &Hvcc) -> Result<(), Error> {
+        check_nal_length_size(self.nal_length_size)?;
+        if self.nal_length_size != hvcc.nal_length_size {
+            return Err(Error::InvalidValue("lengthSizeMinusOne"));
+        }
+        let base = hvcc.parse_sps()?;
+        let ParamSets { sps, pps, .. } = validate_arrays(&self.arrays, Some(&base))?;
+        if sps.is_empty() || pps.is_empty() {
+            return Err(Error::InvalidValue("missing parameter sets"));
+        }
+        for a in &self.arrays {
+            if a.nals
+                .iter()
+                .any(|n| Nal::new(n).is_some_and(|n| n.layer_id() == 0))
+            {
+                return Err(Error::InvalidValue("nuh_layer_id"));
+            }
+        }
+        Ok(())
+    }
+
+    /// `nuh_layer_id`s of parameter sets
+    pub fn layer_ids(&self) -> Vec<u8> {
+        let mut ids: Vec<u8> = self
+            .arrays
+            .iter()
+            .flat_map(|a| a.nals.iter().filter_map(|n| Nal::new(n)))
+            .map(|n| n.layer_id())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{bits, nal, vui};
+
+    use super::{Error, Hvcc, Lhvc, Nal, NalUnitType, Pps, Sps, Vps};
+
+    fn nal(unit_type: NalUnitType, layer_id: u8, w: bits::Writer) -> Vec<u8> {
+        let mut res = vec![(unit_type.0 << 1) | (layer_id >> 5), (layer_id << 3) | 1];
+        res.extend(nal::to_ebsp(&w.into_vec()));
+        res
+    }
+
+    fn write_ptl(w: &mut bits::Writer, profile_idc: u8, level_idc: u8) {
+        w.write_bits(profile_idc as u64, 8);
+        w.write_bits(1 << (31 - profile_idc as u64), 32);
+        w.write_bits(0x9000_0000_0000, 48);
+        w.write_bits(level_idc as u64, 8);
+    }
+
+    /// Two layer multiview VPS
+    fn vps_nal() -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(0, 4);
+        w.write_bits(0b11, 2);
+        w.write_bits(1, 6); // max_layers_minus1
+        w.write_bits(0, 3);
+        w.write_bool(true);
+        w.write_bits(0xffff, 16);
+        write_ptl(&mut w, 2, 153);
+        w.write_bool(true);
+        for v in [4, 2, 0] {
+            w.write_ue(v);
+        }
+        w.write_bits(1, 6); // max_layer_id
+        w.write_ue(1);
+        w.write_bits(0b11, 2); // layer set 1 includes both layers
+        w.write_bool(false); // timing
+        w.write_bool(true); // extension
+        while !w.is_byte_aligned() {
+            w.write_bool(true);
+        }
+        w.write_bits(153, 8); // ptl level only
+        w.write_bool(false); // splitting
+        w.write_bits(0x4000, 16); // multiview
+        w.write_bits(0, 3); // dimension_id_len 1
+        w.write_bool(true); // nuh_layer_id present
+        w.write_bits(1, 6);
+        w.write_bits(1, 1); // view order idx 1
+        w.write_bits(8, 4); // view_id_len
+        w.write_bits(0, 8);
+        w.write_bits(1, 8);
+        w.write_bool(true); // layer 1 depends on 0
+        w.write_trailing_bits();
+        nal(NalUnitType::VPS, 0, w)
+    }
+
+    /// Main 10 1920x1080 BT.2100 PQ SPS at 60000/1001 with predicted short-term RPS
+    fn sps_nal() -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(0, 4);
+        w.write_bits(0, 3);
+        w.write_bool(true);
+        write_ptl(&mut w, 2, 153);
+        w.write_ue(0);
+        w.write_ue(1); // 4:2:0
+        w.write_ue(1920);
+        w.write_ue(1088);
+        w.write_bool(true);
+        for v in [0, 0, 0, 4] {
+            w.write_ue(v);
+        }
+        w.write_ue(2);
+        w.write_ue(2);
+        w.write_ue(4); // log2_max_pic_order_cnt_lsb_minus4
+        w.write_bool(true);
+        for v in [4, 2, 0] {
+            w.write_ue(v);
+        }
+        for v in [0, 3, 0, 3, 0, 0] {
+            w.write_ue(v);
+        }
+        w.write_bool(true); // scaling lists
+        w.write_bool(true); // data present
+        for size_id in 0..4 {
+            for _ in (0..6).step_by(if size_id == 3 { 3 } else { 1 }) {
+                w.write_bool(false);
+                w.write_ue(0);
+            }
+        }
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_ue(2);
+        // rps 0: -1, -3
+        w.write_ue(2);
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_bool(true);
+        w.write_ue(1);
+        w.write_bool(true);
+        // rps 1 predicted with deltaRps -1: -2, -4, -1
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_ue(0);
+        for _ in 0..3 {
+            w.write_bool(true);
+        }
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_bool(true); // vui
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bits(5, 3);
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bits(9, 8);
+        w.write_bits(16, 8);
+        w.write_bits(9, 8);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bool(true); // timing
+        w.write_bits(1001, 32);
+        w.write_bits(60000, 32);
+        w.write_bool(false);
+        w.write_bool(true); // hrd
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_bool(false);
+        w.write_bits(0, 4);
+        w.write_bits(0, 4);
+        for v in [23, 15, 4] {
+            w.write_bits(v, 5);
+        }
+        w.write_bool(true);
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_ue(1_562_499);
+        w.write_ue(2_343_749);
+        w.write_bool(false);
+        w.write_bool(true); // bitstream restriction
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bool(true);
+        for v in [0, 2, 1, 15, 15] {
+            w.write_ue(v);
+        }
+        w.write_bool(false);
+        w.write_trailing_bits();
+        nal(NalUnitType::SPS, 0, w)
+    }
+
+    /// Multi-layer extension SPS of the second view
+    fn layer_sps_nal() -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(0, 4);
+        w.write_bits(7, 3);
+        w.write_ue(1);
+        w.write_bool(false);
+        w.write_ue(4);
+        for v in [0, 3, 0, 3, 0, 0] {
+            w.write_ue(v);
+        }
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_ue(0);
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_trailing_bits();
+        nal(NalUnitType::SPS, 1, w)
+    }
+
+    fn pps_nal(id: u32, sps_id: u32, layer_id: u8) -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_ue(id);
+        w.write_ue(sps_id);
+        w.write_bits(0, 7);
+        w.write_ue(0);
+        w.write_ue(0);
+        w.write_se(-4);
+        w.write_bits(0, 2);
+        w.write_bool(true);
+        w.write_ue(1);
+        w.write_se(0);
+        w.write_se(0);
+        w.write_bits(0, 5);
+        w.write_bool(true); // wavefront
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_trailing_bits();
+        nal(NalUnitType::PPS, layer_id, w)
+    }
+
+    #[test]
+    fn nal_types() {
+        let types = [
+            NalUnitType::RASL_N,
+            NalUnitType::IDR_W_RADL,
+            NalUnitType::CRA,
+            NalUnitType::PREFIX_SEI,
+        ];
+        let flags: Vec<_> = types
+            .iter()
+            .map(|t| {
+                (
+                    t.is_vcl(),
+                    t.is_irap(),
+                    t.is_leading(),
+                    t.is_sub_layer_non_ref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            flags,
+            [
+                (true, false, true, true),
+                (true, true, false, false),
+                (true, true, false, false),
+                (false, false, false, false)
+            ]
+        );
+        assert_eq!(format!("{:?}", NalUnitType::BLA_N_LP), "BLA_N_LP(18)");
+
+        let nal = Nal::new(&[0x02, 0x0a, 0xaf]).unwrap();
+        assert_eq!(nal.unit_type(), NalUnitType::TRAIL_R);
+        assert_eq!((nal.layer_id(), nal.temporal_id()), (1, 1));
+        assert!(Nal::new(&[0x26]).is_none());
+
+        let mut sample = Vec::new();
+        nal::push_length_prefixed(&mut sample, &[0x02, 0x09, 0], 4).unwrap();
+        assert!(!super::is_sync_sample(&sample, 4).unwrap());
+        nal::push_length_prefixed(&mut sample, &[0x28, 0x01, 0], 4).unwrap();
+        assert!(super::is_sync_sample(&sample, 4).unwrap());
+    }
+
+    #[test]
+    fn sps_pps() {
+        let sps = Sps::parse(&sps_nal()).unwrap();
+        assert_eq!((sps.width(), sps.height()), (1920, 1080));
+        assert_eq!(
+            (sps.bit_depth_luma(), sps.chroma_format_idc()),
+            (Some(10), Some(1))
+        );
+        assert_eq!(sps.frame_rate(), Some((60000, 1001)));
+        assert_eq!(sps.color(), Some(vui::ColorDescription::BT2100_PQ));
+        assert!(sps.is_hdr());
+        assert_eq!(sps.max_num_reorder_pics, 2);
+        assert_eq!(sps.num_short_term_ref_pic_sets, 2);
+        assert_eq!(sps.codec_string().unwrap(), "hvc1.2.4.L153.90");
+
+        let vui = sps.vui.as_ref().unwrap();
+        let hrd = vui.hrd.as_ref().unwrap();
+        assert_eq!(
+            (hrd.au_cpb_removal_delay_length, hrd.dpb_output_delay_length),
+            (16, 5)
+        );
+        assert_eq!(hrd.sub_layers[0].nal_cpbs[0].bit_rate, 100_000_000);
+        assert_eq!(
+            vui.bitstream_restriction
+                .unwrap()
+                .min_spatial_segmentation_idc,
+            0
+        );
+
+        let pps = Pps::parse(&pps_nal(0, 0, 0)).unwrap();
+        assert_eq!(pps.init_qp, 22);
+        assert_eq!(pps.diff_cu_qp_delta_depth, Some(1));
+        assert_eq!(pps.parallelism_type(), 3);
+        assert!(pps.loop_filter_across_slices_enabled);
+
+        assert_eq!(
+            Sps::parse(&pps_nal(0, 0, 0)),
+            Err(Error::UnexpectedNalType(NalUnitType::PPS))
+        );
+        assert_eq!(Sps::parse(&sps_nal()[..20]), Err(Error::Truncated));
+
+        // log2_max_pic_order_cnt_lsb_minus4 at exp-golomb maximum
+        let mut w = bits::Writer::new();
+        w.write_bits(0, 8);
+        write_ptl(&mut w, 1, 93);
+        for v in [0, 1, 1920, 1080] {
+            w.write_ue(v);
+        }
+        w.write_bool(false);
+        for v in [0, 0, u32::MAX - 1] {
+            w.write_ue(v);
+        }
+        w.write_trailing_bits();
+        assert_eq!(
+            Sps::parse(&nal(NalUnitType::SPS, 0, w)),
+            Err(Error::InvalidValue("log2_max_pic_order_cnt_lsb_minus4"))
+        );
+    }
+
+    #[test]
+    fn multiview() {
+        let vps = Vps::parse(&vps_nal()).unwrap();
+        assert!(vps.is_multiview());
+        assert_eq!((vps.max_layers, vps.max_dec_pic_buffering), (2, 5));
+        let ext = vps.ext.as_ref().unwrap();
+        let layers: Vec<_> = ext
+            .layers
+            .iter()
+            .map(|l| {
+                (
+                    l.id,
+                    l.view_order_idx,
+                    l.view_id,
+                    l.direct_dependencies.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(layers, [(0, 0, Some(0), vec![]), (1, 1, Some(1), vec![0])]);
+
+        let base = Sps::parse(&sps_nal()).unwrap();
+        let layer = Sps::parse(&layer_sps_nal()).unwrap();
+        assert_eq!((layer.layer_id, layer.id, layer.width()), (1, 1, 0));
+        assert!(layer.ptl.is_none());
+        let layer = Sps::parse_with_base(&layer_sps_nal(), &base).unwrap();
+        assert_eq!((layer.width(), layer.height()), (1920, 1080));
+        assert_eq!(layer.max_num_reorder_pics, 2);
+    }
+
+    #[test]
+    fn hvcc() {
+        let nals = [
+            vps_nal(),
+            sps_nal(),
+            pps_nal(0, 0, 0),
+            layer_sps_nal(),
+            pps_nal(1, 1, 1),
+        ];
+        let mut stream = Vec::new();
+        for n in &nals {
+            stream.extend_from_slice(&[0, 0, 1]);
+            stream.extend_from_slice(n);
+        }
+        let hvcc = Hvcc::with_annex_b(&stream, 4).unwrap();
+        assert_eq!(hvcc.arrays.len(), 3);
+        assert_eq!(hvcc.nals(NalUnitType::SPS).count(), 1);
+        assert_eq!((hvcc.bit_depth_luma, hvcc.chroma_format_idc), (10, 1));
+        assert_eq!(hvcc.avg_frame_rate, 15344);
+        assert_eq!(hvcc.parallelism_type, 0);
+        assert!(hvcc.parse_vps().unwrap().is_multiview());
+        hvcc.validate().unwrap();
+
+        let bytes = hvcc.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            23 + hvcc
+                .arrays
+                .iter()
+                .map(|a| 3 + 2 * a.nals.len())
+                .sum::<usize>()
+                + nals[..3].iter().map(|n| n.len()).sum::<usize>()
+        );
+        assert_eq!(bytes[13..16], [0xf0, 0x00, 0xfc]);
+        assert_eq!(Hvcc::parse(&bytes).unwrap(), hvcc);
+        assert_eq!(
+            Hvcc::parse(&bytes[..bytes.len() - 2]),
+            Err(Error::Truncated)
+        );
+
+        let lhvc = Lhvc::with_annex_b(&stream, &hvcc).unwrap();
+        assert_eq!(lhvc.layer_ids(), [1]);
+        assert_eq!(lhvc.arrays.len(), 2);
+        let bytes = lhvc.to_bytes();
+        assert_eq!(Lhvc::parse(&bytes).unwrap(), lhvc);
+
+        let mut wrong = hvcc.clone();
+        wrong.bit_depth_luma = 8;
+        assert_eq!(wrong.validate(), Err(Error::InvalidValue("bitDepth")));
+        assert!(Lhvc::with_nals([&sps_nal()[..]], &hvcc).is_err());
+
+        let annex_b = hvcc.to_annex_b();
+        let sample = nal::annex_b_to_length_prefixed(&annex_b, 4).unwrap();
+        assert_eq!(nal::length_prefixed(&sample, 4).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,455 @@
+//! Adaptive bit rate ladder planning.
+//!
+//! [`LadderConfig::plan`] scales source down to standard rung heights, estimates bit rate
+//! of each rung from bits per pixel, fits the ladder into bandwidth budget and assigns
+//! the lowest H.264 or HEVC level of each rendition.
+//!
+//! ```
+//! use cidre::media::ladder::{Audio, LadderConfig, Profile};
+//!
+//! let mut config = LadderConfig::new(Profile::H264High);
+//! config.audio = Some(Audio::aac_lc(128_000));
+//! let ladder = config.plan(1920, 1080, 30.0, 6_000_000).unwrap();
+//!
+//! let top = &ladder.renditions[0];
+//! assert_eq!((top.width, top.height), (1920, 1080));
+//! assert_eq!(top.codecs(), "avc1.640028");
+//! assert!(ladder.bandwidth(top) <= 6_000_000);
+//!
+//! let lowest = ladder.renditions.last().unwrap();
+//! assert_eq!((lowest.width, lowest.height), (416, 234));
+//!
+//! let variants = ladder.variants(|r| format!("{}p/index.m3u8", r.height));
+//! assert_eq!(variants[0].codecs.as_deref(), Some("avc1.640028,mp4a.40.2"));
+//! assert_eq!(variants[0].resolution, Some((1920, 1080)));
+//! ```
+
+use crate::media::{h264, hevc, m3u8};
+
+#[cfg(feature = "vt")]
+use crate::vt;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Source dimensions or frame rate are zero
+    InvalidSource,
+
+    /// Bandwidth is below `BANDWIDTH` of the smallest rendition
+    BudgetTooLow { min: u64 },
+
+    /// No level of profile fits rendition
+    LevelExceeded { width: u32, height: u32 },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSource => f.write_str("invalid ladder source"),
+            Self::BudgetTooLow { min } => write!(f, "ladder needs at least {min} bits/s"),
+            Self::LevelExceeded { width, height } => {
+                write!(f, "no level fits {width}x{height} rendition")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Video profile of ladder
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Profile {
+    H264Baseline,
+    H264Main,
+    H264High,
+    HevcMain,
+    HevcMain10,
+}
+
+impl Profile {
+    #[inline]
+    pub fn is_hevc(&self) -> bool {
+        matches!(self, Self::HevcMain | Self::HevcMain10)
+    }
+
+    /// `profile_idc` of H.264 or `general_profile_idc` of HEVC
+    pub fn profile_idc(&self) -> u8 {
+        match self {
+            Self::H264Baseline => h264::Sps::PROFILE_BASELINE,
+            Self::H264Main => h264::Sps::PROFILE_MAIN,
+            Self::H264High => h264::Sps::PROFILE_HIGH,
+            Self::HevcMain => hevc::ProfileTierLevel::PROFILE_MAIN,
+            Self::HevcMain10 => hevc::ProfileTierLevel::PROFILE_MAIN_10,
+        }
+    }
+
+    /// Average bits per pixel of rendition bit rate
+    #[inline]
+    pub fn default_bits_per_pixel(&self) -> f64 {
+        if self.is_hevc() {
+            0.06
+        } else {
+            0.1
+        }
+    }
+
+    /// The lowest level fitting stream of peak bit rate
+    pub fn min_level_idc(
+        &self,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bit_rate: u64,
+    ) -> Option<u8> {
+        if self.is_hevc() {
+            hevc::LevelLimits::min_level_idc(width, height, frame_rate, bit_rate, false)
+        } else {
+            let profile_idc = self.profile_idc();
+            h264::LevelLimits::min_level_idc(width, height, frame_rate, bit_rate, profile_idc)
+        }
+    }
+
+    /// RFC 6381 codec string of progressive `avc1` or `hvc1` stream
+    pub fn codec_string(&self, level_idc: u8) -> String {
+        // constraint flags encoders set for the profile
+        let constraint_flags = match self {
+            Self::H264Baseline => 0xe0,
+            Self::H264Main => 0x40,
+            Self::H264High => 0x00,
+            Self::HevcMain | Self::HevcMain10 => {
+                // Main streams are compatible with Main 10 too
+                let compatibility_flags = match self {
+                    Self::HevcMain => 0x6000_0000,
+                    _ => 0x2000_0000,
+                };
+                let ptl = hevc::ProfileTierLevel {
+                    profile_idc: self.profile_idc(),
+                    compatibility_flags,
+                    // progressive, non packed, frame only
+                    constraint_flags: 0xb0 << 40,
+                    level_idc,
+                    ..Default::default()
+                };
+                return ptl.codec_string("hvc1");
+            }
+        };
+        format!(
+            "avc1.{:02x}{constraint_flags:02x}{level_idc:02x}",
+            self.profile_idc()
+        )
+    }
+
+    #[cfg(feature = "vt")]
+    pub fn encoder_profile(&self) -> vt::compression::config::Profile {
+        use vt::compression::config::Profile as P;
+        match self {
+            Self::H264Baseline => P::H264Baseline,
+            Self::H264Main => P::H264Main,
+            Self::H264High => P::H264High,
+            Self::HevcMain => P::HevcMain,
+            Self::HevcMain10 => P::HevcMain10,
+        }
+    }
+}
+
+/// Audio of every variant
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Audio {
+    /// RFC 6381 codec string
+    pub codecs: String,
+
+    /// Peak bits per second
+    pub bit_rate: u64,
+}
+
+impl Audio {
+    #[inline]
+    pub fn aac_lc(bit_rate: u64) -> Self {
+        Self {
+            codecs: "mp4a.40.2".into(),
+            bit_rate,
+        }
+    }
+}
+
+/// Video of ladder rung
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rendition {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+
+    /// Average bits per second
+    pub bit_rate: u64,
+
+    /// Peak bits per second within any second
+    pub peak_bit_rate: u64,
+    pub profile: Profile,
+    pub level_idc: u8,
+}
+
+impl Rendition {
+    /// RFC 6381 codec string of video, e.g. `avc1.64001f`
+    #[inline]
+    pub fn codecs(&self) -> String {
+        self.profile.codec_string(self.level_idc)
+    }
+
+    /// Encoder settings with profile and level, average bit rate and peak within a second.
+    ///
+    /// Level is automatic if video encoder has no constant for it.
+    #[cfg(feature = "vt")]
+    pub fn encoder_config(&self) -> vt::compression::EncoderConfig {
+        use vt::compression::config;
+
+        let codec = if self.profile.is_hevc() {
+            config::Codec::Hevc
+        } else {
+            config::Codec::H264
+        };
+        let profile = self.profile.encoder_profile();
+        let mut res = config::EncoderConfig::new(codec, self.width, self.height);
+        res.profile = Some(profile);
+        res.level = profile
+            .levels()
+            .iter()
+            .copied()
+            .find(|&l| l >= self.level_idc);
+        res.average_bit_rate = Some(self.bit_rate);
+        res.data_rate_limits = vec![config::DataRateLimit {
+            bytes: self.peak_bit_rate / 8,
+            seconds: 1.0,
+        }];
+        res.expected_frame_rate = Some(self.frame_rate);
+        res
+    }
+}
+
+/// Ladder rules, defaults follow HLS authoring recommendations
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderConfig {
+    pub profile: Profile,
+
+    /// Rung heights, descending, source height is the top rung
+    pub heights: Vec<u32>,
+    pub bits_per_pixel: f64,
+
+    /// Peak to average bit rate
+    pub peak_ratio: f64,
+
+    /// Min bit rate ratio of adjacent rungs
+    pub min_step: f64,
+
+    /// Rung is dropped if budget covers less of its estimated bit rate
+    pub min_fill: f64,
+    pub max_renditions: usize,
+
+    /// Rungs below have frame rates above 30 halved
+    pub full_frame_rate_min_height: u32,
+    pub audio: Option<Audio>,
+}
+
+impl LadderConfig {
+    pub const DEFAULT_HEIGHTS: [u32; 9] = [2160, 1440, 1080, 720, 540, 432, 360, 270, 234];
+
+    pub fn new(profile: Profile) -> Self {
+        Self {
+            profile,
+            heights: Self::DEFAULT_HEIGHTS.to_vec(),
+            bits_per_pixel: profile.default_bits_per_pixel(),
+            peak_ratio: 1.1,
+            min_step: 1.3,
+            min_fill: 0.5,
+            max_renditions: 8,
+            full_frame_rate_min_height: 720,
+            audio: None,
+        }
+    }
+
+    /// Renditions of source with `BANDWIDTH` of the top variant up to `bandwidth`
+    pub fn plan(
+        &self,
+        width: u32,
+        height: u32,
+        frame_rate: f64,
+        bandwidth: u64,
+    ) -> Result<Ladder, Error> {
+        if width == 0 || height == 0 || frame_rate <= 0.0 || frame_rate.is_nan() {
+            return Err(Error::InvalidSource);
+        }
+        let audio_bit_rate = self.audio.as_ref().map_or(0, |a| a.bit_rate);
+        let peak_ratio = self.peak_ratio.max(1.0);
+        let max_bit_rate = bandwidth.saturating_sub(audio_bit_rate) as f64 / peak_ratio;
+
+        let sizes = std::iter::once((width, height)).chain(
+            self.heights
+                .iter()
+                .filter(|&&h| h < height)
+                .map(|&h| (even(width as f64 * h as f64 / height as f64), h)),
+        );
+        let mut renditions: Vec<Rendition> = Vec::new();
+        let mut lowest_estimate = 0.0;
+        for (w, h) in sizes {
+            let frame_rate = if h < self.full_frame_rate_min_height && frame_rate > 30.0 {
+                frame_rate / 2.0
+            } else {
+                frame_rate
+            };
+            let estimate = w as f64 * h as f64 * frame_rate * self.bits_per_pixel;
+            lowest_estimate = estimate;
+            if estimate * self.min_fill > max_bit_rate {
+                continue;
+            }
+            let bit_rate = estimate.min(max_bit_rate);
+            if renditions
+                .last()
+                .is_some_and(|r| bit_rate * self.min_step > r.bit_rate as f64)
+            {
+                continue;
+            }
+            let bit_rate = bit_rate as u64;
+            let peak_bit_rate = (bit_rate as f64 * peak_ratio) as u64;
+            let level_idc = self
+                .profile
+                .min_level_idc(w, h, frame_rate, peak_bit_rate)
+                .ok_or(Error::LevelExceeded {
+                    width: w,
+                    height: h,
+                })?;
+            renditions.push(Rendition {
+                width: w,
+                height: h,
+                frame_rate,
+                bit_rate,
+                peak_bit_rate,
+                profile: self.profile,
+                level_idc,
+            });
+        }
+        if renditions.is_empty() {
+            let min = lowest_estimate * self.min_fill * peak_ratio;
+            return Err(Error::BudgetTooLow {
+                min: min.ceil() as u64 + audio_bit_rate,
+            });
+        }
+        // the lowest rung is kept for the slowest networks
+        while renditions.len() > self.max_renditions.max(1) {
+            renditions.remove(renditions.len() - 2);
+        }
+        Ok(Ladder {
+            renditions,
+            audio: self.audio.clone(),
+        })
+    }
+}
+
+impl Default for LadderConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Profile::H264High)
+    }
+}
+
+/// Renditions in descending bit rate
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ladder {
+    pub renditions: Vec<Rendition>,
+    pub audio: Option<Audio>,
+}
+
+impl Ladder {
+    /// `BANDWIDTH` of variant, peak bit rates of video and audio
+    #[inline]
+    pub fn bandwidth(&self, rendition: &Rendition) -> u64 {
+        rendition.peak_bit_rate + self.audio.as_ref().map_or(0, |a| a.bit_rate)
+    }
+
+    /// `CODECS` of variant
+    pub fn codecs(&self, rendition: &Rendition) -> String {
+        let mut res = rendition.codecs();
+        if let Some(audio) = &self.audio {
+            res.push(',');
+            res.push_str(&audio.codecs);
+        }
+        res
+    }
+
+    /// `EXT-X-STREAM-INF` of renditions with URIs of `uri`
+    pub fn variants(&self, mut uri: impl FnMut(&Rendition) -> String) -> Vec<m3u8::Variant> {
+        let audio_bit_rate = self.audio.as_ref().map_or(0, |a| a.bit_rate);
+        self.renditions
+            .iter()
+            .map(|r| m3u8::Variant {
+                uri: uri(r),
+                bandwidth: self.bandwidth(r),
+                average_bandwidth: Some(r.bit_rate + audio_bit_rate),
+                codecs: Some(self.codecs(r)),
+                resolution: Some((r.width, r.height)),
+                frame_rate: Some(r.frame_rate),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+/// Rounds to even number, as 4:2:0 chroma needs
+#[inline]
+fn even(value: f64) -> u32 {
+    ((value / 2.0).round() as u32 * 2).max(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, LadderConfig, Profile};
+
+    #[test]
+    fn uhd_hevc() {
+        let config = LadderConfig::new(Profile::HevcMain10);
+        let ladder = config.plan(3840, 2160, 60.0, 20_000_000).unwrap();
+        let sizes: Vec<_> = ladder
+            .renditions
+            .iter()
+            .map(|r| (r.width, r.height, r.frame_rate))
+            .collect();
+        assert_eq!(
+            sizes,
+            [
+                (3840, 2160, 60.0),
+                (2560, 1440, 60.0),
+                (1920, 1080, 60.0),
+                (1280, 720, 60.0),
+                (960, 540, 30.0),
+                (768, 432, 30.0),
+                (640, 360, 30.0),
+                (416, 234, 30.0),
+            ]
+        );
+        let top = &ladder.renditions[0];
+        assert!(top.peak_bit_rate <= 20_000_000);
+        assert_eq!(top.level_idc, 153);
+        assert_eq!(top.codecs(), "hvc1.2.4.L153.B0");
+        assert!(ladder
+            .renditions
+            .windows(2)
+            .all(|w| w[0].bit_rate as f64 >= w[1].bit_rate as f64 * 1.3));
+    }
+
+    #[test]
+    fn starved_rungs() {
+        let mut config = LadderConfig::new(Profile::H264Main);
+        config.max_renditions = 3;
+        // 1080p needs about 6 Mbit/s, capped 720p leaves too little room for 540p
+        let ladder = config.plan(1920, 1080, 30.0, 2_000_000).unwrap();
+        let heights: Vec<_> = ladder.renditions.iter().map(|r| r.height).collect();
+        assert_eq!(heights, [720, 432, 234]);
+        assert_eq!(ladder.renditions[0].codecs(), "avc1.4d401f");
+
+        assert_eq!(
+            config.plan(1920, 1080, 30.0, 100_000),
+            Err(Error::BudgetTooLow { min: 160_618 })
+        );
+        assert_eq!(
+            config.plan(0, 1080, 30.0, 100_000),
+            Err(Error::InvalidSource)
+        );
+    }
+}
@@ -0,0 +1,928 @@
+//! HLS playlists (RFC 8216 and its LL-HLS extensions).
+//!
+//! [`parse`] reads media and multivariant playlists, `Display` writes them back.
+//! Unknown tags are skipped as the spec requires.
+//!
+//! ```
+//! use cidre::media::m3u8;
+//!
+//! let text = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXT-X-MEDIA-SEQUENCE:0\n\
+//!     #EXTINF:6.006,\nseg0.m4s\n#EXT-X-ENDLIST\n";
+//! let m3u8::Playlist::Media(playlist) = m3u8::parse(text).unwrap() else {
+//!     panic!("media playlist expected");
+//! };
+//! assert_eq!(playlist.segments[0].duration, 6.006);
+//! assert_eq!(playlist.to_string(), text);
+//! ```
+
+mod live;
+pub use live::LiveConfig;
+pub use live::LiveSegmenter;
+
+use std::fmt::{self, Write};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Text does not start with `#EXTM3U`
+    MissingHeader,
+
+    /// Required tag is absent
+    MissingTag(&'static str),
+
+    /// Malformed tag or URI line, lines are counted from 1
+    InvalidLine(usize),
+
+    /// Segment duration rounded to seconds exceeds `EXT-X-TARGETDURATION`
+    TargetDurationExceeded(u32),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingHeader => f.write_str("missing #EXTM3U header"),
+            Self::MissingTag(tag) => write!(f, "missing #{tag} tag"),
+            Self::InvalidLine(n) => write!(f, "invalid playlist line {n}"),
+            Self::TargetDurationExceeded(secs) => {
+                write!(f, "segment of {secs}s exceeds target duration")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// `EXT-X-BYTERANGE` or `BYTERANGE` attribute, `length[@offset]`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ByteRange {
+    pub length: u64,
+
+    /// Continues after the previous range of the same resource if absent
+    pub offset: Option<u64>,
+}
+
+impl ByteRange {
+    fn parse(s: &str) -> Option<Self> {
+        let (length, offset) = match s.split_once('@') {
+            Some((l, o)) => (l, Some(o.parse().ok()?)),
+            None => (s, None),
+        };
+        Some(Self {
+            length: length.parse().ok()?,
+            offset,
+        })
+    }
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.length)?;
+        if let Some(offset) = self.offset {
+            write!(f, "@{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KeyMethod {
+    None,
+    Aes128,
+    SampleAes,
+    SampleAesCtr,
+}
+
+impl KeyMethod {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "NONE" => Self::None,
+            "AES-128" => Self::Aes128,
+            "SAMPLE-AES" => Self::SampleAes,
+            "SAMPLE-AES-CTR" => Self::SampleAesCtr,
+            _ => return None,
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "NONE",
+            Self::Aes128 => "AES-128",
+            Self::SampleAes => "SAMPLE-AES",
+            Self::SampleAesCtr => "SAMPLE-AES-CTR",
+        }
+    }
+}
+
+/// `EXT-X-KEY`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Key {
+    pub method: KeyMethod,
+    pub uri: Option<String>,
+    pub iv: Option<u128>,
+    pub key_format: Option<String>,
+    pub key_format_versions: Option<String>,
+}
+
+/// `EXT-X-MAP` media initialization section
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Map {
+    pub uri: String,
+    pub byte_range: Option<ByteRange>,
+}
+
+/// `EXT-X-PART` of LL-HLS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub uri: String,
+
+    /// Seconds
+    pub duration: f64,
+    pub independent: bool,
+    pub byte_range: Option<ByteRange>,
+    pub gap: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PreloadHintType {
+    Part,
+    Map,
+}
+
+/// `EXT-X-PRELOAD-HINT` of LL-HLS
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PreloadHint {
+    pub hint_type: PreloadHintType,
+    pub uri: String,
+    pub byte_range_start: Option<u64>,
+    pub byte_range_length: Option<u64>,
+}
+
+/// Media segment with tags which precede its URI.
+///
+/// `key` and `map` are set on the segment where the tag appears and apply to
+/// the following segments until changed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment {
+    pub uri: String,
+
+    /// `EXTINF` duration in seconds
+    pub duration: f64,
+    pub title: String,
+    pub byte_range: Option<ByteRange>,
+    pub discontinuity: bool,
+
+    /// `EXT-X-PROGRAM-DATE-TIME` as ISO 8601 date-time
+    pub program_date_time: Option<String>,
+    pub key: Option<Key>,
+    pub map: Option<Map>,
+
+    /// Parts which make up this segment
+    pub parts: Vec<Part>,
+    pub gap: bool,
+}
+
+impl Segment {
+    #[inline]
+    pub fn new(uri: impl Into<String>, duration: f64) -> Self {
+        Self {
+            uri: uri.into(),
+            duration,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PlaylistType {
+    Event,
+    Vod,
+}
+
+/// `EXT-X-SERVER-CONTROL` of LL-HLS
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ServerControl {
+    pub can_block_reload: bool,
+    pub can_skip_until: Option<f64>,
+    pub hold_back: Option<f64>,
+    pub part_hold_back: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaPlaylist {
+    pub version: Option<u32>,
+
+    /// Upper bound of rounded segment durations in seconds
+    pub target_duration: u32,
+    pub media_sequence: u64,
+    pub discontinuity_sequence: u64,
+    pub playlist_type: Option<PlaylistType>,
+    pub independent_segments: bool,
+    pub server_control: Option<ServerControl>,
+
+    /// `PART-TARGET` of `EXT-X-PART-INF`
+    pub part_target: Option<f64>,
+    pub segments: Vec<Segment>,
+
+    /// Parts of the segment being written
+    pub trailing_parts: Vec<Part>,
+    pub preload_hints: Vec<PreloadHint>,
+    pub end_list: bool,
+}
+
+impl MediaPlaylist {
+    /// Total duration of segments in seconds
+    #[inline]
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RenditionType {
+    Audio,
+    Video,
+    Subtitles,
+    ClosedCaptions,
+}
+
+impl RenditionType {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "AUDIO" => Self::Audio,
+            "VIDEO" => Self::Video,
+            "SUBTITLES" => Self::Subtitles,
+            "CLOSED-CAPTIONS" => Self::ClosedCaptions,
+            _ => return None,
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Audio => "AUDIO",
+            Self::Video => "VIDEO",
+            Self::Subtitles => "SUBTITLES",
+            Self::ClosedCaptions => "CLOSED-CAPTIONS",
+        }
+    }
+}
+
+/// `EXT-X-MEDIA`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Rendition {
+    pub media_type: RenditionType,
+    pub group_id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub uri: Option<String>,
+    pub default: bool,
+    pub autoselect: bool,
+    pub channels: Option<String>,
+    pub instream_id: Option<String>,
+}
+
+/// `EXT-X-STREAM-INF` with its URI or `EXT-X-I-FRAME-STREAM-INF`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variant {
+    pub uri: String,
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+    pub codecs: Option<String>,
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+
+    /// `SDR`, `HLG` or `PQ`
+    pub video_range: Option<String>,
+    pub audio: Option<String>,
+    pub video: Option<String>,
+    pub subtitles: Option<String>,
+
+    /// Group id or `NONE`
+    pub closed_captions: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultivariantPlaylist {
+    pub version: Option<u32>,
+    pub independent_segments: bool,
+    pub renditions: Vec<Rendition>,
+    pub variants: Vec<Variant>,
+    pub i_frame_variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Playlist {
+    Media(MediaPlaylist),
+    Multivariant(MultivariantPlaylist),
+}
+
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Media(p) => p.fmt(f),
+            Self::Multivariant(p) => p.fmt(f),
+        }
+    }
+}
+
+/// Attribute list of tag, quotes of quoted strings are stripped
+fn attrs(s: &str) -> Option<Vec<(&str, &str)>> {
+    let mut res = Vec::new();
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let (name, tail) = rest.split_once('=')?;
+        let (value, tail) = match tail.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, tail) = quoted.split_once('"')?;
+                (value, tail)
+            }
+            None => tail.split_at(tail.find(',').unwrap_or(tail.len())),
+        };
+        res.push((name.trim(), value));
+        rest = match tail.strip_prefix(',') {
+            Some(tail) => tail.trim_start(),
+            None if tail.trim().is_empty() => "",
+            None => return None,
+        };
+    }
+    Some(res)
+}
+
+fn attr<'a>(attrs: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|a| a.0 == name).map(|a| a.1)
+}
+
+fn parse_attr<T: std::str::FromStr>(attrs: &[(&str, &str)], name: &str) -> Result<Option<T>, ()> {
+    attr(attrs, name)
+        .map(|v| v.parse().map_err(|_| ()))
+        .transpose()
+}
+
+fn parse_part(a: &[(&str, &str)]) -> Result<Part, ()> {
+    Ok(Part {
+        uri: attr(a, "URI").ok_or(())?.to_string(),
+        duration: parse_attr(a, "DURATION")?.ok_or(())?,
+        independent: attr(a, "INDEPENDENT") == Some("YES"),
+        byte_range: attr(a, "BYTERANGE")
+            .map(|v| ByteRange::parse(v).ok_or(()))
+            .transpose()?,
+        gap: attr(a, "GAP") == Some("YES"),
+    })
+}
+
+fn parse_key(a: &[(&str, &str)]) -> Result<Key, ()> {
+    let iv = match attr(a, "IV") {
+        Some(iv) => {
+            let hex = iv
+                .strip_prefix("0x")
+                .or_else(|| iv.strip_prefix("0X"))
+                .ok_or(())?;
+            Some(u128::from_str_radix(hex, 16).map_err(|_| ())?)
+        }
+        None => None,
+    };
+    Ok(Key {
+        method: KeyMethod::parse(attr(a, "METHOD").ok_or(())?).ok_or(())?,
+        uri: attr(a, "URI").map(str::to_string),
+        iv,
+        key_format: attr(a, "KEYFORMAT").map(str::to_string),
+        key_format_versions: attr(a, "KEYFORMATVERSIONS").map(str::to_string),
+    })
+}
+
+fn parse_variant(a: &[(&str, &str)]) -> Result<Variant, ()> {
+    let resolution = match attr(a, "RESOLUTION") {
+        Some(r) => {
+            let (w, h) = r.split_once('x').ok_or(())?;
+            Some((w.parse().map_err(|_| ())?, h.parse().map_err(|_| ())?))
+        }
+        None => None,
+    };
+    let string = |name| attr(a, name).map(str::to_string);
+    Ok(Variant {
+        uri: string("URI").unwrap_or_default(),
+        bandwidth: parse_attr(a, "BANDWIDTH")?.ok_or(())?,
+        average_bandwidth: parse_attr(a, "AVERAGE-BANDWIDTH")?,
+        codecs: string("CODECS"),
+        resolution,
+        frame_rate: parse_attr(a, "FRAME-RATE")?,
+        video_range: string("VIDEO-RANGE"),
+        audio: string("AUDIO"),
+        video: string("VIDEO"),
+        subtitles: string("SUBTITLES"),
+        closed_captions: string("CLOSED-CAPTIONS"),
+    })
+}
+
+fn parse_rendition(a: &[(&str, &str)]) -> Result<Rendition, ()> {
+    let string = |name| attr(a, name).map(str::to_string);
+    Ok(Rendition {
+        media_type: RenditionType::parse(attr(a, "TYPE").ok_or(())?).ok_or(())?,
+        group_id: string("GROUP-ID").ok_or(())?,
+        name: string("NAME").ok_or(())?,
+        language: string("LANGUAGE"),
+        uri: string("URI"),
+        default: attr(a, "DEFAULT") == Some("YES"),
+        autoselect: attr(a, "AUTOSELECT") == Some("YES"),
+        channels: string("CHANNELS"),
+        instream_id: string("INSTREAM-ID"),
+    })
+}
+
+/// Parses media or multivariant playlist
+pub fn parse(text: &str) -> Result<Playlist, Error> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim_start_matches('\u{feff}').trim() == "#EXTM3U" => {}
+        _ => return Err(Error::MissingHeader),
+    }
+
+    let mut media = MediaPlaylist::default();
+    let mut multivariant = MultivariantPlaylist::default();
+    let mut target_duration = None;
+    let mut version = None;
+    let mut independent_segments = false;
+    let mut segment = Segment::default();
+    let mut has_inf = false;
+    let mut stream_inf: Option<Variant> = None;
+
+    for (i, line) in lines {
+        let line = line.trim();
+        let invalid = || Error::InvalidLine(i + 1);
+        if line.is_empty() {
+            continue;
+        }
+        let Some(tag) = line.strip_prefix('#') else {
+            if let Some(mut variant) = stream_inf.take() {
+                variant.uri = line.to_string();
+                multivariant.variants.push(variant);
+            } else if has_inf {
+                segment.uri = line.to_string();
+                media.segments.push(std::mem::take(&mut segment));
+                has_inf = false;
+            } else {
+                return Err(invalid());
+            }
+            continue;
+        };
+        let (name, value) = tag.split_once(':').unwrap_or((tag, ""));
+        let attributes = || attrs(value).ok_or_else(invalid);
+        match name {
+            "EXT-X-VERSION" => version = Some(value.parse().map_err(|_| invalid())?),
+            "EXT-X-INDEPENDENT-SEGMENTS" => independent_segments = true,
+            "EXT-X-TARGETDURATION" => target_duration = Some(value.parse().map_err(|_| invalid())?),
+            "EXT-X-MEDIA-SEQUENCE" => {
+                media.media_sequence = value.parse().map_err(|_| invalid())?
+            }
+            "EXT-X-DISCONTINUITY-SEQUENCE" => {
+                media.discontinuity_sequence = value.parse().map_err(|_| invalid())?
+            }
+            "EXT-X-PLAYLIST-TYPE" => {
+                media.playlist_type = Some(match value {
+                    "EVENT" => PlaylistType::Event,
+                    "VOD" => PlaylistType::Vod,
+                    _ => return Err(invalid()),
+                })
+            }
+            "EXT-X-ENDLIST" => media.end_list = true,
+            "EXT-X-SERVER-CONTROL" => {
+                let a = attributes()?;
+                media.server_control = Some(ServerControl {
+                    can_block_reload: attr(&a, "CAN-BLOCK-RELOAD") == Some("YES"),
+                    can_skip_until: parse_attr(&a, "CAN-SKIP-UNTIL").map_err(|_| invalid())?,
+                    hold_back: parse_attr(&a, "HOLD-BACK").map_err(|_| invalid())?,
+                    part_hold_back: parse_attr(&a, "PART-HOLD-BACK").map_err(|_| invalid())?,
+                });
+            }
+            "EXT-X-PART-INF" => {
+                media.part_target =
+                    parse_attr(&attributes()?, "PART-TARGET").map_err(|_| invalid())?
+            }
+            "EXTINF" => {
+                let (duration, title) = value.split_once(',').unwrap_or((value, ""));
+                segment.duration = duration.trim().parse().map_err(|_| invalid())?;
+                segment.title = title.to_string();
+                has_inf = true;
+            }
+            "EXT-X-BYTERANGE" => {
+                segment.byte_range = Some(ByteRange::parse(value).ok_or_else(invalid)?)
+            }
+            "EXT-X-DISCONTINUITY" => segment.discontinuity = true,
+            "EXT-X-GAP" => segment.gap = true,
+            "EXT-X-PROGRAM-DATE-TIME" => segment.program_date_time = Some(value.to_string()),
+            "EXT-X-KEY" => segment.key = Some(parse_key(&attributes()?).map_err(|_| invalid())?),
+            "EXT-X-MAP" => {
+                let a = attributes()?;
+                segment.map = Some(Map {
+                    uri: attr(&a, "URI").ok_or_else(invalid)?.to_string(),
+                    byte_range: attr(&a, "BYTERANGE")
+                        .map(|v| ByteRange::parse(v).ok_or_else(invalid))
+                        .transpose()?,
+                });
+            }
+            "EXT-X-PART" => segment
+                .parts
+                .push(parse_part(&attributes()?).map_err(|_| invalid())?),
+            "EXT-X-PRELOAD-HINT" => {
+                let a = attributes()?;
+                media.preload_hints.push(PreloadHint {
+                    hint_type: match attr(&a, "TYPE") {
+                        Some("PART") => PreloadHintType::Part,
+                        Some("MAP") => PreloadHintType::Map,
+                        _ => return Err(invalid()),
+                    },
+                    uri: attr(&a, "URI").ok_or_else(invalid)?.to_string(),
+                    byte_range_start: parse_attr(&a, "BYTERANGE-START").map_err(|_| invalid())?,
+                    byte_range_length: parse_attr(&a, "BYTERANGE-LENGTH").map_err(|_| invalid())?,
+                });
+            }
+            "EXT-X-MEDIA" => multivariant
+                .renditions
+                .push(parse_rendition(&attributes()?).map_err(|_| invalid())?),
+            "EXT-X-STREAM-INF" => {
+                stream_inf = Some(parse_variant(&attributes()?).map_err(|_| invalid())?)
+            }
+            "EXT-X-I-FRAME-STREAM-INF" => {
+                let variant = parse_variant(&attributes()?).map_err(|_| invalid())?;
+                if variant.uri.is_empty() {
+                    return Err(invalid());
+                }
+                multivariant.i_frame_variants.push(variant);
+            }
+            _ => {}
+        }
+    }
+
+    if !multivariant.variants.is_empty()
+        || !multivariant.renditions.is_empty()
+        || !multivariant.i_frame_variants.is_empty()
+    {
+        multivariant.version = version;
+        multivariant.independent_segments = independent_segments;
+        return Ok(Playlist::Multivariant(multivariant));
+    }
+    media.version = version;
+    media.independent_segments = independent_segments;
+    media.target_duration = target_duration.ok_or(Error::MissingTag("EXT-X-TARGETDURATION"))?;
+    media.trailing_parts = segment.parts;
+    Ok(Playlist::Media(media))
+}
+
+fn write_part(f: &mut fmt::Formatter<'_>, part: &Part) -> fmt::Result {
+    write!(
+        f,
+        "#EXT-X-PART:DURATION={},URI=\"{}\"",
+        part.duration, part.uri
+    )?;
+    if part.independent {
+        f.write_str(",INDEPENDENT=YES")?;
+    }
+    if let Some(range) = part.byte_range {
+        write!(f, ",BYTERANGE=\"{range}\"")?;
+    }
+    if part.gap {
+        f.write_str(",GAP=YES")?;
+    }
+    f.write_char('\n')
+}
+
+impl fmt::Display for MediaPlaylist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("#EXTM3U\n")?;
+        if let Some(version) = self.version {
+            writeln!(f, "#EXT-X-VERSION:{version}")?;
+        }
+        if self.independent_segments {
+            f.write_str("#EXT-X-INDEPENDENT-SEGMENTS\n")?;
+        }
+        writeln!(f, "#EXT-X-TARGETDURATION:{}", self.target_duration)?;
+        if let Some(sc) = &self.server_control {
+            let mut sep = "";
+            f.write_str("#EXT-X-SERVER-CONTROL:")?;
+            if sc.can_block_reload {
+                f.write_str("CAN-BLOCK-RELOAD=YES")?;
+                sep = ",";
+            }
+            for (name, value) in [
+                ("CAN-SKIP-UNTIL", sc.can_skip_until),
+                ("HOLD-BACK", sc.hold_back),
+                ("PART-HOLD-BACK", sc.part_hold_back),
+            ] {
+                if let Some(value) = value {
+                    write!(f, "{sep}{name}={value}")?;
+                    sep = ",";
+                }
+            }
+            f.write_char('\n')?;
+        }
+        if let Some(part_target) = self.part_target {
+            writeln!(f, "#EXT-X-PART-INF:PART-TARGET={part_target}")?;
+        }
+        writeln!(f, "#EXT-X-MEDIA-SEQUENCE:{}", self.media_sequence)?;
+        if self.discontinuity_sequence != 0 {
+            writeln!(
+                f,
+                "#EXT-X-DISCONTINUITY-SEQUENCE:{}",
+                self.discontinuity_sequence
+            )?;
+        }
+        match self.playlist_type {
+            Some(PlaylistType::Event) => f.write_str("#EXT-X-PLAYLIST-TYPE:EVENT\n")?,
+            Some(PlaylistType::Vod) => f.write_str("#EXT-X-PLAYLIST-TYPE:VOD\n")?,
+            None => {}
+        }
+        for s in &self.segments {
+            if s.discontinuity {
+                f.write_str("#EXT-X-DISCONTINUITY\n")?;
+            }
+            if let Some(key) = &s.key {
+                write!(f, "#EXT-X-KEY:METHOD={}", key.method.as_str())?;
+                if let Some(uri) = &key.uri {
+                    write!(f, ",URI=\"{uri}\"")?;
+                }
+                if let Some(iv) = key.iv {
+                    write!(f, ",IV=0x{iv:032x}")?;
+                }
+                if let Some(v) = &key.key_format {
+                    write!(f, ",KEYFORMAT=\"{v}\"")?;
+                }
+                if let Some(v) = &key.key_format_versions {
+                    write!(f, ",KEYFORMATVERSIONS=\"{v}\"")?;
+                }
+                f.write_char('\n')?;
+            }
+            if let Some(map) = &s.map {
+                write!(f, "#EXT-X-MAP:URI=\"{}\"", map.uri)?;
+                if let Some(range) = map.byte_range {
+                    write!(f, ",BYTERANGE=\"{range}\"")?;
+                }
+                f.write_char('\n')?;
+            }
+            if let Some(pdt) = &s.program_date_time {
+                writeln!(f, "#EXT-X-PROGRAM-DATE-TIME:{pdt}")?;
+            }
+            for part in &s.parts {
+                write_part(f, part)?;
+            }
+            writeln!(f, "#EXTINF:{},{}", s.duration, s.title)?;
+            if let Some(range) = s.byte_range {
+                writeln!(f, "#EXT-X-BYTERANGE:{range}")?;
+            }
+            if s.gap {
+                f.write_str("#EXT-X-GAP\n")?;
+            }
+            writeln!(f, "{}", s.uri)?;
+        }
+        for part in &self.trailing_parts {
+            write_part(f, part)?;
+        }
+        for hint in &self.preload_hints {
+            let hint_type = match hint.hint_type {
+                PreloadHintType::Part => "PART",
+                PreloadHintType::Map => "MAP",
+            };
+            write!(
+                f,
+                "#EXT-X-PRELOAD-HINT:TYPE={hint_type},URI=\"{}\"",
+                hint.uri
+            )?;
+            if let Some(start) = hint.byte_range_start {
+                write!(f, ",BYTERANGE-START={start}")?;
+            }
+            if let Some(len) = hint.byte_range_length {
+                write!(f, ",BYTERANGE-LENGTH={len}")?;
+            }
+            f.write_char('\n')?;
+        }
+        if self.end_list {
+            f.write_str("#EXT-X-ENDLIST\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_variant(f: &mut fmt::Formatter<'_>, tag: &str, v: &Variant) -> fmt::Result {
+    write!(f, "#{tag}:BANDWIDTH={}", v.bandwidth)?;
+    if let Some(avg) = v.average_bandwidth {
+        write!(f, ",AVERAGE-BANDWIDTH={avg}")?;
+    }
+    if let Some(codecs) = &v.codecs {
+        write!(f, ",CODECS=\"{codecs}\"")?;
+    }
+    if let Some((w, h)) = v.resolution {
+        write!(f, ",RESOLUTION={w}x{h}")?;
+    }
+    if let Some(rate) = v.frame_rate {
+        write!(f, ",FRAME-RATE={rate:.3}")?;
+    }
+    if let Some(range) = &v.video_range {
+        write!(f, ",VIDEO-RANGE={range}")?;
+    }
+    for (name, value) in [
+        ("AUDIO", &v.audio),
+        ("VIDEO", &v.video),
+        ("SUBTITLES", &v.subtitles),
+    ] {
+        if let Some(value) = value {
+            write!(f, ",{name}=\"{value}\"")?;
+        }
+    }
+    match v.closed_captions.as_deref() {
+        Some("NONE") => f.write_str(",CLOSED-CAPTIONS=NONE")?,
+        Some(cc) => write!(f, ",CLOSED-CAPTIONS=\"{cc}\"")?,
+        None => {}
+    }
+    Ok(())
+}
+
+impl fmt::Display for MultivariantPlaylist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("#EXTM3U\n")?;
+        if let Some(version) = self.version {
+            writeln!(f, "#EXT-X-VERSION:{version}")?;
+        }
+        if self.independent_segments {
+            f.write_str("#EXT-X-INDEPENDENT-SEGMENTS\n")?;
+        }
+        for r in &self.renditions {
+            write!(
+                f,
+                "#EXT-X-MEDIA:TYPE={},GROUP-ID=\"{}\",NAME=\"{}\"",
+                r.media_type.as_str(),
+                r.group_id,
+                r.name
+            )?;
+            if let Some(lang) = &r.language {
+                write!(f, ",LANGUAGE=\"{lang}\"")?;
+            }
+            if r.default {
+                f.write_str(",DEFAULT=YES")?;
+            }
+            if r.autoselect {
+                f.write_str(",AUTOSELECT=YES")?;
+            }
+            if let Some(channels) = &r.channels {
+                write!(f, ",CHANNELS=\"{channels}\"")?;
+            }
+            if let Some(id) = &r.instream_id {
+                write!(f, ",INSTREAM-ID=\"{id}\"")?;
+            }
+            if let Some(uri) = &r.uri {
+                write!(f, ",URI=\"{uri}\"")?;
+            }
+            f.write_char('\n')?;
+        }
+        for v in &self.variants {
+            write_variant(f, "EXT-X-STREAM-INF", v)?;
+            writeln!(f, "\n{}", v.uri)?;
+        }
+        for v in &self.i_frame_variants {
+            write_variant(f, "EXT-X-I-FRAME-STREAM-INF", v)?;
+            writeln!(f, ",URI=\"{}\"", v.uri)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LL_HLS: &str = "#EXTM3U
+#EXT-X-VERSION:9
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-BLOCK-RELOAD=YES,PART-HOLD-BACK=1.002
+#EXT-X-PART-INF:PART-TARGET=0.334
+#EXT-X-MEDIA-SEQUENCE:266
+#EXT-X-DISCONTINUITY-SEQUENCE:2
+#EXT-X-KEY:METHOD=SAMPLE-AES,URI=\"skd://key\",IV=0x0000000000000000000000000000002a,KEYFORMAT=\"com.apple.streamingkeydelivery\",KEYFORMATVERSIONS=\"1\"
+#EXT-X-MAP:URI=\"init.mp4\"
+#EXT-X-PROGRAM-DATE-TIME:2019-02-14T02:13:36.106Z
+#EXTINF:4.00008,
+fileSequence266.mp4
+#EXT-X-DISCONTINUITY
+#EXT-X-PART:DURATION=0.33334,URI=\"filePart267.0.mp4\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=0.33334,URI=\"filePart267.1.mp4\"
+#EXTINF:0.66668,live
+#EXT-X-BYTERANGE:1024@512
+fileSequence267.mp4
+#EXT-X-PART:DURATION=0.33334,URI=\"filePart268.0.mp4\",INDEPENDENT=YES,BYTERANGE=\"100@0\"
+#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"filePart268.1.mp4\",BYTERANGE-START=100
+";
+
+    #[test]
+    fn media() {
+        let Playlist::Media(p) = parse(LL_HLS).unwrap() else {
+            panic!("media playlist expected");
+        };
+        assert_eq!(p.version, Some(9));
+        assert!(p.independent_segments);
+        assert_eq!(p.target_duration, 4);
+        assert_eq!(p.media_sequence, 266);
+        assert_eq!(p.discontinuity_sequence, 2);
+        assert_eq!(p.part_target, Some(0.334));
+        let sc = p.server_control.unwrap();
+        assert!(sc.can_block_reload);
+        assert_eq!(sc.part_hold_back, Some(1.002));
+        assert_eq!(p.segments.len(), 2);
+
+        let s = &p.segments[0];
+        assert_eq!(s.uri, "fileSequence266.mp4");
+        assert_eq!(s.duration, 4.00008);
+        assert_eq!(s.map.as_ref().unwrap().uri, "init.mp4");
+        let key = s.key.as_ref().unwrap();
+        assert_eq!(key.method, KeyMethod::SampleAes);
+        assert_eq!(key.iv, Some(42));
+        assert_eq!(
+            s.program_date_time.as_deref(),
+            Some("2019-02-14T02:13:36.106Z")
+        );
+
+        let s = &p.segments[1];
+        assert!(s.discontinuity);
+        assert_eq!(s.title, "live");
+        assert_eq!(s.parts.len(), 2);
+        assert!(s.parts[0].independent && !s.parts[1].independent);
+        assert_eq!(
+            s.byte_range,
+            Some(ByteRange {
+                length: 1024,
+                offset: Some(512)
+            })
+        );
+
+        assert_eq!(p.trailing_parts.len(), 1);
+        assert_eq!(
+            p.trailing_parts[0].byte_range,
+            Some(ByteRange {
+                length: 100,
+                offset: Some(0)
+            })
+        );
+        assert_eq!(p.preload_hints[0].hint_type, PreloadHintType::Part);
+        assert_eq!(p.preload_hints[0].byte_range_start, Some(100));
+        assert!(!p.end_list);
+
+        assert_eq!(p.to_string(), LL_HLS);
+    }
+
+    #[test]
+    fn multivariant() {
+        let text = "#EXTM3U
+#EXT-X-VERSION:6
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aac\",NAME=\"English\",LANGUAGE=\"en\",DEFAULT=YES,AUTOSELECT=YES,CHANNELS=\"2\",URI=\"audio/en.m3u8\"
+#EXT-X-MEDIA:TYPE=CLOSED-CAPTIONS,GROUP-ID=\"cc\",NAME=\"CC1\",INSTREAM-ID=\"CC1\"
+#EXT-X-STREAM-INF:BANDWIDTH=6000000,AVERAGE-BANDWIDTH=5000000,CODECS=\"hvc1.2.4.L123.B0,mp4a.40.2\",RESOLUTION=1920x1080,FRAME-RATE=29.970,VIDEO-RANGE=PQ,AUDIO=\"aac\",CLOSED-CAPTIONS=\"cc\"
+hdr/1080p.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=800000,RESOLUTION=640x360,AUDIO=\"aac\",CLOSED-CAPTIONS=NONE
+sdr/360p.m3u8
+#EXT-X-I-FRAME-STREAM-INF:BANDWIDTH=200000,CODECS=\"avc1.64001f\",RESOLUTION=640x360,URI=\"sdr/iframes.m3u8\"
+";
+        let Playlist::Multivariant(p) = parse(text).unwrap() else {
+            panic!("multivariant playlist expected");
+        };
+        assert_eq!(p.renditions.len(), 2);
+        assert_eq!(p.renditions[0].media_type, RenditionType::Audio);
+        assert!(p.renditions[0].default);
+        assert_eq!(p.renditions[1].instream_id.as_deref(), Some("CC1"));
+        assert_eq!(p.variants.len(), 2);
+        assert_eq!(
+            p.variants[0].codecs.as_deref(),
+            Some("hvc1.2.4.L123.B0,mp4a.40.2")
+        );
+        assert_eq!(p.variants[0].resolution, Some((1920, 1080)));
+        assert_eq!(p.variants[0].frame_rate, Some(29.97));
+        assert_eq!(p.variants[1].uri, "sdr/360p.m3u8");
+        assert_eq!(p.variants[1].closed_captions.as_deref(), Some("NONE"));
+        assert_eq!(p.i_frame_variants[0].uri, "sdr/iframes.m3u8");
+        assert_eq!(p.to_string(), text);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("#EXTINF:1,\na.ts"), Err(Error::MissingHeader));
+        assert_eq!(
+            parse("#EXTM3U\n#EXTINF:1,\na.ts"),
+            Err(Error::MissingTag("EXT-X-TARGETDURATION"))
+        );
+        assert_eq!(
+            parse("#EXTM3U\n#EXT-X-TARGETDURATION:x"),
+            Err(Error::InvalidLine(2))
+        );
+        assert_eq!(
+            parse("#EXTM3U\n#EXT-X-TARGETDURATION:2\na.ts"),
+            Err(Error::InvalidLine(3))
+        );
+        assert_eq!(
+            parse("#EXTM3U\n#EXT-X-MAP:URI=\"init.mp4"),
+            Err(Error::InvalidLine(2))
+        );
+        // unknown tags and comments are skipped
+        let Playlist::Media(p) =
+            parse("#EXTM3U\n# comment\n#EXT-X-FOO:1\n#EXT-X-TARGETDURATION:2\n").unwrap()
+        else {
+            panic!("media playlist expected");
+        };
+        assert!(p.segments.is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+use super::{
+    Error, Key, Map, MediaPlaylist, Part, PlaylistType, PreloadHint, PreloadHintType, Segment,
+    ServerControl,
+};
+
+/// Options of [`LiveSegmenter`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveConfig {
+    /// Maximal segment duration in seconds, fixed for the whole stream
+    pub target_duration: u32,
+
+    /// Number of segments kept in playlist, 0 keeps all and marks playlist as `EVENT`
+    pub window: usize,
+    pub version: u32,
+    pub independent_segments: bool,
+
+    /// Enables LL-HLS parts and blocking reload
+    pub part_target: Option<f64>,
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        Self {
+            target_duration: 6,
+            window: 6,
+            version: 7,
+            independent_segments: true,
+            part_target: None,
+        }
+    }
+}
+
+/// Maintains live media playlist from durations of written segments,
+/// e.g. durations of `av::AssetSegmentReport` track reports.
+///
+/// ```
+/// use cidre::media::m3u8;
+///
+/// let mut live = m3u8::LiveSegmenter::new(m3u8::LiveConfig {
+///     window: 2,
+///     ..Default::default()
+/// });
+/// live.set_map("init.mp4");
+/// for i in 0..3 {
+///     let removed = live.push_segment(format!("seg{i}.m4s"), 6.0).unwrap();
+///     assert_eq!(removed.len(), (i == 2) as usize);
+/// }
+/// let playlist = live.playlist();
+/// assert_eq!(playlist.media_sequence, 1);
+/// assert_eq!(playlist.segments[0].map.as_ref().unwrap().uri, "init.mp4");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LiveSegmenter {
+    window: usize,
+    playlist: MediaPlaylist,
+    discontinuity: bool,
+    map: Option<Map>,
+    key: Option<Key>,
+    program_date_time: Option<String>,
+}
+
+impl LiveSegmenter {
+    pub fn new(config: LiveConfig) -> Self {
+        let server_control = config.part_target.map(|part| ServerControl {
+            can_block_reload: true,
+            part_hold_back: Some(part * 3.0),
+            ..Default::default()
+        });
+        Self {
+            window: config.window,
+            playlist: MediaPlaylist {
+                version: Some(config.version),
+                target_duration: config.target_duration,
+                playlist_type: (config.window == 0).then_some(PlaylistType::Event),
+                independent_segments: config.independent_segments,
+                server_control,
+                part_target: config.part_target,
+                ..Default::default()
+            },
+            discontinuity: false,
+            map: None,
+            key: None,
+            program_date_time: None,
+        }
+    }
+
+    #[inline]
+    pub fn playlist(&self) -> &MediaPlaylist {
+        &self.playlist
+    }
+
+    /// Initialization section of the next and following segments
+    pub fn set_map(&mut self, uri: impl Into<String>) {
+        self.map = Some(Map {
+            uri: uri.into(),
+            byte_range: None,
+        });
+    }
+
+    /// Encryption of the next and following segments
+    pub fn set_key(&mut self, key: Key) {
+        self.key = Some(key);
+    }
+
+    /// Marks the next segment as discontinuous, e.g. after encoder restart
+    pub fn discontinuity(&mut self) {
+        self.discontinuity = true;
+    }
+
+    /// ISO 8601 wall clock time of the next segment
+    pub fn set_program_date_time(&mut self, date_time: impl Into<String>) {
+        self.program_date_time = Some(date_time.into());
+    }
+
+    /// Appends part of the segment being written and clears preload hints
+    pub fn push_part(&mut self, uri: impl Into<String>, duration: f64, independent: bool) {
+        self.playlist.preload_hints.clear();
+        self.playlist.trailing_parts.push(Part {
+            uri: uri.into(),
+            duration,
+            independent,
+            byte_range: None,
+            gap: false,
+        });
+    }
+
+    /// Announces the next part before it is written
+    pub fn set_preload_hint(&mut self, uri: impl Into<String>) {
+        self.playlist.preload_hints = vec![PreloadHint {
+            hint_type: PreloadHintType::Part,
+            uri: uri.into(),
+            byte_range_start: None,
+            byte_range_length: None,
+        }];
+    }
+
+    /// Appends segment made of pushed parts and returns segments which left
+    /// the window, so their files can be removed.
+    ///
+    /// Target duration can't change during the stream (RFC 8216 4.3.3.1),
+    /// segments longer than it are rejected.
+    pub fn push_segment(
+        &mut self,
+        uri: impl Into<String>,
+        duration: f64,
+    ) -> Result<Vec<Segment>, Error> {
+        let p = &mut self.playlist;
+        let rounded = duration.round() as u32;
+        if rounded > p.target_duration {
+            return Err(Error::TargetDurationExceeded(rounded));
+        }
+        p.segments.push(Segment {
+            uri: uri.into(),
+            duration,
+            discontinuity: std::mem::take(&mut self.discontinuity),
+            program_date_time: self.program_date_time.take(),
+            key: self.key.take(),
+            map: self.map.take(),
+            parts: std::mem::take(&mut p.trailing_parts),
+            ..Default::default()
+        });
+
+        let mut removed = Vec::new();
+        if self.window != 0 && p.segments.len() > self.window {
+            removed.extend(p.segments.drain(..p.segments.len() - self.window));
+            p.media_sequence += removed.len() as u64;
+            p.discontinuity_sequence += removed.iter().filter(|s| s.discontinuity).count() as u64;
+            // tags of removed segments still apply to the remaining ones
+            let first = &mut p.segments[0];
+            for s in removed.iter().rev() {
+                if first.map.is_none() {
+                    first.map.clone_from(&s.map);
+                }
+                if first.key.is_none() {
+                    first.key.clone_from(&s.key);
+                }
+            }
+        }
+
+        // parts are kept only for segments within three target durations of the live edge
+        let limit = 3.0 * p.target_duration as f64;
+        let mut age = 0.0;
+        for s in p.segments.iter_mut().rev() {
+            if age > limit {
+                s.parts.clear();
+            }
+            age += s.duration;
+        }
+        Ok(removed)
+    }
+
+    /// Closes the playlist with `EXT-X-ENDLIST`
+    pub fn finish(&mut self) {
+        self.playlist.preload_hints.clear();
+        self.playlist.end_list = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::m3u8::{self, KeyMethod, Playlist};
+
+    #[test]
+    fn sliding_window() {
+        let mut live = LiveSegmenter::new(LiveConfig {
+            target_duration: 7,
+            window: 3,
+            ..Default::default()
+        });
+        live.set_map("init.mp4");
+        live.set_key(Key {
+            method: KeyMethod::Aes128,
+            uri: Some("key.bin".into()),
+            iv: None,
+            key_format: None,
+            key_format_versions: None,
+        });
+        live.push_segment("s0.m4s", 6.0).unwrap();
+        live.discontinuity();
+        live.push_segment("s1.m4s", 6.4).unwrap();
+        live.push_segment("s2.m4s", 7.4).unwrap();
+        assert_eq!(
+            live.push_segment("s3.m4s", 7.6),
+            Err(Error::TargetDurationExceeded(8))
+        );
+        assert_eq!(live.playlist().target_duration, 7);
+        assert!(live.push_segment("s3.m4s", 5.9).unwrap()[0].map.is_some());
+        let removed = live.push_segment("s4.m4s", 6.0).unwrap();
+        assert_eq!(removed[0].uri, "s1.m4s");
+
+        let p = live.playlist();
+        assert_eq!(p.media_sequence, 2);
+        assert_eq!(p.discontinuity_sequence, 1);
+        assert_eq!(p.segments.len(), 3);
+        assert_eq!(p.segments[0].map.as_ref().unwrap().uri, "init.mp4");
+        assert_eq!(
+            p.segments[0].key.as_ref().unwrap().method,
+            KeyMethod::Aes128
+        );
+        assert_eq!(p.playlist_type, None);
+
+        live.finish();
+        let text = live.playlist().to_string();
+        assert!(text.ends_with("s4.m4s\n#EXT-X-ENDLIST\n"));
+        let Playlist::Media(parsed) = m3u8::parse(&text).unwrap() else {
+            panic!("media playlist expected");
+        };
+        assert_eq!(&parsed, live.playlist());
+    }
+
+    #[test]
+    fn parts() {
+        let mut live = LiveSegmenter::new(LiveConfig {
+            target_duration: 1,
+            window: 0,
+            part_target: Some(0.5),
+            ..Default::default()
+        });
+        for i in 0..5 {
+            live.push_part(format!("p{i}.0.m4s"), 0.5, true);
+            live.push_part(format!("p{i}.1.m4s"), 0.5, false);
+            live.push_segment(format!("s{i}.m4s"), 1.0).unwrap();
+        }
+        live.push_part("p5.0.m4s", 0.5, true);
+        live.set_preload_hint("p5.1.m4s");
+
+        let p = live.playlist();
+        assert_eq!(p.playlist_type, Some(PlaylistType::Event));
+        assert_eq!(p.server_control.unwrap().part_hold_back, Some(1.5));
+        assert_eq!(p.segments.len(), 5);
+        // older segments lose their parts
+        assert!(p.segments[0].parts.is_empty());
+        assert_eq!(p.segments[4].parts.len(), 2);
+        assert_eq!(p.trailing_parts.len(), 1);
+        assert_eq!(p.preload_hints[0].uri, "p5.1.m4s");
+
+        let Playlist::Media(parsed) = m3u8::parse(&p.to_string()).unwrap() else {
+            panic!("media playlist expected");
+        };
+        assert_eq!(&parsed, p);
+    }
+}
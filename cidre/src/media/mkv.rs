@@ -0,0 +1,200 @@
+//! Matroska and WebM (RFC 9559) of VP9, AV1, H.264, HEVC, Opus and AAC.
+//!
+//! [`Muxer`] writes live friendly files: segment of unknown size, clusters of
+//! [`SimpleBlock`]s cut at video key frames and cues at the end. [`Segment`]
+//! reads them back with the [`ebml`] reader.
+//!
+//! ```
+//! use cidre::media::mkv;
+//!
+//! let mut mux = mkv::Muxer::new(Default::default());
+//! let video = mux.add_track(mkv::TrackConfig::vp9(640, 360));
+//!
+//! let mut file = mux.header();
+//! for i in 0..90 {
+//!     // 30 fps, key frame every 2 seconds
+//!     let pts = i * 1_000_000_000 / 30;
+//!     let frame = mkv::Frame::new(&[0x82, 0x49, 0x83], pts, i % 60 == 0);
+//!     if let Some(cluster) = mux.push(video, &frame).unwrap() {
+//!         file.extend(cluster);
+//!     }
+//! }
+//! file.extend(mux.finish());
+//!
+//! let segment = mkv::Segment::parse(&file).unwrap();
+//! assert_eq!(segment.doc_type, "webm");
+//! assert_eq!(segment.track(video).unwrap().codec_id, mkv::CodecId::VP9);
+//! assert_eq!(segment.clusters.len(), 2);
+//! assert_eq!(segment.cues.len(), 2);
+//! assert_eq!(segment.blocks().count(), 90);
+//! ```
+
+pub mod ebml;
+
+mod demux;
+pub use demux::Block;
+pub use demux::CuePoint;
+pub use demux::Segment;
+
+mod mux;
+pub use mux::AudioSettings;
+pub use mux::Frame;
+pub use mux::MuxConfig;
+pub use mux::Muxer;
+pub use mux::TrackConfig;
+pub use mux::VideoSettings;
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+/// `TimestampScale` of 1 ms, the default of Matroska
+pub const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the element
+    Truncated,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// Required element is missing
+    Missing(ebml::Id),
+
+    /// Block is of a track not added to muxer or not present in `Tracks`
+    UnknownTrack(u64),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated Matroska data"),
+            Self::InvalidValue(name) => write!(f, "invalid Matroska {name}"),
+            Self::Missing(id) => write!(f, "missing Matroska element {:#x}", id.0),
+            Self::UnknownTrack(track) => write!(f, "unknown Matroska track {track}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// `TrackType` of `TrackEntry`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct TrackType(pub u8);
+
+impl TrackType {
+    pub const VIDEO: Self = Self(1);
+    pub const AUDIO: Self = Self(2);
+    pub const COMPLEX: Self = Self(3);
+    pub const LOGO: Self = Self(0x10);
+    pub const SUBTITLE: Self = Self(0x11);
+    pub const BUTTONS: Self = Self(0x12);
+    pub const CONTROL: Self = Self(0x20);
+    pub const METADATA: Self = Self(0x21);
+}
+
+/// `CodecID` of `TrackEntry`
+pub struct CodecId;
+
+impl CodecId {
+    pub const H264: &'static str = "V_MPEG4/ISO/AVC";
+    pub const HEVC: &'static str = "V_MPEGH/ISO/HEVC";
+    pub const AV1: &'static str = "V_AV1";
+    pub const VP8: &'static str = "V_VP8";
+    pub const VP9: &'static str = "V_VP9";
+    pub const OPUS: &'static str = "A_OPUS";
+    pub const VORBIS: &'static str = "A_VORBIS";
+    pub const AAC: &'static str = "A_AAC";
+
+    /// WebM allows VP8, VP9, AV1, Opus and Vorbis only
+    #[inline]
+    pub fn is_webm(codec_id: &str) -> bool {
+        matches!(
+            codec_id,
+            Self::VP8 | Self::VP9 | Self::AV1 | Self::OPUS | Self::VORBIS
+        )
+    }
+}
+
+/// Block without lacing, timestamp is relative to the cluster one
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SimpleBlock<'a> {
+    pub track: u64,
+    pub timestamp: i16,
+    pub is_key: bool,
+
+    /// Frame should not be shown, e.g. VP8 alt-ref frame
+    pub is_invisible: bool,
+
+    /// Frame can be dropped, e.g. non reference B frame
+    pub is_discardable: bool,
+    pub data: &'a [u8],
+}
+
+impl<'a> SimpleBlock<'a> {
+    const KEY: u8 = 0x80;
+    const INVISIBLE: u8 = 0x08;
+    const LACING: u8 = 0x06;
+    const DISCARDABLE: u8 = 0x01;
+
+    #[inline]
+    pub fn new(track: u64, timestamp: i16, is_key: bool, data: &'a [u8]) -> Self {
+        Self {
+            track,
+            timestamp,
+            is_key,
+            is_invisible: false,
+            is_discardable: false,
+            data,
+        }
+    }
+
+    /// Payload of `SimpleBlock` element, laced blocks are not supported
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (track, len) = ebml::read_vint(data)?;
+        let rest = &data[len..];
+        let [t0, t1, flags] = *rest.first_chunk::<3>().ok_or(Error::Truncated)?;
+        if flags & Self::LACING != 0 {
+            return Err(Error::InvalidValue("block lacing"));
+        }
+        Ok(Self {
+            track,
+            timestamp: i16::from_be_bytes([t0, t1]),
+            is_key: flags & Self::KEY != 0,
+            is_invisible: flags & Self::INVISIBLE != 0,
+            is_discardable: flags & Self::DISCARDABLE != 0,
+            data: &rest[3..],
+        })
+    }
+
+    /// Appends `SimpleBlock` element
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let mut header = Vec::with_capacity(4);
+        ebml::write_vint(&mut header, self.track);
+        header.extend_from_slice(&self.timestamp.to_be_bytes());
+        let mut flags = 0;
+        if self.is_key {
+            flags |= Self::KEY;
+        }
+        if self.is_invisible {
+            flags |= Self::INVISIBLE;
+        }
+        if self.is_discardable {
+            flags |= Self::DISCARDABLE;
+        }
+        header.push(flags);
+        ebml::write_id(out, ebml::Id::SIMPLE_BLOCK);
+        ebml::write_vint(out, (header.len() + self.data.len()) as u64);
+        out.extend_from_slice(&header);
+        out.extend_from_slice(self.data);
+    }
+}
+
+/// Nanoseconds of `cm::Time`
+#[cfg(feature = "cm")]
+#[inline]
+pub fn timestamp(time: cm::Time) -> i64 {
+    time.convert_scale(1_000_000_000, cm::TimeRoundingMethod::default())
+        .value
+}
@@ -0,0 +1,183 @@
+//! Matroska and WebM reader of files in memory.
+
+use super::{ebml, ebml::Id, Error, SimpleBlock, TrackConfig};
+
+/// `CuePoint` with a single `CueTrackPositions`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CuePoint {
+    /// In `TimestampScale` ticks
+    pub time: u64,
+    pub track: u64,
+
+    /// Position of `Cluster` relative to `Segment` payload
+    pub cluster_position: u64,
+}
+
+impl CuePoint {
+    pub fn parse(point: &ebml::Element) -> Result<Self, Error> {
+        let time = point
+            .child(Id::CUE_TIME)
+            .ok_or(Error::Missing(Id::CUE_TIME))?
+            .as_uint()?;
+        let positions = point
+            .child(Id::CUE_TRACK_POSITIONS)
+            .ok_or(Error::Missing(Id::CUE_TRACK_POSITIONS))?;
+        let track = positions
+            .child(Id::CUE_TRACK)
+            .ok_or(Error::Missing(Id::CUE_TRACK))?
+            .as_uint()?;
+        let cluster_position = positions
+            .child(Id::CUE_CLUSTER_POSITION)
+            .ok_or(Error::Missing(Id::CUE_CLUSTER_POSITION))?
+            .as_uint()?;
+        Ok(Self {
+            time,
+            track,
+            cluster_position,
+        })
+    }
+
+    /// Appends `CuePoint` element
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let mut positions = Vec::new();
+        ebml::write_uint(&mut positions, Id::CUE_TRACK, self.track);
+        ebml::write_uint(
+            &mut positions,
+            Id::CUE_CLUSTER_POSITION,
+            self.cluster_position,
+        );
+        let mut point = Vec::new();
+        ebml::write_uint(&mut point, Id::CUE_TIME, self.time);
+        ebml::write_element(&mut point, Id::CUE_TRACK_POSITIONS, &positions);
+        ebml::write_element(out, Id::CUE_POINT, &point);
+    }
+}
+
+/// Frame of [`Segment::blocks`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Block<'a> {
+    pub track: u64,
+
+    /// Presentation time in nanoseconds
+    pub pts: i64,
+    pub is_key: bool,
+    pub data: &'a [u8],
+}
+
+/// Top level elements of the first `Segment`
+#[derive(Debug, Clone)]
+pub struct Segment<'a> {
+    /// `webm` or `matroska`
+    pub doc_type: String,
+
+    /// Nanoseconds per tick
+    pub timestamp_scale: u64,
+
+    /// Ticks of `Duration` if known
+    pub duration: Option<f64>,
+    pub tracks: Vec<(u64, TrackConfig)>,
+    pub clusters: Vec<ebml::Element<'a>>,
+    pub cues: Vec<CuePoint>,
+
+    /// `Segment` payload, the base of cluster positions
+    pub data: &'a [u8],
+}
+
+impl<'a> Segment<'a> {
+    /// Reads EBML header and `Segment` of file
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let mut elements = ebml::elements(data);
+        let header = elements.next().ok_or(Error::Missing(Id::EBML))??;
+        if header.id != Id::EBML {
+            return Err(Error::Missing(Id::EBML));
+        }
+        let doc_type = match header.child(Id::DOC_TYPE) {
+            Some(e) => e.as_str()?.into(),
+            None => "matroska".into(),
+        };
+        let segment = elements
+            .map_while(Result::ok)
+            .find(|e| e.id == Id::SEGMENT)
+            .ok_or(Error::Missing(Id::SEGMENT))?;
+
+        let mut res = Self {
+            doc_type,
+            timestamp_scale: super::DEFAULT_TIMESTAMP_SCALE,
+            duration: None,
+            tracks: Vec::new(),
+            clusters: Vec::new(),
+            cues: Vec::new(),
+            data: segment.data,
+        };
+        for e in segment.children() {
+            let e = e?;
+            match e.id {
+                Id::INFO => {
+                    for e in e.children() {
+                        let e = e?;
+                        match e.id {
+                            Id::TIMESTAMP_SCALE => res.timestamp_scale = e.as_uint()?,
+                            Id::DURATION => res.duration = Some(e.as_float()?),
+                            _ => {}
+                        }
+                    }
+                }
+                Id::TRACKS => {
+                    for entry in e.children() {
+                        let entry = entry?;
+                        if entry.id == Id::TRACK_ENTRY {
+                            res.tracks.push(TrackConfig::parse(&entry)?);
+                        }
+                    }
+                }
+                Id::CLUSTER => res.clusters.push(e),
+                Id::CUES => {
+                    for point in e.children() {
+                        let point = point?;
+                        if point.id == Id::CUE_POINT {
+                            res.cues.push(CuePoint::parse(&point)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn track(&self, number: u64) -> Option<&TrackConfig> {
+        self.tracks
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, config)| config)
+    }
+
+    /// `SimpleBlock`s of clusters in order, blocks of `BlockGroup` are skipped
+    pub fn blocks(&self) -> impl Iterator<Item = Result<Block<'a>, Error>> + '_ {
+        let scale = self.timestamp_scale as i64;
+        self.clusters.iter().flat_map(move |cluster| {
+            let timestamp = match cluster.child(Id::TIMESTAMP) {
+                Some(e) => e.as_uint().map(|t| t as i64),
+                None => Err(Error::Missing(Id::TIMESTAMP)),
+            };
+            cluster.children().filter_map(move |e| {
+                let e = match e {
+                    Ok(e) if e.id != Id::SIMPLE_BLOCK => return None,
+                    Ok(e) => e,
+                    Err(err) => return Some(Err(err)),
+                };
+                let timestamp = match timestamp {
+                    Ok(t) => t,
+                    Err(err) => return Some(Err(err)),
+                };
+                Some(SimpleBlock::parse(e.data).map(|block| Block {
+                    track: block.track,
+                    pts: (timestamp + block.timestamp as i64) * scale,
+                    is_key: block.is_key,
+                    data: block.data,
+                }))
+            })
+        })
+    }
+}
@@ -0,0 +1,419 @@
+//! EBML (RFC 8794) variable size integers and elements.
+
+use super::Error;
+
+/// Size of element with unknown size, e.g. of live `Segment` or `Cluster`
+pub const UNKNOWN_SIZE: u64 = (1 << 56) - 1;
+
+/// Element ID with its length marker bits
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Id(pub u32);
+
+impl Id {
+    pub const EBML: Self = Self(0x1a45_dfa3);
+    pub const EBML_VERSION: Self = Self(0x4286);
+    pub const EBML_READ_VERSION: Self = Self(0x42f7);
+    pub const EBML_MAX_ID_LENGTH: Self = Self(0x42f2);
+    pub const EBML_MAX_SIZE_LENGTH: Self = Self(0x42f3);
+    pub const DOC_TYPE: Self = Self(0x4282);
+    pub const DOC_TYPE_VERSION: Self = Self(0x4287);
+    pub const DOC_TYPE_READ_VERSION: Self = Self(0x4285);
+    pub const VOID: Self = Self(0xec);
+    pub const CRC32: Self = Self(0xbf);
+
+    pub const SEGMENT: Self = Self(0x1853_8067);
+    pub const SEEK_HEAD: Self = Self(0x114d_9b74);
+    pub const INFO: Self = Self(0x1549_a966);
+    pub const TIMESTAMP_SCALE: Self = Self(0x2a_d7b1);
+    pub const DURATION: Self = Self(0x4489);
+    pub const MUXING_APP: Self = Self(0x4d80);
+    pub const WRITING_APP: Self = Self(0x5741);
+
+    pub const TRACKS: Self = Self(0x1654_ae6b);
+    pub const TRACK_ENTRY: Self = Self(0xae);
+    pub const TRACK_NUMBER: Self = Self(0xd7);
+    pub const TRACK_UID: Self = Self(0x73c5);
+    pub const TRACK_TYPE: Self = Self(0x83);
+    pub const FLAG_LACING: Self = Self(0x9c);
+    pub const LANGUAGE: Self = Self(0x22_b59c);
+    pub const CODEC_ID: Self = Self(0x86);
+    pub const CODEC_PRIVATE: Self = Self(0x63a2);
+    pub const DEFAULT_DURATION: Self = Self(0x23_e383);
+    pub const CODEC_DELAY: Self = Self(0x56aa);
+    pub const SEEK_PRE_ROLL: Self = Self(0x56bb);
+    pub const VIDEO: Self = Self(0xe0);
+    pub const PIXEL_WIDTH: Self = Self(0xb0);
+    pub const PIXEL_HEIGHT: Self = Self(0xba);
+    pub const AUDIO: Self = Self(0xe1);
+    pub const SAMPLING_FREQUENCY: Self = Self(0xb5);
+    pub const CHANNELS: Self = Self(0x9f);
+    pub const BIT_DEPTH: Self = Self(0x6264);
+
+    pub const CLUSTER: Self = Self(0x1f43_b675);
+    pub const TIMESTAMP: Self = Self(0xe7);
+    pub const SIMPLE_BLOCK: Self = Self(0xa3);
+    pub const BLOCK_GROUP: Self = Self(0xa0);
+    pub const BLOCK: Self = Self(0xa1);
+
+    pub const CUES: Self = Self(0x1c53_bb6b);
+    pub const CUE_POINT: Self = Self(0xbb);
+    pub const CUE_TIME: Self = Self(0xb3);
+    pub const CUE_TRACK_POSITIONS: Self = Self(0xb7);
+    pub const CUE_TRACK: Self = Self(0xf7);
+    pub const CUE_CLUSTER_POSITION: Self = Self(0xf1);
+
+    pub const CHAPTERS: Self = Self(0x1043_a770);
+    pub const TAGS: Self = Self(0x1254_c367);
+    pub const ATTACHMENTS: Self = Self(0x1941_a469);
+
+    /// Children of `Segment`, which end elements of unknown size
+    #[inline]
+    pub fn is_top_level(&self) -> bool {
+        matches!(
+            *self,
+            Self::SEEK_HEAD
+                | Self::INFO
+                | Self::TRACKS
+                | Self::CLUSTER
+                | Self::CUES
+                | Self::CHAPTERS
+                | Self::TAGS
+                | Self::ATTACHMENTS
+                | Self::SEGMENT
+                | Self::EBML
+        )
+    }
+}
+
+impl std::fmt::Debug for Id {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Id({:#x})", self.0)
+    }
+}
+
+/// Length of variable size integer of its first byte
+#[inline]
+fn vint_len(first: u8) -> Result<usize, Error> {
+    match first.leading_zeros() {
+        8 => Err(Error::InvalidValue("variable size integer")),
+        n => Ok(n as usize + 1),
+    }
+}
+
+/// Value and length of variable size integer, all ones are [`UNKNOWN_SIZE`] of any length
+pub fn read_vint(data: &[u8]) -> Result<(u64, usize), Error> {
+    let len = vint_len(*data.first().ok_or(Error::Truncated)?)?;
+    let bytes = data.get(..len).ok_or(Error::Truncated)?;
+    let mut value = bytes[0] as u64 & (0xff >> len);
+    for &b in &bytes[1..] {
+        value = value << 8 | b as u64;
+    }
+    if value == (1 << (7 * len)) - 1 {
+        return Ok((UNKNOWN_SIZE, len));
+    }
+    Ok((value, len))
+}
+
+/// Appends variable size integer of the shortest length, values with all ones
+/// in shorter lengths are written longer
+pub fn write_vint(out: &mut Vec<u8>, value: u64) {
+    let len = (1..=8)
+        .find(|&len| value < (1 << (7 * len)) - 1)
+        .unwrap_or(8);
+    write_vint_len(out, value.min(UNKNOWN_SIZE), len);
+}
+
+/// Appends variable size integer of `len` bytes
+pub fn write_vint_len(out: &mut Vec<u8>, value: u64, len: usize) {
+    let bytes = (value | 1 << (7 * len)).to_be_bytes();
+    out.extend_from_slice(&bytes[8 - len..]);
+}
+
+/// Element ID and its length
+pub fn read_id(data: &[u8]) -> Result<(Id, usize), Error> {
+    let first = *data.first().ok_or(Error::Truncated)?;
+    let len = vint_len(first)?;
+    if len > 4 {
+        return Err(Error::InvalidValue("element ID"));
+    }
+    let bytes = data.get(..len).ok_or(Error::Truncated)?;
+    let id = bytes.iter().fold(0, |id, &b| id << 8 | b as u32);
+    Ok((Id(id), len))
+}
+
+#[inline]
+pub fn write_id(out: &mut Vec<u8>, id: Id) {
+    let len = (4 - id.0.leading_zeros() as usize / 8).max(1);
+    out.extend_from_slice(&id.0.to_be_bytes()[4 - len..]);
+}
+
+/// Element of [`elements`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Element<'a> {
+    pub id: Id,
+
+    /// Header and payload
+    pub raw: &'a [u8],
+
+    /// Payload, elements of unknown size end before the next top level element
+    pub data: &'a [u8],
+    pub unknown_size: bool,
+}
+
+impl<'a> Element<'a> {
+    /// Element at the start of data
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (id, id_len) = read_id(data)?;
+        let (size, size_len) = read_vint(&data[id_len..])?;
+        let header_len = id_len + size_len;
+        let rest = &data[header_len..];
+        let unknown_size = size == UNKNOWN_SIZE;
+        let len = if !unknown_size {
+            usize::try_from(size)
+                .ok()
+                .filter(|&size| size <= rest.len())
+                .ok_or(Error::Truncated)?
+        } else if id == Id::SEGMENT {
+            rest.len()
+        } else {
+            unknown_size_len(rest)?
+        };
+        Ok(Self {
+            id,
+            raw: &data[..header_len + len],
+            data: &rest[..len],
+            unknown_size,
+        })
+    }
+
+    #[inline]
+    pub fn children(&self) -> Elements<'a> {
+        elements(self.data)
+    }
+
+    /// The first child with ID
+    pub fn child(&self, id: Id) -> Option<Element<'a>> {
+        self.children().map_while(Result::ok).find(|e| e.id == id)
+    }
+
+    /// Unsigned integer of up to 8 bytes
+    pub fn as_uint(&self) -> Result<u64, Error> {
+        if self.data.len() > 8 {
+            return Err(Error::InvalidValue("unsigned integer size"));
+        }
+        Ok(self.data.iter().fold(0, |v, &b| v << 8 | b as u64))
+    }
+
+    /// Signed integer of up to 8 bytes
+    pub fn as_int(&self) -> Result<i64, Error> {
+        let value = self.as_uint()?;
+        let shift = 64 - 8 * self.data.len() as u32;
+        Ok(match shift {
+            64 => 0,
+            shift => ((value << shift) as i64) >> shift,
+        })
+    }
+
+    /// Float of 0, 4 or 8 bytes
+    pub fn as_float(&self) -> Result<f64, Error> {
+        match self.data.len() {
+            0 => Ok(0.0),
+            4 => Ok(f32::from_be_bytes(self.data.try_into().unwrap()) as f64),
+            8 => Ok(f64::from_be_bytes(self.data.try_into().unwrap())),
+            _ => Err(Error::InvalidValue("float size")),
+        }
+    }
+
+    /// String without trailing zeros
+    pub fn as_str(&self) -> Result<&'a str, Error> {
+        let end = self
+            .data
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.data.len());
+        std::str::from_utf8(&self.data[..end]).map_err(|_| Error::InvalidValue("UTF-8 string"))
+    }
+}
+
+/// Length of children of unknown size element up to the next top level element
+fn unknown_size_len(data: &[u8]) -> Result<usize, Error> {
+    let mut pos = 0;
+    while pos < data.len() {
+        let (id, _) = read_id(&data[pos..])?;
+        if id.is_top_level() {
+            break;
+        }
+        pos += Element::parse(&data[pos..])?.raw.len();
+    }
+    Ok(pos)
+}
+
+/// Iterator over elements, see [`elements`]
+#[derive(Debug, Clone)]
+pub struct Elements<'a> {
+    rest: &'a [u8],
+}
+
+/// Consecutive elements of file or master element payload.
+///
+/// ```
+/// use cidre::media::mkv::ebml;
+///
+/// let mut out = Vec::new();
+/// ebml::write_uint(&mut out, ebml::Id::TIMESTAMP, 1000);
+/// ebml::write_str(&mut out, ebml::Id::CODEC_ID, "V_VP9");
+/// let elements: Vec<_> = ebml::elements(&out).map(|e| e.unwrap()).collect();
+/// assert_eq!(elements[0].as_uint().unwrap(), 1000);
+/// assert_eq!(elements[1].as_str().unwrap(), "V_VP9");
+/// ```
+#[inline]
+pub fn elements(data: &[u8]) -> Elements<'_> {
+    Elements { rest: data }
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Result<Element<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let res = Element::parse(self.rest);
+        match res {
+            Ok(e) => self.rest = &self.rest[e.raw.len()..],
+            Err(_) => self.rest = &[],
+        }
+        Some(res)
+    }
+}
+
+/// Appends element of payload
+pub fn write_element(out: &mut Vec<u8>, id: Id, payload: &[u8]) {
+    write_id(out, id);
+    write_vint(out, payload.len() as u64);
+    out.extend_from_slice(payload);
+}
+
+/// Appends header of master element with unknown size, its children follow
+pub fn write_unknown_size(out: &mut Vec<u8>, id: Id) {
+    write_id(out, id);
+    write_vint_len(out, UNKNOWN_SIZE, 8);
+}
+
+/// Appends unsigned integer element of the shortest length
+pub fn write_uint(out: &mut Vec<u8>, id: Id, value: u64) {
+    let len = (8 - value.leading_zeros() as usize / 8).max(1);
+    write_element(out, id, &value.to_be_bytes()[8 - len..]);
+}
+
+/// Appends signed integer element of the shortest length
+pub fn write_int(out: &mut Vec<u8>, id: Id, value: i64) {
+    let redundant = if value < 0 {
+        value.leading_ones()
+    } else {
+        value.leading_zeros()
+    };
+    // keep sign bit
+    let len = (8 - (redundant as usize - 1) / 8).max(1);
+    write_element(out, id, &value.to_be_bytes()[8 - len..]);
+}
+
+#[inline]
+pub fn write_float(out: &mut Vec<u8>, id: Id, value: f64) {
+    write_element(out, id, &value.to_be_bytes());
+}
+
+#[inline]
+pub fn write_str(out: &mut Vec<u8>, id: Id, value: &str) {
+    write_element(out, id, value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        elements, read_id, read_vint, write_id, write_int, write_uint, write_unknown_size,
+        write_vint, Element, Id, UNKNOWN_SIZE,
+    };
+    use crate::media::mkv::Error;
+
+    #[test]
+    fn vint() {
+        for (value, bytes) in [
+            (0, &[0x80][..]),
+            (126, &[0xfe]),
+            // 127 is all ones in one byte
+            (127, &[0x40, 0x7f]),
+            (0x3ffe, &[0x7f, 0xfe]),
+            (0x3fff, &[0x20, 0x3f, 0xff]),
+            (
+                UNKNOWN_SIZE,
+                &[0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+        ] {
+            let mut out = Vec::new();
+            write_vint(&mut out, value);
+            assert_eq!(out, bytes);
+            assert_eq!(read_vint(&out).unwrap(), (value, out.len()));
+        }
+        assert_eq!(read_vint(&[0xff]).unwrap(), (UNKNOWN_SIZE, 1));
+        assert_eq!(
+            read_vint(&[0x00]),
+            Err(Error::InvalidValue("variable size integer"))
+        );
+        assert_eq!(read_vint(&[0x40]), Err(Error::Truncated));
+
+        let mut out = Vec::new();
+        write_id(&mut out, Id::SEGMENT);
+        write_id(&mut out, Id::VOID);
+        assert_eq!(out, [0x18, 0x53, 0x80, 0x67, 0xec]);
+        assert_eq!(read_id(&out).unwrap(), (Id::SEGMENT, 4));
+    }
+
+    #[test]
+    fn integers() {
+        for (value, len) in [(0, 1), (255, 1), (256, 2), (u64::MAX, 8)] {
+            let mut out = Vec::new();
+            write_uint(&mut out, Id::TIMESTAMP, value);
+            assert_eq!(out.len(), 2 + len);
+            assert_eq!(Element::parse(&out).unwrap().as_uint().unwrap(), value);
+        }
+        for (value, len) in [
+            (0, 1),
+            (-1, 1),
+            (127, 1),
+            (128, 2),
+            (-128, 1),
+            (-129, 2),
+            (i64::MIN, 8),
+        ] {
+            let mut out = Vec::new();
+            write_int(&mut out, Id::TIMESTAMP, value);
+            assert_eq!(out.len(), 2 + len, "{value}");
+            assert_eq!(Element::parse(&out).unwrap().as_int().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn unknown_size() {
+        let mut out = Vec::new();
+        write_unknown_size(&mut out, Id::SEGMENT);
+        write_unknown_size(&mut out, Id::CLUSTER);
+        write_uint(&mut out, Id::TIMESTAMP, 0);
+        write_unknown_size(&mut out, Id::CLUSTER);
+        write_uint(&mut out, Id::TIMESTAMP, 1);
+
+        let segment = Element::parse(&out).unwrap();
+        assert!(segment.unknown_size);
+        let clusters: Vec<_> = segment.children().map(|e| e.unwrap()).collect();
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].data.len(), 3);
+        let timestamp = clusters[1].child(Id::TIMESTAMP).unwrap();
+        assert_eq!(timestamp.as_uint().unwrap(), 1);
+
+        assert_eq!(
+            elements(&[0xe7, 0x82, 0]).next().unwrap(),
+            Err(Error::Truncated)
+        );
+    }
+}
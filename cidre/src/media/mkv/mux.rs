@@ -0,0 +1,662 @@
+//! Matroska and WebM muxer.
+
+use std::time::Duration;
+
+use super::{ebml, ebml::Id, CodecId, CuePoint, Error, SimpleBlock, TrackType};
+
+#[cfg(feature = "cm")]
+use crate::{cf, cm};
+
+/// Opus decoders need 80 ms of audio before seek point to converge
+const OPUS_SEEK_PRE_ROLL: u64 = 80_000_000;
+
+/// `Video` of `TrackEntry`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct VideoSettings {
+    pub pixel_width: u32,
+    pub pixel_height: u32,
+}
+
+/// `Audio` of `TrackEntry`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AudioSettings {
+    pub sampling_frequency: f64,
+    pub channels: u32,
+
+    /// 0 for compressed audio
+    pub bit_depth: u32,
+}
+
+/// `TrackEntry` of [`Muxer`] or [`super::Segment`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackConfig {
+    pub track_type: TrackType,
+
+    /// One of [`CodecId`] or other Matroska codec ID
+    pub codec_id: String,
+
+    /// `avcC`, `hvcC`, `av1C`, `OpusHead` or `AudioSpecificConfig`
+    pub codec_private: Vec<u8>,
+
+    /// ISO 639-2 code
+    pub language: String,
+
+    /// Nanoseconds, 0 if unknown or variable
+    pub default_duration: u64,
+
+    /// Nanoseconds of decoded samples to discard, e.g. Opus pre-skip
+    pub codec_delay: u64,
+
+    /// Nanoseconds to decode before seek point
+    pub seek_pre_roll: u64,
+    pub video: Option<VideoSettings>,
+    pub audio: Option<AudioSettings>,
+}
+
+impl TrackConfig {
+    pub fn video(codec_id: &str, codec_private: Vec<u8>, width: u32, height: u32) -> Self {
+        Self {
+            track_type: TrackType::VIDEO,
+            codec_id: codec_id.into(),
+            codec_private,
+            language: "und".into(),
+            default_duration: 0,
+            codec_delay: 0,
+            seek_pre_roll: 0,
+            video: Some(VideoSettings {
+                pixel_width: width,
+                pixel_height: height,
+            }),
+            audio: None,
+        }
+    }
+
+    pub fn audio(codec_id: &str, codec_private: Vec<u8>, channels: u32, sample_rate: f64) -> Self {
+        Self {
+            track_type: TrackType::AUDIO,
+            codec_id: codec_id.into(),
+            codec_private,
+            language: "und".into(),
+            default_duration: 0,
+            codec_delay: 0,
+            seek_pre_roll: 0,
+            video: None,
+            audio: Some(AudioSettings {
+                sampling_frequency: sample_rate,
+                channels,
+                bit_depth: 0,
+            }),
+        }
+    }
+
+    /// H.264 track with `avcC` record, frames are length prefixed
+    #[inline]
+    pub fn h264(avcc: Vec<u8>, width: u32, height: u32) -> Self {
+        Self::video(CodecId::H264, avcc, width, height)
+    }
+
+    /// HEVC track with `hvcC` record, frames are length prefixed
+    #[inline]
+    pub fn hevc(hvcc: Vec<u8>, width: u32, height: u32) -> Self {
+        Self::video(CodecId::HEVC, hvcc, width, height)
+    }
+
+    /// AV1 track with `av1C` record, frames are low overhead OBUs
+    #[inline]
+    pub fn av1(av1c: Vec<u8>, width: u32, height: u32) -> Self {
+        Self::video(CodecId::AV1, av1c, width, height)
+    }
+
+    /// VP9 track, codec configuration is in frame headers
+    #[inline]
+    pub fn vp9(width: u32, height: u32) -> Self {
+        Self::video(CodecId::VP9, Vec::new(), width, height)
+    }
+
+    /// Opus track with Ogg `OpusHead` header, pre-skip becomes codec delay
+    pub fn opus(opus_head: &[u8]) -> Option<Self> {
+        let head = opus_head.strip_prefix(b"OpusHead")?;
+        if head.len() < 11 {
+            return None;
+        }
+        let channels = head[1] as u32;
+        let pre_skip = u16::from_le_bytes([head[2], head[3]]) as u64;
+        let mut config = Self::audio(CodecId::OPUS, opus_head.to_vec(), channels, 48_000.0);
+        config.codec_delay = pre_skip * 1_000_000_000 / 48_000;
+        config.seek_pre_roll = OPUS_SEEK_PRE_ROLL;
+        Some(config)
+    }
+
+    /// AAC track with `AudioSpecificConfig`, frames are raw
+    #[inline]
+    pub fn aac(audio_specific_config: &[u8], channels: u32, sample_rate: u32) -> Self {
+        Self::audio(
+            CodecId::AAC,
+            audio_specific_config.to_vec(),
+            channels,
+            sample_rate as f64,
+        )
+    }
+
+    /// Track with codec configuration of video or audio format description
+    /// produced by `vt::CompressionSession` or `at::audio::Codec`
+    #[cfg(feature = "cm")]
+    pub fn with_format_desc(desc: &cm::FormatDesc) -> Option<Self> {
+        match desc.media_type() {
+            cm::MediaType::VIDEO => {
+                let dims = desc.dimensions();
+                let (width, height) = (dims.width as u32, dims.height as u32);
+                let codec = cm::VideoCodec::from_be_bytes(&desc.media_sub_type().to_be_bytes());
+                match codec {
+                    cm::VideoCodec::H264 => {
+                        Some(Self::h264(desc.ext_atom(cf::str!(c"avcC"))?, width, height))
+                    }
+                    cm::VideoCodec::HEVC
+                    | cm::VideoCodec::HEVC_WITH_ALPHA
+                    | cm::VideoCodec::DOLBY_VISION_HEVC => {
+                        Some(Self::hevc(desc.ext_atom(cf::str!(c"hvcC"))?, width, height))
+                    }
+                    cm::VideoCodec::AV1 => {
+                        Some(Self::av1(desc.ext_atom(cf::str!(c"av1C"))?, width, height))
+                    }
+                    cm::VideoCodec::VP9 => Some(Self::vp9(width, height)),
+                    _ => None,
+                }
+            }
+            #[cfg(feature = "cat")]
+            cm::MediaType::AUDIO => {
+                use crate::cat::audio::Format;
+
+                let asbd = desc.stream_basic_desc()?;
+                let cookie = desc.magic_cookie();
+                let channels = asbd.channels_per_frame;
+                match asbd.format {
+                    Format::MPEG4_AAC
+                    | Format::MPEG4_AAC_HE
+                    | Format::MPEG4_AAC_HE_V2
+                    | Format::MPEG4_AAC_LD
+                    | Format::MPEG4_AAC_ELD => {
+                        let cookie = cookie?;
+                        let rate = asbd.sample_rate as u32;
+                        if cookie.first() == Some(&0x03) {
+                            let esds = crate::media::mp4::Esds::parse_payload(cookie).ok()?;
+                            Some(Self::aac(&esds.decoder_specific_info, channels, rate))
+                        } else {
+                            Some(Self::aac(cookie, channels, rate))
+                        }
+                    }
+                    Format::OPUS => Self::opus(cookie?),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// `TrackEntry` payload with track number
+    pub fn to_bytes(&self, number: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        ebml::write_uint(&mut out, Id::TRACK_NUMBER, number);
+        ebml::write_uint(&mut out, Id::TRACK_UID, number);
+        ebml::write_uint(&mut out, Id::TRACK_TYPE, self.track_type.0 as u64);
+        ebml::write_uint(&mut out, Id::FLAG_LACING, 0);
+        ebml::write_str(&mut out, Id::LANGUAGE, &self.language);
+        ebml::write_str(&mut out, Id::CODEC_ID, &self.codec_id);
+        if !self.codec_private.is_empty() {
+            ebml::write_element(&mut out, Id::CODEC_PRIVATE, &self.codec_private);
+        }
+        if self.default_duration != 0 {
+            ebml::write_uint(&mut out, Id::DEFAULT_DURATION, self.default_duration);
+        }
+        if self.codec_delay != 0 {
+            ebml::write_uint(&mut out, Id::CODEC_DELAY, self.codec_delay);
+        }
+        if self.seek_pre_roll != 0 {
+            ebml::write_uint(&mut out, Id::SEEK_PRE_ROLL, self.seek_pre_roll);
+        }
+        if let Some(video) = &self.video {
+            let mut settings = Vec::new();
+            ebml::write_uint(&mut settings, Id::PIXEL_WIDTH, video.pixel_width as u64);
+            ebml::write_uint(&mut settings, Id::PIXEL_HEIGHT, video.pixel_height as u64);
+            ebml::write_element(&mut out, Id::VIDEO, &settings);
+        }
+        if let Some(audio) = &self.audio {
+            let mut settings = Vec::new();
+            ebml::write_float(
+                &mut settings,
+                Id::SAMPLING_FREQUENCY,
+                audio.sampling_frequency,
+            );
+            ebml::write_uint(&mut settings, Id::CHANNELS, audio.channels as u64);
+            if audio.bit_depth != 0 {
+                ebml::write_uint(&mut settings, Id::BIT_DEPTH, audio.bit_depth as u64);
+            }
+            ebml::write_element(&mut out, Id::AUDIO, &settings);
+        }
+        out
+    }
+
+    /// Track number and configuration of `TrackEntry`
+    pub fn parse(entry: &ebml::Element) -> Result<(u64, Self), Error> {
+        let mut number = None;
+        let mut config = Self {
+            track_type: TrackType(0),
+            codec_id: String::new(),
+            codec_private: Vec::new(),
+            // default of Matroska
+            language: "eng".into(),
+            default_duration: 0,
+            codec_delay: 0,
+            seek_pre_roll: 0,
+            video: None,
+            audio: None,
+        };
+        for e in entry.children() {
+            let e = e?;
+            match e.id {
+                Id::TRACK_NUMBER => number = Some(e.as_uint()?),
+                Id::TRACK_TYPE => config.track_type = TrackType(e.as_uint()? as u8),
+                Id::LANGUAGE => config.language = e.as_str()?.into(),
+                Id::CODEC_ID => config.codec_id = e.as_str()?.into(),
+                Id::CODEC_PRIVATE => config.codec_private = e.data.to_vec(),
+                Id::DEFAULT_DURATION => config.default_duration = e.as_uint()?,
+                Id::CODEC_DELAY => config.codec_delay = e.as_uint()?,
+                Id::SEEK_PRE_ROLL => config.seek_pre_roll = e.as_uint()?,
+                Id::VIDEO => {
+                    let mut video = VideoSettings {
+                        pixel_width: 0,
+                        pixel_height: 0,
+                    };
+                    for e in e.children() {
+                        let e = e?;
+                        match e.id {
+                            Id::PIXEL_WIDTH => video.pixel_width = e.as_uint()? as u32,
+                            Id::PIXEL_HEIGHT => video.pixel_height = e.as_uint()? as u32,
+                            _ => {}
+                        }
+                    }
+                    config.video = Some(video);
+                }
+                Id::AUDIO => {
+                    // defaults of Matroska
+                    let mut audio = AudioSettings {
+                        sampling_frequency: 8000.0,
+                        channels: 1,
+                        bit_depth: 0,
+                    };
+                    for e in e.children() {
+                        let e = e?;
+                        match e.id {
+                            Id::SAMPLING_FREQUENCY => audio.sampling_frequency = e.as_float()?,
+                            Id::CHANNELS => audio.channels = e.as_uint()? as u32,
+                            Id::BIT_DEPTH => audio.bit_depth = e.as_uint()? as u32,
+                            _ => {}
+                        }
+                    }
+                    config.audio = Some(audio);
+                }
+                _ => {}
+            }
+        }
+        let number = number.ok_or(Error::Missing(Id::TRACK_NUMBER))?;
+        if config.codec_id.is_empty() {
+            return Err(Error::Missing(Id::CODEC_ID));
+        }
+        Ok((number, config))
+    }
+}
+
+/// Encoded frame with presentation time in nanoseconds, frames are pushed in decode order
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Frame<'a> {
+    pub data: &'a [u8],
+    pub pts: i64,
+    pub is_sync: bool,
+}
+
+impl<'a> Frame<'a> {
+    #[inline]
+    pub const fn new(data: &'a [u8], pts: i64, is_sync: bool) -> Self {
+        Self { data, pts, is_sync }
+    }
+
+    /// Audio packet starting at `sample_time` in samples, e.g. of `at::audio::Codec` output
+    #[inline]
+    pub const fn with_sample_time(data: &'a [u8], sample_time: i64, sample_rate: u32) -> Self {
+        Self::new(data, sample_time * 1_000_000_000 / sample_rate as i64, true)
+    }
+
+    /// Frame with presentation time of `cm::SampleBuf` timing
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn with_timing(data: &'a [u8], timing: &cm::SampleTimingInfo, is_sync: bool) -> Self {
+        Self::new(data, super::timestamp(timing.pts), is_sync)
+    }
+}
+
+/// Options of [`Muxer`]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MuxConfig {
+    /// `webm` for VP8, VP9, AV1, Opus and Vorbis or `matroska`
+    pub doc_type: String,
+
+    /// Nanoseconds per timestamp tick of clusters and blocks
+    pub timestamp_scale: u64,
+
+    /// Minimal cluster duration, clusters are cut at key frames of the first
+    /// video track or frames of the first track without video
+    pub cluster_duration: Duration,
+
+    /// Writes `Cues` of cluster starts on finish
+    pub cues: bool,
+    pub writing_app: String,
+}
+
+impl Default for MuxConfig {
+    fn default() -> Self {
+        Self {
+            doc_type: "webm".into(),
+            timestamp_scale: super::DEFAULT_TIMESTAMP_SCALE,
+            cluster_duration: Duration::from_secs(2),
+            cues: true,
+            writing_app: "cidre".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Cluster {
+    timestamp: i64,
+
+    /// Relative to `Segment` payload
+    position: u64,
+    blocks: Vec<u8>,
+
+    /// Cue points to the cluster, at its first key frame of primary track
+    has_cue: bool,
+}
+
+/// Matroska muxer of live output.
+///
+/// Header is written with `Segment` of unknown size, so file can be played
+/// while being written. Tracks are to be added before the first frame is pushed.
+#[derive(Debug, Clone)]
+pub struct Muxer {
+    config: MuxConfig,
+    tracks: Vec<TrackConfig>,
+
+    cluster: Option<Cluster>,
+
+    /// Bytes of clusters returned so far
+    clusters_len: u64,
+    cues: Vec<CuePoint>,
+}
+
+impl Muxer {
+    pub fn new(config: MuxConfig) -> Self {
+        Self {
+            config,
+            tracks: Vec::new(),
+            cluster: None,
+            clusters_len: 0,
+            cues: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn config(&self) -> &MuxConfig {
+        &self.config
+    }
+
+    /// Returns track number, starting with 1
+    pub fn add_track(&mut self, config: TrackConfig) -> u64 {
+        self.tracks.push(config);
+        self.tracks.len() as u64
+    }
+
+    #[inline]
+    pub fn track(&self, number: u64) -> Option<&TrackConfig> {
+        self.tracks.get((number as usize).wrapping_sub(1))
+    }
+
+    /// Track which drives clusters and cues, the first video track or the first track
+    fn primary(&self) -> u64 {
+        self.tracks
+            .iter()
+            .position(|t| t.track_type == TrackType::VIDEO)
+            .unwrap_or(0) as u64
+            + 1
+    }
+
+    /// EBML header, start of `Segment` of unknown size, `Info` and `Tracks`
+    pub fn header(&self) -> Vec<u8> {
+        let mut ebml_header = Vec::new();
+        ebml::write_uint(&mut ebml_header, Id::EBML_VERSION, 1);
+        ebml::write_uint(&mut ebml_header, Id::EBML_READ_VERSION, 1);
+        ebml::write_uint(&mut ebml_header, Id::EBML_MAX_ID_LENGTH, 4);
+        ebml::write_uint(&mut ebml_header, Id::EBML_MAX_SIZE_LENGTH, 8);
+        ebml::write_str(&mut ebml_header, Id::DOC_TYPE, &self.config.doc_type);
+        ebml::write_uint(&mut ebml_header, Id::DOC_TYPE_VERSION, 4);
+        ebml::write_uint(&mut ebml_header, Id::DOC_TYPE_READ_VERSION, 2);
+
+        let mut out = Vec::new();
+        ebml::write_element(&mut out, Id::EBML, &ebml_header);
+        ebml::write_unknown_size(&mut out, Id::SEGMENT);
+        out.extend(self.segment_header());
+        out
+    }
+
+    /// `Info` and `Tracks`, cluster positions are relative to their start
+    fn segment_header(&self) -> Vec<u8> {
+        let mut info = Vec::new();
+        ebml::write_uint(&mut info, Id::TIMESTAMP_SCALE, self.config.timestamp_scale);
+        ebml::write_str(&mut info, Id::MUXING_APP, "cidre");
+        ebml::write_str(&mut info, Id::WRITING_APP, &self.config.writing_app);
+
+        let mut tracks = Vec::new();
+        for (i, track) in self.tracks.iter().enumerate() {
+            ebml::write_element(&mut tracks, Id::TRACK_ENTRY, &track.to_bytes(i as u64 + 1));
+        }
+
+        let mut out = Vec::new();
+        ebml::write_element(&mut out, Id::INFO, &info);
+        ebml::write_element(&mut out, Id::TRACKS, &tracks);
+        out
+    }
+
+    /// Adds frame to current cluster, returns the previous cluster if the
+    /// frame starts a new one
+    pub fn push(&mut self, track: u64, frame: &Frame) -> Result<Option<Vec<u8>>, Error> {
+        if self.track(track).is_none() {
+            return Err(Error::UnknownTrack(track));
+        }
+        if frame.pts < 0 {
+            return Err(Error::InvalidValue("negative timestamp"));
+        }
+        let scale = self.config.timestamp_scale.max(1) as i64;
+        let timestamp = frame.pts / scale;
+        let is_primary = track == self.primary();
+
+        let mut res = None;
+        if let Some(cluster) = &self.cluster {
+            let relative = timestamp - cluster.timestamp;
+            let elapsed = Duration::from_nanos((relative.max(0) * scale) as u64);
+            let is_cut = is_primary && frame.is_sync && elapsed >= self.config.cluster_duration;
+            if is_cut || i16::try_from(relative).is_err() {
+                res = self.flush();
+            }
+        }
+
+        let position = self.segment_header().len() as u64 + self.clusters_len;
+        let cluster = self.cluster.get_or_insert_with(|| Cluster {
+            timestamp,
+            position,
+            blocks: Vec::new(),
+            has_cue: false,
+        });
+        if self.config.cues && is_primary && frame.is_sync && !cluster.has_cue {
+            cluster.has_cue = true;
+            self.cues.push(CuePoint {
+                time: timestamp as u64,
+                track,
+                cluster_position: cluster.position,
+            });
+        }
+        // frames before cluster start within i16 range are fine, e.g. B frames
+        let relative = i16::try_from(timestamp - cluster.timestamp)
+            .map_err(|_| Error::InvalidValue("block timestamp"))?;
+        SimpleBlock::new(track, relative, frame.is_sync, frame.data).write(&mut cluster.blocks);
+        Ok(res)
+    }
+
+    /// Pushes samples of `cm::SampleBuf`, e.g. output of `vt::CompressionSession`
+    #[cfg(feature = "cm")]
+    pub fn push_sample_buf(
+        &mut self,
+        track: u64,
+        buf: &cm::SampleBuf,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let block = buf
+            .data_buf()
+            .ok_or(Error::InvalidValue("sample buffer data"))?;
+        let mut data = Vec::with_capacity(block.data_len());
+        while data.len() < block.data_len() {
+            let (chunk, _) = block
+                .data_ptr_at(data.len())
+                .map_err(|_| Error::Truncated)?;
+            if chunk.is_empty() {
+                return Err(Error::Truncated);
+            }
+            data.extend_from_slice(chunk);
+        }
+        let is_sync = buf.is_key_frame();
+        let mut res: Option<Vec<u8>> = None;
+        let mut offset = 0;
+        for i in 0..buf.num_samples() {
+            let timing = buf
+                .timing_info(i)
+                .map_err(|_| Error::InvalidValue("sample timing"))?;
+            let size = buf.sample_size(i);
+            let bytes = data.get(offset..offset + size).ok_or(Error::Truncated)?;
+            offset += size;
+            let frame = Frame::with_timing(bytes, &timing, is_sync);
+            if let Some(cluster) = self.push(track, &frame)? {
+                res.get_or_insert_with(Vec::new).extend(cluster);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Writes current cluster
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        let cluster = self.cluster.take()?;
+        let mut payload = Vec::with_capacity(cluster.blocks.len() + 10);
+        ebml::write_uint(&mut payload, Id::TIMESTAMP, cluster.timestamp as u64);
+        payload.extend(cluster.blocks);
+        let mut out = Vec::with_capacity(payload.len() + 12);
+        ebml::write_element(&mut out, Id::CLUSTER, &payload);
+        self.clusters_len += out.len() as u64;
+        Some(out)
+    }
+
+    /// Writes current cluster and `Cues`, the end of file
+    pub fn finish(&mut self) -> Vec<u8> {
+        let mut out = self.flush().unwrap_or_default();
+        if !self.cues.is_empty() {
+            let mut cues = Vec::new();
+            for cue in self.cues.drain(..) {
+                cue.write(&mut cues);
+            }
+            ebml::write_element(&mut out, Id::CUES, &cues);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Frame, MuxConfig, Muxer, TrackConfig};
+    use crate::media::mkv::{ebml, CodecId, Error, Segment, TrackType};
+
+    #[test]
+    fn tracks() {
+        let mut head = b"OpusHead".to_vec();
+        // version, channels, pre-skip 312, 48 kHz, gain, mapping family
+        head.extend_from_slice(&[1, 2, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0, 0, 0]);
+        let opus = TrackConfig::opus(&head).unwrap();
+        assert_eq!(opus.codec_delay, 6_500_000);
+        assert_eq!(opus.seek_pre_roll, 80_000_000);
+        assert_eq!(opus.audio.unwrap().channels, 2);
+        assert!(TrackConfig::opus(b"OpusTags").is_none());
+
+        let h264 = TrackConfig::h264(vec![1, 0x64, 0, 0x1f], 1920, 1080);
+        for (number, config) in [(1, h264), (2, opus)] {
+            let mut out = Vec::new();
+            ebml::write_element(&mut out, ebml::Id::TRACK_ENTRY, &config.to_bytes(number));
+            let entry = ebml::Element::parse(&out).unwrap();
+            assert_eq!(TrackConfig::parse(&entry).unwrap(), (number, config));
+        }
+    }
+
+    #[test]
+    fn clusters() {
+        let mut mux = Muxer::new(MuxConfig {
+            doc_type: "matroska".into(),
+            cluster_duration: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let video = mux.add_track(TrackConfig::h264(vec![1, 0x64, 0, 0x1f], 320, 240));
+        let audio = mux.add_track(TrackConfig::aac(&[0x11, 0x90], 2, 48_000));
+        assert_eq!(mux.track(audio).unwrap().track_type, TrackType::AUDIO);
+        assert_eq!(
+            mux.push(3, &Frame::new(&[], 0, true)),
+            Err(Error::UnknownTrack(3))
+        );
+
+        let mut file = mux.header();
+        let ms = 1_000_000;
+        let frames = [
+            (audio, 0, true),
+            (video, 0, true),
+            // audio doesn't cut clusters when there is video
+            (audio, 120 * ms, true),
+            (video, 150 * ms, false),
+            (video, 200 * ms, true),
+            // beyond i16 range of block timestamp
+            (video, 40_000 * ms, false),
+        ];
+        let mut cut = Vec::new();
+        for (track, pts, is_sync) in frames {
+            let res = mux.push(track, &Frame::new(&[0xab], pts, is_sync)).unwrap();
+            cut.push(res.is_some());
+            file.extend(res.unwrap_or_default());
+        }
+        assert_eq!(cut, [false, false, false, false, true, true]);
+        file.extend(mux.finish());
+
+        let segment = Segment::parse(&file).unwrap();
+        assert_eq!(segment.doc_type, "matroska");
+        assert_eq!(segment.track(video).unwrap().codec_id, CodecId::H264);
+        assert_eq!(segment.clusters.len(), 3);
+
+        // the last cluster starts with a non key frame
+        let cues: Vec<_> = segment.cues.iter().map(|c| c.time).collect();
+        assert_eq!(cues, [0, 200]);
+        for (cue, cluster) in segment.cues.iter().zip(&segment.clusters) {
+            let position = cue.cluster_position as usize;
+            assert_eq!(cluster.raw.as_ptr(), segment.data[position..].as_ptr());
+        }
+
+        let blocks: Vec<_> = segment.blocks().map(|b| b.unwrap()).collect();
+        assert_eq!(blocks.len(), frames.len());
+        for (block, (track, pts, is_sync)) in blocks.iter().zip(frames) {
+            assert_eq!(
+                (block.track, block.pts, block.is_key),
+                (track, pts, is_sync)
+            );
+            assert_eq!(block.data, [0xab]);
+        }
+    }
+}
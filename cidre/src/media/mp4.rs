@@ -0,0 +1,740 @@
+//! ISO base media file format (MP4, fragmented MP4 and CMAF) boxes.
+//!
+//! [`atoms`] walks boxes lazily without copying, so init segments, `.m4s` fragments
+//! and whole files written by `av::AssetWriter` can be inspected in place. Typed
+//! structs decode the boxes needed to validate and index output.
+//!
+//! ```
+//! use cidre::media::mp4;
+//!
+//! let data = [0, 0, 0, 16, b'f', b't', b'y', b'p', b'i', b's', b'o', b'6', 0, 0, 0, 0,
+//!     0, 0, 0, 8, b'm', b'd', b'a', b't'];
+//! let types: Vec<_> = mp4::atoms(&data).map(|a| a.unwrap().atom_type).collect();
+//! assert_eq!(types, [mp4::AtomType::FTYP, mp4::AtomType::MDAT]);
+//! ```
+
+mod moof;
+pub use moof::Emsg;
+pub use moof::Mfhd;
+pub use moof::Moof;
+pub use moof::Sample;
+pub use moof::SampleFlags;
+pub use moof::Sidx;
+pub use moof::SidxRef;
+pub use moof::Tfdt;
+pub use moof::Tfhd;
+pub use moof::Traf;
+pub use moof::Trun;
+pub use moof::TrunSample;
+
+mod moov;
+pub use moov::AudioEntry;
+pub use moov::Esds;
+pub use moov::Ftyp;
+pub use moov::Hdlr;
+pub use moov::Mdhd;
+pub use moov::Moov;
+pub use moov::Mvhd;
+pub use moov::SampleEntry;
+pub use moov::Tkhd;
+pub use moov::Track;
+pub use moov::Trex;
+pub use moov::VisualEntry;
+
+mod mux;
+pub use mux::Edit;
+pub use mux::MuxConfig;
+pub use mux::MuxSample;
+pub use mux::Muxer;
+pub use mux::TrackConfig;
+
+use std::ops::Range;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the box
+    Truncated,
+
+    /// Required child box is missing
+    MissingAtom(AtomType),
+
+    /// Box type differs from the one requested
+    UnexpectedAtom(AtomType),
+
+    /// Field is out of range
+    InvalidValue(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated ISO BMFF box"),
+            Self::MissingAtom(t) => write!(f, "missing {t:?} box"),
+            Self::UnexpectedAtom(t) => write!(f, "unexpected {t:?} box"),
+            Self::InvalidValue(name) => write!(f, "invalid ISO BMFF {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Four character code of box type
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct AtomType(pub u32);
+
+impl AtomType {
+    pub const FTYP: Self = Self::from_be_bytes(b"ftyp");
+    pub const STYP: Self = Self::from_be_bytes(b"styp");
+    pub const MOOV: Self = Self::from_be_bytes(b"moov");
+    pub const MVHD: Self = Self::from_be_bytes(b"mvhd");
+    pub const TRAK: Self = Self::from_be_bytes(b"trak");
+    pub const TKHD: Self = Self::from_be_bytes(b"tkhd");
+    pub const EDTS: Self = Self::from_be_bytes(b"edts");
+    pub const MDIA: Self = Self::from_be_bytes(b"mdia");
+    pub const MDHD: Self = Self::from_be_bytes(b"mdhd");
+    pub const HDLR: Self = Self::from_be_bytes(b"hdlr");
+    pub const MINF: Self = Self::from_be_bytes(b"minf");
+    pub const STBL: Self = Self::from_be_bytes(b"stbl");
+    pub const STSD: Self = Self::from_be_bytes(b"stsd");
+    pub const MVEX: Self = Self::from_be_bytes(b"mvex");
+    pub const TREX: Self = Self::from_be_bytes(b"trex");
+    pub const UDTA: Self = Self::from_be_bytes(b"udta");
+
+    pub const AVCC: Self = Self::from_be_bytes(b"avcC");
+    pub const HVCC: Self = Self::from_be_bytes(b"hvcC");
+    pub const LHVC: Self = Self::from_be_bytes(b"lhvC");
+    pub const AV1C: Self = Self::from_be_bytes(b"av1C");
+    pub const VPCC: Self = Self::from_be_bytes(b"vpcC");
+    pub const ESDS: Self = Self::from_be_bytes(b"esds");
+    pub const DOPS: Self = Self::from_be_bytes(b"dOps");
+    pub const DAC3: Self = Self::from_be_bytes(b"dac3");
+    pub const DEC3: Self = Self::from_be_bytes(b"dec3");
+    pub const COLR: Self = Self::from_be_bytes(b"colr");
+    pub const PASP: Self = Self::from_be_bytes(b"pasp");
+    pub const BTRT: Self = Self::from_be_bytes(b"btrt");
+    pub const MDCV: Self = Self::from_be_bytes(b"mdcv");
+    pub const CLLI: Self = Self::from_be_bytes(b"clli");
+    pub const WAVE: Self = Self::from_be_bytes(b"wave");
+
+    pub const MOOF: Self = Self::from_be_bytes(b"moof");
+    pub const MFHD: Self = Self::from_be_bytes(b"mfhd");
+    pub const TRAF: Self = Self::from_be_bytes(b"traf");
+    pub const TFHD: Self = Self::from_be_bytes(b"tfhd");
+    pub const TFDT: Self = Self::from_be_bytes(b"tfdt");
+    pub const TRUN: Self = Self::from_be_bytes(b"trun");
+    pub const SIDX: Self = Self::from_be_bytes(b"sidx");
+    pub const EMSG: Self = Self::from_be_bytes(b"emsg");
+    pub const PRFT: Self = Self::from_be_bytes(b"prft");
+    pub const MDAT: Self = Self::from_be_bytes(b"mdat");
+    pub const MFRA: Self = Self::from_be_bytes(b"mfra");
+    pub const FREE: Self = Self::from_be_bytes(b"free");
+    pub const UUID: Self = Self::from_be_bytes(b"uuid");
+
+    #[inline]
+    pub const fn from_be_bytes(bytes: &[u8; 4]) -> Self {
+        Self(u32::from_be_bytes(*bytes))
+    }
+
+    #[inline]
+    pub const fn to_be_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+}
+
+impl std::fmt::Debug for AtomType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}'", crate::four_cc_to_string(self.to_be_bytes()))
+    }
+}
+
+/// Big-endian field reader over box payload
+#[derive(Debug, Clone)]
+pub(crate) struct Cursor<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    #[inline]
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        self.data
+    }
+
+    #[inline]
+    pub(crate) fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        let (head, tail) = self.data.split_at_checked(n).ok_or(Error::Truncated)?;
+        self.data = tail;
+        Ok(head)
+    }
+
+    #[inline]
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    #[inline]
+    pub(crate) fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    #[inline]
+    pub(crate) fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub(crate) fn u24(&mut self) -> Result<u32, Error> {
+        let [a, b, c] = self.array()?;
+        Ok(u32::from_be_bytes([0, a, b, c]))
+    }
+
+    #[inline]
+    pub(crate) fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub(crate) fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    /// 32-bit field of version 0 box or 64-bit field of version 1 box
+    #[inline]
+    pub(crate) fn u32_or_u64(&mut self, version: u8) -> Result<u64, Error> {
+        if version == 0 {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn skip(&mut self, n: usize) -> Result<(), Error> {
+        self.bytes(n).map(|_| ())
+    }
+
+    /// Null-terminated UTF-8 string, unterminated string takes the rest
+    pub(crate) fn c_str(&mut self) -> Result<String, Error> {
+        let len = self.data.iter().position(|&b| b == 0);
+        let s = self.bytes(len.unwrap_or(self.data.len()))?;
+        if len.is_some() {
+            self.skip(1)?;
+        }
+        Ok(String::from_utf8_lossy(s).into_owned())
+    }
+}
+
+/// Box (atom) with payload borrowed from the walked data
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Atom<'a> {
+    pub atom_type: AtomType,
+
+    /// Offset of box header from the start of walked data
+    pub offset: usize,
+
+    /// Size of box header including large size and `uuid` user type
+    pub header_len: usize,
+
+    /// Extended type of `uuid` box
+    pub user_type: Option<[u8; 16]>,
+
+    /// Payload after header
+    pub data: &'a [u8],
+}
+
+impl<'a> Atom<'a> {
+    /// Box header and payload range within walked data, e.g. `mdat` byte range
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.payload_range().end
+    }
+
+    #[inline]
+    pub fn payload_range(&self) -> Range<usize> {
+        let start = self.offset + self.header_len;
+        start..start + self.data.len()
+    }
+
+    /// Child boxes of container box
+    #[inline]
+    pub fn children(&self) -> Atoms<'a> {
+        Atoms {
+            data: self.data,
+            base: self.offset + self.header_len,
+        }
+    }
+
+    /// Child boxes which follow fixed fields of sample entry or full box
+    #[inline]
+    pub fn children_after(&self, skip: usize) -> Atoms<'a> {
+        Atoms {
+            data: self.data.get(skip..).unwrap_or_default(),
+            base: self.offset + self.header_len + skip,
+        }
+    }
+
+    /// First child of the type, malformed trailing children are ignored
+    pub fn child(&self, atom_type: AtomType) -> Option<Atom<'a>> {
+        self.children()
+            .map_while(Result::ok)
+            .find(|a| a.atom_type == atom_type)
+    }
+
+    /// Descendant by path of types
+    ///
+    /// ```
+    /// use cidre::media::mp4;
+    ///
+    /// let data = [0, 0, 0, 16, b'm', b'o', b'o', b'v', 0, 0, 0, 8, b't', b'r', b'a', b'k'];
+    /// let moov = mp4::atoms(&data).next().unwrap().unwrap();
+    /// let trak = moov.descendant(&[mp4::AtomType::TRAK]).unwrap();
+    /// assert_eq!(trak.range(), 8..16);
+    /// ```
+    pub fn descendant(&self, path: &[AtomType]) -> Option<Atom<'a>> {
+        path.iter().try_fold(*self, |atom, &t| atom.child(t))
+    }
+
+    /// `version`, `flags` and payload of full box
+    #[inline]
+    pub fn full(&self) -> Result<(u8, u32, &'a [u8]), Error> {
+        let (h, rest) = self.data.split_at_checked(4).ok_or(Error::Truncated)?;
+        Ok((h[0], u32::from_be_bytes([0, h[1], h[2], h[3]]), rest))
+    }
+
+    /// Error if the box type differs from `atom_type`
+    #[inline]
+    pub fn expect(&self, atom_type: AtomType) -> Result<(), Error> {
+        if self.atom_type == atom_type {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedAtom(self.atom_type))
+        }
+    }
+}
+
+/// Lazy iterator over sibling boxes, see [`atoms`]
+#[derive(Debug, Clone)]
+pub struct Atoms<'a> {
+    data: &'a [u8],
+    base: usize,
+}
+
+/// Walks top-level boxes of file or segment.
+///
+/// Box with size 0 extends to the end of data. Iteration stops after the first error.
+#[inline]
+pub fn atoms(data: &[u8]) -> Atoms<'_> {
+    Atoms { data, base: 0 }
+}
+
+impl<'a> Atoms<'a> {
+    fn read(&mut self) -> Result<Atom<'a>, Error> {
+        let mut c = Cursor::new(self.data);
+        let size = c.u32()?;
+        let atom_type = AtomType(c.u32()?);
+        let mut header_len = 8;
+        let size = match size {
+            0 => self.data.len(),
+            1 => {
+                header_len = 16;
+                usize::try_from(c.u64()?).map_err(|_| Error::Truncated)?
+            }
+            size => size as usize,
+        };
+        let mut user_type = None;
+        if atom_type == AtomType::UUID {
+            user_type = Some(c.array()?);
+            header_len += 16;
+        }
+        if size < header_len {
+            return Err(Error::InvalidValue("box size"));
+        }
+        let data = self.data.get(header_len..size).ok_or(Error::Truncated)?;
+        let atom = Atom {
+            atom_type,
+            offset: self.base,
+            header_len,
+            user_type,
+            data,
+        };
+        self.data = &self.data[size..];
+        self.base += size;
+        Ok(atom)
+    }
+
+    /// First box of the type
+    pub fn find(self, atom_type: AtomType) -> Option<Atom<'a>> {
+        self.map_while(Result::ok)
+            .find(|a| a.atom_type == atom_type)
+    }
+}
+
+impl<'a> Iterator for Atoms<'a> {
+    type Item = Result<Atom<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let res = self.read();
+        if res.is_err() {
+            self.data = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Appends box header and payload
+pub(crate) fn write_atom(out: &mut Vec<u8>, atom_type: AtomType, payload: &[u8]) {
+    out.extend_from_slice(&(8 + payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(&atom_type.to_be_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Appends full box header with `version` and `flags` followed by payload
+pub(crate) fn write_full_atom(
+    out: &mut Vec<u8>,
+    atom_type: AtomType,
+    version: u8,
+    flags: u32,
+    payload: &[u8],
+) {
+    out.extend_from_slice(&(12 + payload.len() as u32).to_be_bytes());
+    out.extend_from_slice(&atom_type.to_be_bytes());
+    out.extend_from_slice(&(flags | (version as u32) << 24).to_be_bytes());
+    out.extend_from_slice(payload);
+}
+
+/// Converts time in track or movie timescale
+#[cfg(feature = "cm")]
+#[inline]
+pub fn cm_time(value: i64, timescale: u32) -> crate::cm::Time {
+    crate::cm::Time::new(value, timescale as i32)
+}
+
+/// Init segment or movie header boxes and fragments of media segment.
+///
+/// ```
+/// use cidre::media::mp4;
+///
+/// let segment = mp4::Segment::parse(&[0, 0, 0, 8, b'm', b'd', b'a', b't']).unwrap();
+/// assert!(segment.moov.is_none());
+/// assert_eq!(segment.mdat, [8..8]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment {
+    /// `ftyp` or `styp`
+    pub ftyp: Option<Ftyp>,
+    pub moov: Option<Moov>,
+    pub sidx: Vec<Sidx>,
+    pub emsg: Vec<Emsg>,
+    pub moof: Vec<Moof>,
+
+    /// Payload ranges of `mdat` boxes
+    pub mdat: Vec<Range<usize>>,
+}
+
+impl Segment {
+    /// Decodes top-level boxes, unknown boxes are skipped
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut res = Self::default();
+        for atom in atoms(data) {
+            let atom = atom?;
+            match atom.atom_type {
+                AtomType::FTYP | AtomType::STYP => res.ftyp = Some(Ftyp::parse(&atom)?),
+                AtomType::MOOV => res.moov = Some(Moov::parse(&atom)?),
+                AtomType::SIDX => res.sidx.push(Sidx::parse(&atom)?),
+                AtomType::EMSG => res.emsg.push(Emsg::parse(&atom)?),
+                AtomType::MOOF => res.moof.push(Moof::parse(&atom)?),
+                AtomType::MDAT => res.mdat.push(atom.payload_range()),
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+
+    /// Samples of all fragments with absolute data ranges resolved using `trex` defaults
+    pub fn samples(&self, track_id: u32) -> Result<Vec<Sample>, Error> {
+        let trex = self
+            .moov
+            .as_ref()
+            .and_then(|m| m.trex.iter().find(|t| t.track_id == track_id));
+        let mut res = Vec::new();
+        for moof in &self.moof {
+            for traf in moof.trafs.iter().filter(|t| t.tfhd.track_id == track_id) {
+                res.extend(traf.samples(moof.offset, trex)?);
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(atom_type: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_atom(
+            &mut out,
+            AtomType::from_be_bytes(atom_type),
+            &children.concat(),
+        );
+        out
+    }
+
+    fn full(atom_type: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_full_atom(
+            &mut out,
+            AtomType::from_be_bytes(atom_type),
+            version,
+            flags,
+            payload,
+        );
+        out
+    }
+
+    fn be(fields: &[u32]) -> Vec<u8> {
+        fields.iter().flat_map(|f| f.to_be_bytes()).collect()
+    }
+
+    fn trak(track_id: u32, handler: &[u8; 4], entry: Vec<u8>) -> Vec<u8> {
+        let mut tkhd = be(&[0, 0, track_id, 0, 3000, 0, 0, 0, 0]);
+        tkhd.extend_from_slice(&[0; 36]);
+        tkhd.extend(be(&[1920 << 16, 1080 << 16]));
+        let mut mdhd = be(&[0, 0, 90_000, 0]);
+        // "und"
+        mdhd.extend_from_slice(&[0x55, 0xc4, 0, 0]);
+        let mut hdlr = be(&[0]);
+        hdlr.extend_from_slice(handler);
+        hdlr.extend_from_slice(&[0; 12]);
+        hdlr.extend_from_slice(b"Core Media\0");
+        let mut stsd = be(&[1]);
+        stsd.extend(entry);
+        atom(
+            b"trak",
+            &[
+                full(b"tkhd", 0, 3, &tkhd),
+                atom(
+                    b"mdia",
+                    &[
+                        full(b"mdhd", 0, 0, &mdhd),
+                        full(b"hdlr", 0, 0, &hdlr),
+                        atom(b"minf", &[atom(b"stbl", &[full(b"stsd", 0, 0, &stsd)])]),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    fn init_segment() -> Vec<u8> {
+        let mut avc1 = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        avc1.extend_from_slice(&[0; 16]);
+        avc1.extend_from_slice(&[0x07, 0x80, 0x04, 0x38]);
+        avc1.extend(be(&[0x48_0000, 0x48_0000, 0]));
+        avc1.extend_from_slice(&[0, 1]);
+        let mut name = [0u8; 32];
+        name[0] = 4;
+        name[1..5].copy_from_slice(b"H264");
+        avc1.extend_from_slice(&name);
+        avc1.extend_from_slice(&[0, 0x18, 0xff, 0xff]);
+        avc1.extend(atom(b"avcC", &[vec![1, 0x64, 0, 0x28, 0xff, 0xe0, 0]]));
+
+        let mut mp4a = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        mp4a.extend(be(&[0, 0]));
+        mp4a.extend_from_slice(&[0, 2, 0, 16, 0, 0, 0, 0]);
+        mp4a.extend(be(&[48_000 << 16]));
+        let es = [
+            3, 25, 0, 2, 0, // ES_Descriptor
+            4, 17, 0x40, 0x15, 0, 0, 0, 0, 0, 0xfa, 0, 0, 0, 0xfa, 0, // DecoderConfig
+            5, 2, 0x11, 0x90, // AAC LC 48 kHz stereo
+            6, 1, 2,
+        ];
+        mp4a.extend(full(b"esds", 0, 0, &es));
+
+        let mut mvhd = be(&[0, 0, 1000, 0]);
+        mvhd.extend_from_slice(&[0; 76]);
+        mvhd.extend(be(&[3]));
+        atom(
+            b"moov",
+            &[
+                full(b"mvhd", 0, 0, &mvhd),
+                trak(1, b"vide", atom(b"avc1", &[avc1])),
+                trak(2, b"soun", atom(b"mp4a", &[mp4a])),
+                atom(
+                    b"mvex",
+                    &[
+                        full(b"trex", 0, 0, &be(&[1, 1, 3000, 0, 0x0101_0000])),
+                        full(b"trex", 0, 0, &be(&[2, 1, 1024, 0, 0x0200_0000])),
+                    ],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn walk() {
+        let mut data = atom(b"ftyp", &[b"iso6".to_vec(), be(&[0]), b"cmfc".to_vec()]);
+        data.extend(atom(b"free", &[]));
+        let mut large = vec![0, 0, 0, 1];
+        large.extend_from_slice(b"mdat");
+        large.extend_from_slice(&20u64.to_be_bytes());
+        large.extend_from_slice(&[1, 2, 3, 4]);
+        data.extend(large);
+
+        let atoms: Vec<_> = atoms(&data).collect::<Result<_, _>>().unwrap();
+        assert_eq!(atoms.len(), 3);
+        assert_eq!(atoms[2].header_len, 16);
+        assert_eq!(atoms[2].data, [1, 2, 3, 4]);
+        assert_eq!(atoms[2].range(), 28..48);
+
+        let ftyp = Ftyp::parse(&atoms[0]).unwrap();
+        assert!(ftyp.is_compatible(AtomType::from_be_bytes(b"cmfc")));
+
+        let mut broken = data.clone();
+        broken.extend_from_slice(&[0, 0, 0, 64, b'm', b'o', b'o', b'f']);
+        let res: Vec<_> = super::atoms(&broken).collect();
+        assert_eq!(res.len(), 4);
+        assert_eq!(res[3], Err(Error::Truncated));
+
+        assert_eq!(format!("{:?}", AtomType::MOOV), "'moov'");
+    }
+
+    #[test]
+    fn moov() {
+        let data = init_segment();
+        let segment = Segment::parse(&data).unwrap();
+        let moov = segment.moov.unwrap();
+        assert_eq!(moov.mvhd.timescale, 1000);
+        assert_eq!(moov.mvhd.next_track_id, 3);
+        assert!(moov.is_fragmented());
+        assert_eq!(moov.tracks.len(), 2);
+
+        let video = moov.track(1).unwrap();
+        assert!(video.tkhd.enabled);
+        assert_eq!(video.tkhd.size(), (1920.0, 1080.0));
+        assert_eq!(video.timescale(), 90_000);
+        assert_eq!(&video.mdhd.language, b"und");
+        assert_eq!(video.hdlr.handler_type, Hdlr::VIDE);
+        assert_eq!(video.hdlr.name, "Core Media");
+        let entry = &video.sample_entries[0];
+        assert_eq!(entry.format, AtomType::from_be_bytes(b"avc1"));
+        assert_eq!(entry.data_reference_index, 1);
+        let visual = entry.visual.as_ref().unwrap();
+        assert_eq!((visual.width, visual.height), (1920, 1080));
+        assert_eq!(visual.compressor_name, "H264");
+        assert_eq!(visual.depth, 24);
+        assert_eq!(
+            entry.extension(AtomType::AVCC),
+            Some(&[1, 0x64, 0, 0x28, 0xff, 0xe0, 0][..])
+        );
+
+        let audio = moov.track(2).unwrap();
+        let entry = &audio.sample_entries[0];
+        let fields = entry.audio.unwrap();
+        assert_eq!(fields.channel_count, 2);
+        assert_eq!(fields.sample_size, 16);
+        assert_eq!(fields.sample_rate, 48_000.0);
+        let esds = entry.esds().unwrap().unwrap();
+        assert_eq!(esds.es_id, 2);
+        assert_eq!(esds.object_type_indication, Esds::OBJECT_TYPE_MPEG4_AUDIO);
+        assert_eq!(esds.stream_type, 5);
+        assert_eq!(esds.avg_bitrate, 64_000);
+        assert_eq!(esds.decoder_specific_info, [0x11, 0x90]);
+        assert_eq!(esds.audio_object_type(), Some(2));
+
+        assert_eq!(moov.trex[1].default_sample_duration, 1024);
+        assert!(moov.trex[1].default_sample_flags.is_sync());
+    }
+
+    #[test]
+    fn fragments() {
+        let mut data = init_segment();
+        let moof_offset = data.len();
+
+        let tfhd = full(b"tfhd", 0, 0x2_0000 | 0x20, &be(&[1, 0x0101_0000]));
+        let tfdt = full(b"tfdt", 1, 0, &180_000u64.to_be_bytes());
+        // data offset, first sample flags, sizes and composition offsets
+        let trun = full(
+            b"trun",
+            0,
+            0x1 | 0x4 | 0x200 | 0x800,
+            &be(&[3, 0, 0x0200_0000, 5, 3000, 3, 0, 4, 0xffff_f448]),
+        );
+        let tfhd_audio = full(b"tfhd", 0, 0x2_0000 | 0x10, &be(&[2, 6]));
+        let trun_audio = full(b"trun", 0, 0, &be(&[2]));
+        let moof_len = 8
+            + 16
+            + (8 + tfhd.len() + tfdt.len() + trun.len())
+            + (8 + tfhd_audio.len() + trun_audio.len());
+        let mut trun = trun;
+        trun[16..20].copy_from_slice(&(moof_len as u32 + 8).to_be_bytes());
+        let moof = atom(
+            b"moof",
+            &[
+                full(b"mfhd", 0, 0, &be(&[7])),
+                atom(b"traf", &[tfhd, tfdt, trun]),
+                atom(b"traf", &[tfhd_audio, trun_audio]),
+            ],
+        );
+        assert_eq!(moof.len(), moof_len);
+        data.extend(moof);
+        data.extend(atom(b"mdat", &[vec![0; 12]]));
+
+        let segment = Segment::parse(&data).unwrap();
+        assert_eq!(segment.moof.len(), 1);
+        assert_eq!(segment.moof[0].offset, moof_offset);
+        assert_eq!(segment.moof[0].mfhd.sequence_number, 7);
+        assert_eq!(segment.mdat.len(), 1);
+        assert_eq!(segment.mdat[0], moof_offset + moof_len + 8..data.len());
+
+        let samples = segment.samples(1).unwrap();
+        let start = (moof_offset + moof_len + 8) as u64;
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].range(), start..start + 5);
+        assert_eq!(samples[1].range(), start + 5..start + 8);
+        assert_eq!(samples[2].offset, start + 8);
+        assert_eq!(samples[2].decode_time, 180_000 + 6000);
+        assert_eq!(samples[1].pts(), 183_000);
+        assert_eq!(samples[2].pts(), 186_000 - 3000);
+        assert!(samples[0].is_sync());
+        assert!(!samples[1].is_sync());
+
+        // no data offset, continues from moof start
+        let audio = segment.samples(2).unwrap();
+        assert_eq!(audio.len(), 2);
+        assert_eq!(audio[1].offset, moof_offset as u64 + 6);
+        assert_eq!(audio[1].decode_time, 1024);
+    }
+
+    #[test]
+    fn emsg() {
+        let mut v0 = b"urn:scte:scte35:2013:bin\0\0".to_vec();
+        v0.extend(be(&[90_000, 900, 0xffff_ffff, 1]));
+        v0.extend_from_slice(&[0xfc]);
+        let data = full(b"emsg", 0, 0, &v0);
+        let emsg = Emsg::parse(&atoms(&data).next().unwrap().unwrap()).unwrap();
+        assert_eq!(emsg.scheme_id_uri, "urn:scte:scte35:2013:bin");
+        assert_eq!(emsg.value, "");
+        assert_eq!(emsg.presentation_time, 900);
+        assert_eq!(emsg.event_duration, u32::MAX);
+        assert_eq!(emsg.message_data, [0xfc]);
+
+        let mut v1 = be(&[1000]);
+        v1.extend_from_slice(&5_000_000_000u64.to_be_bytes());
+        v1.extend(be(&[0, 2]));
+        v1.extend_from_slice(b"https://aomedia.org/emsg/ID3\0x\0ID3");
+        let data = full(b"emsg", 1, 0, &v1);
+        let emsg = Emsg::parse(&atoms(&data).next().unwrap().unwrap()).unwrap();
+        assert_eq!(emsg.presentation_time, 5_000_000_000);
+        assert_eq!(emsg.id, 2);
+        assert_eq!(emsg.value, "x");
+        assert_eq!(emsg.message_data, b"ID3");
+    }
+}
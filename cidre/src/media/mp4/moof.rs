@@ -0,0 +1,525 @@
+use super::{Atom, AtomType, Cursor, Error, Trex};
+
+/// Sample flags of `trex`, `tfhd` and `trun`
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct SampleFlags(pub u32);
+
+impl SampleFlags {
+    /// Independently decodable sync sample
+    pub const SYNC: Self = Self(0x0200_0000);
+
+    /// Sample which depends on others and is not a sync sample
+    pub const NON_SYNC: Self = Self(0x0101_0000);
+
+    #[inline]
+    pub const fn is_leading(self) -> u8 {
+        (self.0 >> 26 & 3) as u8
+    }
+
+    /// 1 if sample depends on others, 2 if it does not
+    #[inline]
+    pub const fn depends_on(self) -> u8 {
+        (self.0 >> 24 & 3) as u8
+    }
+
+    /// 1 if other samples depend on this one, 2 if it is disposable
+    #[inline]
+    pub const fn is_depended_on(self) -> u8 {
+        (self.0 >> 22 & 3) as u8
+    }
+
+    #[inline]
+    pub const fn has_redundancy(self) -> u8 {
+        (self.0 >> 20 & 3) as u8
+    }
+
+    #[inline]
+    pub const fn padding(self) -> u8 {
+        (self.0 >> 17 & 7) as u8
+    }
+
+    #[inline]
+    pub const fn is_non_sync(self) -> bool {
+        self.0 & 0x1_0000 != 0
+    }
+
+    #[inline]
+    pub const fn degradation_priority(self) -> u16 {
+        self.0 as u16
+    }
+
+    #[inline]
+    pub const fn is_sync(self) -> bool {
+        !self.is_non_sync()
+    }
+}
+
+/// `mfhd`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Mfhd {
+    pub sequence_number: u32,
+}
+
+impl Mfhd {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, _, data) = atom.full()?;
+        Ok(Self {
+            sequence_number: Cursor::new(data).u32()?,
+        })
+    }
+}
+
+/// `tfhd`
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Tfhd {
+    pub track_id: u32,
+    pub base_data_offset: Option<u64>,
+    pub sample_description_index: Option<u32>,
+    pub default_sample_duration: Option<u32>,
+    pub default_sample_size: Option<u32>,
+    pub default_sample_flags: Option<SampleFlags>,
+    pub duration_is_empty: bool,
+    pub default_base_is_moof: bool,
+}
+
+impl Tfhd {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, flags, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let mut res = Self {
+            track_id: c.u32()?,
+            duration_is_empty: flags & 0x1_0000 != 0,
+            default_base_is_moof: flags & 0x2_0000 != 0,
+            ..Default::default()
+        };
+        if flags & 0x1 != 0 {
+            res.base_data_offset = Some(c.u64()?);
+        }
+        if flags & 0x2 != 0 {
+            res.sample_description_index = Some(c.u32()?);
+        }
+        if flags & 0x8 != 0 {
+            res.default_sample_duration = Some(c.u32()?);
+        }
+        if flags & 0x10 != 0 {
+            res.default_sample_size = Some(c.u32()?);
+        }
+        if flags & 0x20 != 0 {
+            res.default_sample_flags = Some(SampleFlags(c.u32()?));
+        }
+        Ok(res)
+    }
+}
+
+/// `tfdt`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Tfdt {
+    /// Decode time of the first sample in track timescale
+    pub base_media_decode_time: u64,
+}
+
+impl Tfdt {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, _, data) = atom.full()?;
+        Ok(Self {
+            base_media_decode_time: Cursor::new(data).u32_or_u64(version)?,
+        })
+    }
+}
+
+/// Per-sample fields of `trun`, absent fields fall back to `tfhd` and `trex` defaults
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct TrunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<SampleFlags>,
+    pub composition_time_offset: Option<i32>,
+}
+
+/// `trun`
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Trun {
+    /// Offset of sample data from the base data offset
+    pub data_offset: Option<i32>,
+    pub first_sample_flags: Option<SampleFlags>,
+    pub samples: Vec<TrunSample>,
+}
+
+impl Trun {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, flags, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let count = c.u32()? as usize;
+        let mut res = Self::default();
+        if flags & 0x1 != 0 {
+            res.data_offset = Some(c.u32()? as i32);
+        }
+        if flags & 0x4 != 0 {
+            res.first_sample_flags = Some(SampleFlags(c.u32()?));
+        }
+        let per_sample = (flags >> 8 & 0xf).count_ones() as usize * 4;
+        if per_sample * count > c.rest().len() {
+            return Err(Error::Truncated);
+        }
+        res.samples.reserve_exact(count);
+        for _ in 0..count {
+            let mut s = TrunSample::default();
+            if flags & 0x100 != 0 {
+                s.duration = Some(c.u32()?);
+            }
+            if flags & 0x200 != 0 {
+                s.size = Some(c.u32()?);
+            }
+            if flags & 0x400 != 0 {
+                s.flags = Some(SampleFlags(c.u32()?));
+            }
+            if flags & 0x800 != 0 {
+                // version 0 offsets are unsigned but writers rely on wrapping
+                s.composition_time_offset = Some(c.u32()? as i32);
+            }
+            res.samples.push(s);
+        }
+        Ok(res)
+    }
+}
+
+/// Sample of track fragment with resolved data range and timing
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Sample {
+    /// Offset of sample data from the start of parsed data
+    pub offset: u64,
+    pub size: u32,
+
+    /// Decode time in track timescale
+    pub decode_time: u64,
+    pub duration: u32,
+    pub composition_offset: i32,
+    pub flags: SampleFlags,
+}
+
+impl Sample {
+    /// Presentation time in track timescale
+    #[inline]
+    pub fn pts(&self) -> i64 {
+        self.decode_time as i64 + self.composition_offset as i64
+    }
+
+    #[inline]
+    pub fn range(&self) -> std::ops::Range<u64> {
+        self.offset..self.offset + self.size as u64
+    }
+
+    #[inline]
+    pub fn is_sync(&self) -> bool {
+        self.flags.is_sync()
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_decode_time(&self, timescale: u32) -> crate::cm::Time {
+        super::cm_time(self.decode_time as i64, timescale)
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_pts(&self, timescale: u32) -> crate::cm::Time {
+        super::cm_time(self.pts(), timescale)
+    }
+}
+
+/// `traf`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Traf {
+    pub tfhd: Tfhd,
+    pub tfdt: Option<Tfdt>,
+    pub truns: Vec<Trun>,
+}
+
+impl Traf {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        atom.expect(AtomType::TRAF)?;
+        let mut tfhd = None;
+        let mut tfdt = None;
+        let mut truns = Vec::new();
+        for child in atom.children() {
+            let child = child?;
+            match child.atom_type {
+                AtomType::TFHD => tfhd = Some(Tfhd::parse(&child)?),
+                AtomType::TFDT => tfdt = Some(Tfdt::parse(&child)?),
+                AtomType::TRUN => truns.push(Trun::parse(&child)?),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            tfhd: tfhd.ok_or(Error::MissingAtom(AtomType::TFHD))?,
+            tfdt,
+            truns,
+        })
+    }
+
+    /// Resolves samples of all runs.
+    ///
+    /// Data offsets are relative to `base_data_offset` or to the enclosing `moof`
+    /// at `moof_offset`, as CMAF requires. Run without data offset continues after
+    /// the previous one.
+    pub fn samples(&self, moof_offset: usize, trex: Option<&Trex>) -> Result<Vec<Sample>, Error> {
+        let base = self.tfhd.base_data_offset.unwrap_or(moof_offset as u64);
+        let mut offset = base;
+        let mut decode_time = self.tfdt.map_or(0, |t| t.base_media_decode_time);
+        let default_duration = self
+            .tfhd
+            .default_sample_duration
+            .or(trex.map(|t| t.default_sample_duration));
+        let default_size = self
+            .tfhd
+            .default_sample_size
+            .or(trex.map(|t| t.default_sample_size));
+        let default_flags = self
+            .tfhd
+            .default_sample_flags
+            .or(trex.map(|t| t.default_sample_flags));
+
+        let mut res = Vec::with_capacity(self.truns.iter().map(|t| t.samples.len()).sum());
+        for trun in &self.truns {
+            if let Some(data_offset) = trun.data_offset {
+                offset = base
+                    .checked_add_signed(data_offset as i64)
+                    .ok_or(Error::InvalidValue("trun data offset"))?;
+            }
+            for (i, s) in trun.samples.iter().enumerate() {
+                let size = s
+                    .size
+                    .or(default_size)
+                    .ok_or(Error::InvalidValue("sample size"))?;
+                let duration = s.duration.or(default_duration).unwrap_or(0);
+                let flags = match trun.first_sample_flags {
+                    Some(flags) if i == 0 => flags,
+                    _ => s.flags.or(default_flags).unwrap_or_default(),
+                };
+                res.push(Sample {
+                    offset,
+                    size,
+                    decode_time,
+                    duration,
+                    composition_offset: s.composition_time_offset.unwrap_or(0),
+                    flags,
+                });
+                offset += size as u64;
+                decode_time += duration as u64;
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// `moof`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Moof {
+    /// Offset of `moof` box from the start of parsed data
+    pub offset: usize,
+    pub mfhd: Mfhd,
+    pub trafs: Vec<Traf>,
+}
+
+impl Moof {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        atom.expect(AtomType::MOOF)?;
+        let mut mfhd = None;
+        let mut trafs = Vec::new();
+        for child in atom.children() {
+            let child = child?;
+            match child.atom_type {
+                AtomType::MFHD => mfhd = Some(Mfhd::parse(&child)?),
+                AtomType::TRAF => trafs.push(Traf::parse(&child)?),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            offset: atom.offset,
+            mfhd: mfhd.ok_or(Error::MissingAtom(AtomType::MFHD))?,
+            trafs,
+        })
+    }
+}
+
+/// Reference of segment index
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SidxRef {
+    /// Reference to another `sidx` instead of media
+    pub reference_type: bool,
+
+    /// 31-bit size in bytes
+    pub referenced_size: u32,
+    pub subsegment_duration: u32,
+    pub starts_with_sap: bool,
+
+    /// 3-bit SAP type, 1 or 2 for IDR
+    pub sap_type: u8,
+
+    /// 28-bit delta of SAP presentation time
+    pub sap_delta_time: u32,
+}
+
+/// `sidx` segment index
+///
+/// ```
+/// use cidre::media::mp4;
+///
+/// let sidx = mp4::Sidx {
+///     version: 0,
+///     reference_id: 1,
+///     timescale: 90_000,
+///     earliest_presentation_time: 0,
+///     first_offset: 0,
+///     references: vec![mp4::SidxRef {
+///         reference_type: false,
+///         referenced_size: 1024,
+///         subsegment_duration: 180_000,
+///         starts_with_sap: true,
+///         sap_type: 1,
+///         sap_delta_time: 0,
+///     }],
+/// };
+/// let bytes = sidx.to_bytes();
+/// let atom = mp4::atoms(&bytes).next().unwrap().unwrap();
+/// assert_eq!(mp4::Sidx::parse(&atom).unwrap(), sidx);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Sidx {
+    pub version: u8,
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+
+    /// Distance from the end of `sidx` to the first referenced byte
+    pub first_offset: u64,
+    pub references: Vec<SidxRef>,
+}
+
+impl Sidx {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let reference_id = c.u32()?;
+        let timescale = c.u32()?;
+        let earliest_presentation_time = c.u32_or_u64(version)?;
+        let first_offset = c.u32_or_u64(version)?;
+        c.skip(2)?;
+        let count = c.u16()? as usize;
+        if count * 12 > c.rest().len() {
+            return Err(Error::Truncated);
+        }
+        let mut references = Vec::with_capacity(count);
+        for _ in 0..count {
+            let a = c.u32()?;
+            let subsegment_duration = c.u32()?;
+            let b = c.u32()?;
+            references.push(SidxRef {
+                reference_type: a >> 31 != 0,
+                referenced_size: a & 0x7fff_ffff,
+                subsegment_duration,
+                starts_with_sap: b >> 31 != 0,
+                sap_type: (b >> 28 & 7) as u8,
+                sap_delta_time: b & 0x0fff_ffff,
+            });
+        }
+        Ok(Self {
+            version,
+            reference_id,
+            timescale,
+            earliest_presentation_time,
+            first_offset,
+            references,
+        })
+    }
+
+    /// Whole box with header, version 1 is written if times do not fit into 32 bits
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let version = if self.version == 0
+            && self.earliest_presentation_time <= u32::MAX as u64
+            && self.first_offset <= u32::MAX as u64
+        {
+            0
+        } else {
+            1
+        };
+        let mut payload = Vec::with_capacity(28 + self.references.len() * 12);
+        payload.extend_from_slice(&self.reference_id.to_be_bytes());
+        payload.extend_from_slice(&self.timescale.to_be_bytes());
+        if version == 0 {
+            payload.extend_from_slice(&(self.earliest_presentation_time as u32).to_be_bytes());
+            payload.extend_from_slice(&(self.first_offset as u32).to_be_bytes());
+        } else {
+            payload.extend_from_slice(&self.earliest_presentation_time.to_be_bytes());
+            payload.extend_from_slice(&self.first_offset.to_be_bytes());
+        }
+        payload.extend_from_slice(&[0, 0]);
+        payload.extend_from_slice(&(self.references.len() as u16).to_be_bytes());
+        for r in &self.references {
+            let a = (r.reference_type as u32) << 31 | r.referenced_size & 0x7fff_ffff;
+            let b = (r.starts_with_sap as u32) << 31
+                | (r.sap_type as u32 & 7) << 28
+                | r.sap_delta_time & 0x0fff_ffff;
+            payload.extend_from_slice(&a.to_be_bytes());
+            payload.extend_from_slice(&r.subsegment_duration.to_be_bytes());
+            payload.extend_from_slice(&b.to_be_bytes());
+        }
+        let mut res = Vec::with_capacity(12 + payload.len());
+        super::write_full_atom(&mut res, AtomType::SIDX, version, 0, &payload);
+        res
+    }
+}
+
+/// `emsg` event message
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Emsg {
+    pub version: u8,
+    pub scheme_id_uri: String,
+    pub value: String,
+    pub timescale: u32,
+
+    /// Absolute time for version 1, delta from segment start for version 0
+    pub presentation_time: u64,
+    pub event_duration: u32,
+    pub id: u32,
+    pub message_data: Vec<u8>,
+}
+
+impl Emsg {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let mut res = if version == 0 {
+            let scheme_id_uri = c.c_str()?;
+            let value = c.c_str()?;
+            Self {
+                version,
+                scheme_id_uri,
+                value,
+                timescale: c.u32()?,
+                presentation_time: c.u32()? as u64,
+                event_duration: c.u32()?,
+                id: c.u32()?,
+                message_data: Vec::new(),
+            }
+        } else {
+            let timescale = c.u32()?;
+            let presentation_time = c.u64()?;
+            let event_duration = c.u32()?;
+            let id = c.u32()?;
+            Self {
+                version,
+                scheme_id_uri: c.c_str()?,
+                value: c.c_str()?,
+                timescale,
+                presentation_time,
+                event_duration,
+                id,
+                message_data: Vec::new(),
+            }
+        };
+        res.message_data = c.rest().to_vec();
+        Ok(res)
+    }
+}
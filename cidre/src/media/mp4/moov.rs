@@ -0,0 +1,725 @@
+use crate::media::{av1, h264, hevc, vp9};
+
+use super::{Atom, AtomType, Cursor, Error, SampleFlags};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+#[cfg(feature = "cat")]
+use crate::cat;
+
+/// `ftyp` or `styp`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Ftyp {
+    pub major_brand: AtomType,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<AtomType>,
+}
+
+impl Ftyp {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let mut c = Cursor::new(atom.data);
+        let major_brand = AtomType(c.u32()?);
+        let minor_version = c.u32()?;
+        let compatible_brands = c
+            .rest()
+            .chunks_exact(4)
+            .map(|b| AtomType(u32::from_be_bytes(b.try_into().unwrap())))
+            .collect();
+        Ok(Self {
+            major_brand,
+            minor_version,
+            compatible_brands,
+        })
+    }
+
+    #[inline]
+    pub fn is_compatible(&self, brand: AtomType) -> bool {
+        self.major_brand == brand || self.compatible_brands.contains(&brand)
+    }
+}
+
+/// `mvhd`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Mvhd {
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+    pub next_track_id: u32,
+}
+
+impl Mvhd {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let creation_time = c.u32_or_u64(version)?;
+        let modification_time = c.u32_or_u64(version)?;
+        let timescale = c.u32()?;
+        let duration = c.u32_or_u64(version)?;
+        // rate, volume, reserved, matrix, pre_defined
+        c.skip(4 + 2 + 10 + 36 + 24)?;
+        Ok(Self {
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            next_track_id: c.u32()?,
+        })
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_duration(&self) -> cm::Time {
+        super::cm_time(self.duration as i64, self.timescale)
+    }
+}
+
+/// `tkhd`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Tkhd {
+    pub enabled: bool,
+    pub track_id: u32,
+
+    /// Duration in movie timescale
+    pub duration: u64,
+    pub layer: i16,
+    pub alternate_group: i16,
+
+    /// 8.8 fixed point, 0x0100 for audio
+    pub volume: u16,
+
+    /// 16.16 fixed point
+    pub width: u32,
+
+    /// 16.16 fixed point
+    pub height: u32,
+}
+
+impl Tkhd {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, flags, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        c.u32_or_u64(version)?;
+        c.u32_or_u64(version)?;
+        let track_id = c.u32()?;
+        c.skip(4)?;
+        let duration = c.u32_or_u64(version)?;
+        c.skip(8)?;
+        let layer = c.u16()? as i16;
+        let alternate_group = c.u16()? as i16;
+        let volume = c.u16()?;
+        c.skip(2 + 36)?;
+        Ok(Self {
+            enabled: flags & 1 != 0,
+            track_id,
+            duration,
+            layer,
+            alternate_group,
+            volume,
+            width: c.u32()?,
+            height: c.u32()?,
+        })
+    }
+
+    /// Presentation size in pixels
+    #[inline]
+    pub fn size(&self) -> (f64, f64) {
+        (self.width as f64 / 65536.0, self.height as f64 / 65536.0)
+    }
+}
+
+/// `mdhd`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Mdhd {
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+
+    /// ISO 639-2/T code, `und` if not specified
+    pub language: [u8; 3],
+}
+
+impl Mdhd {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (version, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        let creation_time = c.u32_or_u64(version)?;
+        let modification_time = c.u32_or_u64(version)?;
+        let timescale = c.u32()?;
+        let duration = c.u32_or_u64(version)?;
+        let lang = c.u16()?;
+        let ch = |shift: u16| ((lang >> shift) & 0x1f) as u8 + 0x60;
+        Ok(Self {
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            language: [ch(10), ch(5), ch(0)],
+        })
+    }
+
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_duration(&self) -> cm::Time {
+        super::cm_time(self.duration as i64, self.timescale)
+    }
+}
+
+/// `hdlr`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Hdlr {
+    pub handler_type: AtomType,
+    pub name: String,
+}
+
+impl Hdlr {
+    pub const VIDE: AtomType = AtomType::from_be_bytes(b"vide");
+    pub const SOUN: AtomType = AtomType::from_be_bytes(b"soun");
+    pub const TEXT: AtomType = AtomType::from_be_bytes(b"text");
+    pub const SUBT: AtomType = AtomType::from_be_bytes(b"subt");
+    pub const CLCP: AtomType = AtomType::from_be_bytes(b"clcp");
+    pub const META: AtomType = AtomType::from_be_bytes(b"meta");
+
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        c.skip(4)?;
+        let handler_type = AtomType(c.u32()?);
+        c.skip(12)?;
+        Ok(Self {
+            handler_type,
+            name: c.c_str()?,
+        })
+    }
+}
+
+/// Fields of `VisualSampleEntry`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct VisualEntry {
+    pub width: u16,
+    pub height: u16,
+    pub compressor_name: String,
+    pub depth: u16,
+}
+
+/// Fields of `AudioSampleEntry` including QuickTime sound description versions 1 and 2
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AudioEntry {
+    pub channel_count: u32,
+    pub sample_size: u16,
+    pub sample_rate: f64,
+}
+
+/// `esds` elementary stream descriptor
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Esds {
+    pub es_id: u16,
+    pub object_type_indication: u8,
+    pub stream_type: u8,
+    pub buffer_size: u32,
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+
+    /// `AudioSpecificConfig` for AAC
+    pub decoder_specific_info: Vec<u8>,
+}
+
+impl Esds {
+    /// MPEG-4 audio
+    pub const OBJECT_TYPE_MPEG4_AUDIO: u8 = 0x40;
+
+    /// MPEG-1 audio, layer 3 included
+    pub const OBJECT_TYPE_MPEG1_AUDIO: u8 = 0x6b;
+
+    pub const OBJECT_TYPE_MPEG2_AUDIO: u8 = 0x69;
+
+    fn read_descriptor<'a>(c: &mut Cursor<'a>) -> Result<(u8, &'a [u8]), Error> {
+        let tag = c.u8()?;
+        let mut len = 0usize;
+        for _ in 0..4 {
+            let b = c.u8()?;
+            len = len << 7 | (b & 0x7f) as usize;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok((tag, c.bytes(len)?))
+    }
+
+    /// Parses `esds` full box payload after version and flags
+    pub fn parse_payload(data: &[u8]) -> Result<Self, Error> {
+        let mut c = Cursor::new(data);
+        let (tag, es) = Self::read_descriptor(&mut c)?;
+        if tag != 0x03 {
+            return Err(Error::InvalidValue("ES_Descriptor tag"));
+        }
+        let mut c = Cursor::new(es);
+        let es_id = c.u16()?;
+        let flags = c.u8()?;
+        if flags & 0x80 != 0 {
+            c.skip(2)?;
+        }
+        if flags & 0x40 != 0 {
+            let len = c.u8()? as usize;
+            c.skip(len)?;
+        }
+        if flags & 0x20 != 0 {
+            c.skip(2)?;
+        }
+        let (tag, config) = Self::read_descriptor(&mut c)?;
+        if tag != 0x04 {
+            return Err(Error::InvalidValue("DecoderConfigDescriptor tag"));
+        }
+        let mut c = Cursor::new(config);
+        let object_type_indication = c.u8()?;
+        let stream_type = c.u8()? >> 2;
+        let buffer_size = c.u24()?;
+        let max_bitrate = c.u32()?;
+        let avg_bitrate = c.u32()?;
+        let mut decoder_specific_info = Vec::new();
+        if !c.rest().is_empty() {
+            let (tag, info) = Self::read_descriptor(&mut c)?;
+            if tag == 0x05 {
+                decoder_specific_info = info.to_vec();
+            }
+        }
+        Ok(Self {
+            es_id,
+            object_type_indication,
+            stream_type,
+            buffer_size,
+            max_bitrate,
+            avg_bitrate,
+            decoder_specific_info,
+        })
+    }
+
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, _, data) = atom.full()?;
+        Self::parse_payload(data)
+    }
+
+    /// MPEG-4 audio descriptor carrying `AudioSpecificConfig`
+    pub fn aac(audio_specific_config: &[u8], avg_bitrate: u32) -> Self {
+        Self {
+            es_id: 0,
+            object_type_indication: Self::OBJECT_TYPE_MPEG4_AUDIO,
+            stream_type: 5,
+            buffer_size: 0,
+            max_bitrate: avg_bitrate,
+            avg_bitrate,
+            decoder_specific_info: audio_specific_config.to_vec(),
+        }
+    }
+
+    fn write_descriptor(out: &mut Vec<u8>, tag: u8, payload: &[u8]) {
+        out.push(tag);
+        let len = payload.len() as u32;
+        for shift in [21, 14, 7] {
+            if len >> shift != 0 {
+                out.push((len >> shift) as u8 | 0x80);
+            }
+        }
+        out.push(len as u8 & 0x7f);
+        out.extend_from_slice(payload);
+    }
+
+    /// `ES_Descriptor` which [`Esds::parse_payload`] reads
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut config = vec![self.object_type_indication, self.stream_type << 2 | 1];
+        config.extend_from_slice(&self.buffer_size.to_be_bytes()[1..]);
+        config.extend_from_slice(&self.max_bitrate.to_be_bytes());
+        config.extend_from_slice(&self.avg_bitrate.to_be_bytes());
+        if !self.decoder_specific_info.is_empty() {
+            Self::write_descriptor(&mut config, 0x05, &self.decoder_specific_info);
+        }
+        let mut es = self.es_id.to_be_bytes().to_vec();
+        es.push(0);
+        Self::write_descriptor(&mut es, 0x04, &config);
+        // SLConfigDescriptor with predefined MP4 config
+        Self::write_descriptor(&mut es, 0x06, &[2]);
+        let mut res = Vec::with_capacity(es.len() + 5);
+        Self::write_descriptor(&mut res, 0x03, &es);
+        res
+    }
+
+    /// `audioObjectType` of `AudioSpecificConfig`, 2 for AAC LC
+    pub fn audio_object_type(&self) -> Option<u8> {
+        if self.object_type_indication != Self::OBJECT_TYPE_MPEG4_AUDIO {
+            return None;
+        }
+        let asc = &self.decoder_specific_info;
+        let aot = asc.first()? >> 3;
+        if aot == 31 {
+            let ext = (asc.first()? & 7) << 3 | asc.get(1)? >> 5;
+            Some(32 + ext)
+        } else {
+            Some(aot)
+        }
+    }
+}
+
+/// Entry of `stsd` with fixed fields and payloads of child boxes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleEntry {
+    pub format: AtomType,
+    pub data_reference_index: u16,
+    pub visual: Option<VisualEntry>,
+    pub audio: Option<AudioEntry>,
+
+    /// Child boxes such as `avcC`, `colr` or `esds`
+    pub extensions: Vec<(AtomType, Vec<u8>)>,
+}
+
+impl SampleEntry {
+    pub fn video(format: AtomType, width: u16, height: u16) -> Self {
+        Self {
+            format,
+            data_reference_index: 1,
+            visual: Some(VisualEntry {
+                width,
+                height,
+                compressor_name: String::new(),
+                depth: 0x18,
+            }),
+            audio: None,
+            extensions: Vec::new(),
+        }
+    }
+
+    pub fn audio(format: AtomType, channel_count: u32, sample_rate: f64) -> Self {
+        Self {
+            format,
+            data_reference_index: 1,
+            visual: None,
+            audio: Some(AudioEntry {
+                channel_count,
+                sample_size: 16,
+                sample_rate,
+            }),
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Appends child box payload
+    #[inline]
+    pub fn with_extension(mut self, atom_type: AtomType, payload: Vec<u8>) -> Self {
+        self.extensions.push((atom_type, payload));
+        self
+    }
+
+    /// Whole box with header, audio fields are written as version 0 ISO entry
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = vec![0u8; 6];
+        payload.extend_from_slice(&self.data_reference_index.to_be_bytes());
+        if let Some(v) = &self.visual {
+            payload.extend_from_slice(&[0; 16]);
+            payload.extend_from_slice(&v.width.to_be_bytes());
+            payload.extend_from_slice(&v.height.to_be_bytes());
+            // 72 dpi, reserved and frame count
+            payload.extend_from_slice(&[0, 0x48, 0, 0, 0, 0x48, 0, 0, 0, 0, 0, 0, 0, 1]);
+            let mut name = [0u8; 32];
+            let len = v.compressor_name.len().min(31);
+            name[0] = len as u8;
+            name[1..1 + len].copy_from_slice(&v.compressor_name.as_bytes()[..len]);
+            payload.extend_from_slice(&name);
+            payload.extend_from_slice(&v.depth.to_be_bytes());
+            payload.extend_from_slice(&[0xff, 0xff]);
+        } else if let Some(a) = &self.audio {
+            payload.extend_from_slice(&[0; 8]);
+            payload.extend_from_slice(&(a.channel_count as u16).to_be_bytes());
+            payload.extend_from_slice(&a.sample_size.to_be_bytes());
+            payload.extend_from_slice(&[0; 4]);
+            let rate = if a.sample_rate < 65536.0 {
+                (a.sample_rate * 65536.0) as u32
+            } else {
+                0
+            };
+            payload.extend_from_slice(&rate.to_be_bytes());
+        }
+        for (atom_type, data) in &self.extensions {
+            super::write_atom(&mut payload, *atom_type, data);
+        }
+        let mut res = Vec::with_capacity(8 + payload.len());
+        super::write_atom(&mut res, self.format, &payload);
+        res
+    }
+
+    /// Parses sample entry, `handler_type` of track selects visual or audio fields
+    pub fn parse(atom: &Atom, handler_type: AtomType) -> Result<Self, Error> {
+        let mut c = Cursor::new(atom.data);
+        c.skip(6)?;
+        let data_reference_index = c.u16()?;
+        let mut visual = None;
+        let mut audio = None;
+        let mut fixed = 8;
+        match handler_type {
+            Hdlr::VIDE => {
+                c.skip(16)?;
+                let width = c.u16()?;
+                let height = c.u16()?;
+                c.skip(14)?;
+                let name = c.bytes(32)?;
+                let len = (name[0] as usize).min(31);
+                let compressor_name = String::from_utf8_lossy(&name[1..1 + len]).into_owned();
+                let depth = c.u16()?;
+                visual = Some(VisualEntry {
+                    width,
+                    height,
+                    compressor_name,
+                    depth,
+                });
+                fixed += 70;
+            }
+            Hdlr::SOUN => {
+                let version = c.u16()?;
+                c.skip(6)?;
+                let mut entry = AudioEntry {
+                    channel_count: c.u16()? as u32,
+                    sample_size: c.u16()?,
+                    sample_rate: {
+                        c.skip(4)?;
+                        c.u32()? as f64 / 65536.0
+                    },
+                };
+                fixed += 20;
+                match version {
+                    1 => fixed += 16,
+                    2 => {
+                        c.skip(4)?;
+                        entry.sample_rate = f64::from_bits(c.u64()?);
+                        entry.channel_count = c.u32()?;
+                        c.skip(4)?;
+                        entry.sample_size = c.u32()? as u16;
+                        fixed += 36;
+                    }
+                    _ => {}
+                }
+                audio = Some(entry);
+            }
+            _ => {}
+        }
+        let mut extensions = Vec::new();
+        for child in atom.children_after(fixed) {
+            let child = child?;
+            extensions.push((child.atom_type, child.data.to_vec()));
+        }
+        Ok(Self {
+            format: atom.atom_type,
+            data_reference_index,
+            visual,
+            audio,
+            extensions,
+        })
+    }
+
+    /// Payload of the first child box of the type
+    #[inline]
+    pub fn extension(&self, atom_type: AtomType) -> Option<&[u8]> {
+        self.extensions
+            .iter()
+            .find(|e| e.0 == atom_type)
+            .map(|e| e.1.as_slice())
+    }
+
+    #[inline]
+    pub fn avcc(&self) -> Option<Result<h264::Avcc, h264::Error>> {
+        self.extension(AtomType::AVCC).map(h264::Avcc::parse)
+    }
+
+    #[inline]
+    pub fn hvcc(&self) -> Option<Result<hevc::Hvcc, hevc::Error>> {
+        self.extension(AtomType::HVCC).map(hevc::Hvcc::parse)
+    }
+
+    #[inline]
+    pub fn av1c(&self) -> Option<Result<av1::Av1c, av1::Error>> {
+        self.extension(AtomType::AV1C).map(av1::Av1c::parse)
+    }
+
+    #[inline]
+    pub fn vpcc(&self) -> Option<Result<vp9::Vpcc, vp9::Error>> {
+        self.extension(AtomType::VPCC).map(vp9::Vpcc::parse)
+    }
+
+    /// `esds` of MP4 entry or the one nested in QuickTime `wave` box
+    pub fn esds(&self) -> Option<Result<Esds, Error>> {
+        if let Some(data) = self.extension(AtomType::ESDS) {
+            return Some(
+                data.get(4..)
+                    .ok_or(Error::Truncated)
+                    .and_then(Esds::parse_payload),
+            );
+        }
+        let wave = self.extension(AtomType::WAVE)?;
+        let esds = super::atoms(wave).find(AtomType::ESDS)?;
+        Some(Esds::parse(&esds))
+    }
+
+    /// Codec of video sample entry, parameter set carriage variants like `avc3`
+    /// and `hev1` map to the same codec
+    #[cfg(feature = "cm")]
+    pub fn video_codec(&self) -> Option<cm::VideoCodec> {
+        self.visual.as_ref()?;
+        Some(match &self.format.to_be_bytes() {
+            b"avc1" | b"avc3" => cm::VideoCodec::H264,
+            b"hvc1" | b"hev1" => cm::VideoCodec::HEVC,
+            b"dvh1" | b"dvhe" => cm::VideoCodec::DOLBY_VISION_HEVC,
+            bytes => cm::VideoCodec::from_be_bytes(bytes),
+        })
+    }
+
+    /// Core Audio format of audio sample entry, AAC profile is taken from `esds`
+    #[cfg(feature = "cat")]
+    pub fn audio_format(&self) -> Option<cat::audio::Format> {
+        use cat::audio::Format;
+
+        self.audio.as_ref()?;
+        Some(match &self.format.to_be_bytes() {
+            b"mp4a" => {
+                let esds = self.esds()?.ok()?;
+                match esds.object_type_indication {
+                    Esds::OBJECT_TYPE_MPEG1_AUDIO | Esds::OBJECT_TYPE_MPEG2_AUDIO => {
+                        Format::MPEGLAYER3
+                    }
+                    _ => match esds.audio_object_type() {
+                        Some(5) => Format::MPEG4_AAC_HE,
+                        Some(29) => Format::MPEG4_AAC_HE_V2,
+                        Some(23) => Format::MPEG4_AAC_LD,
+                        Some(39) => Format::MPEG4_AAC_ELD,
+                        _ => Format::MPEG4_AAC,
+                    },
+                }
+            }
+            b"ac-3" => Format::AC3,
+            b"ec-3" => Format::ENHANCED_AC3,
+            b"Opus" => Format::OPUS,
+            b"fLaC" => Format::FLAC,
+            b"alac" => Format::APPLE_LOSSLESS,
+            b"lpcm" | b"ipcm" | b"fpcm" | b"sowt" | b"twos" | b"in24" | b"in32" | b"fl32"
+            | b"fl64" => Format::LINEAR_PCM,
+            bytes => Format(u32::from_be_bytes(*bytes)),
+        })
+    }
+}
+
+/// `trex` defaults of fragmented track
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Trex {
+    pub track_id: u32,
+    pub default_sample_description_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: SampleFlags,
+}
+
+impl Trex {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        let (_, _, data) = atom.full()?;
+        let mut c = Cursor::new(data);
+        Ok(Self {
+            track_id: c.u32()?,
+            default_sample_description_index: c.u32()?,
+            default_sample_duration: c.u32()?,
+            default_sample_size: c.u32()?,
+            default_sample_flags: SampleFlags(c.u32()?),
+        })
+    }
+}
+
+/// `trak` headers and sample descriptions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub tkhd: Tkhd,
+    pub mdhd: Mdhd,
+    pub hdlr: Hdlr,
+    pub sample_entries: Vec<SampleEntry>,
+}
+
+impl Track {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        atom.expect(AtomType::TRAK)?;
+        fn child<'a>(atom: &Atom<'a>, t: AtomType) -> Result<Atom<'a>, Error> {
+            atom.child(t).ok_or(Error::MissingAtom(t))
+        }
+        let tkhd = Tkhd::parse(&child(atom, AtomType::TKHD)?)?;
+        let mdia = child(atom, AtomType::MDIA)?;
+        let mdhd = Mdhd::parse(&child(&mdia, AtomType::MDHD)?)?;
+        let hdlr = Hdlr::parse(&child(&mdia, AtomType::HDLR)?)?;
+        let stsd = mdia
+            .descendant(&[AtomType::MINF, AtomType::STBL, AtomType::STSD])
+            .ok_or(Error::MissingAtom(AtomType::STSD))?;
+        let mut sample_entries = Vec::with_capacity(1);
+        for entry in stsd.children_after(8) {
+            sample_entries.push(SampleEntry::parse(&entry?, hdlr.handler_type)?);
+        }
+        Ok(Self {
+            tkhd,
+            mdhd,
+            hdlr,
+            sample_entries,
+        })
+    }
+
+    #[inline]
+    pub fn track_id(&self) -> u32 {
+        self.tkhd.track_id
+    }
+
+    #[inline]
+    pub fn timescale(&self) -> u32 {
+        self.mdhd.timescale
+    }
+}
+
+/// `moov` with tracks and fragment defaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct Moov {
+    pub mvhd: Mvhd,
+    pub tracks: Vec<Track>,
+
+    /// Present in init segments of fragmented files
+    pub trex: Vec<Trex>,
+}
+
+impl Moov {
+    pub fn parse(atom: &Atom) -> Result<Self, Error> {
+        atom.expect(AtomType::MOOV)?;
+        let mvhd = atom
+            .child(AtomType::MVHD)
+            .ok_or(Error::MissingAtom(AtomType::MVHD))?;
+        let mut res = Self {
+            mvhd: Mvhd::parse(&mvhd)?,
+            tracks: Vec::new(),
+            trex: Vec::new(),
+        };
+        for child in atom.children() {
+            let child = child?;
+            match child.atom_type {
+                AtomType::TRAK => res.tracks.push(Track::parse(&child)?),
+                AtomType::MVEX => {
+                    for trex in child.children() {
+                        let trex = trex?;
+                        if trex.atom_type == AtomType::TREX {
+                            res.trex.push(Trex::parse(&trex)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn track(&self, track_id: u32) -> Option<&Track> {
+        self.tracks.iter().find(|t| t.track_id() == track_id)
+    }
+
+    #[inline]
+    pub fn is_fragmented(&self) -> bool {
+        !self.trex.is_empty()
+    }
+}
@@ -0,0 +1,797 @@
+//! Fragmented MP4 muxer.
+
+use std::time::Duration;
+
+use super::{write_atom, write_full_atom, AtomType, Error, Esds, Hdlr, SampleEntry, SampleFlags};
+
+#[cfg(feature = "cm")]
+use crate::{cf, cm};
+
+const IDENTITY_MATRIX: [u32; 9] = [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000];
+
+/// Entry of edit list
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Edit {
+    /// Duration in movie timescale, 0 for the rest of fragmented track
+    pub segment_duration: u64,
+
+    /// Start in track timescale, -1 for empty edit
+    pub media_time: i64,
+}
+
+impl Edit {
+    #[inline]
+    pub const fn new(segment_duration: u64, media_time: i64) -> Self {
+        Self {
+            segment_duration,
+            media_time,
+        }
+    }
+
+    /// Delays presentation of the track
+    #[inline]
+    pub const fn empty(segment_duration: u64) -> Self {
+        Self::new(segment_duration, -1)
+    }
+}
+
+/// Track of [`Muxer`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackConfig {
+    pub handler_type: AtomType,
+    pub timescale: u32,
+
+    /// ISO 639-2/T code
+    pub language: [u8; 3],
+    pub sample_entry: SampleEntry,
+    pub edits: Vec<Edit>,
+}
+
+impl TrackConfig {
+    /// Video track with 90 kHz timescale
+    pub fn video(sample_entry: SampleEntry) -> Self {
+        Self {
+            handler_type: Hdlr::VIDE,
+            timescale: 90_000,
+            language: *b"und",
+            sample_entry,
+            edits: Vec::new(),
+        }
+    }
+
+    /// Audio track with timescale of sample rate
+    pub fn audio(sample_entry: SampleEntry) -> Self {
+        let timescale = sample_entry.audio.map_or(48_000, |a| a.sample_rate as u32);
+        Self {
+            handler_type: Hdlr::SOUN,
+            timescale,
+            language: *b"und",
+            sample_entry,
+            edits: Vec::new(),
+        }
+    }
+
+    /// H.264 track with `avcC` record
+    pub fn h264(avcc: Vec<u8>, width: u16, height: u16) -> Self {
+        let entry = SampleEntry::video(AtomType::from_be_bytes(b"avc1"), width, height)
+            .with_extension(AtomType::AVCC, avcc);
+        Self::video(entry)
+    }
+
+    /// HEVC track with `hvcC` record, parameter sets are carried out of band
+    pub fn hevc(hvcc: Vec<u8>, width: u16, height: u16) -> Self {
+        let entry = SampleEntry::video(AtomType::from_be_bytes(b"hvc1"), width, height)
+            .with_extension(AtomType::HVCC, hvcc);
+        Self::video(entry)
+    }
+
+    /// AAC track with `AudioSpecificConfig`
+    pub fn aac(audio_specific_config: &[u8], channel_count: u32, sample_rate: u32) -> Self {
+        let mut esds = vec![0; 4];
+        esds.extend(Esds::aac(audio_specific_config, 0).to_bytes());
+        let entry = SampleEntry::audio(
+            AtomType::from_be_bytes(b"mp4a"),
+            channel_count,
+            sample_rate as f64,
+        )
+        .with_extension(AtomType::ESDS, esds);
+        Self::audio(entry)
+    }
+
+    /// Opus track with `dOps` made of Ogg `OpusHead` header
+    pub fn opus(opus_head: &[u8]) -> Option<Self> {
+        let head = opus_head.strip_prefix(b"OpusHead")?;
+        if head.len() < 11 {
+            return None;
+        }
+        let channel_count = head[1];
+        let mut dops = vec![0, channel_count];
+        // pre-skip, input sample rate and output gain are little endian in OpusHead
+        dops.extend(u16::from_le_bytes([head[2], head[3]]).to_be_bytes());
+        dops.extend(u32::from_le_bytes([head[4], head[5], head[6], head[7]]).to_be_bytes());
+        dops.extend(u16::from_le_bytes([head[8], head[9]]).to_be_bytes());
+        dops.extend_from_slice(&head[10..]);
+        let entry = SampleEntry::audio(
+            AtomType::from_be_bytes(b"Opus"),
+            channel_count as u32,
+            48_000.0,
+        )
+        .with_extension(AtomType::DOPS, dops);
+        Some(Self::audio(entry))
+    }
+
+    /// Track with codec configuration of video or audio format description
+    /// produced by `vt::CompressionSession` or audio converter
+    #[cfg(feature = "cm")]
+    pub fn with_format_desc(desc: &cm::FormatDesc) -> Option<Self> {
+        let format = AtomType(desc.media_sub_type());
+        match desc.media_type() {
+            cm::MediaType::VIDEO => {
+                let dims = desc.dimensions();
+                let mut entry = SampleEntry::video(format, dims.width as u16, dims.height as u16);
+                let atoms = [
+                    (AtomType::AVCC, cf::str!(c"avcC")),
+                    (AtomType::HVCC, cf::str!(c"hvcC")),
+                    (AtomType::LHVC, cf::str!(c"lhvC")),
+                    (AtomType::AV1C, cf::str!(c"av1C")),
+                    (AtomType::VPCC, cf::str!(c"vpcC")),
+                    (AtomType::from_be_bytes(b"dvcC"), cf::str!(c"dvcC")),
+                    (AtomType::from_be_bytes(b"dvvC"), cf::str!(c"dvvC")),
+                ];
+                for (atom_type, key) in atoms {
+                    if let Some(data) = desc.ext_atom(key) {
+                        entry.extensions.push((atom_type, data));
+                    }
+                }
+                Some(Self::video(entry))
+            }
+            #[cfg(feature = "cat")]
+            cm::MediaType::AUDIO => {
+                use crate::cat::audio::Format;
+
+                let asbd = desc.stream_basic_desc()?;
+                let cookie = desc.magic_cookie();
+                let channels = asbd.channels_per_frame;
+                match asbd.format {
+                    Format::MPEG4_AAC
+                    | Format::MPEG4_AAC_HE
+                    | Format::MPEG4_AAC_HE_V2
+                    | Format::MPEG4_AAC_LD
+                    | Format::MPEG4_AAC_ELD => {
+                        let cookie = cookie?;
+                        let rate = asbd.sample_rate;
+                        if cookie.first() == Some(&0x03) {
+                            let mut esds = vec![0; 4];
+                            esds.extend_from_slice(cookie);
+                            let entry = SampleEntry::audio(
+                                AtomType::from_be_bytes(b"mp4a"),
+                                channels,
+                                rate,
+                            )
+                            .with_extension(AtomType::ESDS, esds);
+                            Some(Self::audio(entry))
+                        } else {
+                            Some(Self::aac(cookie, channels, rate as u32))
+                        }
+                    }
+                    Format::OPUS => Self::opus(cookie?),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Encoded sample, times are in track timescale
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MuxSample<'a> {
+    pub data: &'a [u8],
+    pub dts: i64,
+    pub pts: i64,
+
+    /// 0 to derive from decode time of the next sample
+    pub duration: u32,
+    pub is_sync: bool,
+}
+
+impl<'a> MuxSample<'a> {
+    /// Sample decoded in presentation order
+    #[inline]
+    pub fn new(data: &'a [u8], pts: i64, duration: u32, is_sync: bool) -> Self {
+        Self {
+            data,
+            dts: pts,
+            pts,
+            duration,
+            is_sync,
+        }
+    }
+
+    /// Converts timing to `timescale`, invalid decode time means presentation order
+    #[cfg(feature = "cm")]
+    pub fn with_timing(
+        data: &'a [u8],
+        timing: &cm::SampleTimingInfo,
+        timescale: u32,
+        is_sync: bool,
+    ) -> Self {
+        let convert =
+            |t: cm::Time| t.convert_scale(timescale as i32, cm::TimeRoundingMethod::default());
+        let pts = convert(timing.pts).value;
+        let dts = if timing.dts.is_valid() {
+            convert(timing.dts).value
+        } else {
+            pts
+        };
+        let duration = if timing.duration.is_valid() {
+            convert(timing.duration).value as u32
+        } else {
+            0
+        };
+        Self {
+            data,
+            dts,
+            pts,
+            duration,
+            is_sync,
+        }
+    }
+}
+
+/// Options of [`Muxer`]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MuxConfig {
+    /// Minimal fragment duration, fragments are cut at sync samples of the
+    /// first video track
+    pub fragment_duration: Duration,
+    pub movie_timescale: u32,
+    pub major_brand: AtomType,
+
+    /// Brands of ftyp, add `cmfc` only when muxing a single track, CMAF
+    /// requires one track per init segment and fragment
+    pub compatible_brands: Vec<AtomType>,
+}
+
+impl Default for MuxConfig {
+    fn default() -> Self {
+        Self {
+            fragment_duration: Duration::from_secs(2),
+            movie_timescale: 1000,
+            major_brand: AtomType::from_be_bytes(b"iso6"),
+            compatible_brands: vec![AtomType::from_be_bytes(b"iso6")],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Pending {
+    dts: i64,
+    composition_offset: i64,
+    duration: u32,
+    size: u32,
+    is_sync: bool,
+}
+
+#[derive(Debug)]
+struct Track {
+    config: TrackConfig,
+    samples: Vec<Pending>,
+    data: Vec<u8>,
+    last_dts: Option<i64>,
+    last_duration: u32,
+}
+
+/// Muxes encoded samples of several tracks into fragmented MP4 init segment and fragments.
+///
+/// ```
+/// use cidre::media::mp4;
+///
+/// let mut muxer = mp4::Muxer::new(Default::default());
+/// let track_id = muxer.add_track(mp4::TrackConfig::aac(&[0x11, 0x90], 2, 48_000));
+/// let init = muxer.init_segment();
+///
+/// let sample = mp4::MuxSample::new(&[0x21, 0x10], 0, 1024, true);
+/// assert!(muxer.push(track_id, &sample).unwrap().is_none());
+/// let fragment = muxer.flush().unwrap();
+///
+/// let segment = mp4::Segment::parse(&[init, fragment].concat()).unwrap();
+/// assert_eq!(segment.samples(track_id).unwrap().len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct Muxer {
+    config: MuxConfig,
+    tracks: Vec<Track>,
+    sequence_number: u32,
+}
+
+impl Muxer {
+    pub fn new(config: MuxConfig) -> Self {
+        Self {
+            config,
+            tracks: Vec::new(),
+            sequence_number: 0,
+        }
+    }
+
+    /// Adds track and returns its id, tracks should be added before the first sample
+    pub fn add_track(&mut self, config: TrackConfig) -> u32 {
+        self.tracks.push(Track {
+            config,
+            samples: Vec::new(),
+            data: Vec::new(),
+            last_dts: None,
+            last_duration: 0,
+        });
+        self.tracks.len() as u32
+    }
+
+    #[inline]
+    pub fn track(&self, track_id: u32) -> Option<&TrackConfig> {
+        self.tracks
+            .get(track_id.wrapping_sub(1) as usize)
+            .map(|t| &t.config)
+    }
+
+    /// Track which drives fragmentation, the first video track or the first track
+    fn primary(&self) -> usize {
+        self.tracks
+            .iter()
+            .position(|t| t.config.handler_type == Hdlr::VIDE)
+            .unwrap_or(0)
+    }
+
+    /// `ftyp` and `moov` with `mvex`
+    pub fn init_segment(&self) -> Vec<u8> {
+        let mut ftyp = self.config.major_brand.to_be_bytes().to_vec();
+        ftyp.extend_from_slice(&[0; 4]);
+        for brand in &self.config.compatible_brands {
+            ftyp.extend_from_slice(&brand.to_be_bytes());
+        }
+
+        let mut moov = Vec::new();
+        let durations: Vec<u64> = self
+            .tracks
+            .iter()
+            .map(|t| t.config.edits.iter().map(|e| e.segment_duration).sum())
+            .collect();
+        let mut mvhd = Vec::with_capacity(96);
+        mvhd.extend_from_slice(&[0; 8]);
+        mvhd.extend_from_slice(&self.config.movie_timescale.to_be_bytes());
+        let duration = durations.iter().copied().max().unwrap_or(0);
+        mvhd.extend_from_slice(&(duration.min(u32::MAX as u64) as u32).to_be_bytes());
+        mvhd.extend_from_slice(&[0, 1, 0, 0, 1, 0]);
+        mvhd.extend_from_slice(&[0; 10]);
+        write_u32s(&mut mvhd, &IDENTITY_MATRIX);
+        mvhd.extend_from_slice(&[0; 24]);
+        mvhd.extend_from_slice(&(self.tracks.len() as u32 + 1).to_be_bytes());
+        write_full_atom(&mut moov, AtomType::MVHD, 0, 0, &mvhd);
+
+        for (i, track) in self.tracks.iter().enumerate() {
+            write_trak(&mut moov, &track.config, i as u32 + 1, durations[i]);
+        }
+
+        let mut mvex = Vec::new();
+        for i in 0..self.tracks.len() {
+            let trex = [i as u32 + 1, 1, 0, 0, 0];
+            let mut payload = Vec::with_capacity(20);
+            write_u32s(&mut payload, &trex);
+            write_full_atom(&mut mvex, AtomType::TREX, 0, 0, &payload);
+        }
+        write_atom(&mut moov, AtomType::MVEX, &mvex);
+
+        let mut res = Vec::with_capacity(ftyp.len() + moov.len() + 16);
+        write_atom(&mut res, AtomType::FTYP, &ftyp);
+        write_atom(&mut res, AtomType::MOOV, &moov);
+        res
+    }
+
+    /// Buffers sample and returns completed fragment when sync sample of the primary
+    /// track starts a new one
+    pub fn push(&mut self, track_id: u32, sample: &MuxSample) -> Result<Option<Vec<u8>>, Error> {
+        let index = track_id.wrapping_sub(1) as usize;
+        let primary = self.primary();
+        let min = self.config.fragment_duration.as_secs_f64();
+        let track = self
+            .tracks
+            .get_mut(index)
+            .ok_or(Error::InvalidValue("track id"))?;
+        if sample.dts < 0 || track.last_dts.is_some_and(|dts| sample.dts < dts) {
+            return Err(Error::InvalidValue("decode time"));
+        }
+        if let Some(prev) = track.samples.last_mut() {
+            if prev.duration == 0 {
+                prev.duration = (sample.dts - prev.dts) as u32;
+            }
+        }
+        let min = (min * track.config.timescale as f64).round() as i64;
+        let cut = index == primary
+            && sample.is_sync
+            && track
+                .samples
+                .first()
+                .is_some_and(|first| sample.dts - first.dts >= min);
+        let fragment = if cut { self.flush() } else { None };
+
+        let track = &mut self.tracks[index];
+        track.samples.push(Pending {
+            dts: sample.dts,
+            composition_offset: sample.pts - sample.dts,
+            duration: sample.duration,
+            size: sample.data.len() as u32,
+            is_sync: sample.is_sync,
+        });
+        track.data.extend_from_slice(sample.data);
+        track.last_dts = Some(sample.dts);
+        Ok(fragment)
+    }
+
+    /// Pushes samples of sample buffer, fragments completed meanwhile are concatenated
+    #[cfg(feature = "cm")]
+    pub fn push_sample_buf(
+        &mut self,
+        track_id: u32,
+        buf: &cm::SampleBuf,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let timescale = self
+            .track(track_id)
+            .ok_or(Error::InvalidValue("track id"))?
+            .timescale;
+        let block = buf
+            .data_buf()
+            .ok_or(Error::InvalidValue("sample buffer data"))?;
+        let mut data = Vec::with_capacity(block.data_len());
+        while data.len() < block.data_len() {
+            let (chunk, _) = block
+                .data_ptr_at(data.len())
+                .map_err(|_| Error::Truncated)?;
+            if chunk.is_empty() {
+                return Err(Error::Truncated);
+            }
+            data.extend_from_slice(chunk);
+        }
+        let is_sync = buf.is_key_frame();
+        let mut res: Option<Vec<u8>> = None;
+        let mut offset = 0;
+        for i in 0..buf.num_samples() {
+            let timing = buf
+                .timing_info(i)
+                .map_err(|_| Error::InvalidValue("sample timing"))?;
+            let size = buf.sample_size(i);
+            let bytes = data.get(offset..offset + size).ok_or(Error::Truncated)?;
+            offset += size;
+            let sample = MuxSample::with_timing(bytes, &timing, timescale, is_sync);
+            if let Some(fragment) = self.push(track_id, &sample)? {
+                res.get_or_insert_with(Vec::new).extend(fragment);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Writes buffered samples of all tracks as `moof` and `mdat`
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        if self.tracks.iter().all(|t| t.samples.is_empty()) {
+            return None;
+        }
+        self.sequence_number += 1;
+        let mut moof = Vec::new();
+        write_full_atom(
+            &mut moof,
+            AtomType::MFHD,
+            0,
+            0,
+            &self.sequence_number.to_be_bytes(),
+        );
+        // positions of trun data offsets within moof payload and data offsets
+        // relative to the mdat payload
+        let mut patches = Vec::with_capacity(self.tracks.len());
+        let mut mdat_len = 0usize;
+        for (i, track) in self.tracks.iter_mut().enumerate() {
+            if track.samples.is_empty() {
+                continue;
+            }
+            for j in 0..track.samples.len() {
+                if track.samples[j].duration == 0 {
+                    track.samples[j].duration = match j {
+                        0 => track.last_duration,
+                        _ => track.samples[j - 1].duration,
+                    };
+                }
+            }
+            track.last_duration = track.samples.last().map_or(0, |s| s.duration);
+
+            let mut traf = Vec::new();
+            write_full_atom(
+                &mut traf,
+                AtomType::TFHD,
+                0,
+                0x2_0000,
+                &(i as u32 + 1).to_be_bytes(),
+            );
+            write_full_atom(
+                &mut traf,
+                AtomType::TFDT,
+                1,
+                0,
+                &(track.samples[0].dts as u64).to_be_bytes(),
+            );
+            let has_offsets = track.samples.iter().any(|s| s.composition_offset != 0);
+            let negative = track.samples.iter().any(|s| s.composition_offset < 0);
+            let mut flags = 0x1 | 0x100 | 0x200 | 0x400;
+            if has_offsets {
+                flags |= 0x800;
+            }
+            let mut trun = Vec::with_capacity(8 + track.samples.len() * 16);
+            trun.extend_from_slice(&(track.samples.len() as u32).to_be_bytes());
+            let data_offset_pos = moof.len() + 8 + traf.len() + 12 + trun.len();
+            trun.extend_from_slice(&[0; 4]);
+            for s in &track.samples {
+                let flags = if s.is_sync {
+                    SampleFlags::SYNC
+                } else {
+                    SampleFlags::NON_SYNC
+                };
+                write_u32s(&mut trun, &[s.duration, s.size, flags.0]);
+                if has_offsets {
+                    trun.extend_from_slice(&(s.composition_offset as i32).to_be_bytes());
+                }
+            }
+            write_full_atom(&mut traf, AtomType::TRUN, negative as u8, flags, &trun);
+            write_atom(&mut moof, AtomType::TRAF, &traf);
+            patches.push((data_offset_pos, mdat_len));
+            mdat_len += track.data.len();
+        }
+
+        let moof_len = 8 + moof.len();
+        for (pos, offset) in patches {
+            let data_offset = (moof_len + 8 + offset) as u32;
+            moof[pos..pos + 4].copy_from_slice(&data_offset.to_be_bytes());
+        }
+        let mut res = Vec::with_capacity(moof_len + 8 + mdat_len);
+        write_atom(&mut res, AtomType::MOOF, &moof);
+        res.extend_from_slice(&(8 + mdat_len as u32).to_be_bytes());
+        res.extend_from_slice(&AtomType::MDAT.to_be_bytes());
+        for track in &mut self.tracks {
+            res.append(&mut track.data);
+            track.samples.clear();
+        }
+        Some(res)
+    }
+}
+
+fn write_u32s(out: &mut Vec<u8>, values: &[u32]) {
+    for v in values {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+fn write_trak(out: &mut Vec<u8>, config: &TrackConfig, track_id: u32, duration: u64) {
+    let is_audio = config.handler_type == Hdlr::SOUN;
+    let mut trak = Vec::new();
+
+    let mut tkhd = Vec::with_capacity(80);
+    write_u32s(&mut tkhd, &[0, 0, track_id, 0]);
+    write_u32s(&mut tkhd, &[duration.min(u32::MAX as u64) as u32, 0, 0, 0]);
+    tkhd.extend_from_slice(if is_audio { &[1, 0, 0, 0] } else { &[0; 4] });
+    write_u32s(&mut tkhd, &IDENTITY_MATRIX);
+    let (width, height) = config
+        .sample_entry
+        .visual
+        .as_ref()
+        .map_or((0, 0), |v| (v.width as u32, v.height as u32));
+    write_u32s(&mut tkhd, &[width << 16, height << 16]);
+    write_full_atom(&mut trak, AtomType::TKHD, 0, 3, &tkhd);
+
+    if !config.edits.is_empty() {
+        let wide = config
+            .edits
+            .iter()
+            .any(|e| e.segment_duration > u32::MAX as u64 || e.media_time > i32::MAX as i64);
+        let mut elst = (config.edits.len() as u32).to_be_bytes().to_vec();
+        for e in &config.edits {
+            if wide {
+                elst.extend_from_slice(&e.segment_duration.to_be_bytes());
+                elst.extend_from_slice(&e.media_time.to_be_bytes());
+            } else {
+                elst.extend_from_slice(&(e.segment_duration as u32).to_be_bytes());
+                elst.extend_from_slice(&(e.media_time as i32).to_be_bytes());
+            }
+            elst.extend_from_slice(&[0, 1, 0, 0]);
+        }
+        let mut edts = Vec::new();
+        write_full_atom(
+            &mut edts,
+            AtomType::from_be_bytes(b"elst"),
+            wide as u8,
+            0,
+            &elst,
+        );
+        write_atom(&mut trak, AtomType::EDTS, &edts);
+    }
+
+    let mut mdia = Vec::new();
+    let mut mdhd = Vec::with_capacity(20);
+    write_u32s(&mut mdhd, &[0, 0, config.timescale, 0]);
+    let lang = config.language.iter().fold(0u16, |acc, c| {
+        acc << 5 | (c.wrapping_sub(0x60) & 0x1f) as u16
+    });
+    mdhd.extend_from_slice(&lang.to_be_bytes());
+    mdhd.extend_from_slice(&[0; 2]);
+    write_full_atom(&mut mdia, AtomType::MDHD, 0, 0, &mdhd);
+
+    let mut hdlr = vec![0; 4];
+    hdlr.extend_from_slice(&config.handler_type.to_be_bytes());
+    hdlr.extend_from_slice(&[0; 12]);
+    hdlr.extend_from_slice(if is_audio {
+        b"SoundHandler\0"
+    } else {
+        b"VideoHandler\0"
+    });
+    write_full_atom(&mut mdia, AtomType::HDLR, 0, 0, &hdlr);
+
+    let mut minf = Vec::new();
+    if is_audio {
+        write_full_atom(&mut minf, AtomType::from_be_bytes(b"smhd"), 0, 0, &[0; 4]);
+    } else {
+        write_full_atom(&mut minf, AtomType::from_be_bytes(b"vmhd"), 0, 1, &[0; 8]);
+    }
+    let mut dref = 1u32.to_be_bytes().to_vec();
+    write_full_atom(&mut dref, AtomType::from_be_bytes(b"url "), 0, 1, &[]);
+    let mut dinf = Vec::new();
+    write_full_atom(&mut dinf, AtomType::from_be_bytes(b"dref"), 0, 0, &dref);
+    write_atom(&mut minf, AtomType::from_be_bytes(b"dinf"), &dinf);
+
+    let mut stbl = Vec::new();
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend(config.sample_entry.to_bytes());
+    write_full_atom(&mut stbl, AtomType::STSD, 0, 0, &stsd);
+    write_full_atom(&mut stbl, AtomType::from_be_bytes(b"stts"), 0, 0, &[0; 4]);
+    write_full_atom(&mut stbl, AtomType::from_be_bytes(b"stsc"), 0, 0, &[0; 4]);
+    write_full_atom(&mut stbl, AtomType::from_be_bytes(b"stsz"), 0, 0, &[0; 8]);
+    write_full_atom(&mut stbl, AtomType::from_be_bytes(b"stco"), 0, 0, &[0; 4]);
+    write_atom(&mut minf, AtomType::STBL, &stbl);
+    write_atom(&mut mdia, AtomType::MINF, &minf);
+    write_atom(&mut trak, AtomType::MDIA, &mdia);
+
+    write_atom(out, AtomType::TRAK, &trak);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::mp4::Segment;
+
+    #[test]
+    fn fragment_bytes() {
+        let mut muxer = Muxer::new(Default::default());
+        let id = muxer.add_track(TrackConfig::aac(&[0x11, 0x90], 2, 48_000));
+        muxer
+            .push(id, &MuxSample::new(&[1, 2, 3], 0, 1024, true))
+            .unwrap();
+        muxer
+            .push(id, &MuxSample::new(&[4, 5], 1024, 1024, true))
+            .unwrap();
+        let fragment = muxer.flush().unwrap();
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 0, 0x70, b'm', b'o', b'o', b'f',
+            0, 0, 0, 0x10, b'm', b'f', b'h', b'd', 0, 0, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0x58, b't', b'r', b'a', b'f',
+            0, 0, 0, 0x10, b't', b'f', b'h', b'd', 0, 2, 0, 0, 0, 0, 0, 1,
+            0, 0, 0, 0x14, b't', b'f', b'd', b't', 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0x2c, b't', b'r', b'u', b'n', 0, 0, 7, 1, 0, 0, 0, 2, 0, 0, 0, 0x78,
+            0, 0, 4, 0, 0, 0, 0, 3, 2, 0, 0, 0,
+            0, 0, 4, 0, 0, 0, 0, 2, 2, 0, 0, 0,
+            0, 0, 0, 0x0d, b'm', b'd', b'a', b't', 1, 2, 3, 4, 5,
+        ];
+        assert_eq!(fragment, expected);
+        assert!(muxer.flush().is_none());
+    }
+
+    #[test]
+    fn init_segment() {
+        let mut muxer = Muxer::new(Default::default());
+        let mut video = TrackConfig::h264(vec![1, 0x64, 0, 0x28, 0xff, 0xe0, 0], 1920, 1080);
+        video.edits.push(Edit::new(0, 3000));
+        let video_id = muxer.add_track(video);
+        let head = [
+            b'O', b'p', b'u', b's', b'H', b'e', b'a', b'd', 1, 2, 0x38, 1, 0x80, 0xbb, 0, 0, 0, 0,
+            0,
+        ];
+        let audio_id = muxer.add_track(TrackConfig::opus(&head).unwrap());
+
+        let init = muxer.init_segment();
+        let segment = Segment::parse(&init).unwrap();
+        let ftyp = segment.ftyp.unwrap();
+        assert!(ftyp.is_compatible(AtomType::from_be_bytes(b"iso6")));
+        assert!(!ftyp.is_compatible(AtomType::from_be_bytes(b"cmfc")));
+        let moov = segment.moov.unwrap();
+        assert_eq!(moov.mvhd.next_track_id, 3);
+        assert_eq!(moov.trex.len(), 2);
+
+        let video = moov.track(video_id).unwrap();
+        assert_eq!(video.timescale(), 90_000);
+        assert_eq!(video.tkhd.size(), (1920.0, 1080.0));
+        assert_eq!(&video.mdhd.language, b"und");
+        assert_eq!(
+            video.sample_entries[0],
+            muxer.track(video_id).unwrap().sample_entry
+        );
+
+        let audio = moov.track(audio_id).unwrap();
+        assert_eq!(audio.hdlr.handler_type, Hdlr::SOUN);
+        assert_eq!(audio.tkhd.volume, 0x0100);
+        let entry = &audio.sample_entries[0];
+        assert_eq!(entry.audio.unwrap().sample_rate, 48_000.0);
+        assert_eq!(
+            entry.extension(AtomType::DOPS),
+            Some(&[0, 2, 1, 0x38, 0, 0, 0xbb, 0x80, 0, 0, 0][..])
+        );
+    }
+
+    #[test]
+    fn fragmentation() {
+        let mut muxer = Muxer::new(MuxConfig {
+            fragment_duration: Duration::from_secs(1),
+            ..Default::default()
+        });
+        let audio_id = muxer.add_track(TrackConfig::aac(&[0x11, 0x90], 2, 48_000));
+        let video_id = muxer.add_track(TrackConfig::h264(vec![1, 0x64, 0, 0x28], 640, 360));
+        let init = muxer.init_segment();
+
+        let mut fragments = Vec::new();
+        // IPBB... with 3000 ticks per frame and key frame each 45 frames
+        for i in 0..90i64 {
+            let pts = match i % 3 {
+                1 => (i + 1) * 3000,
+                2 => (i - 1) * 3000,
+                _ => i * 3000,
+            };
+            let sample = MuxSample {
+                data: &[0xaa; 10],
+                dts: i * 3000,
+                pts: pts + 6000,
+                duration: 0,
+                is_sync: i % 45 == 0,
+            };
+            fragments.extend(muxer.push(video_id, &sample).unwrap());
+            if i % 2 == 0 {
+                let pts = i / 2 * 1024;
+                let sample = MuxSample::new(&[0xbb; 4], pts, 1024, true);
+                fragments.extend(muxer.push(audio_id, &sample).unwrap());
+            }
+        }
+        assert_eq!(fragments.len(), 1);
+        fragments.extend(muxer.flush());
+        assert_eq!(
+            muxer.push(video_id, &MuxSample::new(&[], 0, 0, true)),
+            Err(Error::InvalidValue("decode time"))
+        );
+        assert_eq!(
+            muxer.push(7, &MuxSample::new(&[], 0, 0, true)),
+            Err(Error::InvalidValue("track id"))
+        );
+
+        let data = [init, fragments.concat()].concat();
+        let segment = Segment::parse(&data).unwrap();
+        assert_eq!(segment.moof.len(), 2);
+        assert_eq!(segment.moof[1].mfhd.sequence_number, 2);
+
+        let video = segment.samples(video_id).unwrap();
+        assert_eq!(video.len(), 90);
+        assert!(video.iter().all(|s| s.duration == 3000));
+        assert!(video[45].is_sync() && !video[46].is_sync());
+        assert_eq!(video[45].decode_time, 45 * 3000);
+        assert_eq!(video[1].pts(), 2 * 3000 + 6000);
+        for s in &video {
+            assert_eq!(&data[s.offset as usize..][..10], &[0xaa; 10]);
+        }
+
+        let audio = segment.samples(audio_id).unwrap();
+        assert_eq!(audio.len(), 45);
+        assert_eq!(audio[44].decode_time, 44 * 1024);
+        for s in &audio {
+            assert_eq!(&data[s.offset as usize..][..4], &[0xbb; 4]);
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! NAL unit framing shared by H.264 and HEVC.
+//!
+//! Annex B streams separate NAL units with `00 00 01` or `00 00 00 01` start codes,
+//! while `cm::SampleBuf`s and MP4 samples prefix each NAL unit with its length
+//! (`nal_unit_header_length` of `cm::VideoFormatDesc`).
+
+use std::borrow::Cow;
+
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Length prefix size is not 1, 2, 3 or 4
+    InvalidLengthSize(usize),
+
+    /// NAL unit length exceeds sample
+    Truncated,
+
+    /// NAL unit of the size doesn't fit into length prefix
+    TooLarge(usize),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLengthSize(size) => write!(f, "invalid NAL length size {size}"),
+            Self::Truncated => f.write_str("truncated NAL unit"),
+            Self::TooLarge(len) => write!(f, "NAL unit of {len} bytes is too large"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[inline]
+fn check_length_size(length_size: usize) -> Result<(), Error> {
+    if (1..=4).contains(&length_size) {
+        Ok(())
+    } else {
+        Err(Error::InvalidLengthSize(length_size))
+    }
+}
+
+fn find_start_code(data: &[u8]) -> Option<usize> {
+    data.windows(3).position(|w| w == [0, 0, 1])
+}
+
+/// Iterator over NAL units of Annex B stream without start codes, see [`annex_b`]
+#[derive(Debug, Clone)]
+pub struct AnnexB<'a> {
+    rest: &'a [u8],
+}
+
+/// NAL units of Annex B stream.
+///
+/// ```
+/// use cidre::media::nal;
+///
+/// let stream = [0, 0, 0, 1, 0x67, 1, 2, 0, 0, 1, 0x68, 3, 0, 0, 0, 1, 0x65, 4, 0];
+/// let nals: Vec<_> = nal::annex_b(&stream).collect();
+/// assert_eq!(nals, [&[0x67, 1, 2][..], &[0x68, 3], &[0x65, 4]]);
+/// ```
+#[inline]
+pub fn annex_b(data: &[u8]) -> AnnexB<'_> {
+    let rest = match find_start_code(data) {
+        Some(pos) => &data[pos + 3..],
+        None => &[],
+    };
+    AnnexB { rest }
+}
+
+impl<'a> Iterator for AnnexB<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.rest.is_empty() {
+            let (nal, rest) = match find_start_code(self.rest) {
+                Some(pos) => (&self.rest[..pos], &self.rest[pos + 3..]),
+                None => (self.rest, &[][..]),
+            };
+            self.rest = rest;
+            // trailing_zero_8bits and zero_byte of the next 4 byte start code
+            let len = nal.iter().rposition(|&b| b != 0).map_or(0, |p| p + 1);
+            if len > 0 {
+                return Some(&nal[..len]);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over length-prefixed NAL units, see [`length_prefixed`]
+#[derive(Debug, Clone)]
+pub struct LengthPrefixed<'a> {
+    rest: &'a [u8],
+    length_size: usize,
+}
+
+/// NAL units of length-prefixed sample.
+///
+/// ```
+/// use cidre::media::nal;
+///
+/// let sample = [0, 2, 0x65, 1, 0, 1, 0x06];
+/// let nals: Vec<_> = nal::length_prefixed(&sample, 2).unwrap().collect();
+/// assert_eq!(nals, [Ok(&[0x65, 1][..]), Ok(&[0x06][..])]);
+/// ```
+pub fn length_prefixed(data: &[u8], length_size: usize) -> Result<LengthPrefixed<'_>, Error> {
+    check_length_size(length_size)?;
+    Ok(LengthPrefixed {
+        rest: data,
+        length_size,
+    })
+}
+
+impl<'a> Iterator for LengthPrefixed<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let Some((prefix, rest)) = self.rest.split_at_checked(self.length_size) else {
+            self.rest = &[];
+            return Some(Err(Error::Truncated));
+        };
+        let len = prefix.iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+        let Some((nal, rest)) = rest.split_at_checked(len) else {
+            self.rest = &[];
+            return Some(Err(Error::Truncated));
+        };
+        self.rest = rest;
+        Some(Ok(nal))
+    }
+}
+
+/// Appends `nal` with big-endian length prefix of `length_size` bytes
+pub fn push_length_prefixed(
+    out: &mut Vec<u8>,
+    nal: &[u8],
+    length_size: usize,
+) -> Result<(), Error> {
+    check_length_size(length_size)?;
+    if length_size < 4 && nal.len() >> (length_size * 8) != 0 {
+        return Err(Error::TooLarge(nal.len()));
+    }
+    let len = u32::try_from(nal.len()).map_err(|_| Error::TooLarge(nal.len()))?;
+    out.extend_from_slice(&len.to_be_bytes()[4 - length_size..]);
+    out.extend_from_slice(nal);
+    Ok(())
+}
+
+/// Converts Annex B stream into length-prefixed sample (AVCC/HVCC layout)
+///
+/// ```
+/// use cidre::media::nal;
+///
+/// let stream = [0, 0, 1, 0x65, 1, 2, 0, 0, 1, 0x06, 3];
+/// let sample = nal::annex_b_to_length_prefixed(&stream, 4).unwrap();
+/// assert_eq!(sample, [0, 0, 0, 3, 0x65, 1, 2, 0, 0, 0, 2, 0x06, 3]);
+/// assert_eq!(nal::length_prefixed_to_annex_b(&sample, 4).unwrap()[..8], [0, 0, 0, 1, 0x65, 1, 2, 0]);
+/// ```
+pub fn annex_b_to_length_prefixed(data: &[u8], length_size: usize) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(data.len() + 4);
+    for nal in annex_b(data) {
+        push_length_prefixed(&mut out, nal, length_size)?;
+    }
+    Ok(out)
+}
+
+/// Converts length-prefixed sample into Annex B stream with 4 byte start codes
+pub fn length_prefixed_to_annex_b(data: &[u8], length_size: usize) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(data.len() + 16);
+    for nal in length_prefixed(data, length_size)? {
+        out.extend_from_slice(&START_CODE);
+        out.extend_from_slice(nal?);
+    }
+    Ok(out)
+}
+
+/// Removes emulation prevention bytes (`00 00 03` -> `00 00`)
+pub fn to_rbsp(ebsp: &[u8]) -> Cow<'_, [u8]> {
+    if !ebsp.windows(3).any(|w| w == [0, 0, 3]) {
+        return Cow::Borrowed(ebsp);
+    }
+    let mut out = Vec::with_capacity(ebsp.len());
+    let mut zeros = 0;
+    for &b in ebsp {
+        if zeros >= 2 && b == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+        out.push(b);
+    }
+    Cow::Owned(out)
+}
+
+/// Inserts emulation prevention bytes so payload contains no start codes
+///
+/// ```
+/// use cidre::media::nal;
+///
+/// let rbsp = [0x67, 0, 0, 1, 0, 0, 0, 0];
+/// let ebsp = nal::to_ebsp(&rbsp);
+/// assert_eq!(ebsp, [0x67, 0, 0, 3, 1, 0, 0, 3, 0, 0, 3]);
+/// assert_eq!(nal::to_rbsp(&ebsp), &rbsp[..]);
+/// ```
+pub fn to_ebsp(rbsp: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rbsp.len() + rbsp.len() / 64);
+    let mut zeros = 0;
+    for &b in rbsp {
+        if zeros >= 2 && b <= 3 {
+            out.push(3);
+            zeros = 0;
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+        out.push(b);
+    }
+    if zeros >= 2 {
+        out.push(3);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annex_b, length_prefixed, push_length_prefixed, Error};
+
+    #[test]
+    fn length_sizes() {
+        for size in 1..=4 {
+            let mut sample = Vec::new();
+            push_length_prefixed(&mut sample, &[0x41; 200], size).unwrap();
+            push_length_prefixed(&mut sample, &[0x01], size).unwrap();
+            let nals: Vec<_> = length_prefixed(&sample, size)
+                .unwrap()
+                .map(|n| n.unwrap().len())
+                .collect();
+            assert_eq!(nals, [200, 1]);
+        }
+        let mut out = Vec::new();
+        assert_eq!(
+            push_length_prefixed(&mut out, &[0; 256], 1),
+            Err(Error::TooLarge(256))
+        );
+        assert!(length_prefixed(&[], 5).is_err());
+        let mut iter = length_prefixed(&[0, 0, 0, 9, 1], 4).unwrap();
+        assert_eq!(iter.next(), Some(Err(Error::Truncated)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn annex_b_edge_cases() {
+        assert_eq!(annex_b(&[]).count(), 0);
+        assert_eq!(annex_b(&[0x65, 1, 2]).count(), 0);
+        assert_eq!(annex_b(&[0, 0, 1, 0, 0, 1]).count(), 0);
+        let nals: Vec<_> = annex_b(&[9, 0, 0, 1, 0x09, 0xf0, 0, 0, 0, 0, 1, 0x67]).collect();
+        assert_eq!(nals, [&[0x09, 0xf0][..], &[0x67]]);
+    }
+}
@@ -0,0 +1,341 @@
+//! Decode timestamps and composition offsets of encoder output with B frames.
+//!
+//! Encoders emit frames in decode order, often with presentation time only.
+//! [`Reorder`] derives monotonic decode time of each frame from presentation times
+//! and reorder depth, so that composition offset `pts - dts` is never negative.
+//!
+//! With reorder depth `d`, decode time of `n`-th frame is presentation time of
+//! `n - d`-th frame in presentation order. The first `d` frames are extrapolated
+//! back by frame duration, which becomes [`Reorder::dts_shift`] of the stream.
+//!
+//! ```
+//! use cidre::media::reorder::Reorder;
+//!
+//! // I P B B in decode order of 1 tick frames, presented as I B B P
+//! let mut reorder = Reorder::with_depth(1);
+//! for (i, pts) in [0, 3, 1, 2].into_iter().enumerate() {
+//!     reorder.push(pts, None, 1, i);
+//! }
+//! reorder.finish();
+//!
+//! let samples: Vec<_> = std::iter::from_fn(|| reorder.pop()).collect();
+//! let dts: Vec<_> = samples.iter().map(|s| s.dts).collect();
+//! assert_eq!(dts, [-1, 0, 1, 2]);
+//! let offsets: Vec<_> = samples.iter().map(|s| s.composition_offset()).collect();
+//! assert_eq!(offsets, [1, 3, 0, 0]);
+//! assert_eq!(reorder.dts_shift(), Some(1));
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::media::{h264, hevc};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+/// Frames buffered to detect reorder depth if it is unknown
+pub const DEFAULT_DETECTION_WINDOW: usize = 16;
+
+/// Frame of [`Reorder`] in decode order
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Sample<T> {
+    pub pts: i64,
+    pub dts: i64,
+    pub duration: i64,
+    pub item: T,
+}
+
+impl<T> Sample<T> {
+    /// `pts - dts`, 0 for frames reordered deeper than detected depth
+    #[inline]
+    pub fn composition_offset(&self) -> u32 {
+        u32::try_from(self.pts - self.dts).unwrap_or(0)
+    }
+
+    /// Timing with times in `timescale`
+    #[cfg(feature = "cm")]
+    pub fn timing(&self, timescale: i32) -> cm::SampleTimingInfo {
+        cm::SampleTimingInfo {
+            duration: cm::Time::new(self.duration, timescale),
+            pts: cm::Time::new(self.pts, timescale),
+            dts: cm::Time::new(self.dts, timescale),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pending<T> {
+    pts: i64,
+    dts: Option<i64>,
+    duration: i64,
+    item: T,
+}
+
+/// Decode time tracker of frames in decode order.
+///
+/// Decode times set by encoder are kept, but made strictly increasing.
+/// Frames are released once reorder depth and the next frames are known.
+#[derive(Debug, Clone)]
+pub struct Reorder<T> {
+    /// Known or detected depth
+    depth: Option<u32>,
+    window: usize,
+    pending: VecDeque<Pending<T>>,
+
+    /// Presentation times not yet used as decode times, ascending
+    pts: Vec<i64>,
+    released: u64,
+    last_dts: Option<i64>,
+    dts_shift: Option<i64>,
+    is_finished: bool,
+}
+
+impl<T> Reorder<T> {
+    /// Detects reorder depth of the first `window` frames
+    pub fn new(window: usize) -> Self {
+        Self {
+            depth: None,
+            window: window.max(1),
+            pending: VecDeque::new(),
+            pts: Vec::new(),
+            released: 0,
+            last_dts: None,
+            dts_shift: None,
+            is_finished: false,
+        }
+    }
+
+    /// Reorder depth is known, e.g. 0 for streams without B frames
+    pub fn with_depth(depth: u32) -> Self {
+        let mut res = Self::new(DEFAULT_DETECTION_WINDOW);
+        res.depth = Some(depth);
+        res
+    }
+
+    /// Depth of VUI bitstream restriction, profiles without B frames or detection
+    pub fn with_h264_sps(sps: &h264::Sps) -> Self {
+        let is_intra = sps.constraint_flags & 0x10 != 0
+            && matches!(
+                sps.profile_idc,
+                44 | h264::Sps::PROFILE_HIGH_10
+                    | h264::Sps::PROFILE_HIGH_422
+                    | h264::Sps::PROFILE_HIGH_444
+            );
+        match sps.max_num_reorder_frames() {
+            Some(depth) => Self::with_depth(depth),
+            None if sps.profile_idc == h264::Sps::PROFILE_BASELINE || is_intra => {
+                Self::with_depth(0)
+            }
+            None => Self::new(DEFAULT_DETECTION_WINDOW),
+        }
+    }
+
+    /// Depth of `sps_max_num_reorder_pics` of the highest sub-layer
+    #[inline]
+    pub fn with_hevc_sps(sps: &hevc::Sps) -> Self {
+        Self::with_depth(sps.max_num_reorder_pics)
+    }
+
+    /// Known or detected reorder depth
+    #[inline]
+    pub fn depth(&self) -> Option<u32> {
+        self.depth
+    }
+
+    /// The earliest presentation time minus decode time of the first frame,
+    /// e.g. media time of edit list when decode time starts at 0
+    #[inline]
+    pub fn dts_shift(&self) -> Option<i64> {
+        self.dts_shift
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Adds frame in decode order, `dts` is `None` if encoder didn't set it
+    pub fn push(&mut self, pts: i64, dts: Option<i64>, duration: i64, item: T) {
+        let pos = self.pts.partition_point(|&p| p <= pts);
+        self.pts.insert(pos, pts);
+        self.pending.push_back(Pending {
+            pts,
+            dts,
+            duration,
+            item,
+        });
+    }
+
+    /// Adds frame of `cm::SampleBuf` timing converted to `timescale`,
+    /// invalid decode time is derived
+    #[cfg(feature = "cm")]
+    pub fn push_timing(&mut self, timing: &cm::SampleTimingInfo, timescale: i32, item: T) {
+        let convert = |t: cm::Time| t.convert_scale(timescale, cm::TimeRoundingMethod::default());
+        let pts = convert(timing.pts).value;
+        let dts = timing.dts.is_valid().then(|| convert(timing.dts).value);
+        let duration = if timing.duration.is_valid() {
+            convert(timing.duration).value
+        } else {
+            0
+        };
+        self.push(pts, dts, duration, item);
+    }
+
+    /// Releases the rest of frames with [`Reorder::pop`]
+    #[inline]
+    pub fn finish(&mut self) {
+        self.is_finished = true;
+    }
+
+    /// The next frame in decode order, if its decode time is known
+    pub fn pop(&mut self) -> Option<Sample<T>> {
+        let depth = match self.depth {
+            Some(depth) => depth,
+            None if self.pending.len() >= self.window || self.is_finished => {
+                let depth = self.detect_depth();
+                self.depth = Some(depth);
+                depth
+            }
+            None => return None,
+        };
+        // the first frames are extrapolated from the first two presentation times
+        let n = self.released;
+        let pushed = n + self.pending.len() as u64;
+        if n < depth as u64 && pushed < depth as u64 + 2 && !self.is_finished {
+            return None;
+        }
+        let frame = self.pending.pop_front()?;
+
+        let (n, d) = (n as i64, depth as i64);
+        let first_pts = self.pts[0];
+        let derived = if n >= d {
+            self.pts.remove(0)
+        } else {
+            let step = if frame.duration > 0 {
+                frame.duration
+            } else {
+                match self.pts[..] {
+                    [a, b, ..] if b > a => b - a,
+                    _ => 1,
+                }
+            };
+            first_pts - (d - n) * step
+        };
+        let mut dts = frame.dts.unwrap_or(derived);
+        if let Some(last) = self.last_dts {
+            dts = dts.max(last + 1);
+        }
+        if frame.dts.is_none() && dts > frame.pts {
+            // deeper than detected, later frames are decoded a tick apart until caught up
+            if n >= d {
+                self.pts.insert(0, derived);
+            }
+            self.depth = Some(depth + 1);
+        }
+        if self.dts_shift.is_none() {
+            self.dts_shift = Some(first_pts - dts);
+        }
+        self.released += 1;
+        self.last_dts = Some(dts);
+        Some(Sample {
+            pts: frame.pts,
+            dts,
+            duration: frame.duration,
+            item: frame.item,
+        })
+    }
+
+    /// Max number of frames preceding any buffered frame in decode order and
+    /// following it in presentation order
+    fn detect_depth(&self) -> u32 {
+        let mut depth = 0;
+        for (k, frame) in self.pending.iter().enumerate() {
+            let count = self.pending.iter().take(k).filter(|f| f.pts > frame.pts);
+            depth = depth.max(count.count() as u32);
+        }
+        depth
+    }
+}
+
+impl<T> Default for Reorder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_DETECTION_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Reorder;
+
+    fn drain(reorder: &mut Reorder<usize>) -> Vec<(i64, u32)> {
+        std::iter::from_fn(|| reorder.pop())
+            .map(|s| (s.dts, s.composition_offset()))
+            .collect()
+    }
+
+    #[test]
+    fn detection() {
+        // I P B B P B B with 3000 ticks frames and unknown durations
+        let pts = [0, 9000, 3000, 6000, 18000, 12000, 15000];
+        let mut reorder = Reorder::new(4);
+        for (i, &pts) in pts.iter().enumerate() {
+            reorder.push(pts, None, 0, i);
+            if i < 3 {
+                assert!(reorder.pop().is_none());
+            }
+        }
+        assert_eq!(reorder.depth(), None);
+        let samples = drain(&mut reorder);
+        assert_eq!(reorder.depth(), Some(1));
+        assert!(reorder.is_empty());
+        assert_eq!(
+            samples,
+            [
+                (-3000, 3000),
+                (0, 9000),
+                (3000, 0),
+                (6000, 0),
+                (9000, 9000),
+                (12000, 0),
+                (15000, 0)
+            ]
+        );
+        assert_eq!(reorder.dts_shift(), Some(3000));
+    }
+
+    #[test]
+    fn encoder_dts() {
+        let mut reorder = Reorder::with_depth(1);
+        // decode times are kept, the repeated one is moved forward
+        for (i, (pts, dts)) in [(10, 0), (30, 10), (20, 10), (40, 30)]
+            .into_iter()
+            .enumerate()
+        {
+            reorder.push(pts, Some(dts), 10, i);
+        }
+        reorder.finish();
+        assert_eq!(drain(&mut reorder), [(0, 10), (10, 20), (11, 9), (30, 10)]);
+        assert_eq!(reorder.dts_shift(), Some(10));
+    }
+
+    #[test]
+    fn deeper_than_detected() {
+        // I P B b with depth 2, but assumed 1
+        let mut reorder = Reorder::with_depth(1);
+        for (i, pts) in [0, 3, 2, 1, 4, 5].into_iter().enumerate() {
+            reorder.push(pts, None, 1, i);
+        }
+        reorder.finish();
+        let samples = drain(&mut reorder);
+        assert_eq!(reorder.depth(), Some(2));
+        let dts: Vec<_> = samples.iter().map(|s| s.0).collect();
+        assert!(dts.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(dts, [-1, 0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,541 @@
+//! RTMP handshake, chunk stream codec and messages for publishing FLV tag bodies.
+//!
+//! Sockets are left to the caller: handshake bytes and chunks are produced and consumed
+//! as byte buffers, so any transport or async runtime can carry them.
+//!
+//! ```
+//! use cidre::media::{flv, rtmp};
+//!
+//! let mut encoder = rtmp::ChunkEncoder::new();
+//! let mut out = Vec::new();
+//! encoder.write_chunk_size(&mut out, 4096).unwrap();
+//! let connect = rtmp::Command::connect("live", "rtmp://localhost/live");
+//! encoder.write(&mut out, 3, &connect.to_message(0)).unwrap();
+//!
+//! let frame = flv::VideoTag::coded_frames(flv::VideoFourCc::AVC, &[0, 0, 0, 1, 0x41], false, 0);
+//! let video = rtmp::Message::new(rtmp::MessageType::VIDEO, 1, 33, frame.to_bytes());
+//! encoder.write(&mut out, 6, &video).unwrap();
+//!
+//! let mut decoder = rtmp::ChunkDecoder::new();
+//! let messages = decoder.push(&out).unwrap();
+//! assert_eq!(decoder.chunk_size(), 4096);
+//! assert_eq!(rtmp::Command::parse(&messages[1]).unwrap().name, "connect");
+//! assert_eq!(messages[2], video);
+//! ```
+
+mod chunk;
+pub use chunk::ChunkDecoder;
+pub use chunk::ChunkEncoder;
+
+use crate::media::{amf, flv};
+
+/// Handshake version of C0 and S0
+pub const VERSION: u8 = 3;
+
+/// Length of C1, C2, S1 and S2
+pub const HANDSHAKE_LEN: usize = 1536;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 128;
+
+/// Chunk stream of protocol control messages
+pub const CONTROL_CHUNK_STREAM: u32 = 2;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Handshake version other than 3
+    InvalidVersion(u8),
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// Message payload exceeds 24-bit length
+    TooLarge(usize),
+
+    Amf(amf::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated RTMP data"),
+            Self::InvalidVersion(version) => write!(f, "unsupported RTMP version {version}"),
+            Self::InvalidValue(name) => write!(f, "invalid RTMP {name}"),
+            Self::TooLarge(len) => write!(f, "RTMP message of {len} bytes is too large"),
+            Self::Amf(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<amf::Error> for Error {
+    #[inline]
+    fn from(value: amf::Error) -> Self {
+        Self::Amf(value)
+    }
+}
+
+/// Fills handshake random bytes, simple handshake doesn't need them to be unpredictable
+fn fill_random(buf: &mut [u8], seed: u32) {
+    let mut state = seed | 1;
+    for b in buf {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        *b = state as u8;
+    }
+}
+
+/// C0 and C1 of simple handshake with time in milliseconds
+pub fn c0c1(time: u32) -> Vec<u8> {
+    let mut out = vec![0; 1 + HANDSHAKE_LEN];
+    out[0] = VERSION;
+    out[1..5].copy_from_slice(&time.to_be_bytes());
+    fill_random(&mut out[9..], time);
+    out
+}
+
+/// S0, S1 and S2 answering C0 and C1, for server stand-ins
+pub fn s0s1s2(hello: &[u8], time: u32) -> Result<Vec<u8>, Error> {
+    let c1 = read_hello(hello)?;
+    let mut out = c0c1(time);
+    // S2 echoes C1 with time S1 was read
+    out.extend_from_slice(c1);
+    out[1 + HANDSHAKE_LEN + 4..][..4].copy_from_slice(&time.to_be_bytes());
+    Ok(out)
+}
+
+/// C2 echoing S1 of S0, S1 and S2
+pub fn c2(s0s1s2: &[u8]) -> Result<Vec<u8>, Error> {
+    if s0s1s2.len() < 1 + 2 * HANDSHAKE_LEN {
+        return Err(Error::Truncated);
+    }
+    Ok(read_hello(s0s1s2)?.to_vec())
+}
+
+/// C1 or S1 of C0 and C1 or S0 and S1
+fn read_hello(data: &[u8]) -> Result<&[u8], Error> {
+    let (&version, rest) = data.split_first().ok_or(Error::Truncated)?;
+    if version != VERSION {
+        return Err(Error::InvalidVersion(version));
+    }
+    rest.get(..HANDSHAKE_LEN).ok_or(Error::Truncated)
+}
+
+/// Message type ID
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct MessageType(pub u8);
+
+impl MessageType {
+    pub const SET_CHUNK_SIZE: Self = Self(1);
+    pub const ABORT: Self = Self(2);
+    pub const ACKNOWLEDGEMENT: Self = Self(3);
+    pub const USER_CONTROL: Self = Self(4);
+    pub const WINDOW_ACK_SIZE: Self = Self(5);
+    pub const SET_PEER_BANDWIDTH: Self = Self(6);
+    pub const AUDIO: Self = Self(8);
+    pub const VIDEO: Self = Self(9);
+    pub const DATA_AMF3: Self = Self(15);
+    pub const SHARED_OBJECT_AMF3: Self = Self(16);
+    pub const COMMAND_AMF3: Self = Self(17);
+    pub const DATA_AMF0: Self = Self(18);
+    pub const SHARED_OBJECT_AMF0: Self = Self(19);
+    pub const COMMAND_AMF0: Self = Self(20);
+    pub const AGGREGATE: Self = Self(22);
+}
+
+/// Message of message stream, payload of audio and video messages are FLV tag bodies
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    /// Milliseconds
+    pub timestamp: u32,
+    pub message_type: MessageType,
+
+    /// Zero for protocol control and `NetConnection` commands
+    pub stream_id: u32,
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    #[inline]
+    pub fn new(
+        message_type: MessageType,
+        stream_id: u32,
+        timestamp: u32,
+        payload: Vec<u8>,
+    ) -> Self {
+        Self {
+            timestamp,
+            message_type,
+            stream_id,
+            payload,
+        }
+    }
+
+    #[inline]
+    fn control(message_type: MessageType, payload: &[u8]) -> Self {
+        Self::new(message_type, 0, 0, payload.to_vec())
+    }
+
+    #[inline]
+    pub fn set_chunk_size(size: u32) -> Self {
+        Self::control(MessageType::SET_CHUNK_SIZE, &size.to_be_bytes())
+    }
+
+    /// Abort of partially received message of chunk stream
+    #[inline]
+    pub fn abort(csid: u32) -> Self {
+        Self::control(MessageType::ABORT, &csid.to_be_bytes())
+    }
+
+    /// Acknowledgement of bytes received so far, truncated to 32 bits
+    #[inline]
+    pub fn acknowledgement(bytes_received: u64) -> Self {
+        Self::control(
+            MessageType::ACKNOWLEDGEMENT,
+            &(bytes_received as u32).to_be_bytes(),
+        )
+    }
+
+    #[inline]
+    pub fn window_ack_size(size: u32) -> Self {
+        Self::control(MessageType::WINDOW_ACK_SIZE, &size.to_be_bytes())
+    }
+
+    /// Set Peer Bandwidth with limit type 0 hard, 1 soft or 2 dynamic
+    #[inline]
+    pub fn set_peer_bandwidth(size: u32, limit_type: u8) -> Self {
+        let b = size.to_be_bytes();
+        Self::control(
+            MessageType::SET_PEER_BANDWIDTH,
+            &[b[0], b[1], b[2], b[3], limit_type],
+        )
+    }
+
+    /// User Control Stream Begin event
+    #[inline]
+    pub fn stream_begin(stream_id: u32) -> Self {
+        let b = stream_id.to_be_bytes();
+        Self::control(MessageType::USER_CONTROL, &[0, 0, b[0], b[1], b[2], b[3]])
+    }
+
+    /// AMF0 command or data message of values
+    pub fn amf0(message_type: MessageType, stream_id: u32, values: &[amf::Value]) -> Self {
+        let mut payload = Vec::new();
+        for v in values {
+            amf::amf0::write(&mut payload, v);
+        }
+        Self::new(message_type, stream_id, 0, payload)
+    }
+
+    /// `@setDataFrame` data message of `onMetaData`
+    pub fn metadata(stream_id: u32, metadata: &flv::Metadata) -> Self {
+        let values = [
+            "@setDataFrame".into(),
+            "onMetaData".into(),
+            metadata.to_value(),
+        ];
+        Self::amf0(MessageType::DATA_AMF0, stream_id, &values)
+    }
+
+    /// Values of AMF0 or AMF3 command or data message
+    pub fn values(&self) -> Result<Vec<amf::Value>, Error> {
+        let payload = match self.message_type {
+            MessageType::COMMAND_AMF0 | MessageType::DATA_AMF0 => &self.payload[..],
+            // AMF3 messages start with format selector and are AMF0 with AMF3 switches
+            MessageType::COMMAND_AMF3 | MessageType::DATA_AMF3 => {
+                self.payload.get(1..).ok_or(Error::Truncated)?
+            }
+            _ => return Err(Error::InvalidValue("message type of AMF values")),
+        };
+        Ok(amf::amf0::read_all(payload)?)
+    }
+
+    /// 32-bit value of protocol control message
+    pub fn control_value(&self) -> Result<u32, Error> {
+        let b = self.payload.get(..4).ok_or(Error::Truncated)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// `NetConnection` or `NetStream` command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub name: String,
+
+    /// Zero for commands without response
+    pub transaction_id: f64,
+
+    /// Command object, `Null` for most stream commands
+    pub object: amf::Value,
+    pub args: Vec<amf::Value>,
+}
+
+impl Command {
+    #[inline]
+    pub fn new(name: &str, transaction_id: f64, object: amf::Value) -> Self {
+        Self {
+            name: name.to_string(),
+            transaction_id,
+            object,
+            args: Vec::new(),
+        }
+    }
+
+    /// `connect` of application with `tcUrl` and Enhanced RTMP `fourCcList`
+    pub fn connect(app: &str, tc_url: &str) -> Self {
+        let four_ccs = ["avc1", "hvc1", "av01", "vp09"];
+        let object = amf::Value::object([
+            ("app", app.into()),
+            ("type", "nonprivate".into()),
+            ("flashVer", "FMLE/3.0 (compatible; cidre)".into()),
+            ("tcUrl", tc_url.into()),
+            (
+                "fourCcList",
+                amf::Value::Array(four_ccs.iter().map(|&f| f.into()).collect()),
+            ),
+        ]);
+        Self::new("connect", 1.0, object)
+    }
+
+    #[inline]
+    pub fn create_stream(transaction_id: f64) -> Self {
+        Self::new("createStream", transaction_id, amf::Value::Null)
+    }
+
+    /// `publish` of live stream
+    pub fn publish(stream_name: &str) -> Self {
+        let mut command = Self::new("publish", 0.0, amf::Value::Null);
+        command.args = vec![stream_name.into(), "live".into()];
+        command
+    }
+
+    /// AMF0 command message
+    pub fn to_message(&self, stream_id: u32) -> Message {
+        let mut payload = Vec::new();
+        amf::amf0::write(&mut payload, &self.name.as_str().into());
+        amf::amf0::write(&mut payload, &self.transaction_id.into());
+        amf::amf0::write(&mut payload, &self.object);
+        for arg in &self.args {
+            amf::amf0::write(&mut payload, arg);
+        }
+        Message::new(MessageType::COMMAND_AMF0, stream_id, 0, payload)
+    }
+
+    /// Command of AMF0 or AMF3 command message
+    pub fn parse(message: &Message) -> Result<Self, Error> {
+        if message.message_type != MessageType::COMMAND_AMF0
+            && message.message_type != MessageType::COMMAND_AMF3
+        {
+            return Err(Error::InvalidValue("command message type"));
+        }
+        let mut values = message.values()?.into_iter();
+        let name = match values.next() {
+            Some(amf::Value::String(name)) => name,
+            _ => return Err(Error::InvalidValue("command name")),
+        };
+        let transaction_id = values.next().and_then(|v| v.as_f64()).unwrap_or(0.0);
+        Ok(Self {
+            name,
+            transaction_id,
+            object: values.next().unwrap_or_default(),
+            args: values.collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        time::Duration,
+    };
+
+    use super::{ChunkDecoder, ChunkEncoder, Command, Error, Message, MessageType, HANDSHAKE_LEN};
+    use crate::media::{amf, flv, h264};
+
+    #[test]
+    fn handshake() {
+        let c0c1 = super::c0c1(1000);
+        assert_eq!(c0c1.len(), 1 + HANDSHAKE_LEN);
+        assert_eq!(c0c1[..9], [3, 0, 0, 3, 0xe8, 0, 0, 0, 0]);
+        let s = super::s0s1s2(&c0c1, 2000).unwrap();
+        assert_eq!(s.len(), 1 + 2 * HANDSHAKE_LEN);
+        assert_eq!(s[1 + HANDSHAKE_LEN..][..4], c0c1[1..5]);
+        assert_eq!(s[1 + HANDSHAKE_LEN + 8..], c0c1[9..]);
+        assert_eq!(super::c2(&s).unwrap(), s[1..1 + HANDSHAKE_LEN]);
+
+        let mut c0c1 = c0c1;
+        c0c1[0] = 6;
+        assert_eq!(super::s0s1s2(&c0c1, 0), Err(Error::InvalidVersion(6)));
+        assert_eq!(super::c2(&s[..100]), Err(Error::Truncated));
+    }
+
+    /// Reads messages until one matches
+    fn read_until(
+        stream: &mut TcpStream,
+        decoder: &mut ChunkDecoder,
+        pred: impl Fn(&Message) -> bool,
+    ) -> Vec<Message> {
+        let mut messages = Vec::new();
+        let mut buf = [0; 4096];
+        while !messages.iter().any(&pred) {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0, "connection closed");
+            messages.extend(decoder.push(&buf[..n]).unwrap());
+        }
+        messages
+    }
+
+    /// Server stand-in accepting publishing of one stream, returns received media messages
+    fn serve(listener: TcpListener) -> Vec<Message> {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut c0c1 = vec![0; 1 + HANDSHAKE_LEN];
+        stream.read_exact(&mut c0c1).unwrap();
+        stream.write_all(&super::s0s1s2(&c0c1, 0).unwrap()).unwrap();
+        let mut c2 = vec![0; HANDSHAKE_LEN];
+        stream.read_exact(&mut c2).unwrap();
+
+        let mut decoder = ChunkDecoder::new();
+        let mut encoder = ChunkEncoder::new();
+        let is_command =
+            |name: &'static str| move |m: &Message| Command::parse(m).is_ok_and(|c| c.name == name);
+        let mut messages = read_until(&mut stream, &mut decoder, is_command("connect"));
+
+        let mut out = Vec::new();
+        encoder
+            .write(&mut out, 2, &Message::window_ack_size(2_500_000))
+            .unwrap();
+        encoder
+            .write(&mut out, 2, &Message::set_peer_bandwidth(2_500_000, 2))
+            .unwrap();
+        encoder.write_chunk_size(&mut out, 4096).unwrap();
+        let mut result = Command::new("_result", 1.0, amf::Value::Null);
+        result.args.push(amf::Value::object([(
+            "code",
+            "NetConnection.Connect.Success".into(),
+        )]));
+        encoder.write(&mut out, 3, &result.to_message(0)).unwrap();
+        stream.write_all(&out).unwrap();
+
+        messages.extend(read_until(
+            &mut stream,
+            &mut decoder,
+            is_command("createStream"),
+        ));
+        let create = messages
+            .iter()
+            .rev()
+            .find_map(|m| Command::parse(m).ok())
+            .unwrap();
+        let mut result = Command::new("_result", create.transaction_id, amf::Value::Null);
+        result.args.push(1.0.into());
+        out.clear();
+        encoder.write(&mut out, 3, &result.to_message(0)).unwrap();
+        stream.write_all(&out).unwrap();
+
+        messages.extend(read_until(&mut stream, &mut decoder, |m| {
+            m.message_type == MessageType::VIDEO && m.timestamp > 0
+        }));
+        messages.into_iter().filter(|m| m.stream_id == 1).collect()
+    }
+
+    #[test]
+    fn loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || serve(listener));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        stream.write_all(&super::c0c1(0)).unwrap();
+        let mut s = vec![0; 1 + 2 * HANDSHAKE_LEN];
+        stream.read_exact(&mut s).unwrap();
+        stream.write_all(&super::c2(&s).unwrap()).unwrap();
+
+        let mut encoder = ChunkEncoder::new();
+        let mut decoder = ChunkDecoder::new();
+        let mut out = Vec::new();
+        encoder.write_chunk_size(&mut out, 60_000).unwrap();
+        let connect = Command::connect("live", &format!("rtmp://{addr}/live"));
+        encoder.write(&mut out, 3, &connect.to_message(0)).unwrap();
+        stream.write_all(&out).unwrap();
+        let is_result = |m: &Message| Command::parse(m).is_ok_and(|c| c.name == "_result");
+        let messages = read_until(&mut stream, &mut decoder, is_result);
+        assert_eq!(messages[0], Message::window_ack_size(2_500_000));
+        assert_eq!(decoder.chunk_size(), 4096);
+
+        out.clear();
+        encoder
+            .write(&mut out, 3, &Command::create_stream(2.0).to_message(0))
+            .unwrap();
+        stream.write_all(&out).unwrap();
+        let messages = read_until(&mut stream, &mut decoder, is_result);
+        let result = Command::parse(messages.last().unwrap()).unwrap();
+        assert_eq!(result.transaction_id, 2.0);
+        let stream_id = result.args[0].as_f64().unwrap() as u32;
+
+        let avcc = h264::Avcc::with_param_sets(
+            &[0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4],
+            &[0x68, 0xce, 0x3c, 0x80],
+            4,
+        )
+        .unwrap();
+        let metadata = flv::Metadata {
+            width: Some(640),
+            height: Some(480),
+            video_codec: Some(flv::VideoFourCc::AVC),
+            ..Default::default()
+        };
+        let mut idr = vec![0, 0, 0xff, 0xfc, 0x65];
+        idr.resize(0xff_fc + 4, 0x88);
+        let messages = [
+            Command::publish("stream").to_message(stream_id),
+            Message::metadata(stream_id, &metadata),
+            Message::new(
+                MessageType::VIDEO,
+                stream_id,
+                0,
+                flv::avc_sequence_header(&avcc),
+            ),
+            Message::new(
+                MessageType::VIDEO,
+                stream_id,
+                0,
+                flv::VideoTag::coded_frames(flv::VideoFourCc::AVC, &idr, true, 0).to_bytes(),
+            ),
+            Message::new(
+                MessageType::VIDEO,
+                stream_id,
+                33,
+                flv::VideoTag::coded_frames(flv::VideoFourCc::AVC, &[0, 0, 0, 1, 0x41], false, 0)
+                    .to_bytes(),
+            ),
+        ];
+        out.clear();
+        for (i, m) in messages.iter().enumerate() {
+            encoder.write(&mut out, 4 + i as u32, m).unwrap();
+        }
+        stream.write_all(&out).unwrap();
+
+        let received = server.join().unwrap();
+        assert_eq!(received, messages);
+        assert_eq!(
+            flv::Metadata::parse(&received[1].payload).unwrap(),
+            metadata
+        );
+        let tag = flv::VideoTag::parse(&received[3].payload).unwrap();
+        assert!(tag.is_key());
+        assert_eq!(tag.data, idr);
+    }
+}
@@ -0,0 +1,417 @@
+//! Chunk stream, messages split into chunks with compressed headers.
+
+use std::collections::HashMap;
+
+use super::{Error, Message, MessageType, CONTROL_CHUNK_STREAM, DEFAULT_CHUNK_SIZE};
+
+/// Timestamps from this value on are carried in extended timestamp field
+const EXTENDED_TIMESTAMP: u32 = 0xff_ffff;
+
+const MAX_CHUNK_SIZE: usize = 0x7fff_ffff;
+
+/// Message header of the last chunk of chunk stream
+#[derive(Debug, Clone, Default)]
+struct StreamHeader {
+    timestamp: u32,
+
+    /// Timestamp field of the last header, absolute one for type 0 headers
+    delta: u32,
+    length: usize,
+    message_type: MessageType,
+    stream_id: u32,
+    extended: bool,
+}
+
+/// Appends basic header of chunk stream ID 2 to 65599
+fn write_basic_header(out: &mut Vec<u8>, fmt: u8, csid: u32) {
+    match csid {
+        0..=63 => out.push(fmt << 6 | csid as u8),
+        64..=319 => out.extend_from_slice(&[fmt << 6, (csid - 64) as u8]),
+        _ => {
+            let id = (csid - 64) as u16;
+            out.push(fmt << 6 | 1);
+            out.extend_from_slice(&id.to_le_bytes());
+        }
+    }
+}
+
+#[inline]
+fn push_u24(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes()[1..]);
+}
+
+/// Splits messages into chunks
+#[derive(Debug, Clone)]
+pub struct ChunkEncoder {
+    chunk_size: usize,
+    streams: HashMap<u32, StreamHeader>,
+}
+
+impl Default for ChunkEncoder {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            streams: HashMap::new(),
+        }
+    }
+}
+
+impl ChunkEncoder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Appends Set Chunk Size message and uses the size for following chunks
+    pub fn write_chunk_size(&mut self, out: &mut Vec<u8>, chunk_size: usize) -> Result<(), Error> {
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err(Error::InvalidValue("chunk size"));
+        }
+        self.write(
+            out,
+            CONTROL_CHUNK_STREAM,
+            &Message::set_chunk_size(chunk_size as u32),
+        )?;
+        self.chunk_size = chunk_size;
+        Ok(())
+    }
+
+    /// Appends chunks of message on chunk stream `csid`.
+    ///
+    /// Headers are compressed against the previous message of the chunk stream,
+    /// so messages of different message streams should use different chunk streams.
+    pub fn write(&mut self, out: &mut Vec<u8>, csid: u32, message: &Message) -> Result<(), Error> {
+        if !(2..=65599).contains(&csid) {
+            return Err(Error::InvalidValue("chunk stream ID"));
+        }
+        let length = message.payload.len();
+        if length > 0xff_ffff {
+            return Err(Error::TooLarge(length));
+        }
+        let prev = self.streams.get(&csid);
+        let delta = prev.map(|p| message.timestamp.wrapping_sub(p.timestamp));
+        let (fmt, field) = match prev {
+            Some(p) if p.stream_id == message.stream_id && message.timestamp >= p.timestamp => {
+                let delta = delta.unwrap();
+                if p.length != length || p.message_type != message.message_type {
+                    (1, delta)
+                } else if p.delta != delta {
+                    (2, delta)
+                } else {
+                    (3, delta)
+                }
+            }
+            _ => (0, message.timestamp),
+        };
+        let extended = field >= EXTENDED_TIMESTAMP;
+
+        write_basic_header(out, fmt, csid);
+        if fmt < 3 {
+            push_u24(out, field.min(EXTENDED_TIMESTAMP));
+        }
+        if fmt < 2 {
+            push_u24(out, length as u32);
+            out.push(message.message_type.0);
+        }
+        if fmt == 0 {
+            out.extend_from_slice(&message.stream_id.to_le_bytes());
+        }
+        if extended {
+            out.extend_from_slice(&field.to_be_bytes());
+        }
+
+        let mut chunks = message.payload.chunks(self.chunk_size);
+        out.extend_from_slice(chunks.next().unwrap_or_default());
+        for chunk in chunks {
+            write_basic_header(out, 3, csid);
+            if extended {
+                out.extend_from_slice(&field.to_be_bytes());
+            }
+            out.extend_from_slice(chunk);
+        }
+
+        self.streams.insert(
+            csid,
+            StreamHeader {
+                timestamp: message.timestamp,
+                delta: field,
+                length,
+                message_type: message.message_type,
+                stream_id: message.stream_id,
+                extended,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct InStream {
+    header: StreamHeader,
+
+    /// Payload of message being received
+    partial: Vec<u8>,
+}
+
+/// Reassembles messages of chunks, applies received Set Chunk Size and Abort messages
+#[derive(Debug, Clone)]
+pub struct ChunkDecoder {
+    chunk_size: usize,
+    buf: Vec<u8>,
+    streams: HashMap<u32, InStream>,
+    bytes_received: u64,
+}
+
+impl Default for ChunkDecoder {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            buf: Vec::new(),
+            streams: HashMap::new(),
+            bytes_received: 0,
+        }
+    }
+}
+
+impl ChunkDecoder {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chunk size of the peer
+    #[inline]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Bytes pushed so far, for Acknowledgement messages
+    #[inline]
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Messages completed by data, incomplete chunks are kept until more data arrives
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<Message>, Error> {
+        self.bytes_received += data.len() as u64;
+        self.buf.extend_from_slice(data);
+        let buf = std::mem::take(&mut self.buf);
+        let mut messages = Vec::new();
+        let mut pos = 0;
+        let res = loop {
+            match self.read_chunk(&buf[pos..]) {
+                Ok(Some((len, message))) => {
+                    pos += len;
+                    if let Some(message) = message {
+                        messages.push(message);
+                    }
+                }
+                Ok(None) => break Ok(messages),
+                Err(err) => break Err(err),
+            }
+        };
+        self.buf = buf;
+        self.buf.drain(..pos);
+        res
+    }
+
+    /// Length of the chunk and message completed by it, `None` if chunk is incomplete
+    fn read_chunk(&mut self, data: &[u8]) -> Result<Option<(usize, Option<Message>)>, Error> {
+        let Some(&first) = data.first() else {
+            return Ok(None);
+        };
+        let fmt = first >> 6;
+        let (csid, mut pos) = match first & 0x3f {
+            0 => match data.get(1) {
+                Some(&b) => (b as u32 + 64, 2),
+                None => return Ok(None),
+            },
+            1 => match data.get(1..3) {
+                Some(b) => (u16::from_le_bytes([b[0], b[1]]) as u32 + 64, 3),
+                None => return Ok(None),
+            },
+            id => (id as u32, 1),
+        };
+        let header_len = [11, 7, 3, 0][fmt as usize];
+        let Some(fields) = data.get(pos..pos + header_len) else {
+            return Ok(None);
+        };
+        pos += header_len;
+
+        let stream = self.streams.get(&csid);
+        if fmt != 0 && stream.is_none() {
+            return Err(Error::InvalidValue("chunk stream without previous header"));
+        }
+        let mut header = stream.map(|s| s.header.clone()).unwrap_or_default();
+        let starts_message = stream.map_or(true, |s| s.partial.is_empty());
+        let u24 = |b: &[u8]| u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        if fmt < 3 {
+            header.delta = u24(fields);
+            header.extended = header.delta == EXTENDED_TIMESTAMP;
+        }
+        if fmt < 2 {
+            header.length = u24(&fields[3..]) as usize;
+            header.message_type = MessageType(fields[6]);
+        }
+        if fmt == 0 {
+            header.stream_id = u32::from_le_bytes(fields[7..11].try_into().unwrap());
+        }
+        if header.extended {
+            let Some(ext) = data.get(pos..pos + 4) else {
+                return Ok(None);
+            };
+            pos += 4;
+            // type 3 continuation chunks repeat extended timestamp of the message
+            if fmt < 3 || starts_message {
+                header.delta = u32::from_be_bytes(ext.try_into().unwrap());
+            }
+        }
+        if starts_message {
+            header.timestamp = match fmt {
+                0 => header.delta,
+                _ => header.timestamp.wrapping_add(header.delta),
+            };
+        } else if fmt != 3 {
+            return Err(Error::InvalidValue("chunk header inside message"));
+        }
+
+        let received = stream.map_or(0, |s| s.partial.len());
+        let len = (header.length - received).min(self.chunk_size);
+        let Some(payload) = data.get(pos..pos + len) else {
+            return Ok(None);
+        };
+        pos += len;
+
+        let stream = self.streams.entry(csid).or_default();
+        stream.header = header;
+        stream.partial.extend_from_slice(payload);
+        if stream.partial.len() < stream.header.length {
+            return Ok(Some((pos, None)));
+        }
+        let message = Message {
+            timestamp: stream.header.timestamp,
+            message_type: stream.header.message_type,
+            stream_id: stream.header.stream_id,
+            payload: std::mem::take(&mut stream.partial),
+        };
+        match message.message_type {
+            MessageType::SET_CHUNK_SIZE => {
+                let size = message.control_value()? & 0x7fff_ffff;
+                if size == 0 {
+                    return Err(Error::InvalidValue("chunk size"));
+                }
+                self.chunk_size = size as usize;
+            }
+            MessageType::ABORT => {
+                if let Some(s) = self.streams.get_mut(&message.control_value()?) {
+                    s.partial.clear();
+                }
+            }
+            _ => {}
+        }
+        Ok(Some((pos, Some(message))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkDecoder, ChunkEncoder};
+    use crate::media::rtmp::{Error, Message, MessageType};
+
+    #[test]
+    fn headers() {
+        let mut encoder = ChunkEncoder::new();
+        let mut out = Vec::new();
+        let video = |ts, len| Message::new(MessageType::VIDEO, 1, ts, vec![0xaa; len]);
+        encoder.write(&mut out, 6, &video(1000, 10)).unwrap();
+        assert_eq!(out[..12], [0x06, 0, 0x03, 0xe8, 0, 0, 10, 9, 1, 0, 0, 0]);
+        out.clear();
+
+        // length changes, type 1
+        encoder.write(&mut out, 6, &video(1040, 20)).unwrap();
+        assert_eq!(out[..8], [0x46, 0, 0, 40, 0, 0, 20, 9]);
+        out.clear();
+
+        // delta changes, type 2, then type 3
+        encoder.write(&mut out, 6, &video(1073, 20)).unwrap();
+        assert_eq!(out[..4], [0x86, 0, 0, 33]);
+        out.clear();
+        encoder.write(&mut out, 6, &video(1106, 20)).unwrap();
+        assert_eq!(out[0], 0xc6);
+        assert_eq!(out.len(), 21);
+        out.clear();
+
+        // split into chunks of 128 with large chunk stream ID
+        encoder.write(&mut out, 400, &video(0, 300)).unwrap();
+        assert_eq!(out[..3], [0x01, 0x50, 0x01]);
+        assert_eq!(out.len(), 3 + 11 + 300 + 2 * 3);
+        assert_eq!(out[3 + 11 + 128..3 + 11 + 131], [0xc1, 0x50, 0x01]);
+
+        let mut decoder = ChunkDecoder::new();
+        let messages = decoder.push(&out).unwrap();
+        assert_eq!(messages, [video(0, 300)]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut encoder = ChunkEncoder::new();
+        let mut out = Vec::new();
+        encoder.write_chunk_size(&mut out, 64).unwrap();
+        let messages = [
+            Message::new(MessageType::AUDIO, 1, 0, vec![1; 100]),
+            Message::new(MessageType::AUDIO, 1, 23, vec![2; 100]),
+            Message::new(MessageType::AUDIO, 1, 46, vec![3; 100]),
+            Message::new(MessageType::AUDIO, 1, 69, vec![]),
+            // extended timestamps
+            Message::new(MessageType::AUDIO, 1, 0x100_0000, vec![4; 200]),
+            Message::new(MessageType::AUDIO, 1, 0x200_0000, vec![5; 200]),
+            Message::new(MessageType::VIDEO, 1, 0x200_0000, vec![6; 200]),
+        ];
+        for m in &messages {
+            let csid = if m.message_type == MessageType::VIDEO {
+                6
+            } else {
+                4
+            };
+            encoder.write(&mut out, csid, m).unwrap();
+        }
+
+        // byte by byte
+        let mut decoder = ChunkDecoder::new();
+        let mut decoded = Vec::new();
+        for b in &out {
+            decoded.extend(decoder.push(&[*b]).unwrap());
+        }
+        assert_eq!(decoder.chunk_size(), 64);
+        assert_eq!(decoder.bytes_received(), out.len() as u64);
+        assert_eq!(decoded[0], Message::set_chunk_size(64));
+        assert_eq!(decoded[1..], messages);
+
+        assert_eq!(
+            ChunkDecoder::new().push(&[0x46, 0, 0, 0, 0, 0, 0, 9]),
+            Err(Error::InvalidValue("chunk stream without previous header"))
+        );
+        assert!(encoder.write(&mut out, 1, &messages[0]).is_err());
+    }
+
+    #[test]
+    fn abort() {
+        let mut encoder = ChunkEncoder::new();
+        let mut out = Vec::new();
+        let video = Message::new(MessageType::VIDEO, 1, 0, vec![1; 200]);
+        encoder.write(&mut out, 6, &video).unwrap();
+        // first chunk only, then abort and a new message
+        out.truncate(12 + 128);
+        encoder.write(&mut out, 2, &Message::abort(6)).unwrap();
+        let mut encoder = ChunkEncoder::new();
+        encoder.write(&mut out, 6, &video).unwrap();
+
+        let messages = ChunkDecoder::new().push(&out).unwrap();
+        assert_eq!(messages, [Message::abort(6), video]);
+    }
+}
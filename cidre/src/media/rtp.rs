@@ -0,0 +1,557 @@
+//! RTP (RFC 3550) packets with header extensions (RFC 8285) and payload formats
+//! for H.264 (RFC 6184), HEVC (RFC 7798), AAC (RFC 3640) and Opus (RFC 7587).
+//!
+//! [`Sender`] splits encoded frames into packets, [`Receiver`] reorders packets
+//! in [`JitterBuffer`] and reassembles complete frames, reporting lost packets.
+//!
+//! ```
+//! use cidre::media::rtp;
+//!
+//! let mut sender = rtp::Sender::new(rtp::OpusPacketizer::new(1200), 111, 0x1234, 48_000);
+//! let mut receiver = rtp::Receiver::new(rtp::OpusDepacketizer::new(), 48_000, Default::default());
+//!
+//! let mut frames = Vec::new();
+//! for i in 0..3u8 {
+//!     for packet in sender.send(&[i; 40], 960 * i as i64).unwrap() {
+//!         frames.extend(receiver.push(&packet).unwrap());
+//!     }
+//! }
+//! assert_eq!(frames.len(), 3);
+//! assert_eq!(frames[2].timestamp, 1920);
+//! assert_eq!(receiver.stats().lost, 0);
+//! ```
+
+mod aac;
+pub use aac::AacDepacketizer;
+pub use aac::AacPacketizer;
+
+mod h264;
+pub use h264::H264Depacketizer;
+pub use h264::H264Packetizer;
+
+mod hevc;
+pub use hevc::HevcDepacketizer;
+pub use hevc::HevcPacketizer;
+
+mod jitter;
+pub use jitter::JitterBuffer;
+pub use jitter::JitterConfig;
+pub use jitter::Receiver;
+pub use jitter::Released;
+pub use jitter::Stats;
+
+mod opus;
+pub use opus::OpusDepacketizer;
+pub use opus::OpusPacketizer;
+
+use crate::media::nal;
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+pub const VERSION: u8 = 2;
+pub const HEADER_LEN: usize = 12;
+
+/// Profile of RFC 8285 one-byte header extension elements
+pub const ONE_BYTE_PROFILE: u16 = 0xbede;
+
+/// Profile of RFC 8285 two-byte header extension elements, low 4 bits are app bits
+pub const TWO_BYTE_PROFILE: u16 = 0x1000;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Version is not 2
+    InvalidVersion(u8),
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// Frame or NAL unit doesn't fit into packet
+    TooLarge(usize),
+
+    Nal(nal::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated RTP packet"),
+            Self::InvalidVersion(v) => write!(f, "invalid RTP version {v}"),
+            Self::InvalidValue(name) => write!(f, "invalid RTP {name}"),
+            Self::TooLarge(len) => write!(f, "{len} bytes don't fit into RTP packet"),
+            Self::Nal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<nal::Error> for Error {
+    #[inline]
+    fn from(value: nal::Error) -> Self {
+        Self::Nal(value)
+    }
+}
+
+/// Header extension
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Extension {
+    pub profile: u16,
+
+    /// Padded to 32-bit words when written
+    pub data: Vec<u8>,
+}
+
+impl Extension {
+    /// RFC 8285 extension of `(id, data)` elements, one-byte form is used if
+    /// all ids are in 1..=14 and data lengths in 1..=16.
+    ///
+    /// ```
+    /// use cidre::media::rtp;
+    ///
+    /// let ext = rtp::Extension::with_elements(&[(1, &[0xaa]), (3, &[1, 2])]).unwrap();
+    /// assert_eq!(ext.profile, rtp::ONE_BYTE_PROFILE);
+    /// assert_eq!(ext.elements().unwrap(), [(1, &[0xaa][..]), (3, &[1, 2])]);
+    /// ```
+    pub fn with_elements(elements: &[(u8, &[u8])]) -> Result<Self, Error> {
+        let one_byte = elements
+            .iter()
+            .all(|(id, data)| (1..=14).contains(id) && (1..=16).contains(&data.len()));
+        let mut data = Vec::new();
+        for &(id, element) in elements {
+            if id == 0 {
+                return Err(Error::InvalidValue("extension element id"));
+            }
+            if one_byte {
+                data.push(id << 4 | (element.len() - 1) as u8);
+            } else {
+                let len =
+                    u8::try_from(element.len()).map_err(|_| Error::TooLarge(element.len()))?;
+                data.extend_from_slice(&[id, len]);
+            }
+            data.extend_from_slice(element);
+        }
+        let profile = if one_byte {
+            ONE_BYTE_PROFILE
+        } else {
+            TWO_BYTE_PROFILE
+        };
+        Ok(Self { profile, data })
+    }
+
+    /// `(id, data)` elements of RFC 8285 extension
+    pub fn elements(&self) -> Result<Vec<(u8, &[u8])>, Error> {
+        let one_byte = match self.profile {
+            ONE_BYTE_PROFILE => true,
+            p if p & 0xfff0 == TWO_BYTE_PROFILE => false,
+            _ => return Err(Error::InvalidValue("extension profile")),
+        };
+        let mut res = Vec::new();
+        let mut rest = &self.data[..];
+        while let Some((&first, tail)) = rest.split_first() {
+            // padding
+            if first == 0 {
+                rest = tail;
+                continue;
+            }
+            let (id, len, tail) = if one_byte {
+                if first >> 4 == 15 {
+                    break;
+                }
+                (first >> 4, (first & 0xf) as usize + 1, tail)
+            } else {
+                let (&len, tail) = tail.split_first().ok_or(Error::Truncated)?;
+                (first, len as usize, tail)
+            };
+            let (element, tail) = tail.split_at_checked(len).ok_or(Error::Truncated)?;
+            res.push((id, element));
+            rest = tail;
+        }
+        Ok(res)
+    }
+}
+
+/// Fixed header with CSRCs and extension
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Header {
+    pub marker: bool,
+    pub payload_type: u8,
+    pub sequence: u16,
+    pub timestamp: u32,
+    pub ssrc: u32,
+    pub csrc: Vec<u32>,
+    pub extension: Option<Extension>,
+}
+
+impl Header {
+    /// Header and payload of packet without padding.
+    ///
+    /// ```
+    /// use cidre::media::rtp;
+    ///
+    /// let header = rtp::Header {
+    ///     marker: true,
+    ///     payload_type: 96,
+    ///     sequence: 7,
+    ///     timestamp: 90_000,
+    ///     ssrc: 42,
+    ///     ..Default::default()
+    /// };
+    /// let mut packet = Vec::new();
+    /// header.write(&mut packet);
+    /// packet.extend_from_slice(b"payload");
+    /// assert_eq!(packet[..2], [0x80, 0xe0]);
+    ///
+    /// let (parsed, payload) = rtp::Header::parse(&packet).unwrap();
+    /// assert_eq!(parsed, header);
+    /// assert_eq!(payload, b"payload");
+    /// ```
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let fixed = data.get(..HEADER_LEN).ok_or(Error::Truncated)?;
+        let version = fixed[0] >> 6;
+        if version != VERSION {
+            return Err(Error::InvalidVersion(version));
+        }
+        let mut rest = &data[HEADER_LEN..];
+        if fixed[0] & 0x20 != 0 {
+            let &pad = rest.last().ok_or(Error::Truncated)?;
+            if pad == 0 || pad as usize > rest.len() {
+                return Err(Error::InvalidValue("padding"));
+            }
+            rest = &rest[..rest.len() - pad as usize];
+        }
+        let csrc_count = (fixed[0] & 0xf) as usize;
+        let (csrc, tail) = rest
+            .split_at_checked(csrc_count * 4)
+            .ok_or(Error::Truncated)?;
+        rest = tail;
+        let extension = if fixed[0] & 0x10 != 0 {
+            let ext = rest.get(..4).ok_or(Error::Truncated)?;
+            let len = u16::from_be_bytes([ext[2], ext[3]]) as usize * 4;
+            let data = rest.get(4..4 + len).ok_or(Error::Truncated)?;
+            let ext = Extension {
+                profile: u16::from_be_bytes([ext[0], ext[1]]),
+                data: data.to_vec(),
+            };
+            rest = &rest[4 + len..];
+            Some(ext)
+        } else {
+            None
+        };
+        let header = Self {
+            marker: fixed[1] & 0x80 != 0,
+            payload_type: fixed[1] & 0x7f,
+            sequence: u16::from_be_bytes([fixed[2], fixed[3]]),
+            timestamp: u32::from_be_bytes([fixed[4], fixed[5], fixed[6], fixed[7]]),
+            ssrc: u32::from_be_bytes([fixed[8], fixed[9], fixed[10], fixed[11]]),
+            csrc: csrc
+                .chunks_exact(4)
+                .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+            extension,
+        };
+        Ok((header, rest))
+    }
+
+    /// Length of written header
+    #[inline]
+    pub fn size(&self) -> usize {
+        HEADER_LEN
+            + self.csrc.len() * 4
+            + self
+                .extension
+                .as_ref()
+                .map_or(0, |e| 4 + e.data.len().next_multiple_of(4))
+    }
+
+    /// Appends header, at most 15 CSRCs are written
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let csrc = &self.csrc[..self.csrc.len().min(15)];
+        let ext_flag = if self.extension.is_some() { 0x10 } else { 0 };
+        out.push(VERSION << 6 | ext_flag | csrc.len() as u8);
+        out.push((self.marker as u8) << 7 | self.payload_type & 0x7f);
+        out.extend_from_slice(&self.sequence.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.ssrc.to_be_bytes());
+        for c in csrc {
+            out.extend_from_slice(&c.to_be_bytes());
+        }
+        if let Some(ext) = &self.extension {
+            let len = ext.data.len().next_multiple_of(4);
+            out.extend_from_slice(&ext.profile.to_be_bytes());
+            out.extend_from_slice(&((len / 4) as u16).to_be_bytes());
+            out.extend_from_slice(&ext.data);
+            out.resize(out.len() + len - ext.data.len(), 0);
+        }
+    }
+}
+
+/// Splits frames into payloads of packets
+pub trait Packetizer {
+    /// Marker bit is set on the last packet of a frame
+    const MARKER: bool;
+
+    /// Appends payloads of the frame, the last one gets marker bit if [`Packetizer::MARKER`]
+    fn packetize(&mut self, frame: &[u8], payloads: &mut Vec<Vec<u8>>) -> Result<(), Error>;
+}
+
+/// Complete access unit or audio frame
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    /// Annex B access unit, raw AAC frame or Opus packet
+    pub data: Vec<u8>,
+
+    /// RTP timestamp extended to 64 bits
+    pub timestamp: i64,
+
+    /// Access unit with IDR or IRAP picture, always set for audio
+    pub is_sync: bool,
+
+    /// Packets were lost before the frame
+    pub after_loss: bool,
+}
+
+/// Reassembles frames from payloads of packets in sequence order
+pub trait Depacketizer {
+    /// Consumes payload of the next packet and appends completed frames
+    fn push(
+        &mut self,
+        payload: &[u8],
+        timestamp: i64,
+        marker: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), Error>;
+
+    /// Appends pending frame which didn't get marker bit
+    fn flush(&mut self, frames: &mut Vec<Frame>);
+
+    /// Drops partially received frame after packet loss
+    fn reset(&mut self);
+}
+
+/// Payloads of NAL units, runs of small ones are aggregated and large ones fragmented
+fn packetize_nals(
+    nals: &[&[u8]],
+    max_payload: usize,
+    aggregation_header: impl Fn(&[&[u8]]) -> Vec<u8>,
+    fragment: impl Fn(&[u8], &mut Vec<Vec<u8>>) -> Result<(), Error>,
+    payloads: &mut Vec<Vec<u8>>,
+) -> Result<(), Error> {
+    let mut i = 0;
+    while i < nals.len() {
+        if nals[i].len() > max_payload {
+            fragment(nals[i], payloads)?;
+            i += 1;
+            continue;
+        }
+        let header_len = aggregation_header(&nals[i..i + 1]).len();
+        let mut size = header_len;
+        let mut end = i;
+        while end < nals.len() && size + 2 + nals[end].len() <= max_payload {
+            size += 2 + nals[end].len();
+            end += 1;
+        }
+        if end - i < 2 {
+            payloads.push(nals[i].to_vec());
+            i += 1;
+            continue;
+        }
+        let mut payload = aggregation_header(&nals[i..end]);
+        payload.reserve(size - header_len);
+        for nal in &nals[i..end] {
+            payload.extend_from_slice(&(nal.len() as u16).to_be_bytes());
+            payload.extend_from_slice(nal);
+        }
+        payloads.push(payload);
+        i = end;
+    }
+    Ok(())
+}
+
+/// Non-empty NAL units of Annex B or length prefixed frame
+fn split_nals(frame: &[u8], nal_length_size: Option<usize>) -> Result<Vec<&[u8]>, Error> {
+    let mut nals: Vec<&[u8]> = match nal_length_size {
+        Some(size) => nal::length_prefixed(frame, size)?.collect::<Result<_, _>>()?,
+        None => nal::annex_b(frame).collect(),
+    };
+    nals.retain(|n| !n.is_empty());
+    Ok(nals)
+}
+
+/// Access unit being reassembled by H.264 and HEVC depacketizers
+#[derive(Debug, Default, Clone)]
+struct AccessUnit {
+    data: Vec<u8>,
+    timestamp: i64,
+    is_sync: bool,
+
+    /// NAL unit of fragmentation units, `None` until the start one arrives
+    fragment: Option<Vec<u8>>,
+}
+
+impl AccessUnit {
+    /// Completes previous access unit if the timestamp changed without marker
+    fn start(&mut self, timestamp: i64, frames: &mut Vec<Frame>) {
+        if timestamp != self.timestamp {
+            self.finish(frames);
+            self.timestamp = timestamp;
+        }
+    }
+
+    fn push_nal(&mut self, nal: &[u8], is_sync: bool) {
+        self.data.extend_from_slice(&[0, 0, 0, 1]);
+        self.data.extend_from_slice(nal);
+        self.is_sync |= is_sync;
+    }
+
+    /// Appends fragment, returns NAL unit after the end one
+    fn push_fragment(
+        &mut self,
+        header: &[u8],
+        data: &[u8],
+        start: bool,
+        end: bool,
+    ) -> Option<Vec<u8>> {
+        if start {
+            self.fragment = Some(header.to_vec());
+        }
+        self.fragment.as_mut()?.extend_from_slice(data);
+        if end {
+            self.fragment.take()
+        } else {
+            None
+        }
+    }
+
+    fn finish(&mut self, frames: &mut Vec<Frame>) {
+        if !self.data.is_empty() {
+            frames.push(Frame {
+                data: std::mem::take(&mut self.data),
+                timestamp: self.timestamp,
+                is_sync: self.is_sync,
+                after_loss: false,
+            });
+        }
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.data.clear();
+        self.is_sync = false;
+        self.fragment = None;
+    }
+}
+
+/// Stream of packets with the same SSRC
+#[derive(Debug, Clone)]
+pub struct Sender<P> {
+    packetizer: P,
+    header: Header,
+    clock_rate: u32,
+}
+
+impl<P: Packetizer> Sender<P> {
+    pub fn new(packetizer: P, payload_type: u8, ssrc: u32, clock_rate: u32) -> Self {
+        Self {
+            packetizer,
+            header: Header {
+                payload_type,
+                ssrc,
+                ..Default::default()
+            },
+            clock_rate,
+        }
+    }
+
+    #[inline]
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    /// Sequence number of the next packet, random by RFC 3550
+    #[inline]
+    pub fn set_sequence(&mut self, sequence: u16) {
+        self.header.sequence = sequence;
+    }
+
+    /// Extension of the following packets
+    #[inline]
+    pub fn set_extension(&mut self, extension: Option<Extension>) {
+        self.header.extension = extension;
+    }
+
+    /// Packets of the frame at timestamp in clock rate units, truncated to 32 bits
+    pub fn send(&mut self, frame: &[u8], timestamp: i64) -> Result<Vec<Vec<u8>>, Error> {
+        let mut payloads = Vec::new();
+        self.packetizer.packetize(frame, &mut payloads)?;
+        let count = payloads.len();
+        let mut packets = Vec::with_capacity(count);
+        self.header.timestamp = timestamp as u32;
+        for (i, payload) in payloads.into_iter().enumerate() {
+            self.header.marker = P::MARKER && i + 1 == count;
+            let mut packet = Vec::with_capacity(self.header.size() + payload.len());
+            self.header.write(&mut packet);
+            packet.extend_from_slice(&payload);
+            packets.push(packet);
+            self.header.sequence = self.header.sequence.wrapping_add(1);
+        }
+        Ok(packets)
+    }
+
+    /// Packets of the frame at `cm::Time`
+    #[cfg(feature = "cm")]
+    pub fn send_at(&mut self, frame: &[u8], time: cm::Time) -> Result<Vec<Vec<u8>>, Error> {
+        let timestamp = time
+            .convert_scale(self.clock_rate as i32, cm::TimeRoundingMethod::default())
+            .value;
+        self.send(frame, timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Extension, Header, TWO_BYTE_PROFILE};
+
+    #[test]
+    fn header() {
+        let header = Header {
+            marker: false,
+            payload_type: 111,
+            sequence: 0xffff,
+            timestamp: 0xdead_beef,
+            ssrc: 1,
+            csrc: vec![2, 3],
+            extension: Some(Extension::with_elements(&[(1, &[1, 2])]).unwrap()),
+        };
+        let mut packet = Vec::new();
+        header.write(&mut packet);
+        assert_eq!(packet.len(), header.size());
+        assert_eq!(packet.len(), 12 + 8 + 4 + 4);
+        packet.extend_from_slice(&[9, 9, 0, 0, 3]);
+        packet[0] |= 0x20;
+
+        let (parsed, payload) = Header::parse(&packet).unwrap();
+        assert_eq!(payload, [9, 9]);
+        assert_eq!(parsed.csrc, [2, 3]);
+        let ext = parsed.extension.unwrap();
+        // padding is preserved in data and skipped by elements
+        assert_eq!(ext.data, [0x11, 1, 2, 0]);
+        assert_eq!(ext.elements().unwrap(), [(1, &[1, 2][..])]);
+
+        packet[0] = 0x40;
+        assert_eq!(Header::parse(&packet), Err(Error::InvalidVersion(1)));
+        assert_eq!(Header::parse(&packet[..11]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn two_byte_extension() {
+        let big = [7; 20];
+        let ext = Extension::with_elements(&[(15, &[]), (200, &big)]).unwrap();
+        assert_eq!(ext.profile, TWO_BYTE_PROFILE);
+        assert_eq!(ext.elements().unwrap(), [(15, &[][..]), (200, &big[..])]);
+        assert!(Extension::with_elements(&[(0, &[1])]).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! RFC 3640 `mpeg4-generic` AAC payload format in `AAC-hbr` mode:
+//! 13-bit AU sizes and 3-bit AU indices.
+
+use super::{Depacketizer, Error, Frame, Packetizer};
+
+const MAX_AU_SIZE: usize = 0x1fff;
+
+/// Packets of raw AAC frames, frames larger than a packet are fragmented
+#[derive(Debug, Clone)]
+pub struct AacPacketizer {
+    max_payload: usize,
+}
+
+impl AacPacketizer {
+    #[inline]
+    pub fn new(max_payload: usize) -> Self {
+        Self { max_payload }
+    }
+}
+
+impl Packetizer for AacPacketizer {
+    const MARKER: bool = true;
+
+    fn packetize(&mut self, frame: &[u8], payloads: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        if self.max_payload <= 4 {
+            return Err(Error::InvalidValue("max payload size"));
+        }
+        if frame.len() > MAX_AU_SIZE {
+            return Err(Error::TooLarge(frame.len()));
+        }
+        // AU-headers-length in bits and AU-header with index 0
+        let header = [0, 16, (frame.len() >> 5) as u8, (frame.len() << 3) as u8];
+        for chunk in frame.chunks(self.max_payload - 4) {
+            let mut payload = Vec::with_capacity(4 + chunk.len());
+            payload.extend_from_slice(&header);
+            payload.extend_from_slice(chunk);
+            payloads.push(payload);
+        }
+        Ok(())
+    }
+}
+
+/// Raw AAC frames of packets with one or more AUs or fragments of one
+#[derive(Debug, Clone)]
+pub struct AacDepacketizer {
+    samples_per_frame: u32,
+    fragment: Vec<u8>,
+    fragment_size: usize,
+    fragment_timestamp: i64,
+}
+
+impl AacDepacketizer {
+    /// Depacketizer of frames of `samples_per_frame` at RTP clock rate,
+    /// e.g. 1024 for AAC LC at its sample rate
+    #[inline]
+    pub fn new(samples_per_frame: u32) -> Self {
+        Self {
+            samples_per_frame,
+            fragment: Vec::new(),
+            fragment_size: 0,
+            fragment_timestamp: 0,
+        }
+    }
+}
+
+impl Default for AacDepacketizer {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl Depacketizer for AacDepacketizer {
+    fn push(
+        &mut self,
+        payload: &[u8],
+        timestamp: i64,
+        _marker: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), Error> {
+        let (len, rest) = payload.split_at_checked(2).ok_or(Error::Truncated)?;
+        let bits = u16::from_be_bytes([len[0], len[1]]) as usize;
+        if bits == 0 || bits % 16 != 0 {
+            return Err(Error::InvalidValue("AU-headers-length"));
+        }
+        let (headers, mut data) = rest.split_at_checked(bits / 8).ok_or(Error::Truncated)?;
+        let sizes: Vec<usize> = headers
+            .chunks_exact(2)
+            .map(|h| (u16::from_be_bytes([h[0], h[1]]) >> 3) as usize)
+            .collect();
+
+        if let [size] = sizes[..] {
+            if size > data.len() || !self.fragment.is_empty() {
+                if self.fragment.is_empty() || self.fragment_timestamp != timestamp {
+                    self.fragment.clear();
+                    self.fragment_size = size;
+                    self.fragment_timestamp = timestamp;
+                }
+                self.fragment.extend_from_slice(data);
+                if self.fragment.len() >= self.fragment_size {
+                    self.fragment.truncate(self.fragment_size);
+                    frames.push(Frame {
+                        data: std::mem::take(&mut self.fragment),
+                        timestamp,
+                        is_sync: true,
+                        after_loss: false,
+                    });
+                }
+                return Ok(());
+            }
+        }
+        for (i, size) in sizes.into_iter().enumerate() {
+            let (frame, tail) = data.split_at_checked(size).ok_or(Error::Truncated)?;
+            frames.push(Frame {
+                data: frame.to_vec(),
+                timestamp: timestamp + i as i64 * self.samples_per_frame as i64,
+                is_sync: true,
+                after_loss: false,
+            });
+            data = tail;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self, _frames: &mut Vec<Frame>) {
+        self.reset();
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.fragment.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AacDepacketizer, AacPacketizer};
+    use crate::media::rtp::{Depacketizer, Error, Packetizer};
+
+    #[test]
+    fn fragments() {
+        let frame: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let mut payloads = Vec::new();
+        AacPacketizer::new(104)
+            .packetize(&frame, &mut payloads)
+            .unwrap();
+        assert_eq!(payloads.len(), 3);
+        assert_eq!(payloads[0][..4], [0, 16, 0x09, 0x60]);
+
+        let mut depacketizer = AacDepacketizer::default();
+        let mut frames = Vec::new();
+        for p in &payloads {
+            depacketizer.push(p, 1024, true, &mut frames).unwrap();
+        }
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].data, frame);
+    }
+
+    #[test]
+    fn multiple_aus() {
+        // two AUs of 2 and 1 bytes
+        let payload = [0, 32, 0, 0x10, 0, 0x08, 0xa, 0xb, 0xc];
+        let mut depacketizer = AacDepacketizer::new(1024);
+        let mut frames = Vec::new();
+        depacketizer.push(&payload, 0, true, &mut frames).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, [0xa, 0xb]);
+        assert_eq!(frames[1].data, [0xc]);
+        assert_eq!(frames[1].timestamp, 1024);
+
+        assert_eq!(
+            depacketizer.push(&payload[..7], 0, true, &mut frames),
+            Err(Error::Truncated)
+        );
+        assert!(AacPacketizer::new(1200)
+            .packetize(&[0; 0x2000], &mut Vec::new())
+            .is_err());
+    }
+}
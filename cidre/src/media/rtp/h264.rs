@@ -0,0 +1,208 @@
+//! RFC 6184 H.264 payload format in non-interleaved mode.
+
+use super::{packetize_nals, split_nals, AccessUnit, Depacketizer, Error, Frame, Packetizer};
+use crate::media::h264::NalUnitType;
+
+const STAP_A: u8 = 24;
+const FU_A: u8 = 28;
+
+/// Single NAL unit, STAP-A and FU-A packets of H.264 access units
+#[derive(Debug, Clone)]
+pub struct H264Packetizer {
+    max_payload: usize,
+    nal_length_size: Option<usize>,
+}
+
+impl H264Packetizer {
+    /// Packetizer of Annex B access units
+    #[inline]
+    pub fn new(max_payload: usize) -> Self {
+        Self {
+            max_payload,
+            nal_length_size: None,
+        }
+    }
+
+    /// Packetizer of length prefixed samples, e.g. of `vt::CompressionSession` output
+    #[inline]
+    pub fn with_nal_length_size(max_payload: usize, nal_length_size: usize) -> Self {
+        Self {
+            max_payload,
+            nal_length_size: Some(nal_length_size),
+        }
+    }
+}
+
+impl Packetizer for H264Packetizer {
+    const MARKER: bool = true;
+
+    fn packetize(&mut self, frame: &[u8], payloads: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        if self.max_payload <= 2 {
+            return Err(Error::InvalidValue("max payload size"));
+        }
+        let nals = split_nals(frame, self.nal_length_size)?;
+        let aggregation_header = |nals: &[&[u8]]| {
+            let forbidden = nals.iter().fold(0, |f, n| f | n[0] & 0x80);
+            let nri = nals.iter().map(|n| n[0] & 0x60).max().unwrap_or(0);
+            vec![forbidden | nri | STAP_A]
+        };
+        let fragment = |nal: &[u8], payloads: &mut Vec<Vec<u8>>| {
+            let indicator = nal[0] & 0xe0 | FU_A;
+            let chunks = nal[1..].chunks(self.max_payload - 2);
+            let last = chunks.len() - 1;
+            for (i, chunk) in chunks.enumerate() {
+                let start = if i == 0 { 0x80 } else { 0 };
+                let end = if i == last { 0x40 } else { 0 };
+                let mut payload = vec![indicator, start | end | nal[0] & 0x1f];
+                payload.extend_from_slice(chunk);
+                payloads.push(payload);
+            }
+            Ok(())
+        };
+        packetize_nals(
+            &nals,
+            self.max_payload,
+            aggregation_header,
+            fragment,
+            payloads,
+        )
+    }
+}
+
+/// Reassembles Annex B access units from H.264 packets
+#[derive(Debug, Default, Clone)]
+pub struct H264Depacketizer {
+    au: AccessUnit,
+}
+
+impl H264Depacketizer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    fn push_nal(&mut self, nal: &[u8]) {
+        let is_sync = NalUnitType(nal[0] & 0x1f) == NalUnitType::IDR_SLICE;
+        self.au.push_nal(nal, is_sync);
+    }
+}
+
+impl Depacketizer for H264Depacketizer {
+    fn push(
+        &mut self,
+        payload: &[u8],
+        timestamp: i64,
+        marker: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), Error> {
+        self.au.start(timestamp, frames);
+        let (&header, rest) = payload.split_first().ok_or(Error::Truncated)?;
+        match header & 0x1f {
+            1..=23 => self.push_nal(payload),
+            STAP_A => {
+                let mut rest = rest;
+                while !rest.is_empty() {
+                    let (len, tail) = rest.split_at_checked(2).ok_or(Error::Truncated)?;
+                    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+                    let (nal, tail) = tail.split_at_checked(len).ok_or(Error::Truncated)?;
+                    if !nal.is_empty() {
+                        self.push_nal(nal);
+                    }
+                    rest = tail;
+                }
+            }
+            FU_A => {
+                let (&fu, data) = rest.split_first().ok_or(Error::Truncated)?;
+                let nal_header = [header & 0xe0 | fu & 0x1f];
+                let (start, end) = (fu & 0x80 != 0, fu & 0x40 != 0);
+                if let Some(nal) = self.au.push_fragment(&nal_header, data, start, end) {
+                    self.push_nal(&nal);
+                }
+            }
+            _ => return Err(Error::InvalidValue("H.264 packetization type")),
+        }
+        if marker {
+            self.au.finish(frames);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self, frames: &mut Vec<Frame>) {
+        self.au.finish(frames);
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.au.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{H264Depacketizer, H264Packetizer};
+    use crate::media::rtp::{Depacketizer, Error, Packetizer};
+
+    #[test]
+    fn round_trip() {
+        let sps = [0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4];
+        let pps = [0x68, 0xce, 0x3c, 0x80];
+        let mut idr = vec![0x65];
+        idr.extend((0..250).map(|i| i as u8));
+        let mut au = Vec::new();
+        for nal in [&sps[..], &pps, &idr] {
+            au.extend_from_slice(&[0, 0, 0, 1]);
+            au.extend_from_slice(nal);
+        }
+
+        let mut payloads = Vec::new();
+        H264Packetizer::new(100)
+            .packetize(&au, &mut payloads)
+            .unwrap();
+        // STAP-A with SPS and PPS, then FU-A of 250 bytes in chunks of 98
+        assert_eq!(payloads.len(), 4);
+        assert_eq!(payloads[0][..3], [0x78, 0, 8]);
+        assert_eq!(payloads[1][..2], [0x7c, 0x85]);
+        assert_eq!(payloads[2][..2], [0x7c, 0x05]);
+        assert_eq!(payloads[3][..2], [0x7c, 0x45]);
+        assert!(payloads.iter().all(|p| p.len() <= 100));
+
+        let mut depacketizer = H264Depacketizer::new();
+        let mut frames = Vec::new();
+        let last = payloads.len() - 1;
+        for (i, p) in payloads.iter().enumerate() {
+            depacketizer.push(p, 3000, i == last, &mut frames).unwrap();
+        }
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].data, au);
+        assert!(frames[0].is_sync);
+
+        // lost FU-A start drops the NAL unit, missing marker is completed by timestamp change
+        for p in &payloads[2..] {
+            depacketizer.push(p, 6000, false, &mut frames).unwrap();
+        }
+        depacketizer
+            .push(&[0x41, 1], 9000, false, &mut frames)
+            .unwrap();
+        assert_eq!(frames.len(), 1);
+        depacketizer.flush(&mut frames);
+        assert_eq!(frames[1].data, [0, 0, 0, 1, 0x41, 1]);
+        assert!(!frames[1].is_sync);
+
+        assert_eq!(
+            depacketizer.push(&[0x1a], 0, true, &mut frames),
+            Err(Error::InvalidValue("H.264 packetization type"))
+        );
+    }
+
+    #[test]
+    fn length_prefixed() {
+        let sample = [0, 0, 0, 3, 0x41, 1, 2, 0, 0, 0, 2, 0x01, 3];
+        let mut payloads = Vec::new();
+        H264Packetizer::with_nal_length_size(1200, 4)
+            .packetize(&sample, &mut payloads)
+            .unwrap();
+        assert_eq!(payloads, [vec![0x58, 0, 3, 0x41, 1, 2, 0, 2, 0x01, 3]]);
+    }
+}
@@ -0,0 +1,189 @@
+//! RFC 7798 HEVC payload format without DONL fields (`sprop-max-don-diff` of 0).
+
+use super::{packetize_nals, split_nals, AccessUnit, Depacketizer, Error, Frame, Packetizer};
+use crate::media::hevc::NalUnitType;
+
+const AP: u8 = 48;
+const FU: u8 = 49;
+
+#[inline]
+fn layer_id(nal: &[u8]) -> u8 {
+    (nal[0] & 1) << 5 | nal[1] >> 3
+}
+
+/// Single NAL unit, aggregation and fragmentation unit packets of HEVC access units
+#[derive(Debug, Clone)]
+pub struct HevcPacketizer {
+    max_payload: usize,
+    nal_length_size: Option<usize>,
+}
+
+impl HevcPacketizer {
+    /// Packetizer of Annex B access units
+    #[inline]
+    pub fn new(max_payload: usize) -> Self {
+        Self {
+            max_payload,
+            nal_length_size: None,
+        }
+    }
+
+    /// Packetizer of length prefixed samples, e.g. of `vt::CompressionSession` output
+    #[inline]
+    pub fn with_nal_length_size(max_payload: usize, nal_length_size: usize) -> Self {
+        Self {
+            max_payload,
+            nal_length_size: Some(nal_length_size),
+        }
+    }
+}
+
+impl Packetizer for HevcPacketizer {
+    const MARKER: bool = true;
+
+    fn packetize(&mut self, frame: &[u8], payloads: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        if self.max_payload <= 3 {
+            return Err(Error::InvalidValue("max payload size"));
+        }
+        let nals = split_nals(frame, self.nal_length_size)?;
+        if nals.iter().any(|n| n.len() < 2) {
+            return Err(Error::InvalidValue("HEVC NAL unit header"));
+        }
+        let aggregation_header = |nals: &[&[u8]]| {
+            let forbidden = nals.iter().fold(0, |f, n| f | n[0] & 0x80);
+            let layer = nals.iter().map(|n| layer_id(n)).min().unwrap_or(0);
+            let tid = nals.iter().map(|n| n[1] & 7).min().unwrap_or(1);
+            vec![forbidden | AP << 1 | layer >> 5, (layer & 0x1f) << 3 | tid]
+        };
+        let fragment = |nal: &[u8], payloads: &mut Vec<Vec<u8>>| {
+            let header = [nal[0] & 0x81 | FU << 1, nal[1]];
+            let chunks = nal[2..].chunks(self.max_payload - 3);
+            let last = chunks.len() - 1;
+            for (i, chunk) in chunks.enumerate() {
+                let start = if i == 0 { 0x80 } else { 0 };
+                let end = if i == last { 0x40 } else { 0 };
+                let mut payload = vec![header[0], header[1], start | end | nal[0] >> 1 & 0x3f];
+                payload.extend_from_slice(chunk);
+                payloads.push(payload);
+            }
+            Ok(())
+        };
+        packetize_nals(
+            &nals,
+            self.max_payload,
+            aggregation_header,
+            fragment,
+            payloads,
+        )
+    }
+}
+
+/// Reassembles Annex B access units from HEVC packets
+#[derive(Debug, Default, Clone)]
+pub struct HevcDepacketizer {
+    au: AccessUnit,
+}
+
+impl HevcDepacketizer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push_nal(&mut self, nal: &[u8]) -> Result<(), Error> {
+        if nal.len() < 2 {
+            return Err(Error::Truncated);
+        }
+        let is_sync = NalUnitType(nal[0] >> 1 & 0x3f).is_irap();
+        self.au.push_nal(nal, is_sync);
+        Ok(())
+    }
+}
+
+impl Depacketizer for HevcDepacketizer {
+    fn push(
+        &mut self,
+        payload: &[u8],
+        timestamp: i64,
+        marker: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), Error> {
+        self.au.start(timestamp, frames);
+        let (header, rest) = payload.split_at_checked(2).ok_or(Error::Truncated)?;
+        match header[0] >> 1 & 0x3f {
+            0..=47 => self.push_nal(payload)?,
+            AP => {
+                let mut rest = rest;
+                while !rest.is_empty() {
+                    let (len, tail) = rest.split_at_checked(2).ok_or(Error::Truncated)?;
+                    let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+                    let (nal, tail) = tail.split_at_checked(len).ok_or(Error::Truncated)?;
+                    self.push_nal(nal)?;
+                    rest = tail;
+                }
+            }
+            FU => {
+                let (&fu, data) = rest.split_first().ok_or(Error::Truncated)?;
+                let nal_header = [header[0] & 0x81 | (fu & 0x3f) << 1, header[1]];
+                let (start, end) = (fu & 0x80 != 0, fu & 0x40 != 0);
+                if let Some(nal) = self.au.push_fragment(&nal_header, data, start, end) {
+                    self.push_nal(&nal)?;
+                }
+            }
+            _ => return Err(Error::InvalidValue("HEVC packetization type")),
+        }
+        if marker {
+            self.au.finish(frames);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self, frames: &mut Vec<Frame>) {
+        self.au.finish(frames);
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.au.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HevcDepacketizer, HevcPacketizer};
+    use crate::media::rtp::{Depacketizer, Packetizer};
+
+    #[test]
+    fn round_trip() {
+        let vps = [0x40, 0x01, 0x0c, 0x01];
+        let sps = [0x42, 0x01, 0x01, 0x01, 0x60];
+        let pps = [0x44, 0x01, 0xc1, 0x72];
+        let mut idr = vec![0x26, 0x01];
+        idr.extend((0..300).map(|i| i as u8));
+        let mut au = Vec::new();
+        for nal in [&vps[..], &sps, &pps, &idr] {
+            au.extend_from_slice(&[0, 0, 0, 1]);
+            au.extend_from_slice(nal);
+        }
+
+        let mut payloads = Vec::new();
+        HevcPacketizer::new(200)
+            .packetize(&au, &mut payloads)
+            .unwrap();
+        assert_eq!(payloads.len(), 3);
+        assert_eq!(payloads[0][..4], [0x60, 0x01, 0, 4]);
+        assert_eq!(payloads[1][..3], [0x62, 0x01, 0x93]);
+        assert_eq!(payloads[2][..3], [0x62, 0x01, 0x53]);
+        assert_eq!(payloads[1].len(), 200);
+
+        let mut depacketizer = HevcDepacketizer::new();
+        let mut frames = Vec::new();
+        for (i, p) in payloads.iter().enumerate() {
+            depacketizer.push(p, 0, i == 2, &mut frames).unwrap();
+        }
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].data, au);
+        assert!(frames[0].is_sync);
+    }
+}
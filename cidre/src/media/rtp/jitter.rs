@@ -0,0 +1,365 @@
+//! Reordering of received packets and reassembly of frames.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use super::{Depacketizer, Error, Frame, Header};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+/// Limits of [`JitterBuffer`], a missing packet is declared lost once either is exceeded
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct JitterConfig {
+    /// Number of packets buffered after a gap
+    pub max_packets: usize,
+
+    /// Difference of RTP timestamps of buffered packets
+    pub max_delay: Duration,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            max_packets: 64,
+            max_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Counters of received packets
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// Packets accepted into buffer
+    pub received: u64,
+
+    /// Packets which never arrived in time
+    pub lost: u64,
+    pub duplicates: u64,
+
+    /// Packets which arrived after their successors were released
+    pub late: u64,
+
+    /// Packets which arrived after their successors but in time
+    pub reordered: u64,
+}
+
+/// Extends 16 or 32-bit wrapping counter relative to the last extended value
+#[inline]
+fn extend(last: i64, value: u32, bits: u32) -> i64 {
+    let diff = value.wrapping_sub(last as u32) << (32 - bits);
+    last + (diff as i32 >> (32 - bits)) as i64
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    header: Header,
+    timestamp: i64,
+    payload: Vec<u8>,
+}
+
+/// Packet released by [`JitterBuffer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Released {
+    pub header: Header,
+
+    /// RTP timestamp extended to 64 bits
+    pub timestamp: i64,
+    pub payload: Vec<u8>,
+
+    /// Packets before this one were lost
+    pub after_loss: bool,
+}
+
+/// Releases packets in sequence order, waiting for missing ones within limits of [`JitterConfig`]
+#[derive(Debug, Clone)]
+pub struct JitterBuffer {
+    max_packets: usize,
+    max_delay: i64,
+    next: Option<i64>,
+    highest: i64,
+    newest_timestamp: i64,
+    packets: BTreeMap<i64, Entry>,
+    stats: Stats,
+}
+
+impl JitterBuffer {
+    pub fn new(config: JitterConfig, clock_rate: u32) -> Self {
+        Self {
+            max_packets: config.max_packets,
+            max_delay: (config.max_delay.as_secs_f64() * clock_rate as f64) as i64,
+            next: None,
+            highest: 0,
+            newest_timestamp: 0,
+            packets: BTreeMap::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    #[inline]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Number of buffered packets
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    pub fn push(&mut self, header: Header, payload: Vec<u8>) {
+        let (sequence, timestamp) = match self.next {
+            None => {
+                self.next = Some(header.sequence as i64);
+                (header.sequence as i64, header.timestamp as i64)
+            }
+            Some(_) => (
+                extend(self.highest, header.sequence as u32, 16),
+                extend(self.newest_timestamp, header.timestamp, 32),
+            ),
+        };
+        if self.next.is_some_and(|next| sequence < next) {
+            self.stats.late += 1;
+            return;
+        }
+        if self.packets.contains_key(&sequence) {
+            self.stats.duplicates += 1;
+            return;
+        }
+        if self.stats.received > 0 && sequence < self.highest {
+            self.stats.reordered += 1;
+        } else {
+            self.highest = sequence;
+            self.newest_timestamp = timestamp;
+        }
+        self.stats.received += 1;
+        self.packets.insert(
+            sequence,
+            Entry {
+                header,
+                timestamp,
+                payload,
+            },
+        );
+    }
+
+    /// The next packet in sequence order, or after a gap if limits are exceeded
+    #[inline]
+    pub fn pop(&mut self) -> Option<Released> {
+        self.release(false)
+    }
+
+    /// The next buffered packet regardless of gaps
+    #[inline]
+    pub fn pop_any(&mut self) -> Option<Released> {
+        self.release(true)
+    }
+
+    fn release(&mut self, force: bool) -> Option<Released> {
+        let next = self.next?;
+        let (&sequence, first) = self.packets.first_key_value()?;
+        let after_loss = sequence != next;
+        if after_loss
+            && !force
+            && self.packets.len() <= self.max_packets
+            && self.newest_timestamp - first.timestamp <= self.max_delay
+        {
+            return None;
+        }
+        self.stats.lost += (sequence - next) as u64;
+        self.next = Some(sequence + 1);
+        let entry = self.packets.pop_first()?.1;
+        Some(Released {
+            header: entry.header,
+            timestamp: entry.timestamp,
+            payload: entry.payload,
+            after_loss,
+        })
+    }
+}
+
+/// Jitter buffer feeding depacketizer. Payloads which fail to depacketize
+/// are dropped like lost packets.
+///
+/// ```
+/// use cidre::media::rtp;
+///
+/// let mut sender = rtp::Sender::new(rtp::H264Packetizer::new(1200), 96, 1, 90_000);
+/// let mut packets = Vec::new();
+/// for i in 0..5 {
+///     packets.extend(sender.send(&[0, 0, 0, 1, 0x41, i], 3000 * i as i64).unwrap());
+/// }
+/// packets.swap(1, 2);
+/// packets.remove(3);
+///
+/// let config = rtp::JitterConfig {
+///     max_packets: 1,
+///     ..Default::default()
+/// };
+/// let mut receiver = rtp::Receiver::new(rtp::H264Depacketizer::new(), 90_000, config);
+/// let mut frames = Vec::new();
+/// for p in &packets {
+///     frames.extend(receiver.push(p).unwrap());
+/// }
+/// frames.extend(receiver.flush());
+/// let timestamps: Vec<_> = frames.iter().map(|f| f.timestamp).collect();
+/// assert_eq!(timestamps, [0, 3000, 6000, 12000]);
+/// assert!(frames[3].after_loss);
+///
+/// let stats = receiver.stats();
+/// assert_eq!((stats.lost, stats.reordered), (1, 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Receiver<D> {
+    depacketizer: D,
+    jitter: JitterBuffer,
+    clock_rate: u32,
+    after_loss: bool,
+}
+
+impl<D: Depacketizer> Receiver<D> {
+    pub fn new(depacketizer: D, clock_rate: u32, config: JitterConfig) -> Self {
+        Self {
+            depacketizer,
+            jitter: JitterBuffer::new(config, clock_rate),
+            clock_rate,
+            after_loss: false,
+        }
+    }
+
+    #[inline]
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    #[inline]
+    pub fn stats(&self) -> Stats {
+        self.jitter.stats()
+    }
+
+    /// Frames completed by the packet
+    pub fn push(&mut self, packet: &[u8]) -> Result<Vec<Frame>, Error> {
+        let (header, payload) = Header::parse(packet)?;
+        self.jitter.push(header, payload.to_vec());
+        let mut frames = Vec::new();
+        while let Some(released) = self.jitter.pop() {
+            self.deliver(released, &mut frames);
+        }
+        Ok(frames)
+    }
+
+    /// Frames of buffered packets, skipping missing ones
+    pub fn flush(&mut self) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        while let Some(released) = self.jitter.pop_any() {
+            self.deliver(released, &mut frames);
+        }
+        let start = frames.len();
+        self.depacketizer.flush(&mut frames);
+        self.mark_loss(&mut frames[start..]);
+        frames
+    }
+
+    fn deliver(&mut self, released: Released, frames: &mut Vec<Frame>) {
+        if released.after_loss {
+            self.depacketizer.reset();
+            self.after_loss = true;
+        }
+        let start = frames.len();
+        let res = self.depacketizer.push(
+            &released.payload,
+            released.timestamp,
+            released.header.marker,
+            frames,
+        );
+        self.mark_loss(&mut frames[start..]);
+        if res.is_err() {
+            self.depacketizer.reset();
+            self.after_loss = true;
+        }
+    }
+
+    fn mark_loss(&mut self, frames: &mut [Frame]) {
+        if let Some(first) = frames.first_mut() {
+            first.after_loss = std::mem::take(&mut self.after_loss);
+        }
+    }
+
+    /// `cm::Time` of frame timestamp
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_time(&self, frame: &Frame) -> cm::Time {
+        cm::Time::new(frame.timestamp, self.clock_rate as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extend, JitterBuffer, JitterConfig};
+    use crate::media::rtp::Header;
+
+    #[test]
+    fn extension() {
+        assert_eq!(extend(0xfffe, 1, 16), 0x10001);
+        assert_eq!(extend(0x10001, 0xffff, 16), 0xffff);
+        assert_eq!(extend(5, 3, 16), 3);
+        assert_eq!(extend(0xffff_fff0, 0x10, 32), 0x1_0000_0010);
+    }
+
+    fn header(sequence: u16, timestamp: u32) -> Header {
+        Header {
+            sequence,
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reorder() {
+        let config = JitterConfig {
+            max_packets: 3,
+            max_delay: std::time::Duration::from_secs(1),
+        };
+        let mut jitter = JitterBuffer::new(config, 1000);
+        jitter.push(header(65534, 0), vec![0]);
+        assert_eq!(jitter.pop().unwrap().payload, [0]);
+
+        // wraps around, 65535 is reordered and 0 is lost
+        jitter.push(header(1, 20), vec![2]);
+        jitter.push(header(65535, 10), vec![1]);
+        assert_eq!(jitter.pop().unwrap().payload, [1]);
+        assert!(jitter.pop().is_none());
+        jitter.push(header(2, 30), vec![3]);
+        jitter.push(header(2, 30), vec![3]);
+        jitter.push(header(3, 40), vec![4]);
+        assert!(jitter.pop().is_none());
+        jitter.push(header(4, 50), vec![5]);
+        let released = jitter.pop().unwrap();
+        assert!(released.after_loss);
+        assert_eq!(released.header.sequence, 1);
+        assert_eq!(jitter.pop().unwrap().timestamp, 30);
+        jitter.push(header(0, 15), vec![0]);
+
+        let stats = jitter.stats();
+        assert_eq!(stats.lost, 1);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.late, 1);
+        assert_eq!(stats.reordered, 1);
+
+        // delay limit
+        let mut jitter = JitterBuffer::new(config, 1000);
+        jitter.push(header(10, 0), vec![]);
+        jitter.pop();
+        jitter.push(header(12, 500), vec![]);
+        assert!(jitter.pop().is_none());
+        jitter.push(header(13, 1600), vec![]);
+        assert_eq!(jitter.pop().unwrap().header.sequence, 12);
+        assert_eq!(jitter.len(), 1);
+        assert_eq!(jitter.pop().unwrap().header.sequence, 13);
+        assert!(jitter.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+//! RFC 7587 Opus payload format, one Opus packet per RTP packet.
+//!
+//! RTP clock rate of Opus is 48 kHz regardless of its sample rate.
+
+use super::{Depacketizer, Error, Frame, Packetizer};
+
+#[derive(Debug, Clone)]
+pub struct OpusPacketizer {
+    max_payload: usize,
+}
+
+impl OpusPacketizer {
+    #[inline]
+    pub fn new(max_payload: usize) -> Self {
+        Self { max_payload }
+    }
+}
+
+impl Packetizer for OpusPacketizer {
+    /// Marker bit is meant for the first packet after DTX silence only
+    const MARKER: bool = false;
+
+    fn packetize(&mut self, frame: &[u8], payloads: &mut Vec<Vec<u8>>) -> Result<(), Error> {
+        if frame.len() > self.max_payload {
+            return Err(Error::TooLarge(frame.len()));
+        }
+        payloads.push(frame.to_vec());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OpusDepacketizer;
+
+impl OpusDepacketizer {
+    #[inline]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Depacketizer for OpusDepacketizer {
+    fn push(
+        &mut self,
+        payload: &[u8],
+        timestamp: i64,
+        _marker: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), Error> {
+        // empty payload is not a valid Opus packet
+        if payload.is_empty() {
+            return Err(Error::Truncated);
+        }
+        frames.push(Frame {
+            data: payload.to_vec(),
+            timestamp,
+            is_sync: true,
+            after_loss: false,
+        });
+        Ok(())
+    }
+
+    #[inline]
+    fn flush(&mut self, _frames: &mut Vec<Frame>) {}
+
+    #[inline]
+    fn reset(&mut self) {}
+}
@@ -0,0 +1,986 @@
+//! Supplemental enhancement information of H.264 and HEVC streams.
+//!
+//! SEI NAL units carry closed captions (ATSC A/53 `cc_data` with CEA-708 and embedded
+//! CEA-608), picture timing and recovery points next to coded pictures. Neither
+//! `vt` compression sessions nor `cm::SampleBuf`s expose them, so samples are
+//! inspected and rewritten here as length-prefixed NAL units.
+
+use crate::media::{bits, h264, hdr, hevc, nal};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    Nal(nal::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated SEI data"),
+            Self::InvalidValue(name) => write!(f, "invalid SEI {name}"),
+            Self::Nal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Eof => Self::Truncated,
+            bits::Error::InvalidExpGolomb => Self::InvalidValue("exp-golomb code"),
+        }
+    }
+}
+
+impl From<nal::Error> for Error {
+    #[inline]
+    fn from(value: nal::Error) -> Self {
+        Self::Nal(value)
+    }
+}
+
+/// Bitstream SEI belongs to, the syntax of NAL header and some payloads differ
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Codec {
+    H264,
+    Hevc,
+}
+
+impl Codec {
+    #[inline]
+    fn header_len(self) -> usize {
+        match self {
+            Self::H264 => 1,
+            Self::Hevc => 2,
+        }
+    }
+
+    #[inline]
+    fn is_sei(self, nal: &[u8]) -> bool {
+        match self {
+            Self::H264 => {
+                h264::Nal::new(nal).is_some_and(|n| n.unit_type() == h264::NalUnitType::SEI)
+            }
+            Self::Hevc => hevc::Nal::new(nal).is_some_and(|n| n.unit_type().is_sei()),
+        }
+    }
+
+    #[inline]
+    fn is_vcl(self, nal: &[u8]) -> bool {
+        match self {
+            Self::H264 => h264::Nal::new(nal).is_some_and(|n| n.unit_type().is_vcl()),
+            Self::Hevc => hevc::Nal::new(nal).is_some_and(|n| n.unit_type().is_vcl()),
+        }
+    }
+}
+
+/// `payloadType`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct PayloadType(pub u32);
+
+impl PayloadType {
+    pub const BUFFERING_PERIOD: Self = Self(0);
+    pub const PIC_TIMING: Self = Self(1);
+    pub const FILLER: Self = Self(3);
+    pub const USER_DATA_REGISTERED_ITU_T_T35: Self = Self(4);
+    pub const USER_DATA_UNREGISTERED: Self = Self(5);
+    pub const RECOVERY_POINT: Self = Self(6);
+    pub const MASTERING_DISPLAY_COLOUR_VOLUME: Self = Self(hdr::MasteringDisplay::SEI_PAYLOAD_TYPE);
+    pub const CONTENT_LIGHT_LEVEL_INFO: Self = Self(hdr::ContentLightLevel::SEI_PAYLOAD_TYPE);
+    pub const ALTERNATIVE_TRANSFER_CHARACTERISTICS: Self =
+        Self(hdr::AlternativeTransfer::SEI_PAYLOAD_TYPE);
+    pub const AMBIENT_VIEWING_ENVIRONMENT: Self = Self(hdr::AmbientViewing::SEI_PAYLOAD_TYPE);
+    pub const CONTENT_COLOUR_VOLUME: Self = Self(hdr::ContentColorVolume::SEI_PAYLOAD_TYPE);
+}
+
+/// `sei_message()` with payload without emulation prevention bytes
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Message {
+    pub payload_type: PayloadType,
+    pub payload: Vec<u8>,
+}
+
+fn read_ff_coded(data: &[u8], pos: &mut usize) -> Result<u32, Error> {
+    let mut value = 0u32;
+    loop {
+        let &b = data.get(*pos).ok_or(Error::Truncated)?;
+        *pos += 1;
+        value = value
+            .checked_add(b as u32)
+            .ok_or(Error::InvalidValue("payload size"))?;
+        if b != 0xff {
+            return Ok(value);
+        }
+    }
+}
+
+fn write_ff_coded(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0xff {
+        out.push(0xff);
+        value -= 0xff;
+    }
+    out.push(value as u8);
+}
+
+/// Writes SEI payload alignment bits if bit writer stopped in the middle of byte
+#[inline]
+fn align_payload(w: &mut bits::Writer) {
+    if !w.is_byte_aligned() {
+        w.write_trailing_bits();
+    }
+}
+
+impl Message {
+    #[inline]
+    pub fn new(payload_type: PayloadType, payload: Vec<u8>) -> Self {
+        Self {
+            payload_type,
+            payload,
+        }
+    }
+
+    /// Parses `sei_rbsp()` payload of SEI NAL unit without header
+    pub fn parse_rbsp(rbsp: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut res = Vec::new();
+        let mut pos = 0;
+        while pos < rbsp.len() {
+            let rest = &rbsp[pos..];
+            if rest[0] == 0x80 && rest[1..].iter().all(|&b| b == 0) {
+                break;
+            }
+            let payload_type = PayloadType(read_ff_coded(rbsp, &mut pos)?);
+            let size = read_ff_coded(rbsp, &mut pos)? as usize;
+            let payload = rbsp.get(pos..pos + size).ok_or(Error::Truncated)?;
+            pos += size;
+            res.push(Self::new(payload_type, payload.to_vec()));
+        }
+        Ok(res)
+    }
+
+    /// Parses SEI NAL unit with header, suffix SEI of HEVC is accepted as well
+    ///
+    /// ```
+    /// use cidre::media::sei;
+    ///
+    /// let nal = [0x06, 0x05, 0x11, 0xdc, 0x45, 0xe9, 0xbd, 0xe6, 0xd9, 0x48, 0xb7, 0x96, 0x2c,
+    ///     0xd8, 0x20, 0xd9, 0x23, 0xee, 0xef, b'x', 0x80];
+    /// let messages = sei::Message::parse_nal(&nal, sei::Codec::H264).unwrap();
+    /// assert_eq!(messages[0].payload_type, sei::PayloadType::USER_DATA_UNREGISTERED);
+    ///
+    /// let unregistered = sei::UserDataUnregistered::parse(&messages[0].payload).unwrap();
+    /// assert_eq!(unregistered.data, b"x");
+    /// assert_eq!(sei::Message::to_nal(&messages, sei::Codec::H264), nal);
+    /// ```
+    pub fn parse_nal(nal: &[u8], codec: Codec) -> Result<Vec<Self>, Error> {
+        if !codec.is_sei(nal) {
+            return Err(Error::InvalidValue("nal_unit_type"));
+        }
+        Self::parse_rbsp(&nal::to_rbsp(&nal[codec.header_len()..]))
+    }
+
+    /// Messages of all SEI NAL units in length-prefixed sample
+    pub fn parse_sample(
+        sample: &[u8],
+        nal_length_size: usize,
+        codec: Codec,
+    ) -> Result<Vec<Self>, Error> {
+        let mut res = Vec::new();
+        for data in nal::length_prefixed(sample, nal_length_size)? {
+            let data = data?;
+            if codec.is_sei(data) {
+                res.extend(Self::parse_nal(data, codec)?);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Appends `sei_message()` to RBSP
+    pub fn write(&self, out: &mut Vec<u8>) {
+        write_ff_coded(out, self.payload_type.0);
+        write_ff_coded(out, self.payload.len() as u32);
+        out.extend_from_slice(&self.payload);
+    }
+
+    /// Prefix SEI NAL unit of base layer with emulation prevention bytes
+    pub fn to_nal(messages: &[Self], codec: Codec) -> Vec<u8> {
+        let mut rbsp = Vec::with_capacity(messages.iter().map(|m| m.payload.len() + 4).sum());
+        for m in messages {
+            m.write(&mut rbsp);
+        }
+        rbsp.push(0x80);
+        let mut out = match codec {
+            Codec::H264 => vec![h264::NalUnitType::SEI.0],
+            Codec::Hevc => vec![hevc::NalUnitType::PREFIX_SEI.0 << 1, 1],
+        };
+        out.extend(nal::to_ebsp(&rbsp));
+        out
+    }
+
+    /// Inserts prefix SEI NAL unit with `messages` into length-prefixed sample.
+    ///
+    /// The NAL unit goes after access unit delimiter, parameter sets and other SEI, right
+    /// before the first slice, so buffering period SEI stays first.
+    pub fn insert_into_sample(
+        sample: &[u8],
+        nal_length_size: usize,
+        codec: Codec,
+        messages: &[Self],
+    ) -> Result<Vec<u8>, Error> {
+        let sei = Self::to_nal(messages, codec);
+        let mut out = Vec::with_capacity(sample.len() + sei.len() + nal_length_size);
+        let mut inserted = false;
+        for data in nal::length_prefixed(sample, nal_length_size)? {
+            let data = data?;
+            if !inserted && codec.is_vcl(data) {
+                nal::push_length_prefixed(&mut out, &sei, nal_length_size)?;
+                inserted = true;
+            }
+            nal::push_length_prefixed(&mut out, data, nal_length_size)?;
+        }
+        if !inserted {
+            nal::push_length_prefixed(&mut out, &sei, nal_length_size)?;
+        }
+        Ok(out)
+    }
+}
+
+/// `user_data_registered_itu_t_t35()`
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct UserDataRegistered {
+    pub country_code: u8,
+
+    /// Present when `country_code` is 0xff
+    pub country_code_extension: Option<u8>,
+
+    /// Payload starting with `itu_t_t35_provider_code`
+    pub data: Vec<u8>,
+}
+
+impl UserDataRegistered {
+    /// United States
+    pub const COUNTRY_US: u8 = 0xb5;
+
+    /// ATSC
+    pub const PROVIDER_ATSC: u16 = 0x0031;
+
+    pub fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let (&country_code, mut rest) = payload.split_first().ok_or(Error::Truncated)?;
+        let mut country_code_extension = None;
+        if country_code == 0xff {
+            let (&ext, tail) = rest.split_first().ok_or(Error::Truncated)?;
+            country_code_extension = Some(ext);
+            rest = tail;
+        }
+        Ok(Self {
+            country_code,
+            country_code_extension,
+            data: rest.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(2 + self.data.len());
+        out.push(self.country_code);
+        out.extend(self.country_code_extension);
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    #[inline]
+    pub fn provider_code(&self) -> Option<u16> {
+        let p = self.data.get(..2)?;
+        Some(u16::from_be_bytes([p[0], p[1]]))
+    }
+}
+
+/// `user_data_unregistered()` with UUID identifying the payload
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct UserDataUnregistered {
+    pub uuid: [u8; 16],
+    pub data: Vec<u8>,
+}
+
+impl UserDataUnregistered {
+    pub fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let (uuid, data) = payload.split_at_checked(16).ok_or(Error::Truncated)?;
+        Ok(Self {
+            uuid: uuid.try_into().unwrap(),
+            data: data.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.data.len());
+        out.extend_from_slice(&self.uuid);
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    #[inline]
+    pub fn to_message(&self) -> Message {
+        Message::new(PayloadType::USER_DATA_UNREGISTERED, self.to_bytes())
+    }
+}
+
+/// `cc_type` of caption triplet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct CcType(pub u8);
+
+impl CcType {
+    /// CEA-608 data of the first field
+    pub const NTSC_FIELD_1: Self = Self(0);
+
+    /// CEA-608 data of the second field
+    pub const NTSC_FIELD_2: Self = Self(1);
+
+    /// Continuation of CEA-708 DTVCC packet
+    pub const DTVCC_DATA: Self = Self(2);
+
+    /// Start of CEA-708 DTVCC packet
+    pub const DTVCC_START: Self = Self(3);
+}
+
+/// `cc_data_pkt` triplet
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CcTriplet {
+    pub valid: bool,
+    pub cc_type: CcType,
+    pub data: [u8; 2],
+}
+
+/// Adds odd parity bit to 7-bit CEA-608 character or control code byte
+#[inline]
+pub fn cea608_parity(byte: u8) -> u8 {
+    let b = byte & 0x7f;
+    if b.count_ones() % 2 == 0 {
+        b | 0x80
+    } else {
+        b
+    }
+}
+
+/// ATSC A/53 `cc_data()` carried in `user_data_registered_itu_t_t35` SEI.
+///
+/// ```
+/// use cidre::media::sei;
+///
+/// // "HI" on the first field
+/// let cc = sei::CcData::with_cea608(&[[b'H', b'I']]);
+/// let message = cc.to_message();
+/// assert_eq!(message.payload[..8], [0xb5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03]);
+///
+/// let parsed = sei::CcData::with_message(&message).unwrap().unwrap();
+/// assert_eq!(parsed.cea608(sei::CcType::NTSC_FIELD_1), [[b'H', b'I']]);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CcData {
+    pub process_cc_data: bool,
+    pub triplets: Vec<CcTriplet>,
+}
+
+impl CcData {
+    /// `cc_count` is 5 bits
+    pub const MAX_COUNT: usize = 31;
+
+    const USER_IDENTIFIER: [u8; 4] = *b"GA94";
+    const USER_DATA_TYPE_CC: u8 = 3;
+
+    /// Parses `cc_data()` starting with `cc_count` byte
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (&flags, rest) = data.split_first().ok_or(Error::Truncated)?;
+        let count = (flags & 0x1f) as usize;
+        // em_data
+        let rest = rest.get(1..).ok_or(Error::Truncated)?;
+        let triplets = rest.get(..count * 3).ok_or(Error::Truncated)?;
+        Ok(Self {
+            process_cc_data: flags & 0x40 != 0,
+            triplets: triplets
+                .chunks_exact(3)
+                .map(|t| CcTriplet {
+                    valid: t[0] & 0x04 != 0,
+                    cc_type: CcType(t[0] & 3),
+                    data: [t[1], t[2]],
+                })
+                .collect(),
+        })
+    }
+
+    /// `None` if T.35 payload is not ATSC A/53 caption data
+    pub fn with_itu_t35(payload: &[u8]) -> Result<Option<Self>, Error> {
+        let user_data = UserDataRegistered::parse(payload)?;
+        if user_data.country_code != UserDataRegistered::COUNTRY_US
+            || user_data.provider_code() != Some(UserDataRegistered::PROVIDER_ATSC)
+        {
+            return Ok(None);
+        }
+        match user_data.data.get(2..7) {
+            Some([g, a, n9, n4, Self::USER_DATA_TYPE_CC])
+                if [*g, *a, *n9, *n4] == Self::USER_IDENTIFIER =>
+            {
+                Self::parse(&user_data.data[7..]).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// `None` if message is not ATSC A/53 caption data
+    #[inline]
+    pub fn with_message(message: &Message) -> Result<Option<Self>, Error> {
+        if message.payload_type != PayloadType::USER_DATA_REGISTERED_ITU_T_T35 {
+            return Ok(None);
+        }
+        Self::with_itu_t35(&message.payload)
+    }
+
+    /// Caption data of all SEI in length-prefixed sample
+    pub fn parse_sample(
+        sample: &[u8],
+        nal_length_size: usize,
+        codec: Codec,
+    ) -> Result<Vec<Self>, Error> {
+        let mut res = Vec::new();
+        for m in Message::parse_sample(sample, nal_length_size, codec)? {
+            res.extend(Self::with_message(&m)?);
+        }
+        Ok(res)
+    }
+
+    /// First field CEA-608 byte pairs, parity bits are added
+    pub fn with_cea608(pairs: &[[u8; 2]]) -> Self {
+        Self {
+            process_cc_data: true,
+            triplets: pairs
+                .iter()
+                .map(|p| CcTriplet {
+                    valid: true,
+                    cc_type: CcType::NTSC_FIELD_1,
+                    data: [cea608_parity(p[0]), cea608_parity(p[1])],
+                })
+                .collect(),
+        }
+    }
+
+    /// Valid CEA-608 byte pairs of the field with parity bits stripped
+    pub fn cea608(&self, field: CcType) -> Vec<[u8; 2]> {
+        self.triplets
+            .iter()
+            .filter(|t| t.valid && t.cc_type == field)
+            .map(|t| [t.data[0] & 0x7f, t.data[1] & 0x7f])
+            .collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = self.triplets.len().min(Self::MAX_COUNT);
+        let mut out = Vec::with_capacity(3 + count * 3);
+        out.push(0x80 | (self.process_cc_data as u8) << 6 | count as u8);
+        out.push(0xff);
+        for t in &self.triplets[..count] {
+            out.extend_from_slice(&[
+                0xf8 | (t.valid as u8) << 2 | (t.cc_type.0 & 3),
+                t.data[0],
+                t.data[1],
+            ]);
+        }
+        out.push(0xff);
+        out
+    }
+
+    /// `user_data_registered_itu_t_t35` payload with ATSC A/53 header
+    pub fn to_itu_t35(&self) -> Vec<u8> {
+        let cc = self.to_bytes();
+        let mut data = Vec::with_capacity(7 + cc.len());
+        data.extend_from_slice(&UserDataRegistered::PROVIDER_ATSC.to_be_bytes());
+        data.extend_from_slice(&Self::USER_IDENTIFIER);
+        data.push(Self::USER_DATA_TYPE_CC);
+        data.extend(cc);
+        UserDataRegistered {
+            country_code: UserDataRegistered::COUNTRY_US,
+            country_code_extension: None,
+            data,
+        }
+        .to_bytes()
+    }
+
+    #[inline]
+    pub fn to_message(&self) -> Message {
+        Message::new(
+            PayloadType::USER_DATA_REGISTERED_ITU_T_T35,
+            self.to_itu_t35(),
+        )
+    }
+}
+
+/// Reassembles CEA-708 DTVCC packets from triplets of consecutive pictures
+#[derive(Debug, Clone, Default)]
+pub struct DtvccAssembler {
+    packet: Vec<u8>,
+}
+
+impl DtvccAssembler {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Size of the packet being assembled from `packet_size_code`
+    #[inline]
+    fn expected_len(&self) -> Option<usize> {
+        let code = (self.packet.first()? & 0x3f) as usize;
+        Some(if code == 0 { 128 } else { code * 2 })
+    }
+
+    /// Feeds captions of the next picture in presentation order, returns completed packets
+    pub fn push(&mut self, cc: &CcData) -> Vec<Vec<u8>> {
+        let mut res = Vec::new();
+        for t in cc.triplets.iter().filter(|t| t.valid) {
+            match t.cc_type {
+                CcType::DTVCC_START => {
+                    self.packet.clear();
+                    self.packet.extend_from_slice(&t.data);
+                }
+                CcType::DTVCC_DATA if !self.packet.is_empty() => {
+                    self.packet.extend_from_slice(&t.data);
+                }
+                _ => continue,
+            }
+            if let Some(len) = self.expected_len() {
+                if self.packet.len() >= len {
+                    self.packet.truncate(len);
+                    res.push(std::mem::take(&mut self.packet));
+                }
+            }
+        }
+        res
+    }
+}
+
+/// `recovery_point()`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RecoveryPoint {
+    /// `recovery_frame_cnt` of H.264 or `recovery_poc_cnt` of HEVC
+    pub recovery_cnt: i32,
+    pub exact_match: bool,
+    pub broken_link: bool,
+
+    /// H.264 only
+    pub changing_slice_group_idc: u8,
+}
+
+impl RecoveryPoint {
+    pub fn parse(payload: &[u8], codec: Codec) -> Result<Self, Error> {
+        let r = &mut bits::Reader::new(payload);
+        let recovery_cnt = match codec {
+            Codec::H264 => r.read_ue()? as i32,
+            Codec::Hevc => r.read_se()?,
+        };
+        Ok(Self {
+            recovery_cnt,
+            exact_match: r.read_bool()?,
+            broken_link: r.read_bool()?,
+            changing_slice_group_idc: match codec {
+                Codec::H264 => r.read_u8(2)?,
+                Codec::Hevc => 0,
+            },
+        })
+    }
+
+    pub fn to_bytes(&self, codec: Codec) -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        match codec {
+            Codec::H264 => w.write_ue(self.recovery_cnt.max(0) as u32),
+            Codec::Hevc => w.write_se(self.recovery_cnt),
+        }
+        w.write_bool(self.exact_match);
+        w.write_bool(self.broken_link);
+        if codec == Codec::H264 {
+            w.write_bits(self.changing_slice_group_idc as u64, 2);
+        }
+        align_payload(&mut w);
+        w.into_vec()
+    }
+
+    #[inline]
+    pub fn to_message(&self, codec: Codec) -> Message {
+        Message::new(PayloadType::RECOVERY_POINT, self.to_bytes(codec))
+    }
+}
+
+/// SPS fields `pic_timing()` syntax depends on
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PicTimingParams {
+    pub codec: Option<Codec>,
+
+    /// Lengths of CPB removal and DPB output delays when HRD is present
+    pub delay_lengths: Option<(u8, u8)>,
+
+    /// `pic_struct_present_flag` of H.264 or `frame_field_info_present_flag` of HEVC
+    pub pic_struct_present: bool,
+
+    /// H.264 only
+    pub time_offset_length: u8,
+
+    /// `dpb_output_delay_du_length` of HEVC sub-picture HRD
+    pub du_output_delay_length: Option<u8>,
+}
+
+impl PicTimingParams {
+    pub fn with_h264_sps(sps: &h264::Sps) -> Self {
+        let mut res = Self {
+            codec: Some(Codec::H264),
+            ..Default::default()
+        };
+        if let Some(vui) = &sps.vui {
+            res.pic_struct_present = vui.pic_struct_present;
+            if let Some(hrd) = vui.nal_hrd.as_ref().or(vui.vcl_hrd.as_ref()) {
+                res.delay_lengths =
+                    Some((hrd.cpb_removal_delay_length, hrd.dpb_output_delay_length));
+                res.time_offset_length = hrd.time_offset_length;
+            }
+        }
+        res
+    }
+
+    pub fn with_hevc_sps(sps: &hevc::Sps) -> Self {
+        let mut res = Self {
+            codec: Some(Codec::Hevc),
+            ..Default::default()
+        };
+        if let Some(vui) = &sps.vui {
+            res.pic_struct_present = vui.frame_field_info_present;
+            if let Some(hrd) = vui.hrd.as_ref().filter(|h| h.nal_hrd || h.vcl_hrd) {
+                res.delay_lengths =
+                    Some((hrd.au_cpb_removal_delay_length, hrd.dpb_output_delay_length));
+                res.du_output_delay_length = hrd.sub_pic.map(|s| s.dpb_output_delay_du_length);
+            }
+        }
+        res
+    }
+}
+
+/// `clock_timestamp` of H.264 picture timing
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct ClockTimestamp {
+    pub ct_type: u8,
+    pub nuit_field_based: bool,
+    pub counting_type: u8,
+    pub discontinuity: bool,
+    pub cnt_dropped: bool,
+    pub n_frames: u8,
+
+    /// All of seconds, minutes and hours are present with `full_timestamp_flag`
+    pub seconds: Option<u8>,
+    pub minutes: Option<u8>,
+    pub hours: Option<u8>,
+    pub time_offset: i32,
+}
+
+/// `pic_timing()`
+///
+/// Decoding unit info of HEVC sub-picture HRD that may follow is not parsed.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PicTiming {
+    /// `cpb_removal_delay` of H.264 or `au_cpb_removal_delay_minus1 + 1` of HEVC
+    pub cpb_removal_delay: Option<u32>,
+    pub dpb_output_delay: Option<u32>,
+
+    /// `pic_dpb_output_du_delay` of HEVC
+    pub dpb_output_du_delay: Option<u32>,
+    pub pic_struct: Option<u8>,
+
+    /// HEVC only
+    pub source_scan_type: Option<u8>,
+
+    /// HEVC only
+    pub duplicate: bool,
+
+    /// H.264 only, `None` for clock timestamps which are not present
+    pub clock_timestamps: Vec<Option<ClockTimestamp>>,
+}
+
+impl PicTiming {
+    /// `NumClockTS` for H.264 `pic_struct`
+    fn num_clock_ts(pic_struct: u8) -> Result<usize, Error> {
+        Ok(match pic_struct {
+            0..=2 => 1,
+            3 | 4 | 7 => 2,
+            5 | 6 | 8 => 3,
+            _ => return Err(Error::InvalidValue("pic_struct")),
+        })
+    }
+
+    pub fn parse(payload: &[u8], params: &PicTimingParams) -> Result<Self, Error> {
+        let r = &mut bits::Reader::new(payload);
+        let mut res = Self::default();
+        if params.codec == Some(Codec::Hevc) {
+            if params.pic_struct_present {
+                res.pic_struct = Some(r.read_u8(4)?);
+                res.source_scan_type = Some(r.read_u8(2)?);
+                res.duplicate = r.read_bool()?;
+            }
+            if let Some((cpb_len, dpb_len)) = params.delay_lengths {
+                res.cpb_removal_delay = Some(r.read_u32(cpb_len as u32)? + 1);
+                res.dpb_output_delay = Some(r.read_u32(dpb_len as u32)?);
+                if let Some(len) = params.du_output_delay_length {
+                    res.dpb_output_du_delay = Some(r.read_u32(len as u32)?);
+                }
+            }
+            return Ok(res);
+        }
+        if let Some((cpb_len, dpb_len)) = params.delay_lengths {
+            res.cpb_removal_delay = Some(r.read_u32(cpb_len as u32)?);
+            res.dpb_output_delay = Some(r.read_u32(dpb_len as u32)?);
+        }
+        if !params.pic_struct_present {
+            return Ok(res);
+        }
+        let pic_struct = r.read_u8(4)?;
+        res.pic_struct = Some(pic_struct);
+        for _ in 0..Self::num_clock_ts(pic_struct)? {
+            if !r.read_bool()? {
+                res.clock_timestamps.push(None);
+                continue;
+            }
+            let mut ts = ClockTimestamp {
+                ct_type: r.read_u8(2)?,
+                nuit_field_based: r.read_bool()?,
+                counting_type: r.read_u8(5)?,
+                ..Default::default()
+            };
+            let full = r.read_bool()?;
+            ts.discontinuity = r.read_bool()?;
+            ts.cnt_dropped = r.read_bool()?;
+            ts.n_frames = r.read_u8(8)?;
+            if full {
+                ts.seconds = Some(r.read_u8(6)?);
+                ts.minutes = Some(r.read_u8(6)?);
+                ts.hours = Some(r.read_u8(5)?);
+            } else if r.read_bool()? {
+                ts.seconds = Some(r.read_u8(6)?);
+                if r.read_bool()? {
+                    ts.minutes = Some(r.read_u8(6)?);
+                    if r.read_bool()? {
+                        ts.hours = Some(r.read_u8(5)?);
+                    }
+                }
+            }
+            let len = params.time_offset_length as u32;
+            if len > 0 {
+                let v = r.read_bits(len)?;
+                ts.time_offset = ((v << (64 - len)) as i64 >> (64 - len)) as i32;
+            }
+            res.clock_timestamps.push(Some(ts));
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{bits, h264, nal};
+
+    use super::{
+        CcData, CcTriplet, CcType, Codec, DtvccAssembler, Error, Message, PayloadType, PicTiming,
+        PicTimingParams, RecoveryPoint, UserDataRegistered,
+    };
+
+    #[test]
+    fn messages() {
+        let big = Message::new(PayloadType(300), vec![0; 600]);
+        let small = Message::new(PayloadType::RECOVERY_POINT, vec![0x80]);
+        let nal = Message::to_nal(&[big.clone(), small.clone()], Codec::Hevc);
+        assert_eq!(nal[..4], [0x4e, 0x01, 0xff, 0x2d]);
+        assert_eq!(nal[4..7], [0xff, 0xff, 0x5a]);
+        // emulation prevention inside zero payload
+        assert_eq!(nal[7..11], [0, 0, 3, 0]);
+        assert_eq!(Message::parse_nal(&nal, Codec::Hevc).unwrap(), [big, small]);
+
+        assert_eq!(
+            Message::parse_nal(&[0x06, 0x05, 0x10, 0x00], Codec::H264),
+            Err(Error::Truncated)
+        );
+        assert_eq!(
+            Message::parse_nal(&[0x65, 0x00], Codec::H264),
+            Err(Error::InvalidValue("nal_unit_type"))
+        );
+        let suffix = [0x50, 0x01, 0x06, 0x01, 0x84, 0x80];
+        assert_eq!(
+            Message::parse_nal(&suffix, Codec::Hevc).unwrap()[0].payload,
+            [0x84]
+        );
+    }
+
+    #[test]
+    fn captions() {
+        let mut cc = CcData::with_cea608(&[[0x14, 0x2c], [b'A', 0]]);
+        cc.triplets.push(CcTriplet {
+            valid: true,
+            cc_type: CcType::DTVCC_START,
+            data: [0x02, 0x21],
+        });
+        assert_eq!(cc.triplets[0].data, [0x94, 0x2c]);
+        assert_eq!(cc.triplets[1].data, [0xc1, 0x80]);
+
+        let payload = cc.to_itu_t35();
+        assert_eq!(payload[8..10], [0xc3, 0xff]);
+        assert_eq!(payload[10..13], [0xfc, 0x94, 0x2c]);
+        assert_eq!(payload[16], 0xff);
+        assert_eq!(*payload.last().unwrap(), 0xff);
+        assert_eq!(CcData::with_itu_t35(&payload), Ok(Some(cc.clone())));
+        assert_eq!(cc.cea608(CcType::NTSC_FIELD_1), [[0x14, 0x2c], [b'A', 0]]);
+        assert!(cc.cea608(CcType::NTSC_FIELD_2).is_empty());
+
+        let mut other = payload.clone();
+        other[3] = b'D';
+        assert_eq!(CcData::with_itu_t35(&other), Ok(None));
+        let mut truncated = payload.clone();
+        truncated[8] = 0xc9;
+        assert_eq!(
+            CcData::with_itu_t35(&truncated[..20]),
+            Err(Error::Truncated)
+        );
+
+        let ext = UserDataRegistered::parse(&[0xff, 0x10, 1, 2]).unwrap();
+        assert_eq!(ext.country_code_extension, Some(0x10));
+        assert_eq!(ext.to_bytes(), [0xff, 0x10, 1, 2]);
+
+        // packet_size_code 2 is 4 bytes, split across two pictures
+        let mut dtvcc = DtvccAssembler::new();
+        assert!(dtvcc.push(&cc).is_empty());
+        let next = CcData {
+            process_cc_data: true,
+            triplets: vec![CcTriplet {
+                valid: true,
+                cc_type: CcType::DTVCC_DATA,
+                data: [0x20, 0x41],
+            }],
+        };
+        assert_eq!(dtvcc.push(&next), [vec![0x02, 0x21, 0x20, 0x41]]);
+        assert!(dtvcc.push(&next).is_empty());
+    }
+
+    #[test]
+    fn inject() {
+        let mut sample = Vec::new();
+        for data in [
+            &[0x09, 0xf0][..],
+            &[0x06, 0x00, 0x01, 0x00, 0x80],
+            &[0x65, 0x88, 0x80],
+        ] {
+            nal::push_length_prefixed(&mut sample, data, 4).unwrap();
+        }
+        let cc = CcData::with_cea608(&[[b'O', b'K']]);
+        let out = Message::insert_into_sample(&sample, 4, Codec::H264, &[cc.to_message()]).unwrap();
+        let nals: Vec<_> = nal::length_prefixed(&out, 4)
+            .unwrap()
+            .map(|n| n.unwrap()[0])
+            .collect();
+        assert_eq!(nals, [0x09, 0x06, 0x06, 0x65]);
+
+        let messages = Message::parse_sample(&out, 4, Codec::H264).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].payload_type, PayloadType::BUFFERING_PERIOD);
+        assert_eq!(CcData::parse_sample(&out, 4, Codec::H264).unwrap(), [cc]);
+
+        let mut hevc_sample = Vec::new();
+        nal::push_length_prefixed(&mut hevc_sample, &[0x26, 0x01, 0xaf], 2).unwrap();
+        let out = Message::insert_into_sample(&hevc_sample, 2, Codec::Hevc, &[]).unwrap();
+        assert_eq!(out, [0, 3, 0x4e, 0x01, 0x80, 0, 3, 0x26, 0x01, 0xaf]);
+    }
+
+    #[test]
+    fn recovery_point() {
+        let rp = RecoveryPoint {
+            recovery_cnt: 3,
+            exact_match: true,
+            broken_link: false,
+            changing_slice_group_idc: 0,
+        };
+        let bytes = rp.to_bytes(Codec::H264);
+        assert_eq!(bytes, [0b0010_0100, 0b0100_0000]);
+        assert_eq!(RecoveryPoint::parse(&bytes, Codec::H264), Ok(rp));
+
+        let rp = RecoveryPoint {
+            recovery_cnt: -2,
+            ..rp
+        };
+        let msg = rp.to_message(Codec::Hevc);
+        assert_eq!(msg.payload, [0b0010_1101]);
+        assert_eq!(RecoveryPoint::parse(&msg.payload, Codec::Hevc), Ok(rp));
+    }
+
+    #[test]
+    fn pic_timing() {
+        let params = PicTimingParams {
+            codec: Some(Codec::H264),
+            delay_lengths: Some((24, 24)),
+            pic_struct_present: true,
+            time_offset_length: 5,
+            du_output_delay_length: None,
+        };
+        let mut w = bits::Writer::new();
+        w.write_bits(2, 24);
+        w.write_bits(4, 24);
+        w.write_bits(3, 4); // top bottom
+        w.write_bool(true);
+        w.write_bits(1, 2);
+        w.write_bool(false);
+        w.write_bits(4, 5);
+        w.write_bool(true); // full
+        w.write_bool(false);
+        w.write_bool(true);
+        w.write_bits(29, 8);
+        w.write_bits(59, 6);
+        w.write_bits(10, 6);
+        w.write_bits(1, 5);
+        w.write_bits(0x1f, 5); // -1
+        w.write_bool(false);
+        w.write_trailing_bits();
+        let timing = PicTiming::parse(&w.into_vec(), &params).unwrap();
+        assert_eq!(timing.cpb_removal_delay, Some(2));
+        assert_eq!(timing.dpb_output_delay, Some(4));
+        assert_eq!(timing.pic_struct, Some(3));
+        let ts = timing.clock_timestamps[0].unwrap();
+        assert_eq!(
+            (ts.hours, ts.minutes, ts.seconds),
+            (Some(1), Some(10), Some(59))
+        );
+        assert_eq!((ts.n_frames, ts.counting_type, ts.time_offset), (29, 4, -1));
+        assert!(ts.cnt_dropped && !ts.discontinuity);
+        assert_eq!(timing.clock_timestamps[1], None);
+
+        let params = PicTimingParams {
+            codec: Some(Codec::Hevc),
+            delay_lengths: Some((8, 8)),
+            pic_struct_present: true,
+            time_offset_length: 0,
+            du_output_delay_length: None,
+        };
+        let timing = PicTiming::parse(&[0x12, 0x12, 0x04], &params).unwrap();
+        assert_eq!(timing.pic_struct, Some(1));
+        assert_eq!(timing.source_scan_type, Some(0));
+        assert!(timing.duplicate);
+        assert_eq!(timing.cpb_removal_delay, Some(10));
+        assert_eq!(timing.dpb_output_delay, Some(2));
+        assert_eq!(PicTiming::parse(&[0x12], &params), Err(Error::Truncated));
+
+        let sps = h264::Sps::parse(&[
+            0x67, 0x64, 0x00, 0x28, 0xac, 0xd9, 0x40, 0x78, 0x02, 0x27, 0xe5, 0x84, 0x00, 0x00,
+            0x03, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xf0, 0x3c, 0x60, 0xc6, 0x58,
+        ])
+        .unwrap();
+        let params = PicTimingParams::with_h264_sps(&sps);
+        assert_eq!(params.codec, Some(Codec::H264));
+        assert_eq!(params.delay_lengths, None);
+        assert_eq!(PicTiming::parse(&[], &params), Ok(PicTiming::default()));
+    }
+}
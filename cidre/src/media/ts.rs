@@ -0,0 +1,356 @@
+//! MPEG-2 transport stream (ISO/IEC 13818-1) of a single program with H.264, HEVC and AAC.
+//!
+//! [`Muxer`] packetizes Annex B access units and raw AAC frames into PES with PAT/PMT
+//! and PCR, as needed by HLS `.ts` segments. [`Demuxer`] reassembles PES back into
+//! access units with 90 kHz timestamps.
+//!
+//! ```
+//! use cidre::media::{aac, ts};
+//!
+//! let mut mux = ts::Muxer::new(Default::default());
+//! let video = mux.add_stream(ts::StreamType::H264);
+//! let audio = mux
+//!     .add_aac(aac::AudioSpecificConfig::lc(48_000, 2), ts::StreamType::AAC_ADTS)
+//!     .unwrap();
+//!
+//! let mut out = Vec::new();
+//! let idr = [0, 0, 0, 1, 0x65, 0x88, 0x84];
+//! mux.write(&mut out, video, &ts::Frame::new(&idr, 3_000, 0, true)).unwrap();
+//! mux.write(&mut out, audio, &ts::Frame::new(&[1, 2, 3], 0, 0, true)).unwrap();
+//! assert_eq!(out.len() % ts::PACKET_LEN, 0);
+//!
+//! let mut demux = ts::Demuxer::new();
+//! let mut units = demux.push(&out);
+//! // audio PES ends at its length, video one at the next PES or flush
+//! units.extend(demux.flush());
+//! assert_eq!(units.len(), 2);
+//! assert_eq!(units[0].data, [1, 2, 3]);
+//! assert_eq!(units[1].pts, Some(3_000));
+//! ```
+
+mod demux;
+pub use demux::AccessUnit;
+pub use demux::Demuxer;
+pub use demux::Packet;
+
+mod mux;
+pub use mux::Frame;
+pub use mux::MuxConfig;
+pub use mux::Muxer;
+
+use crate::media::{aac, nal};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+pub const PACKET_LEN: usize = 188;
+pub const SYNC_BYTE: u8 = 0x47;
+pub const PAT_PID: u16 = 0;
+pub const NULL_PID: u16 = 0x1fff;
+
+/// Clock of PTS and DTS, PCR base runs at the same rate
+pub const TIMESCALE: u32 = 90_000;
+
+/// Timestamps wrap around at 33 bits
+pub const TIMESTAMP_MASK: i64 = (1 << 33) - 1;
+
+const TABLE_ID_PAT: u8 = 0x00;
+const TABLE_ID_PMT: u8 = 0x02;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Packet doesn't start with sync byte
+    InvalidSync,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+
+    /// PSI section checksum mismatch
+    Crc,
+
+    /// PID is not a stream of the program
+    UnknownPid(u16),
+
+    Aac(aac::Error),
+
+    Nal(nal::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated transport stream data"),
+            Self::InvalidSync => f.write_str("invalid transport stream sync byte"),
+            Self::InvalidValue(name) => write!(f, "invalid transport stream {name}"),
+            Self::Crc => f.write_str("PSI section CRC mismatch"),
+            Self::UnknownPid(pid) => write!(f, "unknown PID {pid:#x}"),
+            Self::Aac(err) => err.fmt(f),
+            Self::Nal(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<aac::Error> for Error {
+    #[inline]
+    fn from(value: aac::Error) -> Self {
+        Self::Aac(value)
+    }
+}
+
+impl From<nal::Error> for Error {
+    #[inline]
+    fn from(value: nal::Error) -> Self {
+        Self::Nal(value)
+    }
+}
+
+/// `stream_type` of PMT
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct StreamType(pub u8);
+
+impl StreamType {
+    pub const MPEG2_VIDEO: Self = Self(0x02);
+    pub const MPEG1_AUDIO: Self = Self(0x03);
+    pub const MPEG2_AUDIO: Self = Self(0x04);
+    pub const PRIVATE_DATA: Self = Self(0x06);
+    pub const AAC_ADTS: Self = Self(0x0f);
+    pub const AAC_LATM: Self = Self(0x11);
+    pub const METADATA: Self = Self(0x15);
+    pub const H264: Self = Self(0x1b);
+    pub const HEVC: Self = Self(0x24);
+    pub const AC3: Self = Self(0x81);
+    pub const EAC3: Self = Self(0x87);
+
+    #[inline]
+    pub const fn is_video(&self) -> bool {
+        matches!(self.0, 0x01 | 0x02 | 0x10 | 0x1b | 0x24)
+    }
+
+    #[inline]
+    pub const fn is_audio(&self) -> bool {
+        matches!(self.0, 0x03 | 0x04 | 0x0f | 0x11 | 0x81 | 0x87)
+    }
+
+    #[inline]
+    pub const fn is_aac(&self) -> bool {
+        matches!(self.0, 0x0f | 0x11)
+    }
+
+    /// PES `stream_id`
+    #[inline]
+    pub const fn stream_id(&self) -> u8 {
+        if self.is_video() {
+            0xe0
+        } else if self.is_audio() && self.0 < 0x80 {
+            0xc0
+        } else {
+            0xbd
+        }
+    }
+}
+
+/// CRC-32/MPEG-2 of PSI sections.
+///
+/// ```
+/// use cidre::media::ts;
+///
+/// assert_eq!(ts::crc32(b"123456789"), 0x0376e6e7);
+/// ```
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &b in data {
+        crc ^= (b as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                crc << 1 ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Elementary stream of [`Pmt`]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PmtStream {
+    pub stream_type: StreamType,
+    pub pid: u16,
+
+    /// Raw `ES_info` descriptors
+    pub descriptors: Vec<u8>,
+}
+
+/// Program map table
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Pmt {
+    pub program_number: u16,
+    pub pcr_pid: u16,
+    pub streams: Vec<PmtStream>,
+}
+
+impl Pmt {
+    #[inline]
+    pub fn stream(&self, pid: u16) -> Option<&PmtStream> {
+        self.streams.iter().find(|s| s.pid == pid)
+    }
+}
+
+/// Appends section with `section_syntax_indicator`, its length and CRC
+fn write_section(out: &mut Vec<u8>, table_id: u8, id: u16, version: u8, body: &[u8]) {
+    let start = out.len();
+    let section_len = 5 + body.len() + 4;
+    out.push(table_id);
+    out.extend_from_slice(&(0xb000 | section_len as u16).to_be_bytes());
+    out.extend_from_slice(&id.to_be_bytes());
+    out.push(0xc1 | (version & 0x1f) << 1);
+    out.extend_from_slice(&[0, 0]); // section_number and last_section_number
+    out.extend_from_slice(body);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Checks section of `table_id` and returns its `id` and body
+fn read_section(data: &[u8], table_id: u8) -> Result<(u16, &[u8]), Error> {
+    let header = data.get(..3).ok_or(Error::Truncated)?;
+    if header[0] != table_id {
+        return Err(Error::InvalidValue("table_id"));
+    }
+    let section_len = (u16::from_be_bytes([header[1], header[2]]) & 0xfff) as usize;
+    if section_len < 9 {
+        return Err(Error::InvalidValue("section_length"));
+    }
+    let section = data.get(..3 + section_len).ok_or(Error::Truncated)?;
+    if crc32(section) != 0 {
+        return Err(Error::Crc);
+    }
+    let id = u16::from_be_bytes([section[3], section[4]]);
+    Ok((id, &section[8..section.len() - 4]))
+}
+
+/// Appends PAT section of a single program
+pub(crate) fn write_pat(out: &mut Vec<u8>, program_number: u16, pmt_pid: u16) {
+    let mut body = program_number.to_be_bytes().to_vec();
+    body.extend_from_slice(&(0xe000 | pmt_pid).to_be_bytes());
+    write_section(out, TABLE_ID_PAT, 1, 0, &body);
+}
+
+/// Program numbers and PMT PIDs of PAT section, network PID is skipped
+pub(crate) fn read_pat(data: &[u8]) -> Result<Vec<(u16, u16)>, Error> {
+    let (_, body) = read_section(data, TABLE_ID_PAT)?;
+    Ok(body
+        .chunks_exact(4)
+        .map(|c| {
+            (
+                u16::from_be_bytes([c[0], c[1]]),
+                u16::from_be_bytes([c[2], c[3]]) & 0x1fff,
+            )
+        })
+        .filter(|&(program, _)| program != 0)
+        .collect())
+}
+
+impl Pmt {
+    /// Appends PMT section
+    pub fn write(&self, out: &mut Vec<u8>) {
+        let mut body = (0xe000 | self.pcr_pid).to_be_bytes().to_vec();
+        body.extend_from_slice(&0xf000u16.to_be_bytes());
+        for s in &self.streams {
+            body.push(s.stream_type.0);
+            body.extend_from_slice(&(0xe000 | s.pid).to_be_bytes());
+            body.extend_from_slice(&(0xf000 | s.descriptors.len() as u16).to_be_bytes());
+            body.extend_from_slice(&s.descriptors);
+        }
+        write_section(out, TABLE_ID_PMT, self.program_number, 0, &body);
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (program_number, body) = read_section(data, TABLE_ID_PMT)?;
+        let header = body.get(..4).ok_or(Error::Truncated)?;
+        let pcr_pid = u16::from_be_bytes([header[0], header[1]]) & 0x1fff;
+        let info_len = (u16::from_be_bytes([header[2], header[3]]) & 0xfff) as usize;
+        let mut rest = body.get(4 + info_len..).ok_or(Error::Truncated)?;
+        let mut streams = Vec::new();
+        while !rest.is_empty() {
+            let es = rest.get(..5).ok_or(Error::Truncated)?;
+            let len = (u16::from_be_bytes([es[3], es[4]]) & 0xfff) as usize;
+            let descriptors = rest.get(5..5 + len).ok_or(Error::Truncated)?;
+            streams.push(PmtStream {
+                stream_type: StreamType(es[0]),
+                pid: u16::from_be_bytes([es[1], es[2]]) & 0x1fff,
+                descriptors: descriptors.to_vec(),
+            });
+            rest = &rest[5 + len..];
+        }
+        Ok(Self {
+            program_number,
+            pcr_pid,
+            streams,
+        })
+    }
+}
+
+/// 90 kHz timestamp of `cm::Time`
+#[cfg(feature = "cm")]
+#[inline]
+pub fn timestamp(time: cm::Time) -> i64 {
+    time.convert_scale(TIMESCALE as i32, cm::TimeRoundingMethod::default())
+        .value
+}
+
+/// `cm::Time` of 90 kHz timestamp
+#[cfg(feature = "cm")]
+#[inline]
+pub fn cm_time(timestamp: i64) -> cm::Time {
+    cm::Time::new(timestamp, TIMESCALE as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, read_pat, write_pat, Error, Pmt, PmtStream, StreamType};
+
+    #[test]
+    fn psi() {
+        let mut pat = Vec::new();
+        write_pat(&mut pat, 1, 0x1000);
+        assert_eq!(
+            pat,
+            [
+                0x00, 0xb0, 0x0d, 0x00, 0x01, 0xc1, 0x00, 0x00, 0x00, 0x01, 0xf0, 0x00, 0x2a, 0xb1,
+                0x04, 0xb2
+            ]
+        );
+        assert_eq!(crc32(&pat), 0);
+        assert_eq!(read_pat(&pat).unwrap(), [(1, 0x1000)]);
+
+        let pmt = Pmt {
+            program_number: 1,
+            pcr_pid: 0x100,
+            streams: vec![
+                PmtStream {
+                    stream_type: StreamType::H264,
+                    pid: 0x100,
+                    descriptors: Vec::new(),
+                },
+                PmtStream {
+                    stream_type: StreamType::AAC_ADTS,
+                    pid: 0x101,
+                    descriptors: vec![0x0a, 0x04, b'e', b'n', b'g', 0],
+                },
+            ],
+        };
+        let mut data = Vec::new();
+        pmt.write(&mut data);
+        assert_eq!(Pmt::parse(&data).unwrap(), pmt);
+
+        data[10] ^= 1;
+        assert_eq!(Pmt::parse(&data), Err(Error::Crc));
+        assert_eq!(Pmt::parse(&pat), Err(Error::InvalidValue("table_id")));
+    }
+}
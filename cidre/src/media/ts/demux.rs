@@ -0,0 +1,659 @@
+//! Transport stream demuxer.
+
+use super::{read_pat, Error, Pmt, StreamType, PACKET_LEN, PAT_PID, SYNC_BYTE, TIMESCALE};
+use crate::media::{aac, h264, hevc, nal};
+
+#[cfg(feature = "cm")]
+use crate::{arc, cm, os};
+
+/// Transport stream packet
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Packet<'a> {
+    pub pid: u16,
+    pub payload_unit_start: bool,
+    pub continuity_counter: u8,
+    pub discontinuity: bool,
+    pub random_access: bool,
+
+    /// PCR base at 90 kHz, extension is dropped
+    pub pcr: Option<i64>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Packet<'a> {
+    /// Parses the first [`PACKET_LEN`] bytes
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let p = data.get(..PACKET_LEN).ok_or(Error::Truncated)?;
+        if p[0] != SYNC_BYTE {
+            return Err(Error::InvalidSync);
+        }
+        let mut res = Self {
+            pid: u16::from_be_bytes([p[1], p[2]]) & 0x1fff,
+            payload_unit_start: p[1] & 0x40 != 0,
+            continuity_counter: p[3] & 0xf,
+            discontinuity: false,
+            random_access: false,
+            pcr: None,
+            payload: &[],
+        };
+        let control = p[3] >> 4 & 3;
+        let mut start = 4;
+        if control & 2 != 0 {
+            let len = p[4] as usize;
+            start = 5 + len;
+            if start > PACKET_LEN {
+                return Err(Error::InvalidValue("adaptation_field_length"));
+            }
+            if len > 0 {
+                let flags = p[5];
+                res.discontinuity = flags & 0x80 != 0;
+                res.random_access = flags & 0x40 != 0;
+                if flags & 0x10 != 0 && len >= 7 {
+                    let mut pcr = [0; 8];
+                    pcr[2..].copy_from_slice(&p[6..12]);
+                    res.pcr = Some((u64::from_be_bytes(pcr) >> 15) as i64);
+                }
+            }
+        }
+        if control & 1 != 0 {
+            res.payload = &p[start..];
+        }
+        Ok(res)
+    }
+}
+
+/// Access unit or audio frame of elementary stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessUnit {
+    pub pid: u16,
+    pub stream_type: StreamType,
+
+    /// 90 kHz timestamp, 33 bits wrapping around, `None` if PES has no PTS
+    pub pts: Option<i64>,
+
+    /// Equals to `pts` if PES has no DTS
+    pub dts: Option<i64>,
+
+    /// Annex B access unit, raw AAC frame or PES payload of other stream types
+    pub data: Vec<u8>,
+
+    /// Random access point or access unit with IDR or IRAP picture, always set for audio
+    pub is_sync: bool,
+
+    /// Config of AAC frame from ADTS header or LATM `StreamMuxConfig`
+    pub asc: Option<aac::AudioSpecificConfig>,
+}
+
+impl AccessUnit {
+    /// NAL units prefixed with their length as in `cm::SampleBuf` and MP4 samples.
+    /// Access unit delimiters and parameter sets are dropped,
+    /// the latter belong to format description.
+    pub fn to_length_prefixed(&self, length_size: usize) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::with_capacity(self.data.len());
+        for data in nal::annex_b(&self.data) {
+            let skip = match self.stream_type {
+                StreamType::H264 => h264::Nal::new(data).is_some_and(|n| {
+                    n.unit_type() == h264::NalUnitType::AUD || n.unit_type().is_param_set()
+                }),
+                StreamType::HEVC => hevc::Nal::new(data).is_some_and(|n| {
+                    n.unit_type() == hevc::NalUnitType::AUD || n.unit_type().is_param_set()
+                }),
+                _ => false,
+            };
+            if !skip {
+                nal::push_length_prefixed(&mut out, data, length_size)?;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Invalid times stand for missing timestamps
+    #[cfg(feature = "cm")]
+    #[inline]
+    pub fn cm_timing(&self, duration: cm::Time) -> cm::SampleTimingInfo {
+        cm::SampleTimingInfo {
+            duration,
+            pts: self.pts.map_or(cm::Time::invalid(), super::cm_time),
+            dts: self.dts.map_or(cm::Time::invalid(), super::cm_time),
+        }
+    }
+
+    /// Format description with parameter sets of H.264 or HEVC key frame
+    #[cfg(feature = "cm")]
+    pub fn video_format_desc(&self) -> Option<arc::R<cm::VideoFormatDesc>> {
+        match self.stream_type {
+            StreamType::H264 => h264::Avcc::with_annex_b(&self.data, 4)
+                .ok()?
+                .to_format_desc()
+                .ok(),
+            StreamType::HEVC => hevc::Hvcc::with_annex_b(&self.data, 4)
+                .ok()?
+                .to_format_desc()
+                .ok(),
+            _ => None,
+        }
+    }
+
+    /// Sample buffer of a single sample, H.264 and HEVC NAL units are prefixed
+    /// with 4 byte lengths
+    #[cfg(feature = "cm")]
+    pub fn to_sample_buf(
+        &self,
+        desc: &cm::FormatDesc,
+        duration: cm::Time,
+    ) -> os::Result<arc::R<cm::SampleBuf>> {
+        let converted;
+        let data = if self.stream_type.is_video() {
+            converted = self
+                .to_length_prefixed(4)
+                .map_err(|_| cm::block_buf_err::BAD_LENGTH_PARAMETER)?;
+            &converted
+        } else {
+            &self.data
+        };
+        let mut block = cm::BlockBuf::with_mem_block(data.len(), None)?;
+        block.as_mut_slice()?.copy_from_slice(data);
+        let timing = self.cm_timing(duration);
+        let size = data.len();
+        unsafe {
+            os::result_unchecked(|res| {
+                cm::SampleBuf::create_in(
+                    None,
+                    Some(&*block),
+                    true,
+                    None,
+                    std::ptr::null(),
+                    Some(desc),
+                    1,
+                    1,
+                    &timing,
+                    1,
+                    &size,
+                    res,
+                )
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PesStream {
+    pid: u16,
+    stream_type: StreamType,
+    buf: Vec<u8>,
+    cc: Option<u8>,
+    random_access: bool,
+    loas: aac::LoasReader,
+}
+
+/// MPEG-TS demuxer of the first program of PAT.
+///
+/// PES are reassembled until the next payload unit start, or until their length
+/// if it is set, so the last access unit of video streams is returned by [`Demuxer::flush`].
+/// PES interrupted by continuity counter gaps are dropped.
+///
+/// Malformed data doesn't stop demuxing: the demuxer resyncs to the next sync byte,
+/// skips bad packets and PES and counts them in [`Demuxer::errors`].
+#[derive(Debug, Default, Clone)]
+pub struct Demuxer {
+    pmt_pid: Option<u16>,
+    pmt: Option<Pmt>,
+    streams: Vec<PesStream>,
+    pcr: Option<i64>,
+    partial: Vec<u8>,
+    errors: usize,
+    last_error: Option<Error>,
+}
+
+impl Demuxer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn pmt(&self) -> Option<&Pmt> {
+        self.pmt.as_ref()
+    }
+
+    /// The last PCR base of the program
+    #[inline]
+    pub fn pcr(&self) -> Option<i64> {
+        self.pcr
+    }
+
+    /// Number of skipped bad packets, lost syncs and dropped PES
+    #[inline]
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    #[inline]
+    pub fn last_error(&self) -> Option<Error> {
+        self.last_error
+    }
+
+    /// Completed access units of packets in `data`, which may end with incomplete packet
+    pub fn push(&mut self, mut data: &[u8]) -> Vec<AccessUnit> {
+        let mut units = Vec::new();
+        if !self.partial.is_empty() {
+            let needed = PACKET_LEN - self.partial.len();
+            let (head, rest) = data.split_at(needed.min(data.len()));
+            self.partial.extend_from_slice(head);
+            data = rest;
+            if self.partial.len() < PACKET_LEN {
+                return units;
+            }
+            let packet = std::mem::take(&mut self.partial);
+            let res = self.push_packet(&packet, &mut units);
+            self.record(res);
+        }
+        while !data.is_empty() {
+            if data[0] != SYNC_BYTE {
+                self.record(Err(Error::InvalidSync));
+                let pos = data.iter().position(|&b| b == SYNC_BYTE);
+                data = &data[pos.unwrap_or(data.len())..];
+                continue;
+            }
+            let Some(packet) = data.get(..PACKET_LEN) else {
+                self.partial.extend_from_slice(data);
+                break;
+            };
+            let res = self.push_packet(packet, &mut units);
+            self.record(res);
+            data = &data[PACKET_LEN..];
+        }
+        units
+    }
+
+    /// Access units of pending PES
+    pub fn flush(&mut self) -> Vec<AccessUnit> {
+        let mut units = Vec::new();
+        for i in 0..self.streams.len() {
+            if !self.streams[i].buf.is_empty() {
+                let res = self.streams[i].finish(&mut units);
+                self.record(res);
+            }
+        }
+        units
+    }
+
+    fn record(&mut self, res: Result<(), Error>) {
+        if let Err(err) = res {
+            self.errors += 1;
+            self.last_error = Some(err);
+        }
+    }
+
+    fn push_packet(&mut self, data: &[u8], units: &mut Vec<AccessUnit>) -> Result<(), Error> {
+        let packet = Packet::parse(data)?;
+        if packet.pid == PAT_PID {
+            if let Some(section) = psi_section(&packet)? {
+                self.pmt_pid = read_pat(section)?.first().map(|&(_, pid)| pid);
+            }
+            return Ok(());
+        }
+        if Some(packet.pid) == self.pmt_pid {
+            if let Some(section) = psi_section(&packet)? {
+                let pmt = Pmt::parse(section)?;
+                self.streams.retain(|s| pmt.stream(s.pid).is_some());
+                for s in &pmt.streams {
+                    if !self.streams.iter().any(|p| p.pid == s.pid) {
+                        self.streams.push(PesStream {
+                            pid: s.pid,
+                            stream_type: s.stream_type,
+                            buf: Vec::new(),
+                            cc: None,
+                            random_access: false,
+                            loas: aac::LoasReader::new(),
+                        });
+                    }
+                }
+                self.pmt = Some(pmt);
+            }
+            return Ok(());
+        }
+        if packet.pcr.is_some() && self.pmt.as_ref().map(|p| p.pcr_pid) == Some(packet.pid) {
+            self.pcr = packet.pcr;
+        }
+        let Some(stream) = self.streams.iter_mut().find(|s| s.pid == packet.pid) else {
+            return Ok(());
+        };
+        if packet.payload.is_empty() {
+            return Ok(());
+        }
+        let cc = packet.continuity_counter;
+        match stream.cc {
+            // duplicate packet
+            Some(prev) if prev == cc => return Ok(()),
+            Some(prev) if (prev + 1) & 0xf != cc && !packet.discontinuity => stream.buf.clear(),
+            _ => {}
+        }
+        stream.cc = Some(cc);
+
+        if packet.payload_unit_start {
+            if !stream.buf.is_empty() {
+                stream.finish(units)?;
+            }
+            stream.random_access = packet.random_access;
+        } else if stream.buf.is_empty() {
+            // waiting for the start of PES
+            return Ok(());
+        }
+        stream.buf.extend_from_slice(packet.payload);
+        if let Some(len) = stream.buf.get(4..6) {
+            let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+            if len != 0 && stream.buf.len() >= 6 + len {
+                stream.finish(units)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Section of PSI packet starting it, sections spanning several packets are not supported
+fn psi_section<'a>(packet: &Packet<'a>) -> Result<Option<&'a [u8]>, Error> {
+    if !packet.payload_unit_start {
+        return Ok(None);
+    }
+    let (&pointer, rest) = packet.payload.split_first().ok_or(Error::Truncated)?;
+    rest.get(pointer as usize..)
+        .map(Some)
+        .ok_or(Error::InvalidValue("pointer_field"))
+}
+
+fn read_timestamp(b: &[u8]) -> i64 {
+    ((b[0] as i64 >> 1) & 7) << 30
+        | (u16::from_be_bytes([b[1], b[2]]) as i64 >> 1) << 15
+        | u16::from_be_bytes([b[3], b[4]]) as i64 >> 1
+}
+
+struct Pes<'a> {
+    pts: Option<i64>,
+    dts: Option<i64>,
+    payload: &'a [u8],
+}
+
+fn parse_pes(data: &[u8]) -> Result<Pes<'_>, Error> {
+    let header = data.get(..6).ok_or(Error::Truncated)?;
+    if header[..3] != [0, 0, 1] {
+        return Err(Error::InvalidValue("packet_start_code_prefix"));
+    }
+    let len = u16::from_be_bytes([header[4], header[5]]) as usize;
+    let data = if len == 0 {
+        data
+    } else {
+        data.get(..6 + len).ok_or(Error::Truncated)?
+    };
+    // streams without optional PES header
+    if matches!(
+        header[3],
+        0xbc | 0xbe | 0xbf | 0xf0 | 0xf1 | 0xf2 | 0xf8 | 0xff
+    ) {
+        return Ok(Pes {
+            pts: None,
+            dts: None,
+            payload: &data[6..],
+        });
+    }
+    let optional = data.get(6..9).ok_or(Error::Truncated)?;
+    let flags = optional[1] >> 6;
+    let fields = data
+        .get(9..9 + optional[2] as usize)
+        .ok_or(Error::Truncated)?;
+    let payload = &data[9 + fields.len()..];
+    let pts = match flags {
+        2 | 3 => Some(read_timestamp(fields.get(..5).ok_or(Error::Truncated)?)),
+        _ => None,
+    };
+    let dts = match flags {
+        3 => Some(read_timestamp(fields.get(5..10).ok_or(Error::Truncated)?)),
+        _ => None,
+    };
+    Ok(Pes { pts, dts, payload })
+}
+
+impl PesStream {
+    fn finish(&mut self, units: &mut Vec<AccessUnit>) -> Result<(), Error> {
+        let buf = std::mem::take(&mut self.buf);
+        let Pes { pts, dts, payload } = parse_pes(&buf)?;
+        let dts = dts.or(pts);
+        let (pid, stream_type) = (self.pid, self.stream_type);
+        let unit = |data: Vec<u8>, pts, dts, is_sync: bool, asc| AccessUnit {
+            pid,
+            stream_type,
+            pts,
+            dts,
+            data,
+            is_sync,
+            asc,
+        };
+        let frame_pts = |asc: &aac::AudioSpecificConfig, i: usize| {
+            let samples = i as i64 * asc.samples_per_frame() as i64;
+            pts.map(|pts| pts + samples * TIMESCALE as i64 / asc.output_sample_rate() as i64)
+        };
+
+        match self.stream_type {
+            StreamType::H264 => {
+                let is_sync = self.random_access
+                    || nal::annex_b(payload)
+                        .any(|n| h264::Nal::new(n).is_some_and(|n| n.is_keyframe()));
+                units.push(unit(payload.to_vec(), pts, dts, is_sync, None));
+            }
+            StreamType::HEVC => {
+                let is_sync = self.random_access
+                    || nal::annex_b(payload)
+                        .any(|n| hevc::Nal::new(n).is_some_and(|n| n.unit_type().is_irap()));
+                units.push(unit(payload.to_vec(), pts, dts, is_sync, None));
+            }
+            StreamType::AAC_ADTS => {
+                for (i, frame) in aac::adts_frames(payload).enumerate() {
+                    let (adts, frame) = frame?;
+                    let asc = adts.to_asc();
+                    let pts = frame_pts(&asc, i);
+                    units.push(unit(frame.to_vec(), pts, pts, true, Some(asc)));
+                }
+            }
+            StreamType::AAC_LATM => {
+                for (i, frame) in self.loas.read(payload)?.into_iter().enumerate() {
+                    let asc = *self.loas.config().ok_or(Error::Truncated)?;
+                    let pts = frame_pts(&asc, i);
+                    units.push(unit(frame, pts, pts, true, Some(asc)));
+                }
+            }
+            _ => units.push(unit(payload.to_vec(), pts, dts, self.random_access, None)),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_pes, Demuxer, Packet};
+    use crate::media::{
+        aac, h264, nal,
+        ts::{Frame, MuxConfig, Muxer, StreamType, PACKET_LEN},
+    };
+
+    #[test]
+    fn packet() {
+        let mut p = [0xff; PACKET_LEN];
+        p[..12].copy_from_slice(&[0x47, 0x41, 0x00, 0x35, 7, 0x50, 0, 0, 0xaf, 0xc8, 0x7e, 0]);
+        let packet = Packet::parse(&p).unwrap();
+        assert_eq!(packet.pid, 0x100);
+        assert!(packet.payload_unit_start && packet.random_access);
+        assert_eq!(packet.continuity_counter, 5);
+        assert_eq!(packet.pcr, Some(90_000));
+        assert_eq!(packet.payload.len(), PACKET_LEN - 12);
+
+        p[4] = 184;
+        assert!(Packet::parse(&p).is_err());
+        p[0] = 0;
+        assert!(Packet::parse(&p).is_err());
+    }
+
+    #[test]
+    fn pes() {
+        let pes = [
+            0, 0, 1, 0xc0, 0, 10, 0x84, 0x80, 5, 0x29, 0x8d, 0x15, 0xcf, 0x13, 0xaa, 0xbb, 0xcc,
+        ];
+        let pes = parse_pes(&pes).unwrap();
+        assert_eq!(pes.pts, Some(0x1_2345_6789));
+        assert_eq!(pes.dts, None);
+        assert_eq!(pes.payload, [0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let sps = [0x67, 0x42, 0xc0, 0x1e, 0xda, 0x05, 0x07, 0xe4];
+        let pps = [0x68, 0xce, 0x3c, 0x80];
+        let avcc = h264::Avcc::with_param_sets(&sps, &pps, 4).unwrap();
+        let mut idr = avcc.to_annex_b();
+        idr.extend_from_slice(&[0, 0, 0, 1, 0x65]);
+        idr.extend(std::iter::repeat(0x11).take(500));
+        let non_idr = [0, 0, 0, 1, 0x41, 0x9a, 0x22];
+        let asc = aac::AudioSpecificConfig::lc(48_000, 2);
+
+        let mut mux = Muxer::new(MuxConfig::default());
+        let video = mux.add_stream(StreamType::H264);
+        let adts = mux.add_aac(asc, StreamType::AAC_ADTS).unwrap();
+        let latm = mux.add_aac(asc, StreamType::AAC_LATM).unwrap();
+        let mut out = Vec::new();
+        for i in 0..4i64 {
+            let frame = if i % 2 == 0 { &idr[..] } else { &non_idr[..] };
+            let pts = 6_000 + i * 3_000;
+            mux.write(
+                &mut out,
+                video,
+                &Frame::new(frame, pts, pts - 3_000, i % 2 == 0),
+            )
+            .unwrap();
+            let audio = [i as u8; 300];
+            mux.write(
+                &mut out,
+                adts,
+                &Frame::new(&audio, i * 1_920, i * 1_920, true),
+            )
+            .unwrap();
+            mux.write(
+                &mut out,
+                latm,
+                &Frame::new(&audio[..7], i * 1_920, i * 1_920, true),
+            )
+            .unwrap();
+        }
+
+        // feed in uneven chunks
+        let mut demux = Demuxer::new();
+        let mut units = Vec::new();
+        for chunk in out.chunks(1000) {
+            units.extend(demux.push(chunk));
+        }
+        units.extend(demux.flush());
+        assert_eq!(demux.pmt(), Some(mux.pmt()));
+        assert_eq!(demux.pcr(), Some(12_000));
+
+        let of = |pid| units.iter().filter(move |u| u.pid == pid);
+        let videos: Vec<_> = of(video).collect();
+        assert_eq!(videos.len(), 4);
+        assert_eq!(videos[1].pts, Some(9_000));
+        assert_eq!(videos[1].dts, Some(6_000));
+        assert!(videos[2].is_sync && !videos[3].is_sync);
+        // AUD and parameter sets are dropped
+        let sample = videos[2].to_length_prefixed(4).unwrap();
+        let nals: Vec<_> = nal::length_prefixed(&sample, 4).unwrap().collect();
+        assert_eq!(nals.len(), 1);
+        assert_eq!(nals[0].unwrap().len(), 501);
+        assert_eq!(h264::Avcc::with_annex_b(&videos[0].data, 4).unwrap(), avcc);
+
+        for pid in [adts, latm] {
+            let audio: Vec<_> = of(pid).collect();
+            assert_eq!(audio.len(), 4);
+            assert_eq!(audio[3].pts, Some(3 * 1_920));
+            assert_eq!(audio[3].asc, Some(asc));
+            assert_eq!(audio[3].data[0], 3);
+        }
+        assert_eq!(of(adts).nth(2).unwrap().data.len(), 300);
+    }
+
+    #[test]
+    fn continuity() {
+        let mut mux = Muxer::new(Default::default());
+        let pid = mux.add_stream(StreamType::PRIVATE_DATA);
+        let mut out = Vec::new();
+        for i in 0..3 {
+            mux.write(
+                &mut out,
+                pid,
+                &Frame::new(&[i; 400], i as i64, i as i64, i == 0),
+            )
+            .unwrap();
+        }
+        // PAT, PMT and three PES of three packets, the middle one is lost
+        assert_eq!(out.len(), 11 * PACKET_LEN);
+        out.drain(6 * PACKET_LEN..7 * PACKET_LEN);
+        let mut demux = Demuxer::new();
+        let mut units = demux.push(&out);
+        units.extend(demux.flush());
+        let pts: Vec<_> = units.iter().map(|u| u.pts).collect();
+        assert_eq!(pts, [Some(0), Some(2)]);
+
+        // duplicate packets are skipped
+        let mut demux = Demuxer::new();
+        let dup = out[3 * PACKET_LEN..4 * PACKET_LEN].to_vec();
+        out.splice(4 * PACKET_LEN..4 * PACKET_LEN, dup);
+        let units = demux.push(&out);
+        assert_eq!(units[0].data, [0; 400]);
+    }
+
+    #[test]
+    fn resync() {
+        let mut mux = Muxer::new(Default::default());
+        let pid = mux.add_stream(StreamType::PRIVATE_DATA);
+        let mut out = Vec::new();
+        for i in 0..2 {
+            mux.write(
+                &mut out,
+                pid,
+                &Frame::new(&[i; 400], i as i64, i as i64, i == 0),
+            )
+            .unwrap();
+        }
+        assert_eq!(out.len(), 8 * PACKET_LEN);
+        let cc = out[out.len() - PACKET_LEN + 3] & 0xf;
+
+        // junk without sync byte, bad adaptation field length and PAT with CRC mismatch
+        let mut bad = vec![0; 50];
+        let mut packet = [0xff; PACKET_LEN];
+        packet[..5].copy_from_slice(&[0x47, (pid >> 8) as u8, pid as u8, 0x30, 184]);
+        bad.extend_from_slice(&packet);
+        packet[..5].copy_from_slice(&[0x47, 0x40, 0x00, 0x10, 0]);
+        packet[5..13].copy_from_slice(&[0, 0xb0, 13, 0, 1, 0xc1, 0, 0]);
+        bad.extend_from_slice(&packet);
+        out.splice(5 * PACKET_LEN..5 * PACKET_LEN, bad);
+
+        // PES without PTS
+        let mut packet = vec![
+            0x47,
+            0x40 | (pid >> 8) as u8,
+            pid as u8,
+            0x10 | (cc + 1) & 0xf,
+        ];
+        packet.extend_from_slice(&[0, 0, 1, 0xbd, 0, 178, 0x80, 0, 0]);
+        packet.resize(PACKET_LEN, 7);
+        out.extend_from_slice(&packet);
+
+        let mut demux = Demuxer::new();
+        let mut units = Vec::new();
+        for chunk in out.chunks(100) {
+            units.extend(demux.push(chunk));
+        }
+        units.extend(demux.flush());
+        assert_eq!(demux.errors(), 3);
+        assert!(demux.pmt().is_some());
+        let pts: Vec<_> = units.iter().map(|u| u.pts).collect();
+        assert_eq!(pts, [Some(0), Some(1), None]);
+        assert_eq!(units[1].data, [1; 400]);
+        assert_eq!(units[2].data, [7; 175]);
+    }
+}
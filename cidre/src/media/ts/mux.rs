@@ -0,0 +1,460 @@
+//! Transport stream muxer.
+
+use super::{
+    write_pat, Error, Pmt, PmtStream, StreamType, PACKET_LEN, PAT_PID, SYNC_BYTE, TIMESTAMP_MASK,
+};
+use crate::media::{aac, h264, hevc, nal};
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+/// Options of [`Muxer`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuxConfig {
+    pub program_number: u16,
+    pub pmt_pid: u16,
+
+    /// PID of the first stream, following streams get the next ones
+    pub first_pid: u16,
+
+    /// Prepends access unit delimiter to H.264 and HEVC access units without it,
+    /// HLS requires them
+    pub access_unit_delimiters: bool,
+}
+
+impl Default for MuxConfig {
+    fn default() -> Self {
+        Self {
+            program_number: 1,
+            pmt_pid: 0x1000,
+            first_pid: 0x100,
+            access_unit_delimiters: true,
+        }
+    }
+}
+
+/// Access unit or audio frame with 90 kHz timestamps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Frame<'a> {
+    /// Annex B access unit, raw AAC frame of [`Muxer::add_aac`] streams
+    /// or elementary stream data of other types
+    pub data: &'a [u8],
+    pub pts: i64,
+    pub dts: i64,
+
+    /// Key frame, starts random access point and repeats PAT and PMT
+    pub is_sync: bool,
+}
+
+impl<'a> Frame<'a> {
+    #[inline]
+    pub const fn new(data: &'a [u8], pts: i64, dts: i64, is_sync: bool) -> Self {
+        Self {
+            data,
+            pts,
+            dts,
+            is_sync,
+        }
+    }
+
+    /// Frame with timestamps of `cm::SampleBuf` timing, invalid DTS means DTS equal to PTS
+    #[cfg(feature = "cm")]
+    pub fn with_timing(data: &'a [u8], timing: &cm::SampleTimingInfo, is_sync: bool) -> Self {
+        let pts = super::timestamp(timing.pts);
+        let dts = if timing.dts.is_valid() {
+            super::timestamp(timing.dts)
+        } else {
+            pts
+        };
+        Self::new(data, pts, dts, is_sync)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Stream {
+    pid: u16,
+    stream_type: StreamType,
+    asc: Option<aac::AudioSpecificConfig>,
+    cc: u8,
+}
+
+/// MPEG-TS muxer of a single program.
+///
+/// Streams are to be added before the first frame is written. PCR is carried by
+/// the first video stream and equals DTS of its frames.
+#[derive(Debug, Clone)]
+pub struct Muxer {
+    config: MuxConfig,
+    pmt: Pmt,
+    streams: Vec<Stream>,
+    pat_cc: u8,
+    pmt_cc: u8,
+    psi_written: bool,
+}
+
+impl Muxer {
+    pub fn new(config: MuxConfig) -> Self {
+        Self {
+            pmt: Pmt {
+                program_number: config.program_number,
+                pcr_pid: config.first_pid,
+                streams: Vec::new(),
+            },
+            config,
+            streams: Vec::new(),
+            pat_cc: 0,
+            pmt_cc: 0,
+            psi_written: false,
+        }
+    }
+
+    #[inline]
+    pub fn pmt(&self) -> &Pmt {
+        &self.pmt
+    }
+
+    /// Adds stream which frames are written as is and returns its PID
+    pub fn add_stream(&mut self, stream_type: StreamType) -> u16 {
+        self.push_stream(stream_type, None)
+    }
+
+    /// Adds AAC stream of raw frames framed with ADTS or LOAS/LATM
+    /// depending on `stream_type`
+    pub fn add_aac(
+        &mut self,
+        asc: aac::AudioSpecificConfig,
+        stream_type: StreamType,
+    ) -> Result<u16, Error> {
+        match stream_type {
+            StreamType::AAC_ADTS => {
+                aac::Adts::with_asc(&asc, 0)?;
+            }
+            StreamType::AAC_LATM => {}
+            _ => return Err(Error::InvalidValue("AAC stream type")),
+        }
+        Ok(self.push_stream(stream_type, Some(asc)))
+    }
+
+    fn push_stream(
+        &mut self,
+        stream_type: StreamType,
+        asc: Option<aac::AudioSpecificConfig>,
+    ) -> u16 {
+        let pid = self.config.first_pid + self.streams.len() as u16;
+        let has_video = self.streams.iter().any(|s| s.stream_type.is_video());
+        if self.streams.is_empty() || (stream_type.is_video() && !has_video) {
+            self.pmt.pcr_pid = pid;
+        }
+        self.pmt.streams.push(PmtStream {
+            stream_type,
+            pid,
+            descriptors: Vec::new(),
+        });
+        self.streams.push(Stream {
+            pid,
+            stream_type,
+            asc,
+            cc: 0,
+        });
+        pid
+    }
+
+    /// Appends PAT and PMT packets
+    pub fn write_psi(&mut self, out: &mut Vec<u8>) {
+        let mut section = Vec::with_capacity(PACKET_LEN);
+        write_pat(
+            &mut section,
+            self.config.program_number,
+            self.config.pmt_pid,
+        );
+        write_psi_packet(out, PAT_PID, &mut self.pat_cc, &section);
+        section.clear();
+        self.pmt.write(&mut section);
+        write_psi_packet(out, self.config.pmt_pid, &mut self.pmt_cc, &section);
+        self.psi_written = true;
+    }
+
+    /// Appends packets of PES with the frame, preceded by PAT and PMT at start
+    /// and before key frames of PCR stream
+    pub fn write(&mut self, out: &mut Vec<u8>, pid: u16, frame: &Frame) -> Result<(), Error> {
+        let pcr_pid = self.pmt.pcr_pid;
+        let index = self
+            .streams
+            .iter()
+            .position(|s| s.pid == pid)
+            .ok_or(Error::UnknownPid(pid))?;
+        if !self.psi_written || (frame.is_sync && pid == pcr_pid) {
+            self.write_psi(out);
+        }
+
+        let stream = &mut self.streams[index];
+        let mut payload = Vec::with_capacity(frame.data.len() + 32);
+        push_pes_header(&mut payload, stream.stream_type, frame.pts, frame.dts);
+        if self.config.access_unit_delimiters {
+            push_aud(&mut payload, stream.stream_type, frame.data);
+        }
+        match (stream.asc, stream.stream_type) {
+            (Some(asc), StreamType::AAC_ADTS) => aac::push_adts(&mut payload, &asc, frame.data)?,
+            (Some(asc), _) => aac::push_loas(&mut payload, &asc, frame.data)?,
+            _ => payload.extend_from_slice(frame.data),
+        }
+        // unbounded length is allowed for video only
+        let pes_len = payload.len() - 6;
+        if !stream.stream_type.is_video() && pes_len <= 0xffff {
+            payload[4..6].copy_from_slice(&(pes_len as u16).to_be_bytes());
+        }
+
+        let pcr = (pid == pcr_pid).then_some(frame.dts);
+        write_packets(out, pid, &mut stream.cc, &payload, pcr, frame.is_sync);
+        Ok(())
+    }
+
+    /// Appends packets of all samples of `cm::SampleBuf`. Length prefixed H.264 and HEVC
+    /// samples are converted to Annex B with parameter sets before key frames.
+    #[cfg(feature = "cm")]
+    pub fn write_sample_buf(
+        &mut self,
+        out: &mut Vec<u8>,
+        pid: u16,
+        buf: &cm::SampleBuf,
+    ) -> Result<(), Error> {
+        let stream_type = self
+            .streams
+            .iter()
+            .find(|s| s.pid == pid)
+            .ok_or(Error::UnknownPid(pid))?
+            .stream_type;
+        let block = buf
+            .data_buf()
+            .ok_or(Error::InvalidValue("sample buffer data"))?;
+        let mut data = Vec::with_capacity(block.data_len());
+        while data.len() < block.data_len() {
+            let (chunk, _) = block
+                .data_ptr_at(data.len())
+                .map_err(|_| Error::Truncated)?;
+            if chunk.is_empty() {
+                return Err(Error::Truncated);
+            }
+            data.extend_from_slice(chunk);
+        }
+
+        if stream_type.is_video() {
+            let desc = buf
+                .format_desc()
+                .ok_or(Error::InvalidValue("sample buffer format description"))?;
+            let (param_sets, length_size) = match stream_type {
+                StreamType::H264 => h264::Avcc::with_format_desc(desc)
+                    .map(|avcc| (avcc.to_annex_b(), avcc.nal_length_size)),
+                StreamType::HEVC => hevc::Hvcc::with_format_desc(desc)
+                    .map(|hvcc| (hvcc.to_annex_b(), hvcc.nal_length_size)),
+                _ => None,
+            }
+            .ok_or(Error::InvalidValue("video format description"))?;
+            let is_sync = buf.is_key_frame();
+            let mut annex_b = if is_sync { param_sets } else { Vec::new() };
+            annex_b.extend_from_slice(&nal::length_prefixed_to_annex_b(
+                &data,
+                length_size as usize,
+            )?);
+            let timing = buf
+                .timing_info(0)
+                .map_err(|_| Error::InvalidValue("sample timing"))?;
+            return self.write(out, pid, &Frame::with_timing(&annex_b, &timing, is_sync));
+        }
+
+        let mut offset = 0;
+        for i in 0..buf.num_samples() {
+            let size = buf.sample_size(i);
+            let sample = data.get(offset..offset + size).ok_or(Error::Truncated)?;
+            let timing = buf
+                .timing_info(i)
+                .map_err(|_| Error::InvalidValue("sample timing"))?;
+            self.write(out, pid, &Frame::with_timing(sample, &timing, true))?;
+            offset += size;
+        }
+        Ok(())
+    }
+}
+
+/// Prepends access unit delimiter of any picture type if the access unit lacks it
+fn push_aud(out: &mut Vec<u8>, stream_type: StreamType, data: &[u8]) {
+    let first = nal::annex_b(data).next().and_then(|n| n.first().copied());
+    match stream_type {
+        StreamType::H264 if first.map(|h| h & 0x1f) != Some(h264::NalUnitType::AUD.0) => {
+            out.extend_from_slice(&[0, 0, 0, 1, 0x09, 0xf0]);
+        }
+        StreamType::HEVC if first.map(|h| h >> 1 & 0x3f) != Some(hevc::NalUnitType::AUD.0) => {
+            out.extend_from_slice(&[0, 0, 0, 1, 0x46, 0x01, 0x50]);
+        }
+        _ => {}
+    }
+}
+
+fn push_timestamp(out: &mut Vec<u8>, prefix: u8, timestamp: i64) {
+    let ts = (timestamp & TIMESTAMP_MASK) as u64;
+    out.push(prefix << 4 | (ts >> 29 & 0x0e) as u8 | 1);
+    out.extend_from_slice(&((ts >> 14 & 0xfffe) as u16 | 1).to_be_bytes());
+    out.extend_from_slice(&((ts << 1 & 0xfffe) as u16 | 1).to_be_bytes());
+}
+
+/// PES header with zero `PES_packet_length`
+fn push_pes_header(out: &mut Vec<u8>, stream_type: StreamType, pts: i64, dts: i64) {
+    out.extend_from_slice(&[0, 0, 1, stream_type.stream_id(), 0, 0]);
+    // data_alignment_indicator
+    out.push(0x84);
+    if pts == dts {
+        out.extend_from_slice(&[0x80, 5]);
+        push_timestamp(out, 0b0010, pts);
+    } else {
+        out.extend_from_slice(&[0xc0, 10]);
+        push_timestamp(out, 0b0011, pts);
+        push_timestamp(out, 0b0001, dts);
+    }
+}
+
+fn write_psi_packet(out: &mut Vec<u8>, pid: u16, cc: &mut u8, section: &[u8]) {
+    debug_assert!(section.len() < PACKET_LEN - 5);
+    let start = out.len();
+    out.push(SYNC_BYTE);
+    out.extend_from_slice(&(0x4000 | pid).to_be_bytes());
+    out.push(0x10 | *cc);
+    *cc = (*cc + 1) & 0xf;
+    // pointer_field
+    out.push(0);
+    out.extend_from_slice(section);
+    out.resize(start + PACKET_LEN, 0xff);
+}
+
+/// Splits PES into packets, PCR and random access indicator go to the first one
+/// and the last one is padded with adaptation field stuffing
+fn write_packets(
+    out: &mut Vec<u8>,
+    pid: u16,
+    cc: &mut u8,
+    mut payload: &[u8],
+    mut pcr: Option<i64>,
+    mut random_access: bool,
+) {
+    let mut start = true;
+    while start || !payload.is_empty() {
+        let mut fields = [0u8; 7];
+        let mut fields_len = 0;
+        if random_access || pcr.is_some() {
+            fields[0] = if random_access { 0x40 } else { 0 };
+            fields_len = 1;
+            if let Some(pcr) = pcr.take() {
+                fields[0] |= 0x10;
+                // 33 bits of base, 6 reserved bits and zero extension
+                let value = ((pcr & TIMESTAMP_MASK) as u64) << 15 | 0x7e00;
+                fields[1..].copy_from_slice(&value.to_be_bytes()[2..]);
+                fields_len = 7;
+            }
+            random_access = false;
+        }
+        let af_len = if fields_len > 0 { fields_len + 1 } else { 0 };
+        let len = payload.len().min(PACKET_LEN - 4 - af_len);
+        let stuffing = PACKET_LEN - 4 - af_len - len;
+        let has_af = af_len + stuffing > 0;
+
+        out.push(SYNC_BYTE);
+        out.extend_from_slice(&((start as u16) << 14 | pid).to_be_bytes());
+        out.push(if has_af { 0x30 } else { 0x10 } | *cc);
+        *cc = (*cc + 1) & 0xf;
+        if has_af {
+            let total = af_len + stuffing;
+            out.push(total as u8 - 1);
+            if total > 1 {
+                let fields = &fields[..fields_len.max(1)];
+                out.extend_from_slice(fields);
+                out.resize(out.len() + total - 1 - fields.len(), 0xff);
+            }
+        }
+        out.extend_from_slice(&payload[..len]);
+        payload = &payload[len..];
+        start = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{push_timestamp, write_packets, Frame, Muxer};
+    use crate::media::ts::{StreamType, PACKET_LEN};
+
+    #[test]
+    fn timestamp() {
+        let mut out = Vec::new();
+        push_timestamp(&mut out, 0b0010, 0x1_2345_6789);
+        assert_eq!(out, [0x29, 0x8d, 0x15, 0xcf, 0x13]);
+        // negative timestamps wrap around
+        out.clear();
+        push_timestamp(&mut out, 0b0010, -1);
+        assert_eq!(out, [0x2f, 0xff, 0xff, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn packets() {
+        let mut out = Vec::new();
+        let mut cc = 15;
+        write_packets(&mut out, 0x100, &mut cc, &[0xaa; 200], Some(90_000), true);
+        assert_eq!(out.len(), 2 * PACKET_LEN);
+        assert_eq!(cc, 1);
+        assert_eq!(out[..4], [0x47, 0x41, 0x00, 0x3f]);
+        // adaptation field with random access indicator and PCR of 1 second
+        assert_eq!(out[4..12], [7, 0x50, 0x00, 0x00, 0xaf, 0xc8, 0x7e, 0x00]);
+        assert!(out[12..PACKET_LEN].iter().all(|&b| b == 0xaa));
+
+        // 200 - 176 = 24 bytes in the second packet after 160 bytes of stuffing
+        let second = &out[PACKET_LEN..];
+        assert_eq!(second[..6], [0x47, 0x01, 0x00, 0x30, 159, 0]);
+        assert!(second[6..164].iter().all(|&b| b == 0xff));
+        assert!(second[164..].iter().all(|&b| b == 0xaa));
+
+        // single stuffing byte
+        out.clear();
+        write_packets(&mut out, 0x100, &mut cc, &[0xbb; 183], None, false);
+        assert_eq!(out[3..6], [0x31, 0, 0xbb]);
+    }
+
+    #[test]
+    fn psi_repetition() {
+        let mut mux = Muxer::new(Default::default());
+        let audio = mux.add_stream(StreamType::AAC_ADTS);
+        let video = mux.add_stream(StreamType::HEVC);
+        assert_eq!((audio, video), (0x100, 0x101));
+        assert_eq!(mux.pmt().pcr_pid, video);
+
+        let mut out = Vec::new();
+        let idr = [0, 0, 0, 1, 0x26, 0x01, 0xaf];
+        mux.write(&mut out, video, &Frame::new(&idr, 6_000, 3_000, true))
+            .unwrap();
+        let pids = |out: &[u8]| -> Vec<u16> {
+            out.chunks(PACKET_LEN)
+                .map(|p| u16::from_be_bytes([p[1], p[2]]) & 0x1fff)
+                .collect()
+        };
+        assert_eq!(pids(&out), [0, 0x1000, video]);
+        // AUD is prepended and PES carries both timestamps
+        let packet = &out[2 * PACKET_LEN..];
+        let pes = &packet[5 + packet[4] as usize..];
+        assert_eq!(pes[..9], [0, 0, 1, 0xe0, 0, 0, 0x84, 0xc0, 10]);
+        assert_eq!(pes[19..26], [0, 0, 0, 1, 0x46, 0x01, 0x50]);
+
+        out.clear();
+        mux.write(
+            &mut out,
+            video,
+            &Frame::new(&[0, 0, 1, 2, 1, 0xd0], 9_000, 6_000, false),
+        )
+        .unwrap();
+        mux.write(&mut out, audio, &Frame::new(&[0xff, 0xf1], 0, 0, true))
+            .unwrap();
+        assert_eq!(pids(&out), [video, audio]);
+        mux.write(&mut out, video, &Frame::new(&idr, 12_000, 9_000, true))
+            .unwrap();
+        assert_eq!(pids(&out), [video, audio, 0, 0x1000, video]);
+        assert_eq!(out[3 * PACKET_LEN + 3] & 0xf, 1);
+        assert!(mux
+            .write(&mut out, 0x200, &Frame::new(&[], 0, 0, true))
+            .is_err());
+    }
+}
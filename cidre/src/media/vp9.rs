@@ -0,0 +1,576 @@
+//! VP9 superframes, uncompressed frame headers and `vpcC` records.
+
+use crate::media::{bits, vui};
+
+#[cfg(all(feature = "cm", feature = "cv"))]
+use crate::{arc, cf, cm, media::ext, os};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended before the end of the structure
+    Truncated,
+
+    /// Syntax element is out of range
+    InvalidValue(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated VP9 data"),
+            Self::InvalidValue(name) => write!(f, "invalid VP9 {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<bits::Error> for Error {
+    #[inline]
+    fn from(_: bits::Error) -> Self {
+        Self::Truncated
+    }
+}
+
+/// Frames of superframe, or the whole `data` if it has no superframe index.
+///
+/// ```
+/// use cidre::media::vp9;
+///
+/// let data = [0x82, 0x49, 0x83, 0x42, 0x86, 0x55, 0xc1, 0x04, 0x02, 0xc1];
+/// let frames = vp9::frames(&data).unwrap();
+/// assert_eq!(frames, [&[0x82, 0x49, 0x83, 0x42][..], &[0x86, 0x55]]);
+/// ```
+pub fn frames(data: &[u8]) -> Result<Vec<&[u8]>, Error> {
+    let Some(&marker) = data.last() else {
+        return Ok(Vec::new());
+    };
+    if marker & 0xe0 != 0xc0 {
+        return Ok(vec![data]);
+    }
+    let size_len = ((marker >> 3) & 3) as usize + 1;
+    let count = (marker & 7) as usize + 1;
+    let index_len = 2 + size_len * count;
+    if data.len() < index_len || data[data.len() - index_len] != marker {
+        return Ok(vec![data]);
+    }
+    let index = &data[data.len() - index_len + 1..data.len() - 1];
+    let mut rest = &data[..data.len() - index_len];
+    let mut res = Vec::with_capacity(count);
+    for size in index.chunks_exact(size_len) {
+        let size = size
+            .iter()
+            .rev()
+            .fold(0usize, |acc, &b| acc << 8 | b as usize);
+        let (frame, tail) = rest.split_at_checked(size).ok_or(Error::Truncated)?;
+        res.push(frame);
+        rest = tail;
+    }
+    Ok(res)
+}
+
+/// `color_space`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct ColorSpace(pub u8);
+
+impl ColorSpace {
+    pub const UNKNOWN: Self = Self(0);
+    pub const BT_601: Self = Self(1);
+    pub const BT_709: Self = Self(2);
+    pub const SMPTE_170: Self = Self(3);
+    pub const SMPTE_240: Self = Self(4);
+    pub const BT_2020: Self = Self(5);
+    pub const RGB: Self = Self(7);
+
+    /// Code points for `vpcC`, transfer of BT.2020 is SDR and should be overridden for HDR
+    pub fn color_description(&self) -> vui::ColorDescription {
+        let (primaries, transfer, matrix) = match *self {
+            Self::BT_601 | Self::SMPTE_170 => (6, 6, 6),
+            Self::BT_709 => (1, 1, 1),
+            Self::SMPTE_240 => (7, 7, 7),
+            Self::BT_2020 => (9, 14, 9),
+            Self::RGB => return vui::ColorDescription::SRGB_IDENTITY,
+            _ => (2, 2, 2),
+        };
+        vui::ColorDescription {
+            primaries,
+            transfer,
+            matrix,
+        }
+    }
+}
+
+/// `color_config()`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ColorConfig {
+    pub bit_depth: u8,
+    pub color_space: ColorSpace,
+    pub full_range: bool,
+    pub subsampling_x: bool,
+    pub subsampling_y: bool,
+}
+
+impl ColorConfig {
+    fn read(r: &mut bits::Reader, profile: u8) -> Result<Self, Error> {
+        let bit_depth = if profile >= 2 {
+            if r.read_bool()? {
+                12
+            } else {
+                10
+            }
+        } else {
+            8
+        };
+        let color_space = ColorSpace(r.read_u8(3)?);
+        let mut cfg = Self {
+            bit_depth,
+            color_space,
+            full_range: true,
+            subsampling_x: true,
+            subsampling_y: true,
+        };
+        if color_space != ColorSpace::RGB {
+            cfg.full_range = r.read_bool()?;
+            if profile == 1 || profile == 3 {
+                cfg.subsampling_x = r.read_bool()?;
+                cfg.subsampling_y = r.read_bool()?;
+                r.skip(1)?;
+            }
+        } else {
+            if profile == 0 || profile == 2 {
+                return Err(Error::InvalidValue("color_space"));
+            }
+            cfg.subsampling_x = false;
+            cfg.subsampling_y = false;
+            r.skip(1)?;
+        }
+        Ok(cfg)
+    }
+
+    /// `chromaSubsampling` of `vpcC`
+    #[inline]
+    pub fn chroma_subsampling(&self) -> u8 {
+        match (self.subsampling_x, self.subsampling_y) {
+            (true, true) => Vpcc::CHROMA_420_COLOCATED,
+            (true, false) => Vpcc::CHROMA_422,
+            _ => Vpcc::CHROMA_444,
+        }
+    }
+}
+
+/// Uncompressed header up to frame size.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct FrameHeader {
+    pub profile: u8,
+
+    /// `frame_to_show_map_idx` of frame which repeats already decoded one
+    pub show_existing_frame: Option<u8>,
+    pub key_frame: bool,
+    pub show_frame: bool,
+    pub error_resilient_mode: bool,
+    pub intra_only: bool,
+
+    /// `None` for inter frames, profile 0 intra-only frames use BT.601 8-bit 4:2:0
+    pub color: Option<ColorConfig>,
+
+    /// `None` for inter frames which take size from reference frames
+    pub size: Option<(u32, u32)>,
+    pub render_size: Option<(u32, u32)>,
+}
+
+impl FrameHeader {
+    const SYNC_CODE: u64 = 0x49_83_42;
+
+    pub fn parse(frame: &[u8]) -> Result<Self, Error> {
+        let r = &mut bits::Reader::new(frame);
+        if r.read_u8(2)? != 2 {
+            return Err(Error::InvalidValue("frame_marker"));
+        }
+        let low = r.read_u8(1)?;
+        let profile = (r.read_u8(1)? << 1) | low;
+        if profile == 3 {
+            r.skip(1)?;
+        }
+        let mut header = Self {
+            profile,
+            show_existing_frame: None,
+            key_frame: false,
+            show_frame: true,
+            error_resilient_mode: false,
+            intra_only: false,
+            color: None,
+            size: None,
+            render_size: None,
+        };
+        if r.read_bool()? {
+            header.show_existing_frame = Some(r.read_u8(3)?);
+            return Ok(header);
+        }
+        header.key_frame = !r.read_bool()?;
+        header.show_frame = r.read_bool()?;
+        header.error_resilient_mode = r.read_bool()?;
+        if header.key_frame {
+            if r.read_bits(24)? != Self::SYNC_CODE {
+                return Err(Error::InvalidValue("frame_sync_code"));
+            }
+            header.color = Some(ColorConfig::read(r, profile)?);
+        } else {
+            header.intra_only = !header.show_frame && r.read_bool()?;
+            if !header.error_resilient_mode {
+                r.skip(2)?; // reset_frame_context
+            }
+            if !header.intra_only {
+                return Ok(header);
+            }
+            if r.read_bits(24)? != Self::SYNC_CODE {
+                return Err(Error::InvalidValue("frame_sync_code"));
+            }
+            header.color = Some(if profile > 0 {
+                ColorConfig::read(r, profile)?
+            } else {
+                ColorConfig {
+                    bit_depth: 8,
+                    color_space: ColorSpace::BT_601,
+                    full_range: false,
+                    subsampling_x: true,
+                    subsampling_y: true,
+                }
+            });
+            r.skip(8)?; // refresh_frame_flags
+        }
+        let size = (r.read_u32(16)? + 1, r.read_u32(16)? + 1);
+        header.size = Some(size);
+        header.render_size = Some(if r.read_bool()? {
+            (r.read_u32(16)? + 1, r.read_u32(16)? + 1)
+        } else {
+            size
+        });
+        Ok(header)
+    }
+}
+
+/// VP9 level for picture size and luma sample rate
+pub fn level(width: u32, height: u32, frame_rate: f64) -> u8 {
+    const LEVELS: [(u8, u64, u64); 14] = [
+        (10, 829_440, 36_864),
+        (11, 2_764_800, 73_728),
+        (20, 4_608_000, 122_880),
+        (21, 9_216_000, 245_760),
+        (30, 20_736_000, 552_960),
+        (31, 36_864_000, 983_040),
+        (40, 83_558_400, 2_228_224),
+        (41, 160_432_128, 2_228_224),
+        (50, 311_951_360, 8_912_896),
+        (51, 588_251_136, 8_912_896),
+        (52, 1_176_502_272, 8_912_896),
+        (60, 1_176_502_272, 35_651_584),
+        (61, 2_353_004_544, 35_651_584),
+        (62, 4_706_009_088, 35_651_584),
+    ];
+    let size = width as u64 * height as u64;
+    let rate = (size as f64 * frame_rate).ceil() as u64;
+    LEVELS
+        .iter()
+        .find(|&&(_, max_rate, max_size)| rate <= max_rate && size <= max_size)
+        .map_or(62, |l| l.0)
+}
+
+/// `VPCodecConfigurationRecord` version 1, contents of `vpcC` box.
+///
+/// ```
+/// use cidre::media::vp9;
+///
+/// let key = [0x82, 0x49, 0x83, 0x42, 0x00, 0x77, 0xf0, 0x43, 0x76, 0x00];
+/// let header = vp9::FrameHeader::parse(&key).unwrap();
+/// assert_eq!(header.size, Some((1920, 1080)));
+///
+/// let vpcc = vp9::Vpcc::with_frame_header(&header, 30.0).unwrap();
+/// assert_eq!(vpcc.codec_string(), "vp09.00.40.08.01.02.02.02.00");
+/// assert_eq!(vp9::Vpcc::parse(&vpcc.to_bytes()).unwrap(), vpcc);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Vpcc {
+    pub profile: u8,
+    pub level: u8,
+    pub bit_depth: u8,
+    pub chroma_subsampling: u8,
+    pub full_range: bool,
+    pub color: vui::ColorDescription,
+    pub codec_init_data: Vec<u8>,
+}
+
+impl Vpcc {
+    pub const CHROMA_420_VERTICAL: u8 = 0;
+    pub const CHROMA_420_COLOCATED: u8 = 1;
+    pub const CHROMA_422: u8 = 2;
+    pub const CHROMA_444: u8 = 3;
+
+    /// Record from key or intra-only frame header, level is derived from size and `frame_rate`
+    pub fn with_frame_header(header: &FrameHeader, frame_rate: f64) -> Result<Self, Error> {
+        let (Some(color), Some((width, height))) = (header.color, header.size) else {
+            return Err(Error::InvalidValue("frame_type"));
+        };
+        Ok(Self {
+            profile: header.profile,
+            level: level(width, height, frame_rate),
+            bit_depth: color.bit_depth,
+            chroma_subsampling: color.chroma_subsampling(),
+            full_range: color.full_range,
+            color: color.color_space.color_description(),
+            codec_init_data: Vec::new(),
+        })
+    }
+
+    /// Parses `vpcC` full box payload with version and flags
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let (h, rest) = data.split_at_checked(12).ok_or(Error::Truncated)?;
+        if h[0] != 1 {
+            return Err(Error::InvalidValue("vpcC version"));
+        }
+        let len = u16::from_be_bytes([h[10], h[11]]) as usize;
+        let codec_init_data = rest.get(..len).ok_or(Error::Truncated)?.to_vec();
+        Ok(Self {
+            profile: h[4],
+            level: h[5],
+            bit_depth: h[6] >> 4,
+            chroma_subsampling: (h[6] >> 1) & 7,
+            full_range: h[6] & 1 != 0,
+            color: vui::ColorDescription {
+                primaries: h[7],
+                transfer: h[8],
+                matrix: h[9],
+            },
+            codec_init_data,
+        })
+    }
+
+    /// `vpcC` full box payload with version 1 and zero flags
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(12 + self.codec_init_data.len());
+        out.extend_from_slice(&[1, 0, 0, 0, self.profile, self.level]);
+        out.push((self.bit_depth << 4) | (self.chroma_subsampling << 1) | self.full_range as u8);
+        out.extend_from_slice(&[self.color.primaries, self.color.transfer, self.color.matrix]);
+        out.extend_from_slice(&(self.codec_init_data.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.codec_init_data);
+        out
+    }
+
+    /// RFC 6381 codec string in full form, e.g. `vp09.02.10.10.01.09.16.09.01`
+    pub fn codec_string(&self) -> String {
+        format!(
+            "vp09.{:02}.{:02}.{:02}.{:02}.{:02}.{:02}.{:02}.{:02}",
+            self.profile,
+            self.level,
+            self.bit_depth,
+            self.chroma_subsampling,
+            self.color.primaries,
+            self.color.transfer,
+            self.color.matrix,
+            self.full_range as u8
+        )
+    }
+
+    /// Format description extensions with `vpcC` atom and color tags
+    #[cfg(all(feature = "cm", feature = "cv"))]
+    pub fn to_format_desc_exts(&self) -> Option<arc::R<ext::Exts>> {
+        ext::video_exts(
+            cf::str!(c"vpcC"),
+            &self.to_bytes(),
+            Some(self.color),
+            self.full_range,
+        )
+    }
+
+    #[cfg(all(feature = "cm", feature = "cv"))]
+    pub fn to_format_desc(
+        &self,
+        width: u32,
+        height: u32,
+    ) -> os::Result<arc::R<cm::VideoFormatDesc>> {
+        let Some(exts) = self.to_format_desc_exts() else {
+            return Err(cm::format_desc_bridge_err::INVALID_PARAMETER);
+        };
+        cm::VideoFormatDesc::video(
+            cm::VideoCodec::VP9,
+            width as i32,
+            height as i32,
+            Some(&exts),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{bits, vui};
+
+    use super::{frames, level, ColorSpace, Error, FrameHeader, Vpcc};
+
+    fn key_frame(profile: u8, color_space: u8, full_range: bool, render: bool) -> Vec<u8> {
+        let mut w = bits::Writer::new();
+        w.write_bits(2, 2);
+        w.write_bits(profile as u64 & 1, 1);
+        w.write_bits(profile as u64 >> 1, 1);
+        if profile == 3 {
+            w.write_bool(false);
+        }
+        w.write_bool(false); // show_existing_frame
+        w.write_bool(false); // key frame
+        w.write_bool(true);
+        w.write_bool(false);
+        w.write_bits(0x49_83_42, 24);
+        if profile >= 2 {
+            w.write_bool(false); // 10-bit
+        }
+        w.write_bits(color_space as u64, 3);
+        if color_space != 7 {
+            w.write_bool(full_range);
+            if profile & 1 == 1 {
+                w.write_bool(true);
+                w.write_bool(false);
+                w.write_bool(false);
+            }
+        } else {
+            w.write_bool(false);
+        }
+        w.write_bits(3839, 16);
+        w.write_bits(2159, 16);
+        w.write_bool(render);
+        if render {
+            w.write_bits(1919, 16);
+            w.write_bits(1079, 16);
+        }
+        w.byte_align();
+        w.into_vec()
+    }
+
+    #[test]
+    fn key_frames() {
+        let header = FrameHeader::parse(&key_frame(2, 5, true, true)).unwrap();
+        assert!(header.key_frame);
+        assert_eq!(header.size, Some((3840, 2160)));
+        assert_eq!(header.render_size, Some((1920, 1080)));
+        let color = header.color.unwrap();
+        assert_eq!(color.bit_depth, 10);
+        assert_eq!(color.color_space, ColorSpace::BT_2020);
+
+        let mut vpcc = Vpcc::with_frame_header(&header, 60.0).unwrap();
+        assert_eq!(vpcc.level, 51);
+        assert_eq!(vpcc.chroma_subsampling, Vpcc::CHROMA_420_COLOCATED);
+        vpcc.color.transfer = vui::ColorDescription::BT2100_PQ.transfer;
+        assert_eq!(vpcc.codec_string(), "vp09.02.51.10.01.09.16.09.01");
+        assert_eq!(Vpcc::parse(&vpcc.to_bytes()).unwrap(), vpcc);
+
+        let header = FrameHeader::parse(&key_frame(1, 2, false, false)).unwrap();
+        let color = header.color.unwrap();
+        assert_eq!(color.chroma_subsampling(), Vpcc::CHROMA_422);
+        assert_eq!(header.render_size, header.size);
+
+        let header = FrameHeader::parse(&key_frame(3, 7, true, false)).unwrap();
+        let color = header.color.unwrap();
+        assert_eq!(color.chroma_subsampling(), Vpcc::CHROMA_444);
+        assert_eq!(
+            color.color_space.color_description(),
+            vui::ColorDescription::SRGB_IDENTITY
+        );
+
+        assert_eq!(
+            FrameHeader::parse(&key_frame(0, 7, true, false)),
+            Err(Error::InvalidValue("color_space"))
+        );
+        let mut data = key_frame(0, 1, false, false);
+        data[1] ^= 0x01;
+        assert_eq!(
+            FrameHeader::parse(&data),
+            Err(Error::InvalidValue("frame_sync_code"))
+        );
+        assert_eq!(FrameHeader::parse(&data[..3]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn non_key_frames() {
+        let mut w = bits::Writer::new();
+        w.write_bits(0b1000, 4);
+        w.write_bool(false);
+        w.write_bool(true); // non-key
+        w.write_bool(false); // hidden
+        w.write_bool(false);
+        w.write_bool(true); // intra_only
+        w.write_bits(0, 2);
+        w.write_bits(0x49_83_42, 24);
+        w.write_bits(0xff, 8);
+        w.write_bits(639, 16);
+        w.write_bits(359, 16);
+        w.write_bool(false);
+        let header = FrameHeader::parse(&w.into_vec()).unwrap();
+        assert!(header.intra_only && !header.show_frame);
+        assert_eq!(header.size, Some((640, 360)));
+        assert_eq!(header.color.unwrap().bit_depth, 8);
+
+        let inter = [0x86, 0x00];
+        let header = FrameHeader::parse(&inter).unwrap();
+        assert!(!header.key_frame && !header.intra_only);
+        assert_eq!(header.size, None);
+        assert_eq!(
+            Vpcc::with_frame_header(&header, 30.0),
+            Err(Error::InvalidValue("frame_type"))
+        );
+
+        let header = FrameHeader::parse(&[0x8d]).unwrap();
+        assert_eq!(header.show_existing_frame, Some(5));
+
+        assert_eq!(
+            FrameHeader::parse(&[0x40]),
+            Err(Error::InvalidValue("frame_marker"))
+        );
+    }
+
+    #[test]
+    fn superframes() {
+        let mut data = vec![0xaa; 300];
+        data.extend_from_slice(&[0x86; 2]);
+        data.extend_from_slice(&[0xc9, 0x2c, 0x01, 0x02, 0x00, 0xc9]);
+        let res = frames(&data).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].len(), 300);
+        assert_eq!(res[1], [0x86, 0x86]);
+
+        let single = [0x82, 0x49, 0x83, 0x42, 0xc1];
+        assert_eq!(frames(&single).unwrap(), [&single[..]]);
+        assert!(frames(&[]).unwrap().is_empty());
+        assert_eq!(frames(&[0x86, 0xc0, 0x05, 0xc0]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn vpcc() {
+        assert_eq!(level(256, 144, 15.0), 10);
+        assert_eq!(level(352, 288, 15.0), 20);
+        assert_eq!(level(1280, 720, 30.0), 31);
+        assert_eq!(level(1920, 1080, 30.0), 40);
+        assert_eq!(level(1920, 1080, 60.0), 41);
+        assert_eq!(level(3840, 2160, 30.0), 50);
+        assert_eq!(level(8192, 4352, 120.0), 62);
+
+        let mut vpcc = Vpcc {
+            profile: 0,
+            level: 30,
+            bit_depth: 8,
+            chroma_subsampling: Vpcc::CHROMA_420_VERTICAL,
+            full_range: false,
+            color: vui::ColorDescription::BT709,
+            codec_init_data: Vec::new(),
+        };
+        let bytes = vpcc.to_bytes();
+        assert_eq!(bytes, [1, 0, 0, 0, 0, 30, 0x80, 1, 1, 1, 0, 0]);
+        assert_eq!(Vpcc::parse(&bytes).unwrap(), vpcc);
+        assert_eq!(Vpcc::parse(&bytes[..11]), Err(Error::Truncated));
+        let mut v0 = bytes.clone();
+        v0[0] = 0;
+        assert_eq!(Vpcc::parse(&v0), Err(Error::InvalidValue("vpcC version")));
+
+        vpcc.codec_init_data = vec![1, 2];
+        let bytes = vpcc.to_bytes();
+        assert_eq!(bytes[10..], [0, 2, 1, 2]);
+        assert_eq!(Vpcc::parse(&bytes[..13]), Err(Error::Truncated));
+    }
+}
@@ -0,0 +1,217 @@
+//! Video usability information shared by H.264 and HEVC parameter sets.
+
+use crate::media::bits;
+
+#[cfg(feature = "cv")]
+use crate::{cf, cv};
+
+/// `colour_primaries`, `transfer_characteristics` and `matrix_coeffs` code points (ITU-T H.273).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorDescription {
+    pub primaries: u8,
+    pub transfer: u8,
+    pub matrix: u8,
+}
+
+impl ColorDescription {
+    pub const PRIMARIES_BT709: u8 = 1;
+    pub const PRIMARIES_UNSPECIFIED: u8 = 2;
+    pub const PRIMARIES_BT601_625: u8 = 5;
+    pub const PRIMARIES_BT601_525: u8 = 6;
+    pub const PRIMARIES_BT2020: u8 = 9;
+    pub const PRIMARIES_P3_D65: u8 = 12;
+
+    pub const TRANSFER_BT709: u8 = 1;
+    pub const TRANSFER_UNSPECIFIED: u8 = 2;
+    pub const TRANSFER_SRGB: u8 = 13;
+    pub const TRANSFER_BT2020_10: u8 = 14;
+    pub const TRANSFER_PQ: u8 = 16;
+    pub const TRANSFER_HLG: u8 = 18;
+
+    pub const MATRIX_IDENTITY: u8 = 0;
+    pub const MATRIX_BT709: u8 = 1;
+    pub const MATRIX_UNSPECIFIED: u8 = 2;
+    pub const MATRIX_BT601: u8 = 6;
+    pub const MATRIX_BT2020_NCL: u8 = 9;
+
+    pub const BT709: Self = Self {
+        primaries: Self::PRIMARIES_BT709,
+        transfer: Self::TRANSFER_BT709,
+        matrix: Self::MATRIX_BT709,
+    };
+
+    pub const BT2100_PQ: Self = Self {
+        primaries: Self::PRIMARIES_BT2020,
+        transfer: Self::TRANSFER_PQ,
+        matrix: Self::MATRIX_BT2020_NCL,
+    };
+
+    pub const BT2100_HLG: Self = Self {
+        primaries: Self::PRIMARIES_BT2020,
+        transfer: Self::TRANSFER_HLG,
+        matrix: Self::MATRIX_BT2020_NCL,
+    };
+
+    /// sRGB with identity matrix, RGB coding of AV1 and VP9
+    pub const SRGB_IDENTITY: Self = Self {
+        primaries: Self::PRIMARIES_BT709,
+        transfer: Self::TRANSFER_SRGB,
+        matrix: Self::MATRIX_IDENTITY,
+    };
+
+    /// PQ or HLG transfer
+    #[inline]
+    pub fn is_hdr(&self) -> bool {
+        matches!(self.transfer, Self::TRANSFER_PQ | Self::TRANSFER_HLG)
+    }
+
+    pub(crate) fn read(r: &mut bits::Reader) -> Result<Self, bits::Error> {
+        Ok(Self {
+            primaries: r.read_u8(8)?,
+            transfer: r.read_u8(8)?,
+            matrix: r.read_u8(8)?,
+        })
+    }
+}
+
+#[cfg(feature = "cv")]
+impl ColorDescription {
+    /// `cv::image_buf_attachment::color_primaries` value, `None` if there is no matching one
+    pub fn cv_primaries(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::color_primaries as p;
+        Some(match self.primaries {
+            1 => p::itu_r_709_2(),
+            5 => p::ebu_3213(),
+            6 => p::smpte_c(),
+            9 => p::itu_r_2020(),
+            11 => p::dci_p3(),
+            12 => p::p3_d65(),
+            22 => p::p22(),
+            _ => return None,
+        })
+    }
+
+    /// `cv::image_buf_attachment::transfer_fn` value, `None` if there is no matching one
+    pub fn cv_transfer_fn(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::transfer_fn as t;
+        Some(match self.transfer {
+            1 | 6 => t::itu_r_709_2(),
+            7 => t::smpte_240m_1995(),
+            8 => t::linear(),
+            13 => t::srgb(),
+            14 | 15 => t::itu_r_2020(),
+            16 => t::smpte_st_2084_pq(),
+            17 => t::smpte_st_428_1(),
+            18 => t::itu_r_2100_hlg(),
+            _ => return None,
+        })
+    }
+
+    /// `cv::image_buf_attachment::ycbcr_matrix` value, `None` if there is no matching one
+    pub fn cv_ycbcr_matrix(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::ycbcr_matrix as m;
+        Some(match self.matrix {
+            1 => m::itu_r_709_2(),
+            5 | 6 => m::itu_r_601_4(),
+            7 => m::smpte_240m_1995(),
+            9 => m::itu_r_2020(),
+            _ => return None,
+        })
+    }
+}
+
+/// `video_signal_type` of VUI
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct VideoSignal {
+    /// 0 component, 1 PAL, 2 NTSC, 3 SECAM, 4 MAC, 5 unspecified
+    pub video_format: u8,
+    pub full_range: bool,
+    pub color: Option<ColorDescription>,
+}
+
+impl VideoSignal {
+    pub(crate) fn read(r: &mut bits::Reader) -> Result<Self, bits::Error> {
+        let video_format = r.read_u8(3)?;
+        let full_range = r.read_bool()?;
+        let color = if r.read_bool()? {
+            Some(ColorDescription::read(r)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            video_format,
+            full_range,
+            color,
+        })
+    }
+}
+
+impl Default for VideoSignal {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            video_format: 5,
+            full_range: false,
+            color: None,
+        }
+    }
+}
+
+/// `chroma_loc_info` of VUI
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ChromaLocation {
+    pub top_field: u32,
+    pub bottom_field: u32,
+}
+
+/// Sample aspect ratio of `aspect_ratio_idc`, `None` for reserved and `Extended_SAR` (255) values
+pub fn sample_aspect_ratio(aspect_ratio_idc: u8) -> Option<(u16, u16)> {
+    const TABLE: [(u16, u16); 17] = [
+        (0, 0),
+        (1, 1),
+        (12, 11),
+        (10, 11),
+        (16, 11),
+        (40, 33),
+        (24, 11),
+        (20, 11),
+        (32, 11),
+        (80, 33),
+        (18, 11),
+        (15, 11),
+        (64, 33),
+        (160, 99),
+        (4, 3),
+        (3, 2),
+        (2, 1),
+    ];
+    match aspect_ratio_idc {
+        1..=16 => Some(TABLE[aspect_ratio_idc as usize]),
+        _ => None,
+    }
+}
+
+/// `aspect_ratio_info` of VUI as sample aspect ratio
+pub(crate) fn read_aspect_ratio(r: &mut bits::Reader) -> Result<(u16, u16), bits::Error> {
+    let idc = r.read_u8(8)?;
+    if idc == 255 {
+        Ok((r.read_u16(16)?, r.read_u16(16)?))
+    } else {
+        Ok(sample_aspect_ratio(idc).unwrap_or((0, 0)))
+    }
+}
+
+/// Frames per second from timing info as reduced fraction
+pub(crate) fn frame_rate(time_scale: u32, num_units_in_tick: u64) -> Option<(u32, u32)> {
+    if time_scale == 0 || num_units_in_tick == 0 {
+        return None;
+    }
+    let (mut a, mut b) = (time_scale as u64, num_units_in_tick);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let num = time_scale as u64 / a;
+    let den = num_units_in_tick / a;
+    Some((u32::try_from(num).ok()?, u32::try_from(den).ok()?))
+}
@@ -1,10 +1,10 @@
-mod types;
-pub use types::Coordinate2d;
-pub use types::Origin;
-pub use types::Region;
-pub use types::ResId;
-pub use types::SamplePos;
-pub use types::Size;
+pub use crate::gfx::mtl::Coordinate2d;
+pub use crate::gfx::mtl::Origin;
+pub use crate::gfx::mtl::Region;
+pub use crate::gfx::mtl::RegionTiles;
+pub use crate::gfx::mtl::ResId;
+pub use crate::gfx::mtl::SamplePos;
+pub use crate::gfx::mtl::Size;
 
 mod stage_input_output_descriptor;
 pub use stage_input_output_descriptor::AttrFormat;
@@ -30,8 +30,9 @@ pub use acceleration_structure_types::PackedF32Quaternion;
 pub use acceleration_structure_types::PackedF32x3;
 pub use acceleration_structure_types::PackedF32x4x3;
 
-mod pixel_format;
-pub use pixel_format::PixelFormat;
+pub use crate::gfx::mtl::PixelFormat;
+pub use crate::gfx::mtl::PixelFormatComponentKind;
+pub use crate::gfx::mtl::PixelFormatViewClass;
 
 mod argument;
 pub use argument::Access as ArgAccess;
@@ -90,15 +91,18 @@ mod command_queue;
 pub use command_queue::CmdQueue;
 
 mod texture;
+pub use crate::gfx::mtl::TextureType;
 pub use texture::Compression as TextureCompression;
 pub use texture::Desc as TextureDesc;
 pub use texture::SharedTextureHandle;
 pub use texture::Swizzle as TextureSwizzle;
 pub use texture::SwizzleChannels as TextureSwizzleChannels;
 pub use texture::Texture;
-pub use texture::Type as TextureType;
 pub use texture::Usage as TextureUsage;
 
+pub use crate::gfx::mtl::TextureLayout;
+pub use crate::gfx::mtl::TextureLevelCopy;
+
 mod device;
 pub use device::ArgBufsTier;
 pub use device::Device;
@@ -3,42 +3,6 @@ use crate::{arc, cf, define_mtl, define_obj_type, define_opts, mtl, ns, objc};
 #[cfg(feature = "io")]
 use crate::io;
 
-/// Describes the dimensionality of each image, and if multiple images are arranged into an array or cube.
-#[doc(alias = "MTLTextureType")]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
-#[repr(usize)]
-pub enum Type {
-    #[doc(alias = "MTLTextureType1D")]
-    _1d = 0,
-
-    #[doc(alias = "MTLTextureType1DArray")]
-    _1dArray = 1,
-
-    #[doc(alias = "MTLTextureType2D")]
-    _2d = 2,
-
-    #[doc(alias = "MTLTextureType2DArray")]
-    _2dArray = 3,
-
-    #[doc(alias = "MTLTextureType2DMultisample")]
-    _2dMultisample = 4,
-
-    #[doc(alias = "MTLTextureTypeCube")]
-    Cube = 5,
-
-    #[doc(alias = "MTLTextureTypeCubeArray")]
-    CubeArray = 6,
-
-    #[doc(alias = "MTLTextureType3D")]
-    _3d = 7,
-
-    #[doc(alias = "MTLTextureType2DMultisampleArray")]
-    _2dMultisampleArray = 8,
-
-    #[doc(alias = "MTLTextureTypeTextureBuffer")]
-    TextureBuffer = 9,
-}
-
 #[doc(alias = "MTLTextureSwizzle")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 #[repr(u8)]
@@ -177,13 +141,13 @@ impl Desc {
     ) -> arc::R<Desc>;
 
     #[objc::msg_send(textureType)]
-    pub fn texture_type(&self) -> Type;
+    pub fn texture_type(&self) -> mtl::TextureType;
 
     #[objc::msg_send(setTextureType:)]
-    pub fn set_texture_type(&mut self, val: Type);
+    pub fn set_texture_type(&mut self, val: mtl::TextureType);
 
     #[inline]
-    pub fn with_texture_type(&mut self, val: Type) -> &mut Self {
+    pub fn with_texture_type(&mut self, val: mtl::TextureType) -> &mut Self {
         self.set_texture_type(val);
         self
     }
@@ -302,7 +266,7 @@ impl Texture {
     pub fn io_surf_plane(&self) -> usize;
 
     #[objc::msg_send(textureType)]
-    pub fn texture_type(&self) -> Type;
+    pub fn texture_type(&self) -> mtl::TextureType;
 
     #[objc::msg_send(pixelFormat)]
     pub fn pixel_format(&self) -> mtl::PixelFormat;
@@ -376,7 +340,7 @@ mod tests {
 
         assert!(t.parent_texture().is_none());
         assert!(t.io_surf().is_none());
-        assert_eq!(t.texture_type(), mtl::texture::Type::_2d);
+        assert_eq!(t.texture_type(), mtl::TextureType::_2d);
         assert_eq!(t.pixel_format(), mtl::PixelFormat::A8UNorm);
         assert!(t.io_surf().is_none());
         assert_eq!(t.io_surf_plane(), 0);
@@ -15,6 +15,9 @@ impl Color {
     #[objc::msg_send(colorWithHue:saturation:brightness:alpha:)]
     pub fn with_hsba(h: cg::Float, s: cg::Float, b: cg::Float, a: cg::Float) -> arc::R<Self>;
 
+    #[objc::msg_send(colorWithCGColor:)]
+    pub fn with_cg_color(cg_color: &cg::Color) -> Option<arc::R<Self>>;
+
     #[objc::msg_send(whiteComponent)]
     pub unsafe fn white_throws(&self) -> cg::Float;
 
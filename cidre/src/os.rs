@@ -1,4 +1,4 @@
-use std::{mem::MaybeUninit, num::NonZeroI32};
+use std::num::NonZeroI32;
 
 use crate::{four_cc_to_str, mac_types::FourCharCode};
 
@@ -82,6 +82,7 @@ impl std::error::Error for Error {}
 
 pub type Result<Ok = ()> = std::result::Result<Ok, Error>;
 
+#[cfg(any(feature = "at", feature = "cm", feature = "cv", feature = "sec"))]
 #[inline]
 pub(crate) unsafe fn result_unchecked<T, R>(op: impl FnOnce(&mut Option<T>) -> R) -> Result<T>
 where
@@ -92,12 +93,13 @@ where
     Ok(unsafe { option.unwrap_unchecked() })
 }
 
+#[cfg(any(feature = "cm", feature = "core_audio", feature = "sec"))]
 #[inline]
 pub(crate) fn result_init<T, R>(op: impl FnOnce(*mut T) -> R) -> Result<T>
 where
     R: Into<Result>,
 {
-    let mut val = MaybeUninit::<T>::uninit();
+    let mut val = std::mem::MaybeUninit::<T>::uninit();
     op(val.as_mut_ptr()).into()?;
     Ok(unsafe { val.assume_init() })
 }
@@ -0,0 +1,377 @@
+//! Pure-Rust property lists.
+//!
+//! [`Value`] mirrors CoreFoundation property list types and reads or writes
+//! `bplist00` binary and XML formats without CoreFoundation, e.g. on Linux build servers.
+//! Conversions to `cf::Plist` are available with `cf` feature.
+//!
+//! ```
+//! use cidre::plist;
+//!
+//! let text = format!(
+//!     "{}<dict>\n\
+//!     \t<key>CFBundleIdentifier</key>\n\
+//!     \t<string>com.example.app</string>\n\
+//!     \t<key>LSRequiresIPhoneOS</key>\n\
+//!     \t<true/>\n\
+//!     </dict>\n\
+//!     </plist>\n",
+//!     plist::xml::HEADER
+//! );
+//! let value = plist::Value::parse(text.as_bytes()).unwrap();
+//! assert_eq!(value.get("CFBundleIdentifier").and_then(|v| v.as_str()), Some("com.example.app"));
+//! assert_eq!(value.to_xml(), text);
+//!
+//! let binary = value.to_binary();
+//! assert!(binary.starts_with(b"bplist00"));
+//! assert_eq!(plist::Value::parse(&binary).unwrap(), value);
+//! ```
+
+pub mod binary;
+pub mod xml;
+
+#[cfg(feature = "cf")]
+mod ext;
+
+use std::collections::BTreeMap;
+
+/// Dictionary with keys in sorted order, as CoreFoundation writes XML
+pub type Dictionary = BTreeMap<String, Value>;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ends before object, offset table or trailer
+    Truncated,
+
+    /// Neither `bplist00` nor XML property list
+    InvalidHeader,
+
+    /// Malformed binary object at offset
+    InvalidObject(u64),
+
+    /// Container of binary property list contains itself
+    Cycle,
+
+    /// Shared objects of binary property list expand beyond decode limit
+    TooLarge,
+
+    /// Malformed XML, lines are counted from 1
+    InvalidXml(usize),
+
+    /// Malformed content of XML element
+    InvalidValue(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("truncated property list"),
+            Self::InvalidHeader => f.write_str("unknown property list format"),
+            Self::InvalidObject(offset) => write!(f, "invalid binary object at {offset}"),
+            Self::Cycle => f.write_str("property list contains itself"),
+            Self::TooLarge => f.write_str("property list expands too much"),
+            Self::InvalidXml(line) => write!(f, "invalid property list XML at line {line}"),
+            Self::InvalidValue(kind) => write!(f, "invalid property list {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Serialized property list format
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Format {
+    Binary,
+    Xml,
+}
+
+/// Seconds since 2001-01-01 00:00:00 UTC, `CFAbsoluteTime`
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Default)]
+pub struct Date(pub f64);
+
+impl Date {
+    /// Seconds between 1970 and 2001 reference dates
+    pub const UNIX_OFFSET: f64 = 978_307_200.0;
+
+    #[inline]
+    pub fn with_unix(secs: f64) -> Self {
+        Self(secs - Self::UNIX_OFFSET)
+    }
+
+    #[inline]
+    pub fn unix(&self) -> f64 {
+        self.0 + Self::UNIX_OFFSET
+    }
+
+    /// Proleptic Gregorian date and time of whole seconds in UTC
+    pub fn to_utc(&self) -> (i64, u8, u8, u8, u8, u8) {
+        let secs = self.unix().floor() as i64;
+        let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+        // days to civil date, March based years
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+        let year = yoe + era * 400 + (month <= 2) as i64;
+        let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+        (year, month, day, h as u8, m as u8, s as u8)
+    }
+
+    pub fn with_utc(year: i64, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        let year = year - (month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146_097 + doe - 719_468;
+        let secs = days * 86400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+        Self::with_unix(secs as f64)
+    }
+}
+
+/// `CF$UID` reference of keyed archive
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Uid(pub u64);
+
+/// Property list object.
+///
+/// Integers cover both `i64` and `u64` ranges like `CFNumber`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Array(Vec<Value>),
+    Dictionary(Dictionary),
+    Boolean(bool),
+    Data(Vec<u8>),
+    Date(Date),
+    Real(f64),
+    Integer(i128),
+    String(String),
+    Uid(Uid),
+}
+
+impl Value {
+    /// Reads binary or XML property list
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        Self::parse_with_format(data).map(|(value, _)| value)
+    }
+
+    pub fn parse_with_format(data: &[u8]) -> Result<(Self, Format), Error> {
+        if data.starts_with(binary::MAGIC) {
+            return binary::parse(data).map(|v| (v, Format::Binary));
+        }
+        let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+        let text = std::str::from_utf8(data).map_err(|_| Error::InvalidHeader)?;
+        if !text.trim_start().starts_with('<') {
+            return Err(Error::InvalidHeader);
+        }
+        xml::parse(text).map(|v| (v, Format::Xml))
+    }
+
+    #[inline]
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        binary::write(self, &mut out);
+        out
+    }
+
+    /// XML document as CoreFoundation writes it
+    #[inline]
+    pub fn to_xml(&self) -> String {
+        let mut out = String::new();
+        xml::write(self, &mut out);
+        out
+    }
+
+    #[inline]
+    pub fn to_bytes(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Binary => self.to_binary(),
+            Format::Xml => self.to_xml().into_bytes(),
+        }
+    }
+
+    /// Value of dictionary key
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dictionary()?.get(key)
+    }
+
+    #[inline]
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_data(&self) -> Option<&[u8]> {
+        match self {
+            Self::Data(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_date(&self) -> Option<Date> {
+        match self {
+            Self::Date(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Real or integer as `f64`
+    #[inline]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Real(v) => Some(*v),
+            Self::Integer(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Integer(v) => i64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Integer(v) => u64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_uid(&self) -> Option<Uid> {
+        match self {
+            Self::Uid(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<i32> for Value {
+    #[inline]
+    fn from(value: i32) -> Self {
+        Self::Integer(value as i128)
+    }
+}
+
+impl From<i64> for Value {
+    #[inline]
+    fn from(value: i64) -> Self {
+        Self::Integer(value as i128)
+    }
+}
+
+impl From<u64> for Value {
+    #[inline]
+    fn from(value: u64) -> Self {
+        Self::Integer(value as i128)
+    }
+}
+
+impl From<f64> for Value {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    #[inline]
+    fn from(value: Vec<Value>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl From<Dictionary> for Value {
+    #[inline]
+    fn from(value: Dictionary) -> Self {
+        Self::Dictionary(value)
+    }
+}
+
+impl From<Date> for Value {
+    #[inline]
+    fn from(value: Date) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<Uid> for Value {
+    #[inline]
+    fn from(value: Uid) -> Self {
+        Self::Uid(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Date;
+
+    #[test]
+    fn dates() {
+        assert_eq!(Date(0.0).to_utc(), (2001, 1, 1, 0, 0, 0));
+        assert_eq!(Date::with_unix(0.0).to_utc(), (1970, 1, 1, 0, 0, 0));
+        // fractions are truncated towards the past
+        assert_eq!(Date(-0.5).to_utc(), (2000, 12, 31, 23, 59, 59));
+        let leap = Date::with_utc(2024, 2, 29, 12, 30, 15);
+        assert_eq!(leap.to_utc(), (2024, 2, 29, 12, 30, 15));
+        assert_eq!(Date::with_utc(2001, 1, 1, 0, 0, 0), Date(0.0));
+    }
+}
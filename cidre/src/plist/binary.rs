@@ -0,0 +1,522 @@
+//! `bplist00` binary property lists.
+//!
+//! Objects are followed by offset table and 32 byte trailer. Containers refer to
+//! objects by index of offset table, equal scalars are written once.
+
+use std::collections::HashMap;
+
+use super::{Date, Dictionary, Error, Uid, Value};
+
+pub const MAGIC: &[u8; 8] = b"bplist00";
+
+/// Nesting deeper than this is rejected
+const MAX_DEPTH: usize = 512;
+
+/// Decoded objects and payload bytes allowed per input byte.
+///
+/// Shared objects are decoded again for each reference, this bounds their expansion.
+const MAX_EXPANSION: u64 = 64;
+
+/// Sizes and positions of trailer
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Trailer {
+    pub offset_size: u8,
+    pub ref_size: u8,
+    pub num_objects: u64,
+    pub top_object: u64,
+    pub offset_table_offset: u64,
+}
+
+impl Trailer {
+    pub const LEN: usize = 32;
+
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < MAGIC.len() + Self::LEN {
+            return Err(Error::Truncated);
+        }
+        let t = &data[data.len() - Self::LEN..];
+        let u64_at = |i: usize| u64::from_be_bytes(t[i..i + 8].try_into().unwrap());
+        let res = Self {
+            offset_size: t[6],
+            ref_size: t[7],
+            num_objects: u64_at(8),
+            top_object: u64_at(16),
+            offset_table_offset: u64_at(24),
+        };
+        let valid_size = |s: u8| matches!(s, 1 | 2 | 4 | 8);
+        let table_end = res
+            .num_objects
+            .checked_mul(res.offset_size as u64)
+            .and_then(|len| len.checked_add(res.offset_table_offset));
+        if !valid_size(res.offset_size)
+            || !valid_size(res.ref_size)
+            || res.top_object >= res.num_objects
+            || res.offset_table_offset < MAGIC.len() as u64
+            || table_end.map_or(true, |end| end > (data.len() - Self::LEN) as u64)
+        {
+            return Err(Error::InvalidHeader);
+        }
+        Ok(res)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 6]);
+        out.push(self.offset_size);
+        out.push(self.ref_size);
+        out.extend_from_slice(&self.num_objects.to_be_bytes());
+        out.extend_from_slice(&self.top_object.to_be_bytes());
+        out.extend_from_slice(&self.offset_table_offset.to_be_bytes());
+    }
+}
+
+/// Big endian unsigned integer of 1 to 8 bytes
+#[inline]
+fn read_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    trailer: Trailer,
+    stack: Vec<u64>,
+
+    /// Remaining decoded objects and payload bytes
+    budget: u64,
+}
+
+impl<'a> Reader<'a> {
+    fn offset(&self, object: u64) -> Result<usize, Error> {
+        if object >= self.trailer.num_objects {
+            return Err(Error::InvalidObject(object));
+        }
+        let size = self.trailer.offset_size as usize;
+        let pos = self.trailer.offset_table_offset as usize + object as usize * size;
+        let offset = read_uint(&self.data[pos..pos + size]);
+        if offset < MAGIC.len() as u64 || offset >= self.trailer.offset_table_offset {
+            return Err(Error::InvalidObject(offset));
+        }
+        Ok(offset as usize)
+    }
+
+    fn bytes(&self, pos: usize, len: u64) -> Result<&'a [u8], Error> {
+        let end = (pos as u64)
+            .checked_add(len)
+            .filter(|&end| end <= self.trailer.offset_table_offset)
+            .ok_or(Error::Truncated)?;
+        Ok(&self.data[pos..end as usize])
+    }
+
+    /// Count of marker low nibble or following integer object, and payload position
+    fn count(&self, pos: usize) -> Result<(u64, usize), Error> {
+        let nibble = self.data[pos] & 0x0f;
+        if nibble != 0x0f {
+            return Ok((nibble as u64, pos + 1));
+        }
+        let marker = *self.bytes(pos + 1, 1)?.first().unwrap();
+        if marker & 0xf0 != 0x10 || marker & 0x0f > 3 {
+            return Err(Error::InvalidObject(pos as u64));
+        }
+        let len = 1 << (marker & 0x0f);
+        let count = read_uint(self.bytes(pos + 2, len)?);
+        Ok((count, pos + 2 + len as usize))
+    }
+
+    fn refs(&self, pos: usize, count: u64) -> Result<Vec<u64>, Error> {
+        let size = self.trailer.ref_size as u64;
+        let len = count.checked_mul(size).ok_or(Error::Truncated)?;
+        let bytes = self.bytes(pos, len)?;
+        Ok(bytes.chunks(size as usize).map(read_uint).collect())
+    }
+
+    fn charge(&mut self, cost: u64) -> Result<(), Error> {
+        self.budget = self.budget.checked_sub(cost).ok_or(Error::TooLarge)?;
+        Ok(())
+    }
+
+    fn object(&mut self, object: u64) -> Result<Value, Error> {
+        self.charge(1)?;
+        if self.stack.contains(&object) {
+            return Err(Error::Cycle);
+        }
+        if self.stack.len() >= MAX_DEPTH {
+            return Err(Error::InvalidObject(object));
+        }
+        let pos = self.offset(object)?;
+        let marker = self.data[pos];
+        let invalid = Error::InvalidObject(pos as u64);
+        let value = match marker >> 4 {
+            0x0 => match marker {
+                0x08 => Value::Boolean(false),
+                0x09 => Value::Boolean(true),
+                _ => return Err(invalid),
+            },
+            0x1 => {
+                let bytes = self.bytes(pos + 1, 1 << (marker & 0x0f))?;
+                Value::Integer(match bytes.len() {
+                    1 | 2 | 4 => read_uint(bytes) as i128,
+                    8 => read_uint(bytes) as i64 as i128,
+                    16 => i128::from_be_bytes(bytes.try_into().unwrap()),
+                    _ => return Err(invalid),
+                })
+            }
+            0x2 => {
+                let bytes = self.bytes(pos + 1, 1 << (marker & 0x0f))?;
+                Value::Real(match bytes.len() {
+                    4 => f32::from_be_bytes(bytes.try_into().unwrap()) as f64,
+                    8 => f64::from_be_bytes(bytes.try_into().unwrap()),
+                    _ => return Err(invalid),
+                })
+            }
+            0x3 if marker == 0x33 => {
+                let bytes = self.bytes(pos + 1, 8)?;
+                Value::Date(Date(f64::from_be_bytes(bytes.try_into().unwrap())))
+            }
+            0x4 => {
+                let (len, pos) = self.count(pos)?;
+                let bytes = self.bytes(pos, len)?;
+                self.charge(len)?;
+                Value::Data(bytes.to_vec())
+            }
+            0x5 => {
+                let (len, pos) = self.count(pos)?;
+                let bytes = self.bytes(pos, len)?;
+                self.charge(len)?;
+                // ASCII, other bytes are taken as Latin-1
+                Value::String(bytes.iter().map(|&b| b as char).collect())
+            }
+            0x6 => {
+                let (len, pos) = self.count(pos)?;
+                let bytes = self.bytes(pos, len.checked_mul(2).ok_or(invalid.clone())?)?;
+                self.charge(bytes.len() as u64)?;
+                let units: Vec<u16> = bytes
+                    .chunks(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                Value::String(String::from_utf16(&units).map_err(|_| invalid)?)
+            }
+            0x8 => {
+                let bytes = self.bytes(pos + 1, (marker & 0x0f) as u64 + 1)?;
+                if bytes.len() > 8 {
+                    return Err(invalid);
+                }
+                Value::Uid(Uid(read_uint(bytes)))
+            }
+            0xa => {
+                let (count, pos) = self.count(pos)?;
+                let refs = self.refs(pos, count)?;
+                self.stack.push(object);
+                let items = refs
+                    .into_iter()
+                    .map(|r| self.object(r))
+                    .collect::<Result<_, _>>()?;
+                self.stack.pop();
+                Value::Array(items)
+            }
+            0xd => {
+                let (count, pos) = self.count(pos)?;
+                let refs = self.refs(pos, count.checked_mul(2).ok_or(invalid.clone())?)?;
+                let (keys, values) = refs.split_at(count as usize);
+                self.stack.push(object);
+                let mut dict = Dictionary::new();
+                for (&k, &v) in keys.iter().zip(values) {
+                    let Value::String(key) = self.object(k)? else {
+                        return Err(invalid);
+                    };
+                    dict.insert(key, self.object(v)?);
+                }
+                self.stack.pop();
+                Value::Dictionary(dict)
+            }
+            _ => return Err(invalid),
+        };
+        Ok(value)
+    }
+}
+
+/// Reads `bplist00` property list
+pub fn parse(data: &[u8]) -> Result<Value, Error> {
+    if !data.starts_with(MAGIC) {
+        return Err(Error::InvalidHeader);
+    }
+    let trailer = Trailer::parse(data)?;
+    let mut reader = Reader {
+        data,
+        trailer,
+        stack: Vec::new(),
+        budget: (data.len() as u64).saturating_mul(MAX_EXPANSION),
+    };
+    reader.object(trailer.top_object)
+}
+
+/// Scalars written once per property list
+#[derive(Eq, PartialEq, Hash)]
+enum Scalar<'a> {
+    Boolean(bool),
+    Data(&'a [u8]),
+    Date(u64),
+    Real(u64),
+    Integer(i128),
+    String(&'a str),
+    Uid(u64),
+}
+
+enum Object<'a> {
+    Scalar(&'a Value),
+    Key(&'a str),
+    Array(Vec<u64>),
+    Dictionary(Vec<u64>),
+}
+
+#[derive(Default)]
+struct Writer<'a> {
+    objects: Vec<Object<'a>>,
+    scalars: HashMap<Scalar<'a>, u64>,
+}
+
+impl<'a> Writer<'a> {
+    /// Assigns object numbers of value and its children in depth-first order
+    fn flatten(&mut self, value: &'a Value) -> u64 {
+        let scalar = match value {
+            Value::Array(items) => {
+                let n = self.objects.len();
+                self.objects.push(Object::Array(Vec::new()));
+                let refs = items.iter().map(|v| self.flatten(v)).collect();
+                self.objects[n] = Object::Array(refs);
+                return n as u64;
+            }
+            Value::Dictionary(dict) => {
+                let n = self.objects.len();
+                self.objects.push(Object::Dictionary(Vec::new()));
+                let mut refs = Vec::with_capacity(dict.len() * 2);
+                for key in dict.keys() {
+                    refs.push(self.flatten_scalar(Scalar::String(key), Object::Key(key)));
+                }
+                for v in dict.values() {
+                    refs.push(self.flatten(v));
+                }
+                self.objects[n] = Object::Dictionary(refs);
+                return n as u64;
+            }
+            Value::Boolean(v) => Scalar::Boolean(*v),
+            Value::Data(v) => Scalar::Data(v),
+            Value::Date(v) => Scalar::Date(v.0.to_bits()),
+            Value::Real(v) => Scalar::Real(v.to_bits()),
+            Value::Integer(v) => Scalar::Integer(*v),
+            Value::String(v) => Scalar::String(v),
+            Value::Uid(v) => Scalar::Uid(v.0),
+        };
+        self.flatten_scalar(scalar, Object::Scalar(value))
+    }
+
+    fn flatten_scalar(&mut self, scalar: Scalar<'a>, object: Object<'a>) -> u64 {
+        let next = self.objects.len() as u64;
+        let n = *self.scalars.entry(scalar).or_insert(next);
+        if n == next {
+            self.objects.push(object);
+        }
+        n
+    }
+}
+
+/// Minimal size of 1, 2, 4 or 8 bytes
+#[inline]
+fn uint_size(value: u64) -> u8 {
+    match value {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xffff_ffff => 4,
+        _ => 8,
+    }
+}
+
+#[inline]
+fn write_uint(out: &mut Vec<u8>, value: u64, size: u8) {
+    out.extend_from_slice(&value.to_be_bytes()[8 - size as usize..]);
+}
+
+fn write_int(out: &mut Vec<u8>, value: i128) {
+    if value < 0 || value > i64::MAX as i128 {
+        if value >= i64::MIN as i128 && value < 0 {
+            out.push(0x13);
+            out.extend_from_slice(&(value as i64).to_be_bytes());
+        } else {
+            out.push(0x14);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+        return;
+    }
+    let size = uint_size(value as u64);
+    out.push(0x10 | size.trailing_zeros() as u8);
+    write_uint(out, value as u64, size);
+}
+
+fn write_marker(out: &mut Vec<u8>, kind: u8, count: usize) {
+    if count < 15 {
+        out.push(kind | count as u8);
+    } else {
+        out.push(kind | 0x0f);
+        write_int(out, count as i128);
+    }
+}
+
+/// Appends `bplist00` property list
+pub fn write(value: &Value, out: &mut Vec<u8>) {
+    let mut writer = Writer::default();
+    writer.flatten(value);
+    let objects = writer.objects;
+
+    let start = out.len();
+    let ref_size = uint_size(objects.len() as u64);
+    let mut offsets = Vec::with_capacity(objects.len());
+    out.extend_from_slice(MAGIC);
+    for object in &objects {
+        offsets.push((out.len() - start) as u64);
+        let value = match object {
+            Object::Scalar(value) => *value,
+            Object::Key(key) => {
+                write_string(out, key);
+                continue;
+            }
+            Object::Array(refs) => {
+                write_marker(out, 0xa0, refs.len());
+                refs.iter().for_each(|&r| write_uint(out, r, ref_size));
+                continue;
+            }
+            Object::Dictionary(refs) => {
+                write_marker(out, 0xd0, refs.len() / 2);
+                refs.iter().for_each(|&r| write_uint(out, r, ref_size));
+                continue;
+            }
+        };
+        match value {
+            Value::Boolean(v) => out.push(0x08 | *v as u8),
+            Value::Data(v) => {
+                write_marker(out, 0x40, v.len());
+                out.extend_from_slice(v);
+            }
+            Value::Date(v) => {
+                out.push(0x33);
+                out.extend_from_slice(&v.0.to_be_bytes());
+            }
+            Value::Real(v) => {
+                out.push(0x23);
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            Value::Integer(v) => write_int(out, *v),
+            Value::String(v) => write_string(out, v),
+            Value::Uid(v) => {
+                let size = uint_size(v.0);
+                out.push(0x80 | (size - 1));
+                write_uint(out, v.0, size);
+            }
+            Value::Array(_) | Value::Dictionary(_) => unreachable!(),
+        }
+    }
+    let offset_table_offset = (out.len() - start) as u64;
+    let offset_size = uint_size(offset_table_offset);
+    for offset in offsets {
+        write_uint(out, offset, offset_size);
+    }
+    Trailer {
+        offset_size,
+        ref_size,
+        num_objects: objects.len() as u64,
+        top_object: 0,
+        offset_table_offset,
+    }
+    .write(out);
+}
+
+/// ASCII or UTF-16 string
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    if s.is_ascii() {
+        write_marker(out, 0x50, s.len());
+        out.extend_from_slice(s.as_bytes());
+    } else {
+        let units: Vec<u16> = s.encode_utf16().collect();
+        write_marker(out, 0x60, units.len());
+        units
+            .iter()
+            .for_each(|u| out.extend_from_slice(&u.to_be_bytes()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write, Trailer, MAGIC};
+    use crate::plist::{Date, Dictionary, Error, Uid, Value};
+
+    #[test]
+    fn round_trip() {
+        let mut dict = Dictionary::new();
+        dict.insert("int".into(), Value::Integer(-1));
+        dict.insert("big".into(), Value::Integer(u64::MAX as i128));
+        dict.insert("small".into(), 300.into());
+        dict.insert("real".into(), 0.5.into());
+        dict.insert("date".into(), Date(1.0).into());
+        dict.insert("data".into(), Value::Data(vec![1; 20]));
+        dict.insert("utf16".into(), "héllo".into());
+        dict.insert("uid".into(), Uid(7).into());
+        // repeated scalars are written once
+        dict.insert(
+            "array".into(),
+            vec!["int".into(), "int".into(), true.into()].into(),
+        );
+        let value = Value::Dictionary(dict);
+
+        let mut out = Vec::new();
+        write(&value, &mut out);
+        assert_eq!(parse(&out).unwrap(), value);
+        let trailer = Trailer::parse(&out).unwrap();
+        assert_eq!(trailer.num_objects, 20);
+        assert_eq!(trailer.ref_size, 1);
+    }
+
+    #[test]
+    fn cf_encoding() {
+        // array of "a" and 1 with 1 byte offsets and refs
+        let data = b"bplist00\xa2\x01\x02\x51a\x10\x01\x08\x0b\x0d\
+            \x00\x00\x00\x00\x00\x00\x01\x01\
+            \x00\x00\x00\x00\x00\x00\x00\x03\
+            \x00\x00\x00\x00\x00\x00\x00\x00\
+            \x00\x00\x00\x00\x00\x00\x00\x0f";
+        let value = parse(data).unwrap();
+        assert_eq!(value, Value::Array(vec!["a".into(), 1.into()]));
+        let mut out = Vec::new();
+        write(&value, &mut out);
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn errors() {
+        // array containing itself
+        let data = b"bplist00\xa1\x00\x08\
+            \x00\x00\x00\x00\x00\x00\x01\x01\
+            \x00\x00\x00\x00\x00\x00\x00\x01\
+            \x00\x00\x00\x00\x00\x00\x00\x00\
+            \x00\x00\x00\x00\x00\x00\x00\x0a";
+        assert_eq!(parse(data), Err(Error::Cycle));
+        assert_eq!(parse(&data[..20]), Err(Error::Truncated));
+        assert_eq!(parse(b"bplist0"), Err(Error::InvalidHeader));
+
+        // each array refers to the next one twice, 2^40 objects when expanded
+        let mut data = MAGIC.to_vec();
+        let mut offsets = Vec::new();
+        for i in 1..=40u8 {
+            offsets.push(data.len() as u8);
+            data.extend_from_slice(&[0xa2, i, i]);
+        }
+        offsets.push(data.len() as u8);
+        data.push(0xa0);
+        let trailer = Trailer {
+            offset_size: 1,
+            ref_size: 1,
+            num_objects: offsets.len() as u64,
+            top_object: 0,
+            offset_table_offset: data.len() as u64,
+        };
+        data.extend_from_slice(&offsets);
+        trailer.write(&mut data);
+        assert_eq!(parse(&data), Err(Error::TooLarge));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{arc, cf};
+
+use super::{Date, Dictionary, Uid, Value};
+
+impl Value {
+    /// Property list of CoreFoundation types.
+    ///
+    /// [`Uid`] becomes `CF$UID` dictionary, integers beyond `i64` become reals.
+    pub fn to_cf(&self) -> arc::R<cf::Plist> {
+        match self {
+            Self::Array(items) => {
+                let mut array = cf::ArrayOfMut::<cf::Plist>::with_capacity(items.len());
+                for item in items {
+                    array.push(&item.to_cf());
+                }
+                let array: &cf::ArrayOf<cf::Plist> = &array;
+                let plist: &cf::Plist = array.into();
+                plist.retained()
+            }
+            Self::Dictionary(dict) => {
+                let mut res = cf::DictionaryMut::with_capacity(dict.len());
+                for (key, value) in dict {
+                    res.insert(&cf::String::from_str(key), &value.to_cf());
+                }
+                // keys are strings and values are property lists
+                unsafe { std::mem::transmute::<arc::R<cf::DictionaryMut>, arc::R<cf::Plist>>(res) }
+            }
+            Self::Boolean(value) => {
+                let value = if *value {
+                    cf::Boolean::value_true()
+                } else {
+                    cf::Boolean::value_false()
+                };
+                let plist: &cf::Plist = value.into();
+                plist.retained()
+            }
+            Self::Data(data) => cf::Data::from_slice(data).unwrap().into(),
+            Self::Date(date) => cf::Date::new_at(date.0).into(),
+            Self::Real(value) => cf::Number::from_f64(*value).into(),
+            Self::Integer(value) => match i64::try_from(*value) {
+                Ok(value) => cf::Number::from_i64(value).into(),
+                Err(_) => cf::Number::from_f64(*value as f64).into(),
+            },
+            Self::String(s) => cf::String::from_str(s).into(),
+            Self::Uid(uid) => {
+                let mut dict = Dictionary::new();
+                dict.insert("CF$UID".into(), Value::Integer(uid.0 as i128));
+                Self::Dictionary(dict).to_cf()
+            }
+        }
+    }
+
+    /// Value of CoreFoundation property list, `None` if it contains other types
+    pub fn with_cf(plist: &cf::Plist) -> Option<Self> {
+        if let Some(s) = plist.try_as_string() {
+            return Some(Self::String(s.to_string()));
+        }
+        if let Some(b) = plist.try_as_boolean() {
+            return Some(Self::Boolean(b.value()));
+        }
+        if let Some(n) = plist.try_as_number() {
+            if n.is_float_type() {
+                return n.to_f64().map(Self::Real);
+            }
+            return match n.to_i64() {
+                Some(value) => Some(Self::Integer(value as i128)),
+                None => n.to_f64().map(Self::Real),
+            };
+        }
+        if let Some(data) = plist.try_as_data() {
+            return Some(Self::Data(data.as_slice().to_vec()));
+        }
+        if let Some(date) = plist.try_as_date() {
+            return Some(Self::Date(Date(date.abs_time())));
+        }
+        if let Some(array) = plist.try_as_array() {
+            let items = array.iter().map(Self::with_cf).collect::<Option<_>>()?;
+            return Some(Self::Array(items));
+        }
+        let dict = plist.try_as_raw_dictionary()?;
+        let (keys, values) = dict.keys_with_values();
+        let mut res = Dictionary::new();
+        for (key, value) in keys.into_iter().zip(values) {
+            // property list types are `cf::Type`s too
+            let key = unsafe { std::mem::transmute::<&cf::Type, &cf::Plist>(key) };
+            let value = unsafe { std::mem::transmute::<&cf::Type, &cf::Plist>(value) };
+            res.insert(key.try_as_string()?.to_string(), Self::with_cf(value)?);
+        }
+        match res.get("CF$UID") {
+            Some(&Self::Integer(n)) if res.len() == 1 && n >= 0 => Some(Self::Uid(Uid(n as u64))),
+            _ => Some(Self::Dictionary(res)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cf, plist};
+
+    #[test]
+    fn cf_round_trip() {
+        let mut dict = plist::Dictionary::new();
+        dict.insert("name".into(), "cidre".into());
+        dict.insert("count".into(), 3.into());
+        dict.insert("ratio".into(), 0.5.into());
+        dict.insert("flags".into(), vec![true.into(), false.into()].into());
+        dict.insert("blob".into(), plist::Value::Data(vec![1, 2, 3]));
+        dict.insert("date".into(), plist::Date(10.0).into());
+        let value = plist::Value::Dictionary(dict);
+
+        let cf_plist = value.to_cf();
+        assert!(cf_plist.is_valid_for_format(cf::PlistFormat::BinaryV1_0));
+        assert_eq!(plist::Value::with_cf(&cf_plist), Some(value.clone()));
+
+        // CoreFoundation reads what we write
+        let data = cf::Data::from_slice(&value.to_binary()).unwrap();
+        let parsed = cf::Plist::from_data(&data, Default::default()).unwrap();
+        assert!(parsed.equal(&cf_plist));
+
+        let xml = cf_plist.to_cf_data(cf::PlistFormat::XmlV1_0).unwrap();
+        assert_eq!(xml.as_slice(), value.to_xml().as_bytes());
+
+        let uid = plist::Value::Uid(plist::Uid(2));
+        assert_eq!(plist::Value::with_cf(&uid.to_cf()), Some(uid));
+    }
+}
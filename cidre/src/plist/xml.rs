@@ -0,0 +1,556 @@
+//! XML property lists as `CFPropertyListCreateData` writes them.
+//!
+//! Dictionary keys are sorted, nested elements are indented with tabs, data is
+//! base64 in lines of 76 characters including indentation, reals have 17 significant
+//! digits and dates are whole seconds in UTC.
+
+use super::{Date, Dictionary, Error, Uid, Value};
+
+pub const HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+    <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+    \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+    <plist version=\"1.0\">\n";
+
+/// Nesting deeper than this is rejected
+const MAX_DEPTH: usize = 512;
+
+/// Key of single key dictionary standing for [`Uid`]
+const UID_KEY: &str = "CF$UID";
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Tag<'a> {
+    Start(&'a str),
+    End(&'a str),
+    Empty(&'a str),
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> Error {
+        let line = self.text[..self.pos.min(self.text.len())]
+            .bytes()
+            .filter(|&b| b == b'\n')
+            .count();
+        Error::InvalidXml(line + 1)
+    }
+
+    #[inline]
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Skips `end` terminated construct
+    fn skip_past(&mut self, end: &str) -> Result<(), Error> {
+        let len = self.rest().find(end).ok_or_else(|| self.error())?;
+        self.pos += len + end.len();
+        Ok(())
+    }
+
+    /// Skips whitespace, declarations, processing instructions and comments
+    fn skip_misc(&mut self) -> Result<(), Error> {
+        loop {
+            self.pos += self.rest().len() - self.rest().trim_start().len();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!") && !rest.starts_with("<![CDATA[") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn tag(&mut self) -> Result<Tag<'a>, Error> {
+        self.skip_misc()?;
+        let rest = self.rest();
+        if !rest.starts_with('<') {
+            return Err(self.error());
+        }
+        let len = rest.find('>').ok_or_else(|| self.error())?;
+        let inner = &rest[1..len];
+        let name = |s: &'a str| s.split_ascii_whitespace().next().unwrap_or("");
+        let tag = if let Some(s) = inner.strip_prefix('/') {
+            Tag::End(s.trim())
+        } else if let Some(s) = inner.strip_suffix('/') {
+            Tag::Empty(name(s))
+        } else {
+            Tag::Start(name(inner))
+        };
+        self.pos += len + 1;
+        Ok(tag)
+    }
+
+    fn expect_end(&mut self, name: &str) -> Result<(), Error> {
+        match self.tag()? {
+            Tag::End(n) if n == name => Ok(()),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Character data up to end tag of `name`
+    fn text(&mut self, name: &str) -> Result<String, Error> {
+        let mut res = String::new();
+        loop {
+            let rest = self.rest();
+            let len = rest.find(['<', '&']).ok_or_else(|| self.error())?;
+            res.push_str(&rest[..len]);
+            self.pos += len;
+            let rest = self.rest();
+            if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let len = cdata.find("]]>").ok_or_else(|| self.error())?;
+                res.push_str(&cdata[..len]);
+                self.pos += "<![CDATA[".len() + len + "]]>".len();
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with('&') {
+                let len = rest.find(';').ok_or_else(|| self.error())?;
+                let c = entity(&rest[1..len]).ok_or_else(|| self.error())?;
+                res.push(c);
+                self.pos += len + 1;
+            } else {
+                self.expect_end(name)?;
+                return Ok(res);
+            }
+        }
+    }
+
+    fn value(&mut self, tag: Tag<'a>, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        let value = match tag {
+            Tag::Start("dict") => {
+                let mut dict = Dictionary::new();
+                loop {
+                    let key = match self.tag()? {
+                        Tag::End("dict") => break,
+                        Tag::Start("key") => self.text("key")?,
+                        Tag::Empty("key") => String::new(),
+                        _ => return Err(self.error()),
+                    };
+                    let tag = self.tag()?;
+                    dict.insert(key, self.value(tag, depth + 1)?);
+                }
+                match dict.get(UID_KEY) {
+                    Some(&Value::Integer(n)) if dict.len() == 1 && u64::try_from(n).is_ok() => {
+                        Value::Uid(Uid(n as u64))
+                    }
+                    _ => Value::Dictionary(dict),
+                }
+            }
+            Tag::Empty("dict") => Value::Dictionary(Dictionary::new()),
+            Tag::Start("array") => {
+                let mut items = Vec::new();
+                loop {
+                    match self.tag()? {
+                        Tag::End("array") => break,
+                        tag => items.push(self.value(tag, depth + 1)?),
+                    }
+                }
+                Value::Array(items)
+            }
+            Tag::Empty("array") => Value::Array(Vec::new()),
+            Tag::Start("string") => Value::String(self.text("string")?),
+            Tag::Empty("string") => Value::String(String::new()),
+            Tag::Start("integer") => {
+                let text = self.text("integer")?;
+                Value::Integer(parse_integer(text.trim()).ok_or(Error::InvalidValue("integer"))?)
+            }
+            Tag::Start("real") => {
+                let text = self.text("real")?;
+                Value::Real(parse_real(text.trim()).ok_or(Error::InvalidValue("real"))?)
+            }
+            Tag::Start("date") => {
+                let text = self.text("date")?;
+                Value::Date(parse_date(text.trim()).ok_or(Error::InvalidValue("date"))?)
+            }
+            Tag::Start("data") => {
+                let text = self.text("data")?;
+                Value::Data(decode_base64(&text).ok_or(Error::InvalidValue("data"))?)
+            }
+            Tag::Empty("data") => Value::Data(Vec::new()),
+            Tag::Empty("true") => Value::Boolean(true),
+            Tag::Empty("false") => Value::Boolean(false),
+            Tag::Start(name @ ("true" | "false")) => {
+                self.expect_end(name)?;
+                Value::Boolean(name == "true")
+            }
+            _ => return Err(self.error()),
+        };
+        Ok(value)
+    }
+}
+
+fn entity(name: &str) -> Option<char> {
+    Some(match name {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    })
+}
+
+/// Decimal or `0x` prefixed integer of `i64` or `u64` range
+fn parse_integer(s: &str) -> Option<i128> {
+    let (negative, digits) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let value = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()? as i128,
+        None => digits.parse::<u64>().ok()? as i128,
+    };
+    if negative {
+        (value <= i64::MAX as i128 + 1).then_some(-value)
+    } else {
+        Some(value)
+    }
+}
+
+fn parse_real(s: &str) -> Option<f64> {
+    match s.to_ascii_lowercase().as_str() {
+        "nan" => Some(f64::NAN),
+        "+infinity" | "infinity" | "+inf" | "inf" => Some(f64::INFINITY),
+        "-infinity" | "-inf" => Some(f64::NEG_INFINITY),
+        s => s.parse().ok(),
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ`
+fn parse_date(s: &str) -> Option<Date> {
+    let b = s.as_bytes();
+    if b.len() != 20 || b[4] != b'-' || b[7] != b'-' || b[10] != b'T' || b[19] != b'Z' {
+        return None;
+    }
+    if b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<u32> {
+        let s = &s[range];
+        s.bytes()
+            .all(|c| c.is_ascii_digit())
+            .then(|| s.parse().ok())?
+    };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+    if second > 60 {
+        return None;
+    }
+    Some(Date::with_utc(
+        year as i64,
+        month as u8,
+        day as u8,
+        hour as u8,
+        minute as u8,
+        second as u8,
+    ))
+}
+
+/// Base64 ignoring whitespace
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(s.len() * 3 / 4);
+    let (mut acc, mut bits, mut padding) = (0u32, 0, 0);
+    for c in s.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+        if padding > 0 {
+            return None;
+        }
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    Some(res)
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+/// `%.17g` of C, as `CFNumber` formats reals
+fn format_real(value: f64, out: &mut String) {
+    const PRECISION: i32 = 17;
+
+    if value.is_nan() {
+        out.push_str("nan");
+        return;
+    }
+    if value.is_infinite() {
+        out.push_str(if value > 0.0 {
+            "+infinity"
+        } else {
+            "-infinity"
+        });
+        return;
+    }
+    if value == 0.0 {
+        out.push_str(if value.is_sign_negative() { "-0" } else { "0" });
+        return;
+    }
+    let sci = format!("{:.*e}", PRECISION as usize - 1, value);
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    let trim = |s: &str| -> String {
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').into()
+        } else {
+            s.into()
+        }
+    };
+    if !(-4..PRECISION).contains(&exp) {
+        let sign = if exp < 0 { '-' } else { '+' };
+        out.push_str(&format!("{}e{sign}{:02}", trim(mantissa), exp.abs()));
+    } else {
+        let decimals = (PRECISION - 1 - exp) as usize;
+        out.push_str(&trim(&format!("{value:.decimals$}")));
+    }
+}
+
+fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[inline]
+fn indent(depth: usize, out: &mut String) {
+    out.extend(std::iter::repeat('\t').take(depth));
+}
+
+fn write_value(value: &Value, depth: usize, out: &mut String) {
+    indent(depth, out);
+    match value {
+        Value::Array(items) if items.is_empty() => out.push_str("<array/>\n"),
+        Value::Array(items) => {
+            out.push_str("<array>\n");
+            for item in items {
+                write_value(item, depth + 1, out);
+            }
+            indent(depth, out);
+            out.push_str("</array>\n");
+        }
+        Value::Dictionary(dict) if dict.is_empty() => out.push_str("<dict/>\n"),
+        Value::Dictionary(dict) => {
+            out.push_str("<dict>\n");
+            for (key, value) in dict {
+                indent(depth + 1, out);
+                out.push_str("<key>");
+                escape(key, out);
+                out.push_str("</key>\n");
+                write_value(value, depth + 1, out);
+            }
+            indent(depth, out);
+            out.push_str("</dict>\n");
+        }
+        Value::Boolean(true) => out.push_str("<true/>\n"),
+        Value::Boolean(false) => out.push_str("<false/>\n"),
+        Value::Data(data) => {
+            out.push_str("<data>\n");
+            // indentation counts against line length, up to 8 tabs
+            let tabs = depth.min(8);
+            let encoded = encode_base64(data);
+            for line in encoded.as_bytes().chunks(76 - 8 * tabs) {
+                indent(tabs, out);
+                out.push_str(std::str::from_utf8(line).unwrap());
+                out.push('\n');
+            }
+            indent(depth, out);
+            out.push_str("</data>\n");
+        }
+        Value::Date(date) => {
+            let (y, m, d, h, min, s) = date.to_utc();
+            out.push_str(&format!(
+                "<date>{y:04}-{m:02}-{d:02}T{h:02}:{min:02}:{s:02}Z</date>\n"
+            ));
+        }
+        Value::Real(value) => {
+            out.push_str("<real>");
+            format_real(*value, out);
+            out.push_str("</real>\n");
+        }
+        Value::Integer(value) => out.push_str(&format!("<integer>{value}</integer>\n")),
+        Value::String(s) => {
+            out.push_str("<string>");
+            escape(s, out);
+            out.push_str("</string>\n");
+        }
+        Value::Uid(uid) => {
+            out.push_str("<dict>\n");
+            indent(depth + 1, out);
+            out.push_str(&format!("<key>{UID_KEY}</key>\n"));
+            indent(depth + 1, out);
+            out.push_str(&format!("<integer>{}</integer>\n", uid.0));
+            indent(depth, out);
+            out.push_str("</dict>\n");
+        }
+    }
+}
+
+/// Reads XML property list, a bare value without `plist` element is accepted too
+pub fn parse(text: &str) -> Result<Value, Error> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = match parser.tag()? {
+        Tag::Start("plist") => {
+            let tag = parser.tag()?;
+            let value = parser.value(tag, 0)?;
+            parser.expect_end("plist")?;
+            value
+        }
+        tag => parser.value(tag, 0)?,
+    };
+    parser.skip_misc()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.error());
+    }
+    Ok(value)
+}
+
+/// Appends XML document
+pub fn write(value: &Value, out: &mut String) {
+    out.push_str(HEADER);
+    write_value(value, 0, out);
+    out.push_str("</plist>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_real, parse, write};
+    use crate::plist::{Date, Dictionary, Error, Uid, Value};
+
+    fn real(value: f64) -> String {
+        let mut out = String::new();
+        format_real(value, &mut out);
+        out
+    }
+
+    #[test]
+    fn reals() {
+        assert_eq!(real(0.1), "0.10000000000000001");
+        assert_eq!(real(1.5), "1.5");
+        assert_eq!(real(100.0), "100");
+        assert_eq!(real(-2.0), "-2");
+        assert_eq!(real(1e20), "1e+20");
+        assert_eq!(real(1.25e-7), "1.2499999999999999e-07");
+        assert_eq!(real(0.0001), "0.0001");
+        assert_eq!(real(f64::NEG_INFINITY), "-infinity");
+    }
+
+    #[test]
+    fn cf_layout() {
+        let mut dict = Dictionary::new();
+        dict.insert("data".into(), Value::Data((0..60).collect()));
+        dict.insert("date".into(), Date(0.0).into());
+        dict.insert("empty".into(), Value::Array(Vec::new()));
+        dict.insert("a<b".into(), Value::Array(vec![Uid(3).into()]));
+        let value = Value::Dictionary(dict);
+
+        let mut text = String::new();
+        write(&value, &mut text);
+        let body = text.split_once("<plist version=\"1.0\">\n").unwrap().1;
+        assert_eq!(
+            body,
+            "<dict>\n\
+            \t<key>a&lt;b</key>\n\
+            \t<array>\n\
+            \t\t<dict>\n\
+            \t\t\t<key>CF$UID</key>\n\
+            \t\t\t<integer>3</integer>\n\
+            \t\t</dict>\n\
+            \t</array>\n\
+            \t<key>data</key>\n\
+            \t<data>\n\
+            \tAAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEy\n\
+            \tMzQ1Njc4OTo7\n\
+            \t</data>\n\
+            \t<key>date</key>\n\
+            \t<date>2001-01-01T00:00:00Z</date>\n\
+            \t<key>empty</key>\n\
+            \t<array/>\n\
+            </dict>\n\
+            </plist>\n"
+        );
+        assert_eq!(parse(&text).unwrap(), value);
+    }
+
+    #[test]
+    fn lenient_input() {
+        let text = "<plist><!-- comment --><array>\n\
+            <string>a &amp; b &#x41;<![CDATA[<c>]]></string>\n\
+            <integer> 0x10 </integer><integer>-9223372036854775808</integer>\n\
+            <real>nan</real><true></true><data/>\n\
+            </array></plist>";
+        let Value::Array(items) = parse(text).unwrap() else {
+            panic!("array expected");
+        };
+        assert_eq!(items[0], Value::String("a & b A<c>".into()));
+        assert_eq!(items[1], Value::Integer(16));
+        assert_eq!(items[2].as_i64(), Some(i64::MIN));
+        assert!(items[3].as_f64().unwrap().is_nan());
+        assert_eq!(items[4], Value::Boolean(true));
+        assert_eq!(items[5], Value::Data(Vec::new()));
+
+        assert_eq!(
+            parse("<plist>\n<dict>\n<string/>"),
+            Err(Error::InvalidXml(3))
+        );
+        assert_eq!(
+            parse("<date>2001-13-01T00:00:00Z</date>"),
+            Err(Error::InvalidValue("date"))
+        );
+    }
+}
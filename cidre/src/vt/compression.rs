@@ -7,6 +7,11 @@ pub use properties::hdr_metadata_insertion_mode;
 pub use properties::keys;
 pub use properties::profile_level;
 
+#[cfg(feature = "media")]
+pub mod config;
+#[cfg(feature = "media")]
+pub use config::EncoderConfig;
+
 #[cfg(test)]
 mod tests {
     use std::ffi::c_void;
@@ -51,10 +56,7 @@ mod tests {
         let mut props = cf::DictionaryMut::with_capacity(10);
         props.insert(keys::real_time(), &bool_true);
         props.insert(keys::allow_frame_reordering(), &bool_false);
-        props.insert(
-            keys::profile_lvl(),
-            profile_level::h264::main_auto_lvl(),
-        );
+        props.insert(keys::profile_lvl(), profile_level::h264::main_auto_lvl());
         props.insert(keys::allow_open_gop(), &bool_false);
         props.insert(keys::h264_entropy_mode(), h264_entropy_mode::cabac());
         props.insert(keys::expected_frame_rate(), &expected_fr);
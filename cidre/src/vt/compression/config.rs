@@ -0,0 +1,802 @@
+//! Typed configuration of compression session.
+//!
+//! [`EncoderConfig`] checks cross-property constraints, which video encoder otherwise
+//! ignores or rejects with a bare status, and converts to property dictionary.
+//!
+//! ```no_run
+//! use cidre::{media::vui, vt::compression::{config, EncoderConfig}};
+//!
+//! let mut config = EncoderConfig::new(config::Codec::H264, 1920, 1080);
+//! config.profile = Some(config::Profile::H264Baseline);
+//! config.level = Some(31);
+//! config.expected_frame_rate = Some(30.0);
+//! // 1080p doesn't fit level 3.1
+//! assert!(matches!(config.validate(), Err(config::Error::LevelExceeded { .. })));
+//!
+//! config.level = Some(40);
+//! config.allow_frame_reordering = Some(true);
+//! // Baseline profile has no B frames
+//! assert!(matches!(config.validate(), Err(config::Error::ProfileDisallows { .. })));
+//!
+//! config.allow_frame_reordering = Some(false);
+//! config.color = Some(vui::ColorDescription::BT709);
+//! let props = config.to_props().unwrap();
+//! ```
+
+use crate::{
+    arc, cf, cm,
+    media::{h264, hdr, vui},
+    os,
+    vt::compression::properties::{
+        h264_entropy_mode, hdr_metadata_insertion_mode, keys, profile_level,
+    },
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Codec {
+    H264,
+    Hevc,
+    HevcWithAlpha,
+}
+
+impl Codec {
+    #[inline]
+    pub fn video_codec(&self) -> cm::VideoCodec {
+        match self {
+            Self::H264 => cm::VideoCodec::H264,
+            Self::Hevc => cm::VideoCodec::HEVC,
+            Self::HevcWithAlpha => cm::VideoCodec::HEVC_WITH_ALPHA,
+        }
+    }
+
+    #[inline]
+    pub fn is_hevc(&self) -> bool {
+        matches!(self, Self::Hevc | Self::HevcWithAlpha)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Profile {
+    H264Baseline,
+    H264ConstrainedBaseline,
+    H264Main,
+    H264Extended,
+    H264High,
+    H264ConstrainedHigh,
+    HevcMain,
+    HevcMain10,
+    HevcMain42210,
+}
+
+impl Profile {
+    #[inline]
+    pub fn is_h264(&self) -> bool {
+        !self.is_hevc()
+    }
+
+    #[inline]
+    pub fn is_hevc(&self) -> bool {
+        matches!(
+            self,
+            Self::HevcMain | Self::HevcMain10 | Self::HevcMain42210
+        )
+    }
+
+    /// `profile_idc` of H.264 profile
+    pub fn h264_profile_idc(&self) -> Option<u8> {
+        Some(match self {
+            Self::H264Baseline | Self::H264ConstrainedBaseline => h264::Sps::PROFILE_BASELINE,
+            Self::H264Main => h264::Sps::PROFILE_MAIN,
+            Self::H264Extended => h264::Sps::PROFILE_EXTENDED,
+            Self::H264High | Self::H264ConstrainedHigh => h264::Sps::PROFILE_HIGH,
+            _ => return None,
+        })
+    }
+
+    /// B frames, which need frame reordering
+    #[inline]
+    pub fn allows_b_frames(&self) -> bool {
+        !matches!(
+            self,
+            Self::H264Baseline | Self::H264ConstrainedBaseline | Self::H264ConstrainedHigh
+        )
+    }
+
+    #[inline]
+    pub fn allows_cabac(&self) -> bool {
+        matches!(
+            self,
+            Self::H264Main | Self::H264High | Self::H264ConstrainedHigh
+        )
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u8 {
+        match self {
+            Self::HevcMain10 | Self::HevcMain42210 => 10,
+            _ => 8,
+        }
+    }
+
+    /// `level_idc` values with profile level constant, others are automatic only
+    pub fn levels(&self) -> &'static [u8] {
+        match self {
+            Self::H264Baseline => &[13, 30, 31, 32, 40, 41, 42, 50, 51, 52],
+            Self::H264Main | Self::H264High => &[30, 31, 32, 40, 41, 42, 50, 51, 52],
+            Self::H264Extended => &[50],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntropyMode {
+    Cavlc,
+    Cabac,
+}
+
+/// Bytes allowed within duration, e.g. to cap peak bit rate of VBV
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DataRateLimit {
+    pub bytes: u64,
+    pub seconds: f64,
+}
+
+impl DataRateLimit {
+    #[inline]
+    pub fn bit_rate(&self) -> f64 {
+        self.bytes as f64 * 8.0 / self.seconds
+    }
+}
+
+/// Temporal layering with base layer decodable alone
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Layering {
+    /// Fraction of frames in base layer
+    pub base_layer_frame_rate_fraction: f64,
+
+    /// Fraction of bit rate for base layer, encoder default if `None`
+    pub base_layer_bit_rate_fraction: Option<f64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HdrMetadataInsertion {
+    None,
+
+    /// Encoder inserts SEI of attachments and properties
+    Auto,
+}
+
+/// Compression session properties, `None` leaves encoder default.
+///
+/// Bit rates are in bits/s, durations in seconds.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncoderConfig {
+    pub codec: Codec,
+    pub width: u32,
+    pub height: u32,
+
+    /// Automatic level of profile if `level` is `None`
+    pub profile: Option<Profile>,
+
+    /// `level_idc`, e.g. 41 for level 4.1
+    pub level: Option<u8>,
+    pub entropy_mode: Option<EntropyMode>,
+
+    pub max_key_frame_interval: Option<u32>,
+    pub max_key_frame_interval_duration: Option<f64>,
+    pub allow_open_gop: Option<bool>,
+
+    pub average_bit_rate: Option<u64>,
+
+    /// Not compatible with average bit rate and data rate limits
+    pub constant_bit_rate: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub data_rate_limits: Vec<DataRateLimit>,
+
+    /// 0.0 for the smallest size through 1.0 for the best quality
+    pub quality: Option<f32>,
+    pub real_time: Option<bool>,
+    pub prioritize_encoding_speed_over_quality: Option<bool>,
+    pub maximize_power_efficiency: Option<bool>,
+    pub expected_frame_rate: Option<f64>,
+    pub allow_frame_reordering: Option<bool>,
+
+    /// Frames the encoder may hold before emitting output
+    pub max_frame_delay_count: Option<u32>,
+
+    pub color: Option<vui::ColorDescription>,
+    pub mastering_display: Option<hdr::MasteringDisplay>,
+    pub content_light_level: Option<hdr::ContentLightLevel>,
+    pub hdr_metadata_insertion: Option<HdrMetadataInsertion>,
+    pub layering: Option<Layering>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Property is out of range
+    InvalidValue(&'static str),
+
+    /// Profile of another codec
+    ProfileMismatch { codec: Codec, profile: Profile },
+
+    /// Level is not supported by video encoder for profile
+    UnsupportedLevel { profile: Profile, level: u8 },
+
+    /// Coding tool is not available in profile
+    ProfileDisallows {
+        profile: Profile,
+        feature: &'static str,
+    },
+
+    /// Properties can't be used together
+    Conflicting(&'static str, &'static str),
+
+    /// Stream exceeds limit of H.264 level
+    LevelExceeded {
+        level: u8,
+        limit: &'static str,
+        max: u64,
+        actual: u64,
+    },
+
+    /// HDR transfer or metadata needs HEVC with 10-bit profile
+    Hdr(&'static str),
+
+    /// Video encoder rejected properties
+    Session(os::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidValue(name) => write!(f, "invalid encoder {name}"),
+            Self::ProfileMismatch { codec, profile } => {
+                write!(f, "profile {profile:?} is not of codec {codec:?}")
+            }
+            Self::UnsupportedLevel { profile, level } => write!(
+                f,
+                "level {}.{} is not supported for profile {profile:?}",
+                level / 10,
+                level % 10
+            ),
+            Self::ProfileDisallows { profile, feature } => {
+                write!(f, "profile {profile:?} doesn't allow {feature}")
+            }
+            Self::Conflicting(a, b) => write!(f, "{a} conflicts with {b}"),
+            Self::LevelExceeded {
+                level,
+                limit,
+                max,
+                actual,
+            } => write!(
+                f,
+                "{limit} of {actual} exceeds {max} of level {}.{}",
+                level / 10,
+                level % 10
+            ),
+            Self::Hdr(reason) => write!(f, "HDR {reason}"),
+            Self::Session(err) => write!(f, "compression session error {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<os::Error> for Error {
+    #[inline]
+    fn from(value: os::Error) -> Self {
+        Self::Session(value)
+    }
+}
+
+impl EncoderConfig {
+    pub fn new(codec: Codec, width: u32, height: u32) -> Self {
+        Self {
+            codec,
+            width,
+            height,
+            profile: None,
+            level: None,
+            entropy_mode: None,
+            max_key_frame_interval: None,
+            max_key_frame_interval_duration: None,
+            allow_open_gop: None,
+            average_bit_rate: None,
+            constant_bit_rate: None,
+            data_rate_limits: Vec::new(),
+            quality: None,
+            real_time: None,
+            prioritize_encoding_speed_over_quality: None,
+            maximize_power_efficiency: None,
+            expected_frame_rate: None,
+            allow_frame_reordering: None,
+            max_frame_delay_count: None,
+            color: None,
+            mastering_display: None,
+            content_light_level: None,
+            hdr_metadata_insertion: None,
+            layering: None,
+        }
+    }
+
+    /// Frame reordering is allowed by default in profiles with B frames
+    #[inline]
+    pub fn has_frame_reordering(&self) -> bool {
+        self.allow_frame_reordering.unwrap_or(true)
+            && self.profile.map_or(true, |p| p.allows_b_frames())
+            && self.max_frame_delay_count != Some(0)
+    }
+
+    /// The highest of bit rates and data rate limits
+    pub fn peak_bit_rate(&self) -> Option<u64> {
+        let limits = self.data_rate_limits.iter().map(|l| l.bit_rate() as u64);
+        self.average_bit_rate
+            .into_iter()
+            .chain(self.constant_bit_rate)
+            .chain(limits)
+            .max()
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::InvalidValue("dimensions"));
+        }
+        if let Some(profile) = self.profile {
+            if profile.is_hevc() != self.codec.is_hevc() {
+                return Err(Error::ProfileMismatch {
+                    codec: self.codec,
+                    profile,
+                });
+            }
+            if self.allow_frame_reordering == Some(true) && !profile.allows_b_frames() {
+                return Err(Error::ProfileDisallows {
+                    profile,
+                    feature: "frame reordering",
+                });
+            }
+            if self.entropy_mode == Some(EntropyMode::Cabac) && !profile.allows_cabac() {
+                return Err(Error::ProfileDisallows {
+                    profile,
+                    feature: "CABAC",
+                });
+            }
+        }
+        if self.entropy_mode.is_some() && self.codec.is_hevc() {
+            return Err(Error::Conflicting("entropy mode", "HEVC"));
+        }
+        if let Some(level) = self.level {
+            let profile = self
+                .profile
+                .ok_or(Error::Conflicting("level", "automatic profile"))?;
+            if !profile.levels().contains(&level) {
+                return Err(Error::UnsupportedLevel { profile, level });
+            }
+            self.validate_level(profile, level)?;
+        }
+
+        if self
+            .max_key_frame_interval_duration
+            .is_some_and(|d| !(d.is_finite() && d >= 0.0))
+        {
+            return Err(Error::InvalidValue("key frame interval duration"));
+        }
+        if self.quality.is_some_and(|q| !(0.0..=1.0).contains(&q)) {
+            return Err(Error::InvalidValue("quality"));
+        }
+        if self
+            .expected_frame_rate
+            .is_some_and(|r| !(r.is_finite() && r > 0.0))
+        {
+            return Err(Error::InvalidValue("expected frame rate"));
+        }
+        if self
+            .data_rate_limits
+            .iter()
+            .any(|l| l.bytes == 0 || !(l.seconds.is_finite() && l.seconds > 0.0))
+        {
+            return Err(Error::InvalidValue("data rate limit"));
+        }
+        if self.constant_bit_rate.is_some() {
+            if self.average_bit_rate.is_some() {
+                return Err(Error::Conflicting("constant bit rate", "average bit rate"));
+            }
+            if !self.data_rate_limits.is_empty() {
+                return Err(Error::Conflicting("constant bit rate", "data rate limits"));
+            }
+        }
+        if let Some(average) = self.average_bit_rate {
+            if self
+                .data_rate_limits
+                .iter()
+                .any(|l| l.bit_rate() < average as f64)
+            {
+                return Err(Error::Conflicting("average bit rate", "data rate limits"));
+            }
+        }
+        if self.allow_frame_reordering == Some(true) && self.max_frame_delay_count == Some(0) {
+            return Err(Error::Conflicting(
+                "frame reordering",
+                "max frame delay count",
+            ));
+        }
+        if let Some(layering) = self.layering {
+            let in_range = |f: f64| f > 0.0 && f < 1.0;
+            if !in_range(layering.base_layer_frame_rate_fraction)
+                || !layering.base_layer_bit_rate_fraction.map_or(true, in_range)
+            {
+                return Err(Error::InvalidValue("base layer fraction"));
+            }
+        }
+        self.validate_hdr()
+    }
+
+    /// Frame size, macroblock rate, bit rate and DPB size of H.264 level
+    fn validate_level(&self, profile: Profile, level: u8) -> Result<(), Error> {
+        let (Some(profile_idc), Some(limits)) = (
+            profile.h264_profile_idc(),
+            h264::LevelLimits::with_level_idc(level),
+        ) else {
+            return Ok(());
+        };
+        let exceeded = |limit, max: u64, actual: u64| {
+            if actual > max {
+                Err(Error::LevelExceeded {
+                    level,
+                    limit,
+                    max,
+                    actual,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        let frame_size = self.width.div_ceil(16) as u64 * self.height.div_ceil(16) as u64;
+        exceeded(
+            "frame size in macroblocks",
+            limits.max_fs as u64,
+            frame_size,
+        )?;
+        if let Some(rate) = self.expected_frame_rate {
+            exceeded(
+                "macroblocks per second",
+                limits.max_mbps as u64,
+                (frame_size as f64 * rate).ceil() as u64,
+            )?;
+        }
+        if let Some(bit_rate) = self.peak_bit_rate() {
+            exceeded("bit rate", limits.max_bit_rate(profile_idc), bit_rate)?;
+        }
+        // B frame refers to a frame before and after it
+        if self.has_frame_reordering() {
+            exceeded(
+                "reference frames",
+                // within u32 after the frame size check
+                limits.max_dpb_frames(frame_size as u32) as u64,
+                2,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn validate_hdr(&self) -> Result<(), Error> {
+        let has_metadata = self.mastering_display.is_some()
+            || self.content_light_level.is_some()
+            || self.hdr_metadata_insertion == Some(HdrMetadataInsertion::Auto);
+        let is_hdr = self.color.is_some_and(|c| c.is_hdr());
+        if (has_metadata || is_hdr) && !self.codec.is_hevc() {
+            return Err(Error::Hdr("needs HEVC"));
+        }
+        if is_hdr && self.profile.is_some_and(|p| p.bit_depth() < 10) {
+            return Err(Error::Hdr("transfer needs 10-bit profile"));
+        }
+        Ok(())
+    }
+
+    fn profile_level(&self) -> Option<&'static cf::String> {
+        use profile_level::{h264 as avc, hevc};
+
+        Some(match (self.profile?, self.level) {
+            (Profile::HevcMain, _) => hevc::main_auto_lvl(),
+            (Profile::HevcMain10, _) => hevc::main10_auto_lvl(),
+            (Profile::HevcMain42210, _) => hevc::main42210_auto_lvl(),
+            (Profile::H264ConstrainedBaseline, _) => avc::constrained_baseline_auto_lvl(),
+            (Profile::H264ConstrainedHigh, _) => avc::constrained_high_auto_lvl(),
+            (Profile::H264Extended, Some(50)) => avc::extended_5_0(),
+            (Profile::H264Extended, _) => avc::extended_auto_lvl(),
+            (Profile::H264Baseline, level) => match level {
+                Some(13) => avc::baseline_1_3(),
+                Some(30) => avc::baseline_3_0(),
+                Some(31) => avc::baseline_3_1(),
+                Some(32) => avc::baseline_3_2(),
+                Some(40) => avc::baseline_4_0(),
+                Some(41) => avc::baseline_4_1(),
+                Some(42) => avc::baseline_4_2(),
+                Some(50) => avc::baseline_5_0(),
+                Some(51) => avc::baseline_5_1(),
+                Some(52) => avc::baseline_5_2(),
+                _ => avc::baseline_auto_lvl(),
+            },
+            (Profile::H264Main, level) => match level {
+                Some(30) => avc::main_3_0(),
+                Some(31) => avc::main_3_1(),
+                Some(32) => avc::main_3_2(),
+                Some(40) => avc::main_4_0(),
+                Some(41) => avc::main_4_1(),
+                Some(42) => avc::main_4_2(),
+                Some(50) => avc::main_5_0(),
+                Some(51) => avc::main_5_1(),
+                Some(52) => avc::main_5_2(),
+                _ => avc::main_auto_lvl(),
+            },
+            (Profile::H264High, level) => match level {
+                Some(30) => avc::high_3_0(),
+                Some(31) => avc::high_3_1(),
+                Some(32) => avc::high_3_2(),
+                Some(40) => avc::high_4_0(),
+                Some(41) => avc::high_4_1(),
+                Some(42) => avc::high_4_2(),
+                Some(50) => avc::high_5_0(),
+                Some(51) => avc::high_5_1(),
+                Some(52) => avc::high_5_2(),
+                _ => avc::high_auto_lvl(),
+            },
+        })
+    }
+
+    /// Validated properties for `vt::CompressionSession::set_props`
+    pub fn to_props(&self) -> Result<arc::R<cf::DictionaryMut>, Error> {
+        self.validate()?;
+
+        let bool = |value: bool| {
+            if value {
+                cf::Boolean::value_true()
+            } else {
+                cf::Boolean::value_false()
+            }
+        };
+        let mut props = cf::DictionaryMut::with_capacity(24);
+        if let Some(profile_level) = self.profile_level() {
+            props.insert(keys::profile_lvl(), profile_level);
+        }
+        if let Some(mode) = self.entropy_mode {
+            let mode = match mode {
+                EntropyMode::Cavlc => h264_entropy_mode::cavlc(),
+                EntropyMode::Cabac => h264_entropy_mode::cabac(),
+            };
+            props.insert(keys::h264_entropy_mode(), mode);
+        }
+        if let Some(interval) = self.max_key_frame_interval {
+            props.insert(
+                keys::max_key_frame_interval(),
+                &cf::Number::from_i64(interval as i64),
+            );
+        }
+        if let Some(duration) = self.max_key_frame_interval_duration {
+            props.insert(
+                keys::max_key_frame_interval_duration(),
+                &cf::Number::from_f64(duration),
+            );
+        }
+        if let Some(value) = self.allow_open_gop {
+            props.insert(keys::allow_open_gop(), bool(value));
+        }
+        if let Some(bit_rate) = self.average_bit_rate {
+            props.insert(
+                keys::avarage_bit_rate(),
+                &cf::Number::from_i64(bit_rate as i64),
+            );
+        }
+        if let Some(bit_rate) = self.constant_bit_rate {
+            props.insert(
+                keys::constant_bit_rate(),
+                &cf::Number::from_i64(bit_rate as i64),
+            );
+        }
+        if !self.data_rate_limits.is_empty() {
+            // pairs of bytes and seconds
+            let mut values = Vec::with_capacity(self.data_rate_limits.len() * 2);
+            for limit in &self.data_rate_limits {
+                values.push(cf::Number::from_i64(limit.bytes as i64));
+                values.push(cf::Number::from_f64(limit.seconds));
+            }
+            let refs: Vec<&cf::Number> = values.iter().map(|n| n.as_ref()).collect();
+            props.insert(
+                keys::data_rate_limits(),
+                &cf::ArrayOf::<cf::Number>::from_slice(&refs),
+            );
+        }
+        if let Some(quality) = self.quality {
+            props.insert(keys::quality(), &cf::Number::from_f64(quality as f64));
+        }
+        if let Some(value) = self.real_time {
+            props.insert(keys::real_time(), bool(value));
+        }
+        if let Some(value) = self.prioritize_encoding_speed_over_quality {
+            props.insert(keys::prioritize_encoding_speed_over_quality(), bool(value));
+        }
+        if let Some(value) = self.maximize_power_efficiency {
+            props.insert(keys::maximize_power_efficiecy(), bool(value));
+        }
+        if let Some(rate) = self.expected_frame_rate {
+            props.insert(keys::expected_frame_rate(), &cf::Number::from_f64(rate));
+        }
+        if let Some(value) = self.allow_frame_reordering {
+            props.insert(keys::allow_frame_reordering(), bool(value));
+        }
+        if let Some(count) = self.max_frame_delay_count {
+            props.insert(
+                keys::max_frame_delay_count(),
+                &cf::Number::from_i64(count as i64),
+            );
+        }
+        if let Some(color) = &self.color {
+            if let Some(primaries) = color.cv_primaries() {
+                props.insert(keys::color_primaries(), primaries);
+            }
+            if let Some(transfer) = color.cv_transfer_fn() {
+                props.insert(keys::transfer_fn(), transfer);
+            }
+            if let Some(matrix) = color.cv_ycbcr_matrix() {
+                props.insert(keys::ycbcr_matrix(), matrix);
+            }
+        }
+        if let Some(data) = self.mastering_display.and_then(|m| m.to_cf_data()) {
+            props.insert(keys::master_display_color_volume(), &data);
+        }
+        if let Some(data) = self.content_light_level.and_then(|c| c.to_cf_data()) {
+            props.insert(keys::content_light_lvl_info(), &data);
+        }
+        if let Some(mode) = self.hdr_metadata_insertion {
+            let mode = match mode {
+                HdrMetadataInsertion::None => hdr_metadata_insertion_mode::none(),
+                HdrMetadataInsertion::Auto => hdr_metadata_insertion_mode::auto(),
+            };
+            props.insert(keys::hdr_metadata_insertion_mode(), mode);
+        }
+        if let Some(layering) = self.layering {
+            props.insert(
+                keys::base_layer_frame_rate_fraction(),
+                &cf::Number::from_f64(layering.base_layer_frame_rate_fraction),
+            );
+            if let Some(fraction) = layering.base_layer_bit_rate_fraction {
+                props.insert(
+                    keys::base_layer_bit_rate_fraction(),
+                    &cf::Number::from_f64(fraction),
+                );
+            }
+        }
+        Ok(props)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{hdr, vui};
+
+    use super::{Codec, DataRateLimit, EncoderConfig, EntropyMode, Error, Profile};
+
+    #[test]
+    fn h264_level_limits() {
+        let mut config = EncoderConfig::new(Codec::H264, 1920, 1080);
+        config.profile = Some(Profile::H264High);
+        config.level = Some(40);
+        config.expected_frame_rate = Some(60.0);
+        // 8160 macroblocks at 60 fps exceed 245760 of level 4.0
+        assert!(matches!(
+            config.validate(),
+            Err(Error::LevelExceeded {
+                limit: "macroblocks per second",
+                ..
+            })
+        ));
+
+        config.level = Some(42);
+        config.average_bit_rate = Some(20_000_000);
+        config.data_rate_limits = vec![DataRateLimit {
+            bytes: 8_000_000,
+            seconds: 1.0,
+        }];
+        // 64 Mbit/s peak exceeds 62.5 Mbit/s of High profile at level 4.2
+        assert!(matches!(
+            config.validate(),
+            Err(Error::LevelExceeded {
+                limit: "bit rate",
+                max: 62_500_000,
+                ..
+            })
+        ));
+
+        config.data_rate_limits[0].bytes = 5_000_000;
+        assert_eq!(config.validate(), Ok(()));
+
+        config.level = Some(33);
+        assert!(matches!(
+            config.validate(),
+            Err(Error::UnsupportedLevel { level: 33, .. })
+        ));
+
+        config.level = Some(42);
+        (config.width, config.height) = (u32::MAX, u32::MAX);
+        assert!(matches!(
+            config.validate(),
+            Err(Error::LevelExceeded {
+                limit: "frame size in macroblocks",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn profile_constraints() {
+        let mut config = EncoderConfig::new(Codec::H264, 1280, 720);
+        config.profile = Some(Profile::H264ConstrainedBaseline);
+        config.entropy_mode = Some(EntropyMode::Cabac);
+        assert!(matches!(
+            config.validate(),
+            Err(Error::ProfileDisallows {
+                feature: "CABAC",
+                ..
+            })
+        ));
+
+        config.entropy_mode = None;
+        config.allow_frame_reordering = Some(true);
+        assert!(matches!(
+            config.validate(),
+            Err(Error::ProfileDisallows { .. })
+        ));
+
+        config.profile = Some(Profile::HevcMain);
+        assert!(matches!(
+            config.validate(),
+            Err(Error::ProfileMismatch { .. })
+        ));
+
+        config.profile = None;
+        config.level = Some(31);
+        assert!(matches!(config.validate(), Err(Error::Conflicting(..))));
+    }
+
+    #[test]
+    fn rate_control_and_hdr() {
+        let mut config = EncoderConfig::new(Codec::Hevc, 3840, 2160);
+        config.constant_bit_rate = Some(20_000_000);
+        config.average_bit_rate = Some(20_000_000);
+        assert!(matches!(config.validate(), Err(Error::Conflicting(..))));
+
+        config.constant_bit_rate = None;
+        config.quality = Some(1.5);
+        assert_eq!(config.validate(), Err(Error::InvalidValue("quality")));
+
+        config.quality = None;
+        config.expected_frame_rate = Some(f64::NAN);
+        assert_eq!(
+            config.validate(),
+            Err(Error::InvalidValue("expected frame rate"))
+        );
+
+        config.expected_frame_rate = None;
+        config.profile = Some(Profile::HevcMain);
+        config.color = Some(vui::ColorDescription::BT2100_PQ);
+        assert!(matches!(config.validate(), Err(Error::Hdr(_))));
+
+        config.profile = Some(Profile::HevcMain10);
+        config.content_light_level = Some(hdr::ContentLightLevel {
+            max_cll: 1000,
+            max_fall: 400,
+        });
+        assert_eq!(config.validate(), Ok(()));
+
+        config.codec = Codec::H264;
+        config.profile = None;
+        assert_eq!(config.validate(), Err(Error::Hdr("needs HEVC")));
+    }
+}
@@ -247,6 +247,17 @@ impl Session {
         self.complete_frames(cm::Time::invalid())
     }
 
+    /// Validates configuration and sets its properties
+    #[cfg(feature = "media")]
+    pub fn set_config(
+        &mut self,
+        config: &vt::compression::EncoderConfig,
+    ) -> Result<(), vt::compression::config::Error> {
+        let props = config.to_props()?;
+        self.set_props(&props)?;
+        Ok(())
+    }
+
     /// Indicates whether the current system supports stereo MV-HEVC encode.
     ///
     /// This call returning true does not guarantee that encode resources will be available at all times.